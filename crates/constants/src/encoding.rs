@@ -1,24 +1,78 @@
 use serde::{Deserialize, Serialize};
 
 /// Supported video encoder types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum VideoEncoderType {
     X264,
     NvEnc,
+    Qsv,
+    Amf,
+}
+impl VideoEncoderType {
+    /// PCI vendor ID of the GPUs that provide this encoder, or `None` for software encoders
+    pub fn gpu_vendor_id(&self) -> Option<u32> {
+        match self {
+            VideoEncoderType::X264 => None,
+            VideoEncoderType::NvEnc => Some(0x10DE),
+            VideoEncoderType::Qsv => Some(0x8086),
+            VideoEncoderType::Amf => Some(0x1002),
+        }
+    }
+
+    pub fn supports_codec(&self, codec: VideoCodec) -> bool {
+        match self {
+            VideoEncoderType::X264 => codec == VideoCodec::H264,
+            VideoEncoderType::NvEnc | VideoEncoderType::Qsv | VideoEncoderType::Amf => true,
+        }
+    }
 }
 impl std::fmt::Display for VideoEncoderType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             VideoEncoderType::X264 => write!(f, "OBS x264 (CPU)"),
             VideoEncoderType::NvEnc => write!(f, "NVIDIA NVENC (GPU)"),
+            VideoEncoderType::Qsv => write!(f, "Intel QuickSync (GPU)"),
+            VideoEncoderType::Amf => write!(f, "AMD AMF (GPU)"),
         }
     }
 }
 
 /// Video encoder constants
-/// List of supported video encoders that will be displayed for user to select
-pub const SUPPORTED_VIDEO_ENCODERS: &[VideoEncoderType] =
-    &[VideoEncoderType::X264, VideoEncoderType::NvEnc];
+/// List of supported video encoders that will be displayed for user to select,
+/// if the hardware they need is present
+pub const SUPPORTED_VIDEO_ENCODERS: &[VideoEncoderType] = &[
+    VideoEncoderType::X264,
+    VideoEncoderType::NvEnc,
+    VideoEncoderType::Qsv,
+    VideoEncoderType::Amf,
+];
+
+/// Output video codecs. Only hardware encoders can produce H.265.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum VideoCodec {
+    #[default]
+    H264,
+    H265,
+}
+impl VideoCodec {
+    pub const ALL: [VideoCodec; 2] = [VideoCodec::H264, VideoCodec::H265];
+
+    /// The encoder profile to use for this codec
+    pub fn profile(&self) -> &'static str {
+        match self {
+            VideoCodec::H264 => VIDEO_PROFILE,
+            VideoCodec::H265 => "main",
+        }
+    }
+}
+impl std::fmt::Display for VideoCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoCodec::H264 => write!(f, "H.264"),
+            VideoCodec::H265 => write!(f, "H.265 (HEVC)"),
+        }
+    }
+}
 
 /// Preset options for different encoder types
 /// https://github.com/obsproject/obs-studio/blob/5ec3af3f6d6465122dc2b0abff9661cbe64b406b/plugins/obs-x264/obs-x264.c
@@ -27,10 +81,11 @@ pub const X264_PRESETS: &[&str] = &["fast", "faster", "veryfast"];
 /// https://github.com/obsproject/obs-studio/blob/0b1229632063a13dfd26cf1cd9dd43431d8c68f6/plugins/obs-nvenc/nvenc-properties.c#L145
 pub const NVENC_PRESETS: &[&str] = &["p5", "p4", "p3", "p2", "p1"];
 
-// Placeholders for now as we only expose obsx264 and ffmpeg nvenc
+/// https://github.com/obsproject/obs-studio/blob/0b1229632063a13dfd26cf1cd9dd43431d8c68f6/plugins/obs-qsv11/obs-qsv11.c
 pub const QSV_PRESETS: &[&str] = &[
     "quality", "balanced", "speed", "veryfast", "faster", "fast", "medium",
 ];
+/// https://github.com/obsproject/obs-studio/blob/0b1229632063a13dfd26cf1cd9dd43431d8c68f6/plugins/obs-ffmpeg/texture-amf.cpp
 pub const AMF_PRESETS: &[&str] = &["quality", "balanced", "speed"];
 
 /// ffmpeg-nvenc: https://github.com/obsproject/obs-studio/blob/0b1229632063a13dfd26cf1cd9dd43431d8c68f6/plugins/obs-ffmpeg/obs-ffmpeg-nvenc.c#L504
@@ -38,11 +93,25 @@ pub const AMF_PRESETS: &[&str] = &["quality", "balanced", "speed"];
 /// both are the same
 pub const NVENC_TUNE_OPTIONS: &[&str] = &["hq", "ll", "ull"];
 
-/// We lock to the high profile for now. Other profiles are not of much use to us.
+/// We lock to the high profile for H.264 for now. Other profiles are not of much use to us.
 pub const VIDEO_PROFILE: &str = "high";
 
-/// Bitrate (kbps)
+/// Default bitrate (kbps)
 pub const BITRATE: i64 = 2500;
+/// Lowest bitrate (kbps) the user can pick
+pub const MIN_BITRATE: i64 = 1000;
+/// Highest bitrate (kbps) the user can pick
+pub const MAX_BITRATE: i64 = 10000;
+
+/// Default time between keyframes, in seconds. Every segment also starts on one, as each has its own encoder.
+pub const KEYFRAME_INTERVAL_SECONDS: u32 = 2;
+/// Shortest time between keyframes, in seconds, that the user can pick
+pub const MIN_KEYFRAME_INTERVAL_SECONDS: u32 = 1;
+/// Longest time between keyframes, in seconds, that the user can pick
+pub const MAX_KEYFRAME_INTERVAL_SECONDS: u32 = 10;
+
+/// Audio bitrate (kbps)
+pub const AUDIO_BITRATE: i64 = 160;
 
 /// Rate control
 pub const RATE_CONTROL: &str = "cbr";
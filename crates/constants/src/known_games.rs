@@ -0,0 +1,38 @@
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+
+/// A game we know the name of, so that recordings of it don't have to be labelled by hand
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KnownGame {
+    pub name: String,
+    pub binaries: Vec<String>,
+}
+
+static KNOWN_GAMES: LazyLock<Vec<KnownGame>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("known_games.json"))
+        .expect("Failed to load known games from embedded data")
+});
+
+/// The name of the game that `game_exe` belongs to, if it's one we know of. The extension and case don't matter.
+pub fn name_for_exe(game_exe: &str) -> Option<&'static str> {
+    let game_exe = game_exe.to_lowercase();
+    let binary = game_exe.strip_suffix(".exe").unwrap_or(&game_exe);
+    KNOWN_GAMES
+        .iter()
+        .find(|game| game.binaries.iter().any(|b| b == binary))
+        .map(|game| game.name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_for_exe() {
+        assert_eq!(name_for_exe("eldenring.exe"), Some("Elden Ring"));
+        assert_eq!(name_for_exe("BG3_DX11.EXE"), Some("Baldur's Gate 3"));
+        assert_eq!(name_for_exe("Stardew Valley.exe"), Some("Stardew Valley"));
+        assert_eq!(name_for_exe("notepad.exe"), None);
+    }
+}
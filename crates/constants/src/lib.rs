@@ -1,25 +1,72 @@
 use std::time::Duration;
 
 pub mod encoding;
+pub mod known_games;
 pub mod unsupported_games;
 
 // Keep in sync with vg_control/constants.py (for now!)
 pub const FPS: u32 = 60;
+/// Frame rate recordings are captured at unless the user picks another
+pub const DEFAULT_FPS: u32 = 30;
+/// Frame rates that the user can choose to record at
+pub const FPS_OPTIONS: [u32; 3] = [15, 30, 60];
+/// Default rate (in Hz) that mouse movement is sampled at, independent of the video's frame rate
+pub const DEFAULT_INPUT_RATE_HZ: u32 = 1000;
+/// Rates (in Hz) that the user can choose to sample mouse movement at, instead of recording every movement
+pub const INPUT_RATE_OPTIONS_HZ: [u32; 4] = [120, 250, 500, 1000];
 pub const RECORDING_WIDTH: u32 = 640;
 pub const RECORDING_HEIGHT: u32 = 360;
 
-/// Minimum free space required to record (in megabytes)
-pub const MIN_FREE_SPACE_MB: u64 = 512;
+/// Where recordings are stored unless the user picks somewhere else, relative to the working directory
+pub const DEFAULT_RECORDING_LOCATION: &str = "./data_dump/games";
+
+/// Default for how much free space must be left on the disk while recording (in megabytes).
+/// Recording stops once the free space drops below this.
+pub const DEFAULT_MIN_FREE_SPACE_MB: u64 = 2048;
+/// A recording will only be started if there's room for at least this much footage on top of the minimum free space
+pub const MIN_FREE_RECORDING_TIME: Duration = Duration::from_secs(5 * 60);
 
 /// Minimum footage length
 pub const MIN_FOOTAGE: Duration = Duration::from_secs(30);
 /// Maximum footage length
 pub const MAX_FOOTAGE: Duration = Duration::from_secs(10 * 60);
+/// Lengths (in minutes) that the user can choose for recordings to be split into. Longer sessions are
+/// recorded as a series of segments, none of which can be longer than [`MAX_FOOTAGE`] (the default).
+pub const SEGMENT_LENGTH_OPTIONS_MINUTES: [u32; 3] = [2, 5, 10];
+/// Lengths (in minutes) that the user can choose to keep around for instant replay
+pub const INSTANT_REPLAY_OPTIONS_MINUTES: [u32; 3] = [2, 5, 10];
+/// Countdowns (in seconds) that the user can choose to have before recording starts
+pub const START_COUNTDOWN_OPTIONS_SECONDS: [u32; 3] = [3, 5, 10];
+/// How many days the user can choose to keep recordings around for after they've been uploaded
+pub const UPLOADED_RETENTION_OPTIONS_DAYS: [u32; 3] = [1, 7, 30];
+/// Recordings that drop more than this percentage of their frames are warned about while recording
+pub const SKIPPED_FRAMES_WARNING_PERCENTAGE: f64 = 1.0;
+/// Recordings that drop more than this percentage of their frames are unusable, and are invalidated
+pub const MAX_SKIPPED_FRAMES_PERCENTAGE: f64 = 5.0;
 /// Maximum idle duration before stopping recording
 pub const MAX_IDLE_DURATION: Duration = Duration::from_secs(30);
+/// How long before an idle session is ended that the user is warned about it
+pub const IDLE_AUTO_STOP_WARNING: Duration = Duration::from_secs(30);
 /// Maximum duration the user can be alt tabbed out of the game before stopping recording
 pub const ALT_TAB_GRACE_PERIOD: Duration = Duration::from_secs(20);
 
+/// Chunk sizes (in megabytes) that the user can choose for uploads, instead of letting the server decide
+pub const UPLOAD_CHUNK_SIZE_OPTIONS_MB: [u64; 5] = [5, 10, 25, 50, 100];
+/// Chunk size used when optimizing for unreliable connections
+pub const UNRELIABLE_CONNECTION_CHUNK_SIZE_MB: u64 = 5;
+/// How many times a failed chunk is retried by default before the upload is given up on
+pub const DEFAULT_UPLOAD_RETRIES: u32 = 5;
+/// How many recordings are uploaded at the same time by default
+pub const DEFAULT_MAX_CONCURRENT_UPLOADS: u32 = 2;
+/// The most recordings that the user can have uploading at the same time
+pub const MAX_CONCURRENT_UPLOADS: u32 = 4;
+
+/// How often anonymous statistics are sent, if the user has opted in to sharing them
+pub const TELEMETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Version of the consent form in `consent.md`. Bump it whenever the form changes, so that everyone is asked to accept it again.
+pub const CONSENT_VERSION: u32 = 1;
+
 /// GitHub organization
 pub const GH_ORG: &str = "Wayfarer-Labs";
 /// GitHub repository
@@ -35,12 +82,29 @@ pub mod filename {
         pub const VIDEO: &str = "recording.mp4";
         /// The input recording file
         pub const INPUTS: &str = "inputs.csv";
+        /// The input recording in the compact binary format, made from [`INPUTS`] when uploading if enabled
+        pub const INPUTS_COMPACT: &str = "inputs.bin";
         /// The metadata file
         pub const METADATA: &str = "metadata.json";
+        /// Progress of an interrupted upload, so that it can be resumed
+        pub const UPLOAD_PROGRESS: &str = ".upload_progress";
+        /// Present while the recording is being made. If it's still there afterwards, OWL Control
+        /// exited without finishing the recording.
+        pub const IN_PROGRESS: &str = ".in_progress";
+        /// Folder within the recording location that instant replay is recorded to until it's saved
+        pub const REPLAY_BUFFER: &str = ".replay";
+        /// Folder within the recording location that an imported recording is unpacked to before it's added
+        pub const IMPORTING: &str = ".importing";
+        /// Marks a test recording, made to try out settings. These are kept locally and never uploaded.
+        pub const TEST: &str = ".test";
     }
 
     pub mod persistent {
         /// The config file, stored in persistent data directory
         pub const CONFIG: &str = "config.json";
+        /// Encrypted API key, used only if the OS credential store is unavailable
+        pub const API_KEY: &str = "api_key.bin";
+        /// Present while OWL Control is running. If it's there at startup, the last run didn't exit cleanly.
+        pub const RUNNING: &str = ".running";
     }
 }
@@ -7,7 +7,8 @@ use color_eyre::{Result, eyre::Context as _};
 
 use windows::{
     Win32::{
-        Foundation::{HWND, STILL_ACTIVE},
+        Foundation::{HANDLE, HWND, LPARAM, STILL_ACTIVE},
+        Security::{GetTokenInformation, TOKEN_ELEVATION, TOKEN_QUERY, TokenElevation},
         System::{
             Diagnostics::ToolHelp::{
                 CreateToolhelp32Snapshot, MODULEENTRY32, Module32First, Module32Next,
@@ -15,14 +16,18 @@ use windows::{
                 TH32CS_SNAPMODULE32, TH32CS_SNAPPROCESS,
             },
             Threading::{
-                GetExitCodeProcess, OpenProcess, PROCESS_NAME_NATIVE, PROCESS_QUERY_INFORMATION,
-                PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameA,
+                GetCurrentProcess, GetExitCodeProcess, OpenProcess, OpenProcessToken,
+                PROCESS_NAME_NATIVE, PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION,
+                QueryFullProcessImageNameA,
             },
             WindowsProgramming::HW_PROFILE_INFOA,
         },
-        UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId},
+        UI::WindowsAndMessaging::{
+            EnumWindows, GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+            IsWindowVisible,
+        },
     },
-    core::{Error, Owned, PSTR},
+    core::{BOOL, Error, Owned, PSTR},
 };
 
 pub use windows;
@@ -58,6 +63,42 @@ pub fn exe_name_for_pid(Pid(pid): Pid) -> Result<PathBuf> {
     }
 }
 
+/// Whether the process is running as administrator. Windows doesn't let a process that isn't hook into
+/// (and so capture) one that is.
+///
+/// Fails with access denied if the process is more privileged than this one, which for a game
+/// usually means the same thing.
+pub fn is_elevated(Pid(pid): Pid) -> Result<bool, Error> {
+    unsafe {
+        let process = Owned::new(OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)?);
+        token_is_elevated(*process)
+    }
+}
+
+/// Whether this process is running as administrator
+pub fn is_current_process_elevated() -> Result<bool, Error> {
+    token_is_elevated(unsafe { GetCurrentProcess() })
+}
+
+fn token_is_elevated(process: HANDLE) -> Result<bool, Error> {
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(process, TOKEN_QUERY, &mut token)?;
+        let token = Owned::new(token);
+
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut size = 0;
+        GetTokenInformation(
+            *token,
+            TokenElevation,
+            Some(&mut elevation as *mut TOKEN_ELEVATION as *mut _),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut size,
+        )?;
+        Ok(elevation.TokenIsElevated != 0)
+    }
+}
+
 pub fn foreground_window() -> Result<(HWND, Pid), Error> {
     unsafe {
         let hwnd = GetForegroundWindow();
@@ -69,6 +110,34 @@ pub fn foreground_window() -> Result<(HWND, Pid), Error> {
     }
 }
 
+/// Returns the visible top-level windows that have a title, frontmost first, along with the process each
+/// belongs to. Minimized windows are included, as they can be restored.
+pub fn titled_windows() -> Vec<(HWND, Pid)> {
+    unsafe extern "system" fn callback(hwnd: HWND, data: LPARAM) -> BOOL {
+        // SAFETY: `data` is the vec below, which outlives the enumeration
+        let windows = unsafe { &mut *(data.0 as *mut Vec<(HWND, Pid)>) };
+        let mut pid = 0;
+        if unsafe { IsWindowVisible(hwnd) }.as_bool()
+            && window_title(hwnd).is_some()
+            && unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) } != 0
+        {
+            windows.push((hwnd, Pid(pid)));
+        }
+        true.into()
+    }
+
+    let mut windows: Vec<(HWND, Pid)> = vec![];
+    let _ = unsafe { EnumWindows(Some(callback), LPARAM(&mut windows as *mut _ as isize)) };
+    windows
+}
+
+/// Returns the title of the window, or `None` if it doesn't have one
+pub fn window_title(hwnd: HWND) -> Option<String> {
+    let mut buffer = [0u16; 512];
+    let len = unsafe { GetWindowTextW(hwnd, &mut buffer) };
+    (len > 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
 pub fn for_each_process(mut f: impl FnMut(PROCESSENTRY32) -> bool) -> Result<(), Error> {
     unsafe {
         let snapshot = Owned::new(CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)?);
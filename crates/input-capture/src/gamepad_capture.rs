@@ -1,6 +1,6 @@
 use std::{
-    collections::HashSet,
-    sync::{Arc, RwLock},
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, RwLock},
 };
 
 use tokio::sync::mpsc;
@@ -43,8 +43,21 @@ pub const AXIS_RIGHTZ: u16 = 6;
 pub const AXIS_DPADX: u16 = 7;
 pub const AXIS_DPADY: u16 = 8;
 
+/// Hands out gamepad indices that are unique across both gilrs backends, whose own IDs overlap.
+/// A gamepad keeps its index if it's disconnected and reconnected, as gilrs reuses the ID.
+#[derive(Clone, Default)]
+struct GamepadIndices(Arc<Mutex<HashMap<(&'static str, usize), u16>>>);
+impl GamepadIndices {
+    fn get(&self, backend: &'static str, id: usize) -> u16 {
+        let mut indices = self.0.lock().unwrap();
+        let next_index = indices.len() as u16;
+        *indices.entry((backend, id)).or_insert(next_index)
+    }
+}
+
 pub fn initialize_thread(input_tx: mpsc::Sender<Event>) -> std::thread::JoinHandle<()> {
     let already_captured_by_xinput = Arc::new(RwLock::new(HashSet::new()));
+    let gamepad_indices = GamepadIndices::default();
 
     // We use both the `xinput` and `wgi` versions of gilrs so that we can capture Xbox controllers
     // (which only work with `xinput`) and PS controllers (which only work with `wgi`).
@@ -59,6 +72,7 @@ pub fn initialize_thread(input_tx: mpsc::Sender<Event>) -> std::thread::JoinHand
     std::thread::spawn({
         let already_captured_by_xinput = already_captured_by_xinput.clone();
         let input_tx = input_tx.clone();
+        let gamepad_indices = gamepad_indices.clone();
         move || {
             let mut gilrs = gilrs_xinput::Gilrs::new().unwrap();
 
@@ -68,7 +82,8 @@ pub fn initialize_thread(input_tx: mpsc::Sender<Event>) -> std::thread::JoinHand
                 let name = gilrs.gamepad(id).name().to_string();
                 already_captured_by_xinput.write().unwrap().insert(name);
 
-                let Some(event) = map_event_xinput(event) else {
+                let gamepad = gamepad_indices.get("xinput", id.into());
+                let Some(event) = map_event_xinput(gamepad, event) else {
                     continue;
                 };
                 if input_tx.blocking_send(event).is_err() {
@@ -92,7 +107,8 @@ pub fn initialize_thread(input_tx: mpsc::Sender<Event>) -> std::thread::JoinHand
                     continue;
                 }
 
-                let Some(event) = map_event_wgi(event) else {
+                let gamepad = gamepad_indices.get("wgi", id.into());
+                let Some(event) = map_event_wgi(gamepad, event) else {
                     continue;
                 };
                 if input_tx.blocking_send(event).is_err() {
@@ -106,28 +122,32 @@ pub fn initialize_thread(input_tx: mpsc::Sender<Event>) -> std::thread::JoinHand
 
 macro_rules! generate_map_functions {
     ($gilrs:ident, $map_event:ident, $map_button:ident, $map_axis:ident) => {
-        fn $map_event(event: $gilrs::EventType) -> Option<Event> {
+        fn $map_event(gamepad: u16, event: $gilrs::EventType) -> Option<Event> {
             use $gilrs::EventType;
             match event {
                 EventType::ButtonPressed(button, _) => Some(Event::GamepadButtonPress {
+                    gamepad,
                     key: $map_button(button),
                     press_state: PressState::Pressed,
                 }),
                 EventType::ButtonReleased(button, _) => Some(Event::GamepadButtonPress {
+                    gamepad,
                     key: $map_button(button),
                     press_state: PressState::Released,
                 }),
                 EventType::ButtonChanged(button, value, _) => Some(Event::GamepadButtonChange {
+                    gamepad,
                     key: $map_button(button),
                     value,
                 }),
                 EventType::AxisChanged(axis, value, _) => Some(Event::GamepadAxisChange {
+                    gamepad,
                     axis: $map_axis(axis),
                     value,
                 }),
+                EventType::Connected => Some(Event::GamepadConnected { gamepad }),
+                EventType::Disconnected => Some(Event::GamepadDisconnected { gamepad }),
                 EventType::ButtonRepeated(..)
-                | EventType::Connected
-                | EventType::Disconnected
                 | EventType::Dropped
                 | EventType::ForceFeedbackEffectCompleted
                 | _ => None,
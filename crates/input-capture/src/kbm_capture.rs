@@ -5,7 +5,7 @@ use color_eyre::{
 
 use windows::{
     Win32::{
-        Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+        Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, POINT, WPARAM},
         System::LibraryLoader::GetModuleHandleA,
         UI::{
             Input::{
@@ -16,8 +16,8 @@ use windows::{
             },
             WindowsAndMessaging::{
                 self, CreateWindowExA, DefWindowProcA, DestroyWindow, DispatchMessageA,
-                GetMessageA, GetSystemMetrics, HWND_MESSAGE, MSG, PostQuitMessage, RI_KEY_BREAK,
-                RI_MOUSE_BUTTON_4_DOWN, RI_MOUSE_BUTTON_4_UP, RI_MOUSE_BUTTON_5_DOWN,
+                GetCursorPos, GetMessageA, GetSystemMetrics, HWND_MESSAGE, MSG, PostQuitMessage,
+                RI_KEY_BREAK, RI_MOUSE_BUTTON_4_DOWN, RI_MOUSE_BUTTON_4_UP, RI_MOUSE_BUTTON_5_DOWN,
                 RI_MOUSE_BUTTON_5_UP, RI_MOUSE_LEFT_BUTTON_DOWN, RI_MOUSE_LEFT_BUTTON_UP,
                 RI_MOUSE_MIDDLE_BUTTON_DOWN, RI_MOUSE_MIDDLE_BUTTON_UP, RI_MOUSE_RIGHT_BUTTON_DOWN,
                 RI_MOUSE_RIGHT_BUTTON_UP, RI_MOUSE_WHEEL, RegisterClassA, SM_CXSCREEN,
@@ -114,11 +114,12 @@ impl KbmCapture {
         unsafe {
             let mut msg = MSG::default();
             let mut last_absolute: Option<(i32, i32)> = None;
+            let mut last_cursor: Option<(i32, i32)> = None;
             while GetMessageA(&mut msg, None, 0, 0).as_bool() {
                 let _ = TranslateMessage(&msg);
                 DispatchMessageA(&msg);
                 if msg.message == WindowsAndMessaging::WM_INPUT {
-                    for event in parse_wm_input(msg.lParam, &mut last_absolute) {
+                    for event in parse_wm_input(msg.lParam, &mut last_absolute, &mut last_cursor) {
                         event_callback(event);
                     }
                 }
@@ -153,7 +154,11 @@ impl KbmCapture {
     }
 }
 
-fn parse_wm_input(lparam: LPARAM, last_absolute: &mut Option<(i32, i32)>) -> Vec<Event> {
+fn parse_wm_input(
+    lparam: LPARAM,
+    last_absolute: &mut Option<(i32, i32)>,
+    last_cursor: &mut Option<(i32, i32)>,
+) -> Vec<Event> {
     unsafe {
         let hrawinput = HRAWINPUT(std::ptr::with_exposed_provenance_mut(lparam.0 as usize));
         let mut rawinput = RAWINPUT::default();
@@ -205,6 +210,15 @@ fn parse_wm_input(lparam: LPARAM, last_absolute: &mut Option<(i32, i32)>) -> Vec
                     if delta_x != 0 || delta_y != 0 {
                         events.push(Event::MouseMove([delta_x, delta_y]));
                     }
+
+                    // Only sent when it actually changes, as it doesn't while the game has the mouse captured
+                    let mut cursor = POINT::default();
+                    if GetCursorPos(&mut cursor).is_ok()
+                        && *last_cursor != Some((cursor.x, cursor.y))
+                    {
+                        *last_cursor = Some((cursor.x, cursor.y));
+                        events.push(Event::CursorMove([cursor.x, cursor.y]));
+                    }
                 }
 
                 let us_button_flags = u32::from(mouse.Anonymous.Anonymous.usButtonFlags);
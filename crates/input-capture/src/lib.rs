@@ -10,6 +10,9 @@ mod gamepad_capture;
 pub enum Event {
     /// Relative mouse movement (x, y)
     MouseMove([i32; 2]),
+    /// The cursor moved to this position on the virtual screen (x, y). Sent after the mouse movement
+    /// that caused it; games that capture the mouse often keep the cursor still while it moves.
+    CursorMove([i32; 2]),
     /// Mouse button press or release
     MousePress { key: u16, press_state: PressState },
     /// Mouse scroll wheel movement
@@ -18,11 +21,20 @@ pub enum Event {
     /// Keyboard key press or release
     KeyPress { key: u16, press_state: PressState },
     /// Gamepad button press or release
-    GamepadButtonPress { key: u16, press_state: PressState },
+    GamepadButtonPress {
+        gamepad: u16,
+        key: u16,
+        press_state: PressState,
+    },
     /// Gamepad button value change (e.g. analogue buttons like triggers)
-    GamepadButtonChange { key: u16, value: f32 },
+    GamepadButtonChange { gamepad: u16, key: u16, value: f32 },
     /// Gamepad axis value change
-    GamepadAxisChange { axis: u16, value: f32 },
+    GamepadAxisChange { gamepad: u16, axis: u16, value: f32 },
+    /// A gamepad was connected. The index is stable while the app is running,
+    /// even if the gamepad is disconnected and reconnected.
+    GamepadConnected { gamepad: u16 },
+    /// A gamepad was disconnected
+    GamepadDisconnected { gamepad: u16 },
 }
 impl Event {
     pub fn key_press_keycode(&self) -> Option<u16> {
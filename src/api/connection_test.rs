@@ -0,0 +1,120 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::api::{ApiClient, ApiKeyValidationError, InitMultipartUploadArgs};
+
+/// Tags the throwaway upload, so that it can be told apart if aborting it fails
+const CONNECTION_TEST_TAG: &str = "connection_test";
+
+/// The outcome of [`ApiClient::test_connection`], with how long each step took
+#[derive(Debug, Clone)]
+pub struct ConnectionTest {
+    pub api_key: Result<Duration, ApiKeyValidationError>,
+    /// `None` if the key was rejected, as the upload would be too
+    pub upload: Option<Result<Duration, String>>,
+}
+
+impl ApiClient {
+    /// Checks the API key, then starts and immediately abandons a one byte upload to make sure that
+    /// uploads would get through too. Nothing is actually uploaded.
+    pub async fn test_connection(&self, api_key: &str) -> ConnectionTest {
+        let start = Instant::now();
+        let api_key_result = self
+            .validate_api_key(api_key)
+            .await
+            .map(|_| start.elapsed());
+        if api_key_result.is_err() {
+            return ConnectionTest {
+                api_key: api_key_result,
+                upload: None,
+            };
+        }
+
+        let start = Instant::now();
+        let upload = async {
+            let response = self
+                .init_multipart_upload(
+                    api_key,
+                    Path::new("connection-test.tar"),
+                    1,
+                    InitMultipartUploadArgs {
+                        tags: Some(&[CONNECTION_TEST_TAG.to_string()]),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            self.abort_multipart_upload(api_key, &response.upload_id)
+                .await?;
+            color_eyre::eyre::Ok(start.elapsed())
+        }
+        .await
+        .map_err(|e| format!("{e:#}"));
+
+        ConnectionTest {
+            api_key: api_key_result,
+            upload: Some(upload),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::fake_server::{FakeResponse, FakeServer};
+
+    const API_KEY: &str = "sk_0123456789abcdef";
+
+    #[tokio::test]
+    async fn test_upload_is_abandoned() {
+        let server = FakeServer::start(vec![
+            FakeResponse::json(200, serde_json::json!({ "userId": "user-1" })),
+            FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "upload_id": "upload-1",
+                    "game_control_id": "gc-1",
+                    "total_chunks": 1,
+                    "chunk_size_bytes": 1,
+                    "expires_at": 0,
+                }),
+            ),
+            FakeResponse::json(
+                200,
+                serde_json::json!({ "success": true, "message": "aborted" }),
+            ),
+        ])
+        .await;
+        let client = ApiClient::new(server.base_url.clone(), None);
+
+        let result = client.test_connection(API_KEY).await;
+        assert!(result.api_key.is_ok());
+        assert!(matches!(result.upload, Some(Ok(_))));
+
+        let requests = server.requests();
+        assert_eq!(requests[1].json()["tags"][0], CONNECTION_TEST_TAG);
+        assert_eq!(
+            requests[2].path,
+            "/tracker/upload/game_control/multipart/abort/upload-1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_key_skips_upload() {
+        let server = FakeServer::start(vec![FakeResponse::json(
+            401,
+            serde_json::json!({ "detail": "Invalid API key" }),
+        )])
+        .await;
+        let client = ApiClient::new(server.base_url.clone(), None);
+
+        let result = client.test_connection(API_KEY).await;
+        assert!(matches!(
+            result.api_key,
+            Err(ApiKeyValidationError::InvalidKey(_))
+        ));
+        assert!(result.upload.is_none());
+        assert_eq!(server.requests().len(), 1);
+    }
+}
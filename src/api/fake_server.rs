@@ -0,0 +1,161 @@
+//! A stand-in for the API server in tests. It answers each request with the next scripted response and
+//! keeps what was sent, so that the client can be tested without a network or a real backend.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::{TcpListener, TcpStream},
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct FakeRequest {
+    pub method: String,
+    pub path: String,
+    /// Names are lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+impl FakeRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("request body isn't JSON")
+    }
+}
+
+pub(crate) struct FakeResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+impl FakeResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: String::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {} Fake\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            self.body.len()
+        );
+        for (name, value) in &self.headers {
+            response += &format!("{name}: {value}\r\n");
+        }
+        response += "\r\n";
+        response += &self.body;
+        response.into_bytes()
+    }
+}
+
+pub(crate) struct FakeServer {
+    /// Pass this to [`super::ApiClient::new`]
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<FakeRequest>>>,
+}
+impl FakeServer {
+    /// Once `responses` run out, everything gets a 500
+    pub async fn start(responses: Vec<FakeResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        tokio::spawn({
+            let requests = requests.clone();
+            let mut responses = VecDeque::from(responses);
+            async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let Some(request) = read_request(&mut stream).await else {
+                        continue;
+                    };
+                    requests.lock().unwrap().push(request);
+                    let response = responses
+                        .pop_front()
+                        .unwrap_or_else(|| FakeResponse::status(500));
+                    stream.write_all(&response.to_bytes()).await.ok();
+                    stream.shutdown().await.ok();
+                }
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    /// Everything received so far, oldest first
+    pub fn requests(&self) -> Vec<FakeRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<FakeRequest> {
+    let mut buffer = vec![];
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = std::str::from_utf8(&buffer[..header_end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = buffer[header_end..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    Some(FakeRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
@@ -3,7 +3,7 @@ use std::path::Path;
 use color_eyre::eyre::{self, Context as _, ContextCompat as _};
 use serde::{Deserialize, Serialize};
 
-use crate::api::{API_BASE_URL, ApiClient, check_for_response_success};
+use crate::api::{ApiClient, check_for_response_success, request_error};
 
 #[derive(Default, Debug, Clone)]
 #[allow(unused)]
@@ -17,6 +17,8 @@ pub struct InitMultipartUploadArgs<'a> {
     pub video_codec: Option<&'a str>,
     pub video_fps: Option<f32>,
     pub chunk_size_bytes: Option<u64>,
+    /// SHA-256 of the whole archive, as lowercase hex
+    pub archive_sha256: Option<&'a str>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -39,7 +41,7 @@ pub struct UploadMultipartChunkResponse {
     pub expires_at: u64,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct CompleteMultipartUploadChunk {
     pub chunk_number: u64,
     pub etag: String,
@@ -54,6 +56,9 @@ pub struct CompleteMultipartUploadResponse {
     pub message: String,
     #[serde(default)]
     pub verified: Option<bool>,
+    /// SHA-256 of the archive as the server assembled it, if it checked
+    #[serde(default)]
+    pub sha256: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -78,6 +83,8 @@ impl ApiClient {
             total_size_bytes: u64,
             #[serde(skip_serializing_if = "Option::is_none")]
             chunk_size_bytes: Option<u64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            sha256: Option<&'a str>,
 
             #[serde(skip_serializing_if = "Option::is_none")]
             tags: Option<&'a [String]>,
@@ -103,9 +110,10 @@ impl ApiClient {
         }
 
         let response = self
-            .client
+            .http_client()
             .post(format!(
-                "{API_BASE_URL}/tracker/upload/game_control/multipart/init"
+                "{}/tracker/upload/game_control/multipart/init",
+                self.base_url
             ))
             .header("Content-Type", "application/json")
             .header("X-API-Key", api_key)
@@ -118,6 +126,7 @@ impl ApiClient {
                 content_type: "application/x-tar",
                 total_size_bytes,
                 chunk_size_bytes: args.chunk_size_bytes,
+                sha256: args.archive_sha256,
 
                 tags: args.tags,
 
@@ -136,6 +145,7 @@ impl ApiClient {
             })
             .send()
             .await
+            .map_err(request_error)
             .context("failed to send init multipart upload request")?;
 
         Ok(
@@ -161,9 +171,10 @@ impl ApiClient {
         }
 
         let response = self
-            .client
+            .http_client()
             .post(format!(
-                "{API_BASE_URL}/tracker/upload/game_control/multipart/chunk"
+                "{}/tracker/upload/game_control/multipart/chunk",
+                self.base_url
             ))
             .header("Content-Type", "application/json")
             .header("X-API-Key", api_key)
@@ -174,6 +185,7 @@ impl ApiClient {
             })
             .send()
             .await
+            .map_err(request_error)
             .context("failed to send upload multipart chunk request")?;
         Ok(
             check_for_response_success(response, "Upload multipart chunk request failed")
@@ -196,9 +208,10 @@ impl ApiClient {
         }
 
         let response = self
-            .client
+            .http_client()
             .post(format!(
-                "{API_BASE_URL}/tracker/upload/game_control/multipart/complete"
+                "{}/tracker/upload/game_control/multipart/complete",
+                self.base_url
             ))
             .header("Content-Type", "application/json")
             .header("X-API-Key", api_key)
@@ -208,6 +221,7 @@ impl ApiClient {
             })
             .send()
             .await
+            .map_err(request_error)
             .context("failed to send complete multipart upload request")?;
 
         Ok(
@@ -224,13 +238,15 @@ impl ApiClient {
         upload_id: &str,
     ) -> eyre::Result<AbortMultipartUploadResponse> {
         let response = self
-            .client
+            .http_client()
             .delete(format!(
-                "{API_BASE_URL}/tracker/upload/game_control/multipart/abort/{upload_id}"
+                "{}/tracker/upload/game_control/multipart/abort/{upload_id}",
+                self.base_url
             ))
             .header("X-API-Key", api_key)
             .send()
             .await
+            .map_err(request_error)
             .context("failed to send abort multipart upload request")?;
 
         Ok(
@@ -241,3 +257,165 @@ impl ApiClient {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::api::{
+        ApiKeyRejected, RateLimited,
+        fake_server::{FakeResponse, FakeServer},
+    };
+
+    const API_KEY: &str = "sk_0123456789abcdef";
+
+    #[tokio::test]
+    async fn test_multipart_upload_flow() {
+        let server = FakeServer::start(vec![
+            FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "upload_id": "upload-1",
+                    "game_control_id": "gc-1",
+                    "total_chunks": 2,
+                    "chunk_size_bytes": 1024,
+                    "expires_at": 0,
+                }),
+            ),
+            FakeResponse::json(
+                200,
+                serde_json::json!({ "upload_url": "https://example.com/1", "chunk_number": 1, "expires_at": 0 }),
+            ),
+            FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "success": true,
+                    "game_control_id": "gc-1",
+                    "object_key": "key",
+                    "message": "done",
+                    "sha256": "0123abcd",
+                }),
+            ),
+        ])
+        .await;
+        let client = ApiClient::new(server.base_url.clone(), None);
+
+        let init = client
+            .init_multipart_upload(
+                API_KEY,
+                Path::new("recordings/session.tar"),
+                2000,
+                InitMultipartUploadArgs {
+                    video_filename: Some("recording.mp4"),
+                    control_filename: Some("inputs.csv"),
+                    video_duration_seconds: Some(12.5),
+                    video_fps: Some(60.0),
+                    chunk_size_bytes: Some(1024),
+                    archive_sha256: Some("0123abcd"),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(init.upload_id, "upload-1");
+        assert_eq!(init.total_chunks, 2);
+
+        let chunk = client
+            .upload_multipart_chunk(API_KEY, &init.upload_id, 1, "abc")
+            .await
+            .unwrap();
+        assert_eq!(chunk.upload_url, "https://example.com/1");
+
+        let complete = client
+            .complete_multipart_upload(
+                API_KEY,
+                &init.upload_id,
+                &[CompleteMultipartUploadChunk {
+                    chunk_number: 1,
+                    etag: "etag-1".into(),
+                }],
+            )
+            .await
+            .unwrap();
+        assert!(complete.success);
+        assert_eq!(complete.sha256.as_deref(), Some("0123abcd"));
+
+        let requests = server.requests();
+        assert_eq!(
+            requests.iter().map(|r| r.path.as_str()).collect::<Vec<_>>(),
+            [
+                "/tracker/upload/game_control/multipart/init",
+                "/tracker/upload/game_control/multipart/chunk",
+                "/tracker/upload/game_control/multipart/complete",
+            ]
+        );
+        assert!(
+            requests
+                .iter()
+                .all(|r| r.header("x-api-key") == Some(API_KEY))
+        );
+
+        let manifest = requests[0].json();
+        assert_eq!(manifest["filename"], "session.tar");
+        assert_eq!(manifest["total_size_bytes"], 2000);
+        assert_eq!(manifest["chunk_size_bytes"], 1024);
+        assert_eq!(manifest["video_filename"], "recording.mp4");
+        assert_eq!(manifest["control_filename"], "inputs.csv");
+        assert_eq!(manifest["video_duration_seconds"], 12.5);
+        assert_eq!(manifest["video_fps"], 60.0);
+        assert_eq!(manifest["sha256"], "0123abcd");
+        // Left out rather than sent as null
+        assert!(manifest.get("tags").is_none());
+
+        assert_eq!(
+            requests[1].json(),
+            serde_json::json!({ "upload_id": "upload-1", "chunk_number": 1, "chunk_hash": "abc" })
+        );
+        assert_eq!(requests[2].json()["chunk_etags"][0]["etag"], "etag-1");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_key_is_recognised() {
+        let server = FakeServer::start(vec![
+            FakeResponse::json(401, serde_json::json!({ "detail": "Invalid API key" })),
+            FakeResponse::status(500),
+        ])
+        .await;
+        let client = ApiClient::new(server.base_url.clone(), None);
+
+        // A rejected key holds the queue for a new login instead of being retried...
+        let e = client
+            .upload_multipart_chunk(API_KEY, "upload-1", 1, "abc")
+            .await
+            .unwrap_err();
+        assert!(ApiKeyRejected::is_cause_of(&e));
+
+        // ...while anything else is worth retrying
+        let e = client
+            .upload_multipart_chunk(API_KEY, "upload-1", 1, "abc")
+            .await
+            .unwrap_err();
+        assert!(!ApiKeyRejected::is_cause_of(&e));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_is_reported() {
+        let server = FakeServer::start(vec![
+            FakeResponse::json(429, serde_json::json!({ "detail": "Slow down" }))
+                .with_header("Retry-After", "12"),
+        ])
+        .await;
+        let client = ApiClient::new(server.base_url.clone(), None);
+
+        let e = client
+            .upload_multipart_chunk(API_KEY, "upload-1", 1, "abc")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            RateLimited::find_in(&e).and_then(|r| r.retry_after),
+            Some(std::time::Duration::from_secs(12))
+        );
+        assert!(!ApiKeyRejected::is_cause_of(&e));
+    }
+}
@@ -0,0 +1,107 @@
+use constants::encoding::{VideoCodec, VideoEncoderType};
+use serde::{Deserialize, Serialize};
+
+/// Capture settings that the server would like recordings to be made with, so that the dataset is
+/// consistent. Sent along with the account details; anything it doesn't mention is left to the user.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteCaptureSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps: Option<RemoteSetting<u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codec: Option<RemoteSetting<VideoCodec>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate_kbps: Option<RemoteSetting<i64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_rate_hz: Option<RemoteSetting<u32>>,
+}
+impl RemoteCaptureSettings {
+    /// Drops anything that this version of OWL Control can't record with, e.g. a frame rate it doesn't
+    /// offer, or a codec that `encoder` doesn't support
+    pub fn sanitized(mut self, encoder: VideoEncoderType) -> Self {
+        if let Some(fps) = &self.fps
+            && !constants::FPS_OPTIONS.contains(&fps.value)
+        {
+            tracing::warn!(
+                "Server recommended unsupported FPS {}, ignoring it",
+                fps.value
+            );
+            self.fps = None;
+        }
+        if let Some(codec) = &self.codec
+            && !encoder.supports_codec(codec.value)
+        {
+            tracing::warn!(
+                "Server recommended {} which {encoder} doesn't support, ignoring it",
+                codec.value
+            );
+            self.codec = None;
+        }
+        if let Some(bitrate) = &mut self.bitrate_kbps {
+            bitrate.value = bitrate.value.clamp(
+                constants::encoding::MIN_BITRATE,
+                constants::encoding::MAX_BITRATE,
+            );
+        }
+        if let Some(rate) = &self.input_rate_hz
+            && !constants::INPUT_RATE_OPTIONS_HZ.contains(&rate.value)
+        {
+            tracing::warn!(
+                "Server recommended unsupported input rate {} Hz, ignoring it",
+                rate.value
+            );
+            self.input_rate_hz = None;
+        }
+        self
+    }
+}
+
+/// A value the server recommends. Mandatory ones can't be changed by the user.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteSetting<T> {
+    pub value: T,
+    #[serde(default)]
+    pub mandatory: bool,
+}
+impl<T> RemoteSetting<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> RemoteSetting<U> {
+        RemoteSetting {
+            value: f(self.value),
+            mandatory: self.mandatory,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_capture_settings_sanitized() {
+        let settings: RemoteCaptureSettings = serde_json::from_str(
+            r#"{
+                "fps": { "value": 7 },
+                "codec": { "value": "H265", "mandatory": true },
+                "bitrateKbps": { "value": 999999999 },
+                "inputRateHz": { "value": 250, "mandatory": true }
+            }"#,
+        )
+        .unwrap();
+        let sanitized = settings.sanitized(VideoEncoderType::X264);
+        assert_eq!(sanitized.fps, None);
+        assert_eq!(sanitized.codec, None);
+        assert_eq!(
+            sanitized.bitrate_kbps.map(|b| b.value),
+            Some(constants::encoding::MAX_BITRATE)
+        );
+        assert_eq!(
+            sanitized.input_rate_hz,
+            constants::INPUT_RATE_OPTIONS_HZ
+                .contains(&250)
+                .then_some(RemoteSetting {
+                    value: 250,
+                    mandatory: true
+                })
+        );
+    }
+}
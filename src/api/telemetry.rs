@@ -0,0 +1,36 @@
+use color_eyre::eyre::{self, Context as _};
+use serde::Serialize;
+
+use crate::{
+    api::{ApiClient, check_for_response_success, request_error},
+    telemetry::TelemetryBatch,
+};
+
+impl ApiClient {
+    /// Sends a batch of anonymous statistics. Deliberately made without the API key, so that it can't be
+    /// tied back to the account.
+    pub async fn send_telemetry(&self, batch: &TelemetryBatch) -> eyre::Result<()> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct TelemetryRequest<'a> {
+            client_version: &'a str,
+            #[serde(flatten)]
+            batch: &'a TelemetryBatch,
+        }
+
+        let response = self
+            .http_client()
+            .post(format!("{}/telemetry/client", self.base_url))
+            .json(&TelemetryRequest {
+                client_version: env!("CARGO_PKG_VERSION"),
+                batch,
+            })
+            .send()
+            .await
+            .map_err(request_error)
+            .context("failed to send telemetry")?;
+
+        check_for_response_success(response, "Telemetry not accepted").await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use serde::Deserialize;
+
+use crate::{api::RemoteCaptureSettings, util::version::is_version_newer};
+
+/// What the server tells us about the account an API key belongs to. Only the ID is guaranteed;
+/// the rest depends on how the account was set up.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserInfo {
+    pub user_id: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub plan: Option<String>,
+    /// How much the account is allowed to upload in total, if there's a limit
+    #[serde(default)]
+    pub upload_quota_bytes: Option<u64>,
+    /// The oldest version of OWL Control the server still takes uploads from, if it's set one
+    #[serde(default)]
+    pub min_client_version: Option<String>,
+    /// Capture settings the server would like recordings to be made with, if it has any
+    #[serde(default)]
+    pub capture_settings: Option<RemoteCaptureSettings>,
+}
+impl UserInfo {
+    /// The most recognisable name for the account, falling back to its ID
+    pub fn display_name(&self) -> &str {
+        self.username
+            .as_deref()
+            .or(self.email.as_deref())
+            .unwrap_or(&self.user_id)
+    }
+
+    /// Whether this version of OWL Control is too old for the server to take uploads from.
+    /// Recording still works; the recordings just have to wait for an update to be uploaded.
+    pub fn is_client_outdated(&self) -> bool {
+        self.min_client_version
+            .as_deref()
+            .is_some_and(|min| is_version_newer(env!("CARGO_PKG_VERSION"), min))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_client_outdated() {
+        let mut user_info = UserInfo {
+            user_id: "user-1".into(),
+            username: None,
+            email: None,
+            plan: None,
+            upload_quota_bytes: None,
+            min_client_version: None,
+            capture_settings: None,
+        };
+        assert!(!user_info.is_client_outdated());
+
+        user_info.min_client_version = Some(env!("CARGO_PKG_VERSION").into());
+        assert!(!user_info.is_client_outdated());
+
+        user_info.min_client_version = Some("0.0.1".into());
+        assert!(!user_info.is_client_outdated());
+
+        user_info.min_client_version = Some("999.0.0".into());
+        assert!(user_info.is_client_outdated());
+    }
+}
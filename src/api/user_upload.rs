@@ -1,13 +1,18 @@
 use chrono::{DateTime, Utc};
 use color_eyre::eyre::{self, Context as _};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::api::{API_BASE_URL, ApiClient, check_for_response_success};
+use crate::{
+    api::{ApiClient, check_for_response_success, request_error},
+    output_types::SessionLabels,
+};
 
 #[derive(Debug, Clone)]
 pub struct UserUploads {
     pub statistics: UserUploadStatistics,
     pub uploads: Vec<UserUpload>,
+    /// When these were fetched from the server
+    pub fetched_at: DateTime<Utc>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -68,12 +73,13 @@ impl ApiClient {
         }
 
         let response = self
-            .client
-            .get(format!("{API_BASE_URL}/tracker/uploads/user/{user_id}"))
+            .http_client()
+            .get(format!("{}/tracker/uploads/user/{user_id}", self.base_url))
             .header("Content-Type", "application/json")
             .header("X-API-Key", api_key)
             .send()
             .await
+            .map_err(request_error)
             .context("failed to get user upload stats")?;
 
         let response =
@@ -87,6 +93,98 @@ impl ApiClient {
         Ok(UserUploads {
             statistics: server_stats.statistics,
             uploads: server_stats.uploads,
+            fetched_at: Utc::now(),
         })
     }
+
+    /// Replaces the labels of a recording that has already been uploaded, for when it was labelled wrongly
+    pub async fn update_upload_labels(
+        &self,
+        api_key: &str,
+        upload_id: &str,
+        labels: &SessionLabels,
+    ) -> eyre::Result<()> {
+        #[derive(Serialize, Debug)]
+        struct UpdateUploadLabelsRequest<'a> {
+            tags: Vec<String>,
+            notes: Option<&'a str>,
+        }
+
+        let response = self
+            .http_client()
+            .patch(format!("{}/tracker/uploads/{upload_id}", self.base_url))
+            .header("Content-Type", "application/json")
+            .header("X-API-Key", api_key)
+            .json(&UpdateUploadLabelsRequest {
+                tags: labels.upload_tags(),
+                notes: labels.notes.as_deref(),
+            })
+            .send()
+            .await
+            .map_err(request_error)
+            .context("failed to send upload labels update request")?;
+
+        check_for_response_success(response, "Updating the upload's labels failed").await?;
+        Ok(())
+    }
+}
+
+impl UserUpload {
+    /// The labels the recording was uploaded with
+    pub fn labels(&self) -> SessionLabels {
+        let tags = self
+            .tags
+            .as_ref()
+            .and_then(|tags| tags.as_array())
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|tag| tag.as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        SessionLabels::from_upload_tags(&tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::fake_server::{FakeResponse, FakeServer};
+
+    #[tokio::test]
+    async fn test_update_upload_labels() {
+        let server = FakeServer::start(vec![
+            FakeResponse::json(200, serde_json::json!({ "success": true })),
+            FakeResponse::json(404, serde_json::json!({ "detail": "Upload not found" })),
+        ])
+        .await;
+        let client = ApiClient::new(server.base_url.clone(), None);
+        let labels = SessionLabels {
+            game_title: Some("Minecraft".to_string()),
+            tags: vec!["survival".to_string()],
+            notes: Some("Mostly building".to_string()),
+            ..Default::default()
+        };
+
+        client
+            .update_upload_labels("sk_0123456789abcdef", "upload-1", &labels)
+            .await
+            .unwrap();
+        let error = client
+            .update_upload_labels("sk_0123456789abcdef", "upload-2", &labels)
+            .await
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("Upload not found"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "PATCH");
+        assert_eq!(requests[0].path, "/tracker/uploads/upload-1");
+        assert_eq!(
+            requests[0].json(),
+            serde_json::json!({
+                "tags": ["game:Minecraft", "survival"],
+                "notes": "Mostly building",
+            })
+        );
+    }
 }
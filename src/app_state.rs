@@ -1,28 +1,76 @@
 use std::{
+    path::PathBuf,
     sync::{OnceLock, RwLock, atomic::AtomicBool},
     time::{Duration, Instant},
 };
 
-use constants::unsupported_games::UnsupportedGames;
+use constants::{encoding::VideoEncoderType, unsupported_games::UnsupportedGames};
 use egui_wgpu::wgpu;
 use tokio::sync::mpsc;
 
 use crate::{
-    api::UserUploads,
+    api::{
+        self, ApiKeyValidationError, ConnectionTest, RemoteCaptureSettings, UserInfo, UserUploads,
+    },
     config::Config,
-    upload::{LocalRecording, ProgressData},
+    encryption::RecordingKeys,
+    output_types::{SessionLabels, SkippedFrames},
+    record::{BenchmarkReport, Playback, PreviewFrame, RecordingSummary},
+    system::crash_sentinel::RunInfo,
+    system::{
+        audio_devices::{self, AudioOutputDevice},
+        capture_permission::CaptureDenied,
+        hardware_specs,
+    },
+    telemetry::Telemetry,
+    upload::{LocalRecording, RetainedRecordings, UploadQueue, UploadThrottle},
 };
 
 pub struct AppState {
     /// holds the current state of recording, recorder <-> overlay
     pub state: RwLock<RecordingStatus>,
+    /// The latest frame of the current recording, if the preview is turned on
+    pub preview_frame: RwLock<Option<PreviewFrame>>,
     pub config: RwLock<Config>,
     pub user_uploads: RwLock<Option<UserUploads>>,
+    /// The API key and the user ID it belongs to, once the key has been validated
+    pub valid_api_key_and_user_id: RwLock<Option<(String, String)>>,
+    /// Details of the logged-in account, refreshed every so often
+    pub user_info: RwLock<Option<UserInfo>>,
+    /// Set while the API key couldn't be checked because our servers were unreachable. Recording carries on,
+    /// uploads are held back, and the key is checked again every so often.
+    pub offline: AtomicBool,
+    /// Whether the server answered when it was last checked, for the connection status in the main view
+    pub server_contact: RwLock<ServerContact>,
+    /// Recordings started while this is set are test recordings, which are never uploaded.
+    /// Only lasts until OWL Control is closed, so that it can't be left on by accident.
+    pub test_recording: AtomicBool,
     pub local_recordings: RwLock<Vec<LocalRecording>>,
+    /// Uploaded recordings that haven't been cleaned up yet, refreshed along with `local_recordings`
+    pub retained_recordings: RwLock<RetainedRecordings>,
+    pub upload_queue: RwLock<UploadQueue>,
+    /// Set when a game couldn't be captured. No recording is started until the user has fixed it and
+    /// asked for it to be checked again.
+    pub capture_denied: RwLock<Option<CaptureDenied>>,
+    /// Set if the previous run didn't exit cleanly, until the user has seen the notice about it
+    pub unclean_exit: RwLock<Option<UncleanExit>>,
+    pub upload_throttle: UploadThrottle,
+    /// Statistics gathered for the next telemetry batch, see [`crate::telemetry`]
+    pub telemetry: Telemetry,
     pub async_request_tx: mpsc::Sender<AsyncRequest>,
     pub ui_update_tx: UiUpdateSender,
     pub is_currently_rebinding: AtomicBool,
     pub adapter_infos: Vec<wgpu::AdapterInfo>,
+    /// Video encoders that work on this machine, detected at startup
+    pub available_video_encoders: Vec<VideoEncoderType>,
+    /// Output devices that desktop audio can be recorded from, detected at startup
+    pub audio_output_devices: Vec<AudioOutputDevice>,
+    /// Recording location passed on the command line, which takes precedence over the configured one
+    pub recording_location_override: Option<PathBuf>,
+    /// API base URL passed on the command line, which takes precedence over the configured one
+    pub api_base_url_override: Option<String>,
+    /// Whether the API may be reached over plain HTTP
+    pub allow_insecure_api: bool,
 }
 
 impl AppState {
@@ -30,16 +78,178 @@ impl AppState {
         async_request_tx: mpsc::Sender<AsyncRequest>,
         ui_update_tx: UiUpdateSender,
         adapter_infos: Vec<wgpu::AdapterInfo>,
+        recording_location_override: Option<PathBuf>,
+        api_base_url_override: Option<String>,
+        allow_insecure_api: bool,
     ) -> Self {
+        Self::with_config(
+            Config::load().expect("failed to init configs"),
+            async_request_tx,
+            ui_update_tx,
+            adapter_infos,
+            recording_location_override,
+            api_base_url_override,
+            allow_insecure_api,
+        )
+    }
+
+    /// State for tests, with `config` in place of the one on disk. The receivers have to be kept for as long as
+    /// anything is expected to get through to the tokio thread or the UI.
+    #[cfg(test)]
+    pub fn for_test(
+        config: Config,
+    ) -> (
+        std::sync::Arc<Self>,
+        mpsc::Receiver<AsyncRequest>,
+        mpsc::Receiver<UiUpdate>,
+    ) {
+        let (async_request_tx, async_request_rx) = mpsc::channel(16);
+        let (ui_update_tx, ui_update_rx) = UiUpdateSender::build(16);
+        let app_state = Self::with_config(
+            config,
+            async_request_tx,
+            ui_update_tx,
+            vec![],
+            None,
+            None,
+            true,
+        );
+        (
+            std::sync::Arc::new(app_state),
+            async_request_rx,
+            ui_update_rx,
+        )
+    }
+
+    fn with_config(
+        mut config: Config,
+        async_request_tx: mpsc::Sender<AsyncRequest>,
+        ui_update_tx: UiUpdateSender,
+        adapter_infos: Vec<wgpu::AdapterInfo>,
+        recording_location_override: Option<PathBuf>,
+        api_base_url_override: Option<String>,
+        allow_insecure_api: bool,
+    ) -> Self {
+        let available_video_encoders = hardware_specs::get_available_video_encoders(&adapter_infos);
+        tracing::info!("Available video encoders: {available_video_encoders:?}");
+
+        let audio_output_devices = match audio_devices::get_audio_output_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                tracing::warn!(e=?e, "Failed to enumerate audio output devices");
+                vec![]
+            }
+        };
+        tracing::info!("Audio output devices: {audio_output_devices:?}");
+
+        let encoder = &mut config.preferences.encoder;
+        if !available_video_encoders.contains(&encoder.encoder) {
+            // e.g. the config was copied from another machine, or the GPU was swapped out
+            tracing::warn!(
+                "{} is not available on this machine, falling back to {}",
+                encoder.encoder,
+                VideoEncoderType::X264
+            );
+            encoder.encoder = VideoEncoderType::X264;
+            encoder.codec = Default::default();
+        }
+
         Self {
             state: RwLock::new(RecordingStatus::Stopped),
-            config: RwLock::new(Config::load().expect("failed to init configs")),
+            preview_frame: RwLock::new(None),
+            config: RwLock::new(config),
             user_uploads: RwLock::new(None),
+            user_info: RwLock::new(None),
+            offline: AtomicBool::new(false),
+            server_contact: RwLock::new(ServerContact::default()),
+            test_recording: AtomicBool::new(false),
+            valid_api_key_and_user_id: RwLock::new(None),
             local_recordings: RwLock::new(Vec::new()),
+            retained_recordings: RwLock::new(RetainedRecordings::default()),
+            upload_queue: RwLock::new(UploadQueue::default()),
+            capture_denied: RwLock::new(None),
+            unclean_exit: RwLock::new(None),
+            upload_throttle: UploadThrottle::default(),
+            telemetry: Telemetry::default(),
             async_request_tx,
             ui_update_tx,
             is_currently_rebinding: AtomicBool::new(false),
             adapter_infos,
+            available_video_encoders,
+            audio_output_devices,
+            recording_location_override,
+            api_base_url_override,
+            allow_insecure_api,
+        }
+    }
+
+    /// Where new recordings go, and where existing ones are looked for
+    pub fn recording_location(&self) -> PathBuf {
+        if let Some(path) = &self.recording_location_override {
+            return path.clone();
+        }
+        self.config
+            .read()
+            .unwrap()
+            .preferences
+            .recording_location
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(constants::DEFAULT_RECORDING_LOCATION))
+    }
+
+    /// What recordings encrypted at rest are decrypted with, and new ones are encrypted with if they're to be
+    pub fn recording_keys(&self) -> color_eyre::Result<RecordingKeys> {
+        RecordingKeys::load(&self.config.read().unwrap())
+    }
+
+    /// The API server to talk to, falling back to production if the configured one isn't usable
+    pub fn api_base_url(&self) -> String {
+        let configured = self
+            .api_base_url_override
+            .clone()
+            .or_else(|| self.config.read().unwrap().preferences.api_base_url.clone());
+        let Some(url) = configured else {
+            return api::DEFAULT_API_BASE_URL.to_string();
+        };
+        match api::check_api_base_url(&url, self.allow_insecure_api) {
+            Ok(url) => {
+                if url != api::DEFAULT_API_BASE_URL {
+                    tracing::info!("Using API server {url}");
+                }
+                url
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Ignoring API server {url}: {e}; using {}",
+                    api::DEFAULT_API_BASE_URL
+                );
+                api::DEFAULT_API_BASE_URL.to_string()
+            }
+        }
+    }
+}
+
+/// What we know about a previous run that didn't exit cleanly
+#[derive(Debug, Clone)]
+pub struct UncleanExit {
+    pub previous_run: RunInfo,
+    /// Interrupted recordings that were finished off at startup
+    pub recovered_recordings: Vec<PathBuf>,
+}
+
+/// How the server has been answering. It's checked every so often, and whenever a request to it gets through.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServerContact {
+    /// When the server last answered
+    pub last_answered: Option<Instant>,
+    /// Whether it answered the latest check; `None` before the first
+    pub reachable: Option<bool>,
+}
+impl ServerContact {
+    pub fn record(&mut self, reachable: bool) {
+        self.reachable = Some(reachable);
+        if reachable {
+            self.last_answered = Some(Instant::now());
         }
     }
 }
@@ -47,42 +257,127 @@ impl AppState {
 #[derive(Clone, PartialEq)]
 pub enum RecordingStatus {
     Stopped,
+    /// Recording was asked for, and will start once the countdown runs out unless it's cancelled
+    CountingDown {
+        starts_at: Instant,
+    },
     Recording {
         start_time: Instant,
         game_exe: String,
+        /// Set while the user has manually paused the recording
+        paused_at: Option<Instant>,
+        /// Frames dropped so far, once the recorder has reported them
+        skipped_frames: Option<SkippedFrames>,
+    },
+    /// Stopped due to inactivity, and will restart on the next input
+    Paused {
+        /// Set once the session is about to be ended for being idle too long, unless there's input before then
+        auto_stop_at: Option<Instant>,
     },
-    Paused,
 }
 
 pub struct GitHubRelease {
     pub name: String,
     pub url: String,
     pub release_date: Option<chrono::DateTime<chrono::Utc>>,
+    /// The release notes, in Markdown
+    pub notes: Option<String>,
 }
 
 /// A request for some async action to happen. Response will be delivered via [`UiUpdate`].
 pub enum AsyncRequest {
-    ValidateApiKey { api_key: String },
+    ValidateApiKey {
+        api_key: String,
+    },
+    /// Queue every recording that's ready to be uploaded
     UploadData,
+    /// Queue a single recording
+    UploadRecording(std::path::PathBuf),
+    RetryUpload(std::path::PathBuf),
+    CancelUpload(std::path::PathBuf),
+    /// Remove finished uploads from the queue
+    ClearCompletedUploads,
     OpenDataDump,
     OpenLog,
+    /// Bundle up the logs and config (and the latest recording's metadata, if asked) for a bug report
+    CreateProblemReport {
+        include_latest_recording: bool,
+    },
     UpdateUnsupportedGames(UnsupportedGames),
     LoadUploadStats,
+    /// Replace the labels of a recording that has already been uploaded
+    UpdateUploadLabels {
+        upload_id: String,
+        labels: SessionLabels,
+    },
     LoadLocalRecordings,
     DeleteAllInvalidRecordings,
+    /// Delete every recording that has been uploaded, regardless of how long they'd otherwise be kept
+    CleanUpUploadedRecordings,
     OpenFolder(std::path::PathBuf),
+    /// Bundle a finished recording into an archive at `archive_path`, to be imported on another machine
+    ExportRecording {
+        folder_path: std::path::PathBuf,
+        archive_path: std::path::PathBuf,
+    },
+    /// Add exported recordings to the recording location, ready to be uploaded
+    ImportRecordings(Vec<std::path::PathBuf>),
+    /// Read a finished recording's inputs back for previewing
+    LoadPlayback(std::path::PathBuf),
+    /// Check that the API key is accepted and that uploads would get through, without logging in with it
+    TestConnection,
+    /// Check that the server can be reached, and that the API key is still accepted
+    CheckConnection,
+    /// Stop any active recording and forget the validated API key
+    Logout,
+    /// Pause the active recording, or resume it if it's already paused
+    TogglePauseRecording,
+    /// Start recording the next game the user switches to. Used from the tray, where the game
+    /// can't be in the foreground at the time of the request.
+    StartRecording,
+    StopRecording,
+    /// Check whether a game that couldn't be captured can be now, after the user has done something about it
+    RecheckCapturePermission,
+    /// Find the best settings this machine can record at without dropping frames, by recording the next game
+    /// the user switches to at each in turn
+    RunBenchmark,
 }
 
 /// A message sent to the UI thread, usually in response to some action taken in another thread
 pub enum UiUpdate {
     /// Dummy update to force the UI to repaint
     ForceUpdate,
-    UpdateUserId(Result<String, String>),
-    UpdateUploadProgress(Option<ProgressData>),
-    UploadFailed(String),
+    UpdateUserId(Result<String, ApiKeyValidationError>),
+    /// Capture settings the server sent along with the account details, to be applied to the preferences
+    UpdateRemoteCaptureSettings(RemoteCaptureSettings),
+    /// The server turned the API key check away for making too many; it's checked again at `retry_at`
+    ApiKeyRateLimited {
+        retry_at: Instant,
+    },
+    /// A queued upload has finished, successfully if `error` is `None`.
+    /// Progress is tracked in [`AppState::upload_queue`].
+    UploadFinished {
+        folder_name: String,
+        error: Option<String>,
+    },
     UpdateTrayIconRecording(bool),
     UpdateNewerReleaseAvailable(GitHubRelease),
     UpdateLocalRecordings(Vec<LocalRecording>),
+    /// Free disk space in MB if it's getting low, or `None` if there's plenty
+    UpdateLowDiskSpace(Option<u64>),
+    /// The reference id of a problem report that was just created, or why it couldn't be
+    ProblemReportCreated(Result<String, String>),
+    /// A recording loaded for previewing, or why it couldn't be
+    PlaybackLoaded(Result<Playback, String>),
+    ConnectionTested(ConnectionTest),
+    /// How the benchmark went, or why it couldn't finish
+    BenchmarkFinished(Result<BenchmarkReport, String>),
+    /// Whether the server took the new labels for an uploaded recording, or why not
+    UploadLabelsUpdated(Result<(), String>),
+    /// Whether a recording was started, or why it couldn't be
+    RecordingStarted(Result<(), String>),
+    /// What was recorded in a session that has just ended, one for each segment
+    RecordingFinished(Vec<RecordingSummary>),
 }
 
 /// A sender for [`UiUpdate`] messages. Will automatically repaint the UI after sending a message.
@@ -1,8 +1,17 @@
 use color_eyre::eyre::{Context, Result, eyre};
-use constants::encoding::VideoEncoderType;
+use constants::encoding::{VideoCodec, VideoEncoderType};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::{fs, path::PathBuf};
 
+use crate::{
+    api::{RemoteCaptureSettings, RemoteSetting},
+    output_types::SessionLabels,
+    system::{
+        credential_store::{self, ProtectedSecret},
+        keycode::lookup_keycode,
+    },
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 // camel case renames are legacy from old existing configs, we want it to be backwards-compatible with previous owl releases that used electron
 #[serde(rename_all = "camelCase")]
@@ -13,20 +22,158 @@ pub struct Preferences {
     pub stop_recording_key: String,
     #[serde(default)]
     pub stop_hotkey_enabled: bool,
+    #[serde(default = "default_pause_key")]
+    pub pause_recording_key: String,
+    /// Pauses the recording straight away, and never resumes it; only the pause key does
+    #[serde(default = "default_privacy_pause_key")]
+    pub privacy_pause_key: String,
+    /// Saves the instant replay buffer as a recording
+    #[serde(default = "default_save_replay_key")]
+    pub save_replay_key: String,
+    /// Queue each recording for upload as soon as it finishes, instead of waiting for the user to upload it
+    #[serde(default)]
+    pub auto_upload: bool,
     #[serde(default)]
     pub unreliable_connection: bool,
+    /// `None` lets the server pick the chunk size
+    #[serde(default)]
+    pub upload_chunk_size_mb: Option<u64>,
+    #[serde(default = "default_upload_retries")]
+    pub upload_retries: u32,
+    #[serde(default = "default_max_concurrent_uploads")]
+    pub max_concurrent_uploads: u32,
+    /// Proxy URL for everything sent to our servers. `None` uses `HTTP_PROXY`/`HTTPS_PROXY` or the system settings.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// API server to use instead of production, e.g. a staging or self-hosted backend
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+    /// Whether recordings are uploaded to the API server. Can only be turned off if they're uploaded to an
+    /// S3 bucket instead.
+    #[serde(default = "default_upload_to_server")]
+    pub upload_to_server: bool,
+    /// A bucket of the user's own that recordings are also uploaded to, if it's enabled
+    #[serde(default)]
+    pub s3_destination: S3Destination,
+    /// Whether recordings are encrypted while they're kept on disk, and with what
+    #[serde(default)]
+    pub encryption: RecordingEncryption,
+    /// Upload speed limit in KB/s, shared between all uploads; `None` for unlimited
+    #[serde(default)]
+    pub upload_speed_limit_kbps: Option<u32>,
+    /// Hours of the day (local time; from the first, up to but not including the second) during which
+    /// the upload speed limit doesn't apply. Wraps around midnight if the first is later than the second.
+    #[serde(default)]
+    pub unlimited_upload_hours: Option<(u8, u8)>,
+    /// Whether the introduction is shown before the login screen on startup
+    #[serde(default = "default_show_onboarding")]
+    pub show_onboarding: bool,
+    /// Whether to look for a newer version on startup
+    #[serde(default = "default_check_for_updates")]
+    pub check_for_updates: bool,
+    #[serde(default)]
+    pub theme: Theme,
+    /// The language the UI is shown in
+    #[serde(default)]
+    pub language: Language,
+    #[serde(default = "default_show_overlay")]
+    pub show_overlay: bool,
+    /// Show a small, live copy of what's being recorded in the main window while recording
+    #[serde(default = "default_show_recording_preview")]
+    pub show_recording_preview: bool,
     #[serde(default)]
     pub overlay_location: OverlayLocation,
     #[serde(default = "default_opacity")]
     pub overlay_opacity: u8,
+    /// Shrink the window down to a small always-on-top HUD while recording
+    #[serde(default)]
+    pub compact_hud: bool,
+    /// Where the HUD was last dragged to, in screen pixels
     #[serde(default)]
+    pub hud_position: Option<(i32, i32)>,
+    /// Superseded by `uploaded_retention`; only read to migrate older configs
+    #[serde(default, skip_serializing)]
     pub delete_uploaded_files: bool,
+    /// What happens to recordings once their upload has been confirmed
+    #[serde(default)]
+    pub uploaded_retention: UploadedRecordingRetention,
+    /// Upload inputs in the compact binary format (see [`crate::input_track`]) instead of as CSV
+    #[serde(default)]
+    pub compact_input_track: bool,
+    /// Send anonymous statistics on dropped frames and upload failures, see [`crate::telemetry`]
+    #[serde(default)]
+    pub telemetry: bool,
     #[serde(default)]
     pub honk: bool,
     #[serde(default)]
     pub recording_backend: RecordingBackend,
     #[serde(default)]
     pub encoder: EncoderSettings,
+    #[serde(default = "default_fps")]
+    pub fps: u32,
+    /// Keeps recording from slowing the game down: a hardware encoder is used instead of x264 if there is one,
+    /// and the frame rate is lowered for the rest of the session if frames start being dropped
+    #[serde(default)]
+    pub low_impact: bool,
+    /// Mouse movement is summed up and written at most this many times a second, regardless of `fps`.
+    /// `None` writes every movement as it arrives. Every other input is always written as it arrives.
+    #[serde(default = "default_input_rate_hz")]
+    pub input_rate_hz: Option<u32>,
+    /// Recordings are split into segments of this many minutes, which are uploaded separately
+    #[serde(default = "default_segment_length_minutes")]
+    pub segment_length_minutes: u32,
+    /// If set, the start key starts an instant replay instead of a recording: only the last this many minutes
+    /// are kept (on disk, in short segments), until the save replay key turns them into a recording
+    #[serde(default)]
+    pub instant_replay_minutes: Option<u32>,
+    /// If set, recording starts this many seconds after it's asked to, so that the user can get into position
+    #[serde(default)]
+    pub start_countdown_seconds: Option<u32>,
+    /// Recording already stops after a short time without input and starts again on the next input.
+    /// If this is set, the session is ended for good once there's been no input for this many minutes.
+    #[serde(default)]
+    pub idle_auto_stop_minutes: Option<u32>,
+    /// Recording stops when the free disk space drops below this
+    #[serde(default = "default_min_free_space_mb")]
+    pub min_free_space_mb: u64,
+    /// Record the sound coming out of an output device, in addition to the game's own audio
+    #[serde(default)]
+    pub desktop_audio: bool,
+    /// WASAPI endpoint ID of the output device to record desktop audio from; `None` for the system default
+    #[serde(default)]
+    pub desktop_audio_device: Option<String>,
+    /// Recording is paused while a window matching one of these is focused.
+    /// See [`crate::system::capture_denylist`] for how entries are matched.
+    #[serde(default)]
+    pub capture_denylist: Vec<String>,
+    /// Where recordings are stored. `None` uses [`constants::DEFAULT_RECORDING_LOCATION`].
+    #[serde(default)]
+    pub recording_location: Option<PathBuf>,
+    /// What each recording's folder is named; see [`crate::record::RECORDING_NAME_TOKENS`] for what can go in it
+    #[serde(default = "default_recording_name_template")]
+    pub recording_name_template: String,
+    /// The only application recorded, if one has been picked; otherwise whichever game is in the foreground is
+    #[serde(default)]
+    pub capture_window: Option<CaptureWindow>,
+    /// Device name of the monitor games are recorded on, see [`crate::system::hardware_specs::MonitorInfo`].
+    /// `None` records games on any monitor.
+    #[serde(default)]
+    pub capture_monitor: Option<String>,
+    /// Only this part of the screen is recorded, if set; otherwise the whole game window is
+    #[serde(default)]
+    pub capture_region: Option<CaptureRegion>,
+    /// Whether the capture region's edges snap to nearby window edges while it's being picked
+    #[serde(default = "default_snap_capture_region")]
+    pub snap_capture_region: bool,
+    /// Labels given to recordings so far, suggested when labelling the next one
+    #[serde(default)]
+    pub label_history: LabelHistory,
+    /// The capture settings the server last sent, kept so that mandatory ones still apply while offline
+    #[serde(default)]
+    pub remote_capture_settings: Option<RemoteCaptureSettings>,
+    /// The capture settings whose current value came from the server, rather than the user
+    #[serde(default)]
+    pub remote_sourced_settings: Vec<CaptureSetting>,
 }
 impl Default for Preferences {
     fn default() -> Self {
@@ -34,13 +181,58 @@ impl Default for Preferences {
             start_recording_key: default_start_key(),
             stop_recording_key: default_stop_key(),
             stop_hotkey_enabled: Default::default(),
+            pause_recording_key: default_pause_key(),
+            privacy_pause_key: default_privacy_pause_key(),
+            save_replay_key: default_save_replay_key(),
+            auto_upload: Default::default(),
             unreliable_connection: Default::default(),
+            upload_chunk_size_mb: Default::default(),
+            upload_retries: default_upload_retries(),
+            max_concurrent_uploads: default_max_concurrent_uploads(),
+            proxy: None,
+            api_base_url: None,
+            upload_to_server: default_upload_to_server(),
+            s3_destination: Default::default(),
+            encryption: Default::default(),
+            upload_speed_limit_kbps: Default::default(),
+            unlimited_upload_hours: Default::default(),
+            show_onboarding: default_show_onboarding(),
+            check_for_updates: default_check_for_updates(),
+            theme: Default::default(),
+            language: Default::default(),
+            show_overlay: default_show_overlay(),
+            show_recording_preview: default_show_recording_preview(),
             overlay_location: Default::default(),
             overlay_opacity: default_opacity(),
+            compact_hud: Default::default(),
+            hud_position: Default::default(),
             delete_uploaded_files: Default::default(),
+            uploaded_retention: Default::default(),
+            compact_input_track: Default::default(),
+            telemetry: Default::default(),
             honk: Default::default(),
             recording_backend: Default::default(),
             encoder: Default::default(),
+            fps: default_fps(),
+            low_impact: Default::default(),
+            input_rate_hz: default_input_rate_hz(),
+            segment_length_minutes: default_segment_length_minutes(),
+            instant_replay_minutes: Default::default(),
+            start_countdown_seconds: Default::default(),
+            idle_auto_stop_minutes: Default::default(),
+            min_free_space_mb: default_min_free_space_mb(),
+            desktop_audio: Default::default(),
+            desktop_audio_device: Default::default(),
+            capture_denylist: Default::default(),
+            recording_location: Default::default(),
+            recording_name_template: default_recording_name_template(),
+            capture_window: Default::default(),
+            capture_monitor: Default::default(),
+            capture_region: Default::default(),
+            snap_capture_region: default_snap_capture_region(),
+            label_history: Default::default(),
+            remote_capture_settings: Default::default(),
+            remote_sourced_settings: Default::default(),
         }
     }
 }
@@ -55,6 +247,303 @@ impl Preferences {
             &self.start_recording_key
         }
     }
+    /// The key bound to `hotkey`, whether or not it's listened for
+    pub fn hotkey(&self, hotkey: Hotkey) -> &str {
+        match hotkey {
+            Hotkey::Start => &self.start_recording_key,
+            Hotkey::Stop => &self.stop_recording_key,
+            Hotkey::Pause => &self.pause_recording_key,
+            Hotkey::PrivacyPause => &self.privacy_pause_key,
+            Hotkey::SaveReplay => &self.save_replay_key,
+        }
+    }
+    pub fn hotkey_mut(&mut self, hotkey: Hotkey) -> &mut String {
+        match hotkey {
+            Hotkey::Start => &mut self.start_recording_key,
+            Hotkey::Stop => &mut self.stop_recording_key,
+            Hotkey::Pause => &mut self.pause_recording_key,
+            Hotkey::PrivacyPause => &mut self.privacy_pause_key,
+            Hotkey::SaveReplay => &mut self.save_replay_key,
+        }
+    }
+    /// Whether `hotkey` is listened for. The stop key is only used if it's been turned on;
+    /// otherwise the start key toggles recording.
+    pub fn is_hotkey_enabled(&self, hotkey: Hotkey) -> bool {
+        hotkey != Hotkey::Stop || self.stop_hotkey_enabled
+    }
+    /// The other hotkeys that are bound to the same key as `hotkey`, and would get in each other's way.
+    /// Start and stop sharing a key is fine, as that's how recording is toggled.
+    pub fn hotkey_conflicts(&self, hotkey: Hotkey) -> Vec<Hotkey> {
+        if !self.is_hotkey_enabled(hotkey) {
+            return vec![];
+        }
+        let is_toggle = |a: Hotkey, b: Hotkey| {
+            matches!(
+                (a, b),
+                (Hotkey::Start, Hotkey::Stop) | (Hotkey::Stop, Hotkey::Start)
+            )
+        };
+        Hotkey::ALL
+            .into_iter()
+            .filter(|other| {
+                *other != hotkey
+                    && self.is_hotkey_enabled(*other)
+                    && !is_toggle(hotkey, *other)
+                    && self
+                        .hotkey(*other)
+                        .eq_ignore_ascii_case(self.hotkey(hotkey))
+            })
+            .collect()
+    }
+    /// Keycodes of the hotkeys that are listened for
+    pub fn hotkey_keycodes(&self) -> Vec<u16> {
+        Hotkey::ALL
+            .into_iter()
+            .filter(|&hotkey| self.is_hotkey_enabled(hotkey))
+            .filter_map(|hotkey| lookup_keycode(self.hotkey(hotkey)))
+            .collect()
+    }
+    pub fn reset_hotkeys(&mut self) {
+        for hotkey in Hotkey::ALL {
+            *self.hotkey_mut(hotkey) = hotkey.default_key();
+        }
+    }
+    /// Replaces anything that can't be used (e.g. left over from an older version, or edited by hand)
+    /// with something that can
+    fn sanitize(&mut self) {
+        // Ensure hotkeys have default values if not set, or if they're set to something we can't listen for
+        for hotkey in Hotkey::ALL {
+            if lookup_keycode(self.hotkey(hotkey)).is_none() {
+                *self.hotkey_mut(hotkey) = hotkey.default_key();
+            }
+        }
+        if self.delete_uploaded_files {
+            self.delete_uploaded_files = false;
+            self.uploaded_retention = UploadedRecordingRetention::DeleteImmediately;
+        }
+        if let UploadedRecordingRetention::KeepDays(days) = self.uploaded_retention
+            && !constants::UPLOADED_RETENTION_OPTIONS_DAYS.contains(&days)
+        {
+            tracing::warn!("Unsupported retention of {days} days in config, using default");
+            self.uploaded_retention = UploadedRecordingRetention::default();
+        }
+        if !constants::FPS_OPTIONS.contains(&self.fps) {
+            tracing::warn!("Unsupported FPS {} in config, using default", self.fps);
+            self.fps = default_fps();
+        }
+        if let Some(rate) = self.input_rate_hz
+            && !constants::INPUT_RATE_OPTIONS_HZ.contains(&rate)
+        {
+            tracing::warn!("Unsupported input rate {rate} Hz in config, using default");
+            self.input_rate_hz = default_input_rate_hz();
+        }
+        if !constants::SEGMENT_LENGTH_OPTIONS_MINUTES.contains(&self.segment_length_minutes) {
+            tracing::warn!(
+                "Unsupported segment length {} minutes in config, using default",
+                self.segment_length_minutes
+            );
+            self.segment_length_minutes = default_segment_length_minutes();
+        }
+        if let Some(minutes) = self.instant_replay_minutes
+            && !constants::INSTANT_REPLAY_OPTIONS_MINUTES.contains(&minutes)
+        {
+            tracing::warn!(
+                "Unsupported instant replay length {minutes} minutes in config, turning it off"
+            );
+            self.instant_replay_minutes = None;
+        }
+        if let Some(seconds) = self.start_countdown_seconds
+            && !constants::START_COUNTDOWN_OPTIONS_SECONDS.contains(&seconds)
+        {
+            tracing::warn!(
+                "Unsupported start countdown of {seconds} seconds in config, turning it off"
+            );
+            self.start_countdown_seconds = None;
+        }
+        if let Some(chunk_size_mb) = self.upload_chunk_size_mb
+            && !constants::UPLOAD_CHUNK_SIZE_OPTIONS_MB.contains(&chunk_size_mb)
+        {
+            tracing::warn!(
+                "Unsupported upload chunk size {chunk_size_mb} MB in config, using default"
+            );
+            self.upload_chunk_size_mb = None;
+        }
+        let encoder = &mut self.encoder;
+        encoder.bitrate = encoder.bitrate.clamp(
+            constants::encoding::MIN_BITRATE,
+            constants::encoding::MAX_BITRATE,
+        );
+        encoder.keyframe_interval_seconds = encoder.keyframe_interval_seconds.clamp(
+            constants::encoding::MIN_KEYFRAME_INTERVAL_SECONDS,
+            constants::encoding::MAX_KEYFRAME_INTERVAL_SECONDS,
+        );
+        if !encoder.encoder.supports_codec(encoder.codec) {
+            encoder.codec = VideoCodec::H264;
+        }
+        if self.idle_auto_stop_minutes == Some(0) {
+            self.idle_auto_stop_minutes = None;
+        }
+        if self.upload_speed_limit_kbps == Some(0) {
+            self.upload_speed_limit_kbps = None;
+        }
+        if let Some((start, end)) = self.unlimited_upload_hours
+            && (start >= 24 || end >= 24)
+        {
+            tracing::warn!("Invalid unlimited upload hours {start}-{end} in config, ignoring them");
+            self.unlimited_upload_hours = None;
+        }
+        if !self.upload_to_server && !self.s3_destination.is_usable() {
+            tracing::warn!(
+                "Uploads would go nowhere without the S3 bucket, uploading to the server"
+            );
+            self.upload_to_server = true;
+        }
+        if self.encryption.enabled && self.encryption.secrets.is_empty() {
+            tracing::warn!("Recording encryption is on without a key, turning it off");
+            self.encryption.enabled = false;
+        }
+        self.max_concurrent_uploads = self
+            .max_concurrent_uploads
+            .clamp(1, constants::MAX_CONCURRENT_UPLOADS);
+        if let Some(region) = &self.capture_region
+            && (region.width == 0
+                || region.height == 0
+                || region.monitor_resolution.0 == 0
+                || region.monitor_resolution.1 == 0)
+        {
+            tracing::warn!("Empty capture region {region:?} in config, recording the whole window");
+            self.capture_region = None;
+        }
+        // Mandatory settings from the server still apply while offline, or if the config was edited by hand
+        if let Some(remote) = self.remote_capture_settings.clone() {
+            self.apply_remote_capture_settings(remote);
+        }
+    }
+    /// Takes on the capture settings the server recommends. Mandatory ones always apply. Others only
+    /// replace what the user has if it's still the default or came from the server, and only when the
+    /// recommendation is new, so that anything the user picks instead sticks.
+    pub fn apply_remote_capture_settings(&mut self, remote: RemoteCaptureSettings) {
+        let remote = remote.sanitized(self.encoder.encoder);
+        let previous = self.remote_capture_settings.take().unwrap_or_default();
+        let defaults = Preferences::default();
+
+        self.apply_remote_setting(
+            CaptureSetting::Fps,
+            remote.fps,
+            previous.fps,
+            defaults.fps,
+            |p| &mut p.fps,
+        );
+        self.apply_remote_setting(
+            CaptureSetting::Codec,
+            remote.codec,
+            previous.codec,
+            defaults.encoder.codec,
+            |p| &mut p.encoder.codec,
+        );
+        self.apply_remote_setting(
+            CaptureSetting::Bitrate,
+            remote.bitrate_kbps,
+            previous.bitrate_kbps,
+            defaults.encoder.bitrate,
+            |p| &mut p.encoder.bitrate,
+        );
+        self.apply_remote_setting(
+            CaptureSetting::InputRate,
+            remote.input_rate_hz.map(|s| s.map(Some)),
+            previous.input_rate_hz.map(|s| s.map(Some)),
+            defaults.input_rate_hz,
+            |p| &mut p.input_rate_hz,
+        );
+        self.remote_capture_settings = Some(remote);
+    }
+    fn apply_remote_setting<T: PartialEq + Copy>(
+        &mut self,
+        kind: CaptureSetting,
+        remote: Option<RemoteSetting<T>>,
+        previous: Option<RemoteSetting<T>>,
+        default: T,
+        field: fn(&mut Preferences) -> &mut T,
+    ) {
+        let Some(remote) = remote else {
+            self.remote_sourced_settings.retain(|s| *s != kind);
+            return;
+        };
+        let is_new = previous.is_none_or(|previous| previous.value != remote.value);
+        let is_replaceable =
+            self.remote_sourced_settings.contains(&kind) || *field(self) == default;
+        if remote.mandatory || (is_new && is_replaceable) {
+            *field(self) = remote.value;
+            if !self.remote_sourced_settings.contains(&kind) {
+                self.remote_sourced_settings.push(kind);
+            }
+        }
+    }
+    /// Whether the server insists on its value for `kind`, so the user can't change it
+    pub fn is_remote_mandatory(&self, kind: CaptureSetting) -> bool {
+        self.remote_capture_settings
+            .as_ref()
+            .is_some_and(|remote| match kind {
+                CaptureSetting::Fps => remote.fps.is_some_and(|s| s.mandatory),
+                CaptureSetting::Codec => remote.codec.is_some_and(|s| s.mandatory),
+                CaptureSetting::Bitrate => remote.bitrate_kbps.is_some_and(|s| s.mandatory),
+                CaptureSetting::InputRate => remote.input_rate_hz.is_some_and(|s| s.mandatory),
+            })
+    }
+    /// Whether the current value of `kind` came from the server
+    pub fn is_remote_sourced(&self, kind: CaptureSetting) -> bool {
+        self.remote_sourced_settings.contains(&kind)
+    }
+    /// Stops treating settings the user has since changed as having come from the server
+    pub fn forget_overridden_remote_settings(&mut self) {
+        let Some(remote) = &self.remote_capture_settings else {
+            self.remote_sourced_settings.clear();
+            return;
+        };
+        let matches_remote = |kind: CaptureSetting| match kind {
+            CaptureSetting::Fps => remote.fps.is_some_and(|s| s.value == self.fps),
+            CaptureSetting::Codec => remote.codec.is_some_and(|s| s.value == self.encoder.codec),
+            CaptureSetting::Bitrate => remote
+                .bitrate_kbps
+                .is_some_and(|s| s.value == self.encoder.bitrate),
+            CaptureSetting::InputRate => remote
+                .input_rate_hz
+                .is_some_and(|s| Some(s.value) == self.input_rate_hz),
+        };
+        let sourced = self
+            .remote_sourced_settings
+            .iter()
+            .copied()
+            .filter(|&kind| matches_remote(kind))
+            .collect();
+        self.remote_sourced_settings = sourced;
+    }
+    /// How long each segment of a recording runs before the next one is started
+    pub fn segment_length(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.segment_length_minutes as u64 * 60)
+    }
+    /// How long to wait before recording starts, if at all
+    pub fn start_countdown(&self) -> Option<std::time::Duration> {
+        self.start_countdown_seconds
+            .map(|seconds| std::time::Duration::from_secs(seconds as u64))
+    }
+    /// The upload speed limit in bytes per second at the given hour of the day, if there is one
+    pub fn upload_speed_limit_at(&self, hour: u32) -> Option<u64> {
+        if let Some((start, end)) = self.unlimited_upload_hours
+            && is_hour_in_range(hour, start as u32, end as u32)
+        {
+            return None;
+        }
+        self.upload_speed_limit_kbps.map(|kbps| kbps as u64 * 1024)
+    }
+}
+
+fn is_hour_in_range(hour: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        (start..end).contains(&hour)
+    } else {
+        hour >= start || hour < end
+    }
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
@@ -64,6 +553,249 @@ pub enum RecordingBackend {
     Socket,
 }
 
+/// An action that can be triggered from anywhere with a hotkey
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hotkey {
+    Start,
+    Stop,
+    Pause,
+    PrivacyPause,
+    SaveReplay,
+}
+impl Hotkey {
+    pub const ALL: [Hotkey; 5] = [
+        Hotkey::Start,
+        Hotkey::Stop,
+        Hotkey::Pause,
+        Hotkey::PrivacyPause,
+        Hotkey::SaveReplay,
+    ];
+
+    pub fn default_key(self) -> String {
+        match self {
+            Hotkey::Start => default_start_key(),
+            Hotkey::Stop => default_stop_key(),
+            Hotkey::Pause => default_pause_key(),
+            Hotkey::PrivacyPause => default_privacy_pause_key(),
+            Hotkey::SaveReplay => default_save_replay_key(),
+        }
+    }
+}
+impl std::fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hotkey::Start => write!(f, "Start Recording"),
+            Hotkey::Stop => write!(f, "Stop Recording"),
+            Hotkey::Pause => write!(f, "Pause Recording"),
+            Hotkey::PrivacyPause => write!(f, "Privacy Pause"),
+            Hotkey::SaveReplay => write!(f, "Save Replay"),
+        }
+    }
+}
+
+/// A capture setting that the server can recommend a value for, see [`RemoteCaptureSettings`]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CaptureSetting {
+    Fps,
+    Codec,
+    Bitrate,
+    InputRate,
+}
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Follow the OS light/dark setting
+    System,
+}
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::System];
+}
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Theme::Dark => write!(f, "Dark"),
+            Theme::Light => write!(f, "Light"),
+            Theme::System => write!(f, "Match System"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    German,
+    French,
+    Portuguese,
+}
+impl Language {
+    pub const ALL: [Language; 5] = [
+        Language::English,
+        Language::Spanish,
+        Language::German,
+        Language::French,
+        Language::Portuguese,
+    ];
+}
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Each in its own language, so that people can find theirs whatever the UI is showing
+        match self {
+            Language::English => write!(f, "English"),
+            Language::Spanish => write!(f, "Español"),
+            Language::German => write!(f, "Deutsch"),
+            Language::French => write!(f, "Français"),
+            Language::Portuguese => write!(f, "Português (Brasil)"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum UploadedRecordingRetention {
+    DeleteImmediately,
+    /// Deleted once it's been this many days since the upload
+    KeepDays(u32),
+    #[default]
+    KeepForever,
+}
+impl UploadedRecordingRetention {
+    pub fn all() -> impl Iterator<Item = Self> {
+        std::iter::once(Self::DeleteImmediately)
+            .chain(
+                constants::UPLOADED_RETENTION_OPTIONS_DAYS
+                    .into_iter()
+                    .map(Self::KeepDays),
+            )
+            .chain(std::iter::once(Self::KeepForever))
+    }
+}
+impl std::fmt::Display for UploadedRecordingRetention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadedRecordingRetention::DeleteImmediately => write!(f, "Delete After Upload"),
+            UploadedRecordingRetention::KeepDays(1) => write!(f, "Keep for 1 Day"),
+            UploadedRecordingRetention::KeepDays(days) => write!(f, "Keep for {days} Days"),
+            UploadedRecordingRetention::KeepForever => write!(f, "Keep Forever"),
+        }
+    }
+}
+
+/// Game titles, genres and tags that recordings have been labelled with, most recently used first
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct LabelHistory {
+    #[serde(default)]
+    pub game_titles: Vec<String>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+impl LabelHistory {
+    /// How many of each are remembered
+    const MAX_ENTRIES: usize = 50;
+
+    pub fn remember(&mut self, labels: &SessionLabels) {
+        fn remember(history: &mut Vec<String>, value: &str) {
+            history.retain(|v| !v.eq_ignore_ascii_case(value));
+            history.insert(0, value.to_string());
+            history.truncate(LabelHistory::MAX_ENTRIES);
+        }
+        if let Some(game_title) = &labels.game_title {
+            remember(&mut self.game_titles, game_title);
+        }
+        if let Some(genre) = &labels.genre {
+            remember(&mut self.genres, genre);
+        }
+        // Reversed, so that they keep the order they were given in
+        for tag in labels.tags.iter().rev() {
+            remember(&mut self.tags, tag);
+        }
+    }
+}
+
+/// An application picked from the list of open windows, so that nothing else is recorded by mistake
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CaptureWindow {
+    /// Executable name, e.g. `game.exe`. Used to find it again, as window titles change (e.g. with the level).
+    pub exe: String,
+    /// The window's title when it was picked, to show the user what they picked
+    pub title: String,
+}
+impl CaptureWindow {
+    /// Whether `game_exe` is this application
+    pub fn matches(&self, game_exe: &str) -> bool {
+        self.exe.eq_ignore_ascii_case(game_exe)
+    }
+}
+
+/// A rectangle on one monitor to record, instead of the whole game window. Only the part of it that the
+/// game window covers ends up in the recording.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CaptureRegion {
+    /// Device name of the monitor the region is on, see [`crate::system::hardware_specs::MonitorInfo`]
+    pub monitor: String,
+    /// The monitor's resolution when the region was picked, so that it can be scaled to match if that changes
+    pub monitor_resolution: (u32, u32),
+    /// Position relative to the monitor's top left, in its pixels
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// An S3-compatible bucket (e.g. AWS S3, Cloudflare R2 or a self-hosted MinIO) that recordings are uploaded to,
+/// for researchers collecting their own copy of the dataset
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct S3Destination {
+    pub enabled: bool,
+    /// Scheme and host of the service, e.g. `https://s3.us-east-1.amazonaws.com`. Buckets are addressed by
+    /// path, which every S3-compatible service supports.
+    pub endpoint: String,
+    /// `auto` for services that don't have regions
+    pub region: String,
+    pub bucket: String,
+    /// Put in front of every recording's object key, e.g. `recordings/`
+    pub key_prefix: String,
+    pub access_key_id: String,
+    pub secret_access_key: ProtectedSecret,
+}
+impl S3Destination {
+    /// Whether it's enabled, with everything needed to upload to it filled in
+    pub fn is_usable(&self) -> bool {
+        self.enabled
+            && !self.endpoint.trim().is_empty()
+            && !self.region.trim().is_empty()
+            && !self.bucket.trim().is_empty()
+            && !self.access_key_id.trim().is_empty()
+            && !self.secret_access_key.is_empty()
+    }
+}
+
+/// Encryption of recordings at rest, see [`crate::encryption`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordingEncryption {
+    /// Whether recordings are encrypted once they finish. Turning it off leaves those already encrypted as they are.
+    pub enabled: bool,
+    pub key_source: EncryptionKeySource,
+    /// Every secret recordings have been encrypted with, the current one first. Those it replaced are kept so
+    /// that the recordings encrypted with them can still be uploaded.
+    pub secrets: Vec<ProtectedSecret>,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum EncryptionKeySource {
+    /// A random key, which only the user's Windows account can read
+    #[default]
+    WindowsAccount,
+    /// A passphrase the user picked, so that the recordings can also be read elsewhere with it
+    Passphrase,
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub enum OverlayLocation {
     #[default]
@@ -99,9 +831,63 @@ fn default_start_key() -> String {
 fn default_stop_key() -> String {
     "F5".to_string()
 }
+fn default_pause_key() -> String {
+    "F6".to_string()
+}
+fn default_privacy_pause_key() -> String {
+    "F7".to_string()
+}
+fn default_save_replay_key() -> String {
+    "F8".to_string()
+}
+fn default_show_onboarding() -> bool {
+    true
+}
+fn default_snap_capture_region() -> bool {
+    true
+}
+fn default_check_for_updates() -> bool {
+    true
+}
+fn default_show_overlay() -> bool {
+    true
+}
+fn default_show_recording_preview() -> bool {
+    true
+}
 fn default_opacity() -> u8 {
     85
 }
+fn default_fps() -> u32 {
+    constants::DEFAULT_FPS
+}
+fn default_input_rate_hz() -> Option<u32> {
+    Some(constants::DEFAULT_INPUT_RATE_HZ)
+}
+fn default_segment_length_minutes() -> u32 {
+    (constants::MAX_FOOTAGE.as_secs() / 60) as u32
+}
+fn default_recording_name_template() -> String {
+    crate::record::DEFAULT_RECORDING_NAME_TEMPLATE.to_string()
+}
+fn default_min_free_space_mb() -> u64 {
+    constants::DEFAULT_MIN_FREE_SPACE_MB
+}
+fn default_upload_to_server() -> bool {
+    true
+}
+fn default_upload_retries() -> u32 {
+    constants::DEFAULT_UPLOAD_RETRIES
+}
+fn default_max_concurrent_uploads() -> u32 {
+    constants::DEFAULT_MAX_CONCURRENT_UPLOADS
+}
+fn default_remember_me() -> bool {
+    true
+}
+fn default_profile_name() -> String {
+    DEFAULT_PROFILE.to_string()
+}
 
 // For some reason, previous electron configs saved hasConsented as a string instead of a boolean? So now we need a custom deserializer
 // to take that into account for backwards compatibility
@@ -121,19 +907,70 @@ where
     }
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Credentials {
-    #[serde(default)]
+    /// Never written to the config file; it lives in the OS credential store instead
+    /// (see [`credential_store`]). Still deserialized so that we can migrate legacy configs.
+    #[serde(default, skip_serializing)]
     pub api_key: String,
     #[serde(default, deserialize_with = "deserialize_string_bool")]
     pub has_consented: bool,
+    /// The [`constants::CONSENT_VERSION`] that was accepted. Missing on configs from before the form was versioned.
+    #[serde(default)]
+    pub consent_version: Option<u32>,
+    /// Whether the API key should be kept between sessions
+    #[serde(default = "default_remember_me")]
+    pub remember_me: bool,
+}
+impl Default for Credentials {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            has_consented: false,
+            consent_version: None,
+            remember_me: default_remember_me(),
+        }
+    }
 }
 impl Credentials {
     pub fn logout(&mut self) {
         self.api_key = String::new();
         self.has_consented = false;
+        self.consent_version = None;
+    }
+
+    pub fn accept_consent(&mut self) {
+        self.has_consented = true;
+        self.consent_version = Some(constants::CONSENT_VERSION);
     }
+
+    /// Agreeing to an older version of the consent form doesn't count, so ask again
+    fn check_consent_version(&mut self) {
+        if self.has_consented && self.consent_version != Some(constants::CONSENT_VERSION) {
+            tracing::info!(
+                accepted_version=?self.consent_version,
+                "Consent form has changed since it was accepted, asking again"
+            );
+            self.has_consented = false;
+        }
+    }
+}
+
+/// The profile everyone starts out with, and the only one there was before there were profiles
+pub const DEFAULT_PROFILE: &str = "Default";
+const MAX_PROFILE_NAME_LENGTH: usize = 32;
+
+/// An account and the settings that go with it, put aside until it's switched to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub name: String,
+    /// The API key is in the credential store under the profile's name, not here
+    #[serde(default)]
+    pub credentials: Credentials,
+    #[serde(default)]
+    pub preferences: Preferences,
 }
 
 /// The directory in which all persistent config data should be stored.
@@ -145,12 +982,29 @@ pub fn get_persistent_dir() -> Result<PathBuf> {
     Ok(dir)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub struct Config {
     #[serde(default)]
     pub credentials: Credentials,
     #[serde(default)]
     pub preferences: Preferences,
+    /// The name of the profile that `credentials` and `preferences` belong to
+    #[serde(default = "default_profile_name")]
+    pub profile_name: String,
+    /// Every other profile, which can be switched to with [`Config::switch_profile`]
+    #[serde(default)]
+    pub other_profiles: Vec<Profile>,
+}
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            credentials: Default::default(),
+            preferences: Default::default(),
+            profile_name: default_profile_name(),
+            other_profiles: Default::default(),
+        }
+    }
 }
 
 impl Config {
@@ -174,12 +1028,25 @@ impl Config {
         let mut config =
             serde_json::from_str::<Config>(&contents).context("Failed to parse config file")?;
 
-        // Ensure hotkeys have default values if not set
-        if config.preferences.start_recording_key.is_empty() {
-            config.preferences.start_recording_key = default_start_key();
+        config.credentials.check_consent_version();
+        config.preferences.sanitize();
+        for profile in &mut config.other_profiles {
+            profile.credentials.check_consent_version();
+            profile.preferences.sanitize();
         }
-        if config.preferences.stop_recording_key.is_empty() {
-            config.preferences.stop_recording_key = default_stop_key();
+
+        if !config.credentials.api_key.is_empty() {
+            // Older configs stored the API key in plaintext. Move it into the credential store,
+            // and rewrite the config so that the plaintext copy is gone.
+            tracing::info!("Migrating API key from config file to credential store");
+            if let Err(e) = config.save_api_key().and_then(|_| config.save()) {
+                tracing::error!(e=?e, "Failed to migrate API key to credential store");
+            }
+        } else if config.credentials.remember_me {
+            match credential_store::load(&config.profile_name) {
+                Ok(api_key) => config.credentials.api_key = api_key.unwrap_or_default(),
+                Err(e) => tracing::error!(e=?e, "Failed to load API key from credential store"),
+            }
         }
 
         Ok(config)
@@ -198,12 +1065,106 @@ impl Config {
         Ok(get_persistent_dir()?.join(constants::filename::persistent::CONFIG))
     }
 
+    /// Writes the config file. The API key isn't part of it; see [`Config::save_api_key`].
     pub fn save(&self) -> Result<()> {
         let config_path = Self::get_path()?;
         tracing::info!("Saving configs to {}", config_path.to_string_lossy());
         fs::write(&config_path, serde_json::to_string_pretty(&self)?)?;
         Ok(())
     }
+
+    /// Stores the API key in the credential store, or clears it if it's not to be remembered.
+    ///
+    /// Only to be called when the key or `remember_me` has been changed (logging in or out). If the
+    /// key couldn't be loaded, it's empty, and saving it then would throw away the stored one.
+    pub fn save_api_key(&self) -> Result<()> {
+        if self.credentials.remember_me && !self.credentials.api_key.is_empty() {
+            credential_store::store(&self.profile_name, &self.credentials.api_key)
+                .context("Failed to store API key")?;
+        } else {
+            credential_store::clear(&self.profile_name)
+                .context("Failed to clear stored API key")?;
+        }
+        Ok(())
+    }
+
+    /// The names of every profile, starting with the one in use
+    pub fn profile_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.profile_name.as_str())
+            .chain(self.other_profiles.iter().map(|p| p.name.as_str()))
+    }
+
+    /// Puts the current profile aside and carries on with the one called `name`, loading its API key
+    /// from the credential store. The current API key should have been saved first (with
+    /// [`Config::save_api_key`]) if it's been changed, as it's forgotten.
+    pub fn switch_profile(&mut self, name: &str) -> Result<()> {
+        let index = self
+            .other_profiles
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| eyre!("There's no profile called {name}"))?;
+        let profile = self.other_profiles.remove(index);
+
+        let mut previous = Profile {
+            name: std::mem::replace(&mut self.profile_name, profile.name),
+            credentials: std::mem::replace(&mut self.credentials, profile.credentials),
+            preferences: std::mem::replace(&mut self.preferences, profile.preferences),
+        };
+        previous.credentials.api_key.clear();
+        self.other_profiles.insert(index, previous);
+
+        if self.credentials.remember_me {
+            match credential_store::load(&self.profile_name) {
+                Ok(api_key) => self.credentials.api_key = api_key.unwrap_or_default(),
+                Err(e) => tracing::error!(e=?e, "Failed to load API key from credential store"),
+            }
+        }
+        tracing::info!("Switched to profile {}", self.profile_name);
+        Ok(())
+    }
+
+    /// Adds a profile with no account yet, starting from the current settings, and switches to it
+    pub fn add_profile(&mut self, name: &str) -> Result<()> {
+        let name = self.check_new_profile_name(name)?;
+        self.other_profiles.push(Profile {
+            name: name.clone(),
+            credentials: Credentials::default(),
+            preferences: self.preferences.clone(),
+        });
+        self.switch_profile(&name)
+    }
+
+    /// Removes a profile other than the one in use, along with its stored API key
+    pub fn remove_profile(&mut self, name: &str) -> Result<()> {
+        let index = self
+            .other_profiles
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| eyre!("There's no other profile called {name}"))?;
+        self.other_profiles.remove(index);
+        credential_store::clear(name).context("Failed to clear the profile's stored API key")?;
+        Ok(())
+    }
+
+    /// The name a new profile would be given, trimmed, or why it can't be used
+    fn check_new_profile_name(&self, name: &str) -> Result<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(eyre!("Please enter a name for the profile"));
+        }
+        if name.chars().count() > MAX_PROFILE_NAME_LENGTH {
+            return Err(eyre!(
+                "Profile names can be at most {MAX_PROFILE_NAME_LENGTH} characters long"
+            ));
+        }
+        if self
+            .profile_names()
+            .any(|existing| existing.eq_ignore_ascii_case(name))
+        {
+            return Err(eyre!("There's already a profile called {name}"));
+        }
+        Ok(name.to_string())
+    }
 }
 
 /// Base struct containing common video encoder settings shared across all encoders
@@ -212,17 +1173,30 @@ impl Config {
 pub struct EncoderSettings {
     /// Encoder type
     pub encoder: VideoEncoderType,
+    /// Output codec; always H.264 for encoders that don't support anything else
+    pub codec: VideoCodec,
+    /// Video bitrate (kbps)
+    pub bitrate: i64,
+    /// Seconds between keyframes, which are where the video can be seeked to or cut without re-encoding
+    pub keyframe_interval_seconds: u32,
 
     /// Encoder specific settings
     pub x264: ObsX264Settings,
     pub nvenc: FfmpegNvencSettings,
+    pub qsv: ObsQsvSettings,
+    pub amf: AmfSettings,
 }
 impl Default for EncoderSettings {
     fn default() -> Self {
         Self {
             encoder: VideoEncoderType::X264,
+            codec: Default::default(),
+            bitrate: constants::encoding::BITRATE,
+            keyframe_interval_seconds: constants::encoding::KEYFRAME_INTERVAL_SECONDS,
             x264: Default::default(),
             nvenc: Default::default(),
+            qsv: Default::default(),
+            amf: Default::default(),
         }
     }
 }
@@ -235,9 +1209,10 @@ impl EncoderSettings {
         // Apply common settings shared by all encoders
         let mut updater = data.bulk_update();
         updater = updater
-            .set_int("bitrate", constants::encoding::BITRATE)
+            .set_int("bitrate", self.bitrate)
+            .set_int("keyint_sec", self.keyframe_interval_seconds as i64)
             .set_string("rate_control", constants::encoding::RATE_CONTROL)
-            .set_string("profile", constants::encoding::VIDEO_PROFILE)
+            .set_string("profile", self.codec.profile())
             .set_int("bf", constants::encoding::B_FRAMES)
             .set_bool("psycho_aq", constants::encoding::PSYCHO_AQ)
             .set_bool("lookahead", constants::encoding::LOOKAHEAD);
@@ -245,6 +1220,8 @@ impl EncoderSettings {
         updater = match self.encoder {
             VideoEncoderType::X264 => self.x264.apply_to_data_updater(updater),
             VideoEncoderType::NvEnc => self.nvenc.apply_to_data_updater(updater),
+            VideoEncoderType::Qsv => self.qsv.apply_to_data_updater(updater),
+            VideoEncoderType::Amf => self.amf.apply_to_data_updater(updater),
         };
         updater.update()?;
 
@@ -303,3 +1280,202 @@ impl FfmpegNvencSettings {
             .set_string("tune", self.tune.as_str())
     }
 }
+
+/// Intel QuickSync (GPU) encoder specific settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ObsQsvSettings {
+    pub target_usage: String,
+}
+impl Default for ObsQsvSettings {
+    fn default() -> Self {
+        Self {
+            target_usage: constants::encoding::QSV_PRESETS[0].to_string(),
+        }
+    }
+}
+impl ObsQsvSettings {
+    fn apply_to_data_updater(
+        &self,
+        updater: libobs_wrapper::data::ObsDataUpdater,
+    ) -> libobs_wrapper::data::ObsDataUpdater {
+        updater.set_string("target_usage", self.target_usage.as_str())
+    }
+}
+
+/// AMD AMF (GPU) encoder specific settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct AmfSettings {
+    pub preset: String,
+}
+impl Default for AmfSettings {
+    fn default() -> Self {
+        Self {
+            preset: constants::encoding::AMF_PRESETS[0].to_string(),
+        }
+    }
+}
+impl AmfSettings {
+    fn apply_to_data_updater(
+        &self,
+        updater: libobs_wrapper::data::ObsDataUpdater,
+    ) -> libobs_wrapper::data::ObsDataUpdater {
+        updater.set_string("preset", self.preset.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_speed_limit_at() {
+        let mut preferences = Preferences {
+            upload_speed_limit_kbps: Some(500),
+            ..Default::default()
+        };
+        assert_eq!(preferences.upload_speed_limit_at(12), Some(500 * 1024));
+
+        // Overnight, wrapping around midnight
+        preferences.unlimited_upload_hours = Some((22, 6));
+        assert_eq!(preferences.upload_speed_limit_at(23), None);
+        assert_eq!(preferences.upload_speed_limit_at(3), None);
+        assert_eq!(preferences.upload_speed_limit_at(6), Some(500 * 1024));
+
+        preferences.unlimited_upload_hours = Some((9, 17));
+        assert_eq!(preferences.upload_speed_limit_at(9), None);
+        assert_eq!(preferences.upload_speed_limit_at(17), Some(500 * 1024));
+
+        preferences.upload_speed_limit_kbps = None;
+        assert_eq!(preferences.upload_speed_limit_at(20), None);
+    }
+
+    #[test]
+    fn test_hotkey_conflicts() {
+        let mut preferences = Preferences::default();
+        // Start and stop share a key by default, to toggle recording
+        preferences.stop_hotkey_enabled = true;
+        for hotkey in Hotkey::ALL {
+            assert_eq!(preferences.hotkey_conflicts(hotkey), vec![]);
+        }
+
+        preferences.pause_recording_key = "F5".to_string();
+        assert_eq!(
+            preferences.hotkey_conflicts(Hotkey::Pause),
+            vec![Hotkey::Start, Hotkey::Stop]
+        );
+        assert_eq!(
+            preferences.hotkey_conflicts(Hotkey::Start),
+            vec![Hotkey::Pause]
+        );
+
+        // The stop key isn't listened for unless it's turned on
+        preferences.stop_hotkey_enabled = false;
+        preferences.pause_recording_key = "F6".to_string();
+        preferences.stop_recording_key = "F6".to_string();
+        assert_eq!(preferences.hotkey_conflicts(Hotkey::Pause), vec![]);
+        assert_eq!(preferences.hotkey_conflicts(Hotkey::Stop), vec![]);
+
+        preferences.reset_hotkeys();
+        assert_eq!(preferences, Preferences::default());
+    }
+
+    #[test]
+    fn test_check_new_profile_name() {
+        let mut config = Config::default();
+        config.other_profiles.push(Profile {
+            name: "Research Lab".to_string(),
+            credentials: Default::default(),
+            preferences: Default::default(),
+        });
+
+        assert_eq!(
+            config.check_new_profile_name("  Personal ").unwrap(),
+            "Personal"
+        );
+        assert!(config.check_new_profile_name("   ").is_err());
+        assert!(config.check_new_profile_name("default").is_err());
+        assert!(config.check_new_profile_name("research lab").is_err());
+        assert!(config.check_new_profile_name(&"x".repeat(33)).is_err());
+        assert_eq!(
+            config.profile_names().collect::<Vec<_>>(),
+            vec!["Default", "Research Lab"]
+        );
+    }
+
+    #[test]
+    fn test_label_history_remember() {
+        let mut history = LabelHistory {
+            tags: vec!["pvp".to_string(), "night".to_string()],
+            ..Default::default()
+        };
+        history.remember(&SessionLabels {
+            game_title: Some("Minecraft".to_string()),
+            genre: None,
+            tags: vec!["Night".to_string(), "survival".to_string()],
+            notes: None,
+        });
+        assert_eq!(history.game_titles, ["Minecraft"]);
+        assert!(history.genres.is_empty());
+        assert_eq!(history.tags, ["Night", "survival", "pvp"]);
+    }
+
+    #[test]
+    fn test_capture_window_matches() {
+        let window = CaptureWindow {
+            exe: "Minecraft.exe".to_string(),
+            title: "Minecraft 1.21".to_string(),
+        };
+        assert!(window.matches("minecraft.exe"));
+        assert!(!window.matches("Discord.exe"));
+    }
+
+    #[test]
+    fn test_apply_remote_capture_settings() {
+        let remote = |fps: u32, mandatory: bool| RemoteCaptureSettings {
+            fps: Some(RemoteSetting {
+                value: fps,
+                mandatory,
+            }),
+            bitrate_kbps: Some(RemoteSetting {
+                value: 4000,
+                mandatory: false,
+            }),
+            ..Default::default()
+        };
+
+        // Defaults are replaced, but the user's own choices aren't
+        let mut preferences = Preferences {
+            fps: 15,
+            ..Default::default()
+        };
+        preferences.apply_remote_capture_settings(remote(30, false));
+        assert_eq!(preferences.fps, 15);
+        assert_eq!(preferences.encoder.bitrate, 4000);
+        assert_eq!(
+            preferences.remote_sourced_settings,
+            [CaptureSetting::Bitrate]
+        );
+
+        // Changing a recommended setting sticks, even when the same recommendation comes again
+        preferences.encoder.bitrate = constants::encoding::BITRATE;
+        preferences.forget_overridden_remote_settings();
+        assert!(!preferences.is_remote_sourced(CaptureSetting::Bitrate));
+        preferences.apply_remote_capture_settings(remote(30, false));
+        assert_eq!(preferences.encoder.bitrate, constants::encoding::BITRATE);
+
+        // Mandatory settings always apply
+        preferences.apply_remote_capture_settings(remote(30, true));
+        assert_eq!(preferences.fps, 30);
+        assert!(preferences.is_remote_mandatory(CaptureSetting::Fps));
+        preferences.fps = 60;
+        preferences.sanitize();
+        assert_eq!(preferences.fps, 30);
+
+        // Settings that come from the server follow it when it changes its mind
+        preferences.apply_remote_capture_settings(remote(15, false));
+        assert_eq!(preferences.fps, 15);
+        assert!(!preferences.is_remote_mandatory(CaptureSetting::Fps));
+    }
+}
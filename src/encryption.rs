@@ -0,0 +1,406 @@
+//! Optional encryption of recordings while they're kept on disk. A recording's video and inputs are encrypted in
+//! place once it finishes, and decrypted as they're read back for previewing, uploading or exporting; the metadata
+//! is left as it is, so that the recordings list still works.
+//!
+//! Files are encrypted with XChaCha20-Poly1305 in fixed-size chunks (the STREAM construction), so that they can
+//! be read back without holding them in memory, and so that a truncated or modified file fails to decrypt rather
+//! than being read as something else. Each file's key is derived from the user's secret with PBKDF2, using a
+//! salt of its own. The secret is either generated at random and only readable by the user's Windows account, or
+//! a passphrase, which also lets the recordings be read on another machine.
+//!
+//! ```text
+//! MAGIC | salt (16 bytes) | nonce prefix (19 bytes) | chunks of CHUNK_LEN bytes, each followed by a 16-byte tag
+//! ```
+
+use std::{
+    fs::File,
+    io::{BufReader, Read, Write as _},
+    path::Path,
+};
+
+use chacha20poly1305::{
+    KeyInit as _, XChaCha20Poly1305,
+    aead::{
+        OsRng,
+        rand_core::RngCore as _,
+        stream::{DecryptorBE32, EncryptorBE32},
+    },
+};
+use color_eyre::{
+    Result,
+    eyre::{self, Context as _, bail, eyre},
+};
+use sha2::Sha256;
+
+use crate::config::Config;
+
+/// Starts every encrypted file. Recordings and inputs never start with it otherwise.
+const MAGIC: &[u8; 8] = b"OWLENC\x00\x01";
+const SALT_LEN: usize = 16;
+/// XChaCha20's 24-byte nonce, less the 5 bytes STREAM uses for the chunk counter and last chunk flag
+const NONCE_PREFIX_LEN: usize = 19;
+const HEADER_LEN: u64 = (MAGIC.len() + SALT_LEN + NONCE_PREFIX_LEN) as u64;
+const CHUNK_LEN: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+/// Enough to make guessing a passphrase slow, while only adding a moment to opening each file
+const KDF_ROUNDS: u32 = 100_000;
+
+/// The secrets that recordings may have been encrypted with
+#[derive(Default, Clone)]
+pub struct RecordingKeys {
+    /// The current secret first, then any it replaced
+    secrets: Vec<String>,
+    /// Whether newly finished recordings are encrypted, with the current secret
+    enabled: bool,
+}
+impl RecordingKeys {
+    /// The current profile's keys, followed by the other profiles' so that the recordings they made can be read too
+    pub fn load(config: &Config) -> Result<Self> {
+        let encryption = &config.preferences.encryption;
+        let secrets = std::iter::once(encryption)
+            .chain(
+                config
+                    .other_profiles
+                    .iter()
+                    .map(|p| &p.preferences.encryption),
+            )
+            .flat_map(|e| &e.secrets)
+            .map(|secret| secret.reveal())
+            .collect::<Result<Vec<_>>>()
+            .context("failed to read the recording encryption key")?;
+        Ok(Self {
+            enabled: encryption.enabled && !encryption.secrets.is_empty(),
+            secrets,
+        })
+    }
+
+    /// The secret to encrypt newly finished recordings with, if they're to be encrypted
+    pub fn current(&self) -> Option<&str> {
+        self.secrets
+            .first()
+            .filter(|_| self.enabled)
+            .map(|s| s.as_str())
+    }
+}
+
+/// A random secret for encrypting recordings with, for when the user hasn't picked a passphrase
+pub fn generate_secret() -> String {
+    let mut secret = [0; 32];
+    OsRng.fill_bytes(&mut secret);
+    secret.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn is_encrypted(path: &Path) -> std::io::Result<bool> {
+    let mut magic = [0; MAGIC.len()];
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == MAGIC),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// The size of the file once decrypted, which is its actual size if it isn't encrypted
+pub fn plaintext_len(path: &Path) -> std::io::Result<u64> {
+    let len = std::fs::metadata(path)?.len();
+    Ok(match is_encrypted(path)? {
+        true => plaintext_len_of(len.saturating_sub(HEADER_LEN)),
+        false => len,
+    })
+}
+
+fn plaintext_len_of(ciphertext_len: u64) -> u64 {
+    let chunks = ciphertext_len.div_ceil((CHUNK_LEN + TAG_LEN) as u64).max(1);
+    ciphertext_len.saturating_sub(chunks * TAG_LEN as u64)
+}
+
+/// A file that's decrypted as it's read, if it's encrypted
+pub struct Reader {
+    inner: ReaderInner,
+    len: u64,
+}
+enum ReaderInner {
+    Plain(File),
+    Encrypted {
+        file: BufReader<File>,
+        /// `None` once the last chunk has been decrypted
+        decryptor: Option<DecryptorBE32<XChaCha20Poly1305>>,
+        /// Ciphertext still to be read
+        remaining: u64,
+        /// The decrypted chunk being read from
+        chunk: Vec<u8>,
+        position: usize,
+    },
+}
+impl Reader {
+    /// The size of the file once decrypted
+    pub fn size(&self) -> u64 {
+        self.len
+    }
+}
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match &mut self.inner {
+            ReaderInner::Plain(file) => file.read(buf),
+            ReaderInner::Encrypted {
+                file,
+                decryptor,
+                remaining,
+                chunk,
+                position,
+            } => {
+                if *position == chunk.len() {
+                    if decryptor.is_none() {
+                        return Ok(0);
+                    }
+                    *chunk = next_chunk(file, decryptor, remaining)?;
+                    *position = 0;
+                }
+                let len = buf.len().min(chunk.len() - *position);
+                buf[..len].copy_from_slice(&chunk[*position..*position + len]);
+                *position += len;
+                Ok(len)
+            }
+        }
+    }
+}
+
+/// Reads and decrypts the next chunk, taking the decryptor once it's used for the last one
+fn next_chunk(
+    file: &mut BufReader<File>,
+    decryptor: &mut Option<DecryptorBE32<XChaCha20Poly1305>>,
+    remaining: &mut u64,
+) -> std::io::Result<Vec<u8>> {
+    let len = (*remaining).min((CHUNK_LEN + TAG_LEN) as u64) as usize;
+    let mut ciphertext = vec![0; len];
+    file.read_exact(&mut ciphertext)?;
+    *remaining -= len as u64;
+    let plaintext = match decryptor.as_mut() {
+        Some(d) if *remaining > 0 => d.decrypt_next(ciphertext.as_slice()),
+        Some(_) => decryptor
+            .take()
+            .unwrap()
+            .decrypt_last(ciphertext.as_slice()),
+        None => return Ok(vec![]),
+    };
+    plaintext.map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "the recording failed to decrypt, so it's been corrupted or modified",
+        )
+    })
+}
+
+/// Opens the file at `path` for reading, decrypting it if it's encrypted
+pub fn open(path: &Path, keys: &RecordingKeys) -> Result<Reader> {
+    let open = || -> Result<Reader> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if !is_encrypted(path)? {
+            return Ok(Reader {
+                inner: ReaderInner::Plain(file),
+                len,
+            });
+        }
+
+        let mut header = [0; HEADER_LEN as usize];
+        file.read_exact(&mut header)?;
+        let (salt, nonce_prefix) = header[MAGIC.len()..].split_at(SALT_LEN);
+        let mut file = BufReader::new(file);
+        let ciphertext_len = len - HEADER_LEN;
+        // Which secret it was encrypted with isn't recorded, as that would make a passphrase easier to guess.
+        // Only the right one decrypts the first chunk.
+        let first_chunk_len = ciphertext_len.min((CHUNK_LEN + TAG_LEN) as u64) as usize;
+        let mut first_chunk = vec![0; first_chunk_len];
+        file.read_exact(&mut first_chunk)?;
+        for secret in &keys.secrets {
+            let mut decryptor = Some(DecryptorBE32::from_aead(
+                cipher(secret, salt),
+                nonce_prefix.into(),
+            ));
+            let remaining = ciphertext_len - first_chunk_len as u64;
+            let plaintext = match remaining {
+                0 => decryptor
+                    .take()
+                    .unwrap()
+                    .decrypt_last(first_chunk.as_slice()),
+                _ => decryptor
+                    .as_mut()
+                    .unwrap()
+                    .decrypt_next(first_chunk.as_slice()),
+            };
+            if let Ok(chunk) = plaintext {
+                return Ok(Reader {
+                    inner: ReaderInner::Encrypted {
+                        file,
+                        decryptor,
+                        remaining,
+                        chunk,
+                        position: 0,
+                    },
+                    len: plaintext_len_of(ciphertext_len),
+                });
+            }
+        }
+        bail!("it was encrypted with a different key or passphrase, or has been corrupted");
+    };
+    open().wrap_err_with(|| eyre!("failed to open {path:?}"))
+}
+
+/// Reads the whole file, decrypting it if it's encrypted
+pub fn read(path: &Path, keys: &RecordingKeys) -> Result<Vec<u8>> {
+    let mut reader = open(path, keys)?;
+    let mut bytes = Vec::with_capacity(reader.size() as usize);
+    reader
+        .read_to_end(&mut bytes)
+        .wrap_err_with(|| eyre!("failed to read {path:?}"))?;
+    Ok(bytes)
+}
+
+/// Encrypts the file at `path` with `secret`, replacing it once it's done. Does nothing if it's already encrypted.
+pub fn encrypt_file(path: &Path, secret: &str) -> Result<()> {
+    if is_encrypted(path)? {
+        return Ok(());
+    }
+    let mut salt = [0; SALT_LEN];
+    let mut nonce_prefix = [0; NONCE_PREFIX_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce_prefix);
+    let mut encryptor =
+        EncryptorBE32::from_aead(cipher(secret, &salt), nonce_prefix.as_slice().into());
+
+    let mut encrypted_path = path.as_os_str().to_owned();
+    encrypted_path.push(".encrypting");
+    let encrypted_path = std::path::PathBuf::from(encrypted_path);
+    let result = (|| {
+        let mut input = File::open(path)?;
+        let mut remaining = input.metadata()?.len();
+        let mut output = std::io::BufWriter::new(File::create(&encrypted_path)?);
+        output.write_all(MAGIC)?;
+        output.write_all(&salt)?;
+        output.write_all(&nonce_prefix)?;
+
+        let mut chunk = vec![0; CHUNK_LEN];
+        loop {
+            let len = remaining.min(CHUNK_LEN as u64) as usize;
+            input.read_exact(&mut chunk[..len])?;
+            remaining -= len as u64;
+            if remaining == 0 {
+                let ciphertext = encryptor
+                    .encrypt_last(&chunk[..len])
+                    .map_err(|_| eyre!("failed to encrypt"))?;
+                output.write_all(&ciphertext)?;
+                break;
+            }
+            let ciphertext = encryptor
+                .encrypt_next(&chunk[..len])
+                .map_err(|_| eyre!("failed to encrypt"))?;
+            output.write_all(&ciphertext)?;
+        }
+        output.into_inner()?.sync_all()?;
+        std::fs::rename(&encrypted_path, path)?;
+        eyre::Ok(())
+    })();
+    if result.is_err() {
+        std::fs::remove_file(&encrypted_path).ok();
+    }
+    result.wrap_err_with(|| eyre!("failed to encrypt {path:?}"))
+}
+
+/// Encrypts the video and inputs of the finished recording in `folder_path`, if recordings are to be encrypted.
+/// Returns whether they were.
+pub fn encrypt_recording(folder_path: &Path, keys: &RecordingKeys) -> Result<bool> {
+    let Some(secret) = keys.current() else {
+        return Ok(false);
+    };
+    if folder_path
+        .join(constants::filename::recording::IN_PROGRESS)
+        .is_file()
+    {
+        bail!("the recording hasn't finished yet");
+    }
+    for entry in folder_path.read_dir()?.flatten() {
+        let path = entry.path();
+        // The video isn't always named the same, see `upload::validate_folder`
+        let is_video = path.extension().and_then(|e| e.to_str()) == Some("mp4");
+        if is_video || entry.file_name() == constants::filename::recording::INPUTS {
+            encrypt_file(&path, secret)?;
+        }
+    }
+    Ok(true)
+}
+
+fn cipher(secret: &str, salt: &[u8]) -> XChaCha20Poly1305 {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(secret.as_bytes(), salt, KDF_ROUNDS, &mut key);
+    XChaCha20Poly1305::new(key.as_slice().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TestDir;
+
+    fn keys(secrets: &[&str]) -> RecordingKeys {
+        RecordingKeys {
+            secrets: secrets.iter().map(|s| s.to_string()).collect(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let dir = TestDir::new("encryption");
+        // Empty, less than a chunk, exactly a chunk, and spilling over into another
+        for len in [0, 1000, CHUNK_LEN, CHUNK_LEN * 2 + 7] {
+            let path = dir.join(format!("{len}.bin"));
+            let plaintext = (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+            std::fs::write(&path, &plaintext).unwrap();
+
+            encrypt_file(&path, "passphrase").unwrap();
+            assert!(is_encrypted(&path).unwrap());
+            assert_eq!(plaintext_len(&path).unwrap(), len as u64);
+            // Encrypting twice would make it unreadable
+            encrypt_file(&path, "passphrase").unwrap();
+
+            // The key it was encrypted with can come after newer ones
+            assert_eq!(
+                read(&path, &keys(&["new", "passphrase"])).unwrap(),
+                plaintext
+            );
+            assert!(read(&path, &keys(&["wrong"])).is_err());
+        }
+    }
+
+    #[test]
+    fn test_tampering() {
+        let dir = TestDir::new("encryption");
+        let path = dir.join("inputs.csv");
+        std::fs::write(&path, vec![b'a'; CHUNK_LEN * 3]).unwrap();
+        encrypt_file(&path, "secret").unwrap();
+        let encrypted = std::fs::read(&path).unwrap();
+
+        // Dropping the last chunk must not go unnoticed
+        std::fs::write(&path, &encrypted[..encrypted.len() - 100]).unwrap();
+        assert!(read(&path, &keys(&["secret"])).is_err());
+
+        let mut modified = encrypted.clone();
+        modified[HEADER_LEN as usize + CHUNK_LEN + TAG_LEN + 5] ^= 1;
+        std::fs::write(&path, &modified).unwrap();
+        assert!(read(&path, &keys(&["secret"])).is_err());
+    }
+
+    #[test]
+    fn test_plain_files_are_read_as_they_are() {
+        let dir = TestDir::new("encryption");
+        let path = dir.join("inputs.csv");
+        std::fs::write(&path, b"timestamp,event_type,event_args\n").unwrap();
+        assert!(!is_encrypted(&path).unwrap());
+        assert_eq!(
+            read(&path, &RecordingKeys::default()).unwrap(),
+            b"timestamp,event_type,event_args\n"
+        );
+        // Nothing is encrypted unless it's been turned on
+        assert!(!encrypt_recording(&dir, &RecordingKeys::default()).unwrap());
+        assert!(!is_encrypted(&path).unwrap());
+    }
+}
@@ -0,0 +1,181 @@
+//! Headless mode: runs the recorder and uploader without any windows, tray icon or overlay,
+//! reporting what's happening on stdout instead. Useful for scripted or unattended setups.
+//!
+//! Recording is driven by the hotkeys, exactly as in the GUI, unless `record` is set, in which case it
+//! starts as soon as a game is in the foreground (for when nobody is at the machine to press them).
+//! On Ctrl+C the current recording is stopped and everything that's ready is uploaded before exiting.
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use color_eyre::{Result, eyre};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::{
+    app_state::{AppState, AsyncRequest, UiUpdate},
+    upload::UploadState,
+};
+
+pub fn run(
+    app_state: Arc<AppState>,
+    mut ui_update_rx: mpsc::Receiver<UiUpdate>,
+    mut stopped_rx: broadcast::Receiver<()>,
+    record: bool,
+) -> Result<()> {
+    let (api_key, has_consented, start_key) = {
+        let config = app_state.config.read().unwrap();
+        (
+            config.credentials.api_key.clone(),
+            config.credentials.has_consented,
+            config.preferences.start_recording_key().to_string(),
+        )
+    };
+
+    if api_key.is_empty() {
+        eyre::bail!(
+            "No API key available. Pass one with --api-key or the OWL_CONTROL_API_KEY environment variable, \
+            or log in through the app first."
+        );
+    }
+    if !has_consented {
+        eyre::bail!(
+            "The data collection terms have not been accepted yet. Please open OWL Control normally once to review and accept them."
+        );
+    }
+
+    app_state
+        .async_request_tx
+        .blocking_send(AsyncRequest::ValidateApiKey { api_key })
+        .map_err(|_| eyre::eyre!("Recorder thread is not running"))?;
+
+    let ready_to_record = || {
+        if !record {
+            println!(
+                "Press {start_key} in-game to start or stop recording, and Ctrl+C to stop and upload."
+            );
+            return Ok(());
+        }
+        println!(
+            "Recording will start once a game is in the foreground. Press Ctrl+C to stop and upload."
+        );
+        app_state
+            .async_request_tx
+            .blocking_send(AsyncRequest::StartRecording)
+            .map_err(|_| eyre::eyre!("Recorder thread is not running"))
+    };
+
+    let mut last_printed_percent: HashMap<PathBuf, u32> = HashMap::new();
+    while let Some(update) = ui_update_rx.blocking_recv() {
+        match update {
+            UiUpdate::UpdateUserId(Ok(user_id)) => {
+                println!("Logged in as {user_id}");
+                ready_to_record()?;
+            }
+            UiUpdate::UpdateUserId(Err(e)) if e.is_transient() => {
+                eprintln!(
+                    "Couldn't check the API key ({e}), recording offline. Uploads will wait until the server can be reached."
+                );
+                ready_to_record()?;
+            }
+            UiUpdate::UpdateRemoteCaptureSettings(capture_settings) => {
+                // There's no UI to keep in sync, so they go straight into the config
+                let mut config = app_state.config.write().unwrap();
+                config
+                    .preferences
+                    .apply_remote_capture_settings(capture_settings);
+                if let Err(e) = config.save() {
+                    eprintln!("Couldn't save the capture settings from the server: {e}");
+                }
+            }
+            UiUpdate::ApiKeyRateLimited { retry_at } => {
+                println!(
+                    "The server is busy, checking the API key again in {}s...",
+                    retry_at
+                        .saturating_duration_since(std::time::Instant::now())
+                        .as_secs()
+                );
+            }
+            UiUpdate::UpdateUserId(Err(e)) => {
+                eyre::bail!("Failed to validate API key: {e}");
+            }
+            UiUpdate::UpdateTrayIconRecording(recording) => {
+                println!(
+                    "{}",
+                    if recording {
+                        "Recording started"
+                    } else {
+                        "Recording stopped"
+                    }
+                );
+            }
+            UiUpdate::UploadFinished {
+                folder_name,
+                error: None,
+            } => {
+                println!("Uploaded {folder_name}");
+            }
+            UiUpdate::UploadFinished {
+                folder_name,
+                error: Some(e),
+            } => {
+                eprintln!("Upload of {folder_name} failed: {e}");
+            }
+            UiUpdate::ForceUpdate => {
+                if stopped_rx.try_recv().is_ok() {
+                    break;
+                }
+
+                // Only print whole-percent changes so that we don't flood the terminal
+                let queue = app_state.upload_queue.read().unwrap();
+                last_printed_percent.retain(|folder_path, _| {
+                    queue
+                        .items()
+                        .iter()
+                        .any(|i| &i.folder_path == folder_path && i.state == UploadState::Uploading)
+                });
+                for item in queue.items() {
+                    let Some(progress) = &item.progress else {
+                        continue;
+                    };
+                    let percent = progress.percent as u32;
+                    if last_printed_percent.insert(item.folder_path.clone(), percent)
+                        != Some(percent)
+                    {
+                        println!(
+                            "Uploading {}: {percent}% ({:.1} MiB / {:.1} MiB, {:.2} MB/s)",
+                            item.folder_name,
+                            progress.bytes_uploaded as f64 / (1024.0 * 1024.0),
+                            progress.total_bytes as f64 / (1024.0 * 1024.0),
+                            progress.speed_mbps,
+                        );
+                    }
+                }
+            }
+            UiUpdate::UpdateNewerReleaseAvailable(release) => {
+                println!(
+                    "A newer version is available: {} ({})",
+                    release.name, release.url
+                );
+            }
+            UiUpdate::UpdateLowDiskSpace(Some(free_space_mb)) => {
+                eprintln!("Warning: disk space is running low ({free_space_mb} MB free)");
+            }
+            UiUpdate::RecordingStarted(Err(e)) => {
+                eprintln!("Failed to start recording: {e}");
+                if record {
+                    println!("Waiting for another window to be in the foreground...");
+                }
+            }
+            UiUpdate::UpdateLocalRecordings(_)
+            | UiUpdate::UpdateLowDiskSpace(None)
+            | UiUpdate::ProblemReportCreated(_)
+            | UiUpdate::PlaybackLoaded(_)
+            | UiUpdate::ConnectionTested(_)
+            | UiUpdate::BenchmarkFinished(_)
+            | UiUpdate::UploadLabelsUpdated(_)
+            | UiUpdate::RecordingStarted(Ok(()))
+            | UiUpdate::RecordingFinished(_) => {}
+        }
+    }
+
+    Ok(())
+}
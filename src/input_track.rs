@@ -0,0 +1,374 @@
+//! A compact binary encoding of the inputs file, for uploading. Most of an inputs CSV is the same
+//! timestamp digits and event names over and over; this stores the time since the previous event and
+//! a one byte event type instead, with the event's arguments packed as varints.
+//!
+//! The format, all integers little-endian:
+//!
+//! - Magic: `OWLI`
+//! - Version: `u8`, currently [`VERSION`]
+//! - Flags: `u8`. Bit 0 is set if everything after the flags is compressed with raw deflate.
+//! - Events, until the end of the file. Each one is:
+//!   - Microseconds since the previous event's timestamp (or since the Unix epoch, for the first event),
+//!     as a zigzag varint
+//!   - Event type: `u8` (see [`tag`] for the values)
+//!   - The event's args, in the same order as in the CSV. Integers are varints (zigzag for signed
+//!     ones), booleans are a `u8` that's 0 or 1, and floats are `f32`.
+//!
+//! Varints are LEB128: 7 bits at a time, least significant first, with the top bit set on every byte but
+//! the last. Timestamps decode exactly to the CSV's, as those have microsecond precision.
+
+use std::{
+    io::{Read as _, Write as _},
+    path::Path,
+    str::FromStr as _,
+};
+
+use color_eyre::eyre::{Result, WrapErr as _, bail, eyre};
+
+use crate::{
+    encryption::{self, RecordingKeys},
+    output_types::{InputEvent, InputEventType},
+};
+
+const MAGIC: &[u8; 4] = b"OWLI";
+pub const VERSION: u8 = 1;
+const FLAG_COMPRESSED: u8 = 1;
+
+/// Encodes the events. `compress` deflates everything after the header.
+pub fn encode(events: &[InputEvent], compress: bool) -> Result<Vec<u8>> {
+    let mut body = vec![];
+    let mut previous = 0;
+    for event in events {
+        let micros = (event.timestamp * 1e6).round() as i64;
+        write_signed(&mut body, micros - previous);
+        previous = micros;
+        write_event(&mut body, &event.event);
+    }
+
+    let mut out = MAGIC.to_vec();
+    out.push(VERSION);
+    if compress {
+        out.push(FLAG_COMPRESSED);
+        let mut encoder = flate2::write::DeflateEncoder::new(out, flate2::Compression::default());
+        encoder.write_all(&body)?;
+        Ok(encoder.finish()?)
+    } else {
+        out.push(0);
+        out.extend(body);
+        Ok(out)
+    }
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<InputEvent>> {
+    let Some((header, body)) = bytes.split_at_checked(MAGIC.len() + 2) else {
+        bail!("input track is too short to have a header");
+    };
+    if &header[..MAGIC.len()] != MAGIC {
+        bail!("not an input track");
+    }
+    let version = header[MAGIC.len()];
+    if version != VERSION {
+        bail!("unsupported input track version {version}");
+    }
+    let flags = header[MAGIC.len() + 1];
+
+    let decompressed;
+    let mut body = if flags & FLAG_COMPRESSED != 0 {
+        let mut buf = vec![];
+        flate2::read::DeflateDecoder::new(body).read_to_end(&mut buf)?;
+        decompressed = buf;
+        decompressed.as_slice()
+    } else {
+        body
+    };
+
+    let mut events = vec![];
+    let mut micros = 0;
+    while !body.is_empty() {
+        micros = read_signed(&mut body)?
+            .checked_add(micros)
+            .ok_or_else(|| eyre!("timestamp is out of range"))?;
+        let event = read_event(&mut body)?;
+        events.push(InputEvent::new(micros as f64 / 1e6, event));
+    }
+    Ok(events)
+}
+
+/// Reads an inputs file in either format: compact, or the CSV it was made from. It's decrypted with `keys`
+/// if it was encrypted at rest.
+pub fn read_file(path: &Path, keys: &RecordingKeys) -> Result<Vec<InputEvent>> {
+    let bytes = encryption::read(path, keys)?;
+    if bytes.starts_with(MAGIC) {
+        return decode(&bytes);
+    }
+    String::from_utf8(bytes)?
+        .lines()
+        .skip(1)
+        .map(InputEvent::from_str)
+        .collect::<Result<Vec<_>, _>>()
+        .wrap_err_with(|| eyre!("failed to parse {path:?}"))
+}
+
+/// The events as JSON, for reading through by hand: an array of `{"timestamp", "event_type", "event_args"}`
+/// objects, using the same names and args as the CSV.
+pub fn to_json(events: &[InputEvent]) -> serde_json::Value {
+    events
+        .iter()
+        .map(|event| {
+            serde_json::json!({
+                "timestamp": event.timestamp,
+                "event_type": event.event.id(),
+                "event_args": event.event.json_args(),
+            })
+        })
+        .collect()
+}
+
+/// The byte each event type is stored as
+fn tag(event: &InputEventType) -> u8 {
+    match event {
+        InputEventType::Start => 0,
+        InputEventType::End => 1,
+        InputEventType::Unfocus => 2,
+        InputEventType::Refocus => 3,
+        InputEventType::MouseMove { .. } => 4,
+        InputEventType::CursorPosition { .. } => 5,
+        InputEventType::MouseButton { .. } => 6,
+        InputEventType::Scroll { .. } => 7,
+        InputEventType::Keyboard { .. } => 8,
+        InputEventType::GamepadButton { .. } => 9,
+        InputEventType::GamepadButtonValue { .. } => 10,
+        InputEventType::GamepadAxis { .. } => 11,
+        InputEventType::GamepadConnected { .. } => 12,
+        InputEventType::GamepadDisconnected { .. } => 13,
+    }
+}
+
+fn write_event(out: &mut Vec<u8>, event: &InputEventType) {
+    out.push(tag(event));
+    match *event {
+        InputEventType::Start
+        | InputEventType::End
+        | InputEventType::Unfocus
+        | InputEventType::Refocus => {}
+        InputEventType::MouseMove { dx, dy } => {
+            write_signed(out, dx as i64);
+            write_signed(out, dy as i64);
+        }
+        InputEventType::CursorPosition {
+            screen_x,
+            screen_y,
+            window_x,
+            window_y,
+        } => {
+            for value in [screen_x, screen_y, window_x, window_y] {
+                write_signed(out, value as i64);
+            }
+        }
+        InputEventType::MouseButton { button, pressed } => {
+            write_unsigned(out, button as u64);
+            out.push(pressed as u8);
+        }
+        InputEventType::Scroll { amount } => write_signed(out, amount as i64),
+        InputEventType::Keyboard { key, pressed } => {
+            write_unsigned(out, key as u64);
+            out.push(pressed as u8);
+        }
+        InputEventType::GamepadButton {
+            button,
+            pressed,
+            gamepad,
+        } => {
+            write_unsigned(out, button as u64);
+            out.push(pressed as u8);
+            write_unsigned(out, gamepad as u64);
+        }
+        InputEventType::GamepadButtonValue {
+            button,
+            value,
+            gamepad,
+        } => {
+            write_unsigned(out, button as u64);
+            out.extend(value.to_le_bytes());
+            write_unsigned(out, gamepad as u64);
+        }
+        InputEventType::GamepadAxis {
+            axis,
+            value,
+            gamepad,
+        } => {
+            write_unsigned(out, axis as u64);
+            out.extend(value.to_le_bytes());
+            write_unsigned(out, gamepad as u64);
+        }
+        InputEventType::GamepadConnected { gamepad }
+        | InputEventType::GamepadDisconnected { gamepad } => write_unsigned(out, gamepad as u64),
+    }
+}
+
+fn read_event(input: &mut &[u8]) -> Result<InputEventType> {
+    let tag = read_u8(input)?;
+    Ok(match tag {
+        0 => InputEventType::Start,
+        1 => InputEventType::End,
+        2 => InputEventType::Unfocus,
+        3 => InputEventType::Refocus,
+        4 => InputEventType::MouseMove {
+            dx: read_signed(input)?.try_into()?,
+            dy: read_signed(input)?.try_into()?,
+        },
+        5 => InputEventType::CursorPosition {
+            screen_x: read_signed(input)?.try_into()?,
+            screen_y: read_signed(input)?.try_into()?,
+            window_x: read_signed(input)?.try_into()?,
+            window_y: read_signed(input)?.try_into()?,
+        },
+        6 => InputEventType::MouseButton {
+            button: read_unsigned(input)?.try_into()?,
+            pressed: read_bool(input)?,
+        },
+        7 => InputEventType::Scroll {
+            amount: read_signed(input)?.try_into()?,
+        },
+        8 => InputEventType::Keyboard {
+            key: read_unsigned(input)?.try_into()?,
+            pressed: read_bool(input)?,
+        },
+        9 => InputEventType::GamepadButton {
+            button: read_unsigned(input)?.try_into()?,
+            pressed: read_bool(input)?,
+            gamepad: read_unsigned(input)?.try_into()?,
+        },
+        10 => InputEventType::GamepadButtonValue {
+            button: read_unsigned(input)?.try_into()?,
+            value: read_f32(input)?,
+            gamepad: read_unsigned(input)?.try_into()?,
+        },
+        11 => InputEventType::GamepadAxis {
+            axis: read_unsigned(input)?.try_into()?,
+            value: read_f32(input)?,
+            gamepad: read_unsigned(input)?.try_into()?,
+        },
+        12 => InputEventType::GamepadConnected {
+            gamepad: read_unsigned(input)?.try_into()?,
+        },
+        13 => InputEventType::GamepadDisconnected {
+            gamepad: read_unsigned(input)?.try_into()?,
+        },
+        _ => bail!("unknown event type {tag}"),
+    })
+}
+
+fn write_unsigned(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_signed(out: &mut Vec<u8>, value: i64) {
+    write_unsigned(out, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn read_u8(input: &mut &[u8]) -> Result<u8> {
+    let (&byte, rest) = input
+        .split_first()
+        .ok_or_else(|| eyre!("input track ends partway through an event"))?;
+    *input = rest;
+    Ok(byte)
+}
+
+fn read_unsigned(input: &mut &[u8]) -> Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = read_u8(input)?;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("varint is too long")
+}
+
+fn read_signed(input: &mut &[u8]) -> Result<i64> {
+    let value = read_unsigned(input)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+fn read_bool(input: &mut &[u8]) -> Result<bool> {
+    match read_u8(input)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        byte => bail!("invalid boolean {byte}"),
+    }
+}
+
+fn read_f32(input: &mut &[u8]) -> Result<f32> {
+    let bytes = [
+        read_u8(input)?,
+        read_u8(input)?,
+        read_u8(input)?,
+        read_u8(input)?,
+    ];
+    Ok(f32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "1700000000.123456,START,\"[]\"\n\
+                       1700000000.125,MOUSE_MOVE,\"[3,-7]\"\n\
+                       1700000000.125001,CURSOR_POSITION,\"[1930,-5,10,-45]\"\n\
+                       1700000000.2,KEYBOARD,\"[65,true]\"\n\
+                       1700000000.3,GAMEPAD_AXIS,\"[1,-0.5,2]\"\n\
+                       1700000000.4,GAMEPAD_BUTTON_VALUE,\"[9,0.25,0]\"\n\
+                       1700000000.5,SCROLL,\"[-120]\"\n\
+                       1700000001,END,\"[]\"";
+
+    fn events() -> Vec<InputEvent> {
+        CSV.lines()
+            .map(|line| InputEvent::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let events = events();
+        for compress in [false, true] {
+            let encoded = encode(&events, compress).unwrap();
+            let decoded = decode(&encoded).unwrap();
+            assert_eq!(decoded, events);
+            // Timestamps have to come back exactly as they were written in the CSV
+            assert_eq!(
+                decoded.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+                CSV.lines().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_smaller_than_csv() {
+        let events = events();
+        let encoded = encode(&events, false).unwrap();
+        assert!(encoded.len() * 3 < CSV.len(), "{} bytes", encoded.len());
+    }
+
+    #[test]
+    fn test_varints() {
+        for value in [0, 1, -1, 63, -64, 64, i32::MAX as i64, i64::MIN, i64::MAX] {
+            let mut out = vec![];
+            write_signed(&mut out, value);
+            assert_eq!(read_signed(&mut out.as_slice()).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_rejects_truncated_track() {
+        let encoded = encode(&events(), false).unwrap();
+        assert!(decode(&encoded[..encoded.len() - 1]).is_err());
+        assert!(decode(b"OWL").is_err());
+        assert!(decode(b"CSV!\x01\x00").is_err());
+    }
+}
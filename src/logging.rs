@@ -0,0 +1,131 @@
+//! Log file rotation, and a copy of the most recent log lines for the in-app log viewer.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tracing_subscriber::{Layer, layer::Context};
+
+/// The log is rotated at startup once it's grown past this
+const MAX_LOG_SIZE_BYTES: u64 = 10 * 1024 * 1024;
+/// How many rotated logs (`.1` being the newest) are kept around
+const MAX_OLD_LOGS: u32 = 3;
+/// How many lines the log viewer can show
+const MAX_RECENT_LINES: usize = 2000;
+
+static RECENT_LINES: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+/// Identifies the build and machine, so that a log sent to us on its own still makes sense
+pub fn header() -> String {
+    format!(
+        "OWL Control v{} ({}) on {} ({})",
+        env!("CARGO_PKG_VERSION"),
+        git_version::git_version!(),
+        sysinfo::System::long_os_version().unwrap_or_else(|| std::env::consts::OS.to_string()),
+        std::env::consts::ARCH,
+    )
+}
+
+/// Moves the log out of the way if it's got too big, shifting the older logs along and dropping the oldest
+pub fn rotate(log_path: &Path) {
+    let too_big = std::fs::metadata(log_path).is_ok_and(|m| m.len() > MAX_LOG_SIZE_BYTES);
+    if !too_big {
+        return;
+    }
+    for i in (1..MAX_OLD_LOGS).rev() {
+        std::fs::rename(old_log_path(log_path, i), old_log_path(log_path, i + 1)).ok();
+    }
+    std::fs::rename(log_path, old_log_path(log_path, 1)).ok();
+}
+
+fn old_log_path(log_path: &Path, index: u32) -> PathBuf {
+    let mut path = log_path.as_os_str().to_owned();
+    path.push(format!(".{index}"));
+    PathBuf::from(path)
+}
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: tracing::Level,
+    /// Formatted like the log file
+    pub text: String,
+}
+
+/// The lines logged since startup (up to [`MAX_RECENT_LINES`]) that are at least as severe as `min_level`
+pub fn recent_lines(min_level: tracing::Level) -> Vec<LogLine> {
+    RECENT_LINES
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|line| line.level <= min_level)
+        .cloned()
+        .collect()
+}
+
+/// Keeps the most recent log lines in memory for the log viewer
+pub struct RecentLinesLayer;
+impl<S: tracing::Subscriber> Layer<S> for RecentLinesLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let line = LogLine {
+            level: *metadata.level(),
+            text: format!(
+                "{} {:>5} {}: {}{}",
+                chrono::Local::now().format("%H:%M:%S%.3f"),
+                metadata.level(),
+                metadata.target(),
+                visitor.message,
+                visitor.fields
+            ),
+        };
+
+        let mut lines = RECENT_LINES.lock().unwrap();
+        if lines.len() == MAX_RECENT_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+/// Formats an event's fields like the file output does: the message, then `name=value` for the rest
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: String,
+}
+impl tracing::field::Visit for FieldVisitor {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            write!(self.fields, " {}={value:?}", field.name()).ok();
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            write!(self.fields, " {}={value:?}", field.name()).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_old_log_path() {
+        assert_eq!(
+            old_log_path(Path::new("logs/owl-control-debug.log"), 2),
+            PathBuf::from("logs/owl-control-debug.log.2")
+        );
+    }
+}
@@ -7,9 +7,15 @@ mod api;
 mod app_state;
 mod assets;
 mod config;
+mod encryption;
+mod headless;
+mod input_track;
+mod logging;
 mod output_types;
 mod record;
+mod report;
 mod system;
+mod telemetry;
 mod tokio_thread;
 mod ui;
 mod upload;
@@ -26,16 +32,75 @@ use std::sync::Arc;
 
 use crate::system::ensure_single_instance::ensure_single_instance;
 
+/// How long we hold up the session ending (logging off or shutting down) while finishing the recording
+const SESSION_END_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 fn main() -> Result<()> {
     #[derive(Parser, Debug)]
     #[command(version, about)]
     struct Args {
-        #[arg(long, default_value = "./data_dump/games")]
-        recording_location: PathBuf,
+        /// Where to store recordings, overriding the setting in the app for this run
+        #[arg(long)]
+        recording_location: Option<PathBuf>,
+        /// Run without any windows, tray icon or overlay, printing progress to the terminal instead.
+        /// Recordings are uploaded when exiting with Ctrl+C.
+        #[arg(long)]
+        headless: bool,
+        /// In headless mode, start recording as soon as a game is in the foreground instead of waiting for the
+        /// start hotkey, e.g. when running over SSH with nobody at the machine
+        #[arg(long, requires = "headless")]
+        record: bool,
+        /// API key to use in headless mode instead of the stored one. Never saved to disk.
+        #[arg(
+            long,
+            env = "OWL_CONTROL_API_KEY",
+            hide_env_values = true,
+            requires = "headless"
+        )]
+        api_key: Option<String>,
+        /// Server to talk to instead of the production API, overriding the setting in the app
+        #[arg(long, env = "OWL_CONTROL_API_BASE_URL")]
+        api_base_url: Option<String>,
+        /// Allow talking to the API over plain HTTP, for development against a local server
+        #[arg(long, env = "OWL_CONTROL_ALLOW_INSECURE_API")]
+        allow_insecure_api: bool,
+        /// Print a recording's inputs file (CSV or compact) as JSON, then exit
+        #[arg(long, value_name = "PATH")]
+        export_inputs: Option<PathBuf>,
+    }
+
+    let Args {
+        recording_location,
+        headless,
+        record,
+        api_key,
+        api_base_url,
+        allow_insecure_api,
+        export_inputs,
+    } = Args::parse();
+
+    // Release builds use the windows subsystem, so there's no console to print to unless we borrow
+    // the one we were launched from.
+    #[cfg(target_os = "windows")]
+    if headless || export_inputs.is_some() {
+        use windows::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+        unsafe { AttachConsole(ATTACH_PARENT_PROCESS).ok() };
+    }
+
+    if let Some(path) = export_inputs {
+        // In case the inputs were encrypted at rest
+        let keys = encryption::RecordingKeys::load(&config::Config::load()?)?;
+        let events = input_track::read_file(&path, &keys)?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&input_track::to_json(&events))?
+        );
+        return Ok(());
     }
 
     // Set up logging, including to file
     let log_path = config::get_persistent_dir()?.join("owl-control-debug.log");
+    logging::rotate(&log_path);
     let log_file = std::fs::OpenOptions::new()
         .create(true)
         .append(true)
@@ -64,23 +129,28 @@ fn main() -> Result<()> {
             tracing_subscriber::fmt::layer()
                 .with_writer(log_file)
                 .with_ansi(false)
-                .with_filter(env_filter),
+                .with_filter(env_filter.clone()),
         )
+        .with(logging::RecentLinesLayer.with_filter(env_filter))
         .init();
 
-    tracing::info!(
-        "OWL Control v{} ({})",
-        env!("CARGO_PKG_VERSION"),
-        git_version::git_version!()
-    );
-
-    let Args { recording_location } = Args::parse();
+    tracing::info!("{}", logging::header());
 
     color_eyre::install()?;
 
     // Ensure only one instance is running
     ensure_single_instance()?;
 
+    let previous_run = system::crash_sentinel::check_and_write();
+    if let Some(run) = &previous_run {
+        tracing::warn!(
+            version = run.version,
+            started_at = %run.started_at,
+            recording_game = ?run.recording_game,
+            "The previous run didn't exit cleanly"
+        );
+    }
+
     let wgpu_instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
     let adapter_infos = wgpu_instance
         .enumerate_adapters(wgpu::Backends::DX12)
@@ -95,10 +165,24 @@ fn main() -> Result<()> {
         async_request_tx,
         ui_update_tx,
         adapter_infos,
+        recording_location,
+        api_base_url,
+        allow_insecure_api,
     ));
+    *app_state.unclean_exit.write().unwrap() =
+        previous_run.map(|previous_run| app_state::UncleanExit {
+            previous_run,
+            recovered_recordings: vec![],
+        });
+    if let Some(api_key) = api_key {
+        // Only kept in memory; the UI is the only thing that saves the config
+        app_state.config.write().unwrap().credentials.api_key = api::sanitize_api_key(&api_key);
+    }
 
     // launch tokio (which hosts the recorder) on seperate thread
     let (stopped_tx, stopped_rx) = tokio::sync::broadcast::channel(1);
+    // Signalled once the tokio thread has finished, and so any recording has been finalised
+    let (tokio_finished_tx, tokio_finished_rx) = std::sync::mpsc::channel::<()>();
     let tokio_thread = std::thread::spawn({
         let app_state = app_state.clone();
         let stopped_tx = stopped_tx.clone();
@@ -106,15 +190,16 @@ fn main() -> Result<()> {
         move || {
             let result = tokio_thread::run(
                 app_state.clone(),
-                recording_location,
                 log_path,
                 async_request_rx,
                 stopped_rx,
+                headless,
             );
 
             if let Err(e) = result {
                 tracing::error!("Error in tokio thread: {e}");
             }
+            tokio_finished_tx.send(()).ok();
 
             // note: this is usually the ctrl+c shut down path, but its a known bug that if the app is minimized to tray,
             // killing it via ctrl+c will not kill the app immediately, the MainApp will not receive the stop signal until
@@ -134,6 +219,27 @@ fn main() -> Result<()> {
         }
     });
 
+    // Logging off or shutting down skips the usual way of closing, so stop everything ourselves
+    system::session_end::listen({
+        let stopped_tx = stopped_tx.clone();
+        move || {
+            stopped_tx.send(()).ok();
+            // Windows doesn't wait around forever, but we may as well use whatever time it gives us
+            if tokio_finished_rx.recv_timeout(SESSION_END_TIMEOUT).is_err() {
+                tracing::warn!("Recorder didn't finish before the session ended");
+            }
+        }
+    });
+
+    if headless {
+        let result = headless::run(app_state, ui_update_rx, stopped_rx, record);
+        // If we bailed out early, make sure the tokio thread winds down too
+        stopped_tx.send(()).ok();
+        tokio_thread.join().unwrap();
+        system::crash_sentinel::clear();
+        return result;
+    }
+
     ui::start(
         wgpu_instance,
         app_state,
@@ -144,6 +250,7 @@ fn main() -> Result<()> {
     tracing::info!("UI thread shut down, joining tokio thread");
     tokio_thread.join().unwrap();
     tracing::info!("Tokio thread joined, shutting down");
+    system::crash_sentinel::clear();
 
     Ok(())
 }
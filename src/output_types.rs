@@ -1,23 +1,91 @@
+use constants::encoding::{VideoCodec, VideoEncoderType};
 use serde::{Deserialize, Serialize};
 
 use crate::{system::hardware_specs, upload::validation::InputStats};
 
+/// Version of the [`Metadata`] format written by this build. Bump this whenever the meaning
+/// of existing fields changes, so that downstream consumers can tell recordings apart.
+pub const METADATA_SCHEMA_VERSION: u32 = 1;
+
+/// Written next to each recording as `metadata.json`
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Metadata {
+    /// Missing on recordings made before the format was versioned
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub schema_version: Option<u32>,
     pub game_exe: String,
+    /// What the game is called, as worked out from `game_exe` (or failing that, the window title) when
+    /// the recording started. Unlike the labels, this is never changed by the user.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub game_name: Option<String>,
     // Whenever adding new fields to this, ensure you use an `Option` to ensure
     // that the uploader will not fail to upload older recordings.
+    /// Title of the game window when the recording started
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub window_title: Option<String>,
+    /// What the user said the recording is of, if they labelled it before uploading
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub labels: Option<SessionLabels>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub game_resolution: Option<(u32, u32)>,
+    /// Frame rate the video was captured at. Older recordings were always captured at [`constants::FPS`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fps: Option<u32>,
+    /// Rate mouse movement was sampled at, independently of `fps`. Missing if every movement was written
+    /// as it arrived, as on older recordings. Each entry keeps its own timestamp either way.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub input_rate_hz: Option<u32>,
+    /// Resolution of the monitor the game window was on
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub monitor_resolution: Option<(u32, u32)>,
+    /// Resolution of the video file. The game is scaled to this regardless of its own resolution.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub output_resolution: Option<(u32, u32)>,
+    /// How much the game was scaled horizontally and vertically to fit `output_resolution`. If only
+    /// `capture_region` was recorded, this is the scaling of that rather than the whole game.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub scale_factor: Option<(f32, f32)>,
+    /// The part of the game that was recorded, if it wasn't all of it. Relative to the top left of the
+    /// game's `game_resolution` frame, which is also what input cursor positions are relative to.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub capture_region: Option<RegionBounds>,
+    /// The encoder that was configured. The recorder may have fallen back to another one;
+    /// see `recorder_extra` for what was actually used.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub video_encoder: Option<VideoEncoderType>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub video_codec: Option<VideoCodec>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub video_bitrate_kbps: Option<i64>,
+    /// Seconds between keyframes. The video also starts on one, so every segment can be decoded on its own.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub keyframe_interval_seconds: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub owl_control_version: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub owl_control_commit: Option<String>,
+    /// The user that was logged in when the recording was made
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub user_id: Option<String>,
+    /// The version of the consent form the user had accepted when the recording was made
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub consent_version: Option<u32>,
     pub session_id: String,
+    /// Where this recording sits in a longer session. Missing on recordings made before sessions were split into segments.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub segment: Option<SegmentInfo>,
+    /// Frames dropped over the course of the recording, if the recorder could tell
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub skipped_frames: Option<SkippedFrames>,
     pub hardware_id: String,
     pub hardware_specs: Option<hardware_specs::HardwareSpecs>,
     pub start_timestamp: u64,
     pub end_timestamp: u64,
+    /// Unix time in seconds at which the session's input clock started. The inputs' timestamps count
+    /// from this on a monotonic clock; see [`InputEventType`]. Missing on older recordings,
+    /// whose timestamps were read off the system clock.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub input_clock_origin: Option<f64>,
     pub duration: f32,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub input_stats: Option<InputStats>,
@@ -25,6 +93,111 @@ pub struct Metadata {
     pub recorder: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub recorder_extra: Option<serde_json::Value>,
+    /// Set if OWL Control exited without finishing the recording, and it was recovered on the next launch
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub interrupted: Option<bool>,
+}
+
+/// A rectangle within the game's frame, in its pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionBounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// How many of a recording's frames were skipped because the encoder couldn't keep up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFrames {
+    pub skipped: usize,
+    pub total: usize,
+}
+impl SkippedFrames {
+    /// 0-100%
+    pub fn percentage(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.skipped as f64 / self.total as f64) * 100.0
+        }
+    }
+}
+
+/// Ties together the segments that a long session was split into. Input timestamps are continuous
+/// across the segments of a session, including the time spent paused before each one being left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentInfo {
+    /// Shared by every segment of the session
+    pub recording_session_id: String,
+    /// Position of this segment in the session, starting from 0
+    pub index: u32,
+    /// Seconds of footage in the session before this segment
+    pub offset: f32,
+    /// Set if this segment was started because the game's resolution changed, which happened `offset`
+    /// seconds into the session
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub resolution_change: Option<ResolutionChange>,
+}
+
+/// The game changing resolution mid-session, e.g. by going fullscreen. A video's frames all have to come
+/// from the same resolution, so the segment is ended there and a new one started at the new resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResolutionChange {
+    pub from: (u32, u32),
+    pub to: (u32, u32),
+    /// When the change was noticed, on the same clock as the inputs' timestamps
+    pub timestamp: f64,
+}
+
+/// Describes what's in a recording, for sorting the dataset by more than the game's executable
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionLabels {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub game_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub genre: Option<String>,
+    /// Free-form
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    /// Anything else worth knowing about the recording, in the user's own words
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub notes: Option<String>,
+}
+impl SessionLabels {
+    pub fn is_empty(&self) -> bool {
+        self.game_title.is_none()
+            && self.genre.is_none()
+            && self.tags.is_empty()
+            && self.notes.is_none()
+    }
+
+    /// Reads the labels back from the tags an upload was sent with; the reverse of [`Self::upload_tags`].
+    /// Notes aren't sent as tags, so they can't be read back.
+    pub fn from_upload_tags(upload_tags: &[String]) -> Self {
+        let mut labels = Self::default();
+        for tag in upload_tags {
+            if let Some(game_title) = tag.strip_prefix("game:") {
+                labels.game_title = Some(game_title.to_string());
+            } else if let Some(genre) = tag.strip_prefix("genre:") {
+                labels.genre = Some(genre.to_string());
+            } else {
+                labels.tags.push(tag.clone());
+            }
+        }
+        labels
+    }
+
+    /// The labels as sent with the upload. The game title and genre are prefixed, so that they can be told
+    /// apart from the free-form tags.
+    pub fn upload_tags(&self) -> Vec<String> {
+        self.game_title
+            .iter()
+            .map(|title| format!("game:{title}"))
+            .chain(self.genre.iter().map(|genre| format!("genre:{genre}")))
+            .chain(self.tags.iter().cloned())
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -73,9 +246,24 @@ impl std::fmt::Display for InputEventReadError {
 /// Quick Rundown on Event Datasets:
 ///
 /// When stored as CSVs, each row has:
-/// - timestamp [unix time]
+/// - timestamp [unix time, seconds, microsecond precision]
 /// - event type (see events.py) [str]
 /// - event_args (see callback args) [list[any]]
+///
+/// Inputs aren't tied to video frames. Mouse movement may be sampled at the metadata's `input_rate_hz`,
+/// in which case each MOUSE_MOVE is the sum of the movement since the previous one, and is timestamped
+/// with the latest movement that went into it; everything else is written as it happens.
+///
+/// The track is trimmed to match the video. START is written once the video has started, and END at the
+/// end of the last whole frame, so that the time between them (less pauses) is the metadata's `duration`.
+/// Anything timestamped after END fell in a frame that never made it into the video. Presses and releases
+/// of the hotkeys are left out entirely, as they control the recording rather than the game; e.g. the stop
+/// key never shows up as the last input.
+///
+/// Timestamps aren't read off the system clock for each event: the session's start is read once (the
+/// metadata's `input_clock_origin`) and each timestamp is that plus the time since on a monotonic clock,
+/// minus any time spent paused. So they can be compared with each other exactly, even if the system
+/// clock was adjusted during the session, but can differ slightly from the wall clock at the time.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InputEventType {
     /// Start
@@ -88,18 +276,42 @@ pub enum InputEventType {
     Refocus,
     /// MOUSE_MOVE: [dx : int, dy : int]
     MouseMove { dx: i32, dy: i32 },
+    /// CURSOR_POSITION: [screen_x : int, screen_y : int, window_x : int, window_y : int]
+    ///
+    /// Where the cursor moved to, on the screen and relative to the top left of the game window's
+    /// client area (which is what's recorded; multiply by the metadata's `scale_factor` to get the
+    /// position in the video). The window position can be negative or past the window's size if the
+    /// cursor is outside of it.
+    CursorPosition {
+        screen_x: i32,
+        screen_y: i32,
+        window_x: i32,
+        window_y: i32,
+    },
     /// MOUSE_BUTTON: [button_idx : int, key_down : bool]
     MouseButton { button: u16, pressed: bool },
     /// SCROLL: [amt : int] (positive = up)
     Scroll { amount: i16 },
     /// KEYBOARD: [keycode : int, key_down : bool] (key down = true, key up = false)
     Keyboard { key: u16, pressed: bool },
-    /// GAMEPAD_BUTTON: [button_idx : int, key_down : bool]
-    GamepadButton { button: u16, pressed: bool },
-    /// GAMEPAD_BUTTON_VALUE: [button_idx : int, value : float]
-    GamepadButtonValue { button: u16, value: f32 },
-    /// GAMEPAD_AXIS: [axis_idx : int, value : float]
-    GamepadAxis { axis: u16, value: f32 },
+    /// GAMEPAD_BUTTON: [button_idx : int, key_down : bool, gamepad_idx : int]
+    GamepadButton {
+        button: u16,
+        pressed: bool,
+        gamepad: u16,
+    },
+    /// GAMEPAD_BUTTON_VALUE: [button_idx : int, value : float, gamepad_idx : int]
+    GamepadButtonValue {
+        button: u16,
+        value: f32,
+        gamepad: u16,
+    },
+    /// GAMEPAD_AXIS: [axis_idx : int, value : float, gamepad_idx : int]
+    GamepadAxis { axis: u16, value: f32, gamepad: u16 },
+    /// GAMEPAD_CONNECTED: [gamepad_idx : int]
+    GamepadConnected { gamepad: u16 },
+    /// GAMEPAD_DISCONNECTED: [gamepad_idx : int]
+    GamepadDisconnected { gamepad: u16 },
 }
 impl InputEventType {
     pub fn id(&self) -> &'static str {
@@ -109,12 +321,15 @@ impl InputEventType {
             InputEventType::Unfocus => "UNFOCUS",
             InputEventType::Refocus => "REFOCUS",
             InputEventType::MouseMove { .. } => "MOUSE_MOVE",
+            InputEventType::CursorPosition { .. } => "CURSOR_POSITION",
             InputEventType::MouseButton { .. } => "MOUSE_BUTTON",
             InputEventType::Scroll { .. } => "SCROLL",
             InputEventType::Keyboard { .. } => "KEYBOARD",
             InputEventType::GamepadButton { .. } => "GAMEPAD_BUTTON",
             InputEventType::GamepadButtonValue { .. } => "GAMEPAD_BUTTON_VALUE",
             InputEventType::GamepadAxis { .. } => "GAMEPAD_AXIS",
+            InputEventType::GamepadConnected { .. } => "GAMEPAD_CONNECTED",
+            InputEventType::GamepadDisconnected { .. } => "GAMEPAD_DISCONNECTED",
         }
     }
 
@@ -126,19 +341,50 @@ impl InputEventType {
             InputEventType::Unfocus => json!([]),
             InputEventType::Refocus => json!([]),
             InputEventType::MouseMove { dx, dy } => json!([dx, dy]),
+            InputEventType::CursorPosition {
+                screen_x,
+                screen_y,
+                window_x,
+                window_y,
+            } => json!([screen_x, screen_y, window_x, window_y]),
             InputEventType::MouseButton { button, pressed } => json!([button, pressed]),
             InputEventType::Scroll { amount } => json!([amount]),
             InputEventType::Keyboard { key, pressed } => json!([key, pressed]),
-            InputEventType::GamepadButton { button, pressed } => json!([button, pressed]),
-            InputEventType::GamepadButtonValue { button, value } => json!([button, value]),
-            InputEventType::GamepadAxis { axis, value } => json!([axis, value]),
+            InputEventType::GamepadButton {
+                button,
+                pressed,
+                gamepad,
+            } => json!([button, pressed, gamepad]),
+            InputEventType::GamepadButtonValue {
+                button,
+                value,
+                gamepad,
+            } => json!([button, value, gamepad]),
+            InputEventType::GamepadAxis {
+                axis,
+                value,
+                gamepad,
+            } => json!([axis, value, gamepad]),
+            InputEventType::GamepadConnected { gamepad } => json!([gamepad]),
+            InputEventType::GamepadDisconnected { gamepad } => json!([gamepad]),
         }
     }
 
-    pub fn from_input_event(event: input_capture::Event) -> Result<Self, InputEventReadError> {
+    /// `window_origin` is where the top left of the game window's client area is on the screen,
+    /// for working out where the cursor is relative to it
+    pub fn from_input_event(
+        event: input_capture::Event,
+        window_origin: (i32, i32),
+    ) -> Result<Self, InputEventReadError> {
         use input_capture::{Event, PressState};
         match event {
             Event::MouseMove([x, y]) => Ok(InputEventType::MouseMove { dx: x, dy: y }),
+            Event::CursorMove([x, y]) => Ok(InputEventType::CursorPosition {
+                screen_x: x,
+                screen_y: y,
+                window_x: x - window_origin.0,
+                window_y: y - window_origin.1,
+            }),
             Event::MousePress { key, press_state } => Ok(InputEventType::MouseButton {
                 button: key,
                 pressed: press_state == PressState::Pressed,
@@ -150,15 +396,36 @@ impl InputEventType {
                 key,
                 pressed: press_state == PressState::Pressed,
             }),
-            Event::GamepadButtonPress { key, press_state } => Ok(InputEventType::GamepadButton {
+            Event::GamepadButtonPress {
+                gamepad,
+                key,
+                press_state,
+            } => Ok(InputEventType::GamepadButton {
                 button: key,
                 pressed: press_state == PressState::Pressed,
+                gamepad,
             }),
-            Event::GamepadButtonChange { key, value } => {
-                Ok(InputEventType::GamepadButtonValue { button: key, value })
-            }
-            Event::GamepadAxisChange { axis, value } => {
-                Ok(InputEventType::GamepadAxis { axis, value })
+            Event::GamepadButtonChange {
+                gamepad,
+                key,
+                value,
+            } => Ok(InputEventType::GamepadButtonValue {
+                button: key,
+                value,
+                gamepad,
+            }),
+            Event::GamepadAxisChange {
+                gamepad,
+                axis,
+                value,
+            } => Ok(InputEventType::GamepadAxis {
+                axis,
+                value,
+                gamepad,
+            }),
+            Event::GamepadConnected { gamepad } => Ok(InputEventType::GamepadConnected { gamepad }),
+            Event::GamepadDisconnected { gamepad } => {
+                Ok(InputEventType::GamepadDisconnected { gamepad })
             }
         }
     }
@@ -180,6 +447,21 @@ impl InputEventType {
             })
         }
 
+        /// Gamepad events gained a trailing gamepad index. Older recordings don't have it,
+        /// and were only ever able to tell gamepads apart by chance, so we treat them as gamepad 0.
+        fn split_gamepad_arg(
+            id: &str,
+            json_args: serde_json::Value,
+        ) -> Result<(serde_json::Value, u16), InputEventReadError> {
+            match json_args {
+                serde_json::Value::Array(mut args) if args.len() == 3 => {
+                    let gamepad = parse_args_tuple(id, args.pop().unwrap())?;
+                    Ok((serde_json::Value::Array(args), gamepad))
+                }
+                json_args => Ok((json_args, 0)),
+            }
+        }
+
         match id {
             "START" => Ok(InputEventType::Start),
             "END" => Ok(InputEventType::End),
@@ -192,6 +474,15 @@ impl InputEventType {
                     dy: args.1,
                 })
             }
+            "CURSOR_POSITION" => {
+                let args: (i32, i32, i32, i32) = parse_args_tuple(id, json_args)?;
+                Ok(InputEventType::CursorPosition {
+                    screen_x: args.0,
+                    screen_y: args.1,
+                    window_x: args.2,
+                    window_y: args.3,
+                })
+            }
             "MOUSE_BUTTON" => {
                 let args: (u16, bool) = parse_args_tuple(id, json_args)?;
                 Ok(InputEventType::MouseButton {
@@ -211,26 +502,40 @@ impl InputEventType {
                 })
             }
             "GAMEPAD_BUTTON" => {
+                let (json_args, gamepad) = split_gamepad_arg(id, json_args)?;
                 let args: (u16, bool) = parse_args_tuple(id, json_args)?;
                 Ok(InputEventType::GamepadButton {
                     button: args.0,
                     pressed: args.1,
+                    gamepad,
                 })
             }
             "GAMEPAD_BUTTON_VALUE" => {
+                let (json_args, gamepad) = split_gamepad_arg(id, json_args)?;
                 let args: (u16, f32) = parse_args_tuple(id, json_args)?;
                 Ok(InputEventType::GamepadButtonValue {
                     button: args.0,
                     value: args.1,
+                    gamepad,
                 })
             }
             "GAMEPAD_AXIS" => {
+                let (json_args, gamepad) = split_gamepad_arg(id, json_args)?;
                 let args: (u16, f32) = parse_args_tuple(id, json_args)?;
                 Ok(InputEventType::GamepadAxis {
                     axis: args.0,
                     value: args.1,
+                    gamepad,
                 })
             }
+            "GAMEPAD_CONNECTED" => {
+                let args: (u16,) = parse_args_tuple(id, json_args)?;
+                Ok(InputEventType::GamepadConnected { gamepad: args.0 })
+            }
+            "GAMEPAD_DISCONNECTED" => {
+                let args: (u16,) = parse_args_tuple(id, json_args)?;
+                Ok(InputEventType::GamepadDisconnected { gamepad: args.0 })
+            }
             _ => Err(InputEventReadError::InvalidEvent { id: id.to_string() }),
         }
     }
@@ -245,16 +550,6 @@ impl InputEvent {
     pub fn new(timestamp: f64, event: InputEventType) -> Self {
         Self { timestamp, event }
     }
-
-    pub fn new_at_now(event: InputEventType) -> Self {
-        Self::new(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs_f64(),
-            event,
-        )
-    }
 }
 impl std::fmt::Display for InputEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -315,3 +610,111 @@ impl std::str::FromStr for InputEvent {
         Ok(InputEvent::new(timestamp, event_type))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use super::*;
+
+    #[test]
+    fn test_gamepad_event_roundtrip() {
+        let event = InputEvent::new(
+            1.5,
+            InputEventType::GamepadAxis {
+                axis: 1,
+                value: 0.5,
+                gamepad: 2,
+            },
+        );
+        assert_eq!(InputEvent::from_str(&event.to_string()).unwrap(), event);
+    }
+
+    #[test]
+    fn test_cursor_position_is_relative_to_window() {
+        let event = InputEventType::from_input_event(
+            input_capture::Event::CursorMove([1930, -5]),
+            (1920, 40),
+        )
+        .unwrap();
+        assert_eq!(
+            event,
+            InputEventType::CursorPosition {
+                screen_x: 1930,
+                screen_y: -5,
+                window_x: 10,
+                window_y: -45,
+            }
+        );
+        let event = InputEvent::new(1.5, event);
+        assert_eq!(InputEvent::from_str(&event.to_string()).unwrap(), event);
+    }
+
+    #[test]
+    fn test_upload_tags() {
+        let labels = SessionLabels {
+            game_title: Some("Minecraft".to_string()),
+            genre: None,
+            tags: vec!["survival".to_string(), "night".to_string()],
+            notes: Some("Mostly building".to_string()),
+        };
+        assert_eq!(
+            labels.upload_tags(),
+            ["game:Minecraft", "survival", "night"]
+        );
+        assert!(SessionLabels::default().upload_tags().is_empty());
+
+        // Everything but the notes comes back
+        assert_eq!(
+            SessionLabels::from_upload_tags(&labels.upload_tags()),
+            SessionLabels {
+                notes: None,
+                ..labels
+            }
+        );
+    }
+
+    #[test]
+    fn test_segment_info_resolution_change() {
+        // Only segments started by a resolution change mention one
+        let segment: SegmentInfo =
+            serde_json::from_str(r#"{"recording_session_id":"a","index":0,"offset":0.0}"#).unwrap();
+        assert_eq!(segment.resolution_change, None);
+        assert!(
+            !serde_json::to_string(&segment)
+                .unwrap()
+                .contains("resolution_change")
+        );
+
+        let change = ResolutionChange {
+            from: (1280, 720),
+            to: (1920, 1080),
+            timestamp: 1700000000.5,
+        };
+        let segment = SegmentInfo {
+            resolution_change: Some(change),
+            ..segment
+        };
+        let json = serde_json::to_string(&segment).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SegmentInfo>(&json)
+                .unwrap()
+                .resolution_change,
+            Some(change)
+        );
+    }
+
+    #[test]
+    fn test_legacy_gamepad_event_defaults_to_first_gamepad() {
+        assert_eq!(
+            InputEvent::from_str("1.5,GAMEPAD_BUTTON,\"[4,true]\"")
+                .unwrap()
+                .event,
+            InputEventType::GamepadButton {
+                button: 4,
+                pressed: true,
+                gamepad: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Works out the most demanding settings this machine can record at without dropping frames. The game is
+//! recorded for a few seconds at a time at each combination of settings, best first, until one keeps up.
+//! Recordings are always scaled down to [`constants::RECORDING_WIDTH`]x[`constants::RECORDING_HEIGHT`], so only
+//! the frame rate, encoder and codec are tried.
+
+use std::{collections::VecDeque, time::Duration};
+
+use constants::{
+    FPS_OPTIONS, SKIPPED_FRAMES_WARNING_PERCENTAGE,
+    encoding::{VideoCodec, VideoEncoderType},
+};
+
+use crate::output_types::SkippedFrames;
+
+/// How long the game is recorded for at each setting
+pub const BENCHMARK_RUN_LENGTH: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchmarkSettings {
+    pub fps: u32,
+    pub encoder: VideoEncoderType,
+    pub codec: VideoCodec,
+}
+
+/// How recording at `settings` went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchmarkRun {
+    pub settings: BenchmarkSettings,
+    /// `None` if the recorder couldn't tell, which counts as not keeping up
+    pub skipped_frames: Option<SkippedFrames>,
+}
+impl BenchmarkRun {
+    /// Whether the encoder kept up, dropping no more frames than would have been warned about while recording
+    pub fn is_sustainable(&self) -> bool {
+        self.skipped_frames.is_some_and(|skipped_frames| {
+            skipped_frames.total > 0
+                && skipped_frames.percentage() <= SKIPPED_FRAMES_WARNING_PERCENTAGE
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BenchmarkReport {
+    /// In the order they were tried, which is best first
+    pub runs: Vec<BenchmarkRun>,
+}
+impl BenchmarkReport {
+    /// The best settings that kept up, if any did
+    pub fn recommended(&self) -> Option<BenchmarkSettings> {
+        self.runs
+            .iter()
+            .find(|run| run.is_sustainable())
+            .map(|run| run.settings)
+    }
+}
+
+/// A benchmark in progress
+#[derive(Debug)]
+pub struct Benchmark {
+    remaining: VecDeque<BenchmarkSettings>,
+    report: BenchmarkReport,
+}
+impl Benchmark {
+    pub fn new(available_encoders: &[VideoEncoderType]) -> Self {
+        Self {
+            remaining: candidates(available_encoders).into(),
+            report: BenchmarkReport::default(),
+        }
+    }
+
+    /// The settings to record at next, or `None` once the benchmark is done
+    pub fn next_settings(&self) -> Option<BenchmarkSettings> {
+        self.remaining.front().copied()
+    }
+
+    /// Most runs the benchmark can take, counting those already done
+    pub fn max_runs(&self) -> usize {
+        self.report.runs.len() + self.remaining.len()
+    }
+
+    /// Records how the run at [`Self::next_settings`] went. Once one keeps up there's no need to try the
+    /// rest, as they'd only be worse.
+    pub fn run_finished(&mut self, skipped_frames: Option<SkippedFrames>) {
+        let Some(settings) = self.remaining.pop_front() else {
+            return;
+        };
+        let run = BenchmarkRun {
+            settings,
+            skipped_frames,
+        };
+        if run.is_sustainable() {
+            self.remaining.clear();
+        }
+        self.report.runs.push(run);
+    }
+
+    pub fn report(&self) -> BenchmarkReport {
+        self.report.clone()
+    }
+}
+
+/// Every combination of settings worth trying, best first: the highest frame rate, then hardware encoders
+/// (which leave the CPU to the game), then H.264, which is what recordings default to
+fn candidates(available_encoders: &[VideoEncoderType]) -> Vec<BenchmarkSettings> {
+    let mut encoders = available_encoders.to_vec();
+    encoders.sort_by_key(|encoder| encoder.gpu_vendor_id().is_none());
+
+    let mut fps_options = FPS_OPTIONS.to_vec();
+    fps_options.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut candidates = vec![];
+    for fps in fps_options {
+        for &encoder in &encoders {
+            for codec in VideoCodec::ALL {
+                if encoder.supports_codec(codec) {
+                    candidates.push(BenchmarkSettings {
+                        fps,
+                        encoder,
+                        codec,
+                    });
+                }
+            }
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skipped(skipped: usize) -> Option<SkippedFrames> {
+        Some(SkippedFrames {
+            skipped,
+            total: 600,
+        })
+    }
+
+    #[test]
+    fn test_candidates_are_best_first() {
+        let candidates = candidates(&[VideoEncoderType::X264, VideoEncoderType::NvEnc]);
+        let settings = |fps, encoder, codec| BenchmarkSettings {
+            fps,
+            encoder,
+            codec,
+        };
+        assert_eq!(
+            &candidates[..4],
+            &[
+                settings(60, VideoEncoderType::NvEnc, VideoCodec::H264),
+                settings(60, VideoEncoderType::NvEnc, VideoCodec::H265),
+                settings(60, VideoEncoderType::X264, VideoCodec::H264),
+                settings(30, VideoEncoderType::NvEnc, VideoCodec::H264),
+            ]
+        );
+        assert_eq!(candidates.len(), 3 * FPS_OPTIONS.len());
+    }
+
+    #[test]
+    fn test_stops_at_first_sustainable_run() {
+        let mut benchmark = Benchmark::new(&[VideoEncoderType::X264]);
+        assert_eq!(benchmark.max_runs(), FPS_OPTIONS.len());
+
+        // 60 FPS drops too many, 30 FPS keeps up
+        benchmark.run_finished(skipped(60));
+        assert_eq!(benchmark.next_settings().map(|s| s.fps), Some(30));
+        benchmark.run_finished(skipped(1));
+        assert_eq!(benchmark.next_settings(), None);
+
+        let report = benchmark.report();
+        assert_eq!(report.runs.len(), 2);
+        assert_eq!(report.recommended().map(|s| s.fps), Some(30));
+    }
+
+    #[test]
+    fn test_nothing_recommended_if_nothing_keeps_up() {
+        let mut benchmark = Benchmark::new(&[VideoEncoderType::X264]);
+        benchmark.run_finished(skipped(60));
+        // Couldn't be measured
+        benchmark.run_finished(None);
+        benchmark.run_finished(skipped(600));
+        assert_eq!(benchmark.next_settings(), None);
+        assert_eq!(benchmark.report().recommended(), None);
+    }
+}
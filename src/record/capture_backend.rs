@@ -0,0 +1,338 @@
+//! The part of recording that captures the game's video and audio. Everything else (inputs, metadata,
+//! segmenting, pausing) is handled by [`super::Recorder`] the same way whatever captures the video.
+//!
+//! To add another way of capturing, implement [`CaptureBackend`] and either add a [`RecordingBackend`]
+//! for it to [`create`], or hand it to [`super::Recorder::with_capture_backend`] directly. The OBS
+//! recorders are the built-in backends, with embedded OBS the default.
+
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use color_eyre::Result;
+use egui_wgpu::wgpu::{self, DeviceType};
+use windows::Win32::Foundation::HWND;
+
+use crate::{
+    config::{EncoderSettings, RecordingBackend},
+    output_types::SkippedFrames,
+    record::{
+        capture_region::Crop, obs_embedded_recorder::ObsEmbeddedRecorder,
+        obs_socket_recorder::ObsSocketRecorder,
+    },
+};
+
+/// Size of the preview frames, a quarter of the recording's resolution each way
+pub const PREVIEW_WIDTH: u32 = constants::RECORDING_WIDTH / 4;
+pub const PREVIEW_HEIGHT: u32 = constants::RECORDING_HEIGHT / 4;
+/// How often the preview is updated while recording
+pub const PREVIEW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A small copy of a recently recorded frame, to show the user what's being captured
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewFrame {
+    pub width: u32,
+    pub height: u32,
+    /// Row by row, 4 bytes per pixel
+    pub rgba: Vec<u8>,
+    pub captured_at: Instant,
+}
+impl PreviewFrame {
+    /// Whether there's nothing to see, which usually means the game isn't being captured at all
+    /// (e.g. it's running as administrator, or is on another GPU)
+    pub fn is_blank(&self) -> bool {
+        const MAX_BLANK_VALUE: u8 = 8;
+        self.rgba
+            .chunks_exact(4)
+            .all(|pixel| pixel[..3].iter().all(|&value| value <= MAX_BLANK_VALUE))
+    }
+}
+
+/// Captures the game into a video file. Backends encode what they capture themselves, so frames are only
+/// handed back through [`CaptureBackend::next_frame`] for the preview.
+#[async_trait::async_trait(?Send)]
+pub trait CaptureBackend {
+    /// Recorded in each recording's metadata, to tell which backend recorded it
+    fn id(&self) -> &'static str;
+
+    async fn start(
+        &mut self,
+        dummy_video_path: &Path,
+        pid: u32,
+        hwnd: HWND,
+        game_exe: &str,
+        video_settings: EncoderSettings,
+        fps: u32,
+        game_resolution: (u32, u32),
+        /// How much to cut off each side of the game, if only part of it is being recorded.
+        /// `game_resolution` is the size before cropping.
+        crop: Option<Crop>,
+        /// Output device to record desktop audio from, if desktop audio is enabled
+        desktop_audio_device: Option<&str>,
+    ) -> Result<()>;
+    /// Result contains any additional metadata the recorder wants to return about the recording
+    /// If this returns an error, the recording will be invalidated with the error message
+    async fn stop(&mut self) -> Result<serde_json::Value>;
+    /// Pauses or resumes the current recording without starting a new output file
+    async fn set_paused(&mut self, paused: bool) -> Result<()>;
+    /// How many frames the current recording has dropped so far, if the recorder can tell
+    async fn skipped_frames(&mut self) -> Result<Option<SkippedFrames>>;
+    /// The latest frame of the current recording, at [`PREVIEW_WIDTH`]x[`PREVIEW_HEIGHT`], if the backend
+    /// can provide one. Backends only start keeping frames around once this is first asked for.
+    async fn next_frame(&mut self) -> Result<Option<PreviewFrame>>;
+    /// Whether anything has been captured from the game during the current recording, if the recorder can tell.
+    /// `false` means the recording is only getting black frames.
+    async fn is_capturing(&mut self) -> Result<Option<bool>>;
+}
+
+/// Stand-ins for a game and what captures it, for exercising the rest of the recording pipeline in tests
+#[cfg(test)]
+pub(crate) mod mock {
+    use windows::{
+        Win32::UI::WindowsAndMessaging::{
+            CreateWindowExW, DestroyWindow, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOZORDER, SetWindowPos,
+            WINDOW_EX_STYLE, WS_POPUP,
+        },
+        core::w,
+    };
+
+    use super::*;
+
+    /// A recorder that doesn't capture anything. It writes a placeholder video file, and makes up
+    /// preview frames while recording.
+    #[derive(Debug, Default)]
+    pub(crate) struct MockCaptureBackend {
+        /// The video file of the current recording, while recording
+        pub video_path: Option<std::path::PathBuf>,
+        pub paused: bool,
+        pub recordings_started: u32,
+        /// Preview frames handed out over every recording so far
+        pub frames: u32,
+        /// Returned from [`CaptureBackend::skipped_frames`]
+        pub skipped_frames: Option<SkippedFrames>,
+        /// Returned from [`CaptureBackend::is_capturing`]
+        pub capturing: Option<bool>,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl CaptureBackend for MockCaptureBackend {
+        fn id(&self) -> &'static str {
+            "Mock"
+        }
+
+        async fn start(
+            &mut self,
+            dummy_video_path: &Path,
+            _pid: u32,
+            _hwnd: HWND,
+            _game_exe: &str,
+            _video_settings: EncoderSettings,
+            _fps: u32,
+            _game_resolution: (u32, u32),
+            _crop: Option<Crop>,
+            _desktop_audio_device: Option<&str>,
+        ) -> Result<()> {
+            color_eyre::eyre::ensure!(self.video_path.is_none(), "already recording");
+            // Enough for anything that only checks the video is there
+            std::fs::write(dummy_video_path, b"not really a video")?;
+            self.video_path = Some(dummy_video_path.to_path_buf());
+            self.paused = false;
+            self.recordings_started += 1;
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> Result<serde_json::Value> {
+            let video_path = self
+                .video_path
+                .take()
+                .ok_or_else(|| color_eyre::eyre::eyre!("not recording"))?;
+            Ok(serde_json::json!({ "mock_video_path": video_path }))
+        }
+
+        async fn set_paused(&mut self, paused: bool) -> Result<()> {
+            color_eyre::eyre::ensure!(self.video_path.is_some(), "not recording");
+            self.paused = paused;
+            Ok(())
+        }
+
+        async fn skipped_frames(&mut self) -> Result<Option<SkippedFrames>> {
+            Ok(self.skipped_frames)
+        }
+
+        async fn next_frame(&mut self) -> Result<Option<PreviewFrame>> {
+            if self.video_path.is_none() {
+                return Ok(None);
+            }
+            self.frames += 1;
+            Ok(Some(synthetic_frame(self.frames)))
+        }
+
+        async fn is_capturing(&mut self) -> Result<Option<bool>> {
+            Ok(self.capturing)
+        }
+    }
+
+    /// A gradient that shifts with every frame, so that consecutive frames differ and none are blank
+    fn synthetic_frame(index: u32) -> PreviewFrame {
+        let rgba = (0..PREVIEW_HEIGHT)
+            .flat_map(|y| {
+                (0..PREVIEW_WIDTH).flat_map(move |x| {
+                    [
+                        (x * 255 / PREVIEW_WIDTH) as u8,
+                        (y * 255 / PREVIEW_HEIGHT) as u8,
+                        (index % 256) as u8,
+                        255,
+                    ]
+                })
+            })
+            .collect();
+        PreviewFrame {
+            width: PREVIEW_WIDTH,
+            height: PREVIEW_HEIGHT,
+            rgba,
+            captured_at: Instant::now(),
+        }
+    }
+
+    /// A window of our own for tests to record in place of a game's. It's never shown.
+    pub(crate) struct TestWindow(pub HWND);
+    impl TestWindow {
+        /// `width` and `height` are of the inside of the window, which is what a game is recorded at
+        pub(crate) fn new(width: u32, height: u32) -> Self {
+            // SAFETY: a borderless window of a built-in class, destroyed again on drop
+            let hwnd = unsafe {
+                CreateWindowExW(
+                    WINDOW_EX_STYLE(0),
+                    w!("STATIC"),
+                    w!("OWL Control test game"),
+                    WS_POPUP,
+                    0,
+                    0,
+                    width as i32,
+                    height as i32,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+            }
+            .expect("failed to create test window");
+            Self(hwnd)
+        }
+
+        /// As if the game had changed resolution
+        pub(crate) fn resize(&self, width: u32, height: u32) {
+            // SAFETY: the window is ours, and still around until drop
+            unsafe {
+                SetWindowPos(
+                    self.0,
+                    None,
+                    0,
+                    0,
+                    width as i32,
+                    height as i32,
+                    SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE,
+                )
+            }
+            .expect("failed to resize test window");
+        }
+    }
+    impl Drop for TestWindow {
+        fn drop(&mut self) {
+            // SAFETY: as above
+            unsafe { DestroyWindow(self.0) }.ok();
+        }
+    }
+}
+
+/// Sets up the capture backend for `backend`
+pub async fn create(
+    backend: RecordingBackend,
+    adapter_infos: &[wgpu::AdapterInfo],
+) -> Result<Box<dyn CaptureBackend>> {
+    Ok(match backend {
+        RecordingBackend::Embedded => {
+            // Incredibly ugly hack: assume that the first dGPU is the one we want,
+            // and that this list agrees with OBS's. There's no real guarantee that
+            // this is the case, and that the target game is even running on the dGPU,
+            // but it's a first-pass solution for now.
+            //
+            // TODO: Investigate what OBS actually does here. I spent over an hour
+            // pouring through the OBS source code and couldn't find anything of
+            // note with regards to how it chooses the adapter; I might have to
+            // reach out to an OBS developer if this becomes an issue again.
+            let adapter_index = adapter_infos
+                .iter()
+                .position(|a| a.device_type == DeviceType::DiscreteGpu)
+                .unwrap_or_default();
+            tracing::info!(
+                "Initializing recorder with adapter index {adapter_index} ({:?})",
+                adapter_infos.get(adapter_index)
+            );
+            Box::new(ObsEmbeddedRecorder::new(adapter_index).await?)
+        }
+        RecordingBackend::Socket => Box::new(ObsSocketRecorder::new().await?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mock::MockCaptureBackend, *};
+    use crate::util::TestDir;
+
+    #[tokio::test]
+    async fn test_mock_capture_backend() {
+        let dir = TestDir::new("mock");
+        let video_path = dir.join("recording.mp4");
+        let mut recorder = MockCaptureBackend::default();
+
+        assert!(recorder.set_paused(true).await.is_err());
+        recorder
+            .start(
+                &video_path,
+                0,
+                HWND::default(),
+                "game.exe",
+                EncoderSettings::default(),
+                60,
+                (1920, 1080),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(video_path.is_file());
+        assert_eq!(recorder.recordings_started, 1);
+        recorder.set_paused(true).await.unwrap();
+        assert!(recorder.paused);
+        assert_eq!(recorder.skipped_frames().await.unwrap(), None);
+
+        let first = recorder.next_frame().await.unwrap().unwrap();
+        let second = recorder.next_frame().await.unwrap().unwrap();
+        assert_eq!((first.width, first.height), (PREVIEW_WIDTH, PREVIEW_HEIGHT));
+        assert_eq!(
+            first.rgba.len(),
+            (PREVIEW_WIDTH * PREVIEW_HEIGHT * 4) as usize
+        );
+        assert!(!first.is_blank());
+        assert_ne!(first.rgba, second.rgba);
+
+        assert!(recorder.stop().await.is_ok());
+        assert!(recorder.stop().await.is_err());
+        assert_eq!(recorder.next_frame().await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_preview_frame_is_blank() {
+        let mut frame = PreviewFrame {
+            width: 2,
+            height: 1,
+            rgba: vec![0, 0, 0, 255, 3, 5, 2, 255],
+            captured_at: Instant::now(),
+        };
+        assert!(frame.is_blank());
+        frame.rgba[4] = 120;
+        assert!(!frame.is_blank());
+    }
+}
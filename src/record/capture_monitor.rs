@@ -0,0 +1,96 @@
+//! Recording only games on one monitor, for setups with a game on one screen and chat or a browser on
+//! another. The monitor is picked by its device name, and only looked up when a recording starts, as
+//! monitors can be plugged in or out while the app is running.
+
+use color_eyre::{Result, eyre::bail};
+
+use crate::system::hardware_specs::MonitorInfo;
+
+/// The monitor games are recorded on, as found when a recording starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureMonitor<'a> {
+    pub monitor: &'a MonitorInfo,
+    /// Whether the chosen monitor wasn't connected, and the primary one is being used instead
+    pub fell_back: bool,
+}
+
+/// Finds the monitor named `name` in `monitors`, or the primary monitor if it isn't connected any more.
+/// `None` if there aren't any monitors at all.
+pub fn resolve<'a>(name: &str, monitors: &'a [MonitorInfo]) -> Option<CaptureMonitor<'a>> {
+    if let Some(monitor) = monitors.iter().find(|m| m.name == name) {
+        return Some(CaptureMonitor {
+            monitor,
+            fell_back: false,
+        });
+    }
+    monitors
+        .iter()
+        .find(|m| m.primary)
+        .or(monitors.first())
+        .map(|monitor| CaptureMonitor {
+            monitor,
+            fell_back: true,
+        })
+}
+
+/// Checks that the game, on `game_monitor`, is on the monitor being recorded
+pub fn check_game_on_monitor(
+    game_exe: &str,
+    game_monitor: Option<&str>,
+    capture_monitor: &MonitorInfo,
+) -> Result<()> {
+    if game_monitor != Some(capture_monitor.name.as_str()) {
+        bail!(
+            "{game_exe} isn't on {}, the monitor games are recorded on. Move it there, or change the capture monitor in the settings.",
+            capture_monitor.name
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::hardware_specs::ScreenRect;
+
+    fn monitor(name: &str, x: i32, primary: bool) -> MonitorInfo {
+        MonitorInfo {
+            name: name.to_string(),
+            rect: ScreenRect {
+                x,
+                y: 0,
+                width: 1920,
+                height: 1080,
+            },
+            primary,
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let monitors = [
+            monitor("DISPLAY1", 0, true),
+            monitor("DISPLAY2", 1920, false),
+        ];
+
+        let chosen = resolve("DISPLAY2", &monitors).unwrap();
+        assert_eq!(chosen.monitor.name, "DISPLAY2");
+        assert!(!chosen.fell_back);
+
+        // Unplugged since it was picked
+        let fallback = resolve("DISPLAY3", &monitors).unwrap();
+        assert_eq!(fallback.monitor.name, "DISPLAY1");
+        assert!(fallback.fell_back);
+
+        assert_eq!(resolve("DISPLAY1", &[]), None);
+    }
+
+    #[test]
+    fn test_check_game_on_monitor() {
+        let monitor = monitor("DISPLAY2", 1920, false);
+        assert!(check_game_on_monitor("game.exe", Some("DISPLAY2"), &monitor).is_ok());
+        assert!(check_game_on_monitor("game.exe", Some("DISPLAY1"), &monitor).is_err());
+        // The game's window has gone, or is somewhere we can't tell
+        assert!(check_game_on_monitor("game.exe", None, &monitor).is_err());
+    }
+}
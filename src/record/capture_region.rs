@@ -0,0 +1,217 @@
+//! Recording part of the screen instead of the whole game window. The region is picked on a monitor, and
+//! only turned into a crop of the game window when a recording starts, as that's when we know where the
+//! game is and what the monitor's resolution is.
+
+use color_eyre::{
+    Result,
+    eyre::{OptionExt as _, bail},
+};
+
+use crate::{
+    config::CaptureRegion,
+    system::hardware_specs::{MonitorInfo, ScreenRect},
+};
+
+/// Regions smaller than this on either side are more likely a slip of the mouse than something worth recording
+pub const MIN_REGION_SIZE: u32 = 64;
+
+/// How many pixels are cut off each side of the game window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Crop {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+impl Crop {
+    /// The size of a `(width, height)` frame once cropped
+    pub fn apply(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        (
+            width.saturating_sub(self.left + self.right),
+            height.saturating_sub(self.top + self.bottom),
+        )
+    }
+}
+
+/// Where `region` is on the desktop now. It's scaled to match if the monitor's resolution has changed since
+/// it was picked, and cut down to what's still on the monitor.
+pub fn region_on_screen(region: &CaptureRegion, monitors: &[MonitorInfo]) -> Result<ScreenRect> {
+    let monitor = monitors
+        .iter()
+        .find(|m| m.name == region.monitor)
+        .ok_or_eyre("The capture region's monitor isn't connected")?;
+
+    let (old_width, old_height) = region.monitor_resolution;
+    let scale = |value: i64, new: u32, old: u32| {
+        if old == 0 {
+            value
+        } else {
+            value * new as i64 / old as i64
+        }
+    };
+    let scaled = ScreenRect {
+        x: monitor.rect.x + scale(region.x as i64, monitor.rect.width, old_width) as i32,
+        y: monitor.rect.y + scale(region.y as i64, monitor.rect.height, old_height) as i32,
+        width: scale(region.width as i64, monitor.rect.width, old_width) as u32,
+        height: scale(region.height as i64, monitor.rect.height, old_height) as u32,
+    };
+    scaled
+        .intersect(&monitor.rect)
+        .ok_or_eyre("The capture region is off the edge of its monitor")
+}
+
+/// The crop that turns the game window's client area (`game`, on the desktop) into `region`
+pub fn crop_for_region(region: ScreenRect, game: ScreenRect) -> Result<Crop> {
+    let Some(visible) = region.intersect(&game) else {
+        bail!("The game window isn't inside the capture region");
+    };
+    if visible.width < MIN_REGION_SIZE || visible.height < MIN_REGION_SIZE {
+        bail!(
+            "Only {}x{} of the game window is inside the capture region",
+            visible.width,
+            visible.height
+        );
+    }
+    Ok(Crop {
+        left: (visible.x - game.x) as u32,
+        top: (visible.y - game.y) as u32,
+        right: (game.right() - visible.right()) as u32,
+        bottom: (game.bottom() - visible.bottom()) as u32,
+    })
+}
+
+/// Moves each edge of `rect` onto the nearest parallel edge of one of `windows`, if there's one within
+/// `distance` pixels
+pub fn snap_to_edges(rect: ScreenRect, windows: &[ScreenRect], distance: i32) -> ScreenRect {
+    let snap = |value: i32, edges: &mut dyn Iterator<Item = i32>| {
+        edges
+            .filter(|edge| (edge - value).abs() <= distance)
+            .min_by_key(|edge| (edge - value).abs())
+            .unwrap_or(value)
+    };
+    let vertical_edges = || windows.iter().flat_map(|w| [w.x, w.right()]);
+    let horizontal_edges = || windows.iter().flat_map(|w| [w.y, w.bottom()]);
+
+    let left = snap(rect.x, &mut vertical_edges());
+    let top = snap(rect.y, &mut horizontal_edges());
+    let right = snap(rect.right(), &mut vertical_edges());
+    let bottom = snap(rect.bottom(), &mut horizontal_edges());
+    // Both edges can snap onto the same one if the rectangle's thin enough, which would leave nothing
+    if right <= left || bottom <= top {
+        return rect;
+    }
+    ScreenRect {
+        x: left,
+        y: top,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> ScreenRect {
+        ScreenRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn region(x: i32, y: i32, width: u32, height: u32) -> CaptureRegion {
+        CaptureRegion {
+            monitor: "DISPLAY2".to_string(),
+            monitor_resolution: (1920, 1080),
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn monitors(width: u32, height: u32) -> Vec<MonitorInfo> {
+        vec![
+            MonitorInfo {
+                name: "DISPLAY1".to_string(),
+                rect: rect(0, 0, 1920, 1080),
+                primary: true,
+            },
+            MonitorInfo {
+                name: "DISPLAY2".to_string(),
+                rect: rect(-(width as i32), 0, width, height),
+                primary: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_region_on_screen() {
+        assert_eq!(
+            region_on_screen(&region(100, 50, 800, 600), &monitors(1920, 1080)).unwrap(),
+            rect(-1820, 50, 800, 600)
+        );
+        // The monitor's resolution went up since the region was picked
+        assert_eq!(
+            region_on_screen(&region(100, 50, 800, 600), &monitors(3840, 2160)).unwrap(),
+            rect(-3640, 100, 1600, 1200)
+        );
+        // Hangs off the edge
+        assert_eq!(
+            region_on_screen(&region(1600, 900, 800, 600), &monitors(1920, 1080)).unwrap(),
+            rect(-320, 900, 320, 180)
+        );
+        assert!(region_on_screen(&region(2000, 0, 800, 600), &monitors(1920, 1080)).is_err());
+        assert!(region_on_screen(&region(0, 0, 800, 600), &monitors(1920, 1080)[..1]).is_err());
+    }
+
+    #[test]
+    fn test_crop_for_region() {
+        let game = rect(100, 100, 1280, 720);
+        assert_eq!(
+            crop_for_region(rect(110, 140, 1000, 600), game).unwrap(),
+            Crop {
+                left: 10,
+                top: 40,
+                right: 270,
+                bottom: 80,
+            }
+        );
+        assert_eq!(
+            crop_for_region(rect(0, 0, 1920, 1080), game).unwrap(),
+            Crop::default()
+        );
+        assert!(crop_for_region(rect(1500, 0, 400, 400), game).is_err());
+        assert!(crop_for_region(rect(1350, 100, 400, 400), game).is_err());
+        assert_eq!(
+            Crop {
+                left: 10,
+                top: 40,
+                right: 270,
+                bottom: 80,
+            }
+            .apply((1280, 720)),
+            (1000, 600)
+        );
+    }
+
+    #[test]
+    fn test_snap_to_edges() {
+        let windows = [rect(100, 100, 800, 600)];
+        assert_eq!(
+            snap_to_edges(rect(95, 104, 810, 590), &windows, 8),
+            rect(100, 100, 800, 600)
+        );
+        assert_eq!(
+            snap_to_edges(rect(50, 50, 300, 300), &windows, 8),
+            rect(50, 50, 300, 300)
+        );
+        // Both sides would land on the same edge
+        assert_eq!(
+            snap_to_edges(rect(96, 300, 6, 100), &windows, 8),
+            rect(96, 300, 6, 100)
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::{
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use color_eyre::{
     Result,
@@ -8,55 +11,439 @@ use tokio::{fs::File, io::AsyncWriteExt as _};
 
 use crate::output_types::{InputEvent, InputEventType};
 
+/// The clock input timestamps are read from. It's tied to the wall clock once, when the session starts,
+/// and counts on the monotonic clock from then on, so that the system clock being adjusted partway through
+/// (by NTP, or the user) can't shift the inputs relative to each other or to the video.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct InputClock {
+    /// Unix time in seconds at `origin`
+    origin_unix: f64,
+    origin: Instant,
+}
+impl InputClock {
+    pub(crate) fn start() -> Self {
+        Self {
+            origin_unix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64(),
+            origin: Instant::now(),
+        }
+    }
+
+    /// Unix time in seconds at which the clock started
+    pub(crate) fn origin_unix(&self) -> f64 {
+        self.origin_unix
+    }
+
+    /// The clock's current time, in Unix seconds with microsecond precision
+    pub(crate) fn now(&self) -> f64 {
+        self.at(self.origin.elapsed())
+    }
+
+    fn at(&self, elapsed: Duration) -> f64 {
+        let micros = (self.origin_unix * 1e6).round() + elapsed.as_micros() as f64;
+        micros / 1e6
+    }
+}
+
+/// Brings mouse movement down to the configured sample rate. Movement is summed, and only the cursor's
+/// latest position kept, until a sample interval has passed since they were last written. Each entry is
+/// timestamped with the latest movement that went into it, so the track can be resampled afterwards.
+#[derive(Debug)]
+struct MotionSampler {
+    /// `None` if every movement is written as it arrives
+    interval: Option<Duration>,
+    last_sample: Option<Instant>,
+    movement: Option<InputEvent>,
+    cursor: Option<InputEvent>,
+}
+impl MotionSampler {
+    fn new(rate_hz: Option<u32>) -> Self {
+        Self {
+            interval: rate_hz
+                .filter(|&rate| rate > 0)
+                .map(|rate| Duration::from_secs(1) / rate),
+            last_sample: None,
+            movement: None,
+            cursor: None,
+        }
+    }
+
+    /// Holds on to the event if it's mouse movement that's being sampled, otherwise hands it back
+    fn add(&mut self, event: InputEvent) -> Option<InputEvent> {
+        if self.interval.is_none() {
+            return Some(event);
+        }
+        match event.event {
+            InputEventType::MouseMove { dx, dy } => {
+                let (pending_dx, pending_dy) = match self.movement {
+                    Some(InputEvent {
+                        event: InputEventType::MouseMove { dx, dy },
+                        ..
+                    }) => (dx, dy),
+                    _ => (0, 0),
+                };
+                self.movement = Some(InputEvent::new(
+                    event.timestamp,
+                    InputEventType::MouseMove {
+                        dx: pending_dx.saturating_add(dx),
+                        dy: pending_dy.saturating_add(dy),
+                    },
+                ));
+                None
+            }
+            InputEventType::CursorPosition { .. } => {
+                self.cursor = Some(event);
+                None
+            }
+            _ => Some(event),
+        }
+    }
+
+    /// Whether enough time has passed since the last sample for the held movement to be written
+    fn is_due(&self, now: Instant) -> bool {
+        match (self.interval, self.last_sample) {
+            (Some(interval), Some(last_sample)) => now.duration_since(last_sample) >= interval,
+            _ => true,
+        }
+    }
+
+    /// The held movement, followed by the cursor position it led to
+    fn take(&mut self, now: Instant) -> impl Iterator<Item = InputEvent> + use<> {
+        if self.movement.is_some() || self.cursor.is_some() {
+            self.last_sample = Some(now);
+        }
+        [self.movement.take(), self.cursor.take()]
+            .into_iter()
+            .flatten()
+    }
+}
+
+/// `elapsed` cut down to a whole number of frames at `fps`, which is how much of it makes it into the video
+pub(crate) fn trim_to_whole_frames(elapsed: Duration, fps: u32) -> Duration {
+    if fps == 0 {
+        return elapsed;
+    }
+    let frames = (elapsed.as_secs_f64() * fps as f64).floor();
+    Duration::from_secs_f64(frames / fps as f64)
+}
+
 pub(crate) struct InputRecorder {
     file: File,
+    clock: InputClock,
+    motion: MotionSampler,
+    /// Frame rate of the video, which the end of the input track is lined up with
+    fps: u32,
+    /// Keys that control the recording (i.e. the hotkeys), which are left out as they aren't part of the game
+    control_keys: Vec<u16>,
+    /// Timestamp of the START entry
+    start_timestamp: f64,
+    /// When the current pause started, if we're paused
+    paused_since: Option<Instant>,
+    /// Total time spent paused in previous pauses. Subtracted from every timestamp
+    /// so that the inputs line up with the video, which doesn't include the paused time.
+    paused_duration: Duration,
+    /// Inputs written so far, not counting the entries that mark the start, end and focus changes
+    inputs_written: u64,
 }
 
 impl InputRecorder {
-    pub(crate) async fn start(path: &Path) -> Result<Self> {
+    /// `paused_duration` is the time already spent paused in earlier segments of the session,
+    /// so that timestamps carry on from where the previous segment left off. `clock` is the session's.
+    /// `input_rate_hz` is how often mouse movement is sampled; `None` to write every movement.
+    /// Presses and releases of `control_keys` aren't written.
+    pub(crate) async fn start(
+        path: &Path,
+        clock: InputClock,
+        paused_duration: Duration,
+        input_rate_hz: Option<u32>,
+        fps: u32,
+        control_keys: Vec<u16>,
+    ) -> Result<Self> {
         let file = File::create_new(path)
             .await
             .wrap_err_with(|| eyre!("failed to create and open {path:?}"))?;
-        let mut recorder = Self { file };
+        let mut recorder = Self {
+            file,
+            clock,
+            motion: MotionSampler::new(input_rate_hz),
+            fps,
+            control_keys,
+            start_timestamp: 0.0,
+            paused_since: None,
+            paused_duration,
+            inputs_written: 0,
+        };
 
         recorder.write_header().await?;
+        recorder.start_timestamp = recorder.timestamp();
         recorder
-            .write_entry(InputEvent::new_at_now(InputEventType::Start))
+            .write_event(InputEvent::new(
+                recorder.start_timestamp,
+                InputEventType::Start,
+            ))
             .await?;
 
         Ok(recorder)
     }
 
-    pub(crate) async fn seen_input(&mut self, e: input_capture::Event) -> Result<()> {
-        self.write_entry(InputEvent::new_at_now(InputEventType::from_input_event(e)?))
-            .await
+    /// `window_origin` is where the game window's client area is on the screen; see [`InputEventType::from_input_event`]
+    pub(crate) async fn seen_input(
+        &mut self,
+        e: input_capture::Event,
+        window_origin: (i32, i32),
+    ) -> Result<()> {
+        if self.is_paused() || self.is_control_input(&e) {
+            return Ok(());
+        }
+        let event = InputEvent::new(
+            self.timestamp(),
+            InputEventType::from_input_event(e, window_origin)?,
+        );
+        let now = Instant::now();
+        match self.motion.add(event) {
+            Some(event) => {
+                // Anything held back happened before this, so it has to be written first
+                self.write_motion(now).await?;
+                self.write_event(event).await
+            }
+            None if self.motion.is_due(now) => self.write_motion(now).await,
+            None => Ok(()),
+        }
     }
 
-    pub(crate) async fn stop(mut self) -> Result<()> {
-        self.write_entry(InputEvent::new_at_now(InputEventType::End))
-            .await
+    /// Returns how many inputs were recorded
+    pub(crate) async fn stop(mut self) -> Result<u64> {
+        // The end of the recording is where the video ends, i.e. where the pause started
+        self.resume();
+        self.write_motion(Instant::now()).await?;
+        // The video only has whole frames, so the end is put where the last whole frame ends
+        let elapsed = Duration::from_secs_f64((self.timestamp() - self.start_timestamp).max(0.0));
+        let end = self.start_timestamp + trim_to_whole_frames(elapsed, self.fps).as_secs_f64();
+        self.write_event(InputEvent::new(end, InputEventType::End))
+            .await?;
+        Ok(self.inputs_written)
+    }
+
+    /// Whether `e` is the press or release of a key that controls the recording
+    fn is_control_input(&self, e: &input_capture::Event) -> bool {
+        matches!(e, input_capture::Event::KeyPress { key, .. } if self.control_keys.contains(key))
     }
 
     pub(crate) async fn write_focus(&mut self, focused: bool) -> Result<()> {
+        if self.is_paused() {
+            return Ok(());
+        }
         // write alt tab status to the input tracker
-        self.write_entry(InputEvent::new_at_now(match focused {
+        self.write_entry(match focused {
             true => InputEventType::Refocus,
             false => InputEventType::Unfocus,
-        }))
+        })
         .await
     }
 
+    pub(crate) fn pause(&mut self) {
+        if self.paused_since.is_none() {
+            self.paused_since = Some(Instant::now());
+        }
+    }
+
+    pub(crate) fn resume(&mut self) {
+        if let Some(paused_since) = self.paused_since.take() {
+            self.paused_duration += paused_since.elapsed();
+        }
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused_since.is_some()
+    }
+
+    /// Total time spent paused, including the current pause
+    pub(crate) fn paused_duration(&self) -> Duration {
+        self.paused_duration + self.paused_since.map_or(Duration::ZERO, |p| p.elapsed())
+    }
+
     async fn write_header(&mut self) -> Result<()> {
         const HEADER: &str = "timestamp,event_type,event_args\n";
         self.file.write_all(HEADER.as_bytes()).await?;
         Ok(())
     }
 
-    async fn write_entry(&mut self, event: InputEvent) -> Result<()> {
+    /// The current time on the session's clock, less the time spent paused
+    fn timestamp(&self) -> f64 {
+        self.clock.now() - self.paused_duration.as_secs_f64()
+    }
+
+    async fn write_motion(&mut self, now: Instant) -> Result<()> {
+        for event in self.motion.take(now) {
+            self.write_event(event).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_entry(&mut self, event: InputEventType) -> Result<()> {
+        self.write_motion(Instant::now()).await?;
+        self.write_event(InputEvent::new(self.timestamp(), event))
+            .await
+    }
+
+    async fn write_event(&mut self, event: InputEvent) -> Result<()> {
         let line = format!("{}\n", event);
         self.file
             .write_all(line.as_bytes())
             .await
-            .wrap_err("failed to save entry to inputs file")
+            .wrap_err("failed to save entry to inputs file")?;
+        if !matches!(
+            event.event,
+            InputEventType::Start
+                | InputEventType::End
+                | InputEventType::Unfocus
+                | InputEventType::Refocus
+        ) {
+            self.inputs_written += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TestDir;
+
+    #[test]
+    fn test_input_clock_counts_from_origin() {
+        let clock = InputClock {
+            origin_unix: 1_700_000_000.123_456_7,
+            origin: Instant::now(),
+        };
+        assert_eq!(clock.at(Duration::ZERO), 1_700_000_000.123_457);
+        assert_eq!(
+            clock.at(Duration::from_micros(2_500_001)),
+            1_700_000_002.623_458
+        );
+    }
+
+    #[test]
+    fn test_motion_sampler_sums_movement_until_due() {
+        let mut sampler = MotionSampler::new(Some(100));
+        let start = Instant::now();
+        let cursor = |timestamp, x| {
+            InputEvent::new(
+                timestamp,
+                InputEventType::CursorPosition {
+                    screen_x: x,
+                    screen_y: 0,
+                    window_x: x,
+                    window_y: 0,
+                },
+            )
+        };
+
+        assert!(sampler.is_due(start));
+        sampler.add(InputEvent::new(
+            1.0,
+            InputEventType::MouseMove { dx: 1, dy: 2 },
+        ));
+        assert_eq!(sampler.take(start).count(), 1);
+
+        assert_eq!(
+            sampler.add(InputEvent::new(
+                1.001,
+                InputEventType::MouseMove { dx: 3, dy: -1 }
+            )),
+            None
+        );
+        assert_eq!(sampler.add(cursor(1.001, 10)), None);
+        sampler.add(InputEvent::new(
+            1.002,
+            InputEventType::MouseMove { dx: 4, dy: 0 },
+        ));
+        sampler.add(cursor(1.002, 14));
+        assert!(!sampler.is_due(start + Duration::from_millis(5)));
+        assert!(sampler.is_due(start + Duration::from_millis(10)));
+        assert_eq!(
+            sampler
+                .take(start + Duration::from_millis(10))
+                .collect::<Vec<_>>(),
+            [
+                InputEvent::new(1.002, InputEventType::MouseMove { dx: 7, dy: -1 }),
+                cursor(1.002, 14),
+            ]
+        );
+
+        // Other inputs aren't held back
+        let key = InputEvent::new(
+            1.003,
+            InputEventType::Keyboard {
+                key: 65,
+                pressed: true,
+            },
+        );
+        assert_eq!(sampler.add(key), Some(key));
+    }
+
+    #[test]
+    fn test_trim_to_whole_frames() {
+        assert_eq!(
+            trim_to_whole_frames(Duration::from_millis(1010), 30),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            trim_to_whole_frames(Duration::from_millis(1040), 30),
+            Duration::from_secs_f64(31.0 / 30.0)
+        );
+        assert_eq!(
+            trim_to_whole_frames(Duration::from_millis(10), 60),
+            Duration::ZERO
+        );
+        assert_eq!(
+            trim_to_whole_frames(Duration::from_millis(1010), 0),
+            Duration::from_millis(1010)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_control_keys_are_left_out() {
+        let dir = TestDir::new("inputs");
+        let path = dir.join("inputs.csv");
+        let mut recorder = InputRecorder::start(
+            &path,
+            InputClock::start(),
+            Duration::ZERO,
+            None,
+            60,
+            vec![116],
+        )
+        .await
+        .unwrap();
+        for key in [65, 116] {
+            for press_state in [
+                input_capture::PressState::Pressed,
+                input_capture::PressState::Released,
+            ] {
+                recorder
+                    .seen_input(input_capture::Event::KeyPress { key, press_state }, (0, 0))
+                    .await
+                    .unwrap();
+            }
+        }
+        recorder.stop().await.unwrap();
+
+        let inputs = std::fs::read_to_string(&path).unwrap();
+        let event_types = inputs
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(1).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(event_types, ["START", "KEYBOARD", "KEYBOARD", "END"]);
+    }
+
+    #[test]
+    fn test_motion_sampler_passes_everything_through_without_a_rate() {
+        let mut sampler = MotionSampler::new(None);
+        let event = InputEvent::new(1.0, InputEventType::MouseMove { dx: 1, dy: 2 });
+        assert_eq!(sampler.add(event), Some(event));
     }
 }
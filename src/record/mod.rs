@@ -1,7 +1,36 @@
+mod benchmark;
+mod capture_backend;
+mod capture_monitor;
+mod capture_region;
 mod input_recorder;
+mod naming;
 mod obs_embedded_recorder;
 mod obs_socket_recorder;
+mod playback;
 mod recorder;
 mod recording;
+mod recovery;
+mod replay;
+mod summary;
+mod video_decoder;
 
-pub use recorder::Recorder;
+pub use benchmark::{
+    BENCHMARK_RUN_LENGTH, Benchmark, BenchmarkReport, BenchmarkRun, BenchmarkSettings,
+};
+#[cfg(test)]
+pub(crate) use capture_backend::mock::TestWindow;
+pub use capture_backend::{PREVIEW_INTERVAL, PreviewFrame};
+pub use capture_region::{MIN_REGION_SIZE, region_on_screen, snap_to_edges};
+pub use naming::{
+    DEFAULT_RECORDING_NAME_TEMPLATE, RECORDING_NAME_TOKENS, RecordingName,
+    check_recording_name_template, new_recording_folder, recording_folder_name,
+};
+pub use playback::{Button, InputState, Playback};
+pub use recorder::{
+    Recorder, check_recording_location_writable, estimated_recording_size_mb, get_free_space_in_mb,
+    recording_location_drive_warning,
+};
+pub use recovery::recover_interrupted_recordings;
+pub use replay::REPLAY_SEGMENT_LENGTH;
+pub use summary::RecordingSummary;
+pub use video_decoder::{VideoDecoder, VideoFrame};
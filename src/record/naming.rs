@@ -0,0 +1,194 @@
+//! Recordings are each kept in a folder of their own, named after a template the user can change so that
+//! they can find their way around the recording folder outside of OWL Control. Only the folder is named;
+//! the files inside keep the names that uploading expects.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+
+/// Names folders after the time the recording started, in seconds since the Unix epoch, as they always were
+pub const DEFAULT_RECORDING_NAME_TEMPLATE: &str = "{timestamp}";
+
+/// Each token, and what it's replaced with
+pub const RECORDING_NAME_TOKENS: &[(&str, &str)] = &[
+    ("{timestamp}", "seconds since 1970, e.g. 1760524800"),
+    ("{date}", "the date, e.g. 2025-10-15"),
+    ("{time}", "the time, e.g. 14-30-05"),
+    ("{game}", "the game's name"),
+    (
+        "{index}",
+        "which part of the session it is, from 1; long sessions are split into parts",
+    ),
+];
+
+/// Longest folder name that's made, leaving room for the rest of the path within Windows' limit
+const MAX_NAME_LEN: usize = 100;
+
+/// Characters that Windows doesn't allow in file names
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// What a recording's folder can be named after
+pub struct RecordingName<'a> {
+    pub started_at: DateTime<Local>,
+    pub game: &'a str,
+    /// From 0, as in [`crate::output_types::SegmentInfo::index`]
+    pub segment_index: u32,
+}
+
+/// Checks that `template` makes folder names that Windows allows, and that it only uses tokens we know of
+pub fn check_recording_name_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("The name can't be empty.".to_string());
+    }
+
+    let mut rest = template;
+    let mut literal = String::new();
+    while let Some(start) = rest.find('{') {
+        literal += &rest[..start];
+        let Some(end) = rest[start..].find('}') else {
+            return Err("A token isn't closed; tokens look like {date}.".to_string());
+        };
+        let token = &rest[start..start + end + 1];
+        if !RECORDING_NAME_TOKENS.iter().any(|(t, _)| *t == token) {
+            return Err(format!("{token} isn't a token that can be used."));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    literal += rest;
+
+    if literal.contains('}') {
+        return Err("There's a } without a {.".to_string());
+    }
+    if let Some(c) = literal
+        .chars()
+        .find(|c| ILLEGAL_CHARS.contains(c) || c.is_control())
+    {
+        return Err(format!("Folder names can't contain {c:?}."));
+    }
+    if template.ends_with(['.', ' ']) {
+        return Err("Folder names can't end with a dot or a space.".to_string());
+    }
+    if template.starts_with('.') {
+        // That's how OWL Control's own folders are named, and recordings named like it can't be imported
+        return Err("Folder names can't start with a dot.".to_string());
+    }
+    Ok(())
+}
+
+/// The folder name that `template` gives a recording. Falls back to the default template if `template`
+/// isn't valid, e.g. after the config was edited by hand.
+pub fn recording_folder_name(template: &str, name: &RecordingName) -> String {
+    let template = if check_recording_name_template(template).is_ok() {
+        template
+    } else {
+        DEFAULT_RECORDING_NAME_TEMPLATE
+    };
+    let folder_name = template
+        .replace("{timestamp}", &name.started_at.timestamp().to_string())
+        .replace("{date}", &name.started_at.format("%Y-%m-%d").to_string())
+        .replace("{time}", &name.started_at.format("%H-%M-%S").to_string())
+        .replace("{game}", &sanitize(name.game))
+        .replace("{index}", &(name.segment_index + 1).to_string());
+    let folder_name = folder_name.chars().take(MAX_NAME_LEN).collect::<String>();
+    folder_name.trim_end_matches(['.', ' ']).to_string()
+}
+
+/// A folder for a new recording in `recording_location`. If another recording already has the name (the
+/// template needn't include the time), a number is added to the end of it.
+pub fn new_recording_folder(recording_location: &Path, folder_name: &str) -> PathBuf {
+    let mut folder_path = recording_location.join(folder_name);
+    let mut number = 2;
+    while folder_path.exists() {
+        folder_path = recording_location.join(format!("{folder_name} ({number})"));
+        number += 1;
+    }
+    folder_path
+}
+
+/// Replaces whatever Windows wouldn't allow in a file name, so that a game's name can go into one
+fn sanitize(value: &str) -> String {
+    let sanitized = value
+        .trim()
+        .chars()
+        .map(|c| {
+            if ILLEGAL_CHARS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    let sanitized = sanitized.trim_matches(['.', ' ']);
+    if sanitized.is_empty() {
+        "Unknown".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone as _;
+
+    use super::*;
+    use crate::util::TestDir;
+
+    fn name(game: &str) -> RecordingName<'_> {
+        RecordingName {
+            started_at: Local.with_ymd_and_hms(2025, 10, 15, 14, 30, 5).unwrap(),
+            game,
+            segment_index: 1,
+        }
+    }
+
+    #[test]
+    fn test_recording_folder_name() {
+        assert_eq!(
+            recording_folder_name("{date} {time} {game} #{index}", &name("Minecraft")),
+            "2025-10-15 14-30-05 Minecraft #2"
+        );
+        assert_eq!(
+            recording_folder_name(DEFAULT_RECORDING_NAME_TEMPLATE, &name("Minecraft")),
+            name("Minecraft").started_at.timestamp().to_string()
+        );
+        // Whatever the game is called, it makes a valid folder name
+        assert_eq!(
+            recording_folder_name("{game} {time}", &name("Half-Life: Alyx?")),
+            "Half-Life_ Alyx_ 14-30-05"
+        );
+        assert_eq!(
+            recording_folder_name("{time} {game}", &name(" ... ")),
+            "14-30-05 Unknown"
+        );
+        // An invalid template isn't used
+        assert_eq!(
+            recording_folder_name("{game}/{time}", &name("Minecraft")),
+            recording_folder_name(DEFAULT_RECORDING_NAME_TEMPLATE, &name("Minecraft"))
+        );
+    }
+
+    #[test]
+    fn test_check_recording_name_template() {
+        assert!(check_recording_name_template(DEFAULT_RECORDING_NAME_TEMPLATE).is_ok());
+        assert!(check_recording_name_template("{date}_{time}_{game}_{index}").is_ok());
+        assert!(check_recording_name_template("").is_err());
+        assert!(check_recording_name_template(" ").is_err());
+        assert!(check_recording_name_template("{time} {unknown}").is_err());
+        assert!(check_recording_name_template("{time").is_err());
+        assert!(check_recording_name_template("{time}}").is_err());
+        assert!(check_recording_name_template("{date}/{time}").is_err());
+        assert!(check_recording_name_template("{time}.").is_err());
+        assert!(check_recording_name_template(".{time}").is_err());
+    }
+
+    #[test]
+    fn test_new_recording_folder_is_unique() {
+        let dir = TestDir::new("naming");
+        std::fs::create_dir_all(dir.join("Minecraft")).unwrap();
+        assert_eq!(
+            new_recording_folder(&dir, "Minecraft"),
+            dir.join("Minecraft (2)")
+        );
+        assert_eq!(new_recording_folder(&dir, "Other"), dir.join("Other"));
+    }
+}
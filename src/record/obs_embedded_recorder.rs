@@ -1,13 +1,20 @@
 use std::{
     path::Path,
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Instant,
 };
 
 use color_eyre::{
     Result,
     eyre::{self, Context, OptionExt as _, bail, eyre},
 };
-use constants::{FPS, RECORDING_HEIGHT, RECORDING_WIDTH, encoding::VideoEncoderType};
+use constants::{
+    FPS, MAX_SKIPPED_FRAMES_PERCENTAGE, RECORDING_HEIGHT, RECORDING_WIDTH,
+    encoding::{VideoCodec, VideoEncoderType},
+};
 use windows::Win32::Foundation::HWND;
 
 use libobs_sources::{
@@ -22,13 +29,23 @@ use libobs_wrapper::{
     logger::ObsLogger,
     scenes::ObsSceneRef,
     sources::ObsSourceRef,
-    utils::{AudioEncoderInfo, ObsPath, OutputInfo, VideoEncoderInfo},
+    utils::{AudioEncoderInfo, ObsPath, OutputInfo, SourceInfo, VideoEncoderInfo},
 };
 
-use crate::{config::EncoderSettings, record::recorder::VideoRecorder};
+use crate::{
+    config::EncoderSettings,
+    output_types::SkippedFrames,
+    record::{
+        capture_backend::{
+            CaptureBackend, PREVIEW_HEIGHT, PREVIEW_INTERVAL, PREVIEW_WIDTH, PreviewFrame,
+        },
+        capture_region::Crop,
+    },
+};
 
 const OWL_SCENE_NAME: &str = "owl_data_collection_scene";
 const OWL_CAPTURE_NAME: &str = "owl_game_capture";
+const OWL_DESKTOP_AUDIO_NAME: &str = "owl_desktop_audio";
 
 // Untested! Added for testing purposes, but will probably not be used as
 // we want to ensure we're capturing a game and WindowCapture will capture
@@ -58,19 +75,22 @@ impl ObsEmbeddedRecorder {
     }
 }
 #[async_trait::async_trait(?Send)]
-impl VideoRecorder for ObsEmbeddedRecorder {
+impl CaptureBackend for ObsEmbeddedRecorder {
     fn id(&self) -> &'static str {
         "ObsEmbedded"
     }
 
-    async fn start_recording(
+    async fn start(
         &mut self,
         dummy_video_path: &Path,
         pid: u32,
         _hwnd: HWND,
         game_exe: &str,
         video_settings: EncoderSettings,
-        (base_width, base_height): (u32, u32),
+        fps: u32,
+        game_resolution: (u32, u32),
+        crop: Option<Crop>,
+        desktop_audio_device: Option<&str>,
     ) -> Result<()> {
         let recording_path = dummy_video_path
             .to_str()
@@ -83,11 +103,15 @@ impl VideoRecorder for ObsEmbeddedRecorder {
         self.obs_tx
             .send(RecorderMessage::StartRecording {
                 request: RecordingRequest {
-                    game_resolution: (base_width, base_height),
+                    game_resolution,
+                    crop,
                     video_settings,
+                    fps,
                     recording_path,
                     game_exe: game_exe.to_string(),
                     pid,
+                    desktop_audio_device: desktop_audio_device.map(str::to_string),
+                    obs_encoder: None,
                 },
                 result_tx,
             })
@@ -99,7 +123,7 @@ impl VideoRecorder for ObsEmbeddedRecorder {
         Ok(())
     }
 
-    async fn stop_recording(&mut self) -> Result<serde_json::Value> {
+    async fn stop(&mut self) -> Result<serde_json::Value> {
         tracing::info!("Stopping OBS embedded recording...");
 
         let (result_tx, result_rx) = tokio::sync::oneshot::channel();
@@ -112,6 +136,42 @@ impl VideoRecorder for ObsEmbeddedRecorder {
 
         Ok(result)
     }
+
+    async fn set_paused(&mut self, paused: bool) -> Result<()> {
+        let (result_tx, result_rx) = tokio::sync::oneshot::channel();
+        self.obs_tx
+            .send(RecorderMessage::SetPaused { paused, result_tx })
+            .await?;
+        result_rx.await??;
+
+        tracing::info!(paused, "OBS embedded recording pause state changed");
+
+        Ok(())
+    }
+
+    async fn skipped_frames(&mut self) -> Result<Option<SkippedFrames>> {
+        let (result_tx, result_rx) = tokio::sync::oneshot::channel();
+        self.obs_tx
+            .send(RecorderMessage::SkippedFrames { result_tx })
+            .await?;
+        Ok(result_rx.await?)
+    }
+
+    async fn next_frame(&mut self) -> Result<Option<PreviewFrame>> {
+        let (result_tx, result_rx) = tokio::sync::oneshot::channel();
+        self.obs_tx
+            .send(RecorderMessage::PreviewFrame { result_tx })
+            .await?;
+        Ok(result_rx.await?)
+    }
+
+    async fn is_capturing(&mut self) -> Result<Option<bool>> {
+        let (result_tx, result_rx) = tokio::sync::oneshot::channel();
+        self.obs_tx
+            .send(RecorderMessage::IsHooked { result_tx })
+            .await?;
+        Ok(result_rx.await?)
+    }
 }
 
 enum RecorderMessage {
@@ -122,14 +182,32 @@ enum RecorderMessage {
     StopRecording {
         result_tx: tokio::sync::oneshot::Sender<Result<serde_json::Value>>,
     },
+    SetPaused {
+        paused: bool,
+        result_tx: tokio::sync::oneshot::Sender<Result<()>>,
+    },
+    SkippedFrames {
+        result_tx: tokio::sync::oneshot::Sender<Option<SkippedFrames>>,
+    },
+    PreviewFrame {
+        result_tx: tokio::sync::oneshot::Sender<Option<PreviewFrame>>,
+    },
+    IsHooked {
+        result_tx: tokio::sync::oneshot::Sender<Option<bool>>,
+    },
 }
 
 struct RecordingRequest {
     game_resolution: (u32, u32),
+    crop: Option<Crop>,
     video_settings: EncoderSettings,
+    fps: u32,
     recording_path: String,
     game_exe: String,
     pid: u32,
+    desktop_audio_device: Option<String>,
+    /// Records with only this OBS encoder, rather than the best of [`obs_video_encoders`] that can be set up
+    obs_encoder: Option<ObsVideoEncoderType>,
 }
 
 fn recorder_thread(
@@ -172,8 +250,12 @@ fn recorder_thread(
         skipped_frames,
         current_output: None,
         source: None,
+        desktop_audio_source: None,
         last_encoder_settings: None,
         last_hooked_signal: None,
+        hooked: false,
+        video_frames_at_start: None,
+        preview: None,
     };
 
     while let Some(message) = rx.blocking_recv() {
@@ -184,6 +266,18 @@ fn recorder_thread(
             RecorderMessage::StopRecording { result_tx } => {
                 result_tx.send(state.stop_recording()).ok();
             }
+            RecorderMessage::SetPaused { paused, result_tx } => {
+                result_tx.send(state.set_paused(paused)).ok();
+            }
+            RecorderMessage::SkippedFrames { result_tx } => {
+                result_tx.send(state.skipped_frames()).ok();
+            }
+            RecorderMessage::PreviewFrame { result_tx } => {
+                result_tx.send(state.preview_frame()).ok();
+            }
+            RecorderMessage::IsHooked { result_tx } => {
+                result_tx.send(state.is_hooked()).ok();
+            }
         }
     }
 }
@@ -194,9 +288,16 @@ struct RecorderState {
     skipped_frames: Arc<Mutex<Option<SkippedFrames>>>,
     current_output: Option<ObsOutputRef>,
     source: Option<ObsSourceRef>,
+    desktop_audio_source: Option<ObsSourceRef>,
     last_encoder_settings: Option<serde_json::Value>,
     last_hooked_signal:
         Option<tokio::sync::broadcast::Receiver<libobs_wrapper::sources::HookedSignal>>,
+    /// Set once the current recording's source has hooked into the game
+    hooked: bool,
+    /// OBS's video frame counters when the current recording started
+    video_frames_at_start: Option<(u32, u32)>,
+    /// Set once a preview has been asked for during the current recording
+    preview: Option<Box<PreviewCapture>>,
 }
 impl RecorderState {
     fn start_recording(&mut self, request: RecordingRequest) -> eyre::Result<()> {
@@ -207,13 +308,18 @@ impl RecorderState {
         // Set up scene and window capture based on input pid
         let mut scene = self.obs_context.scene(OWL_SCENE_NAME)?;
 
+        // The canvas only needs to fit what's left of the game after cropping
+        let (base_width, base_height) = request
+            .crop
+            .unwrap_or_default()
+            .apply(request.game_resolution);
         self.obs_context.reset_video(
             ObsVideoInfoBuilder::new()
                 .adapter(self.adapter_index as u32)
-                .fps_num(FPS)
+                .fps_num(request.fps)
                 .fps_den(1)
-                .base_width(request.game_resolution.0)
-                .base_height(request.game_resolution.1)
+                .base_width(base_width)
+                .base_height(base_height)
                 .output_width(RECORDING_WIDTH)
                 .output_height(RECORDING_HEIGHT)
                 .build(),
@@ -226,6 +332,16 @@ impl RecorderState {
             &mut scene,
         )?;
 
+        // The scene item outlives the recording, so this also clears the crop from the last one
+        match (
+            set_capture_crop(request.crop.unwrap_or_default()),
+            request.crop,
+        ) {
+            (Err(e), Some(_)) => return Err(e.wrap_err("Failed to crop to the capture region")),
+            (Err(e), None) => tracing::warn!(e=?e, "Failed to clear the capture crop"),
+            (Ok(()), _) => {}
+        }
+
         // Register a signal to detect when the source is hooked,
         // so we can invalidate non-hooked recordings
         self.last_hooked_signal = Some(
@@ -234,6 +350,22 @@ impl RecorderState {
                 .on_hooked()
                 .context("failed to register on_hooked signal")?,
         );
+        self.hooked = false;
+
+        // Desktop audio goes into the same scene as the game, so OBS mixes it in sync with the video.
+        // It's optional, so a missing or broken device shouldn't stop the recording.
+        self.desktop_audio_source = match &request.desktop_audio_device {
+            Some(device_id) => {
+                match build_desktop_audio_source(&mut self.obs_context, device_id, &mut scene) {
+                    Ok(source) => Some(source),
+                    Err(e) => {
+                        tracing::warn!(e=?e, "Failed to set up desktop audio, recording without it");
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
 
         // Register the source
         scene.set_to_channel(0)?;
@@ -241,16 +373,98 @@ impl RecorderState {
         // Set up output
         let mut output_settings = self.obs_context.data()?;
         output_settings.set_string("path", ObsPath::new(&request.recording_path).build())?;
+        // Write the video in fragments, so that everything up to the last fragment is still playable
+        // if we never get to finish the file (e.g. a crash or power cut)
+        output_settings.set_string("muxer_settings", "movflags=frag_keyframe+empty_moov")?;
 
         let output_info = OutputInfo::new("ffmpeg_muxer", "output", Some(output_settings), None);
         let mut output = self.obs_context.output(output_info)?;
 
         // TODO: it seems that video encoder and audio encoder should only be created once, instead of new ones every time that recording starts.
-        // Register the video encoder with encoder-specific settings
+        // Register the video encoder with encoder-specific settings, falling back to x264 if the hardware encoder doesn't work out
+        if let Err(e) = self.attach_video_encoder(
+            &mut output,
+            &request.video_settings,
+            request.obs_encoder.clone(),
+        ) {
+            if request.video_settings.encoder == VideoEncoderType::X264
+                || request.obs_encoder.is_some()
+            {
+                return Err(e);
+            }
+            tracing::warn!(
+                e=?e,
+                "Failed to set up {} encoder, falling back to {}",
+                request.video_settings.encoder,
+                VideoEncoderType::X264
+            );
+            let fallback_settings = EncoderSettings {
+                encoder: VideoEncoderType::X264,
+                codec: VideoCodec::H264,
+                ..request.video_settings.clone()
+            };
+            self.attach_video_encoder(&mut output, &fallback_settings, None)?;
+        }
+
+        // Register the audio encoder
+        let mut audio_settings = self.obs_context.data()?;
+        audio_settings.set_int("bitrate", constants::encoding::AUDIO_BITRATE)?;
+
+        let audio_info =
+            AudioEncoderInfo::new("ffmpeg_aac", "audio_encoder", Some(audio_settings), None);
+
+        let audio_handler = self.obs_context.get_audio_ptr()?;
+        output.audio_encoder(audio_info, 0, audio_handler)?;
+
+        // Just before we start, clear out our skipped frame counter
+        self.skipped_frames.lock().unwrap().take();
+
+        output.start()?;
+        self.video_frames_at_start = Some(video_frame_counts());
+
+        self.current_output = Some(output);
+        self.source = Some(source);
+
+        Ok(())
+    }
+
+    /// Attaches the first encoder out of [`obs_video_encoders`] that can be set up, or `obs_encoder` if given
+    fn attach_video_encoder(
+        &mut self,
+        output: &mut ObsOutputRef,
+        video_settings: &EncoderSettings,
+        obs_encoder: Option<ObsVideoEncoderType>,
+    ) -> eyre::Result<()> {
+        let mut encoder_types = match obs_encoder {
+            Some(obs_encoder) => vec![obs_encoder],
+            None => obs_video_encoders(video_settings.encoder, video_settings.codec),
+        }
+        .into_iter()
+        .peekable();
+        while let Some(encoder_type) = encoder_types.next() {
+            match self.attach_obs_video_encoder(output, video_settings, encoder_type.clone()) {
+                Ok(()) => return Ok(()),
+                Err(e) if encoder_types.peek().is_some() => {
+                    tracing::warn!(
+                        e=?e,
+                        "Failed to set up {encoder_type:?}, falling back to {:?}",
+                        encoder_types.peek()
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!("every encoder has at least one OBS encoder")
+    }
+
+    fn attach_obs_video_encoder(
+        &mut self,
+        output: &mut ObsOutputRef,
+        video_settings: &EncoderSettings,
+        encoder_type: ObsVideoEncoderType,
+    ) -> eyre::Result<()> {
         let video_encoder_data = self.obs_context.data()?;
-        let video_encoder_settings = request
-            .video_settings
-            .apply_to_obs_data(video_encoder_data)?;
+        let video_encoder_settings = video_settings.apply_to_obs_data(video_encoder_data)?;
         self.last_encoder_settings = video_encoder_settings
             .get_json()
             .ok()
@@ -259,12 +473,27 @@ impl RecorderState {
             if let Some(object) = encoder_settings_json.as_object_mut() {
                 object.insert(
                     "encoder".to_string(),
-                    match request.video_settings.encoder {
+                    match video_settings.encoder {
                         VideoEncoderType::X264 => "x264",
                         VideoEncoderType::NvEnc => "nvenc",
+                        VideoEncoderType::Qsv => "qsv",
+                        VideoEncoderType::Amf => "amf",
+                    }
+                    .into(),
+                );
+                object.insert(
+                    "codec".to_string(),
+                    match video_settings.codec {
+                        VideoCodec::H264 => "h264",
+                        VideoCodec::H265 => "h265",
                     }
                     .into(),
                 );
+                // Tells apart the texture and the copying variants of the same encoder
+                object.insert(
+                    "obs_encoder".to_string(),
+                    format!("{encoder_type:?}").into(),
+                );
             }
             tracing::info!("Recording starting with video settings: {encoder_settings_json:?}");
         }
@@ -273,58 +502,86 @@ impl RecorderState {
         let video_handler = self.obs_context.get_video_ptr()?;
         output.video_encoder(
             VideoEncoderInfo::new(
-                match request.video_settings.encoder {
-                    VideoEncoderType::X264 => ObsVideoEncoderType::OBS_X264,
-                    VideoEncoderType::NvEnc => ObsVideoEncoderType::FFMPEG_NVENC,
-                },
+                encoder_type,
                 "video_encoder",
                 Some(video_encoder_settings),
                 None,
             ),
             video_handler,
         )?;
+        Ok(())
+    }
 
-        // Register the audio encoder
-        let mut audio_settings = self.obs_context.data()?;
-        audio_settings.set_int("bitrate", 160)?;
-
-        let audio_info =
-            AudioEncoderInfo::new("ffmpeg_aac", "audio_encoder", Some(audio_settings), None);
-
-        let audio_handler = self.obs_context.get_audio_ptr()?;
-        output.audio_encoder(audio_info, 0, audio_handler)?;
-
-        // Just before we start, clear out our skipped frame counter
-        self.skipped_frames.lock().unwrap().take();
+    fn set_paused(&mut self, paused: bool) -> eyre::Result<()> {
+        let output = self
+            .current_output
+            .as_mut()
+            .ok_or_eyre("No active recording to pause")?;
+        output
+            .pause(paused)
+            .wrap_err_with(|| format!("Failed to set OBS output paused to {paused}"))?;
+        Ok(())
+    }
 
-        output.start()?;
+    /// The frames skipped so far in the current recording. The final count comes from OBS's logs once
+    /// the recording is stopped; this is the same calculation, done while the recording is running.
+    /// Whether the current recording's source has hooked into the game, or `None` if there's no recording
+    fn is_hooked(&mut self) -> Option<bool> {
+        let hooked_signal = self.last_hooked_signal.as_mut()?;
+        if !self.hooked && hooked_signal.try_recv().is_ok() {
+            self.hooked = true;
+        }
+        Some(self.hooked)
+    }
 
-        self.current_output = Some(output);
-        self.source = Some(source);
+    fn skipped_frames(&self) -> Option<SkippedFrames> {
+        self.current_output.as_ref()?;
+        let (skipped_at_start, total_at_start) = self.video_frames_at_start?;
+        let (skipped, total) = video_frame_counts();
+        Some(SkippedFrames {
+            skipped: skipped.saturating_sub(skipped_at_start) as usize,
+            total: total.saturating_sub(total_at_start) as usize,
+        })
+    }
 
-        Ok(())
+    /// The latest preview frame, starting to capture them if this is the first time it's been asked for
+    fn preview_frame(&mut self) -> Option<PreviewFrame> {
+        self.current_output.as_ref()?;
+        let preview = self.preview.get_or_insert_with(PreviewCapture::start);
+        preview.latest.lock().unwrap().clone()
     }
 
     fn stop_recording(&mut self) -> eyre::Result<serde_json::Value> {
+        let desktop_audio = self.desktop_audio_source.is_some();
+        if let Some(preview) = self.preview.take() {
+            preview.stop();
+        }
+        self.video_frames_at_start = None;
         if let Some(mut output) = self.current_output.take() {
             output.stop().wrap_err("Failed to stop OBS output")?;
-            if let Some(mut scene) = self.obs_context.get_scene(OWL_SCENE_NAME)
-                && let Some(source) = self.source.take()
-            {
-                scene.remove_source(&source)?;
+            if let Some(mut scene) = self.obs_context.get_scene(OWL_SCENE_NAME) {
+                for source in [self.source.take(), self.desktop_audio_source.take()]
+                    .into_iter()
+                    .flatten()
+                {
+                    scene.remove_source(&source)?;
+                }
             }
             tracing::debug!("OBS recording stopped");
         } else {
             tracing::warn!("No active recording to stop");
         }
 
-        if let Some(mut hooked_signal) = self.last_hooked_signal.take()
-            && hooked_signal.try_recv().is_err()
-        {
+        let hooked = self.is_hooked();
+        self.last_hooked_signal = None;
+        if hooked == Some(false) {
             bail!("Application was never hooked, recording will be blank");
         }
 
         let mut output = self.last_encoder_settings.take().unwrap_or_default();
+        if let Some(object) = output.as_object_mut() {
+            object.insert("desktop_audio".to_string(), desktop_audio.into());
+        }
 
         // Extremely ugly hack: We want to get the skipped frames percentage from the logs,
         // but that's not guaranteed to be present by the time this function would normally end.
@@ -333,7 +590,7 @@ impl RecorderState {
         std::thread::sleep(std::time::Duration::from_millis(200));
         if let Some(skipped_frames) = self.skipped_frames.lock().unwrap().take() {
             let percentage = skipped_frames.percentage();
-            if percentage > 5.0 {
+            if percentage > MAX_SKIPPED_FRAMES_PERCENTAGE {
                 bail!(
                     "Too many frames were dropped ({}/{}, {percentage:.2}%), recording is unusable. Please consider using another encoder or tweaking your settings.",
                     skipped_frames.skipped,
@@ -404,19 +661,161 @@ fn build_source(
     Ok(result?)
 }
 
-#[derive(Debug, serde::Serialize)]
-struct SkippedFrames {
-    skipped: usize,
-    total: usize,
+fn build_desktop_audio_source(
+    obs_context: &mut ObsContext,
+    device_id: &str,
+    scene: &mut ObsSceneRef,
+) -> Result<ObsSourceRef> {
+    let mut settings = obs_context.data()?;
+    settings.set_string("device_id", device_id)?;
+    Ok(scene.add_source(SourceInfo::new(
+        "wasapi_output_capture",
+        OWL_DESKTOP_AUDIO_NAME,
+        Some(settings),
+        None,
+    ))?)
 }
-impl SkippedFrames {
-    /// 0-100%
-    pub fn percentage(&self) -> f64 {
-        if self.total == 0 {
-            0.0
-        } else {
-            (self.skipped as f64 / self.total as f64) * 100.0
+
+/// The OBS encoders that can encode with `encoder` and `codec`, best first.
+///
+/// Texture encoders take frames straight from OBS's GPU textures, so a frame never leaves the GPU between
+/// being captured and being encoded. Others have every frame copied into system memory and back up to the
+/// encoder, which costs CPU time and bandwidth, and drops frames at high resolutions. The NVENC texture
+/// encoder needs a recent driver and OBS to be running on the same GPU as the encoder, so the copying one
+/// is kept as a fallback. The AMF encoders only come as texture encoders, and OBS's QSV encoder picks the
+/// texture path by itself when it can.
+fn obs_video_encoders(encoder: VideoEncoderType, codec: VideoCodec) -> Vec<ObsVideoEncoderType> {
+    match (encoder, codec) {
+        (VideoEncoderType::X264, _) => vec![ObsVideoEncoderType::OBS_X264],
+        (VideoEncoderType::NvEnc, VideoCodec::H264) => vec![
+            ObsVideoEncoderType::JIM_NVENC,
+            ObsVideoEncoderType::FFMPEG_NVENC,
+        ],
+        (VideoEncoderType::NvEnc, VideoCodec::H265) => vec![
+            ObsVideoEncoderType::JIM_HEVC_NVENC,
+            ObsVideoEncoderType::FFMPEG_HEVC_NVENC,
+        ],
+        (VideoEncoderType::Qsv, VideoCodec::H264) => vec![ObsVideoEncoderType::OBS_QSV11],
+        (VideoEncoderType::Qsv, VideoCodec::H265) => vec![ObsVideoEncoderType::OBS_QSV11_HEVC],
+        (VideoEncoderType::Amf, VideoCodec::H264) => vec![ObsVideoEncoderType::H264_TEXTURE_AMF],
+        (VideoEncoderType::Amf, VideoCodec::H265) => vec![ObsVideoEncoderType::H265_TEXTURE_AMF],
+    }
+}
+
+/// Crops the game capture's scene item. It sits in the canvas's top left, so cropping it leaves just the
+/// region filling a canvas of the cropped size.
+fn set_capture_crop(crop: Crop) -> Result<()> {
+    let scene_name = std::ffi::CString::new(OWL_SCENE_NAME)?;
+    let capture_name = std::ffi::CString::new(OWL_CAPTURE_NAME)?;
+    // SAFETY: called on the recorder thread once the scene and capture are set up. The scene reference
+    // from `obs_get_scene_by_name` is ours to release, while the scene item is borrowed from the scene.
+    unsafe {
+        let scene = libobs::obs_get_scene_by_name(scene_name.as_ptr());
+        if scene.is_null() {
+            bail!("Couldn't find the recording scene");
         }
+        let item = libobs::obs_scene_find_source(scene, capture_name.as_ptr());
+        if !item.is_null() {
+            libobs::obs_sceneitem_set_crop(
+                item,
+                &libobs::obs_sceneitem_crop {
+                    left: crop.left as i32,
+                    top: crop.top as i32,
+                    right: crop.right as i32,
+                    bottom: crop.bottom as i32,
+                },
+            );
+        }
+        libobs::obs_scene_release(scene);
+        if item.is_null() {
+            bail!("Couldn't find the game capture in the recording scene");
+        }
+    }
+    Ok(())
+}
+
+/// The number of frames that OBS's video output has skipped due to encoding lag, and the number it has produced in total
+fn video_frame_counts() -> (u32, u32) {
+    // SAFETY: OBS is running for as long as the recorder thread is, and these only read counters
+    unsafe {
+        let video = libobs::obs_get_video();
+        (
+            libobs::video_output_get_skipped_frames(video),
+            libobs::video_output_get_total_frames(video),
+        )
+    }
+}
+
+/// Has OBS hand over every frame of the recording, scaled down and converted to RGBA for us, keeping one
+/// every [`PREVIEW_INTERVAL`]. Lives on the heap so that OBS's video thread can hold on to it.
+#[derive(Default)]
+struct PreviewCapture {
+    latest: Mutex<Option<PreviewFrame>>,
+    /// OBS's timestamp (in nanoseconds) of the frame in `latest`
+    last_timestamp: AtomicU64,
+}
+impl PreviewCapture {
+    fn start() -> Box<Self> {
+        let preview = Box::<Self>::default();
+        let conversion = libobs::video_scale_info {
+            format: libobs::video_format_VIDEO_FORMAT_RGBA,
+            width: PREVIEW_WIDTH,
+            height: PREVIEW_HEIGHT,
+            range: libobs::video_range_type_VIDEO_RANGE_DEFAULT,
+            colorspace: libobs::video_colorspace_VIDEO_CS_DEFAULT,
+        };
+        // SAFETY: `preview` stays where it is on the heap until `stop` has removed the callback
+        unsafe {
+            libobs::obs_add_raw_video_callback(
+                &conversion,
+                Some(Self::on_frame),
+                preview.as_ref() as *const Self as *mut std::ffi::c_void,
+            );
+        }
+        preview
+    }
+
+    fn stop(self: Box<Self>) {
+        // SAFETY: the same callback and parameter it was added with. OBS waits for a callback that's
+        // running to finish before removing it, so `self` isn't used after it's dropped.
+        unsafe {
+            libobs::obs_remove_raw_video_callback(
+                Some(Self::on_frame),
+                self.as_ref() as *const Self as *mut std::ffi::c_void,
+            );
+        }
+    }
+
+    unsafe extern "C" fn on_frame(param: *mut std::ffi::c_void, frame: *mut libobs::video_data) {
+        // SAFETY: `param` is the `PreviewCapture` the callback was added with, and OBS hands us a frame
+        // in the format we asked for, which is only valid for the duration of the call
+        let (preview, frame) = unsafe { (&*(param as *const Self), &*frame) };
+        let last = preview.last_timestamp.load(Ordering::Relaxed);
+        if last != 0 && frame.timestamp.saturating_sub(last) < PREVIEW_INTERVAL.as_nanos() as u64 {
+            return;
+        }
+        preview
+            .last_timestamp
+            .store(frame.timestamp, Ordering::Relaxed);
+
+        let row_bytes = PREVIEW_WIDTH as usize * 4;
+        let mut rgba = Vec::with_capacity(row_bytes * PREVIEW_HEIGHT as usize);
+        for row in 0..PREVIEW_HEIGHT as usize {
+            // SAFETY: each of the frame's rows is `linesize` bytes apart, and at least `row_bytes` long
+            let row = unsafe {
+                std::slice::from_raw_parts(
+                    frame.data[0].add(row * frame.linesize[0] as usize),
+                    row_bytes,
+                )
+            };
+            rgba.extend_from_slice(row);
+        }
+        *preview.latest.lock().unwrap() = Some(PreviewFrame {
+            width: PREVIEW_WIDTH,
+            height: PREVIEW_HEIGHT,
+            rgba,
+            captured_at: Instant::now(),
+        });
     }
 }
 
@@ -481,6 +880,13 @@ fn parse_skipped_frames(msg: &str) -> Option<SkippedFrames> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        record::{
+            benchmark::{BENCHMARK_RUN_LENGTH, BenchmarkRun, BenchmarkSettings},
+            recording::get_recording_base_resolution,
+        },
+        util::TestDir,
+    };
 
     #[test]
     fn test_parse_skipped_frames_basic() {
@@ -492,4 +898,115 @@ mod tests {
         assert_eq!(result.total, 22640);
         assert!((result.percentage() - 47.48).abs() < 0.1);
     }
+
+    #[test]
+    fn test_texture_encoders_are_tried_first() {
+        assert_eq!(
+            obs_video_encoders(VideoEncoderType::NvEnc, VideoCodec::H264),
+            [
+                ObsVideoEncoderType::JIM_NVENC,
+                ObsVideoEncoderType::FFMPEG_NVENC
+            ]
+        );
+        assert_eq!(
+            obs_video_encoders(VideoEncoderType::NvEnc, VideoCodec::H265),
+            [
+                ObsVideoEncoderType::JIM_HEVC_NVENC,
+                ObsVideoEncoderType::FFMPEG_HEVC_NVENC
+            ]
+        );
+        // Anything without a choice of paths is left to OBS
+        for encoder in [
+            VideoEncoderType::X264,
+            VideoEncoderType::Qsv,
+            VideoEncoderType::Amf,
+        ] {
+            assert_eq!(obs_video_encoders(encoder, VideoCodec::H264).len(), 1);
+        }
+    }
+
+    /// Records a running game through NVENC's texture encoder, then through its FFmpeg encoder (which copies
+    /// every frame through system memory), and compares how many frames each dropped. Needs an NVIDIA GPU and
+    /// the game to be running, so it's only run when asked for:
+    /// `OWL_BENCHMARK_GAME_EXE=game.exe cargo test benchmark_nvenc_paths -- --ignored --nocapture`
+    #[tokio::test]
+    #[ignore = "needs an NVIDIA GPU and a running game"]
+    async fn benchmark_nvenc_paths() {
+        let game_exe = std::env::var("OWL_BENCHMARK_GAME_EXE")
+            .expect("OWL_BENCHMARK_GAME_EXE should be the exe name of a running game");
+        let (hwnd, pid) = game_process::titled_windows()
+            .into_iter()
+            .find(|&(_, pid)| {
+                game_process::exe_name_for_pid(pid).is_ok_and(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(&game_exe))
+                })
+            })
+            .expect("the game should be running");
+        let game_resolution = get_recording_base_resolution(hwnd).unwrap();
+        let dir = TestDir::new("nvenc-benchmark");
+        let mut recorder = ObsEmbeddedRecorder::new(0).await.unwrap();
+
+        let settings = BenchmarkSettings {
+            fps: FPS,
+            encoder: VideoEncoderType::NvEnc,
+            codec: VideoCodec::H264,
+        };
+        let mut runs = vec![];
+        for obs_encoder in obs_video_encoders(settings.encoder, settings.codec) {
+            let (result_tx, result_rx) = tokio::sync::oneshot::channel();
+            let request = RecordingRequest {
+                game_resolution,
+                crop: None,
+                video_settings: EncoderSettings {
+                    encoder: settings.encoder,
+                    codec: settings.codec,
+                    ..Default::default()
+                },
+                fps: settings.fps,
+                recording_path: dir
+                    .join(format!("{obs_encoder:?}.mp4"))
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+                game_exe: game_exe.clone(),
+                pid: pid.0,
+                desktop_audio_device: None,
+                obs_encoder: Some(obs_encoder.clone()),
+            };
+            recorder
+                .obs_tx
+                .send(RecorderMessage::StartRecording { request, result_tx })
+                .await
+                .unwrap();
+            result_rx.await.unwrap().unwrap();
+
+            tokio::time::sleep(BENCHMARK_RUN_LENGTH).await;
+            let run = BenchmarkRun {
+                settings,
+                skipped_frames: recorder.skipped_frames().await.unwrap(),
+            };
+            // Dropping too many frames makes stopping fail, which is what's being measured here
+            if let Err(e) = recorder.stop().await {
+                println!("{obs_encoder:?}: {e}");
+            }
+            println!(
+                "{obs_encoder:?}: skipped {:?}, keeps up: {}",
+                run.skipped_frames,
+                run.is_sustainable()
+            );
+            runs.push(run);
+        }
+
+        let [texture, copying] = &runs[..] else {
+            panic!(
+                "expected a texture and a copying encoder, got {} runs",
+                runs.len()
+            );
+        };
+        assert!(
+            texture.is_sustainable() || !copying.is_sustainable(),
+            "the texture encoder dropped more frames than the copying one"
+        );
+    }
 }
@@ -4,20 +4,27 @@ use color_eyre::{
     Result,
     eyre::{Context, OptionExt as _},
 };
-use constants::{FPS, RECORDING_HEIGHT, RECORDING_WIDTH};
+use constants::{RECORDING_HEIGHT, RECORDING_WIDTH};
 use obws::{
     Client,
     requests::{
         config::SetVideoSettings,
         inputs::{InputId, SetSettings, Volume},
         profiles::SetParameter,
-        scene_items::{Position, Scale, SceneItemTransform, SetTransform},
+        scene_items::{self, Position, Scale, SceneItemTransform, SetTransform},
         scenes::SceneId,
     },
 };
 use windows::Win32::Foundation::HWND;
 
-use crate::{config::EncoderSettings, record::recorder::VideoRecorder};
+use crate::{
+    config::EncoderSettings,
+    output_types::SkippedFrames,
+    record::{
+        capture_backend::{CaptureBackend, PreviewFrame},
+        capture_region::Crop,
+    },
+};
 
 const OWL_PROFILE_NAME: &str = "owl_data_recorder";
 const OWL_SCENE_NAME: &str = "owl_data_collection_scene";
@@ -28,29 +35,38 @@ const SET_ENCODER: bool = false;
 pub struct ObsSocketRecorder {
     // Use an Option to allow it to be consumed within the destructor
     client: Option<Client>,
+    /// OBS's output frame counters when the current recording started. They cover everything
+    /// OBS has output since it was launched, so we need to subtract these.
+    output_frames_at_start: Option<(u32, u32)>,
 }
 impl ObsSocketRecorder {
     pub async fn new() -> Result<Self>
     where
         Self: Sized,
     {
-        Ok(Self { client: None })
+        Ok(Self {
+            client: None,
+            output_frames_at_start: None,
+        })
     }
 }
 #[async_trait::async_trait(?Send)]
-impl VideoRecorder for ObsSocketRecorder {
+impl CaptureBackend for ObsSocketRecorder {
     fn id(&self) -> &'static str {
         "ObsSocket"
     }
 
-    async fn start_recording(
+    async fn start(
         &mut self,
         dummy_video_path: &Path,
         _pid: u32,
         hwnd: HWND,
         game_exe: &str,
         _video_settings: EncoderSettings,
-        (base_width, base_height): (u32, u32),
+        fps: u32,
+        game_resolution: (u32, u32),
+        crop: Option<Crop>,
+        desktop_audio_device: Option<&str>,
     ) -> Result<()> {
         // Connect to OBS
         let client = Client::connect("localhost", 4455, None::<&str>)
@@ -143,8 +159,16 @@ impl VideoRecorder for ObsSocketRecorder {
         let _ = inputs
             .set_volume(InputId::Name("Mic/Aux"), Volume::Db(-100.0))
             .await;
+        // OBS records desktop audio from the device picked in its own audio settings
         let _ = inputs
-            .set_volume(InputId::Name("Desktop Audio"), Volume::Db(-100.0))
+            .set_volume(
+                InputId::Name("Desktop Audio"),
+                Volume::Db(if desktop_audio_device.is_some() {
+                    0.0
+                } else {
+                    -100.0
+                }),
+            )
             .await;
 
         for (category, name, value) in [
@@ -195,10 +219,12 @@ impl VideoRecorder for ObsSocketRecorder {
             .wrap_err("Failed to get FilePath")?;
         tracing::info!("OBS confirmed recording path: {:?}", current_path.value);
 
-        // Set video settings
+        // Set video settings. The canvas only needs to fit what's left of the game after cropping.
+        let crop = crop.unwrap_or_default();
+        let (base_width, base_height) = crop.apply(game_resolution);
         config
             .set_video_settings(SetVideoSettings {
-                fps_numerator: Some(FPS),
+                fps_numerator: Some(fps),
                 fps_denominator: Some(1),
                 base_width: Some(base_width),
                 base_height: Some(base_height),
@@ -234,7 +260,13 @@ impl VideoRecorder for ObsSocketRecorder {
                     }),
                     alignment: None,
                     bounds: None,
-                    crop: None,
+                    // Always set, so that a crop from an earlier recording doesn't linger
+                    crop: Some(scene_items::Crop {
+                        left: Some(crop.left),
+                        top: Some(crop.top),
+                        right: Some(crop.right),
+                        bottom: Some(crop.bottom),
+                    }),
                 },
             })
             .await
@@ -269,13 +301,22 @@ impl VideoRecorder for ObsSocketRecorder {
             .wrap_err("Failed to start recording")?;
         tracing::info!("OBS recording started successfully");
 
+        self.output_frames_at_start = match client.general().stats().await {
+            Ok(stats) => Some((stats.output_skipped_frames, stats.output_total_frames)),
+            Err(e) => {
+                tracing::warn!(e=?e, "Failed to get OBS stats, skipped frames won't be tracked");
+                None
+            }
+        };
+
         self.client = Some(client);
 
         Ok(())
     }
 
-    async fn stop_recording(&mut self) -> Result<serde_json::Value> {
+    async fn stop(&mut self) -> Result<serde_json::Value> {
         tracing::info!("Stopping OBS recording");
+        self.output_frames_at_start = None;
         if let Some(client) = &self.client {
             // Log, but do not explode if it fails
             if let Err(e) = client.recording().stop().await {
@@ -285,6 +326,51 @@ impl VideoRecorder for ObsSocketRecorder {
         tracing::info!("OBS recording stopped successfully");
         Ok(serde_json::Value::Null)
     }
+
+    async fn set_paused(&mut self, paused: bool) -> Result<()> {
+        let client = self.client.as_ref().ok_or_eyre("Not connected to OBS")?;
+        if paused {
+            client
+                .recording()
+                .pause()
+                .await
+                .wrap_err("Failed to pause recording")?;
+        } else {
+            client
+                .recording()
+                .resume()
+                .await
+                .wrap_err("Failed to resume recording")?;
+        }
+        Ok(())
+    }
+
+    async fn skipped_frames(&mut self) -> Result<Option<SkippedFrames>> {
+        let (Some(client), Some((skipped_at_start, total_at_start))) =
+            (&self.client, self.output_frames_at_start)
+        else {
+            return Ok(None);
+        };
+        let stats = client
+            .general()
+            .stats()
+            .await
+            .wrap_err("Failed to get OBS stats")?;
+        Ok(Some(SkippedFrames {
+            skipped: stats.output_skipped_frames.saturating_sub(skipped_at_start) as usize,
+            total: stats.output_total_frames.saturating_sub(total_at_start) as usize,
+        }))
+    }
+
+    async fn next_frame(&mut self) -> Result<Option<PreviewFrame>> {
+        // OBS Studio already shows what it's recording in its own window
+        Ok(None)
+    }
+
+    async fn is_capturing(&mut self) -> Result<Option<bool>> {
+        // OBS Studio doesn't say whether its game capture source has hooked anything
+        Ok(None)
+    }
 }
 impl Drop for ObsSocketRecorder {
     fn drop(&mut self) {
@@ -0,0 +1,216 @@
+//! Reads a finished recording back, so that its inputs can be played through alongside the video to check
+//! that they were captured properly before it's uploaded.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    Result,
+    eyre::{ContextCompat as _, WrapErr as _},
+};
+use constants::filename::recording::{INPUTS, METADATA};
+
+use crate::{
+    encryption::{self, RecordingKeys},
+    input_track,
+    output_types::{InputEvent, InputEventType, Metadata},
+};
+
+/// How far back mouse movement is summed up to show which way the mouse is going
+const MOVEMENT_WINDOW: f64 = 0.1;
+
+/// A recording's inputs, with times in seconds from the start of the video
+#[derive(Debug, Clone)]
+pub struct Playback {
+    /// `None` if there's no video, or if it's encrypted, as video players couldn't open it
+    pub video_path: Option<PathBuf>,
+    pub game_resolution: Option<(u32, u32)>,
+    /// Length of the video in seconds
+    pub duration: f64,
+    /// Where the cursor moved to, relative to the game window
+    cursor: Vec<(f64, (i32, i32))>,
+    movement: Vec<(f64, (i32, i32))>,
+    /// Key and mouse button presses and releases
+    presses: Vec<(f64, Press)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Button {
+    Key(u16),
+    Mouse(u16),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    button: Button,
+    pressed: bool,
+}
+
+/// What the inputs were doing at a point in the recording
+#[derive(Debug, Default, PartialEq)]
+pub struct InputState {
+    /// Where the cursor last moved to, relative to the game window
+    pub cursor: Option<(i32, i32)>,
+    /// Mouse movement over the last [`MOVEMENT_WINDOW`]
+    pub movement: (i32, i32),
+    pub held: BTreeSet<Button>,
+}
+
+impl Playback {
+    pub fn load(folder_path: &Path, keys: &RecordingKeys) -> Result<Self> {
+        let metadata: Metadata = serde_json::from_str(
+            &std::fs::read_to_string(folder_path.join(METADATA))
+                .wrap_err("failed to read metadata")?,
+        )
+        .wrap_err("failed to parse metadata")?;
+        let events = input_track::read_file(&folder_path.join(INPUTS), keys)?;
+        // Same as the uploader: the video isn't always named the same
+        let video_path = folder_path
+            .read_dir()?
+            .flatten()
+            .map(|e| e.path())
+            .find(|p| p.extension().and_then(|e| e.to_str()) == Some("mp4"))
+            .filter(|p| !encryption::is_encrypted(p).unwrap_or(true));
+
+        let mut playback = Self::from_events(&events)?;
+        playback.video_path = video_path;
+        playback.game_resolution = metadata.game_resolution;
+        Ok(playback)
+    }
+
+    fn from_events(events: &[InputEvent]) -> Result<Self> {
+        let start = events
+            .iter()
+            .find(|e| e.event == InputEventType::Start)
+            .context("the inputs have no start")?
+            .timestamp;
+        let mut playback = Self {
+            video_path: None,
+            game_resolution: None,
+            duration: 0.0,
+            cursor: vec![],
+            movement: vec![],
+            presses: vec![],
+        };
+        for event in events {
+            let time = event.timestamp - start;
+            if time < 0.0 {
+                continue;
+            }
+            match event.event {
+                InputEventType::End => {
+                    playback.duration = time;
+                    break;
+                }
+                InputEventType::CursorPosition {
+                    window_x, window_y, ..
+                } => playback.cursor.push((time, (window_x, window_y))),
+                InputEventType::MouseMove { dx, dy } => playback.movement.push((time, (dx, dy))),
+                InputEventType::Keyboard { key, pressed } => playback.presses.push((
+                    time,
+                    Press {
+                        button: Button::Key(key),
+                        pressed,
+                    },
+                )),
+                InputEventType::MouseButton { button, pressed } => playback.presses.push((
+                    time,
+                    Press {
+                        button: Button::Mouse(button),
+                        pressed,
+                    },
+                )),
+                _ => {}
+            }
+            playback.duration = time;
+        }
+        Ok(playback)
+    }
+
+    /// The state of the inputs `time` seconds into the video
+    pub fn state_at(&self, time: f64) -> InputState {
+        let cursor = self.cursor.partition_point(|(t, _)| *t <= time);
+        let movement = self.movement[self
+            .movement
+            .partition_point(|(t, _)| *t <= time - MOVEMENT_WINDOW)
+            ..self.movement.partition_point(|(t, _)| *t <= time)]
+            .iter()
+            .fold((0, 0), |(x, y), (_, (dx, dy))| (x + dx, y + dy));
+
+        let mut held = BTreeSet::new();
+        for (_, press) in self.presses.iter().take_while(|(t, _)| *t <= time) {
+            if press.pressed {
+                held.insert(press.button);
+            } else {
+                held.remove(&press.button);
+            }
+        }
+
+        InputState {
+            cursor: cursor.checked_sub(1).map(|i| self.cursor[i].1),
+            movement,
+            held,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_at() {
+        let events = [
+            (100.0, InputEventType::Start),
+            (
+                100.5,
+                InputEventType::Keyboard {
+                    key: 87,
+                    pressed: true,
+                },
+            ),
+            (100.55, InputEventType::MouseMove { dx: 4, dy: -2 }),
+            (
+                100.55,
+                InputEventType::CursorPosition {
+                    screen_x: 110,
+                    screen_y: 40,
+                    window_x: 10,
+                    window_y: 20,
+                },
+            ),
+            (100.6, InputEventType::MouseMove { dx: 1, dy: 0 }),
+            (
+                101.0,
+                InputEventType::Keyboard {
+                    key: 87,
+                    pressed: false,
+                },
+            ),
+            (102.0, InputEventType::End),
+        ]
+        .map(|(timestamp, event)| InputEvent::new(timestamp, event));
+        let playback = Playback::from_events(&events).unwrap();
+
+        assert_eq!(playback.duration, 2.0);
+        assert_eq!(playback.state_at(0.2), InputState::default());
+        assert_eq!(
+            playback.state_at(0.6),
+            InputState {
+                cursor: Some((10, 20)),
+                movement: (5, -2),
+                held: [Button::Key(87)].into(),
+            }
+        );
+        assert_eq!(
+            playback.state_at(1.5),
+            InputState {
+                cursor: Some((10, 20)),
+                movement: (0, 0),
+                held: BTreeSet::new(),
+            }
+        );
+    }
+}
@@ -1,54 +1,75 @@
 use std::{
     path::{Path, PathBuf},
-    sync::Arc,
-    time::Instant,
+    sync::{Arc, atomic::Ordering},
+    time::{Duration, Instant},
 };
 
 use color_eyre::{
     Result,
     eyre::{Context as _, OptionExt as _, bail},
 };
-use egui_wgpu::wgpu::DeviceType;
 use windows::Win32::Foundation::HWND;
 
 use crate::{
-    app_state::{AppState, RecordingStatus},
-    config::{EncoderSettings, RecordingBackend},
+    app_state::{AppState, AsyncRequest, RecordingStatus, UiUpdate},
+    encryption,
+    output_types::SkippedFrames,
     record::{
-        obs_embedded_recorder::ObsEmbeddedRecorder, obs_socket_recorder::ObsSocketRecorder,
-        recording::Recording,
+        benchmark::BenchmarkSettings,
+        capture_backend::{self, CaptureBackend},
+        capture_monitor,
+        naming::RecordingName,
+        recording::{self, Recording, Segment},
+        replay::ReplayBuffer,
+        summary::RecordingSummary,
+    },
+    system::{
+        audio_devices::DEFAULT_AUDIO_DEVICE_ID,
+        capture_permission::{self, CaptureDenied},
+        hardware_specs,
     },
     ui::notification::{NotificationType, show_notification},
 };
-use constants::{MIN_FREE_SPACE_MB, unsupported_games::UnsupportedGames};
+use constants::{
+    MIN_FREE_RECORDING_TIME, SKIPPED_FRAMES_WARNING_PERCENTAGE, encoding::VideoEncoderType,
+    unsupported_games::UnsupportedGames,
+};
 
-#[async_trait::async_trait(?Send)]
-pub trait VideoRecorder {
-    fn id(&self) -> &'static str;
+/// Too few frames make for a misleading percentage, so don't warn before this many have been recorded
+const MIN_FRAMES_FOR_SKIPPED_FRAMES_WARNING: usize = 300;
+/// What the game recorded by [`Recorder::for_test`] is called
+#[cfg(test)]
+pub(crate) const TEST_GAME_EXE: &str = "owl-test-game.exe";
+/// Games often go through a few resolutions when switching modes, so a new one has to last this long before
+/// a new segment is started for it
+const RESOLUTION_CHANGE_SETTLE_TIME: Duration = Duration::from_secs(2);
 
-    async fn start_recording(
-        &mut self,
-        dummy_video_path: &Path,
-        pid: u32,
-        hwnd: HWND,
-        game_exe: &str,
-        video_settings: EncoderSettings,
-        game_resolution: (u32, u32),
-    ) -> Result<()>;
-    /// Result contains any additional metadata the recorder wants to return about the recording
-    /// If this returns an error, the recording will be invalidated with the error message
-    async fn stop_recording(&mut self) -> Result<serde_json::Value>;
-}
 pub struct Recorder {
-    recording_dir: Box<dyn FnMut() -> PathBuf>,
+    recording_dir: Box<dyn FnMut(&RecordingName) -> PathBuf>,
     recording: Option<Recording>,
     app_state: Arc<AppState>,
-    video_recorder: Box<dyn VideoRecorder>,
+    capture_backend: Box<dyn CaptureBackend>,
+    /// Whether the user has already been told that the current recording is dropping too many frames
+    warned_about_skipped_frames: bool,
+    /// Frame rate the session has been lowered to in low impact mode, because it was dropping frames
+    reduced_fps: Option<u32>,
+    /// Set while recording for instant replay rather than recording outright
+    replay: Option<ReplayBuffer>,
+    /// A resolution the game has changed to, and when it was first seen, while waiting for it to settle
+    pending_resolution: Option<((u32, u32), Instant)>,
+    /// Set once the current segment is known to be capturing the game, so that it needn't be asked again
+    capture_confirmed: bool,
+    /// Settings the current recording is a benchmark run at. Benchmark runs are thrown away once they finish.
+    benchmark_run: Option<BenchmarkSettings>,
+    /// Segments of the current session that have finished, to show the user once it ends
+    session_summaries: Vec<RecordingSummary>,
+    /// Finds the game to record: the one in the foreground, other than in tests
+    find_game: Box<dyn Fn() -> Result<Option<(String, game_process::Pid, HWND)>>>,
 }
 
 impl Recorder {
     pub async fn new(
-        recording_dir: Box<dyn FnMut() -> PathBuf>,
+        recording_dir: Box<dyn FnMut(&RecordingName) -> PathBuf>,
         app_state: Arc<AppState>,
     ) -> Result<Self> {
         let backend = app_state
@@ -57,39 +78,54 @@ impl Recorder {
             .unwrap()
             .preferences
             .recording_backend;
+        let capture_backend = capture_backend::create(backend, &app_state.adapter_infos).await?;
+        Ok(Self::with_capture_backend(
+            recording_dir,
+            app_state,
+            capture_backend,
+        ))
+    }
 
-        // Incredibly ugly hack: assume that the first dGPU is the one we want,
-        // and that this list agrees with OBS's. There's no real guarantee that
-        // this is the case, and that the target game is even running on the dGPU,
-        // but it's a first-pass solution for now.
-        //
-        // TODO: Investigate what OBS actually does here. I spent over an hour
-        // pouring through the OBS source code and couldn't find anything of
-        // note with regards to how it chooses the adapter; I might have to
-        // reach out to an OBS developer if this becomes an issue again.
-        let adapter_index = app_state
-            .adapter_infos
-            .iter()
-            .position(|a| a.device_type == DeviceType::DiscreteGpu)
-            .unwrap_or_default();
-
-        tracing::info!(
-            "Initializing recorder with adapter index {adapter_index} ({:?})",
-            app_state.adapter_infos[adapter_index]
-        );
-
-        let video_recorder: Box<dyn VideoRecorder> = match backend {
-            RecordingBackend::Embedded => Box::new(ObsEmbeddedRecorder::new(adapter_index).await?),
-            RecordingBackend::Socket => Box::new(ObsSocketRecorder::new().await?),
-        };
-
-        tracing::info!("Using {} as video recorder", video_recorder.id());
-        Ok(Self {
+    /// Uses `capture_backend` instead of the one picked by the recording backend setting
+    pub fn with_capture_backend(
+        recording_dir: Box<dyn FnMut(&RecordingName) -> PathBuf>,
+        app_state: Arc<AppState>,
+        capture_backend: Box<dyn CaptureBackend>,
+    ) -> Self {
+        tracing::info!("Using {} as capture backend", capture_backend.id());
+        Self {
             recording_dir,
             recording: None,
             app_state,
-            video_recorder,
-        })
+            capture_backend,
+            warned_about_skipped_frames: false,
+            reduced_fps: None,
+            replay: None,
+            pending_resolution: None,
+            capture_confirmed: false,
+            benchmark_run: None,
+            session_summaries: vec![],
+            find_game: Box::new(get_foregrounded_game),
+        }
+    }
+
+    /// Records `window` with a [`capture_backend::mock::MockCaptureBackend`], as if it belonged to a game in the
+    /// foreground. Each segment goes into a folder in `recording_location` named after its index.
+    #[cfg(test)]
+    pub(crate) fn for_test(
+        app_state: Arc<AppState>,
+        recording_location: &Path,
+        window: HWND,
+    ) -> Self {
+        let recording_location = recording_location.to_path_buf();
+        let mut recorder = Self::with_capture_backend(
+            Box::new(move |name| recording_location.join(name.segment_index.to_string())),
+            app_state,
+            Box::new(capture_backend::mock::MockCaptureBackend::default()),
+        );
+        let pid = game_process::Pid(std::process::id());
+        recorder.find_game = Box::new(move || Ok(Some((TEST_GAME_EXE.to_string(), pid, window))));
+        recorder
     }
 
     pub fn recording(&self) -> Option<&Recording> {
@@ -100,29 +136,185 @@ impl Recorder {
         if self.recording.is_some() {
             return Ok(());
         }
+        self.reduced_fps = None;
+        self.session_summaries.clear();
 
-        let recording_location = (self.recording_dir)();
+        let replay_length = self
+            .app_state
+            .config
+            .read()
+            .unwrap()
+            .preferences
+            .instant_replay_minutes
+            .map(|minutes| Duration::from_secs(minutes as u64 * 60));
+        // Carry on with the same buffer if recording is only resuming, e.g. after being idle
+        if self.replay.as_ref().map(|r| r.length()) != replay_length {
+            self.replay = replay_length
+                .map(|length| ReplayBuffer::new(&self.app_state.recording_location(), length));
+        }
 
-        std::fs::create_dir_all(&recording_location)
-            .wrap_err("Failed to create directory for recording. Did you install OWL Control to a location where your account is allowed to write files?")?;
+        self.start_segment(unsupported_games, Segment::first())
+            .await
+    }
 
-        let free_space_mb = get_free_space_in_mb(&recording_location);
-        if let Some(free_space_mb) = free_space_mb
-            && free_space_mb < MIN_FREE_SPACE_MB
-        {
+    /// Records the foregrounded game at `settings` for the benchmark, instead of the configured ones
+    pub async fn start_benchmark_run(
+        &mut self,
+        unsupported_games: &UnsupportedGames,
+        settings: BenchmarkSettings,
+    ) -> Result<()> {
+        if self.recording.is_some() {
+            bail!("Stop recording before running the benchmark.");
+        }
+        self.benchmark_run = Some(settings);
+        let result = self
+            .start_segment(unsupported_games, Segment::first())
+            .await;
+        if result.is_err() {
+            self.benchmark_run = None;
+        }
+        result
+    }
+
+    /// Finishes the current benchmark run, returning how many frames it dropped if the recorder can tell
+    pub async fn finish_benchmark_run(&mut self) -> Result<Option<SkippedFrames>> {
+        if self.benchmark_run.is_none() {
+            bail!("No benchmark run is in progress");
+        }
+        let skipped_frames = self.capture_backend.skipped_frames().await?;
+        self.stop().await?;
+        Ok(skipped_frames)
+    }
+
+    /// What was recorded in the session that last ended. Instant replay's segments aren't included, as
+    /// they aren't kept unless saved.
+    pub fn take_session_summaries(&mut self) -> Vec<RecordingSummary> {
+        std::mem::take(&mut self.session_summaries)
+    }
+
+    pub fn is_benchmarking(&self) -> bool {
+        self.benchmark_run.is_some()
+    }
+
+    /// Whether recordings are only being kept for instant replay, until they're saved
+    pub fn is_replay(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Saves what's in the instant replay buffer as a recording, along with the segment being recorded
+    /// once it finishes. Returns how many segments were saved straight away.
+    pub fn save_replay(&mut self) -> Result<usize> {
+        let Some(replay) = self.replay.as_mut() else {
+            bail!("Instant replay isn't running");
+        };
+        let saved = replay.save(
+            &self.app_state.recording_location(),
+            self.recording.is_some(),
+        )?;
+        for folder_path in &saved {
+            self.finished(folder_path.clone());
+        }
+        Ok(saved.len())
+    }
+
+    /// Encrypts a recording that has just been finished, if recordings are encrypted at rest, then queues it for
+    /// upload if the user wants them uploaded straight away
+    fn finished(&self, folder_path: PathBuf) {
+        let app_state = self.app_state.clone();
+        // Takes a moment for a long recording, which mustn't hold up the next segment
+        tokio::task::spawn_blocking(move || {
+            match app_state
+                .recording_keys()
+                .and_then(|keys| encryption::encrypt_recording(&folder_path, &keys))
+            {
+                Ok(true) => tracing::info!("Encrypted {}", folder_path.display()),
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::error!(e=?e, "Failed to encrypt {}", folder_path.display());
+                    show_notification(
+                        "OWL Control - Error",
+                        &format!(
+                            "Failed to encrypt the recording, so it's been left unencrypted: {e:#}"
+                        ),
+                        "",
+                        NotificationType::Error,
+                    );
+                }
+            }
+
+            if !app_state.config.read().unwrap().preferences.auto_upload {
+                return;
+            }
+            tracing::info!("Queueing {} for upload", folder_path.display());
+            if let Err(e) = app_state
+                .async_request_tx
+                .try_send(AsyncRequest::UploadRecording(folder_path))
+            {
+                tracing::warn!(e=?e, "Failed to queue finished recording for upload");
+            }
+        });
+    }
+
+    /// Finishes the current segment of the recording and starts the next one, continuing the same session.
+    /// Does nothing if there is no recording.
+    pub async fn start_next_segment(&mut self, unsupported_games: &UnsupportedGames) -> Result<()> {
+        let Some(recording) = self.recording.as_ref() else {
+            return Ok(());
+        };
+        let segment = recording.next_segment();
+        self.stop().await?;
+        self.start_segment(unsupported_games, segment).await
+    }
+
+    async fn start_segment(
+        &mut self,
+        unsupported_games: &UnsupportedGames,
+        segment: Segment,
+    ) -> Result<()> {
+        // Anything recorded would only be black frames until the user has dealt with it
+        if let Some(denied) = self.app_state.capture_denied.read().unwrap().as_ref() {
+            bail!("{denied}");
+        }
+
+        let (min_free_space_mb, bitrate, capture_window, capture_monitor, consent_version) = {
+            let config = self.app_state.config.read().unwrap();
+            (
+                config.preferences.min_free_space_mb,
+                config.preferences.encoder.bitrate,
+                config.preferences.capture_window.clone(),
+                config.preferences.capture_monitor.clone(),
+                config
+                    .credentials
+                    .has_consented
+                    .then_some(config.credentials.consent_version)
+                    .flatten(),
+            )
+        };
+        // The UI won't let anyone this far without consenting, but nothing gets captured without it regardless
+        let Some(consent_version) = consent_version else {
             bail!(
-                "There is not enough free space on the disk to record. Please free up some space. Required: at least {MIN_FREE_SPACE_MB} MB, available: {free_space_mb} MB"
+                "You need to accept the data collection terms before recording. Please open OWL Control to review them."
             );
-        }
+        };
 
         let Some((game_exe, pid, hwnd)) =
-            get_foregrounded_game().wrap_err("failed to get foregrounded game")?
+            (self.find_game)().wrap_err("failed to get foregrounded game")?
         else {
             bail!(
                 "You do not have a game window in focus. Please focus on a game window and try again."
             );
         };
 
+        if let Some(capture_window) = capture_window
+            && !capture_window.matches(&game_exe)
+        {
+            bail!(
+                "Only {} ({}) is recorded, but {game_exe} is in the foreground. Switch to it, or change the capture window in the settings.",
+                capture_window.title,
+                capture_window.exe
+            );
+        }
+
         let game_exe_without_extension = game_exe
             .split('.')
             .next()
@@ -143,6 +335,68 @@ impl Recorder {
             );
         }
 
+        // Checked once per session, so that a game that's moved mid-session doesn't cut it short
+        if let Some(capture_monitor) = capture_monitor
+            && segment.index() == 0
+        {
+            let monitors = hardware_specs::get_monitors();
+            if let Some(resolved) = capture_monitor::resolve(&capture_monitor, &monitors) {
+                if resolved.fell_back {
+                    tracing::warn!(
+                        capture_monitor,
+                        fallback = resolved.monitor.name,
+                        "Capture monitor isn't connected, using the primary monitor"
+                    );
+                    show_notification(
+                        "OWL Control - Capture monitor not found",
+                        &format!(
+                            "{capture_monitor} isn't connected, so games on {} are being recorded instead.",
+                            resolved.monitor.name
+                        ),
+                        "",
+                        NotificationType::Info,
+                    );
+                }
+                capture_monitor::check_game_on_monitor(
+                    &game_exe,
+                    hardware_specs::get_window_monitor_name(hwnd).as_deref(),
+                    resolved.monitor,
+                )?;
+            }
+        }
+
+        if let Err(denied) = capture_permission::check(pid, &game_exe) {
+            tracing::warn!(game_exe, "Game can't be captured: {denied:?}");
+            let message = denied.to_string();
+            self.deny_capture(denied);
+            bail!(message);
+        }
+
+        // Named now that the game is known, as it can go into the folder's name
+        let recording_location = (self.recording_dir)(&RecordingName {
+            started_at: chrono::Local::now(),
+            game: &recording::game_name(&game_exe, game_process::window_title(hwnd).as_deref()),
+            segment_index: segment.index(),
+        });
+        let recording_location = match &self.replay {
+            Some(replay) if self.benchmark_run.is_none() => replay.segment_dir(&recording_location),
+            _ => recording_location,
+        };
+
+        std::fs::create_dir_all(&recording_location)
+            .wrap_err("Failed to create directory for recording. Did you install OWL Control to a location where your account is allowed to write files?")?;
+
+        let required_space_mb =
+            min_free_space_mb + estimated_recording_size_mb(MIN_FREE_RECORDING_TIME, bitrate);
+        let free_space_mb = get_free_space_in_mb(&recording_location);
+        if let Some(free_space_mb) = free_space_mb
+            && free_space_mb < required_space_mb
+        {
+            bail!(
+                "There is not enough free space on the disk to record. Please free up some space. Required: at least {required_space_mb} MB, available: {free_space_mb} MB"
+            );
+        }
+
         tracing::info!(
             game_exe,
             ?pid,
@@ -151,22 +405,76 @@ impl Recorder {
             "Starting recording"
         );
 
-        let video_settings = self
+        let (
+            mut video_settings,
+            fps,
+            input_rate_hz,
+            capture_region,
+            control_keys,
+            desktop_audio_device,
+            low_impact,
+        ) = {
+            let preferences = &self.app_state.config.read().unwrap().preferences;
+            (
+                preferences.encoder.clone(),
+                preferences.fps,
+                preferences.input_rate_hz,
+                preferences.capture_region.clone(),
+                preferences.hotkey_keycodes(),
+                preferences
+                    .desktop_audio
+                    .then(|| preferences.desktop_audio_device.clone()),
+                preferences.low_impact,
+            )
+        };
+        // x264 encodes on the CPU, competing with the game; hardware encoders have their own silicon
+        if low_impact
+            && video_settings.encoder == VideoEncoderType::X264
+            && let Some(&encoder) = self
+                .app_state
+                .available_video_encoders
+                .iter()
+                .find(|&&e| e != VideoEncoderType::X264)
+        {
+            tracing::info!("Low impact mode is on, using {encoder} instead of x264");
+            video_settings.encoder = encoder;
+            video_settings.codec = Default::default();
+        }
+        let fps = self.reduced_fps.filter(|&r| r < fps).unwrap_or(fps);
+        let fps = match self.benchmark_run {
+            Some(settings) => {
+                video_settings.encoder = settings.encoder;
+                video_settings.codec = settings.codec;
+                settings.fps
+            }
+            None => fps,
+        };
+        let desktop_audio_device =
+            desktop_audio_device.and_then(|device| self.resolve_desktop_audio_device(device));
+        let user_id = self
             .app_state
-            .config
+            .valid_api_key_and_user_id
             .read()
             .unwrap()
-            .preferences
-            .encoder
-            .clone();
+            .as_ref()
+            .map(|(_, user_id)| user_id.clone());
 
+        let first_segment = segment.is_first();
         let recording = Recording::start(
-            self.video_recorder.as_mut(),
+            self.capture_backend.as_mut(),
             recording_location.clone(),
             game_exe.clone(),
             pid,
             hwnd,
             video_settings,
+            fps,
+            input_rate_hz,
+            capture_region,
+            control_keys,
+            desktop_audio_device,
+            user_id,
+            Some(consent_version),
+            segment,
         )
         .await;
 
@@ -177,22 +485,87 @@ impl Recorder {
                 return Err(e);
             }
         };
+        if let Err(e) = recording
+            .write_provisional_metadata(self.capture_backend.id(), &self.app_state.adapter_infos)
+            .await
+        {
+            tracing::warn!(e=?e, "Failed to write provisional metadata, recording can't be recovered if interrupted");
+        }
+        // Benchmark runs are deleted as soon as they finish, but shouldn't be uploaded before then either
+        let test =
+            self.app_state.test_recording.load(Ordering::Relaxed) || self.benchmark_run.is_some();
+        if test {
+            // Written before anything else can pick the recording up, so that it's never uploaded
+            tokio::fs::write(
+                recording_location.join(constants::filename::recording::TEST),
+                "",
+            )
+            .await
+            .wrap_err("failed to mark the recording as a test")?;
+        }
 
-        show_notification(
-            "Started recording",
-            &format!("Recording `{game_exe}`"),
-            "",
-            NotificationType::Info,
-        );
+        match &self.replay {
+            // The benchmark tells the user about its own progress
+            _ if self.benchmark_run.is_some() => {}
+            // Replay segments come and go all the time, so only the first is worth telling the user about
+            Some(replay) if first_segment => show_notification(
+                "Instant replay started",
+                &format!(
+                    "Keeping the last {} minutes of `{game_exe}`. Press your save replay hotkey to keep them.",
+                    replay.length().as_secs() / 60
+                ),
+                "",
+                NotificationType::Info,
+            ),
+            Some(_) => {}
+            None if test => show_notification(
+                "Started test recording",
+                &format!("Recording `{game_exe}`. This recording won't be uploaded."),
+                "",
+                NotificationType::Info,
+            ),
+            None => show_notification(
+                "Started recording",
+                &format!("Recording `{game_exe}`"),
+                "",
+                NotificationType::Info,
+            ),
+        }
 
         self.recording = Some(recording);
+        self.warned_about_skipped_frames = false;
+        self.pending_resolution = None;
+        self.capture_confirmed = false;
         *self.app_state.state.write().unwrap() = RecordingStatus::Recording {
             start_time: Instant::now(),
             game_exe,
+            paused_at: None,
+            skipped_frames: None,
         };
         Ok(())
     }
 
+    /// Works out which device desktop audio should be recorded from, given the configured one
+    fn resolve_desktop_audio_device(&self, configured: Option<String>) -> Option<String> {
+        let devices = &self.app_state.audio_output_devices;
+        if devices.is_empty() {
+            tracing::warn!(
+                "Desktop audio is enabled, but there are no audio output devices to record from"
+            );
+            return None;
+        }
+        match configured {
+            Some(id) if devices.iter().any(|d| d.id == id) => Some(id),
+            Some(id) => {
+                tracing::warn!(
+                    "Audio output device {id} is no longer available, recording desktop audio from the default device"
+                );
+                Some(DEFAULT_AUDIO_DEVICE_ID.to_string())
+            }
+            None => Some(DEFAULT_AUDIO_DEVICE_ID.to_string()),
+        }
+    }
+
     pub async fn seen_input(&mut self, e: input_capture::Event) -> Result<()> {
         let Some(recording) = self.recording.as_mut() else {
             return Ok(());
@@ -210,29 +583,340 @@ impl Recorder {
         Ok(())
     }
 
-    pub async fn stop(&mut self) -> Result<()> {
-        let Some(recording) = self.recording.take() else {
+    /// Pauses the current recording if it's running, or resumes it if it's paused.
+    /// Does nothing if there is no recording.
+    pub async fn toggle_pause(&mut self) -> Result<()> {
+        let Some(recording) = self.recording.as_mut() else {
             return Ok(());
         };
 
+        let paused = !recording.is_paused();
+        if paused {
+            recording.pause(self.capture_backend.as_mut()).await?;
+        } else {
+            recording.resume(self.capture_backend.as_mut()).await?;
+        }
+
+        if let RecordingStatus::Recording {
+            start_time,
+            paused_at,
+            ..
+        } = &mut *self.app_state.state.write().unwrap()
+        {
+            match paused_at.take() {
+                // Push the start time forward so that the displayed duration excludes the pause
+                Some(paused_at) if !paused => *start_time += paused_at.elapsed(),
+                _ if paused => *paused_at = Some(Instant::now()),
+                _ => {}
+            }
+        }
+
         show_notification(
-            "Stopped recording",
-            &format!("No longer recording `{}`", recording.game_exe()),
+            if paused {
+                "Paused recording"
+            } else {
+                "Resumed recording"
+            },
+            &format!("`{}`", recording.game_exe()),
             "",
             NotificationType::Info,
         );
 
-        recording
-            .stop(self.video_recorder.as_mut(), &self.app_state.adapter_infos)
+        self.app_state
+            .ui_update_tx
+            .try_send(UiUpdate::ForceUpdate)
+            .ok();
+
+        tracing::info!(paused, "Recording pause toggled");
+        Ok(())
+    }
+
+    /// Checks how many frames the current recording has dropped and shows it in the UI, warning the user
+    /// (once per recording) if it's becoming too many. In low impact mode, the recording is instead continued
+    /// at a lower frame rate. Does nothing if there is no recording.
+    pub async fn update_skipped_frames(
+        &mut self,
+        unsupported_games: &UnsupportedGames,
+    ) -> Result<()> {
+        let Some(recording) = self.recording.as_ref() else {
+            return Ok(());
+        };
+        let Some(skipped_frames) = self.capture_backend.skipped_frames().await? else {
+            return Ok(());
+        };
+
+        if let RecordingStatus::Recording {
+            skipped_frames: status_skipped_frames,
+            ..
+        } = &mut *self.app_state.state.write().unwrap()
+        {
+            *status_skipped_frames = Some(skipped_frames);
+        }
+        // Dropping frames is what the benchmark is finding out about, so it's left to deal with it
+        if self.benchmark_run.is_some() {
+            return Ok(());
+        }
+
+        let percentage = skipped_frames.percentage();
+        if !self.warned_about_skipped_frames
+            && skipped_frames.total >= MIN_FRAMES_FOR_SKIPPED_FRAMES_WARNING
+            && percentage > SKIPPED_FRAMES_WARNING_PERCENTAGE
+        {
+            self.warned_about_skipped_frames = true;
+            tracing::warn!(
+                skipped = skipped_frames.skipped,
+                total = skipped_frames.total,
+                "Recording is dropping frames"
+            );
+
+            let (fps, low_impact) = {
+                let preferences = &self.app_state.config.read().unwrap().preferences;
+                (
+                    self.reduced_fps.unwrap_or(preferences.fps),
+                    preferences.low_impact,
+                )
+            };
+            if low_impact && let Some(lower_fps) = lower_fps(fps) {
+                let game_exe = recording.game_exe().to_string();
+                tracing::info!("Low impact mode is on, continuing at {lower_fps} FPS");
+                self.reduced_fps = Some(lower_fps);
+                // The frame rate can only be changed by starting a new output
+                self.start_next_segment(unsupported_games).await?;
+                show_notification(
+                    "OWL Control - Lowered frame rate",
+                    &format!(
+                        "Recording `{game_exe}` was slowing down. It will carry on at {lower_fps} FPS for the rest of this session."
+                    ),
+                    "",
+                    NotificationType::Info,
+                );
+                return Ok(());
+            }
+            show_notification(
+                "OWL Control - Dropping frames",
+                &format!(
+                    "Recording `{}` has dropped {percentage:.1}% of its frames. Try recording at a lower frame rate or with a different encoder.",
+                    recording.game_exe()
+                ),
+                "",
+                NotificationType::Error,
+            );
+        }
+        Ok(())
+    }
+
+    /// Starts a new segment once the game has settled on a different resolution from the one the current
+    /// segment is being recorded at, so that no video mixes frames from both. Returns whether it did.
+    /// Does nothing if there is no recording.
+    pub async fn check_resolution_change(
+        &mut self,
+        unsupported_games: &UnsupportedGames,
+    ) -> Result<bool> {
+        let Some(recording) = self.recording.as_ref() else {
+            return Ok(false);
+        };
+        // Runs are too short to be worth splitting
+        if self.benchmark_run.is_some() {
+            return Ok(false);
+        }
+        let Some(resolution) = recording.changed_resolution() else {
+            self.pending_resolution = None;
+            return Ok(false);
+        };
+        match self.pending_resolution {
+            Some((pending, since))
+                if pending == resolution && since.elapsed() >= RESOLUTION_CHANGE_SETTLE_TIME => {}
+            Some((pending, _)) if pending == resolution => return Ok(false),
+            _ => {
+                self.pending_resolution = Some((resolution, Instant::now()));
+                return Ok(false);
+            }
+        }
+
+        tracing::info!(
+            ?resolution,
+            "Game changed resolution, starting next segment"
+        );
+        let segment = recording.next_segment_at_resolution(resolution);
+        self.stop().await?;
+        self.start_segment(unsupported_games, segment).await?;
+        Ok(true)
+    }
+
+    /// Checks that the game is actually being captured, once it's had [`capture_permission::HOOK_TIMEOUT`] to be.
+    /// If it isn't, the recording should be stopped, as it's only getting black frames, and no more are started
+    /// until the user has fixed it; why is returned. Does nothing if there is no recording, or the recorder can't tell.
+    pub async fn check_capture(&mut self) -> Result<Option<CaptureDenied>> {
+        let Some(recording) = self.recording.as_ref() else {
+            return Ok(None);
+        };
+        if self.capture_confirmed
+            || recording.start_instant().elapsed() < capture_permission::HOOK_TIMEOUT
+        {
+            return Ok(None);
+        }
+        if self.capture_backend.is_capturing().await? != Some(false) {
+            self.capture_confirmed = true;
+            return Ok(None);
+        }
+
+        let denied = CaptureDenied::NotHooked {
+            game_exe: recording.game_exe().to_string(),
+        };
+        tracing::warn!(
+            game_exe = recording.game_exe(),
+            "Nothing has been captured from the game since the recording started"
+        );
+        self.deny_capture(denied.clone());
+        Ok(Some(denied))
+    }
+
+    /// Holds back recording until the user has done something about the game not being capturable
+    fn deny_capture(&self, denied: CaptureDenied) {
+        *self.app_state.capture_denied.write().unwrap() = Some(denied);
+        self.app_state
+            .ui_update_tx
+            .try_send(UiUpdate::ForceUpdate)
+            .ok();
+    }
+
+    /// Passes the latest frame on to the UI, if the preview is turned on. Does nothing if there is no recording.
+    pub async fn update_preview(&mut self) -> Result<()> {
+        if self.recording.is_none() {
+            return Ok(());
+        }
+        let show_preview = self
+            .app_state
+            .config
+            .read()
+            .unwrap()
+            .preferences
+            .show_recording_preview;
+        let frame = match show_preview {
+            true => self.capture_backend.next_frame().await?,
+            false => None,
+        };
+        let mut preview_frame = self.app_state.preview_frame.write().unwrap();
+        if *preview_frame != frame {
+            *preview_frame = frame;
+            self.app_state
+                .ui_update_tx
+                .try_send(UiUpdate::ForceUpdate)
+                .ok();
+        }
+        Ok(())
+    }
+
+    pub async fn stop(&mut self) -> Result<()> {
+        let Some(recording) = self.recording.take() else {
+            return Ok(());
+        };
+
+        if self.benchmark_run.take().is_some() {
+            let segment_dir = recording.recording_location().to_path_buf();
+            let result = recording
+                .stop(self.capture_backend.as_mut(), &self.app_state.adapter_infos)
+                .await
+                .map(|_| ());
+            *self.app_state.state.write().unwrap() = RecordingStatus::Stopped;
+            *self.app_state.preview_frame.write().unwrap() = None;
+            if let Err(e) = tokio::fs::remove_dir_all(&segment_dir).await {
+                tracing::warn!(e=?e, "Failed to delete benchmark recording {}", segment_dir.display());
+            }
+            tracing::info!("Benchmark run stopped");
+            return result;
+        }
+
+        if self.replay.is_none() {
+            show_notification(
+                "Stopped recording",
+                &format!("No longer recording `{}`", recording.game_exe()),
+                "",
+                NotificationType::Info,
+            );
+        }
+
+        let segment_dir = recording.recording_location().to_path_buf();
+        let (duration, encoder) = (recording.elapsed(), recording.encoder());
+        let skipped_frames = self.capture_backend.skipped_frames().await.ok().flatten();
+        let summary = recording
+            .stop(self.capture_backend.as_mut(), &self.app_state.adapter_infos)
             .await?;
+        self.app_state
+            .telemetry
+            .recording_finished(duration, skipped_frames, encoder);
+        // Segments are uploaded separately, so each is queued as it finishes rather than once the session ends
+        match self.replay.as_mut() {
+            Some(replay) => {
+                if let Some(saved) =
+                    replay.segment_finished(segment_dir, &self.app_state.recording_location())
+                {
+                    self.finished(saved);
+                }
+            }
+            None => {
+                self.finished(segment_dir);
+                self.session_summaries.push(summary);
+            }
+        }
         *self.app_state.state.write().unwrap() = RecordingStatus::Stopped;
+        *self.app_state.preview_frame.write().unwrap() = None;
 
         tracing::info!("Recording stopped");
         Ok(())
     }
 }
 
-fn get_free_space_in_mb(path: &std::path::Path) -> Option<u64> {
+/// The next frame rate down from `fps` that can be recorded at, if there is one
+fn lower_fps(fps: u32) -> Option<u32> {
+    constants::FPS_OPTIONS
+        .into_iter()
+        .filter(|&option| option < fps)
+        .max()
+}
+
+/// Estimates how much disk space a recording of the given length will take up.
+///
+/// Recordings are encoded at a constant bitrate, so this holds regardless of the frame rate.
+pub fn estimated_recording_size_mb(duration: std::time::Duration, video_bitrate_kbps: i64) -> u64 {
+    let kbps = (video_bitrate_kbps + constants::encoding::AUDIO_BITRATE) as u64;
+    kbps * duration.as_secs() / 8 / 1000
+}
+
+/// Returns the free space, in MB, on the disk that `path` is on
+pub fn get_free_space_in_mb(path: &std::path::Path) -> Option<u64> {
+    with_disk(path, |disk| disk.available_space() / 1024 / 1024)
+}
+
+/// Checks that recordings can be saved to `path`, creating the folder if it doesn't exist yet
+pub fn check_recording_location_writable(path: &Path) -> Result<()> {
+    std::fs::create_dir_all(path).wrap_err("Failed to create the folder")?;
+    let test_path = path.join(".owl_control_write_test");
+    std::fs::write(&test_path, b"")
+        .wrap_err("Your account isn't allowed to write to this folder")?;
+    std::fs::remove_file(&test_path).ok();
+    Ok(())
+}
+
+/// Explains why recording to `path` might drop frames, if it's on a drive that's likely to be too slow
+pub fn recording_location_drive_warning(path: &Path) -> Option<&'static str> {
+    with_disk(path, |disk| {
+        if disk.is_removable() {
+            Some(
+                "This folder is on a removable drive. Removable drives are often too slow to record to, which causes dropped frames, and recordings will be lost if the drive is unplugged mid-recording.",
+            )
+        } else if disk.kind() == sysinfo::DiskKind::HDD {
+            Some(
+                "This folder is on a hard drive. Hard drives can be too slow to keep up with recording while a game is loading from the same drive, which causes dropped frames.",
+            )
+        } else {
+            None
+        }
+    })
+    .flatten()
+}
+
+fn with_disk<R>(path: &Path, f: impl FnOnce(&sysinfo::Disk) -> R) -> Option<R> {
     let disks = sysinfo::Disks::new_with_refreshed_list();
     let path = dunce::canonicalize(path).ok()?;
 
@@ -241,7 +925,7 @@ fn get_free_space_in_mb(path: &std::path::Path) -> Option<u64> {
         .iter()
         .filter(|disk| path.starts_with(disk.mount_point()))
         .max_by_key(|disk| disk.mount_point().as_os_str().len())
-        .map(|disk| disk.available_space() / 1024 / 1024)
+        .map(f)
 }
 
 fn get_foregrounded_game() -> Result<Option<(String, game_process::Pid, HWND)>> {
@@ -311,3 +995,196 @@ fn is_process_game_shaped(pid: game_process::Pid) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use input_capture::{Event, PressState};
+
+    use super::*;
+    use crate::{
+        config::Config, output_types::Metadata, record::capture_backend::mock::TestWindow,
+        util::TestDir,
+    };
+
+    fn test_recorder(dir: &Path, window: &TestWindow) -> (Recorder, Arc<AppState>) {
+        let mut config = Config::default();
+        config.credentials.accept_consent();
+        let (app_state, _, _) = AppState::for_test(config);
+        (
+            Recorder::for_test(app_state.clone(), dir, window.0),
+            app_state,
+        )
+    }
+
+    async fn press_keys(recorder: &mut Recorder, keys: &[u16]) {
+        for &key in keys {
+            for press_state in [PressState::Pressed, PressState::Released] {
+                recorder
+                    .seen_input(Event::KeyPress { key, press_state })
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    fn read_metadata(folder: &Path) -> Metadata {
+        let metadata =
+            std::fs::read_to_string(folder.join(constants::filename::recording::METADATA)).unwrap();
+        serde_json::from_str(&metadata).unwrap()
+    }
+
+    /// Timestamps of the key presses in the folder's inputs, in order
+    fn key_timestamps(folder: &Path) -> Vec<f64> {
+        std::fs::read_to_string(folder.join(constants::filename::recording::INPUTS))
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').collect::<Vec<_>>())
+            .filter(|fields| fields[1] == "KEYBOARD")
+            .map(|fields| fields[0].parse().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_segments_continue_the_session() {
+        let dir = TestDir::new("recorder");
+        let window = TestWindow::new(1280, 720);
+        let (mut recorder, app_state) = test_recorder(&dir, &window);
+        let unsupported_games = UnsupportedGames::load_from_embedded();
+
+        recorder.start(&unsupported_games).await.unwrap();
+        press_keys(&mut recorder, &[87, 65]).await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        recorder
+            .start_next_segment(&unsupported_games)
+            .await
+            .unwrap();
+        press_keys(&mut recorder, &[83, 68]).await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        recorder.stop().await.unwrap();
+        assert!(matches!(
+            *app_state.state.read().unwrap(),
+            RecordingStatus::Stopped
+        ));
+
+        let (first, second) = (dir.join("0"), dir.join("1"));
+        for folder in [&first, &second] {
+            for file in [
+                constants::filename::recording::VIDEO,
+                constants::filename::recording::INPUTS,
+                constants::filename::recording::METADATA,
+            ] {
+                assert!(folder.join(file).is_file(), "{file} missing in {folder:?}");
+            }
+            assert!(
+                !folder
+                    .join(constants::filename::recording::IN_PROGRESS)
+                    .exists()
+            );
+        }
+
+        let (first_metadata, second_metadata) = (read_metadata(&first), read_metadata(&second));
+        for metadata in [&first_metadata, &second_metadata] {
+            assert_eq!(metadata.game_exe, TEST_GAME_EXE);
+            assert_eq!(
+                metadata.window_title.as_deref(),
+                Some("OWL Control test game")
+            );
+            assert_eq!(metadata.game_resolution, Some((1280, 720)));
+            assert_eq!(metadata.fps, Some(constants::DEFAULT_FPS));
+            assert_eq!(metadata.recorder.as_deref(), Some("Mock"));
+            assert_eq!(metadata.consent_version, Some(constants::CONSENT_VERSION));
+            assert!(metadata.duration > 0.0);
+        }
+        let (first_segment, second_segment) = (
+            first_metadata.segment.unwrap(),
+            second_metadata.segment.unwrap(),
+        );
+        assert_eq!(
+            first_segment.recording_session_id,
+            second_segment.recording_session_id
+        );
+        assert_eq!((first_segment.index, second_segment.index), (0, 1));
+        assert_eq!(first_segment.offset, 0.0);
+        // The second picks up where the first left off, give or take a frame
+        assert!(
+            (second_segment.offset - first_metadata.duration).abs()
+                <= 1.0 / constants::DEFAULT_FPS as f32
+        );
+
+        // The inputs of both are on the session's clock
+        let (first_keys, second_keys) = (key_timestamps(&first), key_timestamps(&second));
+        assert_eq!((first_keys.len(), second_keys.len()), (4, 4));
+        assert!(first_keys.last() < second_keys.first());
+    }
+
+    #[tokio::test]
+    async fn test_resolution_change_starts_a_new_segment() {
+        let dir = TestDir::new("recorder");
+        let window = TestWindow::new(1280, 720);
+        let (mut recorder, _app_state) = test_recorder(&dir, &window);
+        let unsupported_games = UnsupportedGames::load_from_embedded();
+
+        recorder.start(&unsupported_games).await.unwrap();
+        assert!(
+            !recorder
+                .check_resolution_change(&unsupported_games)
+                .await
+                .unwrap()
+        );
+        window.resize(1920, 1080);
+        // Only once the game has settled on it
+        assert!(
+            !recorder
+                .check_resolution_change(&unsupported_games)
+                .await
+                .unwrap()
+        );
+        tokio::time::sleep(RESOLUTION_CHANGE_SETTLE_TIME).await;
+        assert!(
+            recorder
+                .check_resolution_change(&unsupported_games)
+                .await
+                .unwrap()
+        );
+        recorder.stop().await.unwrap();
+
+        assert_eq!(
+            read_metadata(&dir.join("0")).game_resolution,
+            Some((1280, 720))
+        );
+        let second = read_metadata(&dir.join("1"));
+        assert_eq!(second.game_resolution, Some((1920, 1080)));
+        let change = second.segment.unwrap().resolution_change.unwrap();
+        assert_eq!((change.from, change.to), ((1280, 720), (1920, 1080)));
+    }
+
+    #[tokio::test]
+    async fn test_preview_shows_the_backends_frames() {
+        let dir = TestDir::new("recorder");
+        let window = TestWindow::new(1280, 720);
+        let (mut recorder, app_state) = test_recorder(&dir, &window);
+        app_state
+            .config
+            .write()
+            .unwrap()
+            .preferences
+            .show_recording_preview = true;
+
+        recorder
+            .start(&UnsupportedGames::load_from_embedded())
+            .await
+            .unwrap();
+        recorder.update_preview().await.unwrap();
+        let first = app_state.preview_frame.read().unwrap().clone().unwrap();
+        assert!(!first.is_blank());
+        recorder.update_preview().await.unwrap();
+        assert_ne!(
+            app_state.preview_frame.read().unwrap().as_ref(),
+            Some(&first)
+        );
+
+        recorder.stop().await.unwrap();
+        assert_eq!(*app_state.preview_frame.read().unwrap(), None);
+    }
+}
@@ -1,26 +1,48 @@
 use std::{
-    path::PathBuf,
-    time::{Instant, SystemTime, UNIX_EPOCH},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use color_eyre::{Result, eyre::ContextCompat};
+use constants::encoding::VideoEncoderType;
 use egui_wgpu::wgpu;
 use game_process::{Pid, windows::Win32::Foundation::HWND};
 
 use crate::{
-    config::EncoderSettings,
-    output_types::Metadata,
-    record::{input_recorder::InputRecorder, recorder::VideoRecorder},
+    config::{CaptureRegion, EncoderSettings},
+    output_types::{
+        METADATA_SCHEMA_VERSION, Metadata, RegionBounds, ResolutionChange, SegmentInfo,
+        SessionLabels, SkippedFrames,
+    },
+    record::{
+        capture_backend::CaptureBackend,
+        capture_region::{self, Crop},
+        input_recorder::{InputClock, InputRecorder, trim_to_whole_frames},
+        summary::RecordingSummary,
+    },
     system::{hardware_id, hardware_specs},
 };
 
+/// Both recorders have OBS scale the game to this resolution as it's rendered, before it reaches the encoder
+const OUTPUT_RESOLUTION: (u32, u32) = (constants::RECORDING_WIDTH, constants::RECORDING_HEIGHT);
+
 pub(crate) struct Recording {
     input_recorder: InputRecorder,
 
     recording_location: PathBuf,
     metadata_path: PathBuf,
     game_exe: String,
+    game_name: String,
     game_resolution: (u32, u32),
+    crop: Option<Crop>,
+    fps: u32,
+    input_rate_hz: Option<u32>,
+    window_title: Option<String>,
+    monitor_resolution: Option<(u32, u32)>,
+    video_settings: EncoderSettings,
+    user_id: Option<String>,
+    consent_version: Option<u32>,
+    segment: Segment,
     start_time: SystemTime,
     start_instant: Instant,
 
@@ -28,43 +50,126 @@ pub(crate) struct Recording {
     hwnd: HWND,
 }
 
+/// Where a recording sits in its session, which is split into segments of limited length
+#[derive(Debug, Clone)]
+pub(crate) struct Segment {
+    session_id: String,
+    index: u32,
+    /// Footage recorded in the session before this segment
+    offset: Duration,
+    /// Time spent paused in the session before this segment
+    paused_duration: Duration,
+    /// Shared by every segment, so that their input timestamps are on the same clock
+    clock: InputClock,
+    /// Set if the segment was started because the game changed resolution
+    resolution_change: Option<ResolutionChange>,
+}
+impl Segment {
+    /// The first segment of a new session
+    pub(crate) fn first() -> Self {
+        Self {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            index: 0,
+            offset: Duration::ZERO,
+            paused_duration: Duration::ZERO,
+            clock: InputClock::start(),
+            resolution_change: None,
+        }
+    }
+
+    pub(crate) fn is_first(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Position in the session, starting from 0
+    pub(crate) fn index(&self) -> u32 {
+        self.index
+    }
+}
+
 impl Recording {
     pub(crate) async fn start(
-        video_recorder: &mut dyn VideoRecorder,
+        capture_backend: &mut dyn CaptureBackend,
         recording_location: PathBuf,
         game_exe: String,
         pid: Pid,
         hwnd: HWND,
         video_settings: EncoderSettings,
+        fps: u32,
+        input_rate_hz: Option<u32>,
+        capture_region: Option<CaptureRegion>,
+        control_keys: Vec<u16>,
+        desktop_audio_device: Option<String>,
+        user_id: Option<String>,
+        consent_version: Option<u32>,
+        segment: Segment,
     ) -> Result<Self> {
         let start_time = SystemTime::now();
         let start_instant = Instant::now();
 
         let game_resolution = get_recording_base_resolution(hwnd)?;
-        tracing::info!("Game resolution: {game_resolution:?}");
+        tracing::info!("Game resolution: {game_resolution:?}, recording at {fps} FPS");
+        let window_title = game_process::window_title(hwnd);
+        let game_name = game_name(&game_exe, window_title.as_deref());
+        tracing::info!("Detected game: {game_name}");
+        let monitor_resolution = hardware_specs::get_window_monitor_resolution(hwnd);
+        let crop = capture_region.and_then(|region| {
+            // Worked out afresh for every segment, as the game could have moved or changed resolution
+            match get_capture_crop(&region, hwnd, game_resolution) {
+                Ok(crop) => {
+                    tracing::info!("Cropping to capture region: {crop:?}");
+                    Some(crop)
+                }
+                Err(e) => {
+                    tracing::warn!(e=?e, ?region, "Can't use the capture region, recording the whole game instead");
+                    None
+                }
+            }
+        });
 
         let metadata_path = recording_location.join(constants::filename::recording::METADATA);
         let video_path = recording_location.join(constants::filename::recording::VIDEO);
         let csv_path = recording_location.join(constants::filename::recording::INPUTS);
 
-        video_recorder
-            .start_recording(
+        capture_backend
+            .start(
                 &video_path,
                 pid.0,
                 hwnd,
                 &game_exe,
-                video_settings,
+                video_settings.clone(),
+                fps,
                 game_resolution,
+                crop,
+                desktop_audio_device.as_deref(),
             )
             .await?;
-        let input_recorder = InputRecorder::start(&csv_path).await?;
+        let input_recorder = InputRecorder::start(
+            &csv_path,
+            segment.clock,
+            segment.paused_duration,
+            input_rate_hz,
+            fps,
+            control_keys,
+        )
+        .await?;
 
         Ok(Self {
             input_recorder,
             recording_location,
             metadata_path,
             game_exe,
+            game_name,
             game_resolution,
+            crop,
+            fps,
+            input_rate_hz,
+            window_title,
+            monitor_resolution,
+            video_settings,
+            user_id,
+            consent_version,
+            segment,
             start_time,
             start_instant,
 
@@ -78,6 +183,15 @@ impl Recording {
         &self.game_exe
     }
 
+    pub(crate) fn recording_location(&self) -> &Path {
+        &self.recording_location
+    }
+
+    /// The encoder actually in use, which can differ from the configured one in low impact mode
+    pub(crate) fn encoder(&self) -> VideoEncoderType {
+        self.video_settings.encoder
+    }
+
     #[allow(dead_code)]
     pub(crate) fn start_time(&self) -> SystemTime {
         self.start_time
@@ -88,9 +202,52 @@ impl Recording {
         self.start_instant
     }
 
+    /// How much footage has been recorded so far, i.e. excluding any time spent paused
     #[allow(dead_code)]
     pub(crate) fn elapsed(&self) -> std::time::Duration {
-        self.start_instant.elapsed()
+        // The input recorder's total includes the pauses from earlier segments, which didn't happen during this one
+        let paused_duration = self
+            .input_recorder
+            .paused_duration()
+            .saturating_sub(self.segment.paused_duration);
+        self.start_instant.elapsed().saturating_sub(paused_duration)
+    }
+
+    /// The segment that should follow this one to continue the session
+    pub(crate) fn next_segment(&self) -> Segment {
+        Segment {
+            session_id: self.segment.session_id.clone(),
+            index: self.segment.index + 1,
+            offset: self.segment.offset + trim_to_whole_frames(self.elapsed(), self.fps),
+            paused_duration: self.input_recorder.paused_duration(),
+            clock: self.segment.clock,
+            resolution_change: None,
+        }
+    }
+
+    /// The game's current resolution, if it's no longer the one that this segment is being recorded at.
+    /// Zero-sized windows (e.g. minimized ones) don't count, as they have nothing to capture anyway.
+    pub(crate) fn changed_resolution(&self) -> Option<(u32, u32)> {
+        get_recording_base_resolution(self.hwnd)
+            .ok()
+            .filter(|&(width, height)| width > 0 && height > 0)
+            .filter(|&resolution| resolution != self.game_resolution)
+    }
+
+    /// The segment that should follow this one once the game has changed resolution to `to`
+    pub(crate) fn next_segment_at_resolution(&self, to: (u32, u32)) -> Segment {
+        Segment {
+            resolution_change: Some(ResolutionChange {
+                from: self.game_resolution,
+                to,
+                timestamp: self.segment.clock.now(),
+            }),
+            ..self.next_segment()
+        }
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.input_recorder.is_paused()
     }
 
     #[allow(dead_code)]
@@ -104,7 +261,12 @@ impl Recording {
     }
 
     pub(crate) async fn seen_input(&mut self, e: input_capture::Event) -> Result<()> {
-        self.input_recorder.seen_input(e).await
+        // Only needed for the cursor position. Looked up each time, as the window can be moved.
+        let window_origin = match e {
+            input_capture::Event::CursorMove(_) => get_window_client_origin(self.hwnd),
+            _ => (0, 0),
+        };
+        self.input_recorder.seen_input(e, window_origin).await
     }
 
     pub(crate) async fn write_focus(&mut self, focused: bool) -> Result<()> {
@@ -112,19 +274,44 @@ impl Recording {
         self.input_recorder.write_focus(focused).await
     }
 
+    /// Pauses both the video and the input recording, keeping the same output file.
+    pub(crate) async fn pause(&mut self, capture_backend: &mut dyn CaptureBackend) -> Result<()> {
+        if self.is_paused() {
+            return Ok(());
+        }
+        capture_backend.set_paused(true).await?;
+        self.input_recorder.pause();
+        Ok(())
+    }
+
+    pub(crate) async fn resume(&mut self, capture_backend: &mut dyn CaptureBackend) -> Result<()> {
+        if !self.is_paused() {
+            return Ok(());
+        }
+        capture_backend.set_paused(false).await?;
+        self.input_recorder.resume();
+        Ok(())
+    }
+
+    /// Returns what was recorded, for the user to look over
     pub(crate) async fn stop(
         self,
-        recorder: &mut dyn VideoRecorder,
+        recorder: &mut dyn CaptureBackend,
         adapter_infos: &[wgpu::AdapterInfo],
-    ) -> Result<()> {
-        let result = recorder.stop_recording().await;
-        self.input_recorder.stop().await?;
+    ) -> Result<RecordingSummary> {
+        // Lines up with the end of the input track, see `InputRecorder::stop`
+        let duration = trim_to_whole_frames(self.elapsed(), self.fps);
+        // Has to be asked for before the recorder forgets about the recording
+        let skipped_frames = recorder.skipped_frames().await.unwrap_or_else(|e| {
+            tracing::warn!(e=?e, "Failed to get skipped frames");
+            None
+        });
+        let result = recorder.stop().await;
+        let metadata_inputs = self.metadata_inputs(duration, skipped_frames);
+        let inputs = self.input_recorder.stop().await?;
 
         let metadata = Self::final_metadata(
-            self.game_exe,
-            self.game_resolution,
-            self.start_instant,
-            self.start_time,
+            metadata_inputs,
             adapter_infos,
             recorder.id(),
             result.as_ref().ok().cloned(),
@@ -132,30 +319,117 @@ impl Recording {
         .await?;
         let metadata = serde_json::to_string_pretty(&metadata)?;
         tokio::fs::write(&self.metadata_path, &metadata).await?;
+        tokio::fs::remove_file(
+            self.recording_location
+                .join(constants::filename::recording::IN_PROGRESS),
+        )
+        .await
+        .ok();
 
-        if let Err(e) = result {
-            tracing::error!("Error while stopping recording, invalidating recording: {e}");
-            tokio::fs::write(
-                self.recording_location
-                    .join(constants::filename::recording::INVALID),
-                e.to_string(),
-            )
-            .await?;
-        }
+        let invalid_reason = match result {
+            Ok(_) => None,
+            Err(e) => {
+                tracing::error!("Error while stopping recording, invalidating recording: {e}");
+                tokio::fs::write(
+                    self.recording_location
+                        .join(constants::filename::recording::INVALID),
+                    e.to_string(),
+                )
+                .await?;
+                Some(e.to_string())
+            }
+        };
 
+        Ok(RecordingSummary {
+            size_bytes: crate::upload::folder_size(&self.recording_location),
+            folder_path: self.recording_location,
+            game_name: self.game_name,
+            duration,
+            game_resolution: self.game_resolution,
+            fps: self.fps,
+            skipped_frames,
+            inputs,
+            invalid_reason,
+        })
+    }
+
+    /// Writes the metadata as it stands at the start of the recording, along with a marker saying that the
+    /// recording isn't finished. If we never get to stop the recording (e.g. a crash), the recording is
+    /// recovered from these on the next launch.
+    pub(crate) async fn write_provisional_metadata(
+        &self,
+        recorder: &str,
+        adapter_infos: &[wgpu::AdapterInfo],
+    ) -> Result<()> {
+        let metadata = Self::final_metadata(
+            self.metadata_inputs(Duration::ZERO, None),
+            adapter_infos,
+            recorder,
+            None,
+        )
+        .await?;
+        tokio::fs::write(
+            self.recording_location
+                .join(constants::filename::recording::IN_PROGRESS),
+            "",
+        )
+        .await?;
+        tokio::fs::write(
+            &self.metadata_path,
+            serde_json::to_string_pretty(&metadata)?,
+        )
+        .await?;
         Ok(())
     }
 
+    fn metadata_inputs(
+        &self,
+        duration: Duration,
+        skipped_frames: Option<SkippedFrames>,
+    ) -> FinalMetadataInputs {
+        FinalMetadataInputs {
+            game_exe: self.game_exe.clone(),
+            game_name: self.game_name.clone(),
+            window_title: self.window_title.clone(),
+            game_resolution: self.game_resolution,
+            crop: self.crop,
+            monitor_resolution: self.monitor_resolution,
+            fps: self.fps,
+            input_rate_hz: self.input_rate_hz,
+            video_settings: self.video_settings.clone(),
+            user_id: self.user_id.clone(),
+            consent_version: self.consent_version,
+            segment: self.segment.clone(),
+            skipped_frames,
+            duration,
+            start_time: self.start_time,
+        }
+    }
+
     async fn final_metadata(
-        game_exe: String,
-        game_resolution: (u32, u32),
-        start_instant: Instant,
-        start_time: SystemTime,
+        FinalMetadataInputs {
+            game_exe,
+            game_name,
+            window_title,
+            game_resolution,
+            crop,
+            monitor_resolution,
+            fps,
+            input_rate_hz,
+            video_settings,
+            user_id,
+            consent_version,
+            segment,
+            skipped_frames,
+            duration,
+            start_time,
+        }: FinalMetadataInputs,
         adapter_infos: &[wgpu::AdapterInfo],
         recorder: &str,
         recorder_extra: Option<serde_json::Value>,
     ) -> Result<Metadata> {
-        let duration = start_instant.elapsed().as_secs_f32();
+        let duration = duration.as_secs_f32();
+        let captured_resolution = crop.unwrap_or_default().apply(game_resolution);
 
         let start_timestamp = start_time.duration_since(UNIX_EPOCH).unwrap().as_secs();
         let end_timestamp = SystemTime::now()
@@ -163,6 +437,7 @@ impl Recording {
             .unwrap()
             .as_secs();
 
+        let input_clock_origin = segment.clock.origin_unix();
         let hardware_id = hardware_id::get()?;
 
         let hardware_specs = match hardware_specs::get_hardware_specs(
@@ -179,8 +454,31 @@ impl Recording {
         };
 
         Ok(Metadata {
+            schema_version: Some(METADATA_SCHEMA_VERSION),
             game_exe,
+            // Prefilled so that the user only has to correct it, if anything
+            labels: Some(SessionLabels {
+                game_title: Some(game_name.clone()),
+                ..Default::default()
+            }),
+            game_name: Some(game_name),
+            window_title,
             game_resolution: Some(game_resolution),
+            fps: Some(fps),
+            input_rate_hz,
+            monitor_resolution,
+            output_resolution: Some(OUTPUT_RESOLUTION),
+            scale_factor: Some(scale_factor(captured_resolution, OUTPUT_RESOLUTION)),
+            capture_region: crop.map(|crop| RegionBounds {
+                x: crop.left,
+                y: crop.top,
+                width: captured_resolution.0,
+                height: captured_resolution.1,
+            }),
+            video_encoder: Some(video_settings.encoder),
+            video_codec: Some(video_settings.codec),
+            video_bitrate_kbps: Some(video_settings.bitrate),
+            keyframe_interval_seconds: Some(video_settings.keyframe_interval_seconds),
             owl_control_version: Some(env!("CARGO_PKG_VERSION").to_string()),
             owl_control_commit: Some(
                 git_version::git_version!(
@@ -189,19 +487,99 @@ impl Recording {
                 )
                 .to_string(),
             ),
+            user_id,
+            consent_version,
             session_id: uuid::Uuid::new_v4().to_string(),
+            segment: Some(SegmentInfo {
+                recording_session_id: segment.session_id,
+                index: segment.index,
+                offset: segment.offset.as_secs_f32(),
+                resolution_change: segment.resolution_change,
+            }),
+            skipped_frames,
             hardware_id,
             hardware_specs,
             start_timestamp,
             end_timestamp,
+            input_clock_origin: Some(input_clock_origin),
             duration,
             input_stats: None,
             recorder: Some(recorder.to_string()),
             recorder_extra,
+            interrupted: None,
         })
     }
 }
 
+/// Everything about the recording that goes into its metadata, gathered over the course of the recording
+struct FinalMetadataInputs {
+    game_exe: String,
+    game_name: String,
+    window_title: Option<String>,
+    game_resolution: (u32, u32),
+    crop: Option<Crop>,
+    monitor_resolution: Option<(u32, u32)>,
+    fps: u32,
+    input_rate_hz: Option<u32>,
+    video_settings: EncoderSettings,
+    user_id: Option<String>,
+    consent_version: Option<u32>,
+    segment: Segment,
+    skipped_frames: Option<SkippedFrames>,
+    duration: Duration,
+    start_time: SystemTime,
+}
+
+/// What to call the game: its name if it's one we know of, otherwise the window's title, or failing that the
+/// executable's name without its extension
+pub(crate) fn game_name(game_exe: &str, window_title: Option<&str>) -> String {
+    constants::known_games::name_for_exe(game_exe)
+        .or(window_title.map(str::trim).filter(|t| !t.is_empty()))
+        .unwrap_or_else(|| game_exe.split('.').next().unwrap_or(game_exe))
+        .to_string()
+}
+
+/// How much each axis of `from` is scaled to become `to`. The axes can differ if the aspect ratios do.
+fn scale_factor(from: (u32, u32), to: (u32, u32)) -> (f32, f32) {
+    let axis = |from: u32, to: u32| {
+        if from == 0 {
+            1.0
+        } else {
+            to as f32 / from as f32
+        }
+    };
+    (axis(from.0, to.0), axis(from.1, to.1))
+}
+
+/// The crop that leaves just the part of the game inside `region`
+fn get_capture_crop(
+    region: &CaptureRegion,
+    hwnd: HWND,
+    game_resolution: (u32, u32),
+) -> Result<Crop> {
+    let region = capture_region::region_on_screen(region, &hardware_specs::get_monitors())?;
+    let (x, y) = get_window_client_origin(hwnd);
+    capture_region::crop_for_region(
+        region,
+        hardware_specs::ScreenRect {
+            x,
+            y,
+            width: game_resolution.0,
+            height: game_resolution.1,
+        },
+    )
+}
+
+/// Where the top left of the window's client area is on the screen
+fn get_window_client_origin(hwnd: HWND) -> (i32, i32) {
+    use windows::Win32::{Foundation::POINT, Graphics::Gdi::ClientToScreen};
+
+    let mut origin = POINT::default();
+    // If the window's gone, the recording is about to stop anyway
+    let _ = unsafe { ClientToScreen(hwnd, &mut origin) };
+    (origin.x, origin.y)
+}
+
 pub fn get_recording_base_resolution(hwnd: HWND) -> Result<(u32, u32)> {
     use windows::Win32::{Foundation::RECT, UI::WindowsAndMessaging::GetClientRect};
 
@@ -217,12 +595,21 @@ pub fn get_recording_base_resolution(hwnd: HWND) -> Result<(u32, u32)> {
         }
     }
 
-    match get_window_inner_size(hwnd) {
-        Some(size) => Ok(size),
-        None => {
-            tracing::info!("Failed to get window inner size, using primary monitor resolution");
-            hardware_specs::get_primary_monitor_resolution()
-                .context("Failed to get primary monitor resolution")
-        }
+    if let Some(size) = get_window_inner_size(hwnd) {
+        return Ok(size);
     }
+
+    // The game can be on any monitor, so prefer the one it's actually on
+    if let Some(size) = hardware_specs::get_window_monitor_resolution(hwnd) {
+        tracing::info!(
+            "Failed to get window inner size, using resolution of the game's monitor: {size:?}"
+        );
+        return Ok(size);
+    }
+
+    tracing::warn!(
+        "Failed to get window inner size or the game's monitor, falling back to primary monitor resolution"
+    );
+    hardware_specs::get_primary_monitor_resolution()
+        .context("Failed to get primary monitor resolution")
 }
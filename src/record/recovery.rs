@@ -0,0 +1,96 @@
+//! Recordings that OWL Control didn't get to finish (because of a crash, a power cut, or being killed)
+//! still have usable footage, as the video is written in fragments and the inputs line by line. On launch,
+//! we complete their metadata from what was written so that they can be uploaded like any other recording.
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{
+    Result,
+    eyre::{ContextCompat as _, WrapErr as _},
+};
+use constants::filename::recording::{IN_PROGRESS, INPUTS, INVALID, METADATA};
+
+use crate::output_types::Metadata;
+
+/// Finishes off every recording in `recording_location` that was interrupted. Those that can't be
+/// recovered are marked as invalid, so that the user can see what happened to them. Returns the ones
+/// that were recovered.
+pub fn recover_interrupted_recordings(recording_location: &Path) -> Vec<PathBuf> {
+    let mut recovered = vec![];
+    let Ok(entries) = recording_location.read_dir() else {
+        return recovered;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if !path.join(IN_PROGRESS).is_file() {
+            continue;
+        }
+        match recover(&path) {
+            Ok(duration) => {
+                tracing::info!(path=%path.display(), duration, "Recovered interrupted recording");
+                recovered.push(path.clone());
+            }
+            Err(e) => {
+                tracing::warn!(path=%path.display(), e=?e, "Failed to recover interrupted recording");
+                std::fs::write(
+                    path.join(INVALID),
+                    format!("Recording was interrupted and couldn't be recovered: {e}"),
+                )
+                .ok();
+            }
+        }
+        std::fs::remove_file(path.join(IN_PROGRESS)).ok();
+    }
+    recovered
+}
+
+/// Returns the duration of the recovered recording, in seconds
+fn recover(path: &Path) -> Result<f32> {
+    let mut metadata: Metadata = serde_json::from_str(
+        &std::fs::read_to_string(path.join(METADATA)).wrap_err("failed to read metadata")?,
+    )
+    .wrap_err("failed to parse metadata")?;
+    let inputs = std::fs::read_to_string(path.join(INPUTS)).wrap_err("failed to read inputs")?;
+    let (first, last) = input_timestamp_range(&inputs).context("no inputs were recorded")?;
+
+    // Input timestamps leave out the time spent paused, as the video does
+    metadata.duration = (last - first) as f32;
+    metadata.end_timestamp = metadata.start_timestamp + metadata.duration as u64;
+    metadata.interrupted = Some(true);
+    std::fs::write(
+        path.join(METADATA),
+        serde_json::to_string_pretty(&metadata)?,
+    )?;
+
+    Ok(metadata.duration)
+}
+
+/// The first and last timestamps in an inputs file. A last line that was only partly written
+/// (so has no newline yet) is left out, as are any other lines that can't be read.
+fn input_timestamp_range(inputs: &str) -> Option<(f64, f64)> {
+    let mut timestamps = inputs
+        .split_inclusive('\n')
+        .filter(|line| line.ends_with('\n'))
+        .skip(1)
+        .filter_map(|line| line.split(',').next()?.parse::<f64>().ok());
+    let first = timestamps.next()?;
+    Some((first, timestamps.last().unwrap_or(first)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_timestamp_range() {
+        let inputs = "timestamp,event_type,event_args\n\
+                      100.5,START,[]\n\
+                      101.0,KEYBOARD,[65,true]\n\
+                      160.25,MOUSE_MOVE,[1,2]\n\
+                      160.3,KEYB";
+        assert_eq!(input_timestamp_range(inputs), Some((100.5, 160.25)));
+        assert_eq!(
+            input_timestamp_range("timestamp,event_type,event_args\n"),
+            None
+        );
+    }
+}
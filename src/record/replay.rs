@@ -0,0 +1,146 @@
+//! Instant replay keeps the last few minutes of a game around without the user having to have started
+//! recording. It records as usual, but in short segments to a hidden folder within the recording location,
+//! and throws away segments once they're older than the replay length. Saving moves the segments that are
+//! still around into the recording location, where they're uploaded like any other recording.
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use color_eyre::{Result, eyre::WrapErr as _};
+
+/// Replay segments are kept short so that little more than the replay length is kept around, but long
+/// enough that each one is still a valid recording
+pub const REPLAY_SEGMENT_LENGTH: Duration =
+    Duration::from_secs(2 * constants::MIN_FOOTAGE.as_secs());
+
+pub(crate) struct ReplayBuffer {
+    dir: PathBuf,
+    length: Duration,
+    /// Finished segments, oldest first, along with when they finished
+    segments: VecDeque<(PathBuf, Instant)>,
+    /// Set when the replay was saved while a segment was being recorded, so that the segment is saved
+    /// too once it finishes
+    keep_current: bool,
+}
+impl ReplayBuffer {
+    /// Starts an empty buffer in `recording_location`, clearing out anything left behind by a previous one
+    pub(crate) fn new(recording_location: &Path, length: Duration) -> Self {
+        let dir = recording_location.join(constants::filename::recording::REPLAY_BUFFER);
+        std::fs::remove_dir_all(&dir).ok();
+        Self {
+            dir,
+            length,
+            segments: VecDeque::new(),
+            keep_current: false,
+        }
+    }
+
+    pub(crate) fn length(&self) -> Duration {
+        self.length
+    }
+
+    /// Where a segment that would otherwise be recorded to `recording_dir` goes instead
+    pub(crate) fn segment_dir(&self, recording_dir: &Path) -> PathBuf {
+        self.dir.join(recording_dir.file_name().unwrap_or_default())
+    }
+
+    /// Takes note of a segment that just finished, saving it if the replay was saved while it was
+    /// being recorded. Segments that have fallen out of the replay are deleted. Returns where the
+    /// segment was saved to, if it was.
+    pub(crate) fn segment_finished(
+        &mut self,
+        segment_dir: PathBuf,
+        recording_location: &Path,
+    ) -> Option<PathBuf> {
+        let mut saved = None;
+        if std::mem::take(&mut self.keep_current) {
+            match save_segment(&segment_dir, recording_location) {
+                Ok(destination) => saved = Some(destination),
+                Err(e) => {
+                    tracing::error!(e=?e, "Failed to save replay segment {}", segment_dir.display());
+                }
+            }
+        } else {
+            self.segments.push_back((segment_dir, Instant::now()));
+        }
+        self.prune(Instant::now());
+        saved
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some((segment_dir, finished_at)) = self.segments.front()
+            && now.duration_since(*finished_at) > self.length
+        {
+            std::fs::remove_dir_all(segment_dir).ok();
+            self.segments.pop_front();
+        }
+    }
+
+    /// Moves every segment that's still in the replay into `recording_location`. If `recording` is set, the
+    /// segment being recorded is saved too once it finishes. Returns where the segments were saved to.
+    pub(crate) fn save(
+        &mut self,
+        recording_location: &Path,
+        recording: bool,
+    ) -> Result<Vec<PathBuf>> {
+        self.prune(Instant::now());
+        let mut saved = vec![];
+        while let Some((segment_dir, _)) = self.segments.pop_front() {
+            saved.push(save_segment(&segment_dir, recording_location)?);
+        }
+        self.keep_current = recording;
+        Ok(saved)
+    }
+}
+
+fn save_segment(segment_dir: &Path, recording_location: &Path) -> Result<PathBuf> {
+    let destination = recording_location.join(segment_dir.file_name().unwrap_or_default());
+    // Both are in the recording location, so this is a rename on the same drive
+    std::fs::rename(segment_dir, &destination)
+        .wrap_err_with(|| format!("failed to move {segment_dir:?} to {destination:?}"))?;
+    tracing::info!("Saved replay segment to {}", destination.display());
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TestDir;
+
+    #[test]
+    fn test_old_segments_are_dropped() {
+        let recording_location = TestDir::new("replay");
+        let mut buffer = ReplayBuffer::new(&recording_location, Duration::from_secs(60));
+        let segment = |name: &str| {
+            let dir = buffer.segment_dir(&recording_location.join(name));
+            std::fs::create_dir_all(&dir).unwrap();
+            dir
+        };
+        let (first, second) = (segment("1"), segment("2"));
+
+        buffer.segment_finished(first.clone(), &recording_location);
+        buffer.segment_finished(second.clone(), &recording_location);
+        // Long after the first finished, but not the second
+        buffer.segments[0].1 -= Duration::from_secs(120);
+        buffer.prune(Instant::now());
+        assert!(!first.exists());
+        assert!(second.exists());
+
+        // The one being recorded is saved once it finishes
+        assert_eq!(
+            buffer.save(&recording_location, true).unwrap(),
+            [recording_location.join("2")]
+        );
+        assert!(recording_location.join("2").is_dir());
+        let third = segment("3");
+        assert_eq!(
+            buffer.segment_finished(third, &recording_location),
+            Some(recording_location.join("3"))
+        );
+        assert!(recording_location.join("3").is_dir());
+        assert!(buffer.segments.is_empty());
+    }
+}
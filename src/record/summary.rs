@@ -0,0 +1,64 @@
+//! What a recording turned out like, shown once it's finished so that the user can check it over and decide
+//! what to do with it while it's still fresh in their mind
+
+use std::{path::PathBuf, time::Duration};
+
+use constants::SKIPPED_FRAMES_WARNING_PERCENTAGE;
+
+use crate::output_types::SkippedFrames;
+
+/// A finished recording. A session that was split into segments has one of these for each.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingSummary {
+    pub folder_path: PathBuf,
+    pub game_name: String,
+    /// Excluding any time spent paused
+    pub duration: Duration,
+    pub game_resolution: (u32, u32),
+    pub fps: u32,
+    /// `None` if the recorder couldn't tell
+    pub skipped_frames: Option<SkippedFrames>,
+    pub size_bytes: u64,
+    /// Key presses, mouse movement and so on; see [`crate::output_types::InputEventType`]
+    pub inputs: u64,
+    /// Why the recording was invalidated as it stopped, if it was
+    pub invalid_reason: Option<String>,
+}
+impl RecordingSummary {
+    /// Whether it dropped more frames than would have been warned about while recording
+    pub fn dropped_too_many_frames(&self) -> bool {
+        self.skipped_frames.is_some_and(|skipped_frames| {
+            skipped_frames.percentage() > SKIPPED_FRAMES_WARNING_PERCENTAGE
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_too_many_frames() {
+        let summary = |skipped_frames| RecordingSummary {
+            folder_path: PathBuf::new(),
+            game_name: "Minecraft".to_string(),
+            duration: Duration::from_secs(60),
+            game_resolution: (1920, 1080),
+            fps: 60,
+            skipped_frames,
+            size_bytes: 0,
+            inputs: 0,
+            invalid_reason: None,
+        };
+        let skipped = |skipped| {
+            Some(SkippedFrames {
+                skipped,
+                total: 3600,
+            })
+        };
+        assert!(!summary(skipped(0)).dropped_too_many_frames());
+        assert!(summary(skipped(1800)).dropped_too_many_frames());
+        // Not knowing isn't worth worrying the user over
+        assert!(!summary(None).dropped_too_many_frames());
+    }
+}
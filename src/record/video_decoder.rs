@@ -0,0 +1,322 @@
+//! Decodes a recording's video with Media Foundation, so that the preview can show it under the inputs.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, mpsc},
+};
+
+use color_eyre::{
+    Result,
+    eyre::{Context as _, ContextCompat as _},
+};
+use windows::{
+    Win32::{
+        Media::MediaFoundation::{
+            IMF2DBuffer, IMFMediaBuffer, IMFSourceReader, MF_MT_FRAME_SIZE, MF_MT_MAJOR_TYPE,
+            MF_MT_MINIMUM_DISPLAY_APERTURE, MF_MT_SUBTYPE, MF_SOURCE_READER_ALL_STREAMS,
+            MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING, MF_SOURCE_READER_FIRST_VIDEO_STREAM,
+            MF_SOURCE_READERF_ENDOFSTREAM, MF_VERSION, MFCreateAttributes, MFCreateMediaType,
+            MFCreateSourceReaderFromURL, MFMediaType_Video, MFSTARTUP_FULL, MFShutdown, MFStartup,
+            MFVideoArea, MFVideoFormat_RGB32,
+        },
+        System::Com::{COINIT_MULTITHREADED, CoInitializeEx, CoUninitialize},
+    },
+    core::{GUID, HSTRING, Interface as _, PROPVARIANT},
+};
+
+/// Media Foundation times are in units of 100ns
+const TICKS_PER_SECOND: f64 = 10_000_000.0;
+/// Seeking goes back to the last keyframe and decodes forward from there, so reading on is quicker for
+/// anything up to this far ahead
+const MAX_READ_AHEAD: i64 = 2 * TICKS_PER_SECOND as i64;
+
+/// A decoded frame of the video
+#[derive(Debug, Clone)]
+pub struct VideoFrame {
+    pub width: u32,
+    pub height: u32,
+    /// Row by row, 4 bytes per pixel
+    pub rgba: Vec<u8>,
+    /// Seconds into the video that the frame is shown
+    pub time: f64,
+}
+
+/// Decodes a video on a thread of its own, as decoding, and seeking especially, would hold up the UI
+pub struct VideoDecoder {
+    requests: mpsc::Sender<f64>,
+    /// `Err` if the video couldn't be decoded
+    frame: Arc<Mutex<Option<Result<VideoFrame, String>>>>,
+}
+impl VideoDecoder {
+    /// Starts decoding the video at `path`. `on_frame` is called from the decoding thread whenever a new
+    /// frame is ready. The thread stops once this is dropped.
+    pub fn spawn(path: PathBuf, on_frame: impl Fn() + Send + 'static) -> Self {
+        let (requests, requests_rx) = mpsc::channel();
+        let frame = Arc::new(Mutex::new(None));
+        std::thread::spawn({
+            let frame = frame.clone();
+            move || unsafe {
+                let result = CoInitializeEx(None, COINIT_MULTITHREADED)
+                    .ok()
+                    .context("Failed to initialize COM")
+                    .and_then(|_| {
+                        MFStartup(MF_VERSION, MFSTARTUP_FULL)
+                            .context("Failed to start Media Foundation")?;
+                        let result = decode(&path, requests_rx, &frame, &on_frame);
+                        MFShutdown().ok();
+                        result
+                    });
+                CoUninitialize();
+                if let Err(e) = result {
+                    tracing::error!(path=%path.display(), e=?e, "Failed to decode video for preview");
+                    *frame.lock().unwrap() = Some(Err(format!("{e:#}")));
+                    on_frame();
+                }
+            }
+        });
+        Self { requests, frame }
+    }
+
+    /// Asks for the frame shown `time` seconds into the video. It's in [`Self::frame`] once decoded.
+    pub fn seek(&self, time: f64) {
+        self.requests.send(time).ok();
+    }
+
+    /// The last frame decoded, or why the video couldn't be
+    pub fn frame(&self) -> MutexGuard<'_, Option<Result<VideoFrame, String>>> {
+        self.frame.lock().unwrap()
+    }
+}
+
+/// Decodes whichever frame was asked for last, until the [`VideoDecoder`] is dropped
+unsafe fn decode(
+    path: &Path,
+    requests: mpsc::Receiver<f64>,
+    frame: &Mutex<Option<Result<VideoFrame, String>>>,
+    on_frame: &dyn Fn(),
+) -> Result<()> {
+    let mut reader = unsafe { Reader::open(path)? };
+    while let Ok(mut time) = requests.recv() {
+        // Anything asked for in the meantime has already been scrubbed past
+        while let Ok(later) = requests.try_recv() {
+            time = later;
+        }
+        if let Some(decoded) = unsafe { reader.frame_at(time)? } {
+            *frame.lock().unwrap() = Some(Ok(decoded));
+            on_frame();
+        }
+    }
+    Ok(())
+}
+
+struct Reader {
+    reader: IMFSourceReader,
+    /// Of the decoded frames, which can be padded past what's meant to be shown
+    frame_size: (u32, u32),
+    /// The part of the decoded frames that's meant to be shown, as x, y, width and height
+    visible: (u32, u32, u32, u32),
+    /// Start and end of the last frame read, in ticks
+    current: Option<(i64, i64)>,
+}
+impl Reader {
+    unsafe fn open(path: &Path) -> Result<Self> {
+        unsafe {
+            let mut attributes = None;
+            MFCreateAttributes(&mut attributes, 1)?;
+            let attributes = attributes.context("no attributes were created")?;
+            // Converts whatever the video was encoded as to RGB
+            attributes.SetUINT32(&MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING, 1)?;
+            let reader = MFCreateSourceReaderFromURL(&HSTRING::from(path), &attributes)
+                .context("Failed to open the video")?;
+
+            let video_stream = MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32;
+            reader.SetStreamSelection(MF_SOURCE_READER_ALL_STREAMS.0 as u32, false)?;
+            reader.SetStreamSelection(video_stream, true)?;
+            let media_type = MFCreateMediaType()?;
+            media_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video)?;
+            media_type.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_RGB32)?;
+            reader
+                .SetCurrentMediaType(video_stream, None, &media_type)
+                .context("The video can't be decoded, its codec may not be installed")?;
+
+            let media_type = reader.GetCurrentMediaType(video_stream)?;
+            let frame_size = media_type.GetUINT64(&MF_MT_FRAME_SIZE)?;
+            let frame_size = ((frame_size >> 32) as u32, frame_size as u32);
+            // Set if the codec had to pad the frames out, e.g. to a multiple of 16 rows
+            let mut aperture = MFVideoArea::default();
+            let visible = match media_type.GetBlob(
+                &MF_MT_MINIMUM_DISPLAY_APERTURE,
+                std::slice::from_raw_parts_mut(
+                    (&raw mut aperture).cast::<u8>(),
+                    size_of::<MFVideoArea>(),
+                ),
+                None,
+            ) {
+                Ok(()) => (
+                    aperture.OffsetX.value.max(0) as u32,
+                    aperture.OffsetY.value.max(0) as u32,
+                    aperture.Area.cx as u32,
+                    aperture.Area.cy as u32,
+                ),
+                Err(_) => (0, 0, frame_size.0, frame_size.1),
+            };
+
+            Ok(Self {
+                reader,
+                frame_size,
+                visible: clamp_to_frame(visible, frame_size),
+                current: None,
+            })
+        }
+    }
+
+    /// The frame shown `time` seconds in, or `None` if it's the one that was returned last
+    unsafe fn frame_at(&mut self, time: f64) -> Result<Option<VideoFrame>> {
+        let target = (time.max(0.0) * TICKS_PER_SECOND) as i64;
+        match self.current {
+            Some((start, end)) if (start..end).contains(&target) => return Ok(None),
+            Some((start, end)) if target >= start && target < end + MAX_READ_AHEAD => {}
+            _ => unsafe {
+                self.reader
+                    .SetCurrentPosition(&GUID::zeroed(), &PROPVARIANT::from(target))
+                    .context("Failed to seek in the video")?;
+            },
+        }
+
+        loop {
+            let mut flags = 0;
+            let mut timestamp = 0;
+            let mut sample = None;
+            unsafe {
+                self.reader
+                    .ReadSample(
+                        MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32,
+                        0,
+                        None,
+                        Some(&mut flags),
+                        Some(&mut timestamp),
+                        Some(&mut sample),
+                    )
+                    .context("Failed to decode the video")?;
+            }
+            if flags & MF_SOURCE_READERF_ENDOFSTREAM.0 as u32 != 0 {
+                // Past the last frame, which stays up
+                return Ok(None);
+            }
+            // Gaps in the stream have no sample
+            let Some(sample) = sample else {
+                continue;
+            };
+            let duration = unsafe { sample.GetSampleDuration() }.unwrap_or_default();
+            self.current = Some((timestamp, timestamp + duration.max(1)));
+            if timestamp + duration <= target {
+                continue;
+            }
+
+            let buffer = unsafe { sample.ConvertToContiguousBuffer()? };
+            let rgba = unsafe { self.read_rgba(&buffer)? };
+            return Ok(Some(VideoFrame {
+                width: self.visible.2,
+                height: self.visible.3,
+                rgba,
+                time: timestamp as f64 / TICKS_PER_SECOND,
+            }));
+        }
+    }
+
+    /// Copies the visible part of a decoded frame out as RGBA
+    unsafe fn read_rgba(&self, buffer: &IMFMediaBuffer) -> Result<Vec<u8>> {
+        let (width, height) = self.frame_size;
+        unsafe {
+            // Rows can be padded, or go bottom to top, which only the 2D interface says
+            if let Ok(buffer) = buffer.cast::<IMF2DBuffer>() {
+                let mut scanline0 = std::ptr::null_mut();
+                let mut pitch = 0;
+                buffer.Lock2D(&mut scanline0, &mut pitch)?;
+                let rgba = self.crop_to_rgba(|y| {
+                    std::slice::from_raw_parts(
+                        scanline0.offset(y as isize * pitch as isize),
+                        width as usize * 4,
+                    )
+                });
+                buffer.Unlock2D()?;
+                return Ok(rgba);
+            }
+
+            let mut data = std::ptr::null_mut();
+            let mut length = 0;
+            buffer.Lock(&mut data, None, Some(&mut length))?;
+            let rgba = match length as usize >= width as usize * height as usize * 4 {
+                true => Ok(self.crop_to_rgba(|y| {
+                    std::slice::from_raw_parts(
+                        data.add(y as usize * width as usize * 4),
+                        width as usize * 4,
+                    )
+                })),
+                false => Err(color_eyre::eyre::eyre!(
+                    "The decoded frame is smaller than {width}x{height}"
+                )),
+            };
+            buffer.Unlock()?;
+            rgba
+        }
+    }
+
+    fn crop_to_rgba<'a>(&self, row: impl Fn(u32) -> &'a [u8]) -> Vec<u8> {
+        let (x, y, width, height) = self.visible;
+        bgrx_to_rgba((y..y + height).map(|y| &row(y)[x as usize * 4..][..width as usize * 4]))
+    }
+}
+
+/// Keeps the visible part of the frames within them, in case the aperture is off
+fn clamp_to_frame(
+    (x, y, width, height): (u32, u32, u32, u32),
+    (frame_width, frame_height): (u32, u32),
+) -> (u32, u32, u32, u32) {
+    let x = x.min(frame_width);
+    let y = y.min(frame_height);
+    let width = match width {
+        0 => frame_width - x,
+        width => width.min(frame_width - x),
+    };
+    let height = match height {
+        0 => frame_height - y,
+        height => height.min(frame_height - y),
+    };
+    (x, y, width, height)
+}
+
+/// Media Foundation's RGB32 is blue, green, red, then a byte that's left undefined
+fn bgrx_to_rgba<'a>(rows: impl Iterator<Item = &'a [u8]>) -> Vec<u8> {
+    rows.flat_map(|row| row.chunks_exact(4))
+        .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], 255])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bgrx_to_rgba() {
+        let rows: [&[u8]; 2] = [&[1, 2, 3, 0, 4, 5, 6, 99], &[7, 8, 9, 0, 10, 11, 12, 0]];
+        assert_eq!(
+            bgrx_to_rgba(rows.into_iter()),
+            [3, 2, 1, 255, 6, 5, 4, 255, 9, 8, 7, 255, 12, 11, 10, 255]
+        );
+    }
+
+    #[test]
+    fn test_clamp_to_frame() {
+        // 360p is padded out to 368 rows, as H.264 works in blocks of 16
+        assert_eq!(
+            clamp_to_frame((0, 0, 640, 360), (640, 368)),
+            (0, 0, 640, 360)
+        );
+        assert_eq!(clamp_to_frame((0, 0, 0, 0), (640, 368)), (0, 0, 640, 368));
+        assert_eq!(
+            clamp_to_frame((600, 0, 640, 360), (640, 368)),
+            (600, 0, 40, 360)
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Enumerates the audio output devices that desktop audio can be captured from.
+
+use color_eyre::{Result, eyre::Context as _};
+use windows::Win32::{
+    Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
+    Media::Audio::{DEVICE_STATE_ACTIVE, IMMDeviceEnumerator, MMDeviceEnumerator, eRender},
+    System::Com::{
+        CLSCTX_ALL, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx, CoTaskMemFree,
+        CoUninitialize, STGM_READ,
+    },
+};
+
+/// What OBS calls whichever device is currently the system default
+pub const DEFAULT_AUDIO_DEVICE_ID: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioOutputDevice {
+    /// WASAPI endpoint ID, which is what OBS expects
+    pub id: String,
+    pub name: String,
+}
+
+/// Lists the active audio output devices.
+///
+/// This runs on its own thread, as COM has to be initialized for it, and we don't want to clash with
+/// whatever the calling thread (e.g. the UI thread) has set up.
+pub fn get_audio_output_devices() -> Result<Vec<AudioOutputDevice>> {
+    std::thread::spawn(|| unsafe {
+        CoInitializeEx(None, COINIT_MULTITHREADED)
+            .ok()
+            .context("Failed to initialize COM")?;
+        let result = enumerate_audio_output_devices();
+        CoUninitialize();
+        result
+    })
+    .join()
+    .expect("audio device enumeration thread panicked")
+}
+
+unsafe fn enumerate_audio_output_devices() -> Result<Vec<AudioOutputDevice>> {
+    unsafe {
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .context("Failed to create device enumerator")?;
+        let collection = enumerator
+            .EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)
+            .context("Failed to enumerate audio endpoints")?;
+
+        let mut devices = vec![];
+        for i in 0..collection.GetCount()? {
+            let device = collection.Item(i)?;
+
+            let id_ptr = device.GetId()?;
+            let id = id_ptr.to_string();
+            CoTaskMemFree(Some(id_ptr.0 as *const _));
+            let Ok(id) = id else {
+                continue;
+            };
+
+            let name = device
+                .OpenPropertyStore(STGM_READ)
+                .and_then(|store| store.GetValue(&PKEY_Device_FriendlyName))
+                .map(|name| name.to_string())
+                .unwrap_or_else(|_| id.clone());
+
+            devices.push(AudioOutputDevice { id, name });
+        }
+        Ok(devices)
+    }
+}
@@ -0,0 +1,66 @@
+//! Windows that the user never wants recorded (password managers, chat apps, webcam previews...).
+//! Recording is paused while one of them is focused.
+
+/// Returns the denylist entry that the foreground window matches, if any
+pub fn foreground_window_match(denylist: &[String]) -> Option<&str> {
+    if denylist.is_empty() {
+        return None;
+    }
+    let (hwnd, pid) = game_process::foreground_window().ok()?;
+    let exe_name = game_process::exe_name_for_pid(pid)
+        .ok()
+        .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()));
+    let title = game_process::window_title(hwnd);
+    find_match(denylist, exe_name.as_deref(), title.as_deref())
+}
+
+/// Entries ending in `.exe` match the process name exactly; anything else matches part of the window title.
+/// Both are case-insensitive.
+fn find_match<'a>(
+    denylist: &'a [String],
+    exe_name: Option<&str>,
+    title: Option<&str>,
+) -> Option<&'a str> {
+    let title = title.map(str::to_lowercase);
+    denylist
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .find(|entry| {
+            let entry_lower = entry.to_lowercase();
+            if entry_lower.ends_with(".exe") {
+                exe_name.is_some_and(|exe| exe.eq_ignore_ascii_case(entry))
+            } else {
+                title.as_deref().is_some_and(|t| t.contains(&entry_lower))
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_match() {
+        let denylist = vec![
+            "Discord.exe".to_string(),
+            "1password".to_string(),
+            "  ".to_string(),
+        ];
+        assert_eq!(
+            find_match(&denylist, Some("discord.EXE"), None),
+            Some("Discord.exe")
+        );
+        assert_eq!(
+            find_match(&denylist, Some("chrome.exe"), Some("Vault - 1Password")),
+            Some("1password")
+        );
+        // A process name in the title doesn't count
+        assert_eq!(
+            find_match(&denylist, Some("notepad.exe"), Some("Discord.exe notes")),
+            None
+        );
+        // Blank entries match nothing
+        assert_eq!(find_match(&denylist, Some("game.exe"), Some("")), None);
+    }
+}
@@ -0,0 +1,80 @@
+//! Games can refuse to be captured. Windows doesn't let a process hook into one running with more rights than
+//! itself (i.e. a game run as administrator, when OWL Control isn't), and anti-cheat can block capture outright.
+//! Either way, OBS carries on recording black frames without complaint. This catches both cases so that the
+//! user can be told how to fix it, and recording is held back until they have.
+
+use std::time::Duration;
+
+use game_process::{Pid, windows::Win32::Foundation::E_ACCESSDENIED};
+
+/// How long a recording is given for the game to be hooked before it's assumed that it can't be.
+/// Hooking usually takes a second or two, but can take longer while the game is still loading.
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Why a game can't be captured
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureDenied {
+    /// The game is running as administrator, and OWL Control isn't
+    Elevated { game_exe: String, pid: Pid },
+    /// The recording never got anything from the game, for reasons we can't tell from here
+    NotHooked { game_exe: String },
+}
+
+impl CaptureDenied {
+    pub fn game_exe(&self) -> &str {
+        match self {
+            Self::Elevated { game_exe, .. } | Self::NotHooked { game_exe } => game_exe,
+        }
+    }
+
+    /// Whether the problem has gone away. Checked when the user says they've fixed it.
+    pub fn is_resolved(&self) -> bool {
+        match self {
+            // The game has to be restarted to stop running as administrator, so it being gone is progress
+            Self::Elevated { pid, .. } => {
+                !game_process::does_process_exist(*pid).unwrap_or_default()
+                    || check(*pid, self.game_exe()).is_ok()
+            }
+            // Only another recording can tell
+            Self::NotHooked { .. } => true,
+        }
+    }
+}
+
+impl std::fmt::Display for CaptureDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Elevated { game_exe, .. } => write!(
+                f,
+                "{game_exe} is running as administrator, so OWL Control can't capture it. Either run OWL Control as administrator too, or restart {game_exe} without it. Recording can't be started until then; see OWL Control for details."
+            ),
+            Self::NotHooked { game_exe } => write!(
+                f,
+                "OWL Control couldn't capture {game_exe}, so the recording was stopped and won't be uploaded. Recording can't be started until this is fixed; see OWL Control for details."
+            ),
+        }
+    }
+}
+
+/// Checks that OWL Control is allowed to capture the game before it's recorded
+pub fn check(pid: Pid, game_exe: &str) -> Result<(), CaptureDenied> {
+    // Administrators can capture anything
+    if game_process::is_current_process_elevated().unwrap_or_default() {
+        return Ok(());
+    }
+    let denied = || CaptureDenied::Elevated {
+        game_exe: game_exe.to_string(),
+        pid,
+    };
+    match game_process::is_elevated(pid) {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(denied()),
+        // Only a process with more rights than ours keeps its token from us
+        Err(e) if e.code() == E_ACCESSDENIED => Err(denied()),
+        Err(e) => {
+            // Not knowing isn't worth holding up the recording over; if it can't be captured, that's caught later
+            tracing::warn!(e=?e, game_exe, "Failed to check whether the game is running as administrator");
+            Ok(())
+        }
+    }
+}
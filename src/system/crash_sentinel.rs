@@ -0,0 +1,84 @@
+//! Notices when the previous run didn't exit cleanly (a crash, being killed from Task Manager, a power cut):
+//! a file describing the run is written at startup and removed on a clean exit, so if it's still there on
+//! the next launch, something went wrong.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunInfo {
+    pub version: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// The game that was being recorded, if any
+    #[serde(default)]
+    pub recording_game: Option<String>,
+}
+
+fn path() -> Option<PathBuf> {
+    config::get_persistent_dir()
+        .ok()
+        .map(|dir| dir.join(constants::filename::persistent::RUNNING))
+}
+
+/// Marks this run as started, returning the previous run if it never got to [`clear`].
+/// Has to be called after [`super::ensure_single_instance`], or we'd pick up the other instance's run.
+pub fn check_and_write() -> Option<RunInfo> {
+    let path = path()?;
+    let previous = std::fs::read_to_string(&path).ok().map(|contents| {
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            // Probably cut off by the crash; the run still didn't end cleanly
+            tracing::warn!(e=?e, "Couldn't read the previous run's details");
+            RunInfo {
+                version: "unknown".to_string(),
+                started_at: chrono::DateTime::UNIX_EPOCH,
+                recording_game: None,
+            }
+        })
+    });
+
+    write(&RunInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        started_at: chrono::Utc::now(),
+        recording_game: None,
+    });
+    previous
+}
+
+/// Notes which game is being recorded, so that we can say what was interrupted if we crash
+pub fn set_recording_game(recording_game: Option<String>) {
+    let Some(path) = path() else {
+        return;
+    };
+    let Some(mut run) = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<RunInfo>(&contents).ok())
+    else {
+        return;
+    };
+    run.recording_game = recording_game;
+    write(&run);
+}
+
+/// Marks this run as having exited cleanly
+pub fn clear() {
+    if let Some(path) = path() {
+        std::fs::remove_file(path).ok();
+    }
+}
+
+fn write(run: &RunInfo) {
+    let Some(path) = path() else {
+        return;
+    };
+    match serde_json::to_string(run) {
+        Ok(contents) => {
+            if let Err(e) = std::fs::write(&path, contents) {
+                tracing::warn!(e=?e, "Failed to write {}", path.display());
+            }
+        }
+        Err(e) => tracing::warn!(e=?e, "Failed to serialize run details"),
+    }
+}
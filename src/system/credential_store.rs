@@ -0,0 +1,187 @@
+//! Secure storage for the API key.
+//!
+//! The key is kept in the Windows Credential Manager. If that isn't available for whatever reason,
+//! we fall back to a DPAPI-encrypted file in the persistent directory, which can only be decrypted
+//! by the same Windows user. Each profile's key is stored separately; the default profile's is
+//! stored where the only key was before there were profiles.
+//!
+//! Other secrets that belong with the settings are kept in the config file as a [`ProtectedSecret`],
+//! encrypted the same way as the fallback file.
+
+use std::{fs, path::PathBuf};
+
+use color_eyre::{
+    Result,
+    eyre::{Context, eyre},
+};
+use serde::{Deserialize, Serialize};
+use windows::{
+    Win32::{
+        Foundation::{HLOCAL, LocalFree},
+        Security::Cryptography::{
+            CRYPT_INTEGER_BLOB, CRYPTPROTECT_UI_FORBIDDEN, CryptProtectData, CryptUnprotectData,
+        },
+    },
+    core::PCWSTR,
+};
+
+use crate::config::{DEFAULT_PROFILE, get_persistent_dir};
+
+const SERVICE: &str = "OWL Control";
+const USER: &str = "api_key";
+
+/// Loads the API key stored for `profile`, if there is one.
+pub fn load(profile: &str) -> Result<Option<String>> {
+    match keyring_entry(profile).map(|e| e.get_password()) {
+        Ok(Ok(api_key)) => return Ok(Some(api_key)),
+        Ok(Err(keyring::Error::NoEntry)) => {}
+        Ok(Err(e)) | Err(e) => {
+            tracing::warn!(e=?e, "Failed to read API key from credential store, trying fallback file");
+        }
+    }
+
+    let path = fallback_path(profile)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let encrypted = fs::read(&path).context("Failed to read encrypted API key file")?;
+    let decrypted = unprotect(&encrypted).context("Failed to decrypt API key file")?;
+    Ok(Some(
+        String::from_utf8(decrypted).context("Decrypted API key was not valid UTF-8")?,
+    ))
+}
+
+/// Stores the API key for `profile`, replacing any existing key.
+pub fn store(profile: &str, api_key: &str) -> Result<()> {
+    match keyring_entry(profile).and_then(|e| e.set_password(api_key)) {
+        Ok(()) => {
+            // Make sure we don't leave a stale copy of an older key lying around
+            remove_fallback_file(profile)?;
+            Ok(())
+        }
+        Err(e) => {
+            tracing::warn!(e=?e, "Failed to write API key to credential store, using fallback file");
+            let encrypted = protect(api_key.as_bytes()).context("Failed to encrypt API key")?;
+            fs::write(fallback_path(profile)?, encrypted)
+                .context("Failed to write encrypted API key")?;
+            Ok(())
+        }
+    }
+}
+
+/// Removes the API key stored for `profile` from all locations.
+pub fn clear(profile: &str) -> Result<()> {
+    match keyring_entry(profile).and_then(|e| e.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => {
+            tracing::warn!(e=?e, "Failed to delete API key from credential store");
+        }
+    }
+    remove_fallback_file(profile)
+}
+
+/// A secret encrypted for the current Windows user, stored as hex so that it can go in the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProtectedSecret(String);
+impl ProtectedSecret {
+    pub fn protect(secret: &str) -> Result<Self> {
+        let encrypted = protect(secret.as_bytes()).context("Failed to encrypt secret")?;
+        Ok(Self(encrypted.iter().map(|b| format!("{b:02x}")).collect()))
+    }
+
+    pub fn reveal(&self) -> Result<String> {
+        let encrypted = (0..self.0.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(self.0.get(i..i + 2).unwrap_or_default(), 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .context("Stored secret was not valid hex")?;
+        let decrypted = unprotect(&encrypted).context("Failed to decrypt secret")?;
+        String::from_utf8(decrypted).context("Decrypted secret was not valid UTF-8")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn keyring_entry(profile: &str) -> keyring::Result<keyring::Entry> {
+    if profile == DEFAULT_PROFILE {
+        keyring::Entry::new(SERVICE, USER)
+    } else {
+        keyring::Entry::new(SERVICE, &format!("{USER}:{profile}"))
+    }
+}
+
+fn fallback_path(profile: &str) -> Result<PathBuf> {
+    let filename = if profile == DEFAULT_PROFILE {
+        constants::filename::persistent::API_KEY.to_string()
+    } else {
+        // Profile names can have anything in them, so they're hex encoded to make a safe filename
+        let encoded = profile
+            .bytes()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+        format!("api_key.{encoded}.bin")
+    };
+    Ok(get_persistent_dir()?.join(filename))
+}
+
+fn remove_fallback_file(profile: &str) -> Result<()> {
+    let path = fallback_path(profile)?;
+    if path.exists() {
+        fs::remove_file(&path).context("Failed to remove encrypted API key file")?;
+    }
+    Ok(())
+}
+
+fn protect(data: &[u8]) -> Result<Vec<u8>> {
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: data.len() as u32,
+        pbData: data.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    unsafe {
+        CryptProtectData(
+            &input,
+            PCWSTR::null(),
+            None,
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+        .map_err(|e| eyre!("CryptProtectData failed: {e}"))?;
+        Ok(take_blob(output))
+    }
+}
+
+fn unprotect(data: &[u8]) -> Result<Vec<u8>> {
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: data.len() as u32,
+        pbData: data.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    unsafe {
+        CryptUnprotectData(
+            &input,
+            None,
+            None,
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+        .map_err(|e| eyre!("CryptUnprotectData failed: {e}"))?;
+        Ok(take_blob(output))
+    }
+}
+
+/// Copies the contents of a DPAPI-allocated blob and frees it.
+unsafe fn take_blob(blob: CRYPT_INTEGER_BLOB) -> Vec<u8> {
+    unsafe {
+        let bytes = std::slice::from_raw_parts(blob.pbData, blob.cbData as usize).to_vec();
+        LocalFree(Some(HLOCAL(blob.pbData as _)));
+        bytes
+    }
+}
@@ -1,4 +1,6 @@
 use color_eyre::Result;
+use constants::encoding::{SUPPORTED_VIDEO_ENCODERS, VideoEncoderType};
+use egui_wgpu::wgpu;
 use serde::{Deserialize, Serialize};
 use sysinfo::System;
 
@@ -52,6 +54,21 @@ pub struct HardwareSpecs {
     pub system: SystemSpecs,
 }
 
+/// Video encoders that can be used on this machine: the software encoder, and the hardware encoders
+/// of any GPU that's present
+pub fn get_available_video_encoders(adapter_infos: &[wgpu::AdapterInfo]) -> Vec<VideoEncoderType> {
+    SUPPORTED_VIDEO_ENCODERS
+        .iter()
+        .copied()
+        .filter(|encoder| match encoder.gpu_vendor_id() {
+            None => true,
+            Some(vendor_id) => adapter_infos
+                .iter()
+                .any(|a| a.vendor == vendor_id && a.device_type != wgpu::DeviceType::Cpu),
+        })
+        .collect()
+}
+
 pub fn get_hardware_specs(gpus: Vec<GpuSpecs>) -> Result<HardwareSpecs> {
     let mut sys = System::new_all();
     sys.refresh_all();
@@ -89,29 +106,287 @@ pub fn get_hardware_specs(gpus: Vec<GpuSpecs>) -> Result<HardwareSpecs> {
 #[cfg(target_os = "windows")]
 /// Returns the resolution of the primary monitor
 pub fn get_primary_monitor_resolution() -> Option<(u32, u32)> {
+    get_monitor_display_mode(get_primary_monitor()?)
+        .map(|devmode| (devmode.dmPelsWidth, devmode.dmPelsHeight))
+}
+
+#[cfg(target_os = "windows")]
+/// Returns the resolution of the monitor that the given window is (mostly) on.
+/// Returns None if the window isn't on any monitor, e.g. because it no longer exists.
+pub fn get_window_monitor_resolution(hwnd: windows::Win32::Foundation::HWND) -> Option<(u32, u32)> {
+    use windows::Win32::Graphics::Gdi::{MONITOR_DEFAULTTONULL, MonitorFromWindow};
+
+    let monitor = unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL) };
+    if monitor.is_invalid() {
+        return None;
+    }
+    get_monitor_display_mode(monitor).map(|devmode| (devmode.dmPelsWidth, devmode.dmPelsHeight))
+}
+
+#[cfg(target_os = "windows")]
+/// Returns the device name of the monitor that the given window is (mostly) on, see [`MonitorInfo::name`].
+/// Returns None if the window isn't on any monitor, e.g. because it no longer exists.
+pub fn get_window_monitor_name(hwnd: windows::Win32::Foundation::HWND) -> Option<String> {
+    use windows::Win32::Graphics::Gdi::{MONITOR_DEFAULTTONULL, MonitorFromWindow};
+
+    let monitor = unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL) };
+    if monitor.is_invalid() {
+        return None;
+    }
+    get_monitor_info(monitor).map(|info| device_name(&info.szDevice))
+}
+
+/// A rectangle on the desktop, in physical pixels. Monitors other than the primary one can have negative coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+impl ScreenRect {
+    pub fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+    /// The part of this that's also in `other`, if they overlap at all
+    pub fn intersect(&self, other: &ScreenRect) -> Option<ScreenRect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        (right > x && bottom > y).then(|| ScreenRect {
+            x,
+            y,
+            width: (right - x) as u32,
+            height: (bottom - y) as u32,
+        })
+    }
+}
+
+/// A monitor, and where it sits on the desktop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorInfo {
+    /// Device name, e.g. `\\.\DISPLAY1`, which stays the same while the monitor's plugged into the same port
+    pub name: String,
+    pub rect: ScreenRect,
+    pub primary: bool,
+}
+
+#[cfg(target_os = "windows")]
+/// Returns every monitor on the desktop, the primary one first
+pub fn get_monitors() -> Vec<MonitorInfo> {
     use windows::{
         Win32::{
-            Foundation::POINT,
-            Graphics::Gdi::{
-                DEVMODEW, ENUM_CURRENT_SETTINGS, EnumDisplaySettingsW, GetMonitorInfoW,
-                MONITORINFO, MONITORINFOEXW, MonitorFromPoint,
-            },
+            Foundation::{LPARAM, RECT},
+            Graphics::Gdi::{EnumDisplayMonitors, HDC, HMONITOR, MONITORINFOF_PRIMARY},
         },
-        core::PCWSTR,
+        core::BOOL,
     };
 
-    // Get the primary monitor handle
-    let primary_monitor = unsafe {
-        MonitorFromPoint(
-            POINT { x: 0, y: 0 },
-            windows::Win32::Graphics::Gdi::MONITOR_DEFAULTTOPRIMARY,
+    unsafe extern "system" fn callback(
+        monitor: HMONITOR,
+        _: HDC,
+        _: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        // SAFETY: `data` is the vec below, which outlives the enumeration
+        let monitors = unsafe { &mut *(data.0 as *mut Vec<MonitorInfo>) };
+        if let Some(info) = get_monitor_info(monitor) {
+            let rect = info.monitorInfo.rcMonitor;
+            monitors.push(MonitorInfo {
+                name: device_name(&info.szDevice),
+                rect: ScreenRect {
+                    x: rect.left,
+                    y: rect.top,
+                    width: (rect.right - rect.left).max(0) as u32,
+                    height: (rect.bottom - rect.top).max(0) as u32,
+                },
+                primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+            });
+        }
+        true.into()
+    }
+
+    let mut monitors: Vec<MonitorInfo> = vec![];
+    let _ = unsafe {
+        EnumDisplayMonitors(
+            None,
+            None,
+            Some(callback),
+            LPARAM(&mut monitors as *mut _ as isize),
         )
     };
-    if primary_monitor.is_invalid() {
+    monitors.sort_by_key(|m| !m.primary);
+    monitors
+}
+
+/// A monitor's device name, from the nul-terminated buffer Windows fills in
+fn device_name(sz_device: &[u16]) -> String {
+    let len = sz_device
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(sz_device.len());
+    String::from_utf16_lossy(&sz_device[..len])
+}
+
+/// A small picture of what's on a monitor, to tell monitors apart when picking one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorThumbnail {
+    pub width: u32,
+    pub height: u32,
+    /// Row by row, 4 bytes per pixel
+    pub rgba: Vec<u8>,
+}
+
+#[cfg(target_os = "windows")]
+/// Takes a screenshot of `rect` on the desktop, scaled down to `width` pixels wide
+pub fn get_screen_thumbnail(rect: ScreenRect, width: u32) -> Option<MonitorThumbnail> {
+    use windows::Win32::Graphics::Gdi::{
+        BI_RGB, BITMAPINFO, BITMAPINFOHEADER, CreateCompatibleBitmap, CreateCompatibleDC,
+        DIB_RGB_COLORS, DeleteDC, DeleteObject, GetDC, GetDIBits, HALFTONE, ReleaseDC, SRCCOPY,
+        SelectObject, SetStretchBltMode, StretchBlt,
+    };
+
+    if rect.width == 0 || rect.height == 0 || width == 0 {
         return None;
     }
+    let height = ((rect.height as u64 * width as u64) / rect.width as u64).max(1) as u32;
+
+    unsafe {
+        let screen = GetDC(None);
+        if screen.is_invalid() {
+            return None;
+        }
+        let memory = CreateCompatibleDC(Some(screen));
+        let bitmap = CreateCompatibleBitmap(screen, width as i32, height as i32);
+        let previous = SelectObject(memory, bitmap.into());
+        SetStretchBltMode(memory, HALFTONE);
+        let copied = StretchBlt(
+            memory,
+            0,
+            0,
+            width as i32,
+            height as i32,
+            Some(screen),
+            rect.x,
+            rect.y,
+            rect.width as i32,
+            rect.height as i32,
+            SRCCOPY,
+        )
+        .as_bool();
+
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width as i32,
+                // Negative for rows top to bottom
+                biHeight: -(height as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bgra = vec![0u8; width as usize * height as usize * 4];
+        // The bitmap can't be selected into a DC while its bits are read
+        SelectObject(memory, previous);
+        let lines = GetDIBits(
+            memory,
+            bitmap,
+            0,
+            height,
+            Some(bgra.as_mut_ptr().cast()),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(memory);
+        ReleaseDC(None, screen);
+
+        if !copied || lines != height as i32 {
+            return None;
+        }
+        for pixel in bgra.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
+        }
+        Some(MonitorThumbnail {
+            width,
+            height,
+            rgba: bgra,
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+/// Returns the outlines of the visible, non-minimized top-level windows, frontmost first
+pub fn get_visible_window_rects() -> Vec<ScreenRect> {
+    use windows::{
+        Win32::{
+            Foundation::{HWND, LPARAM, RECT},
+            UI::WindowsAndMessaging::{EnumWindows, GetWindowRect, IsIconic, IsWindowVisible},
+        },
+        core::BOOL,
+    };
+
+    unsafe extern "system" fn callback(hwnd: HWND, data: LPARAM) -> BOOL {
+        // SAFETY: `data` is the vec below, which outlives the enumeration
+        let rects = unsafe { &mut *(data.0 as *mut Vec<ScreenRect>) };
+        let mut rect = RECT::default();
+        if unsafe { IsWindowVisible(hwnd) }.as_bool()
+            && !unsafe { IsIconic(hwnd) }.as_bool()
+            && unsafe { GetWindowRect(hwnd, &mut rect) }.is_ok()
+            && rect.right > rect.left
+            && rect.bottom > rect.top
+        {
+            rects.push(ScreenRect {
+                x: rect.left,
+                y: rect.top,
+                width: (rect.right - rect.left) as u32,
+                height: (rect.bottom - rect.top) as u32,
+            });
+        }
+        true.into()
+    }
+
+    let mut rects: Vec<ScreenRect> = vec![];
+    let _ = unsafe { EnumWindows(Some(callback), LPARAM(&mut rects as *mut _ as isize)) };
+    rects
+}
+
+#[cfg(target_os = "windows")]
+/// Returns the refresh rate of the primary monitor, in Hz
+pub fn get_primary_monitor_refresh_rate() -> Option<u32> {
+    // 0 and 1 both mean "hardware default", which doesn't tell us anything
+    get_monitor_display_mode(get_primary_monitor()?)
+        .map(|devmode| devmode.dmDisplayFrequency)
+        .filter(|&hz| hz > 1)
+}
+
+#[cfg(target_os = "windows")]
+fn get_primary_monitor() -> Option<windows::Win32::Graphics::Gdi::HMONITOR> {
+    use windows::Win32::{
+        Foundation::POINT,
+        Graphics::Gdi::{MONITOR_DEFAULTTOPRIMARY, MonitorFromPoint},
+    };
+
+    let primary_monitor =
+        unsafe { MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY) };
+    (!primary_monitor.is_invalid()).then_some(primary_monitor)
+}
+
+#[cfg(target_os = "windows")]
+fn get_monitor_info(
+    monitor: windows::Win32::Graphics::Gdi::HMONITOR,
+) -> Option<windows::Win32::Graphics::Gdi::MONITORINFOEXW> {
+    use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MONITORINFO, MONITORINFOEXW};
 
-    // Get the monitor info
     let mut monitor_info = MONITORINFOEXW {
         monitorInfo: MONITORINFO {
             cbSize: std::mem::size_of::<MONITORINFOEXW>() as u32,
@@ -119,14 +394,24 @@ pub fn get_primary_monitor_resolution() -> Option<(u32, u32)> {
         },
         ..Default::default()
     };
-    unsafe {
-        GetMonitorInfoW(
-            primary_monitor,
-            &mut monitor_info as *mut _ as *mut MONITORINFO,
-        )
-    }
-    .ok()
-    .ok()?;
+    unsafe { GetMonitorInfoW(monitor, &mut monitor_info as *mut _ as *mut MONITORINFO) }
+        .ok()
+        .ok()?;
+    Some(monitor_info)
+}
+
+#[cfg(target_os = "windows")]
+/// Returns the current display mode of the given monitor
+fn get_monitor_display_mode(
+    monitor: windows::Win32::Graphics::Gdi::HMONITOR,
+) -> Option<windows::Win32::Graphics::Gdi::DEVMODEW> {
+    use windows::{
+        Win32::Graphics::Gdi::{DEVMODEW, ENUM_CURRENT_SETTINGS, EnumDisplaySettingsW},
+        core::PCWSTR,
+    };
+
+    // Get the monitor info
+    let monitor_info = get_monitor_info(monitor)?;
 
     // Get the display mode
     let mut devmode = DEVMODEW {
@@ -143,5 +428,5 @@ pub fn get_primary_monitor_resolution() -> Option<(u32, u32)> {
     .ok()
     .ok()?;
 
-    Some((devmode.dmPelsWidth, devmode.dmPelsHeight))
+    Some(devmode)
 }
@@ -78,3 +78,22 @@ static CODE_KEY_MAP: LazyLock<HashMap<&str, u16>> = LazyLock::new(|| {
 pub(crate) fn lookup_keycode(key: &str) -> Option<u16> {
     CODE_KEY_MAP.get(&key.to_lowercase().as_str()).copied()
 }
+
+/// The name of a keycode, if it's one we know about
+pub(crate) fn keycode_name(code: u16) -> Option<&'static str> {
+    CODE_KEY_MAP
+        .iter()
+        .find(|&(_, &c)| c == code)
+        .map(|(&key, _)| key)
+}
+
+/// Keys that most games bind by default. Using one of these as a recording hotkey means that
+/// recording will be toggled during normal play, so we warn the user about it.
+const COMMON_GAME_KEYS: &[&str] = &[
+    "w", "a", "s", "d", "q", "e", "r", "f", "c", "g", "x", "z", "1", "2", "3", "4", "5", "space",
+    "lshift", "lctrl", "lalt", "tab", "escape", "enter",
+];
+
+pub(crate) fn is_common_game_key(key: &str) -> bool {
+    COMMON_GAME_KEYS.contains(&key.to_lowercase().as_str())
+}
@@ -1,5 +1,11 @@
+pub mod audio_devices;
+pub mod capture_denylist;
+pub mod capture_permission;
+pub mod crash_sentinel;
+pub mod credential_store;
 pub mod ensure_single_instance;
 pub mod hardware_id;
 pub mod hardware_specs;
 pub mod keycode;
 pub mod raw_input_debouncer;
+pub mod session_end;
@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    hash::Hash,
     time::Duration,
 };
 
@@ -10,9 +11,10 @@ use input_capture::{Event, PressState};
 pub(crate) struct EventDebouncer {
     keyboard: KeyDebouncer,
     mouse_key: KeyDebouncer,
-    gamepad_button: KeyDebouncer,
-    gamepad_button_value: AnalogDebouncer,
-    gamepad_axis: AnalogDebouncer,
+    /// Keyed by (gamepad, button) so that gamepads don't debounce each other
+    gamepad_button: KeyDebouncer<(u16, u16)>,
+    gamepad_button_value: AnalogDebouncer<(u16, u16)>,
+    gamepad_axis: AnalogDebouncer<(u16, u16)>,
 }
 
 impl EventDebouncer {
@@ -25,28 +27,38 @@ impl EventDebouncer {
         match e {
             Event::MousePress { key, press_state } => self.mouse_key.debounce(key, press_state),
             Event::KeyPress { key, press_state } => self.keyboard.debounce(key, press_state),
-            Event::GamepadButtonPress { key, press_state } => {
-                self.gamepad_button.debounce(key, press_state)
+            Event::GamepadButtonPress {
+                gamepad,
+                key,
+                press_state,
+            } => self.gamepad_button.debounce((gamepad, key), press_state),
+            Event::GamepadButtonChange { gamepad, key, .. } => {
+                self.gamepad_button_value.debounce((gamepad, key))
             }
-            Event::GamepadButtonChange { key, .. } => self.gamepad_button_value.debounce(key),
-            Event::GamepadAxisChange { axis: key, .. } => self.gamepad_axis.debounce(key),
-            Event::MouseMove(_) | Event::MouseScroll { .. } => true,
+            Event::GamepadAxisChange { gamepad, axis, .. } => {
+                self.gamepad_axis.debounce((gamepad, axis))
+            }
+            Event::MouseMove(_)
+            | Event::CursorMove(_)
+            | Event::MouseScroll { .. }
+            | Event::GamepadConnected { .. }
+            | Event::GamepadDisconnected { .. } => true,
         }
     }
 }
 
 #[derive(Default)]
-struct KeyDebouncer {
-    pressed_keys: HashSet<u16>,
+struct KeyDebouncer<K = u16> {
+    pressed_keys: HashSet<K>,
 }
-impl KeyDebouncer {
+impl<K: Hash + Eq + Default> KeyDebouncer<K> {
     #[allow(dead_code)]
     pub(crate) fn new() -> Self {
         Self::default()
     }
 
     /// Returns true if the key event should be processed, or false if it should be ignored.
-    pub(crate) fn debounce(&mut self, key: u16, press_state: PressState) -> bool {
+    pub(crate) fn debounce(&mut self, key: K, press_state: PressState) -> bool {
         match press_state {
             PressState::Pressed => self.pressed_keys.insert(key),
             PressState::Released => {
@@ -58,12 +70,12 @@ impl KeyDebouncer {
 }
 
 #[derive(Default)]
-struct AnalogDebouncer {
-    last_change: HashMap<u16, std::time::Instant>,
+struct AnalogDebouncer<K = u16> {
+    last_change: HashMap<K, std::time::Instant>,
 }
-impl AnalogDebouncer {
+impl<K: Hash + Eq> AnalogDebouncer<K> {
     /// Returns whether or not a sufficient amount of time has passed since the last change.
-    pub(crate) fn debounce(&mut self, key: u16) -> bool {
+    pub(crate) fn debounce(&mut self, key: K) -> bool {
         const MAX_ANALOGUE_SAMPLING_MICROSECONDS: u64 = (1_000_000.0 / (FPS as f32 * 2.0)) as u64;
 
         let now = std::time::Instant::now();
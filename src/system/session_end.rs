@@ -0,0 +1,113 @@
+//! Windows doesn't close applications the usual way when the user logs off or shuts down: it asks every
+//! top-level window whether the session can end, and terminates the process shortly after the answer.
+//! We listen for this on a hidden window so that recordings can be finalised before that happens.
+
+use std::sync::Mutex;
+
+use color_eyre::{
+    Result,
+    eyre::{Context as _, bail},
+};
+use windows::{
+    Win32::{
+        Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+        System::{
+            LibraryLoader::GetModuleHandleW,
+            Shutdown::{ShutdownBlockReasonCreate, ShutdownBlockReasonDestroy},
+        },
+        UI::WindowsAndMessaging::{
+            CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, MSG, RegisterClassW,
+            TranslateMessage, WINDOW_EX_STYLE, WINDOW_STYLE, WM_ENDSESSION, WM_QUERYENDSESSION,
+            WNDCLASSW,
+        },
+    },
+    core::{PCWSTR, w},
+};
+
+/// Run when the session ends. Windows terminates us once this returns, so it should block until
+/// everything that needs saving has been saved.
+type SessionEndCallback = Box<dyn FnOnce() + Send>;
+
+static ON_SESSION_END: Mutex<Option<SessionEndCallback>> = Mutex::new(None);
+
+/// Starts listening for the session ending on a thread of its own. `on_session_end` is called at most once.
+pub fn listen(on_session_end: impl FnOnce() + Send + 'static) {
+    *ON_SESSION_END.lock().unwrap() = Some(Box::new(on_session_end));
+    std::thread::spawn(|| {
+        if let Err(e) = run_window() {
+            tracing::error!(e=?e, "Failed to listen for the session ending");
+        }
+    });
+}
+
+fn run_window() -> Result<()> {
+    unsafe {
+        let class_name = w!("OwlControlSessionEndWindowClass");
+        let h_instance: HINSTANCE = GetModuleHandleW(None)?.into();
+
+        let wc = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: h_instance,
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        if RegisterClassW(&wc) == 0 {
+            bail!("failed to register window class");
+        }
+
+        // This has to be a top-level window (albeit never shown); message-only windows don't get told about the session ending
+        CreateWindowExW(
+            WINDOW_EX_STYLE(0),
+            class_name,
+            PCWSTR::null(),
+            WINDOW_STYLE(0),
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            Some(h_instance),
+            None,
+        )
+        .wrap_err("failed to create window")?;
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+        Ok(())
+    }
+}
+
+unsafe extern "system" fn window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    unsafe {
+        match msg {
+            WM_QUERYENDSESSION => {
+                // We don't stop the session from ending, but this tells the user why we're taking a moment
+                if ShutdownBlockReasonCreate(hwnd, w!("Saving your recording")).is_err() {
+                    tracing::warn!("Failed to set shutdown block reason");
+                }
+                LRESULT(1)
+            }
+            WM_ENDSESSION => {
+                // A zero `wparam` means that something else cancelled the session ending
+                if wparam.0 != 0 {
+                    tracing::info!("Windows session is ending, shutting down");
+                    if let Some(on_session_end) = ON_SESSION_END.lock().unwrap().take() {
+                        on_session_end();
+                    }
+                }
+                ShutdownBlockReasonDestroy(hwnd).ok();
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+}
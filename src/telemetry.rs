@@ -0,0 +1,143 @@
+//! Anonymous statistics on how recording and uploading are going, sent only if the user has opted in.
+//! Nothing that could identify the user or what they recorded is collected: no API key, user ID, game,
+//! file names, error messages or footage, only counts and totals. See `TELEMETRY.md` for the full list.
+//!
+//! Statistics are gathered in memory and sent in one batch every [`constants::TELEMETRY_INTERVAL`]. If the
+//! user hasn't opted in, each batch is thrown away instead, without ever leaving the computer.
+
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
+
+use color_eyre::eyre;
+use constants::encoding::VideoEncoderType;
+use serde::Serialize;
+
+use crate::{
+    api::{ProxyRejected, RateLimited},
+    output_types::SkippedFrames,
+};
+
+#[derive(Debug, Default)]
+pub struct Telemetry {
+    batch: Mutex<TelemetryBatch>,
+}
+impl Telemetry {
+    pub fn recording_finished(
+        &self,
+        duration: Duration,
+        skipped_frames: Option<SkippedFrames>,
+        encoder: VideoEncoderType,
+    ) {
+        let mut batch = self.batch.lock().unwrap();
+        batch.recordings += 1;
+        batch.recorded_seconds += duration.as_secs();
+        *batch.recordings_by_encoder.entry(encoder).or_default() += 1;
+        if let Some(skipped_frames) = skipped_frames {
+            batch.frames += skipped_frames.total as u64;
+            batch.skipped_frames += skipped_frames.skipped as u64;
+            if skipped_frames.percentage() > constants::SKIPPED_FRAMES_WARNING_PERCENTAGE {
+                batch.recordings_dropping_frames += 1;
+            }
+        }
+    }
+
+    pub fn upload_succeeded(&self, bytes: u64) {
+        let mut batch = self.batch.lock().unwrap();
+        batch.uploads += 1;
+        batch.uploaded_bytes += bytes;
+    }
+
+    pub fn upload_failed(&self, error: &eyre::Report) {
+        let mut batch = self.batch.lock().unwrap();
+        *batch
+            .upload_failures
+            .entry(UploadFailure::of(error))
+            .or_default() += 1;
+    }
+
+    /// Everything gathered since the last batch was taken, unless there's nothing to report
+    pub fn take_batch(&self) -> Option<TelemetryBatch> {
+        let batch = std::mem::take(&mut *self.batch.lock().unwrap());
+        (batch != TelemetryBatch::default()).then_some(batch)
+    }
+}
+
+/// What's sent, see `TELEMETRY.md`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryBatch {
+    pub recordings: u32,
+    pub recorded_seconds: u64,
+    pub recordings_by_encoder: BTreeMap<VideoEncoderType, u32>,
+    /// Frames that the encoder had to be given, and how many of them it couldn't keep up with
+    pub frames: u64,
+    pub skipped_frames: u64,
+    /// Recordings that dropped enough frames for the user to be warned about it
+    pub recordings_dropping_frames: u32,
+    pub uploads: u32,
+    pub uploaded_bytes: u64,
+    pub upload_failures: BTreeMap<UploadFailure, u32>,
+}
+
+/// Why an upload failed, in broad enough terms that nothing about the recording or the user comes with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UploadFailure {
+    RateLimited,
+    /// The server, or the proxy in front of it, couldn't be reached
+    Network,
+    Cancelled,
+    Other,
+}
+impl UploadFailure {
+    fn of(error: &eyre::Report) -> Self {
+        if RateLimited::find_in(error).is_some() {
+            UploadFailure::RateLimited
+        } else if error
+            .chain()
+            .any(|e| e.is::<reqwest::Error>() || e.is::<ProxyRejected>())
+        {
+            UploadFailure::Network
+        } else if error.to_string() == "Cancelled" {
+            UploadFailure::Cancelled
+        } else {
+            UploadFailure::Other
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_batch() {
+        let telemetry = Telemetry::default();
+        assert_eq!(telemetry.take_batch(), None);
+
+        telemetry.recording_finished(
+            Duration::from_secs(90),
+            Some(SkippedFrames {
+                skipped: 300,
+                total: 5400,
+            }),
+            VideoEncoderType::NvEnc,
+        );
+        telemetry.recording_finished(Duration::from_secs(30), None, VideoEncoderType::NvEnc);
+        telemetry.upload_succeeded(1024);
+        telemetry.upload_failed(&eyre::eyre!("Cancelled"));
+        telemetry.upload_failed(&eyre::Report::new(RateLimited { retry_after: None }));
+
+        let batch = telemetry.take_batch().unwrap();
+        assert_eq!(batch.recordings, 2);
+        assert_eq!(batch.recorded_seconds, 120);
+        assert_eq!(batch.recordings_by_encoder[&VideoEncoderType::NvEnc], 2);
+        assert_eq!((batch.skipped_frames, batch.frames), (300, 5400));
+        assert_eq!(batch.recordings_dropping_frames, 1);
+        assert_eq!((batch.uploads, batch.uploaded_bytes), (1, 1024));
+        assert_eq!(batch.upload_failures[&UploadFailure::Cancelled], 1);
+        assert_eq!(batch.upload_failures[&UploadFailure::RateLimited], 1);
+
+        // Each batch only has what happened since the last one
+        assert_eq!(telemetry.take_batch(), None);
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    api::ApiClient,
+    api::{ApiClient, ApiKeyValidationError, UserInfo},
     app_state::{AppState, AsyncRequest, GitHubRelease, RecordingStatus, UiUpdate},
     assets::{get_honk_0_bytes, get_honk_1_bytes},
-    system::keycode::lookup_keycode,
+    encryption, report,
+    system::{capture_denylist, crash_sentinel, keycode::lookup_keycode},
     ui::notification::{NotificationType, show_notification},
     upload,
     util::version::is_version_newer,
@@ -11,7 +12,7 @@ use std::{
     io::Cursor,
     path::PathBuf,
     sync::{Arc, atomic::Ordering},
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant},
 };
 
 use color_eyre::{
@@ -20,39 +21,45 @@ use color_eyre::{
 };
 
 use constants::{
-    ALT_TAB_GRACE_PERIOD, GH_ORG, GH_REPO, MAX_FOOTAGE, MAX_IDLE_DURATION,
+    ALT_TAB_GRACE_PERIOD, GH_ORG, GH_REPO, IDLE_AUTO_STOP_WARNING, MAX_FOOTAGE, MAX_IDLE_DURATION,
     unsupported_games::UnsupportedGames,
 };
 use game_process::does_process_exist;
 use input_capture::InputCapture;
 use rodio::{Decoder, Sink};
 use tokio::{sync::oneshot, time::MissedTickBehavior};
-use windows::Win32::{Foundation::HWND, UI::WindowsAndMessaging::GetForegroundWindow};
+use windows::Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{GetForegroundWindow, IsIconic},
+};
 
-use crate::{record::Recorder, system::raw_input_debouncer::EventDebouncer};
+use crate::{
+    record::{self, Benchmark, BenchmarkReport, Recorder, RecordingName},
+    system::raw_input_debouncer::EventDebouncer,
+};
 
 pub fn run(
     app_state: Arc<AppState>,
-    recording_location: PathBuf,
     log_path: PathBuf,
     async_request_rx: tokio::sync::mpsc::Receiver<AsyncRequest>,
     stopped_rx: tokio::sync::broadcast::Receiver<()>,
+    headless: bool,
 ) -> Result<()> {
     tokio::runtime::Runtime::new().unwrap().block_on(main(
         app_state,
-        recording_location,
         log_path,
         async_request_rx,
         stopped_rx,
+        headless,
     ))
 }
 
 async fn main(
     app_state: Arc<AppState>,
-    recording_location: PathBuf,
     log_path: PathBuf,
     mut async_request_rx: tokio::sync::mpsc::Receiver<AsyncRequest>,
     mut stopped_rx: tokio::sync::broadcast::Receiver<()>,
+    headless: bool,
 ) -> Result<()> {
     let stream_handle =
         rodio::OutputStreamBuilder::open_default_stream().expect("open default audio stream");
@@ -60,14 +67,18 @@ async fn main(
 
     let mut recorder = Recorder::new(
         Box::new({
-            let recording_location = recording_location.clone();
-            move || {
-                recording_location.join(
-                    SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs()
-                        .to_string(),
+            let app_state = app_state.clone();
+            move |name: &RecordingName| {
+                let template = app_state
+                    .config
+                    .read()
+                    .unwrap()
+                    .preferences
+                    .recording_name_template
+                    .clone();
+                record::new_recording_folder(
+                    &app_state.recording_location(),
+                    &record::recording_folder_name(&template, name),
                 )
             }
         }),
@@ -83,37 +94,100 @@ async fn main(
     let mut last_active = Instant::now();
     let mut start_on_activity = false;
     let mut actively_recording_window: Option<HWND> = None;
+    // Set by a start request from the tray: the window that was in the foreground at the time,
+    // so that we can start recording once the user switches away from it to their game
+    let mut start_on_focus_change: Option<HWND> = None;
+    // When recording is due to start, if the user asked for a countdown beforehand
+    let mut countdown_until: Option<Instant> = None;
+    // The benchmark being run, and like `start_on_focus_change`, the window it's waiting for the user to switch away from
+    let mut benchmark: Option<(Benchmark, Option<HWND>)> = None;
+    // Whether the current pause is ours, because a denylisted window was focused, rather than the user's
+    let mut paused_for_denylist = false;
+    // Likewise, because the game was minimized
+    let mut paused_for_minimize = false;
     let mut window_unfocused_at: Option<Instant> = None;
+    // The API key being validated, so that asking again while it is doesn't send it to the server twice
+    let mut api_key_validation: Option<(String, tokio::task::JoinHandle<()>)> = None;
 
     let mut perform_checks = tokio::time::interval(Duration::from_secs(1));
     perform_checks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // Checked much more often than everything else, so that as little as possible of a denylisted window is recorded
+    let mut check_denylist = tokio::time::interval(Duration::from_millis(100));
+    check_denylist.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // Looking up the free space requires enumerating disks, so we do it less often than the other checks
+    const DISK_SPACE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+    let mut last_disk_space_check: Option<Instant> = None;
+    let mut low_disk_space_mb: Option<u64> = None;
+
+    // Account details and upload stats can change without us knowing (e.g. uploads being verified),
+    // so they're fetched again every so often. The first tick is immediate, before anyone has logged in.
+    let mut refresh_account_interval = tokio::time::interval(Duration::from_secs(15 * 60));
+    refresh_account_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut offline_retry_interval = tokio::time::interval(Duration::from_secs(60));
+    offline_retry_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // Uploads that lost their connection are picked up again soon after it comes back
+    let mut reconnect_interval = tokio::time::interval(Duration::from_secs(10));
+    reconnect_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // Keeps the connection status in the main view current. Only asks whether the server is there, which is cheap.
+    let mut connection_check_interval = tokio::time::interval(Duration::from_secs(60));
+    connection_check_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut telemetry_interval = tokio::time::interval(constants::TELEMETRY_INTERVAL);
+    telemetry_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut preview_interval = tokio::time::interval(record::PREVIEW_INTERVAL);
+    preview_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
     let mut debouncer = EventDebouncer::new();
 
-    let api_client = Arc::new(ApiClient::new());
-    let mut valid_api_key_and_user_id: Option<(String, String)> = None;
+    // Finish off anything left over from an unclean exit, before it could be picked up for upload
+    let recording_location = app_state.recording_location();
+    let recovered_recordings = tokio::task::spawn_blocking(move || {
+        record::recover_interrupted_recordings(&recording_location)
+    })
+    .await
+    .unwrap_or_default();
+    if let Some(unclean_exit) = app_state.unclean_exit.write().unwrap().as_mut() {
+        unclean_exit.recovered_recordings = recovered_recordings;
+    }
+
+    let proxy = app_state.config.read().unwrap().preferences.proxy.clone();
+    let api_client = Arc::new(ApiClient::new(app_state.api_base_url(), proxy.as_deref()));
 
     let mut unsupported_games = UnsupportedGames::load_from_embedded();
 
     // Initial async requests to GitHub/server
     tokio::spawn(startup_requests(app_state.clone()));
 
+    // In headless mode, Ctrl+C is how the user says they're done, so we upload on the way out
+    let mut upload_on_exit = false;
+
     loop {
-        let (honk, start_key, stop_key) = {
+        let (honk, start_key, stop_key, pause_key, privacy_pause_key, save_replay_key) = {
             let cfg = app_state.config.read().unwrap();
             (
                 cfg.preferences.honk,
                 cfg.preferences.start_recording_key().to_string(),
                 cfg.preferences.stop_recording_key().to_string(),
+                cfg.preferences.pause_recording_key.clone(),
+                cfg.preferences.privacy_pause_key.clone(),
+                cfg.preferences.save_replay_key.clone(),
             )
         };
         let start_key =
             lookup_keycode(&start_key).ok_or_else(|| eyre!("Invalid start key: {start_key}"))?;
         let stop_key =
             lookup_keycode(&stop_key).ok_or_else(|| eyre!("Invalid stop key: {stop_key}"))?;
+        let pause_key =
+            lookup_keycode(&pause_key).ok_or_else(|| eyre!("Invalid pause key: {pause_key}"))?;
+        let privacy_pause_key = lookup_keycode(&privacy_pause_key)
+            .ok_or_else(|| eyre!("Invalid privacy pause key: {privacy_pause_key}"))?;
+        let save_replay_key = lookup_keycode(&save_replay_key)
+            .ok_or_else(|| eyre!("Invalid save replay key: {save_replay_key}"))?;
         tokio::select! {
             r = &mut ctrlc_rx => {
-                r.expect("ctrl-c signal handler was closed early");
+                let pressed_ctrl_c = r.expect("ctrl-c signal handler was closed early");
+                // There's no time to upload if the console is going away
+                upload_on_exit = headless && pressed_ctrl_c;
                 break;
             },
             r = stopped_rx.recv() => {
@@ -136,9 +210,17 @@ async fn main(
                     }
                 }
                 if let Some(key) = e.key_press_keycode() && !app_state.is_currently_rebinding.load(Ordering::Relaxed) {
-                    if key == start_key && recorder.recording().is_none() {
+                    if (key == start_key || key == stop_key) && countdown_until.is_some() {
+                        tracing::info!("Hotkey pressed during the countdown, cancelling it");
+                        countdown_until = None;
+                        cancel_countdown(&app_state);
+                    } else if key == start_key && recorder.recording().is_none() {
                         tracing::info!("Start key pressed, starting recording");
-                        if start_recording_safely(&mut recorder, &unsupported_games, Some((&sink, honk, &app_state))).await {
+                        start_on_focus_change = None;
+                        let countdown = app_state.config.read().unwrap().preferences.start_countdown();
+                        if let Some(countdown) = countdown {
+                            countdown_until = Some(start_countdown(&app_state, countdown));
+                        } else if start_recording_safely(&mut recorder, &unsupported_games, Some((&sink, honk, &app_state))).await {
                             actively_recording_window = recorder.recording().as_ref().map(|r| r.hwnd());
                             window_unfocused_at = None;
                             tracing::info!("Recording started with HWND {actively_recording_window:?}");
@@ -152,6 +234,46 @@ async fn main(
                         actively_recording_window = None;
                         window_unfocused_at = None;
                         start_on_activity = false;
+                        start_on_focus_change = None;
+                    } else if key == pause_key && recorder.recording().is_some() {
+                        tracing::info!("Pause key pressed, toggling pause");
+                        paused_for_denylist = false;
+                        if let Err(e) = recorder.toggle_pause().await {
+                            tracing::error!(e=?e, "Failed to toggle pause on pause key");
+                        }
+                    } else if key == privacy_pause_key && let Some(recording) = recorder.recording() {
+                        tracing::info!("Privacy pause key pressed, pausing");
+                        // Even if we paused for a denylisted window, it's now up to the user to resume
+                        paused_for_denylist = false;
+                        if !recording.is_paused() && let Err(e) = recorder.toggle_pause().await {
+                            tracing::error!(e=?e, "Failed to pause on privacy pause key");
+                        }
+                    } else if key == save_replay_key && recorder.is_replay() {
+                        tracing::info!("Save replay key pressed, saving instant replay");
+                        match recorder.save_replay() {
+                            Ok(saved) => {
+                                show_notification(
+                                    "Saved instant replay",
+                                    &if recorder.recording().is_some() {
+                                        "The replay will be ready to upload once the current segment finishes.".to_string()
+                                    } else {
+                                        format!("Saved {saved} segment(s), ready to upload.")
+                                    },
+                                    "",
+                                    NotificationType::Info,
+                                );
+                            }
+                            Err(e) => {
+                                tracing::error!(e=?e, "Failed to save instant replay");
+                                show_notification(
+                                    "OWL Control - Error",
+                                    &format!("Failed to save instant replay: {e}"),
+                                    "",
+                                    NotificationType::Error,
+                                );
+                            }
+                        }
+                        app_state.async_request_tx.send(AsyncRequest::LoadLocalRecordings).await.ok();
                     }
                 } else if start_on_activity && actively_recording_window.is_some_and(is_window_focused) {
                     tracing::info!("Input detected, restarting recording");
@@ -166,24 +288,159 @@ async fn main(
                 let e = e.expect("async request reader was closed early");
                 match e {
                     AsyncRequest::ValidateApiKey { api_key } => {
-                        let response = api_client.validate_api_key(&api_key).await;
-                        tracing::info!("Received response from API key validation: {response:?}");
-
-                        valid_api_key_and_user_id = response.as_ref().ok().map(|s| (api_key.clone(), s.clone()));
-                        app_state
-                            .ui_update_tx
-                            .try_send(UiUpdate::UpdateUserId(response.map_err(|e| e.to_string())))
-                            .ok();
+                        spawn_api_key_validation(&mut api_key_validation, &app_state, &api_client, api_key);
+                    }
+                    AsyncRequest::TestConnection => {
+                        tokio::spawn({
+                            let app_state = app_state.clone();
+                            let api_client = api_client.clone();
+                            async move {
+                                // The key that's in use, which may not have reached the config yet
+                                let api_key = app_state
+                                    .valid_api_key_and_user_id
+                                    .read()
+                                    .unwrap()
+                                    .as_ref()
+                                    .map(|(api_key, _)| api_key.clone())
+                                    .unwrap_or_else(|| app_state.config.read().unwrap().credentials.api_key.clone());
+                                let result = api_client.test_connection(&api_key).await;
+                                tracing::info!(result=?result, "Tested connection");
+                                app_state.ui_update_tx.send(UiUpdate::ConnectionTested(result)).await.ok();
+                            }
+                        });
+                    }
+                    AsyncRequest::CheckConnection => {
+                        let api_key = app_state.config.read().unwrap().credentials.api_key.clone();
+                        if app_state.offline.load(Ordering::Relaxed) && !api_key.is_empty() {
+                            // Brings us back online if the key can be checked now
+                            spawn_api_key_validation(&mut api_key_validation, &app_state, &api_client, api_key);
+                        } else {
+                            tokio::spawn(check_connection(app_state.clone(), api_client.clone(), true));
+                        }
+                    }
+                    AsyncRequest::Logout => {
+                        if recorder.recording().is_some() {
+                            tracing::info!("Logging out, stopping recording");
+                            if let Err(e) = stop_recording_with_notification(&mut recorder, &sink, honk, &app_state).await {
+                                tracing::error!(e=?e, "Failed to stop recording on logout");
+                            }
+                        }
+                        actively_recording_window = None;
+                        window_unfocused_at = None;
+                        start_on_activity = false;
+                        start_on_focus_change = None;
+                        if countdown_until.take().is_some() {
+                            cancel_countdown(&app_state);
+                        }
 
-                        if valid_api_key_and_user_id.is_some() {
-                            app_state.async_request_tx.send(AsyncRequest::LoadUploadStats).await.ok();
-                            app_state.async_request_tx.send(AsyncRequest::LoadLocalRecordings).await.ok();
+                        *app_state.valid_api_key_and_user_id.write().unwrap() = None;
+                        app_state.offline.store(false, Ordering::Relaxed);
+                        *app_state.user_uploads.write().unwrap() = None;
+                        *app_state.user_info.write().unwrap() = None;
+                    }
+                    AsyncRequest::TogglePauseRecording => {
+                        paused_for_denylist = false;
+                        paused_for_minimize = false;
+                        if let Err(e) = recorder.toggle_pause().await {
+                            tracing::error!(e=?e, "Failed to toggle pause");
+                        }
+                        last_active = Instant::now();
+                    }
+                    AsyncRequest::StartRecording if headless => {
+                        // There's no tray to switch away from, so whatever is in the foreground already will do
+                        if recorder.recording().is_none() && start_on_focus_change.is_none() {
+                            tracing::info!("Start requested, waiting for a game to be in the foreground");
+                            start_on_focus_change = Some(HWND::default());
+                        }
+                    }
+                    AsyncRequest::StartRecording => {
+                        if recorder.recording().is_none() && start_on_focus_change.is_none() {
+                            tracing::info!("Start requested, waiting for a game to be focused");
+                            start_on_focus_change = Some(unsafe { GetForegroundWindow() });
+                            show_notification(
+                                "OWL Control",
+                                "Recording will start when you switch to your game.",
+                                "",
+                                NotificationType::Info,
+                            );
+                        }
+                    }
+                    AsyncRequest::StopRecording => {
+                        if countdown_until.take().is_some() {
+                            tracing::info!("Stop requested during the countdown, cancelling it");
+                            cancel_countdown(&app_state);
+                        }
+                        if recorder.recording().is_some() {
+                            tracing::info!("Stop requested, stopping recording");
+                            if let Err(e) = stop_recording_with_notification(&mut recorder, &sink, honk, &app_state).await {
+                                tracing::error!(e=?e, "Failed to stop recording on stop request");
+                            }
+                        }
+                        actively_recording_window = None;
+                        window_unfocused_at = None;
+                        start_on_activity = false;
+                        start_on_focus_change = None;
+                    }
+                    AsyncRequest::RecheckCapturePermission => {
+                        let denied = app_state.capture_denied.read().unwrap().clone();
+                        if let Some(denied) = denied {
+                            if denied.is_resolved() {
+                                tracing::info!(game_exe = denied.game_exe(), "Capture problem resolved, recording allowed again");
+                                *app_state.capture_denied.write().unwrap() = None;
+                            } else {
+                                tracing::info!(game_exe = denied.game_exe(), "Game still can't be captured");
+                                show_notification(
+                                    "OWL Control - Error",
+                                    &denied.to_string(),
+                                    "",
+                                    NotificationType::Error,
+                                );
+                            }
+                            app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+                        }
+                    }
+                    AsyncRequest::RunBenchmark => {
+                        if recorder.recording().is_some() || countdown_until.is_some() || benchmark.is_some() {
+                            app_state
+                                .ui_update_tx
+                                .try_send(UiUpdate::BenchmarkFinished(Err("Stop recording before running the benchmark.".to_string())))
+                                .ok();
+                        } else {
+                            tracing::info!("Benchmark requested, waiting for a game to be focused");
+                            benchmark = Some((Benchmark::new(&app_state.available_video_encoders), Some(unsafe { GetForegroundWindow() })));
+                            show_notification(
+                                "OWL Control - Benchmark",
+                                "The benchmark will start when you switch to your game. Play as you normally would until it's finished.",
+                                "",
+                                NotificationType::Info,
+                            );
                         }
                     }
                     AsyncRequest::UploadData => {
-                        tokio::spawn(upload::start(app_state.clone(), api_client.clone(), recording_location.clone()));
+                        upload::enqueue_all(&app_state, &api_client, &app_state.recording_location());
+                    }
+                    AsyncRequest::UploadRecording(folder_path) => {
+                        if upload::is_test_recording(&folder_path) {
+                            tracing::info!("Not uploading {}, as it's a test recording", folder_path.display());
+                        } else if app_state.upload_queue.write().unwrap().enqueue(folder_path) {
+                            upload::process_queue(app_state.clone(), api_client.clone());
+                        }
+                    }
+                    AsyncRequest::RetryUpload(folder_path) => {
+                        if app_state.upload_queue.write().unwrap().retry(&folder_path) {
+                            upload::process_queue(app_state.clone(), api_client.clone());
+                        }
+                    }
+                    AsyncRequest::CancelUpload(folder_path) => {
+                        app_state.upload_queue.write().unwrap().cancel(&folder_path);
+                        app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+                    }
+                    AsyncRequest::ClearCompletedUploads => {
+                        app_state.upload_queue.write().unwrap().clear_done();
+                        app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
                     }
                     AsyncRequest::OpenDataDump => {
+                        let recording_location = app_state.recording_location();
                         // Create directory if it doesn't exist
                         if !recording_location.exists() {
                             let _ = std::fs::create_dir_all(&recording_location);
@@ -195,9 +452,106 @@ async fn main(
                     AsyncRequest::OpenLog => {
                         opener::reveal(&log_path).ok();
                     }
+                    AsyncRequest::CreateProblemReport { include_latest_recording } => {
+                        let app_state = app_state.clone();
+                        let log_path = log_path.clone();
+                        tokio::task::spawn_blocking(move || {
+                            let result = match report::create(&app_state, &log_path, include_latest_recording) {
+                                Ok(report) => {
+                                    opener::reveal(&report.path).ok();
+                                    Ok(report.reference_id)
+                                }
+                                Err(e) => {
+                                    tracing::error!(e=?e, "Failed to create problem report");
+                                    Err(format!("{e:#}"))
+                                }
+                            };
+                            app_state.ui_update_tx.try_send(UiUpdate::ProblemReportCreated(result)).ok();
+                        });
+                    }
                     AsyncRequest::OpenFolder(path) => {
                         opener::open(&path).ok();
                     }
+                    AsyncRequest::ExportRecording { folder_path, archive_path } => {
+                        let app_state = app_state.clone();
+                        tokio::task::spawn_blocking(move || {
+                            match app_state.recording_keys().and_then(|keys| upload::export_recording(&folder_path, &archive_path, &keys)) {
+                                Ok(()) => {
+                                    tracing::info!("Exported {} to {}", folder_path.display(), archive_path.display());
+                                    opener::reveal(&archive_path).ok();
+                                }
+                                Err(e) => {
+                                    tracing::error!(path=%folder_path.display(), e=?e, "Failed to export recording");
+                                    show_notification(
+                                        "OWL Control - Error",
+                                        &format!("Failed to export recording: {e:#}"),
+                                        "",
+                                        NotificationType::Error,
+                                    );
+                                }
+                            }
+                        });
+                    }
+                    AsyncRequest::ImportRecordings(archive_paths) => {
+                        let app_state = app_state.clone();
+                        let recording_location = app_state.recording_location();
+                        tokio::spawn(async move {
+                            let keys = app_state.recording_keys();
+                            let (imported, errors) = tokio::task::spawn_blocking(move || {
+                                let mut imported = 0;
+                                let mut errors = vec![];
+                                for archive_path in archive_paths {
+                                    // Encrypted at rest like the recordings made here, if they are
+                                    let result = keys.as_ref().map_err(|e| eyre!("{e:#}")).and_then(|keys| {
+                                        let folder_path = upload::import_recording(&archive_path, &recording_location)?;
+                                        encryption::encrypt_recording(&folder_path, keys)?;
+                                        Ok(folder_path)
+                                    });
+                                    match result {
+                                        Ok(folder_path) => {
+                                            tracing::info!("Imported {} to {}", archive_path.display(), folder_path.display());
+                                            imported += 1;
+                                        }
+                                        Err(e) => {
+                                            tracing::error!(path=%archive_path.display(), e=?e, "Failed to import recording");
+                                            let name = archive_path.file_name().unwrap_or_default().to_string_lossy();
+                                            errors.push(format!("{name}: {e:#}"));
+                                        }
+                                    }
+                                }
+                                (imported, errors)
+                            }).await.unwrap_or_default();
+
+                            app_state.async_request_tx.send(AsyncRequest::LoadLocalRecordings).await.ok();
+                            if !errors.is_empty() {
+                                tokio::task::spawn_blocking(move || {
+                                    show_notification(
+                                        "OWL Control - Error",
+                                        &format!("Failed to import recordings:\n{}", errors.join("\n")),
+                                        "",
+                                        NotificationType::Error,
+                                    );
+                                });
+                            } else if imported > 0 {
+                                show_notification(
+                                    "Imported recordings",
+                                    &format!("Imported {imported} recording(s), ready to upload."),
+                                    "",
+                                    NotificationType::Info,
+                                );
+                            }
+                        });
+                    }
+                    AsyncRequest::LoadPlayback(path) => {
+                        let app_state = app_state.clone();
+                        tokio::task::spawn_blocking(move || {
+                            let result = app_state.recording_keys().and_then(|keys| record::Playback::load(&path, &keys)).map_err(|e| {
+                                tracing::error!(path=%path.display(), e=?e, "Failed to load recording for preview");
+                                format!("{e:#}")
+                            });
+                            app_state.ui_update_tx.try_send(UiUpdate::PlaybackLoaded(result)).ok();
+                        });
+                    }
                     AsyncRequest::UpdateUnsupportedGames(new_games) => {
                         let old_game_count = unsupported_games.games.len();
                         unsupported_games = new_games;
@@ -207,7 +561,8 @@ async fn main(
                         );
                     }
                     AsyncRequest::LoadUploadStats => {
-                        match valid_api_key_and_user_id.clone() {
+                        let valid_api_key_and_user_id = app_state.valid_api_key_and_user_id.read().unwrap().clone();
+                        match valid_api_key_and_user_id {
                             Some((api_key, user_id)) => {
                                 tokio::spawn({
                                     let app_state = app_state.clone();
@@ -222,6 +577,7 @@ async fn main(
                                         };
                                         tracing::info!(stats=?stats.statistics, "Loaded upload stats");
                                         *app_state.user_uploads.write().unwrap() = Some(stats);
+                                        app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
                                     }
                                 });
                             }
@@ -230,14 +586,53 @@ async fn main(
                             }
                         }
                     }
+                    AsyncRequest::UpdateUploadLabels { upload_id, labels } => {
+                        let valid_api_key_and_user_id = app_state.valid_api_key_and_user_id.read().unwrap().clone();
+                        match valid_api_key_and_user_id {
+                            Some((api_key, _)) => {
+                                tokio::spawn({
+                                    let app_state = app_state.clone();
+                                    let api_client = api_client.clone();
+                                    async move {
+                                        let result = api_client.update_upload_labels(&api_key, &upload_id, &labels).await;
+                                        match &result {
+                                            Ok(()) => {
+                                                tracing::info!("Updated the labels of upload {upload_id}");
+                                                // So that the recordings list shows the new labels
+                                                app_state.async_request_tx.send(AsyncRequest::LoadUploadStats).await.ok();
+                                            }
+                                            Err(e) => tracing::error!(e=?e, "Failed to update the labels of upload {upload_id}"),
+                                        }
+                                        app_state
+                                            .ui_update_tx
+                                            .send(UiUpdate::UploadLabelsUpdated(result.map_err(|e| format!("{e:#}"))))
+                                            .await
+                                            .ok();
+                                    }
+                                });
+                            }
+                            None => {
+                                app_state
+                                    .ui_update_tx
+                                    .try_send(UiUpdate::UploadLabelsUpdated(Err("Log in to change the labels of uploaded recordings".to_string())))
+                                    .ok();
+                            }
+                        }
+                    }
                     AsyncRequest::LoadLocalRecordings => {
                         tokio::spawn({
                             let app_state = app_state.clone();
-                            let recording_location = recording_location.clone();
+                            let recording_location = app_state.recording_location();
+                            let retention = app_state.config.read().unwrap().preferences.uploaded_retention;
                             async move {
-                                let local_recordings = tokio::task::spawn_blocking(move || {
-                                    upload::scan_local_recordings(&recording_location)
+                                let (local_recordings, retained_recordings) = tokio::task::spawn_blocking(move || {
+                                    upload::apply_retention(&recording_location, retention);
+                                    (
+                                        upload::scan_local_recordings(&recording_location),
+                                        upload::retained_recordings(&recording_location),
+                                    )
                                 }).await.unwrap_or_default();
+                                *app_state.retained_recordings.write().unwrap() = retained_recordings;
 
                                 tracing::info!("Found {} local recordings", local_recordings.len());
                                 app_state
@@ -247,10 +642,23 @@ async fn main(
                             }
                         });
                     }
+                    AsyncRequest::CleanUpUploadedRecordings => {
+                        tokio::spawn({
+                            let app_state = app_state.clone();
+                            let recording_location = app_state.recording_location();
+                            async move {
+                                let deleted = tokio::task::spawn_blocking(move || {
+                                    upload::delete_uploaded(&recording_location, Duration::ZERO)
+                                }).await.unwrap_or_default();
+                                tracing::info!("Cleaned up {deleted} uploaded recordings");
+                                app_state.async_request_tx.send(AsyncRequest::LoadLocalRecordings).await.ok();
+                            }
+                        });
+                    }
                     AsyncRequest::DeleteAllInvalidRecordings => {
                         tokio::spawn({
                             let app_state = app_state.clone();
-                            let recording_location = recording_location.clone();
+                            let recording_location = app_state.recording_location();
                             async move {
                                 // Get current list of local recordings
                                 let local_recordings = tokio::task::spawn_blocking({
@@ -319,29 +727,301 @@ async fn main(
                     }
                 }
             },
+            _ = offline_retry_interval.tick() => {
+                // The key comes from the config, as it's only there once the user has chosen to record offline
+                let api_key = app_state.config.read().unwrap().credentials.api_key.clone();
+                if app_state.offline.load(Ordering::Relaxed) && !api_key.is_empty() {
+                    spawn_api_key_validation(&mut api_key_validation, &app_state, &api_client, api_key);
+                }
+            },
+            _ = connection_check_interval.tick() => {
+                if app_state.valid_api_key_and_user_id.read().unwrap().is_some() {
+                    tokio::spawn(check_connection(app_state.clone(), api_client.clone(), false));
+                }
+            },
+            _ = reconnect_interval.tick() => {
+                if app_state.upload_queue.read().unwrap().is_awaiting_network() {
+                    tokio::spawn(upload::resume_if_reconnected(app_state.clone(), api_client.clone()));
+                }
+            },
+            _ = refresh_account_interval.tick() => {
+                if app_state.valid_api_key_and_user_id.read().unwrap().is_some() {
+                    tokio::spawn(refresh_user_info(app_state.clone(), api_client.clone()));
+                    app_state.async_request_tx.send(AsyncRequest::LoadUploadStats).await.ok();
+                }
+            },
+            _ = preview_interval.tick() => {
+                if let Err(e) = recorder.update_preview().await {
+                    tracing::debug!(e=?e, "Failed to get a preview of the recording");
+                }
+            },
+            _ = telemetry_interval.tick() => {
+                // Taken either way, so that nothing gathered before opting in is sent later
+                let batch = app_state.telemetry.take_batch();
+                if let Some(batch) = batch
+                    && app_state.config.read().unwrap().preferences.telemetry
+                {
+                    tokio::spawn({
+                        let api_client = api_client.clone();
+                        async move {
+                            if let Err(e) = api_client.send_telemetry(&batch).await {
+                                tracing::warn!(e=?e, "Failed to send telemetry");
+                            }
+                        }
+                    });
+                }
+            },
+            _ = check_denylist.tick() => {
+                let is_paused = recorder.recording().map(|r| r.is_paused());
+                if is_paused.is_none() {
+                    paused_for_denylist = false;
+                } else {
+                    let denylisted = {
+                        let config = app_state.config.read().unwrap();
+                        capture_denylist::foreground_window_match(&config.preferences.capture_denylist)
+                            .map(str::to_string)
+                    };
+                    match denylisted {
+                        Some(entry) if is_paused == Some(false) => {
+                            tracing::info!("Denylisted window ({entry}) focused, pausing recording");
+                            if let Err(e) = recorder.toggle_pause().await {
+                                tracing::error!(e=?e, "Failed to pause recording for denylisted window");
+                            } else {
+                                paused_for_denylist = true;
+                            }
+                        }
+                        None if paused_for_denylist => {
+                            tracing::info!("Denylisted window no longer focused, resuming recording");
+                            if let Err(e) = recorder.toggle_pause().await {
+                                tracing::error!(e=?e, "Failed to resume recording after denylisted window");
+                            }
+                            paused_for_denylist = false;
+                            last_active = Instant::now();
+                        }
+                        _ => {}
+                    }
+                }
+            },
+            // Always evaluated, even while there's no countdown, so it needs something to wait on regardless
+            _ = tokio::time::sleep_until(countdown_until.unwrap_or_else(Instant::now).into()), if countdown_until.is_some() => {
+                countdown_until = None;
+                tracing::info!("Countdown finished, starting recording");
+                // Back to where it was before the countdown, in case recording can't be started
+                *app_state.state.write().unwrap() = RecordingStatus::Stopped;
+                if start_recording_safely(&mut recorder, &unsupported_games, Some((&sink, honk, &app_state))).await {
+                    actively_recording_window = recorder.recording().as_ref().map(|r| r.hwnd());
+                    window_unfocused_at = None;
+                    last_active = Instant::now();
+                    tracing::info!("Recording started with HWND {actively_recording_window:?}");
+                }
+            },
             _ = perform_checks.tick() => {
+                let proxy = app_state.config.read().unwrap().preferences.proxy.clone();
+                api_client.set_proxy(proxy.as_deref());
+
+                if let Some(requested_from) = start_on_focus_change
+                    && recorder.recording().is_none()
+                    && let Ok((hwnd, pid)) = game_process::foreground_window()
+                    && hwnd != requested_from
+                    // Opening our own window from the tray isn't the user picking a game
+                    && pid.0 != std::process::id()
+                {
+                    start_on_focus_change = None;
+                    tracing::info!("Window {hwnd:?} focused after start request, starting recording");
+                    let countdown = app_state.config.read().unwrap().preferences.start_countdown();
+                    if let Some(countdown) = countdown {
+                        countdown_until = Some(start_countdown(&app_state, countdown));
+                    } else if start_recording_safely(&mut recorder, &unsupported_games, Some((&sink, honk, &app_state))).await {
+                        actively_recording_window = recorder.recording().as_ref().map(|r| r.hwnd());
+                        window_unfocused_at = None;
+                        last_active = Instant::now();
+                    } else if headless {
+                        // Nobody is there to ask again, so keep waiting for a window that is a game
+                        start_on_focus_change = Some(hwnd);
+                    }
+                }
+
+                if let Some((current, requested_from)) = benchmark.as_mut()
+                    && let Some(result) = step_benchmark(current, requested_from, &mut recorder, &unsupported_games).await
+                {
+                    benchmark = None;
+                    match &result {
+                        Ok(report) => {
+                            tracing::info!(?report, "Benchmark finished");
+                            show_notification(
+                                "OWL Control - Benchmark finished",
+                                &match report.recommended() {
+                                    Some(settings) => format!(
+                                        "This computer can record at {} FPS with {}, {}. Open OWL Control to apply these settings.",
+                                        settings.fps, settings.encoder, settings.codec
+                                    ),
+                                    None => "None of the settings kept up without dropping frames. Open OWL Control for details.".to_string(),
+                                },
+                                "",
+                                NotificationType::Info,
+                            );
+                        }
+                        Err(e) => {
+                            tracing::error!(e=?e, "Benchmark failed");
+                            show_notification(
+                                "OWL Control - Error",
+                                &format!("The benchmark couldn't finish: {e}"),
+                                "",
+                                NotificationType::Error,
+                            );
+                        }
+                    }
+                    app_state.ui_update_tx.try_send(UiUpdate::BenchmarkFinished(result)).ok();
+                }
+
+                if last_disk_space_check.is_none_or(|last| last.elapsed() > DISK_SPACE_CHECK_INTERVAL) {
+                    last_disk_space_check = Some(Instant::now());
+
+                    let (min_free_space_mb, bitrate) = {
+                        let config = app_state.config.read().unwrap();
+                        (config.preferences.min_free_space_mb, config.preferences.encoder.bitrate)
+                    };
+                    let free_space_mb = record::get_free_space_in_mb(&app_state.recording_location());
+
+                    if let Some(free_space_mb) = free_space_mb
+                        && free_space_mb < min_free_space_mb
+                        && recorder.recording().is_some()
+                    {
+                        tracing::warn!(free_space_mb, min_free_space_mb, "Free disk space is below the minimum, stopping recording");
+                        if let Err(e) = stop_recording_with_notification(&mut recorder, &sink, honk, &app_state).await {
+                            tracing::error!(e=?e, "Failed to stop recording on low disk space");
+                        }
+                        show_notification(
+                            "OWL Control - Low disk space",
+                            &format!("Recording was stopped because only {free_space_mb} MB of disk space is left. Please free up some space."),
+                            "",
+                            NotificationType::Error,
+                        );
+                        // Don't restart the recording when the window is refocused, it would just fail
+                        actively_recording_window = None;
+                        window_unfocused_at = None;
+                        start_on_activity = false;
+                    }
+
+                    // Warn the user once there's less than a full recording's worth of room left above the minimum
+                    let new_low_disk_space_mb = free_space_mb.filter(|&free_space_mb| {
+                        free_space_mb < min_free_space_mb + record::estimated_recording_size_mb(MAX_FOOTAGE, bitrate)
+                    });
+                    if new_low_disk_space_mb.is_some() || low_disk_space_mb.is_some() {
+                        app_state
+                            .ui_update_tx
+                            .try_send(UiUpdate::UpdateLowDiskSpace(new_low_disk_space_mb))
+                            .ok();
+                    }
+                    low_disk_space_mb = new_low_disk_space_mb;
+                }
+
+                if let Err(e) = recorder.update_skipped_frames(&unsupported_games).await {
+                    tracing::warn!(e=?e, "Failed to update skipped frames");
+                }
+
+                match recorder.check_capture().await {
+                    Ok(None) => {}
+                    Ok(Some(denied)) => {
+                        if let Err(e) = stop_recording_with_notification(&mut recorder, &sink, honk, &app_state).await {
+                            tracing::error!(e=?e, "Failed to stop recording that isn't capturing the game");
+                        }
+                        show_notification(
+                            "OWL Control - Error",
+                            &denied.to_string(),
+                            "",
+                            NotificationType::Error,
+                        );
+                        // Don't restart the recording when the window is refocused, it would just fail
+                        actively_recording_window = None;
+                        window_unfocused_at = None;
+                        start_on_activity = false;
+                    }
+                    Err(e) => tracing::warn!(e=?e, "Failed to check whether the game is being captured"),
+                }
+
+                // Paused recordings aren't capturing anything, so can wait until they're resumed
+                if recorder.recording().is_some_and(|r| !r.is_paused()) {
+                    match recorder.check_resolution_change(&unsupported_games).await {
+                        // The finished segment can be uploaded on its own
+                        Ok(true) => { app_state.async_request_tx.send(AsyncRequest::LoadLocalRecordings).await.ok(); }
+                        Ok(false) => {}
+                        Err(e) => {
+                            tracing::error!(e=?e, "Failed to start next recording segment after resolution change");
+                            show_notification(
+                                "OWL Control - Error",
+                                &e.to_string(),
+                                "",
+                                NotificationType::Error,
+                            );
+                            recorder.stop().await.ok();
+                        }
+                    }
+                }
+
+                let (segment_length, idle_auto_stop) = {
+                    let preferences = &app_state.config.read().unwrap().preferences;
+                    (
+                        // Replays are kept in short segments, so that old footage can be let go of bit by bit
+                        if recorder.is_replay() { record::REPLAY_SEGMENT_LENGTH } else { preferences.segment_length() },
+                        preferences.idle_auto_stop_minutes.map(|minutes| Duration::from_secs(minutes as u64 * 60)),
+                    )
+                };
+                if recorder.recording().is_none() {
+                    paused_for_minimize = false;
+                }
                 if let Some(recording) = recorder.recording() {
                     if !does_process_exist(recording.pid()).unwrap_or_default() {
                         tracing::info!(pid=recording.pid().0, "Game process no longer exists, stopping recording");
                         if let Err(e) = stop_recording_with_notification(&mut recorder, &sink, honk, &app_state).await {
                             tracing::error!(e=?e, "Failed to stop recording on game process exit");
                         }
+                    } else if paused_for_minimize {
+                        if let Some(window) = actively_recording_window && !is_window_minimized(window) {
+                            tracing::info!("Window {window:?} was restored, resuming recording");
+                            if let Err(e) = recorder.toggle_pause().await {
+                                tracing::error!(e=?e, "Failed to resume recording on window restored");
+                            }
+                            paused_for_minimize = false;
+                            last_active = Instant::now();
+                            window_unfocused_at = None;
+                        }
+                    } else if recording.is_paused() {
+                        // Paused by the user or for a denylisted window, so leave it be until it's resumed or stopped
                     } else if last_active.elapsed() > MAX_IDLE_DURATION {
                         tracing::info!("No input detected for {} seconds, stopping recording", MAX_IDLE_DURATION.as_secs());
                         if let Err(e) = stop_recording_with_notification(&mut recorder, &sink, honk, &app_state).await {
                             tracing::error!(e=?e, "Failed to stop recording on idle timeout");
                         }
-                        *app_state.state.write().unwrap() = RecordingStatus::Paused;
+                        *app_state.state.write().unwrap() = RecordingStatus::Paused { auto_stop_at: None };
                         start_on_activity = true;
-                    } else if recording.elapsed() > MAX_FOOTAGE {
-                        tracing::info!("Recording duration exceeded {} s, restarting recording", MAX_FOOTAGE.as_secs());
-                        // We intentionally do not notify of recording state change here because we're restarting the recording
-                        if let Err(e) = recorder.stop().await {
-                            tracing::error!(e=?e, "Failed to stop recording on recording duration exceeded");
+                    } else if recording.elapsed() > segment_length {
+                        tracing::info!("Recording duration exceeded {} s, starting next segment", segment_length.as_secs());
+                        // We intentionally do not notify of recording state change here because the session carries on
+                        if let Err(e) = recorder.start_next_segment(&unsupported_games).await {
+                            tracing::error!(e=?e, "Failed to start next recording segment");
+                            show_notification(
+                                "OWL Control - Error",
+                                &e.to_string(),
+                                "",
+                                NotificationType::Error,
+                            );
+                            recorder.stop().await.ok();
                         }
-                        start_recording_safely(&mut recorder, &unsupported_games, None).await;
+                        // The finished segment can be uploaded on its own
+                        app_state.async_request_tx.send(AsyncRequest::LoadLocalRecordings).await.ok();
                         last_active = Instant::now();
                         window_unfocused_at = None;
+                    } else if let Some(window) = actively_recording_window && is_window_minimized(window) {
+                        // A minimized game has nothing to capture but black frames, so we pause rather than waiting
+                        // out the grace period, and resume once the window is restored.
+                        tracing::info!("Window {window:?} was minimized, pausing recording");
+                        if let Err(e) = recorder.toggle_pause().await {
+                            tracing::error!(e=?e, "Failed to pause recording on window minimized");
+                        } else {
+                            paused_for_minimize = true;
+                        }
+                        window_unfocused_at = None;
                     } else if let Some(window) = actively_recording_window && !is_window_focused(window) {
                         // Window lost focus - start grace period if not already started
                         if window_unfocused_at.is_none() {
@@ -375,6 +1055,25 @@ async fn main(
                     tracing::info!("Window {window:?} regained focus, restarting recording");
                     start_recording_safely(&mut recorder, &unsupported_games, Some((&sink, honk, &app_state))).await;
                     window_unfocused_at = None;
+                } else if start_on_activity && let Some(idle_auto_stop) = idle_auto_stop {
+                    let remaining = idle_auto_stop.saturating_sub(last_active.elapsed());
+                    if remaining.is_zero() {
+                        tracing::info!("No input detected for {} minutes, ending recording session", idle_auto_stop.as_secs() / 60);
+                        start_on_activity = false;
+                        actively_recording_window = None;
+                        window_unfocused_at = None;
+                        *app_state.state.write().unwrap() = RecordingStatus::Stopped;
+                        show_notification(
+                            "OWL Control - Stopped recording",
+                            &format!("There was no input for {} minutes, so recording won't start again until you start it.", idle_auto_stop.as_secs() / 60),
+                            "",
+                            NotificationType::Info,
+                        );
+                    } else {
+                        // Counts down on the overlay, and any input cancels it by starting the recording again
+                        let auto_stop_at = (remaining <= IDLE_AUTO_STOP_WARNING).then(|| Instant::now() + remaining);
+                        *app_state.state.write().unwrap() = RecordingStatus::Paused { auto_stop_at };
+                    }
                 }
             },
         }
@@ -383,6 +1082,16 @@ async fn main(
     if let Err(e) = recorder.stop().await {
         tracing::error!(e=?e, "Failed to stop recording on shutdown");
     }
+
+    if upload_on_exit {
+        tracing::info!("Uploading recordings before exiting");
+        upload::upload_all_and_wait(
+            app_state.clone(),
+            api_client.clone(),
+            app_state.recording_location(),
+        )
+        .await;
+    }
     Ok(())
 }
 
@@ -395,7 +1104,7 @@ async fn start_recording_safely(
     unsupported_games: &UnsupportedGames,
     notification_state: Option<(&Sink, bool, &AppState)>,
 ) -> bool {
-    if let Err(e) = recorder.start(unsupported_games).await {
+    let result = recorder.start(unsupported_games).await.map_err(|e| {
         tracing::error!(e=?e, "Failed to start recording");
         show_notification(
             "OWL Control - Error",
@@ -403,14 +1112,23 @@ async fn start_recording_safely(
             "",
             NotificationType::Error,
         );
+        e.to_string()
+    });
+    if result.is_err() {
         recorder.stop().await.ok();
-        false
-    } else {
-        if let Some((sink, honk, app_state)) = notification_state {
+    }
+    if let Some((sink, honk, app_state)) = notification_state {
+        if result.is_ok() {
             notify_of_recording_state_change(sink, honk, app_state, true);
         }
-        true
+        // So that the UI can say why, as the notification is easily missed
+        app_state
+            .ui_update_tx
+            .send(UiUpdate::RecordingStarted(result.clone()))
+            .await
+            .ok();
     }
+    result.is_ok()
 }
 
 async fn stop_recording_with_notification(
@@ -427,9 +1145,91 @@ async fn stop_recording_with_notification(
         .send(AsyncRequest::LoadLocalRecordings)
         .await
         .ok();
+    let summaries = recorder.take_session_summaries();
+    if !summaries.is_empty() {
+        app_state
+            .ui_update_tx
+            .send(UiUpdate::RecordingFinished(summaries))
+            .await
+            .ok();
+    }
     Ok(())
 }
 
+/// Moves the benchmark along: starts it once the user has switched to their game, and each run after the one before
+/// has recorded for long enough. Returns the outcome once it's over.
+async fn step_benchmark(
+    benchmark: &mut Benchmark,
+    requested_from: &mut Option<HWND>,
+    recorder: &mut Recorder,
+    unsupported_games: &UnsupportedGames,
+) -> Option<Result<BenchmarkReport, String>> {
+    if let Some(window) = *requested_from {
+        let Ok((hwnd, pid)) = game_process::foreground_window() else {
+            return None;
+        };
+        // Opening our own window from the tray isn't the user picking a game
+        if hwnd == window || pid.0 == std::process::id() {
+            return None;
+        }
+        *requested_from = None;
+    } else if !recorder.is_benchmarking() {
+        // e.g. the stop hotkey was pressed, or the game was closed
+        return Some(Err("it was stopped before it finished".to_string()));
+    } else if recorder
+        .recording()
+        .is_some_and(|r| r.start_instant().elapsed() >= record::BENCHMARK_RUN_LENGTH)
+    {
+        match recorder.finish_benchmark_run().await {
+            Ok(skipped_frames) => benchmark.run_finished(skipped_frames),
+            Err(e) => return Some(Err(format!("{e:#}"))),
+        }
+    } else {
+        return None;
+    }
+
+    let settings = match benchmark.next_settings() {
+        Some(settings) => settings,
+        None => return Some(Ok(benchmark.report())),
+    };
+    let run = benchmark.report().runs.len() + 1;
+    tracing::info!(?settings, "Starting benchmark run {run}");
+    if let Err(e) = recorder
+        .start_benchmark_run(unsupported_games, settings)
+        .await
+    {
+        recorder.stop().await.ok();
+        return Some(Err(format!("{e:#}")));
+    }
+    show_notification(
+        "OWL Control - Benchmark",
+        &format!(
+            "Trying {} FPS with {}, {} ({run} of up to {}).",
+            settings.fps,
+            settings.encoder,
+            settings.codec,
+            benchmark.max_runs()
+        ),
+        "",
+        NotificationType::Info,
+    );
+    None
+}
+
+/// Shows the countdown before recording starts, returning when it runs out
+fn start_countdown(app_state: &AppState, countdown: Duration) -> Instant {
+    tracing::info!("Recording starts in {} seconds", countdown.as_secs());
+    let starts_at = Instant::now() + countdown;
+    *app_state.state.write().unwrap() = RecordingStatus::CountingDown { starts_at };
+    app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+    starts_at
+}
+
+fn cancel_countdown(app_state: &AppState) {
+    *app_state.state.write().unwrap() = RecordingStatus::Stopped;
+    app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+}
+
 fn notify_of_recording_state_change(
     sink: &Sink,
     should_play_sound: bool,
@@ -440,6 +1240,11 @@ fn notify_of_recording_state_change(
         .ui_update_tx
         .try_send(UiUpdate::UpdateTrayIconRecording(is_recording))
         .ok();
+    let recording_game = match &*app_state.state.read().unwrap() {
+        RecordingStatus::Recording { game_exe, .. } if is_recording => Some(game_exe.clone()),
+        _ => None,
+    };
+    crash_sentinel::set_recording_game(recording_game);
     if should_play_sound {
         let source = Decoder::new_mp3(Cursor::new(if is_recording {
             get_honk_0_bytes()
@@ -457,14 +1262,30 @@ fn notify_of_recording_state_change(
     }
 }
 
-fn wait_for_ctrl_c() -> oneshot::Receiver<()> {
+/// Resolves on Ctrl+C, or when the console we're running in (if any) is closed or the system is shutting down.
+/// The value is whether it was Ctrl+C.
+fn wait_for_ctrl_c() -> oneshot::Receiver<bool> {
     let (ctrl_c_tx, ctrl_c_rx) = oneshot::channel();
 
     tokio::spawn(async move {
-        tokio::signal::ctrl_c()
-            .await
-            .expect("failed to listen for Ctrl+C signal");
-        let _ = ctrl_c_tx.send(());
+        use tokio::signal::windows::{ctrl_close, ctrl_shutdown};
+        let mut ctrl_close = ctrl_close().expect("failed to listen for console close signal");
+        let mut ctrl_shutdown = ctrl_shutdown().expect("failed to listen for shutdown signal");
+        let pressed_ctrl_c = tokio::select! {
+            r = tokio::signal::ctrl_c() => {
+                r.expect("failed to listen for Ctrl+C signal");
+                true
+            }
+            _ = ctrl_close.recv() => {
+                tracing::info!("Console is closing, shutting down");
+                false
+            }
+            _ = ctrl_shutdown.recv() => {
+                tracing::info!("System is shutting down, shutting down");
+                false
+            }
+        };
+        let _ = ctrl_c_tx.send(pressed_ctrl_c);
     });
     ctrl_c_rx
 }
@@ -473,6 +1294,184 @@ fn is_window_focused(hwnd: HWND) -> bool {
     unsafe { GetForegroundWindow() == hwnd }
 }
 
+fn is_window_minimized(hwnd: HWND) -> bool {
+    unsafe { IsIconic(hwnd).as_bool() }
+}
+
+/// Validates `api_key` in the background, as retries can take a while and we don't want to hold up the
+/// hotkeys. Does nothing if it's already being validated; the result of that is sent to the UI like any other.
+/// A different key replaces the one being validated, whose result would only be out of date by the time it came.
+fn spawn_api_key_validation(
+    validation: &mut Option<(String, tokio::task::JoinHandle<()>)>,
+    app_state: &Arc<AppState>,
+    api_client: &Arc<ApiClient>,
+    api_key: String,
+) {
+    if let Some((validating, handle)) = validation {
+        if *validating == api_key && !handle.is_finished() {
+            tracing::debug!("API key is already being validated, not validating it again");
+            return;
+        }
+        handle.abort();
+    }
+    let handle = tokio::spawn(validate_api_key(
+        app_state.clone(),
+        api_client.clone(),
+        api_key.clone(),
+    ));
+    *validation = Some((api_key, handle));
+}
+
+/// Validates the API key, retrying with backoff if the failure looks like it might go away by itself
+/// (network trouble, server errors, rate limiting). The UI keeps showing that validation is in progress
+/// until the final result arrives.
+async fn validate_api_key(app_state: Arc<AppState>, api_client: Arc<ApiClient>, api_key: String) {
+    const MAX_RETRIES: u32 = 3;
+
+    // Longer waits are left to the user, rather than leaving them looking at a spinner
+    const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+    let mut attempt = 0;
+    let response = loop {
+        let response = api_client.validate_api_key(&api_key).await;
+        match &response {
+            Err(e) if e.is_transient() && attempt < MAX_RETRIES => {
+                attempt += 1;
+                let delay = match e {
+                    // Trying any sooner than the server asked would only be turned away again
+                    ApiKeyValidationError::RateLimited(Some(retry_after)) => *retry_after,
+                    _ => Duration::from_secs(1 << attempt),
+                };
+                if delay > MAX_RATE_LIMIT_WAIT {
+                    break response;
+                }
+                tracing::warn!(
+                    "API key validation failed ({e}), retrying in {delay:?} (attempt {attempt}/{MAX_RETRIES})"
+                );
+                if let ApiKeyValidationError::RateLimited(_) = e {
+                    app_state
+                        .ui_update_tx
+                        .send(UiUpdate::ApiKeyRateLimited {
+                            retry_at: Instant::now() + delay,
+                        })
+                        .await
+                        .ok();
+                }
+                tokio::time::sleep(delay).await;
+            }
+            _ => break response,
+        }
+    };
+    tracing::info!("Received response from API key validation: {response:?}");
+
+    let is_valid = response.is_ok();
+    let offline = matches!(&response, Err(e) if e.is_transient());
+    app_state.server_contact.write().unwrap().record(!offline);
+    if offline != app_state.offline.swap(offline, Ordering::Relaxed) {
+        tracing::info!(
+            "{}",
+            if offline {
+                "Can't reach the server, recording offline until the API key can be checked"
+            } else {
+                "Back online"
+            }
+        );
+    }
+    *app_state.valid_api_key_and_user_id.write().unwrap() = response
+        .as_ref()
+        .ok()
+        .map(|user_info| (api_key, user_info.user_id.clone()));
+    *app_state.user_info.write().unwrap() = response.as_ref().ok().cloned();
+    if let Ok(UserInfo {
+        capture_settings: Some(capture_settings),
+        ..
+    }) = &response
+    {
+        app_state
+            .ui_update_tx
+            .send(UiUpdate::UpdateRemoteCaptureSettings(
+                capture_settings.clone(),
+            ))
+            .await
+            .ok();
+    }
+    app_state
+        .ui_update_tx
+        .send(UiUpdate::UpdateUserId(
+            response.map(|user_info| user_info.user_id),
+        ))
+        .await
+        .ok();
+
+    if is_valid {
+        // Carry on with anything that was held back because the previous key stopped working
+        if app_state.upload_queue.write().unwrap().resume_after_login() {
+            upload::process_queue(app_state.clone(), api_client.clone());
+        }
+        app_state
+            .async_request_tx
+            .send(AsyncRequest::LoadUploadStats)
+            .await
+            .ok();
+        app_state
+            .async_request_tx
+            .send(AsyncRequest::LoadLocalRecordings)
+            .await
+            .ok();
+    }
+}
+
+/// Fetches the logged-in account's details again, in case they've changed on the server (e.g. a new plan).
+/// A failure keeps the details we already have, as they're only informational, but is returned in case the
+/// caller has more to make of it.
+async fn refresh_user_info(
+    app_state: Arc<AppState>,
+    api_client: Arc<ApiClient>,
+) -> Result<(), ApiKeyValidationError> {
+    let Some((api_key, user_id)) = app_state.valid_api_key_and_user_id.read().unwrap().clone()
+    else {
+        return Ok(());
+    };
+    match api_client.validate_api_key(&api_key).await {
+        // The user may have logged out or in as someone else while we were waiting
+        Ok(user_info) if user_info.user_id == user_id => {
+            *app_state.user_info.write().unwrap() = Some(user_info);
+            app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+            Ok(())
+        }
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::warn!("Failed to refresh account info: {e}");
+            Err(e)
+        }
+    }
+}
+
+/// Asks whether the server is there, for the connection status in the main view. If `check_api_key`, the API
+/// key is checked too, and the user is sent back to the login screen if it's no longer accepted.
+async fn check_connection(
+    app_state: Arc<AppState>,
+    api_client: Arc<ApiClient>,
+    check_api_key: bool,
+) {
+    let reachable = api_client.is_reachable().await;
+    app_state.server_contact.write().unwrap().record(reachable);
+    if reachable
+        && check_api_key
+        && let Err(ApiKeyValidationError::InvalidKey(_) | ApiKeyValidationError::Malformed(_)) =
+            refresh_user_info(app_state.clone(), api_client).await
+    {
+        tracing::warn!("API key is no longer accepted, waiting for a new login");
+        *app_state.valid_api_key_and_user_id.write().unwrap() = None;
+        app_state
+            .ui_update_tx
+            .send(UiUpdate::UpdateUserId(Err(ApiKeyValidationError::Revoked)))
+            .await
+            .ok();
+    }
+    app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+}
+
 async fn startup_requests(app_state: Arc<AppState>) {
     if cfg!(debug_assertions) {
         tracing::info!("Skipping fetch of unsupported games in dev/debug build");
@@ -495,6 +1494,16 @@ async fn startup_requests(app_state: Arc<AppState>) {
         });
     }
 
+    if !app_state
+        .config
+        .read()
+        .unwrap()
+        .preferences
+        .check_for_updates
+    {
+        tracing::info!("Skipping update check, as it's been turned off");
+        return;
+    }
     tokio::spawn(async move {
         if let Err(e) = check_for_updates(app_state).await {
             tracing::error!(e=?e, "Failed to check for updates");
@@ -519,6 +1528,7 @@ async fn check_for_updates(app_state: Arc<AppState>) -> Result<()> {
         published_at: Option<chrono::DateTime<chrono::Utc>>,
         tag_name: String,
         name: String,
+        body: Option<String>,
         draft: bool,
         prerelease: bool,
     }
@@ -552,6 +1562,9 @@ async fn check_for_updates(app_state: Arc<AppState>) -> Result<()> {
                 name: latest_valid_release.name,
                 url: latest_valid_release.html_url,
                 release_date: latest_valid_release.published_at,
+                notes: latest_valid_release
+                    .body
+                    .filter(|body| !body.trim().is_empty()),
             }))
             .ok();
     }
@@ -2,7 +2,10 @@
 use egui_wgpu::ScreenDescriptor;
 use egui_wgpu::wgpu;
 use egui_winit::State as EguiWinitState;
-use winit::window::Window;
+use winit::{
+    event_loop::{ActiveEventLoop, EventLoopProxy},
+    window::Window,
+};
 
 pub struct EguiRenderer {
     egui_ctx: egui::Context,
@@ -50,6 +53,28 @@ impl EguiRenderer {
         &self.egui_ctx
     }
 
+    /// Exposes the UI to screen readers. The window must not have been shown yet.
+    pub fn init_accesskit<T: From<egui_winit::accesskit_winit::Event> + Send>(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window: &Window,
+        event_loop_proxy: EventLoopProxy<T>,
+    ) {
+        self.egui_state
+            .init_accesskit(event_loop, window, event_loop_proxy);
+    }
+
+    pub fn handle_accesskit_event(&mut self, event: egui_winit::accesskit_winit::WindowEvent) {
+        use egui_winit::accesskit_winit::WindowEvent;
+        match event {
+            WindowEvent::InitialTreeRequested => self.egui_ctx.enable_accesskit(),
+            WindowEvent::ActionRequested(request) => {
+                self.egui_state.on_accesskit_action_request(request)
+            }
+            WindowEvent::AccessibilityDeactivated => self.egui_ctx.disable_accesskit(),
+        }
+    }
+
     pub fn handle_input(
         &mut self,
         window: &Window,
@@ -70,9 +95,14 @@ impl EguiRenderer {
         encoder: &mut wgpu::CommandEncoder,
         window: &Window,
         window_surface_view: &wgpu::TextureView,
-        screen_descriptor: ScreenDescriptor,
+        size_in_pixels: [u32; 2],
     ) {
         let full_output = self.egui_ctx.end_pass();
+        // Whatever scale egui laid the frame out at, which follows the monitor the window is on
+        let screen_descriptor = ScreenDescriptor {
+            size_in_pixels,
+            pixels_per_point: full_output.pixels_per_point,
+        };
 
         self.egui_state
             .handle_platform_output(window, full_output.platform_output);
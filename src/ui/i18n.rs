@@ -0,0 +1,117 @@
+//! Translations of the text shown in the UI. Each language has a table in `i18n/<code>.json` that maps
+//! keys, named after where the text is shown, to that language's text. Anything a table doesn't have
+//! (yet) is shown in English.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        LazyLock,
+        atomic::{AtomicU8, Ordering},
+    },
+};
+
+use crate::config::Language;
+
+static TABLES: LazyLock<HashMap<Language, HashMap<String, String>>> = LazyLock::new(|| {
+    Language::ALL
+        .into_iter()
+        .map(|language| {
+            let table = serde_json::from_str(table_source(language)).unwrap_or_else(|e| {
+                panic!("Failed to load the embedded {language} translations: {e}")
+            });
+            (language, table)
+        })
+        .collect()
+});
+
+fn table_source(language: Language) -> &'static str {
+    match language {
+        Language::English => include_str!("i18n/en.json"),
+        Language::Spanish => include_str!("i18n/es.json"),
+        Language::German => include_str!("i18n/de.json"),
+        Language::French => include_str!("i18n/fr.json"),
+        Language::Portuguese => include_str!("i18n/pt-BR.json"),
+    }
+}
+
+/// The language [`tr`] translates into, as its index in [`Language::ALL`]
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    let index = Language::ALL
+        .iter()
+        .position(|l| *l == language)
+        .unwrap_or_default();
+    CURRENT.store(index as u8, Ordering::Relaxed);
+}
+
+fn current() -> Language {
+    Language::ALL
+        .get(CURRENT.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// The text for `key` in the current language
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(current(), key)
+}
+
+/// Like [`tr`], with each `{name}` in the text replaced by its value in `args`
+pub fn tr_args(key: &'static str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    args.iter()
+        .fold(tr(key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+fn lookup(language: Language, key: &'static str) -> &'static str {
+    [language, Language::English]
+        .into_iter()
+        .find_map(|language| TABLES[&language].get(key))
+        .map(String::as_str)
+        .unwrap_or_else(|| {
+            tracing::warn!("Missing translation for {key}");
+            key
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The `{name}`s in `text`, sorted
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut placeholders = text
+            .split('{')
+            .skip(1)
+            .filter_map(|s| s.split_once('}').map(|(name, _)| name))
+            .collect::<Vec<_>>();
+        placeholders.sort_unstable();
+        placeholders
+    }
+
+    #[test]
+    fn test_tables_match_english() {
+        let english = &TABLES[&Language::English];
+        for language in Language::ALL {
+            for (key, text) in &TABLES[&language] {
+                let Some(english_text) = english.get(key) else {
+                    panic!("{language} has {key}, which English doesn't");
+                };
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(english_text),
+                    "{language} {key} has different placeholders to English"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup(Language::German, "login.continue"), "Weiter");
+        assert_eq!(lookup(Language::English, "login.continue"), "Continue");
+        assert_eq!(lookup(Language::German, "not.a.key"), "not.a.key");
+    }
+}
@@ -8,26 +8,29 @@ use std::{
 
 use color_eyre::Result;
 use egui_commonmark::CommonMarkCache;
-use egui_wgpu::{ScreenDescriptor, wgpu};
+use egui_wgpu::wgpu;
 use wgpu::SurfaceError;
 use winit::{
     application::ApplicationHandler,
-    dpi::PhysicalSize,
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     event::WindowEvent,
-    event_loop::{ActiveEventLoop, EventLoop},
-    window::{Window, WindowId},
+    event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy},
+    window::{Window, WindowId, WindowLevel},
 };
 
 use crate::{
-    app_state::{AppState, AsyncRequest, GitHubRelease, UiUpdate},
+    api::ApiKeyValidationError,
+    app_state::{AppState, AsyncRequest, GitHubRelease, RecordingStatus, UiUpdate},
     assets,
-    config::{Credentials, Preferences},
-    upload,
+    config::{Config, Credentials, Hotkey, Preferences},
+    system::keycode::lookup_keycode,
 };
 
 mod egui_renderer;
+mod i18n;
 use egui_renderer::EguiRenderer;
 mod overlay;
+mod theme;
 pub mod tray_icon;
 mod util;
 
@@ -35,21 +38,16 @@ mod views;
 
 pub mod notification;
 
-#[derive(PartialEq, Clone, Copy)]
-enum HotkeyRebindTarget {
-    /// Listening for start key
-    Start,
-    /// Listening for stop key
-    Stop,
-}
-
 /// Optimized to show everything in the layout at 1x scaling.
 ///
 /// Update this whenever you add or remove content. Assume that everything that a normal useer
 /// might see should be covered by this size (e.g. no temporary notices, but yes "delete invalid" button)
 ///
 /// Try to keep this below ~840px ((1080/1.25 = 864) - 24px taskbar)).
-const WINDOW_INNER_SIZE: PhysicalSize<u32> = PhysicalSize::new(600, 820);
+const WINDOW_INNER_SIZE: LogicalSize<u32> = LogicalSize::new(600, 820);
+const WINDOW_MIN_INNER_SIZE: LogicalSize<u32> = LogicalSize::new(400, 450);
+/// The size of the window while it's shrunk down to the recording HUD
+const HUD_INNER_SIZE: LogicalSize<u32> = LogicalSize::new(320, 64);
 
 pub fn start(
     wgpu_instance: wgpu::Instance,
@@ -70,13 +68,14 @@ pub fn start(
         }
     });
 
-    let event_loop = EventLoop::new().unwrap();
+    let event_loop = EventLoop::<UserEvent>::with_user_event().build().unwrap();
     // setting controlflow::wait is important. This means that once minimized to tray,
     // unlike eframe, it will no longer poll for updates - massively saving CPU.
     event_loop.set_control_flow(winit::event_loop::ControlFlow::Wait);
 
     let mut app = App::new(
         wgpu_instance,
+        event_loop.create_proxy(),
         app_state,
         visible,
         stopped_rx,
@@ -95,7 +94,6 @@ struct WgpuState {
     queue: wgpu::Queue,
     surface_config: wgpu::SurfaceConfiguration,
     surface: wgpu::Surface<'static>,
-    scale_factor: f32,
     egui_renderer: EguiRenderer,
 }
 
@@ -152,15 +150,12 @@ impl WgpuState {
 
         let egui_renderer = EguiRenderer::new(&device, surface_config.format, None, 1, window);
 
-        let scale_factor = 1.0;
-
         Self {
             device,
             queue,
             surface,
             surface_config,
             egui_renderer,
-            scale_factor,
         }
     }
 
@@ -171,17 +166,32 @@ impl WgpuState {
     }
 }
 
+/// Events sent to the winit event loop from outside of it
+enum UserEvent {
+    /// Screen reader requests, forwarded by accesskit
+    AccessKit(egui_winit::accesskit_winit::Event),
+}
+impl From<egui_winit::accesskit_winit::Event> for UserEvent {
+    fn from(event: egui_winit::accesskit_winit::Event) -> Self {
+        UserEvent::AccessKit(event)
+    }
+}
+
 struct App {
     instance: wgpu::Instance,
+    event_loop_proxy: EventLoopProxy<UserEvent>,
     wgpu_state: Option<WgpuState>,
     window: Option<Arc<Window>>,
     main_app: MainApp,
     last_repaint_requested: Instant,
+    /// Where the window was and how big it was before it was shrunk down to the recording HUD
+    restore_window: Option<(Option<PhysicalPosition<i32>>, PhysicalSize<u32>)>,
 }
 
 impl App {
     fn new(
         wgpu_instance: wgpu::Instance,
+        event_loop_proxy: EventLoopProxy<UserEvent>,
         app_state: Arc<AppState>,
         visible: Arc<AtomicBool>,
         stopped_rx: tokio::sync::broadcast::Receiver<()>,
@@ -200,16 +210,17 @@ impl App {
 
         Ok(Self {
             instance: wgpu_instance,
+            event_loop_proxy,
             wgpu_state: None,
             window: None,
             main_app,
             last_repaint_requested: Instant::now(),
+            restore_window: None,
         })
     }
 
     async fn set_window(&mut self, window: Window, inner_size: PhysicalSize<u32>) {
         let window = Arc::new(window);
-        let _ = window.request_inner_size(inner_size);
 
         let surface = self
             .instance
@@ -249,12 +260,6 @@ impl App {
 
         let state = self.wgpu_state.as_mut().unwrap();
 
-        let screen_descriptor = ScreenDescriptor {
-            size_in_pixels: [state.surface_config.width, state.surface_config.height],
-            pixels_per_point: self.window.as_ref().unwrap().scale_factor() as f32
-                * state.scale_factor,
-        };
-
         let surface_texture = state.surface.get_current_texture();
 
         match surface_texture {
@@ -293,7 +298,7 @@ impl App {
                 &mut encoder,
                 window,
                 &surface_view,
-                screen_descriptor,
+                [state.surface_config.width, state.surface_config.height],
             );
         }
 
@@ -304,10 +309,49 @@ impl App {
         window.pre_present_notify();
 
         surface_texture.present();
+
+        self.update_compact_hud();
+    }
+
+    /// Shrinks the window down to the recording HUD, or brings it back, when the main app has
+    /// switched to or from it this frame
+    fn update_compact_hud(&mut self) {
+        let Some(window) = self.window.clone() else {
+            return;
+        };
+
+        if std::mem::take(&mut self.main_app.hud_drag_requested) {
+            window.drag_window().ok();
+        }
+
+        let compact = self.main_app.compact_hud;
+        if compact == self.restore_window.is_some() {
+            return;
+        }
+
+        if compact {
+            self.restore_window = Some((window.outer_position().ok(), window.inner_size()));
+            window.set_min_inner_size(None::<LogicalSize<u32>>);
+            window.set_decorations(false);
+            window.set_window_level(WindowLevel::AlwaysOnTop);
+            let _ = window.request_inner_size(HUD_INNER_SIZE);
+            if let Some((x, y)) = self.main_app.local_preferences.hud_position {
+                window.set_outer_position(PhysicalPosition::new(x, y));
+            }
+        } else if let Some((position, size)) = self.restore_window.take() {
+            window.set_window_level(WindowLevel::Normal);
+            window.set_decorations(true);
+            window.set_min_inner_size(Some(WINDOW_MIN_INNER_SIZE));
+            let _ = window.request_inner_size(size);
+            if let Some(position) = position {
+                window.set_outer_position(position);
+            }
+        }
+        window.request_redraw();
     }
 }
 
-impl ApplicationHandler for App {
+impl ApplicationHandler<UserEvent> for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         if self.window.is_some() {
             return;
@@ -322,24 +366,28 @@ impl ApplicationHandler for App {
         let inner_size = WINDOW_INNER_SIZE;
         let window_attributes = Window::default_attributes()
             .with_title("OWL Control")
+            // In logical pixels, so that the window is the same size to the eye on every monitor
             .with_inner_size(inner_size)
-            .with_min_inner_size(PhysicalSize::new(400, 450))
+            .with_min_inner_size(WINDOW_MIN_INNER_SIZE)
             .with_resizable(true)
-            .with_window_icon(Some(window_icon));
+            .with_window_icon(Some(window_icon))
+            // accesskit has to be set up before the window is shown
+            .with_visible(false);
 
         let window = event_loop.create_window(window_attributes).unwrap();
-
-        // Now that we have the scale factor, we can multiply the inner size by it
-        // to ensure that the user will see the content at their DPI scaling.
-        let scale_factor = window.scale_factor();
-        let inner_size = PhysicalSize::new(
-            (inner_size.width as f64 * scale_factor) as u32,
-            (inner_size.height as f64 * scale_factor) as u32,
-        );
+        let inner_size = window.inner_size();
 
         // Block on async initialization
         futures::executor::block_on(self.set_window(window, inner_size));
 
+        let window = self.window.clone().unwrap();
+        self.wgpu_state
+            .as_mut()
+            .unwrap()
+            .egui_renderer
+            .init_accesskit(event_loop, &window, self.event_loop_proxy.clone());
+        window.set_visible(true);
+
         // Initialize tray icon and egui context after window is created
         let ctx = self.wgpu_state.as_ref().unwrap().egui_renderer.context();
         let _ = self.main_app.app_state.ui_update_tx.ctx.set(ctx.clone());
@@ -350,16 +398,9 @@ impl ApplicationHandler for App {
             self.main_app.visible.clone(),
             self.main_app.stopped_tx.clone(),
             self.main_app.app_state.ui_update_tx.clone(),
+            self.main_app.app_state.async_request_tx.clone(),
         );
 
-        catppuccin_egui::set_theme(ctx, catppuccin_egui::MACCHIATO);
-
-        ctx.style_mut(|style| {
-            let bg_color = egui::Color32::from_rgb(19, 21, 26);
-            style.visuals.window_fill = bg_color;
-            style.visuals.panel_fill = bg_color;
-        });
-
         if let Some(window) = self.window.clone() {
             ctx.set_request_repaint_callback(move |_info| {
                 // We just ignore the delay for now
@@ -368,6 +409,22 @@ impl ApplicationHandler for App {
         }
     }
 
+    fn user_event(&mut self, _: &ActiveEventLoop, event: UserEvent) {
+        match event {
+            UserEvent::AccessKit(event) => {
+                let Some(state) = self.wgpu_state.as_mut() else {
+                    return;
+                };
+                state
+                    .egui_renderer
+                    .handle_accesskit_event(event.window_event);
+                if let Some(window) = self.window.as_ref() {
+                    window.request_redraw();
+                }
+            }
+        }
+    }
+
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
         if self.wgpu_state.is_none() {
             return;
@@ -415,6 +472,20 @@ impl ApplicationHandler for App {
             WindowEvent::Resized(new_size) => {
                 self.handle_resized(new_size.width, new_size.height);
             }
+            WindowEvent::Moved(position) if self.restore_window.is_some() => {
+                // Remember where the HUD was dragged to for next time
+                self.main_app.local_preferences.hud_position = Some((position.x, position.y));
+            }
+            WindowEvent::ScaleFactorChanged { .. } => {
+                // e.g. the window was dragged onto a monitor with different scaling. egui has already picked up
+                // the new scale; the window's new size usually follows as a resize, but not if it works out
+                // to the same number of pixels, so the surface is brought up to date and laid out again here too.
+                if let Some(window) = self.window.clone() {
+                    let size = window.inner_size();
+                    self.handle_resized(size.width, size.height);
+                    window.request_redraw();
+                }
+            }
             _ => (),
         }
     }
@@ -430,9 +501,18 @@ pub struct MainApp {
     ui_update_rx: tokio::sync::mpsc::Receiver<UiUpdate>,
 
     login_api_key: String,
+    /// Whether the API key is shown in plain text on the login screen
+    login_api_key_visible: bool,
     is_authenticating_login_api_key: bool,
-    authenticated_user_id: Option<Result<String, String>>,
+    /// Set whenever the login screen is (re)opened, so that the key field can be focused
+    focus_login_api_key: bool,
+    authenticated_user_id: Option<Result<String, ApiKeyValidationError>>,
+    /// When the server will take another API key check, if it has been turning them away
+    login_rate_limited_until: Option<Instant>,
     has_scrolled_to_bottom_of_consent: bool,
+    /// The onboarding page being shown, if it hasn't been finished yet this session
+    onboarding_page: Option<usize>,
+    skip_onboarding_next_time: bool,
 
     /// Local copy of credentials, used to track UI state before saving to config
     local_credentials: Credentials,
@@ -440,16 +520,17 @@ pub struct MainApp {
     local_preferences: Preferences,
     /// Time since last requested config edit: we only attempt to save once enough time has passed
     config_last_edit: Option<Instant>,
+    /// The API key or whether to remember it has changed since it was last saved to the credential store
+    api_key_edited: bool,
     /// Is the UI currently listening for user to select a new hotkey for recording shortcut
-    listening_for_hotkey_rebind: Option<HotkeyRebindTarget>,
-
-    /// Current upload progress, updated from upload bridge via mpsc channel
-    current_upload_progress: Option<upload::ProgressData>,
-    /// Last upload error, updated from upload bridge via mpsc channel
-    last_upload_error: Option<String>,
+    listening_for_hotkey_rebind: Option<Hotkey>,
+    /// The last key pressed during a rebind that can't be used as a hotkey
+    rejected_hotkey: Option<String>,
 
     /// A newer release is available, updated from tokio thread via mpsc channel
     newer_release_available: Option<GitHubRelease>,
+    /// Free disk space in MB when it's running low, updated from tokio thread via mpsc channel
+    low_disk_space_mb: Option<u64>,
 
     md_cache: CommonMarkCache,
     visible: Arc<AtomicBool>,
@@ -458,11 +539,28 @@ pub struct MainApp {
     has_stopped: bool,
 
     main_view_state: views::main::MainViewState,
+    settings_view_state: views::settings::SettingsViewState,
+    logs_view_state: views::logs::LogsViewState,
+    preview_view_state: views::preview::PreviewViewState,
+    tagging_view_state: views::tagging::TaggingViewState,
+    capture_region_view_state: views::capture_region::CaptureRegionViewState,
+    s3_destination_view_state: views::s3_destination::S3DestinationViewState,
+    encryption_view_state: views::encryption::EncryptionViewState,
+    benchmark_view_state: views::benchmark::BenchmarkViewState,
+    summary_view_state: views::summary::SummaryViewState,
 
     tray_icon: tray_icon::TrayIconState,
 
     /// Whether the encoder settings window is open
     encoder_settings_window_open: bool,
+    /// Whether the window listing every hotkey is open
+    hotkey_reference_window_open: bool,
+    /// Whether the dark theme is applied, `None` before the first frame
+    applied_dark_mode: Option<bool>,
+    /// Whether the recording HUD is being shown in place of everything else
+    compact_hud: bool,
+    /// Set when the HUD is dragged, for the window to start moving with the mouse
+    hud_drag_requested: bool,
 }
 impl MainApp {
     fn new(
@@ -494,19 +592,27 @@ impl MainApp {
             ui_update_rx,
 
             login_api_key: local_credentials.api_key.clone(),
+            login_api_key_visible: false,
             is_authenticating_login_api_key: false,
+            focus_login_api_key: true,
             authenticated_user_id: None,
+            login_rate_limited_until: None,
             has_scrolled_to_bottom_of_consent: false,
+            // People who've already been through the consent form know what they signed up for
+            onboarding_page: (local_preferences.show_onboarding
+                && !local_credentials.has_consented)
+                .then_some(0),
+            skip_onboarding_next_time: true,
 
             local_credentials,
             local_preferences,
             config_last_edit: None,
+            api_key_edited: false,
             listening_for_hotkey_rebind: None,
-
-            current_upload_progress: None,
-            last_upload_error: None,
+            rejected_hotkey: None,
 
             newer_release_available: None,
+            low_disk_space_mb: None,
 
             md_cache: CommonMarkCache::default(),
             visible,
@@ -515,10 +621,23 @@ impl MainApp {
             has_stopped: false,
 
             main_view_state: views::main::MainViewState::default(),
+            settings_view_state: views::settings::SettingsViewState::default(),
+            logs_view_state: views::logs::LogsViewState::default(),
+            preview_view_state: views::preview::PreviewViewState::default(),
+            tagging_view_state: views::tagging::TaggingViewState::default(),
+            capture_region_view_state: views::capture_region::CaptureRegionViewState::default(),
+            s3_destination_view_state: views::s3_destination::S3DestinationViewState::default(),
+            encryption_view_state: views::encryption::EncryptionViewState::default(),
+            benchmark_view_state: views::benchmark::BenchmarkViewState::default(),
+            summary_view_state: views::summary::SummaryViewState::default(),
 
             tray_icon,
 
             encoder_settings_window_open: false,
+            hotkey_reference_window_open: false,
+            applied_dark_mode: None,
+            compact_hud: false,
+            hud_drag_requested: false,
         })
     }
 
@@ -536,22 +655,33 @@ impl MainApp {
             Ok(UiUpdate::ForceUpdate) => {
                 ctx.request_repaint();
             }
-            Ok(UiUpdate::UpdateUploadProgress(progress_data)) => {
-                self.current_upload_progress = progress_data;
-            }
             Ok(UiUpdate::UpdateUserId(uid)) => {
                 let was_successful = uid.is_ok();
+                self.login_rate_limited_until = match &uid {
+                    Err(ApiKeyValidationError::RateLimited(Some(retry_after))) => {
+                        Some(Instant::now() + *retry_after)
+                    }
+                    _ => None,
+                };
                 self.authenticated_user_id = Some(uid);
                 self.is_authenticating_login_api_key = false;
-                if was_successful && !self.local_credentials.has_consented {
-                    self.go_to_consent();
+                if was_successful {
+                    self.accept_login_api_key();
                 }
             }
-            Ok(UiUpdate::UploadFailed(error)) => {
-                self.last_upload_error = Some(error);
+            Ok(UiUpdate::UpdateRemoteCaptureSettings(capture_settings)) => {
+                self.local_preferences
+                    .apply_remote_capture_settings(capture_settings);
+            }
+            Ok(UiUpdate::ApiKeyRateLimited { retry_at }) => {
+                self.login_rate_limited_until = Some(retry_at);
+            }
+            Ok(UiUpdate::UploadFinished { .. }) => {
+                // The outcome is shown in the upload queue
+                ctx.request_repaint();
             }
             Ok(UiUpdate::UpdateTrayIconRecording(recording)) => {
-                self.tray_icon.set_icon_recording(recording);
+                self.tray_icon.set_recording(recording);
             }
             Ok(UiUpdate::UpdateNewerReleaseAvailable(release)) => {
                 self.newer_release_available = Some(release);
@@ -559,8 +689,45 @@ impl MainApp {
             Ok(UiUpdate::UpdateLocalRecordings(local_recordings)) => {
                 *self.app_state.local_recordings.write().unwrap() = local_recordings;
             }
+            Ok(UiUpdate::UpdateLowDiskSpace(free_space_mb)) => {
+                self.low_disk_space_mb = free_space_mb;
+            }
+            Ok(UiUpdate::ProblemReportCreated(result)) => {
+                self.logs_view_state.report_created(result);
+            }
+            Ok(UiUpdate::PlaybackLoaded(result)) => {
+                self.preview_view_state.playback_loaded(result);
+            }
+            Ok(UiUpdate::ConnectionTested(result)) => {
+                self.main_view_state.connection_tested(result);
+            }
+            Ok(UiUpdate::BenchmarkFinished(result)) => {
+                self.benchmark_view_state.benchmark_finished(result);
+            }
+            Ok(UiUpdate::UploadLabelsUpdated(result)) => {
+                self.tagging_view_state.upload_labels_updated(result);
+            }
+            Ok(UiUpdate::RecordingStarted(result)) => {
+                self.main_view_state.recording_started(result);
+            }
+            Ok(UiUpdate::RecordingFinished(summaries)) => {
+                self.summary_view_state.recording_finished(summaries);
+            }
             Err(_) => {}
         };
+        let upload_summary = {
+            let queue = self.app_state.upload_queue.read().unwrap();
+            queue.is_active().then(|| match queue.total_progress() {
+                Some(progress) => format!(
+                    "{:.0}% at {:.1} MB/s, {} left",
+                    progress.percent,
+                    progress.speed_mbps,
+                    util::format_seconds(progress.eta_seconds as u64)
+                ),
+                None => "Starting".to_string(),
+            })
+        };
+        self.tray_icon.set_uploading(upload_summary);
 
         if self.stopped_rx.try_recv().is_ok() {
             tracing::info!("MainApp received stop signal");
@@ -582,12 +749,17 @@ impl MainApp {
                     return;
                 };
 
-                let rebind_target = match target {
-                    HotkeyRebindTarget::Start => &mut self.local_preferences.start_recording_key,
-                    HotkeyRebindTarget::Stop => &mut self.local_preferences.stop_recording_key,
-                };
-                *rebind_target = key;
+                // The recorder can only listen for keys it knows the keycode of,
+                // so keep listening until we get one of those
+                if lookup_keycode(&key).is_none() {
+                    tracing::warn!("Ignoring unsupported hotkey {key}");
+                    self.rejected_hotkey = Some(key);
+                    return;
+                }
+
+                *self.local_preferences.hotkey_mut(target) = key;
                 self.listening_for_hotkey_rebind = None;
+                self.rejected_hotkey = None;
             });
         }
         // Very lazy solution (as opposed to tracking state changes), but should be sufficient
@@ -598,15 +770,39 @@ impl MainApp {
     }
 
     fn render(&mut self, ctx: &egui::Context) {
+        i18n::set_language(self.local_preferences.language);
+
+        // Checked every frame so that "Match System" picks up OS theme changes while we're open
+        let dark_mode = theme::is_dark(ctx, self.local_preferences.theme);
+        if self.applied_dark_mode != Some(dark_mode) {
+            theme::apply(ctx, dark_mode);
+            self.applied_dark_mode = Some(dark_mode);
+        }
+
+        // A stored key that was rejected (e.g. it was revoked) sends the user back to the login screen,
+        // where the error is shown and they can enter a new key. If it just couldn't be checked, we carry on offline.
         let (has_api_key, has_consented) = (
-            !self.local_credentials.api_key.is_empty(),
+            !self.local_credentials.api_key.is_empty()
+                && !matches!(&self.authenticated_user_id, Some(Err(e)) if !e.is_transient()),
             self.local_credentials.has_consented,
         );
 
-        match (has_api_key, has_consented) {
-            (true, true) => self.main_view(ctx),
-            (true, false) => self.consent_view(ctx),
-            (false, _) => self.login_view(ctx),
+        self.compact_hud = self.local_preferences.compact_hud
+            && has_api_key
+            && has_consented
+            && self.onboarding_page.is_none()
+            && *self.app_state.state.read().unwrap() != RecordingStatus::Stopped;
+
+        if self.compact_hud {
+            self.hud_view(ctx);
+        } else if let Some(page) = self.onboarding_page {
+            self.onboarding_view(ctx, page);
+        } else {
+            match (has_api_key, has_consented) {
+                (true, true) => self.main_view(ctx),
+                (true, false) => self.consent_view(ctx),
+                (false, _) => self.login_view(ctx),
+            }
         }
 
         // Queue up a save if any state has changed
@@ -614,9 +810,12 @@ impl MainApp {
             let mut config = self.app_state.config.write().unwrap();
             let mut requires_save = false;
             if config.credentials != self.local_credentials {
+                self.api_key_edited |= config.credentials.api_key != self.local_credentials.api_key
+                    || config.credentials.remember_me != self.local_credentials.remember_me;
                 config.credentials = self.local_credentials.clone();
                 requires_save = true;
             }
+            self.local_preferences.forget_overridden_remote_settings();
             if config.preferences != self.local_preferences {
                 config.preferences = self.local_preferences.clone();
                 requires_save = true;
@@ -630,7 +829,13 @@ impl MainApp {
             .config_last_edit
             .is_some_and(|t| t.elapsed() > Duration::from_millis(250))
         {
-            let _ = self.app_state.config.read().unwrap().save();
+            let config = self.app_state.config.read().unwrap();
+            let _ = config.save();
+            if std::mem::take(&mut self.api_key_edited)
+                && let Err(e) = config.save_api_key()
+            {
+                tracing::error!(e=?e, "Failed to save API key");
+            }
             self.config_last_edit = None;
         }
 
@@ -639,10 +844,95 @@ impl MainApp {
 }
 
 impl MainApp {
-    fn go_to_login(&mut self) {
+    /// Logs the user out: clears the API key from both the UI and disk, and asks the tokio
+    /// thread to stop any in-flight recording and forget the validated key.
+    fn logout(&mut self) {
         self.local_credentials.logout();
+        self.login_api_key.clear();
+        self.login_api_key_visible = false;
         self.authenticated_user_id = None;
+        self.login_rate_limited_until = None;
         self.is_authenticating_login_api_key = false;
+        self.focus_login_api_key = true;
+
+        // Don't wait for the debounced save in `render`; the key should be gone from disk immediately.
+        {
+            let mut config = self.app_state.config.write().unwrap();
+            config.credentials = self.local_credentials.clone();
+            if let Err(e) = config.save_api_key().and_then(|_| config.save()) {
+                tracing::error!(e=?e, "Failed to save config after logging out");
+            }
+        }
+        self.api_key_edited = false;
+
+        self.app_state
+            .async_request_tx
+            .blocking_send(AsyncRequest::Logout)
+            .ok();
+    }
+
+    /// Makes a change to which profile is in use (e.g. [`Config::switch_profile`]), then carries on with
+    /// the new profile's key and settings as if OWL Control had just been started with them
+    fn change_profile(
+        &mut self,
+        change: impl FnOnce(&mut Config) -> color_eyre::Result<()>,
+    ) -> color_eyre::Result<()> {
+        {
+            let mut config = self.app_state.config.write().unwrap();
+            config.credentials = self.local_credentials.clone();
+            config.preferences = self.local_preferences.clone();
+            // The current key has to be stored before it's put aside with its profile
+            if std::mem::take(&mut self.api_key_edited) {
+                config.save_api_key()?;
+            }
+            config.save()?;
+            change(&mut config)?;
+            if let Err(e) = config.save() {
+                tracing::error!(e=?e, "Failed to save config after changing profile");
+            }
+            self.local_credentials = config.credentials.clone();
+            self.local_preferences = config.preferences.clone();
+        }
+        self.config_last_edit = None;
+
+        self.login_api_key = self.local_credentials.api_key.clone();
+        self.login_api_key_visible = false;
+        self.authenticated_user_id = None;
+        self.login_rate_limited_until = None;
+        self.is_authenticating_login_api_key = false;
+        self.focus_login_api_key = true;
+
+        // Stops any recording made with the old key, and forgets it
+        self.app_state
+            .async_request_tx
+            .blocking_send(AsyncRequest::Logout)
+            .ok();
+        if !self.local_credentials.api_key.is_empty() && self.local_credentials.has_consented {
+            self.app_state
+                .async_request_tx
+                .blocking_send(AsyncRequest::ValidateApiKey {
+                    api_key: self.local_credentials.api_key.clone(),
+                })
+                .ok();
+        }
+        // The new profile may record somewhere else
+        self.app_state
+            .async_request_tx
+            .blocking_send(AsyncRequest::LoadLocalRecordings)
+            .ok();
+        Ok(())
+    }
+
+    /// Moves on from the login screen with the entered key, whether it's been validated or the user is
+    /// recording offline until it can be
+    fn accept_login_api_key(&mut self) {
+        if self.local_credentials.has_consented {
+            // Already consented on a previous run (e.g. the key wasn't remembered),
+            // so we can go straight to the main view.
+            self.local_credentials.api_key = self.login_api_key.clone();
+        } else {
+            self.go_to_consent();
+        }
     }
 
     fn go_to_consent(&mut self) {
@@ -652,6 +942,6 @@ impl MainApp {
     }
 
     fn go_to_main(&mut self) {
-        self.local_credentials.has_consented = true;
+        self.local_credentials.accept_consent();
     }
 }
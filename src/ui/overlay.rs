@@ -10,8 +10,8 @@ use windows::Win32::{
     Foundation::HWND,
     UI::WindowsAndMessaging::{
         FLASHW_STOP, FLASHWINFO, FlashWindowEx, GWL_EXSTYLE, GetWindowLongPtrW, SW_HIDE,
-        SW_SHOWDEFAULT, SetWindowLongPtrW, ShowWindow, WS_EX_APPWINDOW, WS_EX_NOACTIVATE,
-        WS_EX_TOOLWINDOW,
+        SW_SHOWDEFAULT, SetWindowDisplayAffinity, SetWindowLongPtrW, ShowWindow,
+        WDA_EXCLUDEFROMCAPTURE, WS_EX_APPWINDOW, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
     },
 };
 
@@ -31,6 +31,8 @@ pub struct OverlayApp {
     overlay_location: OverlayLocation,
     /// local opacity tracker
     overlay_opacity: u8,
+    /// local visibility tracker
+    show_overlay: bool,
     /// local recording status
     rec_status: RecordingStatus,
 
@@ -39,11 +41,12 @@ pub struct OverlayApp {
 }
 impl OverlayApp {
     pub fn new(app_state: Arc<AppState>, stopped_rx: tokio::sync::broadcast::Receiver<()>) -> Self {
-        let (overlay_location, overlay_opacity) = {
+        let (overlay_location, overlay_opacity, show_overlay) = {
             let config = app_state.config.read().unwrap();
             (
                 config.preferences.overlay_location,
                 config.preferences.overlay_opacity,
+                config.preferences.show_overlay,
             )
         };
         let rec_status = app_state.state.read().unwrap().clone();
@@ -53,6 +56,7 @@ impl OverlayApp {
 
             overlay_location,
             overlay_opacity,
+            show_overlay,
             rec_status,
 
             last_paint_time: Instant::now(),
@@ -66,7 +70,7 @@ impl OverlayApp {
         egui_context: &egui::Context,
         glfw_backend: &mut egui_window_glfw_passthrough::GlfwBackend,
         curr_location: OverlayLocation,
-        curr_opacity: u8,
+        curr_visible: bool,
     ) {
         // install image loaders
         egui_extras::install_image_loaders(egui_context);
@@ -107,11 +111,16 @@ impl OverlayApp {
                 SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style);
 
                 let _ = ShowWindow(hwnd, SW_SHOWDEFAULT); // show the window for the new style to come into effect
+
+                // keep the overlay out of screen/window captures, including our own recordings.
+                // only supported on Windows 10 2004 and up, older versions will just capture it
+                if let Err(e) = SetWindowDisplayAffinity(hwnd, WDA_EXCLUDEFROMCAPTURE) {
+                    tracing::warn!(e=?e, "Failed to exclude overlay from capture");
+                }
             }
         }
 
-        // Hide window if opacity is 0
-        if curr_opacity == 0 {
+        if !curr_visible {
             self.set_window_visible(glfw_backend, false);
         }
     }
@@ -140,18 +149,24 @@ impl EguiOverlay for OverlayApp {
         _default_gfx_backend: &mut DefaultGfxBackend,
         glfw_backend: &mut egui_window_glfw_passthrough::GlfwBackend,
     ) {
-        let (curr_opacity, curr_location) = {
+        let (curr_opacity, curr_location, curr_show_overlay) = {
             let config = self.app_state.config.read().unwrap();
             (
                 config.preferences.overlay_opacity,
                 config.preferences.overlay_location,
+                config.preferences.show_overlay,
             )
         };
 
         // kind of cringe that we are forced to check first frame setup logic like this, but egui_overlay doesn't expose
         // any setup/init interface
         if !self.initialized {
-            self.first_frame_init(egui_context, glfw_backend, curr_location, curr_opacity);
+            self.first_frame_init(
+                egui_context,
+                glfw_backend,
+                curr_location,
+                curr_show_overlay && curr_opacity > 0,
+            );
             egui_context.request_repaint();
             self.initialized = true;
         }
@@ -162,11 +177,12 @@ impl EguiOverlay for OverlayApp {
             return;
         }
 
-        if curr_opacity != self.overlay_opacity {
+        if curr_opacity != self.overlay_opacity || curr_show_overlay != self.show_overlay {
             self.overlay_opacity = curr_opacity;
+            self.show_overlay = curr_show_overlay;
             egui_context.request_repaint();
 
-            self.set_window_visible(glfw_backend, curr_opacity > 0);
+            self.set_window_visible(glfw_backend, curr_show_overlay && curr_opacity > 0);
         }
         if curr_location != self.overlay_location {
             self.overlay_location = curr_location;
@@ -212,6 +228,29 @@ impl EguiOverlay for OverlayApp {
                             .tint(Color32::from_white_alpha(self.overlay_opacity)),
                     );
 
+                    // blinking red dot while recording, in step with the 500ms repaints
+                    if let RecordingStatus::Recording {
+                        start_time,
+                        paused_at: None,
+                        ..
+                    } = &self.rec_status
+                    {
+                        let (rect, _) =
+                            ui.allocate_exact_size(Vec2::splat(10.0), egui::Sense::hover());
+                        if start_time.elapsed().as_millis() / 500 % 2 == 0 {
+                            ui.painter().circle_filled(
+                                rect.center(),
+                                5.0,
+                                Color32::from_rgba_unmultiplied(
+                                    220,
+                                    30,
+                                    30,
+                                    self.overlay_opacity.max(128),
+                                ),
+                            );
+                        }
+                    }
+
                     let font_id = egui::FontId::new(12.0, egui::FontFamily::Proportional);
                     let color = Color32::from_white_alpha(self.overlay_opacity);
                     let recording_text: egui::WidgetText = match &self.rec_status {
@@ -222,10 +261,16 @@ impl EguiOverlay for OverlayApp {
                         RecordingStatus::Recording {
                             start_time,
                             game_exe,
+                            paused_at,
+                            ..
                         } => {
                             let mut job = LayoutJob::default();
                             job.append(
-                                "Recording ",
+                                if paused_at.is_some() {
+                                    "Paused "
+                                } else {
+                                    "Recording "
+                                },
                                 0.0,
                                 TextFormat {
                                     font_id: font_id.clone(),
@@ -246,7 +291,12 @@ impl EguiOverlay for OverlayApp {
                             job.append(
                                 &format!(
                                     " ({})",
-                                    util::format_seconds(start_time.elapsed().as_secs())
+                                    util::format_seconds(
+                                        paused_at
+                                            .unwrap_or_else(Instant::now)
+                                            .duration_since(*start_time)
+                                            .as_secs()
+                                    )
                                 ),
                                 0.0,
                                 TextFormat {
@@ -257,10 +307,37 @@ impl EguiOverlay for OverlayApp {
                             );
                             job.into()
                         }
-                        RecordingStatus::Paused => egui::RichText::new("Paused")
+                        RecordingStatus::CountingDown { starts_at } => {
+                            egui::RichText::new(format!(
+                                "Recording in {}s - press the start key again to cancel",
+                                util::countdown_seconds(*starts_at)
+                            ))
                             .font(font_id)
                             .color(color)
-                            .into(),
+                            .into()
+                        }
+                        RecordingStatus::Paused { auto_stop_at: None } => {
+                            egui::RichText::new("Paused")
+                                .font(font_id)
+                                .color(color)
+                                .into()
+                        }
+                        RecordingStatus::Paused {
+                            auto_stop_at: Some(auto_stop_at),
+                        } => egui::RichText::new(format!(
+                            "Idle, stopping in {}s - press any key to keep going",
+                            auto_stop_at
+                                .saturating_duration_since(Instant::now())
+                                .as_secs()
+                        ))
+                        .font(font_id)
+                        .color(Color32::from_rgba_unmultiplied(
+                            255,
+                            200,
+                            0,
+                            self.overlay_opacity.max(128),
+                        ))
+                        .into(),
                     };
                     ui.label(recording_text);
                 });
@@ -0,0 +1,70 @@
+//! Colours used throughout the UI, in versions that are readable against both the dark and the light theme
+
+use egui::Color32;
+
+use crate::config::Theme;
+
+pub struct Palette {
+    pub heading: Color32,
+    pub subheading: Color32,
+    /// Secondary text, like hints and info icons
+    pub muted: Color32,
+    pub warning: Color32,
+    pub error: Color32,
+    pub success: Color32,
+}
+
+const DARK: Palette = Palette {
+    heading: Color32::from_rgb(220, 220, 220),
+    subheading: Color32::from_rgb(180, 180, 180),
+    muted: Color32::from_rgb(128, 128, 128),
+    warning: Color32::from_rgb(255, 200, 0),
+    error: Color32::from_rgb(255, 0, 0),
+    success: Color32::from_rgb(100, 255, 100),
+};
+
+const LIGHT: Palette = Palette {
+    heading: Color32::from_rgb(40, 40, 40),
+    subheading: Color32::from_rgb(80, 80, 80),
+    muted: Color32::from_rgb(110, 110, 110),
+    warning: Color32::from_rgb(170, 110, 0),
+    error: Color32::from_rgb(200, 0, 0),
+    success: Color32::from_rgb(20, 130, 50),
+};
+
+/// The palette for whichever theme is currently applied
+pub fn palette(ctx: &egui::Context) -> &'static Palette {
+    if ctx.style().visuals.dark_mode {
+        &DARK
+    } else {
+        &LIGHT
+    }
+}
+
+/// Whether `theme` means dark mode right now. The OS theme is reported to egui by winit.
+pub fn is_dark(ctx: &egui::Context, theme: Theme) -> bool {
+    match theme {
+        Theme::Dark => true,
+        Theme::Light => false,
+        Theme::System => ctx.system_theme() != Some(egui::Theme::Light),
+    }
+}
+
+pub fn apply(ctx: &egui::Context, dark: bool) {
+    // egui keeps separate styles for dark and light mode, so pin it to the one we're about to set up
+    ctx.set_theme(if dark {
+        egui::Theme::Dark
+    } else {
+        egui::Theme::Light
+    });
+    if dark {
+        catppuccin_egui::set_theme(ctx, catppuccin_egui::MACCHIATO);
+        ctx.style_mut(|style| {
+            let bg_color = Color32::from_rgb(19, 21, 26);
+            style.visuals.window_fill = bg_color;
+            style.visuals.panel_fill = bg_color;
+        });
+    } else {
+        catppuccin_egui::set_theme(ctx, catppuccin_egui::LATTE);
+    }
+}
@@ -4,31 +4,72 @@ use std::sync::{
 };
 
 use color_eyre::eyre::{self, Context as _};
+use tokio::sync::mpsc;
 use tray_icon::{
     MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent,
-    menu::{Menu, MenuEvent, MenuId, MenuItem},
+    menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem},
 };
 use winit::window::Window;
 
 use crate::{
-    app_state::{UiUpdate, UiUpdateSender},
+    app_state::{AsyncRequest, UiUpdate, UiUpdateSender},
     assets,
 };
 
+/// What the app is currently doing, as shown by the tray icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayStatus {
+    Idle,
+    Recording,
+    Uploading,
+}
+impl TrayStatus {
+    fn label(self) -> &'static str {
+        match self {
+            TrayStatus::Idle => "Idle",
+            TrayStatus::Recording => "Recording",
+            TrayStatus::Uploading => "Uploading",
+        }
+    }
+}
+
 pub struct TrayIconState {
     icon: TrayIcon,
+    status_item: MenuItem,
+    open_item_id: MenuId,
+    start_item: MenuItem,
+    stop_item: MenuItem,
     quit_item_id: MenuId,
 
+    is_recording: bool,
+    /// Condensed upload progress, while anything is uploading
+    upload_summary: Option<String>,
+    status: TrayStatus,
+    tooltip: String,
+
     default_tray_icon_data: tray_icon::Icon,
     recording_tray_icon_data: tray_icon::Icon,
 }
 impl TrayIconState {
     pub fn new() -> eyre::Result<Self> {
-        // tray icon right click menu for quit option
+        // tray icon right click menu
+        let status_item = MenuItem::new(TrayStatus::Idle.label(), false, None);
+        let open_item = MenuItem::new("Open OWL Control", true, None);
+        let start_item = MenuItem::new("Start Recording", true, None);
+        let stop_item = MenuItem::new("Stop Recording", false, None);
         let quit_item = MenuItem::new("Quit", true, None);
+        let open_item_id = open_item.id().clone();
         let quit_item_id = quit_item.id().clone();
         let tray_menu = Menu::new();
-        let _ = tray_menu.append(&quit_item);
+        let _ = tray_menu.append_items(&[
+            &status_item,
+            &PredefinedMenuItem::separator(),
+            &open_item,
+            &start_item,
+            &stop_item,
+            &PredefinedMenuItem::separator(),
+            &quit_item,
+        ]);
 
         // create tray icon
         fn create_tray_icon_data_from_bytes(bytes: &[u8]) -> eyre::Result<tray_icon::Icon> {
@@ -44,13 +85,21 @@ impl TrayIconState {
 
         let tray_icon = TrayIconBuilder::new()
             .with_icon(default_tray_icon_data.clone())
-            .with_tooltip("OWL Control")
+            .with_tooltip(tooltip(TrayStatus::Idle, None))
             .with_menu(Box::new(tray_menu))
             .build()?;
 
         Ok(TrayIconState {
             icon: tray_icon,
+            status_item,
+            open_item_id,
+            start_item,
+            stop_item,
             quit_item_id,
+            is_recording: false,
+            upload_summary: None,
+            status: TrayStatus::Idle,
+            tooltip: tooltip(TrayStatus::Idle, None),
             default_tray_icon_data,
             recording_tray_icon_data,
         })
@@ -64,12 +113,34 @@ impl TrayIconState {
         visible: Arc<AtomicBool>,
         stopped_tx: tokio::sync::broadcast::Sender<()>,
         ui_update_tx: UiUpdateSender,
+        async_request_tx: mpsc::Sender<AsyncRequest>,
     ) {
         MenuEvent::set_event_handler({
+            let open_item_id = self.open_item_id.clone();
+            let start_item_id = self.start_item.id().clone();
+            let stop_item_id = self.stop_item.id().clone();
             let quit_item_id = self.quit_item_id.clone();
+            let context = context.clone();
             let window = window.clone();
             let visible = visible.clone();
             Some(move |event: MenuEvent| match event.id() {
+                id if id == &open_item_id => {
+                    window.set_visible(true);
+                    visible.store(true, Ordering::Relaxed);
+                    window.set_minimized(false);
+                    window.focus_window();
+                    context.request_repaint();
+                }
+                id if id == &start_item_id => {
+                    async_request_tx
+                        .blocking_send(AsyncRequest::StartRecording)
+                        .ok();
+                }
+                id if id == &stop_item_id => {
+                    async_request_tx
+                        .blocking_send(AsyncRequest::StopRecording)
+                        .ok();
+                }
                 id if id == &quit_item_id => {
                     tracing::info!("Tray icon requested shutdown");
                     stopped_tx.send(()).unwrap();
@@ -111,13 +182,57 @@ impl TrayIconState {
         }));
     }
 
-    pub fn set_icon_recording(&self, recording: bool) {
+    pub fn set_recording(&mut self, recording: bool) {
+        self.is_recording = recording;
+        self.update_status();
+    }
+
+    /// `summary` is a short description of the upload progress, or `None` if nothing is uploading
+    pub fn set_uploading(&mut self, summary: Option<String>) {
+        self.upload_summary = summary;
+        self.update_status();
+    }
+
+    /// Brings the icon, tooltip and menu in line with what the app is doing. Recording takes
+    /// precedence over uploading, as it's the one the user is most likely to care about.
+    fn update_status(&mut self) {
+        let status = if self.is_recording {
+            TrayStatus::Recording
+        } else if self.upload_summary.is_some() {
+            TrayStatus::Uploading
+        } else {
+            TrayStatus::Idle
+        };
+
+        // Upload progress is shown even while recording, as uploads carry on in the background
+        let tooltip = tooltip(status, self.upload_summary.as_deref());
+        if tooltip != self.tooltip {
+            self.icon.set_tooltip(Some(&tooltip)).ok();
+            self.tooltip = tooltip;
+        }
+
+        if status == self.status {
+            return;
+        }
+        self.status = status;
+
         self.icon
-            .set_icon(Some(if recording {
+            .set_icon(Some(if status == TrayStatus::Recording {
                 self.recording_tray_icon_data.clone()
             } else {
                 self.default_tray_icon_data.clone()
             }))
             .ok();
+        self.status_item.set_text(status.label());
+        self.start_item.set_enabled(!self.is_recording);
+        self.stop_item.set_enabled(self.is_recording);
+    }
+}
+
+fn tooltip(status: TrayStatus, upload_summary: Option<&str>) -> String {
+    match (status, upload_summary) {
+        (TrayStatus::Uploading, Some(summary)) => format!("OWL Control - Uploading {summary}"),
+        (_, Some(summary)) => format!("OWL Control - {}, uploading {summary}", status.label()),
+        (_, None) => format!("OWL Control - {}", status.label()),
     }
 }
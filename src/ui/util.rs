@@ -25,6 +25,14 @@ pub fn format_seconds(total_seconds: u64) -> String {
     }
 }
 
+/// Whole seconds left until `deadline`, rounded up so that a countdown never shows 0 while it's still going
+pub fn countdown_seconds(deadline: std::time::Instant) -> u64 {
+    deadline
+        .saturating_duration_since(std::time::Instant::now())
+        .as_millis()
+        .div_ceil(1000) as u64
+}
+
 /// Give a datetime, formats it into a human-readable string (e.g., "2025-03-10 10:00:00").
 pub fn format_datetime(dt: chrono::DateTime<chrono::Local>) -> String {
     dt.format("%Y-%m-%d %H:%M:%S").to_string()
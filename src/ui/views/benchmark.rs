@@ -0,0 +1,150 @@
+use constants::{RECORDING_HEIGHT, RECORDING_WIDTH};
+
+use crate::{
+    app_state::AsyncRequest,
+    record::{BENCHMARK_RUN_LENGTH, BenchmarkReport},
+    ui::{MainApp, theme},
+};
+
+#[derive(Default)]
+pub(crate) struct BenchmarkViewState {
+    /// Whether the benchmark window is open
+    pub(super) open: bool,
+    is_running: bool,
+    /// How the last benchmark went, or why it couldn't finish
+    report: Option<Result<BenchmarkReport, String>>,
+}
+impl BenchmarkViewState {
+    pub(crate) fn benchmark_finished(&mut self, result: Result<BenchmarkReport, String>) {
+        self.is_running = false;
+        self.report = Some(result);
+        // It finishes while the user is off playing, so bring the results back up
+        self.open = true;
+    }
+}
+
+impl MainApp {
+    pub(super) fn benchmark_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.benchmark_view_state;
+        let app_state = &self.app_state;
+        let preferences = &mut self.local_preferences;
+        egui::Window::new("Benchmark")
+            .open(&mut state.open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Finds the highest frame rate this computer can record your game at without dropping frames. \
+                     Once you switch to your game, it's recorded for {} seconds at each setting, best first, \
+                     until one keeps up. These recordings are deleted straight away.",
+                    BENCHMARK_RUN_LENGTH.as_secs()
+                ));
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Recordings are always {RECORDING_WIDTH}x{RECORDING_HEIGHT}, so only the frame rate, encoder and codec are tried. \
+                         Play as you normally would while it runs, so that the results match what you'll record."
+                    ))
+                    .small()
+                    .color(theme::palette(ctx).muted),
+                );
+                ui.add_space(6.0);
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !state.is_running,
+                            egui::Button::new(if state.is_running {
+                                "Running..."
+                            } else {
+                                "Run Benchmark"
+                            }),
+                        )
+                        .clicked()
+                    {
+                        state.is_running = true;
+                        state.report = None;
+                        app_state
+                            .async_request_tx
+                            .blocking_send(AsyncRequest::RunBenchmark)
+                            .ok();
+                    }
+                    if state.is_running {
+                        ui.spinner();
+                        ui.label("Switch to your game; the results will show here once it's done");
+                    }
+                });
+
+                match &state.report {
+                    Some(Ok(report)) => {
+                        ui.add_space(6.0);
+                        egui::Grid::new("benchmark_runs")
+                            .num_columns(4)
+                            .spacing([12.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for header in ["Frame Rate", "Encoder", "Codec", "Dropped"] {
+                                    ui.label(egui::RichText::new(header).strong());
+                                }
+                                ui.end_row();
+                                for run in &report.runs {
+                                    ui.label(format!("{} FPS", run.settings.fps));
+                                    ui.label(run.settings.encoder.to_string());
+                                    ui.label(run.settings.codec.to_string());
+                                    let (text, color) = match run.skipped_frames {
+                                        Some(skipped_frames) => (
+                                            format!("{:.1}%", skipped_frames.percentage()),
+                                            if run.is_sustainable() {
+                                                theme::palette(ctx).success
+                                            } else {
+                                                theme::palette(ctx).error
+                                            },
+                                        ),
+                                        None => ("Unknown".to_string(), theme::palette(ctx).muted),
+                                    };
+                                    ui.label(egui::RichText::new(text).color(color));
+                                    ui.end_row();
+                                }
+                            });
+
+                        ui.add_space(6.0);
+                        match report.recommended() {
+                            Some(settings) => {
+                                ui.label(format!(
+                                    "Recommended: {} FPS with {}, {}",
+                                    settings.fps, settings.encoder, settings.codec
+                                ));
+                                let is_current = preferences.fps == settings.fps
+                                    && preferences.encoder.encoder == settings.encoder
+                                    && preferences.encoder.codec == settings.codec;
+                                if is_current {
+                                    ui.label(
+                                        egui::RichText::new("These are your current settings.")
+                                            .color(theme::palette(ctx).success),
+                                    );
+                                } else if ui.button("Apply").clicked() {
+                                    preferences.fps = settings.fps;
+                                    preferences.encoder.encoder = settings.encoder;
+                                    preferences.encoder.codec = settings.codec;
+                                }
+                            }
+                            None => {
+                                ui.label(
+                                    egui::RichText::new(
+                                        "None of the settings kept up. Try closing other programs, or lowering the game's graphics settings, and run it again.",
+                                    )
+                                    .color(theme::palette(ctx).warning),
+                                );
+                            }
+                        }
+                    }
+                    Some(Err(e)) => {
+                        ui.label(
+                            egui::RichText::new(format!("The benchmark couldn't finish: {e}"))
+                                .color(theme::palette(ctx).error),
+                        );
+                    }
+                    None => {}
+                }
+            });
+    }
+}
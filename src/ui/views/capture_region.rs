@@ -0,0 +1,280 @@
+use crate::{
+    config::{CaptureRegion, Preferences},
+    record,
+    system::hardware_specs::{self, MonitorInfo, ScreenRect},
+    ui::{MainApp, theme},
+};
+
+/// How close, in the monitor's pixels, an edge has to be dragged to a window's edge to snap onto it
+const SNAP_DISTANCE: i32 = 12;
+/// Width of the monitor as drawn in the window
+const CANVAS_WIDTH: f32 = 480.0;
+
+#[derive(Default)]
+pub(crate) struct CaptureRegionViewState {
+    /// Whether the capture region window is open
+    pub(super) open: bool,
+    /// Looked up when the window is opened, as they rarely change while it's open
+    monitors: Vec<MonitorInfo>,
+    /// Outlines of the windows that were open when the window was opened, for snapping to
+    windows: Vec<ScreenRect>,
+    /// The monitor being picked on
+    monitor_index: usize,
+    /// Where the current drag started, on the desktop
+    drag_origin: Option<(i32, i32)>,
+}
+impl CaptureRegionViewState {
+    /// Opens the window on the monitor that `current` is on, or the primary one if there isn't a region yet
+    pub(super) fn open(&mut self, current: Option<&CaptureRegion>) {
+        let monitors = hardware_specs::get_monitors();
+        let monitor_index = current
+            .and_then(|region| monitors.iter().position(|m| m.name == region.monitor))
+            .unwrap_or_default();
+        *self = Self {
+            open: true,
+            monitors,
+            windows: hardware_specs::get_visible_window_rects(),
+            monitor_index,
+            drag_origin: None,
+        };
+    }
+}
+
+impl MainApp {
+    pub(super) fn capture_region_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.capture_region_view_state;
+        let preferences = &mut self.local_preferences;
+        egui::Window::new("Capture Region")
+            .open(&mut state.open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if state.monitors.is_empty() {
+                    ui.label(
+                        egui::RichText::new("Couldn't find any monitors.")
+                            .color(theme::palette(ctx).error),
+                    );
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Monitor:");
+                    let monitor_text = |monitor: &MonitorInfo| {
+                        format!(
+                            "{} ({}×{}){}",
+                            monitor.name,
+                            monitor.rect.width,
+                            monitor.rect.height,
+                            if monitor.primary { ", primary" } else { "" }
+                        )
+                    };
+                    egui::ComboBox::from_id_salt("capture_region_monitor")
+                        .selected_text(monitor_text(&state.monitors[state.monitor_index]))
+                        .show_ui(ui, |ui| {
+                            for (index, monitor) in state.monitors.iter().enumerate() {
+                                ui.selectable_value(
+                                    &mut state.monitor_index,
+                                    index,
+                                    monitor_text(monitor),
+                                );
+                            }
+                        });
+                    ui.checkbox(
+                        &mut preferences.snap_capture_region,
+                        "Snap to window edges",
+                    );
+                });
+
+                let monitor = &state.monitors[state.monitor_index];
+                if let Some(region) = &preferences.capture_region
+                    && !state.monitors.iter().any(|m| m.name == region.monitor)
+                {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "The region is on {}, which isn't connected. The whole game is recorded until it's reconnected or another region is picked.",
+                            region.monitor
+                        ))
+                        .color(theme::palette(ctx).warning),
+                    );
+                }
+
+                // The monitor, scaled to fit, with the windows on it outlined
+                let scale = CANVAS_WIDTH / monitor.rect.width.max(1) as f32;
+                let (canvas, response) = ui.allocate_exact_size(
+                    egui::vec2(CANVAS_WIDTH, monitor.rect.height as f32 * scale),
+                    egui::Sense::drag(),
+                );
+                let to_canvas = |rect: &ScreenRect| {
+                    egui::Rect::from_min_size(
+                        canvas.min
+                            + egui::vec2(
+                                (rect.x - monitor.rect.x) as f32,
+                                (rect.y - monitor.rect.y) as f32,
+                            ) * scale,
+                        egui::vec2(rect.width as f32, rect.height as f32) * scale,
+                    )
+                };
+                let to_screen = |position: egui::Pos2| {
+                    let position = ((position.clamp(canvas.min, canvas.max) - canvas.min) / scale)
+                        .round();
+                    (
+                        monitor.rect.x + position.x as i32,
+                        monitor.rect.y + position.y as i32,
+                    )
+                };
+
+                let painter = ui.painter_at(canvas);
+                painter.rect_filled(canvas, 0.0, ui.visuals().extreme_bg_color);
+                // Back to front, so that the frontmost windows are drawn on top
+                for window in state.windows.iter().rev() {
+                    if window.intersect(&monitor.rect).is_some() {
+                        painter.rect(
+                            to_canvas(window),
+                            0.0,
+                            ui.visuals().extreme_bg_color,
+                            egui::Stroke::new(1.0, theme::palette(ctx).muted),
+                            egui::StrokeKind::Inside,
+                        );
+                    }
+                }
+
+                if response.drag_started()
+                    && let Some(position) = response.interact_pointer_pos()
+                {
+                    state.drag_origin = Some(to_screen(position));
+                }
+                if let (Some((origin_x, origin_y)), Some(position)) =
+                    (state.drag_origin, response.interact_pointer_pos())
+                    && response.dragged()
+                {
+                    let (x, y) = to_screen(position);
+                    let mut rect = ScreenRect {
+                        x: origin_x.min(x),
+                        y: origin_y.min(y),
+                        width: origin_x.abs_diff(x),
+                        height: origin_y.abs_diff(y),
+                    };
+                    if preferences.snap_capture_region {
+                        rect = record::snap_to_edges(rect, &state.windows, SNAP_DISTANCE);
+                    }
+                    set_region(preferences, monitor, rect);
+                }
+                if response.drag_stopped() {
+                    state.drag_origin = None;
+                }
+
+                // Only drawn on the monitor it's on, where it could have been scaled or cut down to fit
+                let region = preferences
+                    .capture_region
+                    .as_ref()
+                    .filter(|region| region.monitor == monitor.name)
+                    .and_then(|region| record::region_on_screen(region, &state.monitors).ok());
+                if let Some(region) = region {
+                    let accent = ui.visuals().selection.bg_fill;
+                    painter.rect(
+                        to_canvas(&region),
+                        0.0,
+                        accent.gamma_multiply(0.4),
+                        egui::Stroke::new(2.0, accent),
+                        egui::StrokeKind::Inside,
+                    );
+                }
+                ui.label(
+                    egui::RichText::new(
+                        "Drag on the monitor to pick the region. Only the part of the game inside it is recorded.",
+                    )
+                    .small()
+                    .color(theme::palette(ctx).muted),
+                );
+
+                // For fine-tuning, in the monitor's own pixels
+                if let Some(region) = region {
+                    let mut x = region.x - monitor.rect.x;
+                    let mut y = region.y - monitor.rect.y;
+                    let mut width = region.width;
+                    let mut height = region.height;
+                    let changed = ui
+                        .horizontal(|ui| {
+                            ui.label("X:");
+                            let mut changed = ui
+                                .add(egui::DragValue::new(&mut x).range(0..=monitor.rect.width as i32))
+                                .changed();
+                            ui.label("Y:");
+                            changed |= ui
+                                .add(egui::DragValue::new(&mut y).range(0..=monitor.rect.height as i32))
+                                .changed();
+                            ui.label("Width:");
+                            changed |= ui
+                                .add(egui::DragValue::new(&mut width).range(1..=monitor.rect.width))
+                                .changed();
+                            ui.label("Height:");
+                            changed |= ui
+                                .add(egui::DragValue::new(&mut height).range(1..=monitor.rect.height))
+                                .changed();
+                            changed
+                        })
+                        .inner;
+                    if changed {
+                        set_region(
+                            preferences,
+                            monitor,
+                            ScreenRect {
+                                x: monitor.rect.x + x,
+                                y: monitor.rect.y + y,
+                                width,
+                                height,
+                            },
+                        );
+                    }
+                    if region.width < record::MIN_REGION_SIZE
+                        || region.height < record::MIN_REGION_SIZE
+                    {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "The region needs to be at least {0}×{0} to be used.",
+                                record::MIN_REGION_SIZE
+                            ))
+                            .color(theme::palette(ctx).warning),
+                        );
+                    }
+                }
+
+                ui.add_space(6.0);
+                if ui
+                    .add_enabled(
+                        preferences.capture_region.is_some(),
+                        egui::Button::new("Record Whole Game"),
+                    )
+                    .clicked()
+                {
+                    preferences.capture_region = None;
+                }
+            });
+    }
+}
+
+/// Stores `rect` (on the desktop) as the capture region, keeping to the part of it that's on `monitor`
+fn set_region(preferences: &mut Preferences, monitor: &MonitorInfo, rect: ScreenRect) {
+    let Some(rect) = rect.intersect(&monitor.rect) else {
+        return;
+    };
+    preferences.capture_region = Some(CaptureRegion {
+        monitor: monitor.name.clone(),
+        monitor_resolution: (monitor.rect.width, monitor.rect.height),
+        x: rect.x - monitor.rect.x,
+        y: rect.y - monitor.rect.y,
+        width: rect.width,
+        height: rect.height,
+    });
+}
+
+/// How the capture region is described in the settings
+pub(super) fn capture_region_summary(region: Option<&CaptureRegion>) -> String {
+    match region {
+        Some(region) => format!(
+            "{}×{} at ({}, {}) on {}",
+            region.width, region.height, region.x, region.y, region.monitor
+        ),
+        None => "Whole game".to_string(),
+    }
+}
@@ -49,7 +49,7 @@ impl MainApp {
                                 )
                                 .clicked()
                             {
-                                self.go_to_login();
+                                self.logout();
                             }
                         });
                     });
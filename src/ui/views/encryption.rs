@@ -0,0 +1,152 @@
+use crate::{
+    config::{EncryptionKeySource, RecordingEncryption},
+    system::credential_store::ProtectedSecret,
+    ui::{MainApp, theme, views::main::tooltip},
+};
+
+/// Shortest passphrase that's accepted. It's stretched before use, but a short one is still quick to guess.
+const MIN_PASSPHRASE_LEN: usize = 12;
+
+#[derive(Default)]
+pub(crate) struct EncryptionViewState {
+    /// Whether the encryption window is open
+    pub(super) open: bool,
+    /// How the next key is to be made. Defaults to how the current one was.
+    key_source: Option<EncryptionKeySource>,
+    /// The passphrase being typed in, twice. It's only kept (encrypted) once it's been confirmed.
+    passphrase: String,
+    passphrase_confirmation: String,
+    /// Why the key couldn't be changed
+    error: Option<String>,
+}
+
+impl MainApp {
+    pub(super) fn encryption_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.encryption_view_state;
+        let encryption = &mut self.local_preferences.encryption;
+        egui::Window::new("Recording Encryption")
+            .open(&mut state.open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "Recordings are encrypted once they finish, and decrypted as they're previewed, uploaded or exported. \
+                         Only the video and inputs are encrypted; the details shown in the recordings list are not.",
+                    )
+                    .small()
+                    .color(theme::palette(ctx).muted),
+                );
+                ui.add_space(6.0);
+
+                let key_source = state.key_source.get_or_insert(encryption.key_source);
+                ui.horizontal(|ui| {
+                    ui.radio_value(key_source, EncryptionKeySource::WindowsAccount, "Windows account");
+                    ui.radio_value(key_source, EncryptionKeySource::Passphrase, "Passphrase");
+                    tooltip(
+                        ui,
+                        "With your Windows account, a key is made that only you can read on this computer. With a passphrase, anyone who knows it can read the recordings, including on another computer. Either way, recordings are uploaded without asking for anything.",
+                        None,
+                    );
+                });
+
+                let key_source = *key_source;
+                match key_source {
+                    EncryptionKeySource::WindowsAccount => {
+                        let text = if encryption.secrets.is_empty() { "Create Key" } else { "Create New Key" };
+                        if ui.button(text).clicked() {
+                            state.error =
+                                add_secret(encryption, key_source, &crate::encryption::generate_secret())
+                                    .err();
+                        }
+                    }
+                    EncryptionKeySource::Passphrase => {
+                        egui::Grid::new("encryption_passphrase")
+                            .num_columns(2)
+                            .spacing([12.0, 6.0])
+                            .show(ui, |ui| {
+                                let label = ui.label("Passphrase:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut state.passphrase)
+                                        .password(true)
+                                        .desired_width(220.0),
+                                )
+                                .labelled_by(label.id);
+                                ui.end_row();
+                                let label = ui.label("Confirm:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut state.passphrase_confirmation)
+                                        .password(true)
+                                        .desired_width(220.0),
+                                )
+                                .labelled_by(label.id);
+                                ui.end_row();
+                            });
+                        let passphrase = state.passphrase.trim();
+                        let problem = if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+                            Some(format!("Use at least {MIN_PASSPHRASE_LEN} characters."))
+                        } else if passphrase != state.passphrase_confirmation.trim() {
+                            Some("The passphrases don't match.".to_string())
+                        } else {
+                            None
+                        };
+                        if ui
+                            .add_enabled(problem.is_none(), egui::Button::new("Use Passphrase"))
+                            .on_disabled_hover_text(problem.unwrap_or_default())
+                            .clicked()
+                        {
+                            state.error = add_secret(encryption, key_source, passphrase).err();
+                            state.passphrase.clear();
+                            state.passphrase_confirmation.clear();
+                        }
+                    }
+                }
+                if !encryption.secrets.is_empty() {
+                    ui.label(
+                        egui::RichText::new(
+                            "A key is set. Setting another only affects recordings made from then on; the old key is kept for the rest.",
+                        )
+                        .small()
+                        .color(theme::palette(ctx).muted),
+                    );
+                }
+                if let Some(error) = &state.error {
+                    ui.label(egui::RichText::new(error).color(theme::palette(ctx).error));
+                }
+
+                ui.add_space(6.0);
+                ui.add_enabled(
+                    !encryption.secrets.is_empty(),
+                    egui::Checkbox::new(&mut encryption.enabled, "Encrypt new recordings"),
+                );
+                ui.label(
+                    egui::RichText::new(
+                        "This costs some performance: each recording is read and written again once it finishes (a few seconds per GB), \
+                         and previews and uploads take a little longer. While a recording is uploading, the archive being sent isn't \
+                         encrypted; it's deleted once the upload finishes.",
+                    )
+                    .small()
+                    .color(theme::palette(ctx).warning),
+                );
+            });
+    }
+}
+
+/// Makes `secret` the one new recordings are encrypted with, keeping the old ones to read the recordings made with them
+fn add_secret(
+    encryption: &mut RecordingEncryption,
+    key_source: EncryptionKeySource,
+    secret: &str,
+) -> Result<(), String> {
+    match ProtectedSecret::protect(secret) {
+        Ok(secret) => {
+            encryption.secrets.insert(0, secret);
+            encryption.key_source = key_source;
+            Ok(())
+        }
+        Err(e) => {
+            tracing::error!(e=?e, "Failed to protect the recording encryption key");
+            Err(format!("Couldn't keep the key safely: {e:#}"))
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    app_state::{AsyncRequest, RecordingStatus},
+    record,
+    ui::{
+        MainApp,
+        i18n::{tr, tr_args},
+        theme, util,
+    },
+};
+
+impl MainApp {
+    /// The small always-on-top window shown in place of everything else while recording, if the user
+    /// asked for it. Dragging anywhere that isn't a button moves the window.
+    pub fn hud_view(&mut self, ctx: &egui::Context) {
+        let recording_status = self.app_state.state.read().unwrap().clone();
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let background =
+                ui.interact(ui.max_rect(), ui.id().with("hud_drag"), egui::Sense::drag());
+            if background.drag_started() {
+                self.hud_drag_requested = true;
+            }
+
+            ui.horizontal(|ui| {
+                let text = match &recording_status {
+                    RecordingStatus::Recording {
+                        start_time,
+                        game_exe,
+                        paused_at,
+                        ..
+                    } => {
+                        // blinking red dot while recording, in step with the repaints below
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::Vec2::splat(10.0), egui::Sense::hover());
+                        if paused_at.is_none() && start_time.elapsed().as_secs() % 2 == 0 {
+                            ui.painter().circle_filled(
+                                rect.center(),
+                                5.0,
+                                theme::palette(ctx).error,
+                            );
+                        }
+
+                        let elapsed = paused_at
+                            .unwrap_or_else(Instant::now)
+                            .duration_since(*start_time);
+                        ui.vertical(|ui| {
+                            ui.label(egui::RichText::new(game_exe).strong());
+                            ui.label(tr_args(
+                                "hud.elapsed",
+                                &[
+                                    ("time", &util::format_seconds(elapsed.as_secs())),
+                                    (
+                                        "size",
+                                        &record::estimated_recording_size_mb(
+                                            elapsed,
+                                            self.local_preferences.encoder.bitrate,
+                                        ),
+                                    ),
+                                ],
+                            ));
+                        });
+                        None
+                    }
+                    RecordingStatus::CountingDown { starts_at } => Some(tr_args(
+                        "hud.countdown",
+                        &[("seconds", &util::countdown_seconds(*starts_at))],
+                    )),
+                    RecordingStatus::Paused {
+                        auto_stop_at: Some(auto_stop_at),
+                    } => Some(tr_args(
+                        "hud.idle_stopping",
+                        &[(
+                            "seconds",
+                            &auto_stop_at
+                                .saturating_duration_since(Instant::now())
+                                .as_secs(),
+                        )],
+                    )),
+                    RecordingStatus::Paused { auto_stop_at: None } | RecordingStatus::Stopped => {
+                        Some(tr("hud.idle").to_string())
+                    }
+                };
+                if let Some(text) = text {
+                    ui.label(egui::RichText::new(text).color(theme::palette(ctx).muted));
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(tr("hud.stop")).clicked() {
+                        self.app_state
+                            .async_request_tx
+                            .blocking_send(AsyncRequest::StopRecording)
+                            .ok();
+                    }
+                    if let RecordingStatus::Recording { paused_at, .. } = &recording_status
+                        && ui
+                            .button(if paused_at.is_some() {
+                                tr("main.resume")
+                            } else {
+                                tr("main.pause")
+                            })
+                            .clicked()
+                    {
+                        self.app_state
+                            .async_request_tx
+                            .blocking_send(AsyncRequest::TogglePauseRecording)
+                            .ok();
+                    }
+                });
+            });
+        });
+
+        // Keep the clock ticking
+        ctx.request_repaint_after(Duration::from_secs(1));
+    }
+}
@@ -1,103 +1,454 @@
-use crate::{app_state::AsyncRequest, ui::MainApp};
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use crate::{
+    api::{
+        ApiKeyFormatError, ApiKeyValidationError, MAX_API_KEY_LENGTH, check_api_key_format,
+        check_api_key_length, sanitize_api_key,
+    },
+    app_state::AsyncRequest,
+    ui::{
+        MainApp,
+        i18n::{tr, tr_args},
+        theme,
+    },
+};
+
+/// Room for three lines of the message shown above the Continue button
+const MESSAGE_HEIGHT: f32 = 48.0;
+/// Most that can be typed or pasted into the key field, so that pasting something huge by mistake can't bog
+/// the UI down. Anything longer than a key could be is turned away well before this anyway.
+const MAX_KEY_INPUT_LENGTH: usize = 8 * MAX_API_KEY_LENGTH;
 
 impl MainApp {
     pub fn login_view(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
-            // Center the content vertically and horizontally
-            ui.vertical_centered(|ui| {
-                // Extremely ugly bodge. I assume there's a way to do this correctly, but I can't find it at a glance.
-                let content_height = 240.0;
-                let available_height = ui.available_height();
-                ui.add_space((available_height - content_height) / 2.0);
-
-                ui.set_max_width(ui.available_width() * 0.8);
-                ui.vertical_centered(|ui| {
-                    // Logo/Icon area (placeholder for now)
-                    ui.add_space(20.0);
-
-                    // Main heading with better styling
-                    ui.heading(
-                        egui::RichText::new("Welcome to OWL Control")
-                            .size(28.0)
-                            .strong()
-                            .color(egui::Color32::from_rgb(220, 220, 220)),
-                    );
-
-                    ui.add_space(8.0);
-
-                    // Subtitle
-                    ui.label(
-                        egui::RichText::new("Please enter your API key to continue")
-                            .size(16.0)
-                            .color(egui::Color32::from_rgb(180, 180, 180)),
-                    );
-
-                    ui.add_space(20.0);
-
-                    // API Key input section
+            // Taken out here, as there's no end to the space within a scroll area
+            let available_height = ui.available_height();
+
+            // Scrolls instead of cutting the content off if the window is too short for it
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    // Center the content vertically and horizontally. Its height is only known once it's been laid out
+                    // (and changes with the scale and with errors coming and going), so the height it had last time is
+                    // used, and the frame is laid out again whenever that turns out to be wrong.
                     ui.vertical_centered(|ui| {
-                        // Styled text input
-                        let text_edit = egui::TextEdit::singleline(&mut self.login_api_key)
-                            .desired_width(ui.available_width())
-                            .vertical_align(egui::Align::Center)
-                            .hint_text("sk_...");
+                        let content_height_id = ui.id().with("login_content_height");
+                        let content_height = ui.data(|d| d.get_temp::<f32>(content_height_id));
+                        ui.add_space(
+                            ((available_height - content_height.unwrap_or(available_height)) / 2.0)
+                                .max(0.0),
+                        );
 
-                        ui.add_sized(egui::vec2(ui.available_width(), 40.0), text_edit);
+                        ui.set_max_width(ui.available_width() * 0.8);
+                        let content = ui.vertical_centered(|ui| {
+                            // Logo/Icon area (placeholder for now)
+                            ui.add_space(20.0);
 
-                        ui.add_space(10.0);
+                            // Main heading with better styling
+                            ui.heading(
+                                egui::RichText::new(tr("login.welcome"))
+                                    .size(28.0)
+                                    .strong()
+                                    .color(theme::palette(ui.ctx()).heading),
+                            );
+
+                            ui.add_space(8.0);
+
+                            // Subtitle, which doubles as the key field's accessible label
+                            let subtitle = ui.label(
+                                egui::RichText::new(tr("login.enter_key"))
+                                    .size(16.0)
+                                    .color(theme::palette(ui.ctx()).subheading),
+                            );
+
+                            ui.add_space(20.0);
+
+                            // API Key input section
+                            ui.vertical_centered(|ui| {
+                        // Styled text input, masked unless the user asks to see it
+                        let (enter_pressed, tab_pressed) = ui
+                            .horizontal(|ui| {
+                                let toggle_size = egui::vec2(40.0, 40.0);
+                                let text_edit_width = ui.available_width()
+                                    - 2.0 * (toggle_size.x + ui.spacing().item_spacing.x);
+
+                                let text_edit = egui::TextEdit::singleline(&mut self.login_api_key)
+                                    .desired_width(text_edit_width)
+                                    .vertical_align(egui::Align::Center)
+                                    .password(!self.login_api_key_visible)
+                                    .char_limit(MAX_KEY_INPUT_LENGTH)
+                                    .hint_text("sk_...");
+                                let text_edit_response = ui
+                                    .add_sized(egui::vec2(text_edit_width, 40.0), text_edit)
+                                    .labelled_by(subtitle.id);
+                                if std::mem::take(&mut self.focus_login_api_key) {
+                                    text_edit_response.request_focus();
+                                }
+                                if text_edit_response.changed() {
+                                    self.check_login_api_key_length();
+                                }
+                                // Singleline text edits lose focus when Enter is pressed
+                                let enter_pressed = text_edit_response.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                // Tab goes straight to the Continue button; the buttons in between are still
+                                // reachable with Shift+Tab from there
+                                let tab_pressed = text_edit_response.has_focus()
+                                    && ui.input(|i| {
+                                        i.key_pressed(egui::Key::Tab) && !i.modifiers.shift
+                                    });
+
+                                let toggle = ui
+                                    .add_sized(
+                                        toggle_size,
+                                        egui::Button::new("👁").selected(self.login_api_key_visible),
+                                    )
+                                    .widget_info(|| {
+                                        egui::WidgetInfo::selected(
+                                            egui::WidgetType::Button,
+                                            true,
+                                            self.login_api_key_visible,
+                                            tr("login.show_key"),
+                                        )
+                                    })
+                                    .on_hover_text(if self.login_api_key_visible {
+                                        tr("login.hide_key")
+                                    } else {
+                                        tr("login.show_key")
+                                    });
+                                if toggle.clicked() {
+                                    self.login_api_key_visible = !self.login_api_key_visible;
+                                }
+
+                                if ui
+                                    .add_sized(toggle_size, egui::Button::new("📋"))
+                                    .widget_info(|| {
+                                        egui::WidgetInfo::labeled(
+                                            egui::WidgetType::Button,
+                                            true,
+                                            tr("login.paste_key"),
+                                        )
+                                    })
+                                    .on_hover_text(tr("login.paste_key_tooltip"))
+                                    .clicked()
+                                {
+                                    match arboard::Clipboard::new().and_then(|mut c| c.get_text()) {
+                                        Ok(text) => {
+                                            let key = sanitize_api_key(&text);
+                                            // Whatever was there before is kept, rather than filling the
+                                            // field with something that can't be a key
+                                            self.authenticated_user_id = match check_api_key_length(&key) {
+                                                Ok(()) => {
+                                                    self.login_api_key = key;
+                                                    None
+                                                }
+                                                Err(e) => Some(Err(ApiKeyValidationError::Malformed(e))),
+                                            };
+                                        }
+                                        Err(e) => {
+                                            tracing::warn!(e=?e, "Failed to read clipboard");
+                                            self.authenticated_user_id =
+                                                Some(Err(ApiKeyValidationError::Other(
+                                                    tr("login.clipboard_error").into(),
+                                                )));
+                                        }
+                                    }
+                                }
+
+                                (enter_pressed, tab_pressed)
+                            })
+                            .inner;
+
+                        ui.add_space(6.0);
+
+                        ui.checkbox(
+                            &mut self.local_credentials.remember_me,
+                            egui::RichText::new(tr("login.remember_key")).size(12.0),
+                        );
+
+                        ui.add_space(6.0);
 
                         // Help text
                         ui.horizontal(|ui| {
                             ui.spacing_mut().item_spacing = egui::vec2(0.0, 0.0);
                             ui.label(
-                                egui::RichText::new("Don't have an API key? Please sign up at ")
+                                egui::RichText::new(tr("login.sign_up"))
                                     .size(12.0)
-                                    .color(egui::Color32::from_rgb(140, 140, 140)),
+                                    .color(theme::palette(ui.ctx()).muted),
                             );
                             ui.hyperlink_to(
-                                egui::RichText::new("our website.").size(12.0),
+                                egui::RichText::new(tr("login.sign_up_link")).size(12.0),
                                 "https://wayfarerlabs.ai/handler/sign-in",
                             );
                         });
                         ui.add_space(10.0);
 
-                        if let Some(Err(err)) = &self.authenticated_user_id {
-                            ui.label(
-                                egui::RichText::new(err)
-                                    .size(12.0)
-                                    .color(egui::Color32::from_rgb(255, 0, 0)),
-                            );
-                            ui.add_space(10.0);
-                        }
+                        // The message always gets the same room, whether or not there is one, so that nothing
+                        // moves when it comes and goes
+                        let (message_rect, _) = ui.allocate_exact_size(
+                            egui::vec2(ui.available_width(), MESSAGE_HEIGHT),
+                            egui::Sense::hover(),
+                        );
+                        let rate_limited_for = self.rate_limited_for();
+                        ui.scope_builder(
+                            egui::UiBuilder::new()
+                                .max_rect(message_rect)
+                                .layout(egui::Layout::top_down(egui::Align::Center)),
+                            |ui| {
+                                // Anything longer than there's room for can still be read by hovering over it
+                                ui.set_clip_rect(message_rect.intersect(ui.clip_rect()));
+                                if let Some(wait) = rate_limited_for {
+                                    ui.label(
+                                        egui::RichText::new(if self.is_authenticating_login_api_key {
+                                            tr_args(
+                                                "login.rate_limited_retrying",
+                                                &[("seconds", &(wait.as_secs() + 1))],
+                                            )
+                                        } else {
+                                            tr_args(
+                                                "login.rate_limited_wait",
+                                                &[("seconds", &(wait.as_secs() + 1))],
+                                            )
+                                        })
+                                        .size(12.0)
+                                        .color(theme::palette(ui.ctx()).warning),
+                                    );
+                                    // Keep the countdown ticking
+                                    ui.ctx().request_repaint_after(Duration::from_millis(250));
+                                } else if let Some(Err(err)) = &self.authenticated_user_id
+                                    // Once the wait is over there's nothing more to say than that it can be retried
+                                    && !matches!(err, ApiKeyValidationError::RateLimited(Some(_)))
+                                {
+                                    // Problems on our end or the server's aren't the user's fault, so don't show them as errors
+                                    let color = if err.is_transient() {
+                                        theme::palette(ui.ctx()).warning
+                                    } else {
+                                        theme::palette(ui.ctx()).error
+                                    };
+                                    let message = match err {
+                                        ApiKeyValidationError::Malformed(e) => {
+                                            format_error_text(*e).to_string()
+                                        }
+                                        err => err.to_string(),
+                                    };
+                                    ui.label(egui::RichText::new(&message).size(12.0).color(color))
+                                        .on_hover_text(message);
+                                }
+                            },
+                        );
+                        ui.add_space(10.0);
 
-                        // Submit button
-                        ui.add_enabled_ui(!self.is_authenticating_login_api_key, |ui| {
+                        // Submit button, which becomes a retry button if the key couldn't be checked
+                        let can_retry =
+                            matches!(&self.authenticated_user_id, Some(Err(e)) if e.is_transient());
+                        let enabled =
+                            !self.is_authenticating_login_api_key && rate_limited_for.is_none();
+                        ui.add_enabled_ui(enabled, |ui| {
                             let submit_button = ui.add_sized(
                                 egui::vec2(120.0, 36.0),
                                 egui::Button::new(
                                     egui::RichText::new(if self.is_authenticating_login_api_key {
-                                        "Validating..."
+                                        tr("login.validating").to_string()
+                                    } else if let Some(wait) = rate_limited_for {
+                                        tr_args("login.wait", &[("seconds", &(wait.as_secs() + 1))])
+                                    } else if can_retry {
+                                        tr("login.retry").to_string()
                                     } else {
-                                        "Continue"
+                                        tr("login.continue").to_string()
                                     })
                                     .size(16.0)
                                     .strong(),
                                 ),
                             );
 
-                            if submit_button.clicked() && !self.is_authenticating_login_api_key {
-                                self.is_authenticating_login_api_key = true;
-                                self.app_state
-                                    .async_request_tx
-                                    .blocking_send(AsyncRequest::ValidateApiKey {
-                                        api_key: self.login_api_key.clone(),
-                                    })
-                                    .ok();
+                            if tab_pressed {
+                                submit_button.request_focus();
+                            }
+
+                            if submit_button.clicked() || enter_pressed {
+                                self.submit_login_api_key();
                             }
                         });
+
+                        // Not being able to reach us shouldn't stop anyone from recording
+                        if can_retry && !self.is_authenticating_login_api_key {
+                            ui.add_space(6.0);
+                            if ui
+                                .add_sized(
+                                    egui::vec2(120.0, 28.0),
+                                    egui::Button::new(
+                                        egui::RichText::new(tr("login.record_offline")).size(13.0),
+                                    ),
+                                )
+                                .on_hover_text(tr("login.record_offline_tooltip"))
+                                .clicked()
+                            {
+                                self.app_state.offline.store(true, Ordering::Relaxed);
+                                self.accept_login_api_key();
+                            }
+                        } else {
+                            // Keeps the content the same height, so that it doesn't move when the button appears
+                            ui.add_space(6.0 + 28.0);
+                        }
+                    });
+                        });
+
+                        let measured_height = content.response.rect.height();
+                        if content_height
+                            .is_none_or(|height| (height - measured_height).abs() > 0.5)
+                        {
+                            ui.data_mut(|d| d.insert_temp(content_height_id, measured_height));
+                            ui.ctx().request_discard("login content height changed");
+                        }
                     });
                 });
-            });
         });
     }
+
+    /// How much longer the server has asked us to wait before checking the API key again, if at all
+    fn rate_limited_for(&self) -> Option<Duration> {
+        self.login_rate_limited_until
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+            .filter(|wait| !wait.is_zero())
+    }
+
+    /// Says straight away if the entered API key is too long to be one, and stops saying so once it isn't.
+    /// Whatever else might be wrong with it waits until it's submitted, as it may just not be finished yet.
+    fn check_login_api_key_length(&mut self) {
+        match check_api_key_length(&sanitize_api_key(&self.login_api_key)) {
+            Err(e) => {
+                self.authenticated_user_id = Some(Err(ApiKeyValidationError::Malformed(e)));
+            }
+            Ok(()) => {
+                if matches!(
+                    &self.authenticated_user_id,
+                    Some(Err(ApiKeyValidationError::Malformed(
+                        ApiKeyFormatError::TooLong
+                    )))
+                ) {
+                    self.authenticated_user_id = None;
+                }
+            }
+        }
+    }
+
+    /// Sends the entered API key off for validation. Shared by the Continue button and the Enter key;
+    /// does nothing if a validation is already in flight, or the server has asked us to wait.
+    fn submit_login_api_key(&mut self) {
+        if self.is_authenticating_login_api_key || self.rate_limited_for().is_some() {
+            return;
+        }
+
+        self.login_api_key = sanitize_api_key(&self.login_api_key);
+        if let Err(e) = check_api_key_format(&self.login_api_key) {
+            // No point asking the server about something that can't be a key
+            self.authenticated_user_id = Some(Err(ApiKeyValidationError::Malformed(e)));
+            return;
+        }
+
+        if let Err(e) = queue_validation(
+            &mut self.is_authenticating_login_api_key,
+            &self.app_state.async_request_tx,
+            &self.login_api_key,
+        ) {
+            self.authenticated_user_id = Some(Err(e));
+        }
+    }
+}
+
+/// What's wrong with a key that can't be one, in the user's language
+fn format_error_text(e: ApiKeyFormatError) -> &'static str {
+    tr(match e {
+        ApiKeyFormatError::Empty => "login.key_empty",
+        ApiKeyFormatError::TooLong => "login.key_too_long",
+        ApiKeyFormatError::NotAscii => "login.key_not_ascii",
+        ApiKeyFormatError::MissingPrefix => "login.key_missing_prefix",
+        ApiKeyFormatError::InvalidCharacters => "login.key_invalid_characters",
+        ApiKeyFormatError::TooShort => "login.key_too_short",
+    })
+}
+
+/// Asks for `api_key` to be validated, unless a validation is already in flight. `is_authenticating` is set
+/// before the request is sent, so that another click can't get one in before it is; it's cleared again once
+/// the result comes back, or straight away if the request couldn't be sent.
+///
+/// Never waits for room in the queue, as that would freeze the UI if the background task was stuck.
+fn queue_validation(
+    is_authenticating: &mut bool,
+    async_request_tx: &mpsc::Sender<AsyncRequest>,
+    api_key: &str,
+) -> Result<(), ApiKeyValidationError> {
+    if std::mem::replace(is_authenticating, true) {
+        return Ok(());
+    }
+    let result = async_request_tx.try_send(AsyncRequest::ValidateApiKey {
+        api_key: api_key.to_string(),
+    });
+    if result.is_err() {
+        *is_authenticating = false;
+    }
+    match result {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(_)) => {
+            tracing::warn!("Request queue is full, API key validation not queued");
+            Err(ApiKeyValidationError::Other(tr("login.busy").into()))
+        }
+        Err(TrySendError::Closed(_)) => {
+            tracing::error!("Request queue is closed, API key can't be validated");
+            Err(ApiKeyValidationError::Other(
+                tr("login.background_stopped").into(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rapid_clicks_queue_one_validation() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let mut is_authenticating = false;
+        for _ in 0..5 {
+            assert!(queue_validation(&mut is_authenticating, &tx, "sk_test").is_ok());
+        }
+        assert!(is_authenticating);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AsyncRequest::ValidateApiKey { api_key }) if api_key == "sk_test"
+        ));
+        assert!(rx.try_recv().is_err());
+
+        // Once the result is back, the key can be checked again
+        is_authenticating = false;
+        assert!(queue_validation(&mut is_authenticating, &tx, "sk_test").is_ok());
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_validation_not_stuck_if_it_cant_be_queued() {
+        // Full, e.g. because the background task is stuck
+        let (tx, rx) = mpsc::channel(1);
+        tx.try_send(AsyncRequest::LoadUploadStats).ok();
+        let mut is_authenticating = false;
+        assert!(matches!(
+            queue_validation(&mut is_authenticating, &tx, "sk_test"),
+            Err(ApiKeyValidationError::Other(message)) if message == tr("login.busy")
+        ));
+        assert!(!is_authenticating);
+
+        // Closed, because the background task is gone
+        drop(rx);
+        assert!(matches!(
+            queue_validation(&mut is_authenticating, &tx, "sk_test"),
+            Err(ApiKeyValidationError::Other(message)) if message == tr("login.background_stopped")
+        ));
+        assert!(!is_authenticating);
+    }
 }
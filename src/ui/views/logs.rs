@@ -0,0 +1,171 @@
+use crate::{
+    app_state::AsyncRequest,
+    logging,
+    ui::{MainApp, theme},
+};
+
+const LEVELS: [tracing::Level; 4] = [
+    tracing::Level::ERROR,
+    tracing::Level::WARN,
+    tracing::Level::INFO,
+    tracing::Level::DEBUG,
+];
+
+pub(crate) struct LogsViewState {
+    /// Whether the log viewer window is open
+    pub(super) open: bool,
+    /// Lines less severe than this are hidden
+    min_level: tracing::Level,
+    /// Whether the problem report section is expanded
+    pub(super) report_open: bool,
+    include_latest_recording: bool,
+    is_creating_report: bool,
+    /// The reference id of the last report, or why it couldn't be created
+    report: Option<Result<String, String>>,
+}
+impl Default for LogsViewState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            min_level: tracing::Level::INFO,
+            report_open: false,
+            include_latest_recording: true,
+            is_creating_report: false,
+            report: None,
+        }
+    }
+}
+impl LogsViewState {
+    pub(crate) fn report_created(&mut self, result: Result<String, String>) {
+        self.is_creating_report = false;
+        self.report = Some(result);
+    }
+}
+
+impl MainApp {
+    pub(super) fn logs_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.logs_view_state;
+        let app_state = &self.app_state;
+        egui::Window::new("Logs")
+            .open(&mut state.open)
+            .default_size(egui::vec2(700.0, 400.0))
+            .show(ctx, |ui| {
+                let lines = logging::recent_lines(state.min_level);
+
+                ui.horizontal(|ui| {
+                    ui.label("Show:");
+                    egui::ComboBox::from_id_salt("log_level")
+                        .selected_text(level_name(state.min_level))
+                        .show_ui(ui, |ui| {
+                            for level in LEVELS {
+                                ui.selectable_value(&mut state.min_level, level, level_name(level));
+                            }
+                        });
+
+                    if ui.button("Copy to Clipboard").clicked() {
+                        let mut text = logging::header();
+                        for line in &lines {
+                            text.push('\n');
+                            text.push_str(&line.text);
+                        }
+                        ui.ctx().copy_text(text);
+                    }
+                    if ui.button("Open Log Folder").clicked() {
+                        app_state
+                            .async_request_tx
+                            .blocking_send(AsyncRequest::OpenLog)
+                            .ok();
+                    }
+                });
+                ui.label(
+                    egui::RichText::new(
+                        "Only this session is shown here; the log folder has earlier ones too. \
+                         Please include the log when reporting a problem.",
+                    )
+                    .size(12.0)
+                    .color(theme::palette(ui.ctx()).muted),
+                );
+                egui::CollapsingHeader::new("Report a Problem")
+                    .open(state.report_open.then_some(true))
+                    .show(ui, |ui| {
+                        ui.label(
+                            "Saves the logs and your settings into a single file you can attach to a bug report. \
+                             Your API key, any proxy password and your user folder are removed first.",
+                        );
+                        ui.checkbox(
+                            &mut state.include_latest_recording,
+                            "Include details of the latest recording (not the video or inputs)",
+                        );
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    !state.is_creating_report,
+                                    egui::Button::new(if state.is_creating_report {
+                                        "Creating..."
+                                    } else {
+                                        "Create Report"
+                                    }),
+                                )
+                                .clicked()
+                            {
+                                state.is_creating_report = true;
+                                state.report = None;
+                                app_state
+                                    .async_request_tx
+                                    .blocking_send(AsyncRequest::CreateProblemReport {
+                                        include_latest_recording: state.include_latest_recording,
+                                    })
+                                    .ok();
+                            }
+                            match &state.report {
+                                Some(Ok(reference_id)) => {
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "Saved as report {reference_id}. Please attach it and mention the reference."
+                                        ))
+                                        .color(theme::palette(ui.ctx()).success),
+                                    );
+                                }
+                                Some(Err(e)) => {
+                                    ui.label(
+                                        egui::RichText::new(format!("Couldn't create the report: {e}"))
+                                            .color(theme::palette(ui.ctx()).error),
+                                    );
+                                }
+                                None => {}
+                            }
+                        });
+                    });
+                // Only forced open when asked for; afterwards the user can collapse it again
+                state.report_open = false;
+                ui.separator();
+
+                let palette = theme::palette(ui.ctx());
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                egui::ScrollArea::both()
+                    .auto_shrink(false)
+                    .stick_to_bottom(true)
+                    .show_rows(ui, row_height, lines.len(), |ui, rows| {
+                        for line in &lines[rows] {
+                            let text = egui::RichText::new(&line.text).monospace();
+                            let text = match line.level {
+                                tracing::Level::ERROR => text.color(palette.error),
+                                tracing::Level::WARN => text.color(palette.warning),
+                                tracing::Level::INFO => text,
+                                _ => text.color(palette.muted),
+                            };
+                            ui.add(egui::Label::new(text).extend());
+                        }
+                    });
+            });
+    }
+}
+
+fn level_name(level: tracing::Level) -> &'static str {
+    match level {
+        tracing::Level::ERROR => "Errors",
+        tracing::Level::WARN => "Warnings and above",
+        tracing::Level::INFO => "Info and above",
+        _ => "Everything",
+    }
+}
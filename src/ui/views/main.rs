@@ -1,64 +1,102 @@
-use std::time::{Duration, Instant};
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    api::{UserUpload, UserUploadStatistics},
-    app_state::{AsyncRequest, GitHubRelease},
-    config::{EncoderSettings, FfmpegNvencSettings, ObsX264Settings, RecordingBackend},
-    ui::{HotkeyRebindTarget, MainApp, util},
-    upload::LocalRecording,
+    api::{ConnectionTest, UserInfo, UserUpload, UserUploadStatistics},
+    app_state::{AsyncRequest, GitHubRelease, RecordingStatus, ServerContact, UncleanExit},
+    config::{RecordingBackend, UploadedRecordingRetention},
+    output_types::SkippedFrames,
+    record::PreviewFrame,
+    system::capture_permission::{self, CaptureDenied},
+    ui::{
+        MainApp,
+        i18n::{tr, tr_args},
+        theme, util,
+        views::settings::{SETTINGS_TEXT_HEIGHT, add_settings_text, add_settings_ui},
+    },
+    upload::{LocalRecording, QueuedUpload, UploadState},
 };
 
-use constants::encoding::{SUPPORTED_VIDEO_ENCODERS, VideoEncoderType};
 use constants::{GH_ORG, GH_REPO};
 
 #[derive(Default)]
 pub(crate) struct MainViewState {
     last_obs_check: Option<(std::time::Instant, bool)>,
+    last_refresh_rate_check: Option<(std::time::Instant, Option<u32>)>,
+    /// `Some(None)` while a connection test is running
+    connection_test: Option<Option<ConnectionTest>>,
+    /// The name being typed in for a new profile, while one is being added
+    new_profile_name: Option<String>,
+    /// Why the last change to the profiles didn't work
+    profile_error: Option<String>,
+    /// The recording preview, and when the frame in it was captured
+    preview: Option<(Instant, egui::TextureHandle)>,
+    /// When the preview started showing nothing but black, if it has
+    preview_blank_since: Option<Instant>,
+    /// Why the last recording couldn't be started, until the user dismisses it or one starts
+    recording_start_error: Option<String>,
 }
+impl MainViewState {
+    pub(crate) fn connection_tested(&mut self, result: ConnectionTest) {
+        self.connection_test = Some(Some(result));
+    }
 
-impl MainApp {
-    pub fn main_view(&mut self, ctx: &egui::Context) {
-        const SETTINGS_TEXT_WIDTH: f32 = 150.0;
-        const SETTINGS_TEXT_HEIGHT: f32 = 20.0;
-
-        fn add_settings_text(ui: &mut egui::Ui, widget: impl egui::Widget) -> egui::Response {
-            ui.allocate_ui_with_layout(
-                egui::vec2(SETTINGS_TEXT_WIDTH, SETTINGS_TEXT_HEIGHT),
-                egui::Layout {
-                    main_dir: egui::Direction::LeftToRight,
-                    main_wrap: false,
-                    main_align: egui::Align::RIGHT,
-                    main_justify: true,
-                    cross_align: egui::Align::Center,
-                    cross_justify: true,
-                },
-                |ui| ui.add(widget),
-            )
-            .inner
-        }
-
-        fn add_settings_ui<R>(
-            ui: &mut egui::Ui,
-            add_contents: impl FnOnce(&mut egui::Ui) -> R,
-        ) -> egui::InnerResponse<R> {
-            ui.allocate_ui_with_layout(
-                egui::vec2(ui.available_width(), SETTINGS_TEXT_HEIGHT),
-                egui::Layout {
-                    main_dir: egui::Direction::LeftToRight,
-                    main_wrap: false,
-                    main_align: egui::Align::LEFT,
-                    main_justify: true,
-                    cross_align: egui::Align::Center,
-                    cross_justify: true,
-                },
-                add_contents,
-            )
-        }
+    pub(crate) fn recording_started(&mut self, result: Result<(), String>) {
+        self.recording_start_error = result.err();
+    }
 
-        fn add_settings_widget(ui: &mut egui::Ui, widget: impl egui::Widget) -> egui::Response {
-            add_settings_ui(ui, |ui| ui.add(widget)).inner
+    /// The preview of the recording, uploaded again whenever there's a new frame, and whether it's been
+    /// blank for long enough to be worth mentioning (games often show black for a moment, e.g. while loading)
+    fn preview(
+        &mut self,
+        ctx: &egui::Context,
+        frame: Option<&PreviewFrame>,
+    ) -> Option<(egui::TextureHandle, bool)> {
+        const BLANK_WARNING_DELAY: Duration = Duration::from_secs(3);
+
+        let Some(frame) = frame else {
+            self.preview = None;
+            self.preview_blank_since = None;
+            return None;
+        };
+        if self
+            .preview
+            .as_ref()
+            .is_none_or(|(captured_at, _)| *captured_at != frame.captured_at)
+        {
+            let image = egui::ColorImage::from_rgba_unmultiplied(
+                [frame.width as usize, frame.height as usize],
+                &frame.rgba,
+            );
+            match &mut self.preview {
+                Some((captured_at, texture)) => {
+                    texture.set(image, egui::TextureOptions::LINEAR);
+                    *captured_at = frame.captured_at;
+                }
+                None => {
+                    let texture =
+                        ctx.load_texture("recording_preview", image, egui::TextureOptions::LINEAR);
+                    self.preview = Some((frame.captured_at, texture));
+                }
+            }
+            self.preview_blank_since = match (frame.is_blank(), self.preview_blank_since) {
+                (true, since) => since.or(Some(frame.captured_at)),
+                (false, _) => None,
+            };
         }
+        let blank = self
+            .preview_blank_since
+            .is_some_and(|since| since.elapsed() > BLANK_WARNING_DELAY);
+        self.preview
+            .as_ref()
+            .map(|(_, texture)| (texture.clone(), blank))
+    }
+}
 
+impl MainApp {
+    pub fn main_view(&mut self, ctx: &egui::Context) {
         if self.main_view_state.last_obs_check.is_none()
             || self
                 .main_view_state
@@ -68,10 +106,28 @@ impl MainApp {
             self.main_view_state.last_obs_check = Some((Instant::now(), is_obs_running()));
         }
 
+        // The monitor setup rarely changes, so there's no need to check it as often
+        if self
+            .main_view_state
+            .last_refresh_rate_check
+            .is_none_or(|(last, _)| last.elapsed() > Duration::from_secs(5))
+        {
+            self.main_view_state.last_refresh_rate_check = Some((
+                Instant::now(),
+                crate::system::hardware_specs::get_primary_monitor_refresh_rate(),
+            ));
+        }
+        let refresh_rate = self
+            .main_view_state
+            .last_refresh_rate_check
+            .and_then(|(_, refresh_rate)| refresh_rate);
+
         egui::CentralPanel::default().show(ctx, |ui| {
             // Show new release warning if available
             if let Some(release) = &self.newer_release_available {
-                newer_release_available(ui, release);
+                if newer_release_available(ui, release, &mut self.md_cache) {
+                    self.newer_release_available = None;
+                }
 
                 ui.add_space(15.0);
             }
@@ -88,14 +144,119 @@ impl MainApp {
                 ui.add_space(15.0);
             }
 
+            let unclean_exit = self.app_state.unclean_exit.read().unwrap().clone();
+            if let Some(unclean_exit) = unclean_exit {
+                if let Some(request) = unclean_exit_notice(ui, &unclean_exit) {
+                    if let Some(request) = request {
+                        self.app_state.async_request_tx.blocking_send(request).ok();
+                    }
+                    *self.app_state.unclean_exit.write().unwrap() = None;
+                }
+
+                ui.add_space(15.0);
+            }
+
+            let min_client_version = self
+                .app_state
+                .user_info
+                .read()
+                .unwrap()
+                .as_ref()
+                .filter(|user_info| user_info.is_client_outdated())
+                .and_then(|user_info| user_info.min_client_version.clone());
+            if let Some(min_client_version) = min_client_version {
+                client_outdated_warning(ui, &min_client_version);
+
+                ui.add_space(15.0);
+            }
+
+            if self.app_state.offline.load(Ordering::Relaxed) {
+                offline_warning(ui);
+
+                ui.add_space(15.0);
+            }
+
+            if let Some(free_space_mb) = self.low_disk_space_mb {
+                low_disk_space_warning(ui, free_space_mb, self.local_preferences.min_free_space_mb);
+
+                ui.add_space(15.0);
+            }
+
+            let capture_denied = self.app_state.capture_denied.read().unwrap().clone();
+            if let Some(denied) = capture_denied {
+                if capture_denied_warning(ui, &denied) {
+                    self.app_state
+                        .async_request_tx
+                        .blocking_send(AsyncRequest::RecheckCapturePermission)
+                        .ok();
+                }
+
+                ui.add_space(15.0);
+            } else if let Some(error) = &self.main_view_state.recording_start_error {
+                // Not worth repeating when the banner above already says what's wrong
+                if recording_start_failed_warning(ui, error) {
+                    self.main_view_state.recording_start_error = None;
+                }
+
+                ui.add_space(15.0);
+            }
+
+            // Show the active recording, with pause controls
+            let recording_status = self.app_state.state.read().unwrap().clone();
+            if let RecordingStatus::Recording {
+                game_exe,
+                paused_at,
+                skipped_frames,
+                ..
+            } = recording_status
+            {
+                let preview = {
+                    let frame = self.app_state.preview_frame.read().unwrap();
+                    self.main_view_state.preview(ctx, frame.as_ref())
+                };
+                if active_recording(ui, &game_exe, paused_at.is_some(), skipped_frames, preview) {
+                    self.app_state
+                        .async_request_tx
+                        .blocking_send(AsyncRequest::TogglePauseRecording)
+                        .ok();
+                }
+
+                ui.add_space(15.0);
+            } else if let RecordingStatus::CountingDown { starts_at } = recording_status {
+                if countdown(ui, starts_at) {
+                    self.app_state
+                        .async_request_tx
+                        .blocking_send(AsyncRequest::StopRecording)
+                        .ok();
+                }
+                ctx.request_repaint_after(std::time::Duration::from_millis(250));
+
+                ui.add_space(15.0);
+            }
+
             egui::ScrollArea::vertical().show(ui, |ui| {
                 // Account Section
                 ui.group(|ui| {
-                    ui.label(egui::RichText::new("Account").size(18.0).strong());
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(tr("main.account")).size(18.0).strong());
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let offline = self.app_state.offline.load(Ordering::Relaxed);
+                            let contact = *self.app_state.server_contact.read().unwrap();
+                            if connection_status(ui, offline, contact) {
+                                self.app_state
+                                    .async_request_tx
+                                    .blocking_send(AsyncRequest::CheckConnection)
+                                    .ok();
+                            }
+                        });
+                    });
                     ui.separator();
 
+                    self.profile_picker(ui);
+                    ui.add_space(4.0);
+
                     ui.vertical(|ui| {
-                        ui.label("User ID:");
+                        ui.label(tr("main.user_id"));
                         ui.horizontal(|ui| {
                             ui.with_layout(
                                 egui::Layout::right_to_left(egui::Align::Center),
@@ -103,18 +264,26 @@ impl MainApp {
                                     if ui
                                         .add_sized(
                                             egui::vec2(0.0, SETTINGS_TEXT_HEIGHT),
-                                            egui::Button::new("Log out"),
+                                            egui::Button::new(tr("main.log_out")),
                                         )
                                         .clicked()
                                     {
-                                        self.go_to_login();
+                                        self.logout();
                                     }
 
                                     let user_id = self
                                         .authenticated_user_id
                                         .clone()
-                                        .unwrap_or_else(|| Ok("Authenticating...".to_string()))
-                                        .unwrap_or_else(|e| format!("Error: {e}"));
+                                        .unwrap_or_else(
+                                            || Ok(tr("main.authenticating").to_string()),
+                                        )
+                                        .unwrap_or_else(|e| {
+                                            if e.is_transient() {
+                                                tr("main.offline_pending_auth").to_string()
+                                            } else {
+                                                tr_args("main.error", &[("error", &e)])
+                                            }
+                                        });
                                     ui.add_sized(
                                         egui::vec2(ui.available_width(), SETTINGS_TEXT_HEIGHT),
                                         egui::TextEdit::singleline(&mut user_id.as_str()),
@@ -122,178 +291,33 @@ impl MainApp {
                                 },
                             );
                         });
-                    });
-                });
-                ui.add_space(10.0);
-
-                // Keyboard Shortcuts Section
-                ui.group(|ui| {
-                    ui.horizontal(|ui| {
-                        ui.label(
-                            egui::RichText::new("Keyboard Shortcuts")
-                                .size(18.0)
-                                .strong(),
-                        );
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            tooltip(ui, "Tip: You can set separate hotkeys for starting and stopping recording. By default, the start key will toggle recording.", None);
-                        });
-                    });
-                    ui.separator();
-
-                    ui.horizontal(|ui| {
-                        add_settings_text(ui, egui::Label::new(if self.local_preferences.stop_hotkey_enabled {
-                            "Start Recording:"
-                        } else {
-                            "Toggle Recording:"
-                        }));
-                        let button_text = if self.listening_for_hotkey_rebind
-                            == Some(HotkeyRebindTarget::Start)
-                        {
-                            "Press any key...".to_string()
-                        } else {
-                            self.local_preferences.start_recording_key.clone()
-                        };
 
-                        if add_settings_widget(ui, egui::Button::new(button_text)).clicked() {
-                            self.listening_for_hotkey_rebind = Some(HotkeyRebindTarget::Start);
+                        let user_info = self.app_state.user_info.read().unwrap().clone();
+                        if let Some(user_info) = user_info {
+                            ui.add_space(4.0);
+                            account_details(ui, &user_info);
                         }
                     });
-
-                    let stop_hotkey_enabled = self.local_preferences.stop_hotkey_enabled;
-                    if stop_hotkey_enabled {
-                        ui.horizontal(|ui| {
-                            add_settings_text(ui, egui::Label::new("Stop Recording:"));
-                            let button_text =
-                                if self.listening_for_hotkey_rebind == Some(HotkeyRebindTarget::Stop) {
-                                    "Press any key...".to_string()
-                                } else {
-                                    self.local_preferences.stop_recording_key.clone()
-                                };
-
-                            if add_settings_widget(ui, egui::Button::new(button_text)).clicked() {
-                                self.listening_for_hotkey_rebind = Some(HotkeyRebindTarget::Stop);
-                            }
-                        });
-                    }
-
-                    ui.horizontal(|ui| {
-                        add_settings_text(ui, egui::Label::new("Stop Hotkey:"));
-                        add_settings_widget(
-                            ui,
-                            egui::Checkbox::new(
-                                &mut self.local_preferences.stop_hotkey_enabled,
-                                match stop_hotkey_enabled {
-                                    true => "Enabled",
-                                    false => "Disabled",
-                                },
-                            ),
-                        );
-                    });
                 });
                 ui.add_space(10.0);
 
-                // Overlay Settings Section
-                ui.group(|ui| {
-                    ui.label(
-                        egui::RichText::new("Recorder Customization")
-                            .size(18.0)
-                            .strong(),
-                    );
-                    ui.separator();
-
-                    ui.horizontal(|ui| {
-                        add_settings_text(ui, egui::Label::new("Overlay Location:"));
-                        add_settings_ui(ui, |ui| {
-                            egui::ComboBox::from_id_salt("overlay_location")
-                                .selected_text(self.local_preferences.overlay_location.to_string())
-                                .show_ui(ui, |ui| {
-                                    for location in crate::config::OverlayLocation::ALL {
-                                        ui.selectable_value(
-                                            &mut self.local_preferences.overlay_location,
-                                            location,
-                                            location.to_string(),
-                                        );
-                                    }
-                                });
-                        });
-                    });
-
-                    ui.horizontal(|ui| {
-                        add_settings_text(ui, egui::Label::new("Overlay Opacity:"));
-                        let mut stored_opacity = self.local_preferences.overlay_opacity;
-
-                        let mut egui_opacity = stored_opacity as f32 / 255.0 * 100.0;
-
-                        let r = ui
-                            .scope(|ui| {
-                                // one day egui will make sliders respect their width properly
-                                ui.spacing_mut().slider_width = ui.available_width() - 50.0;
-                                add_settings_widget(
-                                    ui,
-                                    egui::Slider::new(&mut egui_opacity, 0.0..=100.0)
-                                        .suffix("%")
-                                        .integer(),
-                                )
-                            })
-                            .inner;
-                        if r.changed() {
-                            stored_opacity = (egui_opacity / 100.0 * 255.0) as u8;
-                            self.local_preferences.overlay_opacity = stored_opacity;
-                        }
-                    });
-
-                    ui.horizontal(|ui| {
-                        add_settings_text(ui, egui::Label::new("Recording Audio Cue:"));
-                        let honk = self.local_preferences.honk;
-                        add_settings_widget(
-                            ui,
-                            egui::Checkbox::new(
-                                &mut self.local_preferences.honk,
-                                match honk {
-                                    true => "Honk.",
-                                    false => "Honk?",
-                                },
-                            ),
-                        );
-                    });
-
-                    ui.horizontal(|ui| {
-                        add_settings_text(ui, egui::Label::new("Video Encoder:"));
-                        add_settings_ui(ui, |ui| {
-                            let encoder_name = self.local_preferences.encoder.encoder.to_string();
-                            egui::ComboBox::from_id_salt("video_encoder")
-                                .selected_text(&encoder_name)
-                                .width(150.0)
-                                .show_ui(ui, |ui| {
-                                    for encoder in SUPPORTED_VIDEO_ENCODERS {
-                                        ui.selectable_value(
-                                            &mut self.local_preferences.encoder.encoder,
-                                            *encoder,
-                                            encoder.to_string(),
-                                        );
-                                    }
-                                });
-
-                            ui.horizontal(|ui| {
-                                if ui.button("⚙ Settings").clicked() {
-                                    self.encoder_settings_window_open = true;
-                                }
-
-                                tooltip(ui, "Consider switching encoders and/or using a different preset if your recordings suffer from dropped frames. NVENC is known to drop frames when the GPU is under heavy load or does not have enough VRAM.", None)
-                            });
-                        });
-                    });
-                });
-
+                self.settings_sections(ui, refresh_rate);
                 ui.add_space(10.0);
 
                 // Upload Manager Section
                 ui.group(|ui| {
                     ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new("Upload Manager").size(18.0).strong());
+                        ui.label(
+                            egui::RichText::new(tr("main.upload_manager"))
+                                .size(18.0)
+                                .strong(),
+                        );
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             if ui
-                                .button(egui::RichText::new("Open Recordings Folder").size(12.0))
+                                .button(
+                                    egui::RichText::new(tr("main.open_recordings_folder"))
+                                        .size(12.0),
+                                )
                                 .clicked()
                             {
                                 self.app_state
@@ -315,125 +339,499 @@ impl MainApp {
                                 .map(|u| (&u.statistics, u.uploads.as_slice())),
                         );
                     });
+                    ui.horizontal(|ui| {
+                        // If a refresh fails, the last stats we got are kept, so show how old they are
+                        if let Some(user_uploads) = &user_uploads {
+                            ui.label(
+                                egui::RichText::new(tr_args(
+                                    "main.last_updated",
+                                    &[(
+                                        "time",
+                                        &util::format_datetime(
+                                            user_uploads.fetched_at.with_timezone(&chrono::Local),
+                                        ),
+                                    )],
+                                ))
+                                .size(10.0)
+                                .color(theme::palette(ui.ctx()).muted),
+                            );
+                        }
+                        if ui
+                            .small_button(tr("main.refresh"))
+                            .on_hover_text(tr("main.refresh_tooltip"))
+                            .clicked()
+                        {
+                            self.app_state
+                                .async_request_tx
+                                .blocking_send(AsyncRequest::LoadUploadStats)
+                                .ok();
+                        }
+                    });
                     ui.add_space(8.0);
 
                     // Unified Recordings Section
                     let local_recordings = self.app_state.local_recordings.read().unwrap();
-                    let invalid_count = local_recordings.iter()
+                    let invalid_count = local_recordings
+                        .iter()
                         .filter(|r| matches!(r, crate::upload::LocalRecording::Invalid { .. }))
                         .count();
-                    egui::CollapsingHeader::new(
-                        if invalid_count > 0 {
-                            egui::RichText::new(format!("Upload Tracker ({invalid_count} invalid)"))
-                                .size(16.0)
-                        } else {
-                            egui::RichText::new("Upload Tracker").size(16.0)
-                        }
-                    )
+                    egui::CollapsingHeader::new(if invalid_count > 0 {
+                        egui::RichText::new(tr_args(
+                            "main.upload_tracker_invalid",
+                            &[("count", &invalid_count)],
+                        ))
+                        .size(16.0)
+                    } else {
+                        egui::RichText::new(tr("main.upload_tracker")).size(16.0)
+                    })
                     .default_open(true)
                     .show(ui, |ui| {
                         ui.add_space(4.0);
 
                         // Unified view with both successful and invalid recordings
-                        unified_recordings_view(
+                        match unified_recordings_view(
                             ui,
                             user_uploads.as_ref().map(|u| u.uploads.as_slice()),
                             &local_recordings,
                             &self.app_state,
-                        );
+                        ) {
+                            Some(RecordingAction::Preview(folder_path)) => {
+                                self.preview_view_state.load(&self.app_state, &folder_path);
+                            }
+                            Some(RecordingAction::Label(folder_path)) => {
+                                self.tagging_view_state.load(&folder_path);
+                            }
+                            Some(RecordingAction::LabelUploaded { upload_id, labels }) => {
+                                self.tagging_view_state.load_uploaded(&upload_id, labels);
+                            }
+                            None => {}
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button(tr("main.import_recordings"))
+                                .on_hover_text(tr("main.import_recordings_tooltip"))
+                                .clicked()
+                                && let Some(archive_paths) = rfd::FileDialog::new()
+                                    .add_filter("Recording archive", &["tar"])
+                                    .pick_files()
+                            {
+                                self.app_state
+                                    .async_request_tx
+                                    .blocking_send(AsyncRequest::ImportRecordings(archive_paths))
+                                    .ok();
+                            }
+                        });
                     });
 
-                    // Progress Bar
-                    let is_uploading = self.current_upload_progress.is_some();
-                    if let Some(progress) = &self.current_upload_progress {
-                        ui.add_space(10.0);
-                        ui.label(format!(
-                            "Current upload: {:.2}% ({}/{})",
-                            progress.percent,
-                            util::format_bytes(progress.bytes_uploaded),
-                            util::format_bytes(progress.total_bytes),
-                        ));
-                        ui.add(egui::ProgressBar::new(progress.percent as f32 / 100.0));
-                        ui.label(format!(
-                            "Speed: {:.1} MB/s • ETA: {}",
-                            progress.speed_mbps,
-                            util::format_seconds(progress.eta_seconds as u64),
-                        ));
+                    // Upload Queue
+                    // Copied out so that we don't hold the lock while the queue is being updated
+                    let (is_uploading, upload_queue_items) = {
+                        let upload_queue = self.app_state.upload_queue.read().unwrap();
+                        (upload_queue.is_active(), upload_queue.items().to_vec())
+                    };
+                    if !upload_queue_items.is_empty() {
+                        ui.add_space(8.0);
+                        egui::CollapsingHeader::new(
+                            egui::RichText::new(tr("main.upload_queue")).size(16.0),
+                        )
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            ui.add_space(4.0);
+                            upload_queue_view(ui, &upload_queue_items, &self.app_state);
+                        });
                     }
 
-                    // Unreliable Connection Setting
+                    // Auto Upload Setting
                     ui.add_space(5.0);
                     ui.horizontal(|ui| {
                         ui.add(egui::Checkbox::new(
-                            &mut self.local_preferences.unreliable_connection,
-                            "Optimize for unreliable connections",
+                            &mut self.local_preferences.auto_upload,
+                            tr("main.auto_upload"),
                         ));
-                        tooltip(ui, concat!(
-                            "Enable this if you have a slow or unstable internet connection. ",
-                            "This will use smaller file chunks to improve upload success rates."
-                        ), None);
+                        tooltip(ui, tr("main.auto_upload_tooltip"), None);
                     });
 
-                    // Delete Uploaded Recordings Setting
+                    // Unreliable Connection Setting
                     ui.horizontal(|ui| {
                         ui.add(egui::Checkbox::new(
-                            &mut self.local_preferences.delete_uploaded_files,
-                            "Delete recordings after successful upload",
+                            &mut self.local_preferences.unreliable_connection,
+                            tr("main.unreliable_connection"),
                         ));
-                        tooltip(ui, concat!(
-                            "Automatically delete local recordings after they have been successfully uploaded. ",
-                            "Invalid uploads, as well as existing uploads, will not be deleted."
-                        ), None);
+                        tooltip(ui, tr("main.unreliable_connection_tooltip"), None);
                     });
 
-                    // Upload Button
-                    ui.add_space(5.0);
-                    ui.add_enabled_ui(!is_uploading, |ui| {
-                        if ui
-                            .add_sized(
-                                egui::vec2(ui.available_width(), 32.0),
-                                egui::Button::new(
-                                    egui::RichText::new(if is_uploading {
-                                        "Upload in Progress..."
-                                    } else {
-                                        "Upload Recordings"
-                                    })
-                                    .size(12.0),
-                                ),
-                            )
-                            .clicked()
-                        {
-                            self.last_upload_error = None;
+                    // Uploaded Recordings Retention Setting
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("main.uploaded_recordings"));
+                        let retention = self.local_preferences.uploaded_retention;
+                        egui::ComboBox::from_id_salt("uploaded_retention")
+                            .selected_text(retention.to_string())
+                            .show_ui(ui, |ui| {
+                                for option in UploadedRecordingRetention::all() {
+                                    ui.selectable_value(
+                                        &mut self.local_preferences.uploaded_retention,
+                                        option,
+                                        option.to_string(),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label.id);
+                        if self.local_preferences.uploaded_retention != retention {
+                            // Cleans up anything the new setting no longer keeps
                             self.app_state
                                 .async_request_tx
-                                .blocking_send(AsyncRequest::UploadData)
+                                .blocking_send(AsyncRequest::LoadLocalRecordings)
                                 .ok();
                         }
-                        if let Some(error) = &self.last_upload_error {
-                            ui.label(
-                                egui::RichText::new(error)
-                                    .size(12.0)
-                                    .color(egui::Color32::from_rgb(255, 0, 0)),
-                            );
-                        }
+                        tooltip(ui, tr("main.uploaded_recordings_tooltip"), None);
                     });
+                    let retained = *self.app_state.retained_recordings.read().unwrap();
+                    if retained.count > 0 {
+                        ui.horizontal(|ui| {
+                            ui.label(tr_args(
+                                if retained.count == 1 {
+                                    "main.retained_recording"
+                                } else {
+                                    "main.retained_recordings"
+                                },
+                                &[
+                                    ("count", &retained.count),
+                                    ("size", &util::format_bytes(retained.size_bytes)),
+                                ],
+                            ));
+                            if ui.button(tr("main.clean_up_now")).clicked() {
+                                self.app_state
+                                    .async_request_tx
+                                    .blocking_send(AsyncRequest::CleanUpUploadedRecordings)
+                                    .ok();
+                            }
+                        });
+                    }
+
+                    egui::CollapsingHeader::new(tr("main.advanced"))
+                        .id_salt("upload_advanced")
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                add_settings_text(ui, egui::Label::new(tr("main.chunk_size")));
+                                add_settings_ui(ui, |ui| {
+                                    let chunk_size_text =
+                                        |chunk_size_mb: Option<u64>| match chunk_size_mb {
+                                            Some(mb) => format!("{mb} MB"),
+                                            None => tr("main.chunk_size_automatic").to_string(),
+                                        };
+                                    egui::ComboBox::from_id_salt("upload_chunk_size")
+                                        .selected_text(chunk_size_text(
+                                            self.local_preferences.upload_chunk_size_mb,
+                                        ))
+                                        .show_ui(ui, |ui| {
+                                            for chunk_size_mb in std::iter::once(None).chain(
+                                                constants::UPLOAD_CHUNK_SIZE_OPTIONS_MB.map(Some),
+                                            ) {
+                                                ui.selectable_value(
+                                                    &mut self
+                                                        .local_preferences
+                                                        .upload_chunk_size_mb,
+                                                    chunk_size_mb,
+                                                    chunk_size_text(chunk_size_mb),
+                                                );
+                                            }
+                                        });
+                                    tooltip(ui, tr("main.chunk_size_tooltip"), None);
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                let label = add_settings_text(
+                                    ui,
+                                    egui::Label::new(tr("main.concurrent_uploads")),
+                                );
+                                add_settings_ui(ui, |ui| {
+                                    ui.add(
+                                        egui::DragValue::new(
+                                            &mut self.local_preferences.max_concurrent_uploads,
+                                        )
+                                        .range(1..=constants::MAX_CONCURRENT_UPLOADS),
+                                    )
+                                    .labelled_by(label.id);
+                                    tooltip(ui, tr("main.concurrent_uploads_tooltip"), None);
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                let label =
+                                    add_settings_text(ui, egui::Label::new(tr("main.speed_limit")));
+                                add_settings_ui(ui, |ui| {
+                                    let mut limited =
+                                        self.local_preferences.upload_speed_limit_kbps.is_some();
+                                    if ui
+                                        .checkbox(&mut limited, "")
+                                        .labelled_by(label.id)
+                                        .changed()
+                                    {
+                                        self.local_preferences.upload_speed_limit_kbps =
+                                            limited.then_some(1024);
+                                    }
+                                    if let Some(kbps) =
+                                        &mut self.local_preferences.upload_speed_limit_kbps
+                                    {
+                                        let mut mb_per_second = *kbps as f32 / 1024.0;
+                                        if ui
+                                            .add(
+                                                egui::DragValue::new(&mut mb_per_second)
+                                                    .range(0.1..=100.0)
+                                                    .speed(0.1)
+                                                    .fixed_decimals(1)
+                                                    .suffix(" MB/s"),
+                                            )
+                                            .changed()
+                                        {
+                                            *kbps =
+                                                ((mb_per_second * 1024.0).round() as u32).max(1);
+                                        }
+                                    } else {
+                                        ui.label(tr("main.speed_limit_unlimited"));
+                                    }
+                                    tooltip(ui, tr("main.speed_limit_tooltip"), None);
+                                });
+                            });
+                            if self.local_preferences.upload_speed_limit_kbps.is_some() {
+                                ui.horizontal(|ui| {
+                                    let label = add_settings_text(
+                                        ui,
+                                        egui::Label::new(tr("main.full_speed_hours")),
+                                    );
+                                    add_settings_ui(ui, |ui| {
+                                        let mut scheduled =
+                                            self.local_preferences.unlimited_upload_hours.is_some();
+                                        if ui
+                                            .checkbox(&mut scheduled, "")
+                                            .labelled_by(label.id)
+                                            .changed()
+                                        {
+                                            self.local_preferences.unlimited_upload_hours =
+                                                scheduled.then_some((0, 7));
+                                        }
+                                        if let Some((start, end)) =
+                                            &mut self.local_preferences.unlimited_upload_hours
+                                        {
+                                            ui.add(
+                                                egui::DragValue::new(start)
+                                                    .range(0..=23)
+                                                    .suffix(":00"),
+                                            );
+                                            ui.label(tr("main.full_speed_hours_to"));
+                                            ui.add(
+                                                egui::DragValue::new(end)
+                                                    .range(0..=23)
+                                                    .suffix(":00"),
+                                            );
+                                        } else {
+                                            ui.label(tr("main.never"));
+                                        }
+                                        tooltip(ui, tr("main.full_speed_hours_tooltip"), None);
+                                    });
+                                });
+                            }
+                            ui.horizontal(|ui| {
+                                let label =
+                                    add_settings_text(ui, egui::Label::new(tr("main.retries")));
+                                add_settings_ui(ui, |ui| {
+                                    ui.add(
+                                        egui::DragValue::new(
+                                            &mut self.local_preferences.upload_retries,
+                                        )
+                                        .range(0..=20),
+                                    )
+                                    .labelled_by(label.id);
+                                    tooltip(ui, tr("main.retries_tooltip"), None);
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                let label = add_settings_text(
+                                    ui,
+                                    egui::Label::new(tr("main.compact_inputs")),
+                                );
+                                add_settings_ui(ui, |ui| {
+                                    ui.checkbox(
+                                        &mut self.local_preferences.compact_input_track,
+                                        "",
+                                    )
+                                    .labelled_by(label.id);
+                                    tooltip(ui, tr("main.compact_inputs_tooltip"), None);
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                add_settings_text(ui, egui::Label::new(tr("main.upload_to")));
+                                add_settings_ui(ui, |ui| {
+                                    let preferences = &mut self.local_preferences;
+                                    // One of them has to be left on, or uploads would have nowhere to go
+                                    ui.add_enabled(
+                                        preferences.s3_destination.is_usable(),
+                                        egui::Checkbox::new(
+                                            &mut preferences.upload_to_server,
+                                            tr("main.upload_to_server"),
+                                        ),
+                                    );
+                                    if !preferences.s3_destination.is_usable() {
+                                        preferences.upload_to_server = true;
+                                    }
+                                    ui.checkbox(
+                                        &mut preferences.s3_destination.enabled,
+                                        tr("main.upload_to_s3"),
+                                    );
+                                    if ui.button(tr("main.upload_to_set_up")).clicked() {
+                                        self.s3_destination_view_state.open = true;
+                                    }
+                                    tooltip(ui, tr("main.upload_to_tooltip"), None);
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                let label =
+                                    add_settings_text(ui, egui::Label::new(tr("main.proxy")));
+                                add_settings_ui(ui, |ui| {
+                                    let mut proxy =
+                                        self.local_preferences.proxy.clone().unwrap_or_default();
+                                    if ui
+                                        .add(
+                                            egui::TextEdit::singleline(&mut proxy)
+                                                .desired_width(250.0)
+                                                .hint_text(tr("main.proxy_hint")),
+                                        )
+                                        .labelled_by(label.id)
+                                        .changed()
+                                    {
+                                        let proxy = proxy.trim();
+                                        self.local_preferences.proxy =
+                                            (!proxy.is_empty()).then(|| proxy.to_string());
+                                    }
+                                    match self
+                                        .local_preferences
+                                        .proxy
+                                        .as_deref()
+                                        .map(crate::api::check_proxy_url)
+                                    {
+                                        Some(Err(e)) => tooltip(
+                                            ui,
+                                            &tr_args("main.proxy_invalid", &[("error", &e)]),
+                                            Some(theme::palette(ui.ctx()).error),
+                                        ),
+                                        _ => tooltip(ui, tr("main.proxy_tooltip"), None),
+                                    }
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                let label =
+                                    add_settings_text(ui, egui::Label::new(tr("main.api_server")));
+                                add_settings_ui(ui, |ui| {
+                                    let overridden = self.app_state.api_base_url_override.is_some();
+                                    let mut url = self
+                                        .app_state
+                                        .api_base_url_override
+                                        .clone()
+                                        .or_else(|| self.local_preferences.api_base_url.clone())
+                                        .unwrap_or_default();
+                                    if ui
+                                        .add_enabled(
+                                            !overridden,
+                                            egui::TextEdit::singleline(&mut url)
+                                                .desired_width(250.0)
+                                                .hint_text(crate::api::DEFAULT_API_BASE_URL),
+                                        )
+                                        .labelled_by(label.id)
+                                        .changed()
+                                    {
+                                        let url = url.trim();
+                                        self.local_preferences.api_base_url =
+                                            (!url.is_empty()).then(|| url.to_string());
+                                    }
+                                    let check = (!url.is_empty()).then(|| {
+                                        crate::api::check_api_base_url(
+                                            &url,
+                                            self.app_state.allow_insecure_api,
+                                        )
+                                    });
+                                    match check {
+                                        Some(Err(e)) => tooltip(
+                                            ui,
+                                            &tr_args("main.api_server_invalid", &[("error", &e)]),
+                                            Some(theme::palette(ui.ctx()).error),
+                                        ),
+                                        _ if overridden => {
+                                            tooltip(ui, tr("main.api_server_overridden"), None)
+                                        }
+                                        _ => tooltip(ui, tr("main.api_server_tooltip"), None),
+                                    }
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                add_settings_text(ui, egui::Label::new(tr("main.connection")));
+                                add_settings_ui(ui, |ui| {
+                                    let testing =
+                                        matches!(self.main_view_state.connection_test, Some(None));
+                                    if ui
+                                        .add_enabled(
+                                            !testing,
+                                            egui::Button::new(tr("main.test_connection")),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.main_view_state.connection_test = Some(None);
+                                        self.app_state
+                                            .async_request_tx
+                                            .blocking_send(AsyncRequest::TestConnection)
+                                            .ok();
+                                    }
+                                    if testing {
+                                        ui.spinner();
+                                    }
+                                    tooltip(ui, tr("main.test_connection_tooltip"), None);
+                                });
+                            });
+                            if let Some(Some(result)) = &self.main_view_state.connection_test {
+                                connection_test_result(ui, result);
+                            }
+                        });
+
+                    // Upload Button
+                    ui.add_space(5.0);
+                    if ui
+                        .add_sized(
+                            egui::vec2(ui.available_width(), 32.0),
+                            egui::Button::new(
+                                egui::RichText::new(if is_uploading {
+                                    tr("main.queue_new_recordings")
+                                } else {
+                                    tr("main.upload_recordings")
+                                })
+                                .size(12.0),
+                            ),
+                        )
+                        .on_hover_text(tr("main.upload_recordings_tooltip"))
+                        .clicked()
+                    {
+                        self.app_state
+                            .async_request_tx
+                            .blocking_send(AsyncRequest::UploadData)
+                            .ok();
+                    }
                 });
 
                 // Logo
                 ui.separator();
                 ui.horizontal(|ui| {
                     ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
-                        if ui.button("FAQ").clicked() {
+                        if ui.button(tr("main.faq")).clicked() {
                             opener::open_browser(format!(
                                 "https://github.com/{GH_ORG}/{GH_REPO}/blob/main/GAMES.md"
                             ))
                             .ok();
                         }
-                        if ui.button("Logs").clicked() {
-                            self.app_state
-                                .async_request_tx
-                                .blocking_send(AsyncRequest::OpenLog)
-                                .ok();
+                        if ui.button(tr("main.logs")).clicked() {
+                            self.logs_view_state.open = true;
+                        }
+                        if ui.button(tr("main.report_problem")).clicked() {
+                            self.logs_view_state.open = true;
+                            self.logs_view_state.report_open = true;
                         }
                     });
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -448,95 +846,608 @@ impl MainApp {
             });
         });
 
-        // Encoder Settings Window
-        egui::Window::new(format!(
-            "{} Settings",
-            self.local_preferences.encoder.encoder
-        ))
-        .open(&mut self.encoder_settings_window_open)
-        .collapsible(false)
-        .resizable(false)
-        .show(ctx, |ui| {
-            encoder_settings_window(ui, &mut self.local_preferences.encoder);
+        self.encoder_settings_window(ctx);
+        self.hotkey_reference_window(ctx);
+        self.capture_region_window(ctx);
+        self.s3_destination_window(ctx);
+        self.encryption_window(ctx);
+        self.benchmark_window(ctx);
+        self.logs_window(ctx);
+        // Before the windows that it opens, so that they show up over it
+        self.summary_window(ctx);
+        self.preview_window(ctx);
+        self.tagging_window(ctx);
+    }
+}
+
+/// Returns true if the banner was dismissed
+impl MainApp {
+    /// Lets the user switch between, add and remove profiles, each with its own account and settings
+    fn profile_picker(&mut self, ui: &mut egui::Ui) {
+        let (current, others) = {
+            let config = self.app_state.config.read().unwrap();
+            (
+                config.profile_name.clone(),
+                config
+                    .other_profiles
+                    .iter()
+                    .map(|p| p.name.clone())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let mut switch_to = None;
+        let mut add = None;
+        let mut remove = None;
+        ui.horizontal(|ui| {
+            let label = ui.label(tr("main.profile"));
+            egui::ComboBox::from_id_salt("profile")
+                .selected_text(&current)
+                .show_ui(ui, |ui| {
+                    let _ = ui.selectable_label(true, &current);
+                    for name in &others {
+                        ui.horizontal(|ui| {
+                            if ui.selectable_label(false, name).clicked() {
+                                switch_to = Some(name.clone());
+                            }
+                            if ui
+                                .small_button("🗑")
+                                .on_hover_text(tr("main.remove_profile"))
+                                .clicked()
+                            {
+                                remove = Some(name.clone());
+                            }
+                        });
+                    }
+                })
+                .response
+                .labelled_by(label.id);
+
+            match &mut self.main_view_state.new_profile_name {
+                Some(new_profile_name) => {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(new_profile_name)
+                            .hint_text(tr("main.profile_name"))
+                            .desired_width(120.0),
+                    );
+                    let submitted =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button(tr("main.add_profile")).clicked() || submitted {
+                        add = Some(new_profile_name.clone());
+                    }
+                    if ui.button(tr("main.cancel")).clicked() {
+                        self.main_view_state.new_profile_name = None;
+                        self.main_view_state.profile_error = None;
+                    }
+                }
+                None => {
+                    if ui
+                        .button("➕")
+                        .on_hover_text(tr("main.new_profile"))
+                        .clicked()
+                    {
+                        self.main_view_state.new_profile_name = Some(String::new());
+                    }
+                }
+            }
+        });
+
+        if let Some(name) = add {
+            match self.change_profile(|config| config.add_profile(&name)) {
+                Ok(()) => {
+                    self.main_view_state.new_profile_name = None;
+                    self.main_view_state.profile_error = None;
+                }
+                Err(e) => self.main_view_state.profile_error = Some(e.to_string()),
+            }
+        }
+        if let Some(name) = switch_to {
+            self.main_view_state.profile_error = self
+                .change_profile(|config| config.switch_profile(&name))
+                .err()
+                .map(|e| e.to_string());
+        }
+        if let Some(name) = remove {
+            let result = self.app_state.config.write().unwrap().remove_profile(&name);
+            self.main_view_state.profile_error = result.err().map(|e| e.to_string());
+            // Saved straight away, as the local copy of the config doesn't include the other profiles
+            if let Err(e) = self.app_state.config.read().unwrap().save() {
+                tracing::error!(e=?e, "Failed to save config after removing profile");
+            }
+        }
+
+        if let Some(error) = &self.main_view_state.profile_error {
+            ui.label(egui::RichText::new(error).color(theme::palette(ui.ctx()).error));
+        }
+    }
+}
+
+fn newer_release_available(
+    ui: &mut egui::Ui,
+    release: &GitHubRelease,
+    md_cache: &mut egui_commonmark::CommonMarkCache,
+) -> bool {
+    egui::Frame::default()
+        .fill(egui::Color32::DARK_GREEN)
+        .inner_margin(egui::Margin::same(15))
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    egui::RichText::new(tr("main.new_release"))
+                        .size(20.0)
+                        .strong(),
+                );
+
+                // Release name
+                ui.label(egui::RichText::new(&release.name).size(16.0).strong());
+
+                // Release date if available
+                if let Some(date) = &release.release_date {
+                    ui.label(
+                        egui::RichText::new(tr_args(
+                            "main.released",
+                            &[("date", &date.format("%B %d, %Y"))],
+                        ))
+                        .size(12.0),
+                    );
+                }
+
+                if let Some(notes) = &release.notes {
+                    egui::CollapsingHeader::new(tr("main.whats_new"))
+                        .id_salt("release_notes")
+                        .show(ui, |ui| {
+                            egui::ScrollArea::vertical()
+                                .max_height(150.0)
+                                .show(ui, |ui| {
+                                    egui_commonmark::CommonMarkViewer::new()
+                                        .show(ui, md_cache, notes);
+                                });
+                        });
+                }
+
+                ui.add_space(8.0);
+
+                // Download button
+                if ui
+                    .add_sized(
+                        egui::vec2(200.0, 35.0),
+                        egui::Button::new(
+                            egui::RichText::new(tr("main.download_now"))
+                                .size(14.0)
+                                .strong()
+                                .color(egui::Color32::WHITE),
+                        )
+                        .fill(egui::Color32::from_rgb(40, 167, 69)), // Green button
+                    )
+                    .clicked()
+                {
+                    #[allow(clippy::collapsible_if)]
+                    if let Err(e) = opener::open_browser(&release.url) {
+                        tracing::error!("Failed to open release URL: {}", e);
+                    }
+                }
+
+                ui.button(tr("main.later")).clicked()
+            })
+            .inner
+        })
+        .inner
+}
+
+/// Check if any OBS Studio processes are currently running
+fn is_obs_running() -> bool {
+    let mut is_obs_running = false;
+
+    game_process::for_each_process(|process| {
+        let exe_name = unsafe { std::ffi::CStr::from_ptr(process.szExeFile.as_ptr()) };
+        let Some(file_name) = exe_name
+            .to_str()
+            .ok()
+            .map(std::path::Path::new)
+            .and_then(|p| p.file_name())
+            .and_then(|f| f.to_str())
+            .map(|f| f.to_ascii_lowercase())
+        else {
+            return true;
+        };
+
+        if ["obs.exe", "obs64.exe", "obs32.exe"].contains(&file_name.as_str()) {
+            is_obs_running = true;
+            return false;
+        }
+
+        true
+    })
+    .ok();
+
+    is_obs_running
+}
+
+/// Returns true if the pause/resume button was clicked
+/// Returns whether the countdown was cancelled
+fn countdown(ui: &mut egui::Ui, starts_at: std::time::Instant) -> bool {
+    egui::Frame::default()
+        .fill(egui::Color32::from_rgb(40, 90, 140))
+        .inner_margin(egui::Margin::same(10))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(tr_args(
+                        "main.countdown",
+                        &[("seconds", &util::countdown_seconds(starts_at))],
+                    ))
+                    .size(16.0)
+                    .strong()
+                    .color(egui::Color32::WHITE),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.button(tr("main.cancel")).clicked()
+                })
+                .inner
+            })
+            .inner
+        })
+        .inner
+}
+
+fn active_recording(
+    ui: &mut egui::Ui,
+    game_exe: &str,
+    paused: bool,
+    skipped_frames: Option<SkippedFrames>,
+    preview: Option<(egui::TextureHandle, bool)>,
+) -> bool {
+    egui::Frame::default()
+        .fill(if paused {
+            egui::Color32::from_rgb(180, 130, 20)
+        } else {
+            egui::Color32::from_rgb(40, 120, 60)
+        })
+        .inner_margin(egui::Margin::same(10))
+        .show(ui, |ui| {
+            let toggled = ui
+                .horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(if paused {
+                            tr_args("main.recording_paused", &[("game", &game_exe)])
+                        } else {
+                            tr_args("main.recording", &[("game", &game_exe)])
+                        })
+                        .size(16.0)
+                        .strong()
+                        .color(egui::Color32::WHITE),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.button(if paused {
+                            tr("main.resume")
+                        } else {
+                            tr("main.pause")
+                        })
+                        .clicked()
+                    })
+                    .inner
+                })
+                .inner;
+
+            if let Some(skipped_frames) = skipped_frames {
+                let percentage = skipped_frames.percentage();
+                let mut text = tr_args(
+                    "main.dropped_frames",
+                    &[
+                        ("skipped", &skipped_frames.skipped),
+                        ("total", &skipped_frames.total),
+                        ("percentage", &format!("{percentage:.1}")),
+                    ],
+                );
+                let color = if percentage > constants::SKIPPED_FRAMES_WARNING_PERCENTAGE {
+                    text += tr("main.dropped_frames_hint");
+                    egui::Color32::from_rgb(255, 200, 0)
+                } else {
+                    egui::Color32::from_rgb(220, 220, 220)
+                };
+                ui.label(egui::RichText::new(text).size(12.0).color(color));
+            }
+
+            if let Some((texture, blank)) = preview {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.image((texture.id(), texture.size_vec2()));
+                    if blank {
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(tr("main.preview_blank"))
+                                    .size(12.0)
+                                    .color(egui::Color32::from_rgb(255, 200, 0)),
+                            )
+                            .wrap(),
+                        );
+                    }
+                });
+            }
+
+            toggled
+        })
+        .inner
+}
+
+/// A chip showing whether uploads should be getting through, so that the user knows before they record.
+/// Returns true if it was clicked, to check again.
+fn connection_status(ui: &mut egui::Ui, offline: bool, contact: ServerContact) -> bool {
+    let palette = theme::palette(ui.ctx());
+    let (color, text, detail) = if offline {
+        (
+            palette.error,
+            tr("main.status_offline"),
+            tr("main.status_offline_tooltip"),
+        )
+    } else {
+        match contact.reachable {
+            Some(true) => (
+                palette.success,
+                tr("main.status_connected"),
+                tr("main.status_connected_tooltip"),
+            ),
+            Some(false) => (
+                palette.warning,
+                tr("main.status_unreachable"),
+                tr("main.status_unreachable_tooltip"),
+            ),
+            None => (
+                palette.warning,
+                tr("main.status_checking"),
+                tr("main.status_checking_tooltip"),
+            ),
+        }
+    };
+
+    let mut tooltip = detail.to_string();
+    if let Some(last_answered) = contact.last_answered {
+        tooltip += "\n\n";
+        tooltip += &tr_args(
+            "main.status_last_contact",
+            &[(
+                "time",
+                &util::format_seconds(last_answered.elapsed().as_secs()),
+            )],
+        );
+    }
+    tooltip += "\n\n";
+    tooltip += tr("main.status_check_again");
+
+    ui.add(egui::Button::new(
+        egui::RichText::new(format!("⏺ {text}"))
+            .size(12.0)
+            .color(color),
+    ))
+    .on_hover_text(tooltip)
+    .clicked()
+}
+
+/// Who the user is logged in as, so that people sharing a machine can tell whose account is in use
+fn account_details(ui: &mut egui::Ui, user_info: &UserInfo) {
+    ui.horizontal(|ui| {
+        ui.label(tr("main.logged_in_as"));
+        ui.label(egui::RichText::new(user_info.display_name()).strong());
+        if let Some(email) = &user_info.email
+            && user_info.username.is_some()
+        {
+            ui.label(egui::RichText::new(format!("({email})")).weak());
+        }
+    });
+    if user_info.plan.is_some() || user_info.upload_quota_bytes.is_some() {
+        ui.horizontal(|ui| {
+            if let Some(plan) = &user_info.plan {
+                ui.label(tr_args("main.plan", &[("plan", plan)]));
+            }
+            if let Some(quota_bytes) = user_info.upload_quota_bytes {
+                ui.label(tr_args(
+                    "main.upload_quota",
+                    &[("quota", &util::format_bytes(quota_bytes))],
+                ));
+            }
+        });
+    }
+}
+
+fn low_disk_space_warning(ui: &mut egui::Ui, free_space_mb: u64, min_free_space_mb: u64) {
+    egui::Frame::default()
+        .fill(egui::Color32::from_rgb(180, 130, 20))
+        .inner_margin(egui::Margin::same(10))
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    egui::RichText::new(tr_args(
+                        "main.low_disk_space",
+                        &[("free", &free_space_mb), ("min_free", &min_free_space_mb)],
+                    ))
+                    .size(14.0)
+                    .color(egui::Color32::WHITE),
+                );
+            });
+        });
+}
+
+/// Returns true if the warning was dismissed
+fn recording_start_failed_warning(ui: &mut egui::Ui, error: &str) -> bool {
+    let mut dismissed = false;
+    egui::Frame::default()
+        .fill(egui::Color32::from_rgb(220, 53, 69))
+        .inner_margin(egui::Margin::same(10))
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    egui::RichText::new(tr_args(
+                        "main.recording_start_failed",
+                        &[("error", &error)],
+                    ))
+                    .size(14.0)
+                    .color(egui::Color32::WHITE),
+                );
+                dismissed = ui.button(tr("main.dismiss")).clicked();
+            });
         });
-    }
+    dismissed
 }
 
-fn newer_release_available(ui: &mut egui::Ui, release: &GitHubRelease) {
+/// Returns whether the user asked for the game to be checked again
+fn capture_denied_warning(ui: &mut egui::Ui, denied: &CaptureDenied) -> bool {
+    let game = denied.game_exe();
+    let (detail, fix) = match denied {
+        CaptureDenied::Elevated { .. } => (
+            tr_args("main.capture_denied_elevated", &[("game", &game)]),
+            tr_args("main.capture_denied_elevated_fix", &[("game", &game)]),
+        ),
+        CaptureDenied::NotHooked { .. } => (
+            tr_args(
+                "main.capture_denied_not_hooked",
+                &[
+                    ("game", &game),
+                    ("seconds", &capture_permission::HOOK_TIMEOUT.as_secs()),
+                ],
+            ),
+            tr("main.capture_denied_not_hooked_fix").to_string(),
+        ),
+    };
+
+    let mut retry = false;
     egui::Frame::default()
-        .fill(egui::Color32::DARK_GREEN)
+        .fill(egui::Color32::from_rgb(220, 53, 69))
         .inner_margin(egui::Margin::same(15))
         .show(ui, |ui| {
             ui.vertical_centered(|ui| {
                 ui.label(
-                    egui::RichText::new("New Release Available!")
+                    egui::RichText::new(tr_args("main.capture_denied", &[("game", &game)]))
                         .size(20.0)
-                        .strong(),
+                        .strong()
+                        .color(egui::Color32::WHITE),
                 );
 
-                // Release name
-                ui.label(egui::RichText::new(&release.name).size(16.0).strong());
+                ui.add_space(8.0);
 
-                // Release date if available
-                if let Some(date) = &release.release_date {
+                for text in [detail, fix, tr("main.capture_denied_blocked").to_string()] {
                     ui.label(
-                        egui::RichText::new(format!("Released: {}", date.format("%B %d, %Y")))
-                            .size(12.0),
+                        egui::RichText::new(text)
+                            .size(14.0)
+                            .color(egui::Color32::WHITE),
                     );
                 }
 
                 ui.add_space(8.0);
 
-                // Download button
-                if ui
-                    .add_sized(
-                        egui::vec2(200.0, 35.0),
-                        egui::Button::new(
-                            egui::RichText::new("Download Now")
-                                .size(14.0)
-                                .strong()
-                                .color(egui::Color32::WHITE),
-                        )
-                        .fill(egui::Color32::from_rgb(40, 167, 69)), // Green button
-                    )
-                    .clicked()
-                {
-                    #[allow(clippy::collapsible_if)]
-                    if let Err(e) = opener::open_browser(&release.url) {
-                        tracing::error!("Failed to open release URL: {}", e);
-                    }
+                retry = ui
+                    .button(tr("main.capture_denied_retry"))
+                    .on_hover_text(tr_args(
+                        "main.capture_denied_retry_tooltip",
+                        &[("game", &game)],
+                    ))
+                    .clicked();
+            });
+        });
+    retry
+}
+
+/// Returns `Some` once the notice has been dealt with, along with anything the user asked for
+fn unclean_exit_notice(
+    ui: &mut egui::Ui,
+    unclean_exit: &UncleanExit,
+) -> Option<Option<AsyncRequest>> {
+    let mut result = None;
+    egui::Frame::default()
+        .fill(egui::Color32::from_rgb(180, 130, 20))
+        .inner_margin(egui::Margin::same(10))
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                let mut text = match &unclean_exit.previous_run.recording_game {
+                    Some(game) => tr_args("main.unclean_exit_recording", &[("game", game)]),
+                    None => tr("main.unclean_exit").to_string(),
+                };
+                match unclean_exit.recovered_recordings.len() {
+                    0 => {}
+                    1 => text += tr("main.unclean_exit_recovered_one"),
+                    n => text += &tr_args("main.unclean_exit_recovered", &[("count", &n)]),
                 }
+                ui.label(
+                    egui::RichText::new(text)
+                        .size(14.0)
+                        .color(egui::Color32::WHITE),
+                );
+
+                ui.horizontal(|ui| {
+                    if !unclean_exit.recovered_recordings.is_empty()
+                        && ui.button(tr("main.upload_recovered")).clicked()
+                    {
+                        result = Some(Some(AsyncRequest::UploadData));
+                    }
+                    if unclean_exit.previous_run.recording_game.is_some()
+                        && ui
+                            .button(tr("main.resume_recording"))
+                            .on_hover_text(tr("main.resume_recording_tooltip"))
+                            .clicked()
+                    {
+                        result = Some(Some(AsyncRequest::StartRecording));
+                    }
+                    if ui.button(tr("main.dismiss")).clicked() {
+                        result = Some(None);
+                    }
+                });
             });
         });
+    result
 }
 
-/// Check if any OBS Studio processes are currently running
-fn is_obs_running() -> bool {
-    let mut is_obs_running = false;
+fn offline_warning(ui: &mut egui::Ui) {
+    egui::Frame::default()
+        .fill(egui::Color32::from_rgb(180, 130, 20))
+        .inner_margin(egui::Margin::same(10))
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    egui::RichText::new(tr("main.offline"))
+                        .size(14.0)
+                        .color(egui::Color32::WHITE),
+                );
+            });
+        });
+}
 
-    game_process::for_each_process(|process| {
-        let exe_name = unsafe { std::ffi::CStr::from_ptr(process.szExeFile.as_ptr()) };
-        let Some(file_name) = exe_name
-            .to_str()
-            .ok()
-            .map(std::path::Path::new)
-            .and_then(|p| p.file_name())
-            .and_then(|f| f.to_str())
-            .map(|f| f.to_ascii_lowercase())
-        else {
-            return true;
-        };
+fn client_outdated_warning(ui: &mut egui::Ui, min_client_version: &str) {
+    egui::Frame::default()
+        .fill(egui::Color32::from_rgb(220, 53, 69))
+        .inner_margin(egui::Margin::same(15))
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    egui::RichText::new(tr("main.outdated"))
+                        .size(20.0)
+                        .strong()
+                        .color(egui::Color32::WHITE),
+                );
 
-        if ["obs.exe", "obs64.exe", "obs32.exe"].contains(&file_name.as_str()) {
-            is_obs_running = true;
-            return false;
-        }
+                ui.add_space(8.0);
 
-        true
-    })
-    .ok();
+                ui.label(
+                    egui::RichText::new(tr_args(
+                        "main.outdated_detail",
+                        &[
+                            ("version", &env!("CARGO_PKG_VERSION")),
+                            ("min_version", &min_client_version),
+                        ],
+                    ))
+                    .size(14.0)
+                    .color(egui::Color32::WHITE),
+                );
 
-    is_obs_running
+                ui.add_space(8.0);
+
+                if ui.button(tr("main.download_now")).clicked()
+                    && let Err(e) = opener::open_browser(format!(
+                        "https://github.com/{GH_ORG}/{GH_REPO}/releases/latest"
+                    ))
+                {
+                    tracing::error!("Failed to open release URL: {}", e);
+                }
+            });
+        });
 }
 
 fn obs_running_warning(ui: &mut egui::Ui) {
@@ -546,7 +1457,7 @@ fn obs_running_warning(ui: &mut egui::Ui) {
         .show(ui, |ui| {
             ui.vertical_centered(|ui| {
                 ui.label(
-                    egui::RichText::new("OBS Studio Detected!")
+                    egui::RichText::new(tr("main.obs_detected"))
                         .size(20.0)
                         .strong()
                         .color(egui::Color32::WHITE),
@@ -555,12 +1466,9 @@ fn obs_running_warning(ui: &mut egui::Ui) {
                 ui.add_space(8.0);
 
                 ui.label(
-                    egui::RichText::new(
-                        "OBS Studio is currently running and may conflict with OWL Control. \
-                         Please close OBS Studio before using OWL Control for the best experience.",
-                    )
-                    .size(14.0)
-                    .color(egui::Color32::WHITE),
+                    egui::RichText::new(tr("main.obs_detected_detail"))
+                        .size(14.0)
+                        .color(egui::Color32::WHITE),
                 );
             });
         });
@@ -585,7 +1493,7 @@ fn upload_stats(
         ui.label(
             egui::RichText::new(value)
                 .size(10.0)
-                .color(egui::Color32::from_rgb(128, 128, 128)),
+                .color(theme::palette(ui.ctx()).muted),
         );
     }
 
@@ -597,10 +1505,10 @@ fn upload_stats(
             create_upload_cell(
                 ui,
                 "📊", // Icon
-                "Total Uploaded",
+                tr("main.total_uploaded"),
                 &stats
                     .map(|s| util::format_seconds(s.total_video_time.seconds as u64))
-                    .unwrap_or_else(|| "Loading...".to_string()),
+                    .unwrap_or_else(|| tr("main.loading").to_string()),
             );
         },
     );
@@ -613,10 +1521,10 @@ fn upload_stats(
             create_upload_cell(
                 ui,
                 "📁", // Icon
-                "Files Uploaded",
+                tr("main.files_uploaded"),
                 &stats
                     .map(|s| s.total_uploads.to_string())
-                    .unwrap_or_else(|| "Loading...".to_string()),
+                    .unwrap_or_else(|| tr("main.loading").to_string()),
             );
         },
     );
@@ -629,7 +1537,7 @@ fn upload_stats(
             create_upload_cell(
                 ui,
                 "💾", // Icon
-                "Volume Uploaded",
+                tr("main.volume_uploaded"),
                 &util::format_bytes(stats.map(|s| s.total_data.bytes).unwrap_or_else(|| 0)),
             );
         },
@@ -643,15 +1551,15 @@ fn upload_stats(
             create_upload_cell(
                 ui,
                 "🕒", // Icon
-                "Last Upload",
+                tr("main.last_upload"),
                 &uploads
                     .map(|u| {
                         u.first()
                             .map(|upload| upload.created_at.with_timezone(&chrono::Local))
                             .map(util::format_datetime)
-                            .unwrap_or_else(|| "Never".to_string())
+                            .unwrap_or_else(|| tr("main.never").to_string())
                     })
-                    .unwrap_or_else(|| "Loading...".to_string()),
+                    .unwrap_or_else(|| tr("main.loading").to_string()),
             );
         },
     );
@@ -675,13 +1583,202 @@ impl<'a> RecordingEntry<'a> {
     }
 }
 
+fn upload_queue_view(
+    ui: &mut egui::Ui,
+    items: &[QueuedUpload],
+    app_state: &crate::app_state::AppState,
+) {
+    const FONTSIZE: f32 = 13.0;
+
+    if items.iter().any(|i| i.state == UploadState::Done)
+        && ui
+            .button(egui::RichText::new(tr("main.clear_completed")).size(FONTSIZE))
+            .clicked()
+    {
+        app_state
+            .async_request_tx
+            .blocking_send(AsyncRequest::ClearCompletedUploads)
+            .ok();
+    }
+
+    egui::ScrollArea::vertical()
+        .id_salt("upload_queue")
+        .max_height(160.0)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            for item in items {
+                egui::Frame::new()
+                    .fill(ui.visuals().faint_bg_color)
+                    .inner_margin(4.0)
+                    .corner_radius(4.0)
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            let (icon, color) = match item.state {
+                                UploadState::Pending => {
+                                    ("⏳", egui::Color32::from_rgb(200, 200, 200))
+                                }
+                                UploadState::Uploading => ("⬆", egui::Color32::LIGHT_BLUE),
+                                UploadState::Done => ("✔", theme::palette(ui.ctx()).success),
+                                UploadState::Failed(_) => {
+                                    ("❌", egui::Color32::from_rgb(255, 100, 100))
+                                }
+                                UploadState::AwaitingLogin => {
+                                    ("🔒", theme::palette(ui.ctx()).warning)
+                                }
+                                UploadState::AwaitingNetwork => {
+                                    ("📶", theme::palette(ui.ctx()).warning)
+                                }
+                            };
+                            ui.label(egui::RichText::new(icon).size(FONTSIZE).color(color));
+                            ui.label(egui::RichText::new(&item.folder_name).size(FONTSIZE));
+                            match &item.state {
+                                UploadState::Failed(error) => {
+                                    tooltip(ui, error, Some(egui::Color32::from_rgb(255, 150, 150)))
+                                }
+                                UploadState::AwaitingNetwork => {
+                                    tooltip(ui, tr("main.queue_awaiting_network_tooltip"), None)
+                                }
+                                _ => {}
+                            }
+
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    let request = match item.state {
+                                        UploadState::Pending
+                                        | UploadState::Uploading
+                                        | UploadState::AwaitingLogin
+                                        | UploadState::AwaitingNetwork => ui
+                                            .button(
+                                                egui::RichText::new(tr("main.cancel"))
+                                                    .size(FONTSIZE),
+                                            )
+                                            .clicked()
+                                            .then(|| {
+                                                AsyncRequest::CancelUpload(item.folder_path.clone())
+                                            }),
+                                        UploadState::Failed(_) => ui
+                                            .button(
+                                                egui::RichText::new(tr("main.retry"))
+                                                    .size(FONTSIZE),
+                                            )
+                                            .clicked()
+                                            .then(|| {
+                                                AsyncRequest::RetryUpload(item.folder_path.clone())
+                                            }),
+                                        UploadState::Done => None,
+                                    };
+                                    if let Some(request) = request {
+                                        app_state.async_request_tx.blocking_send(request).ok();
+                                    }
+
+                                    match &item.progress {
+                                        Some(_) if item.rate_limited_until.is_some() => {
+                                            let wait = item.rate_limited_until.map_or(
+                                                Duration::ZERO,
+                                                |until| {
+                                                    until.saturating_duration_since(Instant::now())
+                                                },
+                                            );
+                                            ui.label(
+                                                egui::RichText::new(tr_args(
+                                                    "main.server_busy",
+                                                    &[("seconds", &(wait.as_secs() + 1))],
+                                                ))
+                                                .size(12.0)
+                                                .color(theme::palette(ui.ctx()).warning),
+                                            );
+                                            // Keep the countdown ticking
+                                            ui.ctx().request_repaint_after(Duration::from_secs(1));
+                                        }
+                                        Some(progress) => {
+                                            ui.add(
+                                                egui::ProgressBar::new(
+                                                    progress.percent as f32 / 100.0,
+                                                )
+                                                .text(format!(
+                                                    "{:.0}% • {}/{} • {:.1} MB/s • ETA: {}",
+                                                    progress.percent,
+                                                    util::format_bytes(progress.bytes_uploaded),
+                                                    util::format_bytes(progress.total_bytes),
+                                                    progress.speed_mbps,
+                                                    util::format_seconds(
+                                                        progress.eta_seconds as u64
+                                                    ),
+                                                )),
+                                            );
+                                        }
+                                        None => {
+                                            ui.label(
+                                                egui::RichText::new(match item.state {
+                                                    UploadState::Pending => {
+                                                        tr("main.queue_waiting")
+                                                    }
+                                                    UploadState::Uploading => {
+                                                        tr("main.queue_starting")
+                                                    }
+                                                    UploadState::Done => tr("main.queue_uploaded"),
+                                                    UploadState::Failed(_) => {
+                                                        tr("main.queue_failed")
+                                                    }
+                                                    UploadState::AwaitingLogin
+                                                        if app_state
+                                                            .offline
+                                                            .load(Ordering::Relaxed) =>
+                                                    {
+                                                        tr("main.queue_pending_auth")
+                                                    }
+                                                    UploadState::AwaitingLogin
+                                                        if app_state
+                                                            .user_info
+                                                            .read()
+                                                            .unwrap()
+                                                            .as_ref()
+                                                            .is_some_and(|user_info| {
+                                                                user_info.is_client_outdated()
+                                                            }) =>
+                                                    {
+                                                        tr("main.queue_awaiting_update")
+                                                    }
+                                                    UploadState::AwaitingLogin => {
+                                                        tr("main.queue_awaiting_login")
+                                                    }
+                                                    UploadState::AwaitingNetwork => {
+                                                        tr("main.queue_awaiting_network")
+                                                    }
+                                                })
+                                                .size(FONTSIZE),
+                                            );
+                                        }
+                                    }
+                                },
+                            );
+                        });
+                    });
+            }
+        });
+}
+
+/// Something to be done with a local recording, asked for from the recordings list
+enum RecordingAction {
+    Preview(std::path::PathBuf),
+    Label(std::path::PathBuf),
+    /// Correct the labels of a recording that has already been uploaded
+    LabelUploaded {
+        upload_id: String,
+        labels: crate::output_types::SessionLabels,
+    },
+}
+
 fn unified_recordings_view(
     ui: &mut egui::Ui,
     uploads: Option<&[UserUpload]>,
     local_recordings: &[LocalRecording],
     app_state: &crate::app_state::AppState,
-) {
+) -> Option<RecordingAction> {
     const FONTSIZE: f32 = 13.0;
+    // What was asked for with one of the recordings' buttons, if anything
+    let mut action = None;
     egui::Frame::new()
         .inner_margin(egui::Margin {
             left: 4,
@@ -722,7 +1819,7 @@ fn unified_recordings_view(
                     .add_sized(
                         egui::vec2(ui.available_width(), button_height),
                         egui::Button::new(
-                            egui::RichText::new("Delete Invalid Recordings")
+                            egui::RichText::new(tr("main.delete_invalid_recordings"))
                                 .size(FONTSIZE)
                                 .color(egui::Color32::WHITE),
                         )
@@ -739,6 +1836,9 @@ fn unified_recordings_view(
                 ui.add_space(button_gap);
             }
 
+            // Without auto upload, recordings wait for the user rather than for the queue
+            let auto_upload = app_state.config.read().unwrap().preferences.auto_upload;
+
             // Merge and sort recordings
             let mut entries: Vec<RecordingEntry> = Vec::new();
 
@@ -755,7 +1855,7 @@ fn unified_recordings_view(
                 .auto_shrink([false, true])
                 .show(ui, |ui| {
                     if entries.is_empty() {
-                        ui.label("No recordings yet");
+                        ui.label(tr("main.no_recordings"));
                         return;
                     }
 
@@ -773,7 +1873,7 @@ fn unified_recordings_view(
                                             ui.label(
                                                 egui::RichText::new("✔")
                                                     .size(FONTSIZE)
-                                                    .color(egui::Color32::from_rgb(100, 255, 100)),
+                                                    .color(theme::palette(ui.ctx()).success),
                                             );
 
                                             // Filename
@@ -783,6 +1883,28 @@ fn unified_recordings_view(
                                             ui.with_layout(
                                                 egui::Layout::right_to_left(egui::Align::Center),
                                                 |ui| {
+                                                    // Label button, for correcting what it was uploaded with
+                                                    let labels = upload.labels();
+                                                    if ui
+                                                        .add(
+                                                            egui::Button::new(
+                                                                egui::RichText::new(tr("main.label")).size(FONTSIZE),
+                                                            )
+                                                            .min_size(egui::vec2(60.0, 20.0)),
+                                                        )
+                                                        .on_hover_text(if labels.is_empty() {
+                                                            tr("main.label_uploaded_tooltip").to_string()
+                                                        } else {
+                                                            labels_summary(&labels)
+                                                        })
+                                                        .clicked()
+                                                    {
+                                                        action = Some(RecordingAction::LabelUploaded {
+                                                            upload_id: upload.id.clone(),
+                                                            labels,
+                                                        });
+                                                    }
+
                                                     // Timestamp
                                                     let local_time =
                                                         upload.created_at.with_timezone(&chrono::Local);
@@ -847,7 +1969,7 @@ fn unified_recordings_view(
 
                                                     // Info icon with error tooltip
                                                     tooltip(ui,
-                                                        &std::iter::once("Validation errors:".to_string()).chain(error_reasons.iter().map(|reason| format!("• {reason}"))).collect::<Vec<_>>().join("\n"),
+                                                        &std::iter::once(tr("main.validation_errors").to_string()).chain(error_reasons.iter().map(|reason| format!("• {reason}"))).collect::<Vec<_>>().join("\n"),
                                                         Some(egui::Color32::from_rgb(255, 150, 150))
                                                     );
 
@@ -878,12 +2000,13 @@ fn unified_recordings_view(
                                                                 .add_sized(
                                                                     egui::vec2(60.0, 20.0),
                                                                     egui::Button::new(
-                                                                        egui::RichText::new("Delete")
+                                                                        egui::RichText::new(tr("main.delete"))
                                                                             .size(FONTSIZE)
                                                                             .color(egui::Color32::WHITE),
                                                                     )
                                                                     .fill(egui::Color32::from_rgb(180, 60, 60)),
                                                                 )
+                                                                .on_hover_text(tr("main.delete_tooltip"))
                                                                 .clicked()
                                                             {
                                                                 if let Err(e) =
@@ -912,7 +2035,7 @@ fn unified_recordings_view(
                                                 });
                                             });
                                     }
-                                    LocalRecording::Unuploaded { folder_name, folder_path, timestamp } => {
+                                    LocalRecording::Unuploaded { folder_name, folder_path, timestamp, duration, size_bytes, labels, test } => {
                                         // Unuploaded entry
                                         egui::Frame::new()
                                             .fill(egui::Color32::from_rgb(90, 80, 40))
@@ -952,7 +2075,15 @@ fn unified_recordings_view(
 
                                                     // "Pending upload" label
                                                     ui.label(
-                                                        egui::RichText::new("(pending upload)")
+                                                        egui::RichText::new(if *test {
+                                                            tr("main.test_recording")
+                                                        } else if app_state.offline.load(Ordering::Relaxed) {
+                                                            tr("main.pending_auth")
+                                                        } else if auto_upload {
+                                                            tr("main.pending_upload")
+                                                        } else {
+                                                            tr("main.not_uploaded")
+                                                        })
                                                             .size(FONTSIZE - 1.0)
                                                             .color(egui::Color32::from_rgb(200, 180, 100))
                                                             .italics()
@@ -985,7 +2116,7 @@ fn unified_recordings_view(
                                                                 .add_sized(
                                                                     egui::vec2(60.0, 20.0),
                                                                     egui::Button::new(
-                                                                        egui::RichText::new("Delete")
+                                                                        egui::RichText::new(tr("main.delete"))
                                                                             .size(FONTSIZE)
                                                                             .color(egui::Color32::WHITE),
                                                                     )
@@ -1014,6 +2145,100 @@ fn unified_recordings_view(
                                                                         .ok();
                                                                 }
                                                             }
+
+                                                            // Upload button, only once the recording has finished, and never for tests
+                                                            if ui
+                                                                .add_enabled(
+                                                                    duration.is_some() && !*test,
+                                                                    egui::Button::new(
+                                                                        egui::RichText::new(tr("main.upload")).size(FONTSIZE),
+                                                                    )
+                                                                    .min_size(egui::vec2(60.0, 20.0)),
+                                                                )
+                                                                .clicked()
+                                                            {
+                                                                app_state
+                                                                    .async_request_tx
+                                                                    .blocking_send(
+                                                                        crate::app_state::AsyncRequest::UploadRecording(
+                                                                            folder_path.clone(),
+                                                                        ),
+                                                                    )
+                                                                    .ok();
+                                                            }
+
+                                                            // Export button, for uploading from another machine; like uploading, never for tests
+                                                            if ui
+                                                                .add_enabled(
+                                                                    duration.is_some() && !*test,
+                                                                    egui::Button::new(
+                                                                        egui::RichText::new(tr("main.export")).size(FONTSIZE),
+                                                                    )
+                                                                    .min_size(egui::vec2(60.0, 20.0)),
+                                                                )
+                                                                .on_hover_text(tr("main.export_tooltip"))
+                                                                .clicked()
+                                                                && let Some(archive_path) = rfd::FileDialog::new()
+                                                                    .add_filter("Recording archive", &["tar"])
+                                                                    .set_file_name(format!("{folder_name}.tar"))
+                                                                    .save_file()
+                                                            {
+                                                                app_state
+                                                                    .async_request_tx
+                                                                    .blocking_send(
+                                                                        crate::app_state::AsyncRequest::ExportRecording {
+                                                                            folder_path: folder_path.clone(),
+                                                                            archive_path,
+                                                                        },
+                                                                    )
+                                                                    .ok();
+                                                            }
+
+                                                            // Preview button, also only once the recording has finished
+                                                            if ui
+                                                                .add_enabled(
+                                                                    duration.is_some(),
+                                                                    egui::Button::new(
+                                                                        egui::RichText::new(tr("main.preview")).size(FONTSIZE),
+                                                                    )
+                                                                    .min_size(egui::vec2(60.0, 20.0)),
+                                                                )
+                                                                .clicked()
+                                                            {
+                                                                action = Some(RecordingAction::Preview(folder_path.clone()));
+                                                            }
+
+                                                            // Label button, also only once the recording has finished, as the labels go in its metadata
+                                                            if ui
+                                                                .add_enabled(
+                                                                    duration.is_some(),
+                                                                    egui::Button::new(
+                                                                        egui::RichText::new(tr("main.label")).size(FONTSIZE),
+                                                                    )
+                                                                    .min_size(egui::vec2(60.0, 20.0)),
+                                                                )
+                                                                .on_hover_text(labels.as_ref().map_or_else(
+                                                                    || tr("main.label_tooltip").to_string(),
+                                                                    labels_summary,
+                                                                ))
+                                                                .clicked()
+                                                            {
+                                                                action = Some(RecordingAction::Label(folder_path.clone()));
+                                                            }
+
+                                                            // File size
+                                                            ui.label(
+                                                                egui::RichText::new(util::format_bytes(*size_bytes))
+                                                                    .size(FONTSIZE),
+                                                            );
+
+                                                            // Duration, once the recording has finished
+                                                            if let Some(duration) = duration {
+                                                                ui.label(
+                                                                    egui::RichText::new(format!("{duration:.1}s"))
+                                                                        .size(FONTSIZE),
+                                                                );
+                                                            }
                                                         },
                                                     );
                                                 });
@@ -1026,75 +2251,66 @@ fn unified_recordings_view(
                     }
                 });
         });
+    action
 }
 
-fn encoder_settings_window(ui: &mut egui::Ui, encoder_settings: &mut EncoderSettings) {
-    match encoder_settings.encoder {
-        VideoEncoderType::X264 => encoder_settings_x264(ui, &mut encoder_settings.x264),
-        VideoEncoderType::NvEnc => encoder_settings_nvenc(ui, &mut encoder_settings.nvenc),
+/// The labels, one kind per line
+fn labels_summary(labels: &crate::output_types::SessionLabels) -> String {
+    let mut lines = vec![];
+    if let Some(game_title) = &labels.game_title {
+        lines.push(tr_args("main.labels_game", &[("game", game_title)]));
     }
+    if let Some(genre) = &labels.genre {
+        lines.push(tr_args("main.labels_genre", &[("genre", genre)]));
+    }
+    if !labels.tags.is_empty() {
+        lines.push(tr_args(
+            "main.labels_tags",
+            &[("tags", &labels.tags.join(", "))],
+        ));
+    }
+    if let Some(notes) = &labels.notes {
+        lines.push(tr_args("main.labels_notes", &[("notes", notes)]));
+    }
+    lines.join("\n")
 }
 
 const PRESET_TOOLTIP: &str = "Please keep this as high as possible for best quality; only reduce it if you're experiencing performance issues.";
 
-fn encoder_settings_x264(ui: &mut egui::Ui, x264_settings: &mut ObsX264Settings) {
-    dropdown_list(
-        ui,
-        "Preset:",
-        constants::encoding::X264_PRESETS,
-        &mut x264_settings.preset,
-        |ui| {
-            tooltip(ui, PRESET_TOOLTIP, None);
-        },
-    );
-}
-
-fn encoder_settings_nvenc(ui: &mut egui::Ui, nvenc_settings: &mut FfmpegNvencSettings) {
-    dropdown_list(
-        ui,
-        "Preset:",
-        constants::encoding::NVENC_PRESETS,
-        &mut nvenc_settings.preset2,
-        |ui| {
-            tooltip(ui, PRESET_TOOLTIP, None);
-        },
-    );
-
-    ui.add_space(5.0);
-    dropdown_list(
-        ui,
-        "Tune:",
-        constants::encoding::NVENC_TUNE_OPTIONS,
-        &mut nvenc_settings.tune,
-        |_| {},
-    );
-}
-
-fn tooltip(ui: &mut egui::Ui, text: &str, error_override: Option<egui::Color32>) {
+pub(super) fn tooltip(ui: &mut egui::Ui, text: &str, error_override: Option<egui::Color32>) {
     ui.add(egui::Label::new(egui::RichText::new("ℹ").color(
-        error_override.unwrap_or(egui::Color32::from_rgb(128, 128, 128)),
+        error_override.unwrap_or(theme::palette(ui.ctx()).muted),
     )))
+    // Screen readers would otherwise just read out the icon
+    .widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, true, text))
     .on_hover_cursor(egui::CursorIcon::Help)
     .on_hover_text(text);
 }
 
-fn dropdown_list(
-    ui: &mut egui::Ui,
-    label: &str,
-    options: &[&str],
-    selected: &mut String,
-    add_content: impl FnOnce(&mut egui::Ui),
-) -> egui::Response {
-    ui.horizontal(|ui| {
-        ui.label(label);
-        egui::ComboBox::from_id_salt(label)
-            .selected_text(selected.as_str())
-            .show_ui(ui, |ui| {
-                for option in options {
-                    ui.selectable_value(selected, option.to_string(), *option);
-                }
-            });
-        add_content(ui);
-    })
-    .response
+fn connection_test_result(ui: &mut egui::Ui, result: &ConnectionTest) {
+    let palette = theme::palette(ui.ctx());
+    let mut step = |name: &str, outcome: Result<&Duration, String>| {
+        ui.label(match outcome {
+            Ok(latency) => egui::RichText::new(tr_args(
+                "main.connection_test_ok",
+                &[("step", &name), ("latency", &latency.as_millis())],
+            ))
+            .color(palette.success),
+            Err(e) => egui::RichText::new(tr_args(
+                "main.connection_test_failed",
+                &[("step", &name), ("error", &e)],
+            ))
+            .color(palette.error),
+        });
+    };
+    step(
+        tr("main.connection_test_api_key"),
+        result.api_key.as_ref().map_err(|e| e.to_string()),
+    );
+    if let Some(upload) = &result.upload {
+        step(
+            tr("main.connection_test_uploads"),
+            upload.as_ref().map_err(String::clone),
+        );
+    }
 }
@@ -1,3 +1,14 @@
+pub mod benchmark;
+pub mod capture_region;
 pub mod consent;
+pub mod encryption;
+pub mod hud;
 pub mod login;
+pub mod logs;
 pub mod main;
+pub mod onboarding;
+pub mod preview;
+pub mod s3_destination;
+pub mod settings;
+pub mod summary;
+pub mod tagging;
@@ -0,0 +1,143 @@
+use crate::ui::{HEADING_TEXT_SIZE, MainApp, SUBHEADING_TEXT_SIZE, theme};
+
+/// Number of pages in the onboarding sequence
+const PAGE_COUNT: usize = 4;
+
+impl MainApp {
+    /// Shown on first run, before the login screen. The formal consent form is still shown after logging in;
+    /// this just explains what the user is about to agree to.
+    pub fn onboarding_view(&mut self, ctx: &egui::Context, page: usize) {
+        let padding = 8;
+        let button_font_size = 14.0;
+
+        egui::TopBottomPanel::top("onboarding_panel_top").show(ctx, |ui| {
+            egui::Frame::new()
+                .inner_margin(egui::Margin::same(padding))
+                .show(ui, |ui| {
+                    ui.heading(
+                        egui::RichText::new("Welcome to OWL Control")
+                            .size(HEADING_TEXT_SIZE)
+                            .strong(),
+                    );
+                    ui.label(
+                        egui::RichText::new(format!("Step {} of {PAGE_COUNT}", page + 1))
+                            .size(SUBHEADING_TEXT_SIZE)
+                            .color(theme::palette(ui.ctx()).subheading),
+                    );
+                });
+        });
+
+        egui::TopBottomPanel::bottom("onboarding_panel_bottom").show(ctx, |ui| {
+            egui::Frame::new()
+                .inner_margin(egui::Margin::same(padding))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.skip_onboarding_next_time, "Don't show this again");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.spacing_mut().button_padding = egui::vec2(8.0, 2.0);
+                            let is_last_page = page + 1 == PAGE_COUNT;
+                            if ui
+                                .button(
+                                    egui::RichText::new(if is_last_page {
+                                        "Get Started"
+                                    } else {
+                                        "Next"
+                                    })
+                                    .size(button_font_size)
+                                    .strong(),
+                                )
+                                .clicked()
+                            {
+                                if is_last_page {
+                                    self.finish_onboarding();
+                                } else {
+                                    self.onboarding_page = Some(page + 1);
+                                }
+                            }
+                            if page > 0
+                                && ui
+                                    .button(
+                                        egui::RichText::new("Back").size(button_font_size).strong(),
+                                    )
+                                    .clicked()
+                            {
+                                self.onboarding_page = Some(page - 1);
+                            }
+                        });
+                    });
+                });
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::Frame::new()
+                .inner_margin(egui::Margin::same(padding))
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| match page {
+                        0 => page_content(
+                            ui,
+                            "What is OWL Control?",
+                            &[
+                                "OWL Control records your gameplay, along with your keyboard and mouse inputs, as part of a research study run by Wayfarer Labs.",
+                                "The recordings are used to train world models and AI agents that learn to play games. The curated dataset will be open sourced for research.",
+                                "Participation is voluntary, and there's no minimum amount you have to record.",
+                            ],
+                        ),
+                        1 => page_content(
+                            ui,
+                            "What's collected",
+                            &[
+                                "Video and game audio of the full screen game you're playing. Microphone audio is never recorded, and overlays are avoided.",
+                                "Your keyboard and mouse inputs while the game is being recorded.",
+                                "Details about the recording, such as the game, its window title and resolution, and the encoder settings used.",
+                                "Recordings are processed and anonymized before release, and you can ask for your data to be deleted at any time. The full terms are shown for you to accept after you log in.",
+                                "Anti-cheat systems may flag OWL Control, so we strongly recommend only recording single-player games.",
+                            ],
+                        ),
+                        2 => {
+                            let start_key = self.local_preferences.start_recording_key.clone();
+                            let pause_key = self.local_preferences.pause_recording_key.clone();
+                            page_content(
+                                ui,
+                                "How to record",
+                                &[
+                                    &format!("Start a game, then press {start_key} to start recording. Press {start_key} again to stop."),
+                                    &format!("Press {pause_key} to pause and resume recording without ending it."),
+                                    "Recording pauses by itself when you stop playing for a while, and picks up again when you continue.",
+                                    "Hotkeys, the overlay and the recording quality can all be changed in the settings.",
+                                ],
+                            )
+                        }
+                        _ => {
+                            let recording_location = self.app_state.recording_location();
+                            page_content(
+                                ui,
+                                "Where recordings go",
+                                &[
+                                    &format!(
+                                        "Recordings are saved to {} until they're uploaded. The folder can be changed in the settings.",
+                                        recording_location.display()
+                                    ),
+                                    "Nothing leaves your computer until you upload it, and you can review or delete recordings before then.",
+                                    "To get started, you'll need the API key from your account on our website.",
+                                ],
+                            )
+                        }
+                    });
+                });
+        });
+    }
+
+    fn finish_onboarding(&mut self) {
+        self.onboarding_page = None;
+        self.local_preferences.show_onboarding = !self.skip_onboarding_next_time;
+    }
+}
+
+fn page_content(ui: &mut egui::Ui, title: &str, paragraphs: &[&str]) {
+    ui.label(egui::RichText::new(title).size(18.0).strong());
+    ui.add_space(8.0);
+    for paragraph in paragraphs {
+        ui.label(*paragraph);
+        ui.add_space(6.0);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    app_state::AsyncRequest,
+    record::{Button, Playback, VideoDecoder},
+    system::keycode::keycode_name,
+    ui::{MainApp, theme},
+};
+
+#[derive(Default)]
+pub(crate) struct PreviewViewState {
+    /// Whether the preview window is open
+    pub(super) open: bool,
+    /// `None` while the recording is loading
+    playback: Option<Result<Playback, String>>,
+    /// Seconds into the recording
+    position: f64,
+    playing: bool,
+    /// Started once the recording has loaded, if it has a video to show
+    video: Option<VideoDecoder>,
+    /// The frame on show, with when in the video it's from
+    video_texture: Option<(f64, egui::TextureHandle)>,
+}
+impl PreviewViewState {
+    pub(crate) fn playback_loaded(&mut self, result: Result<Playback, String>) {
+        self.playback = Some(result);
+    }
+
+    /// Opens the window and starts loading the recording at `folder_path`
+    pub(super) fn load(
+        &mut self,
+        app_state: &crate::app_state::AppState,
+        folder_path: &std::path::Path,
+    ) {
+        *self = Self {
+            open: true,
+            ..Default::default()
+        };
+        app_state
+            .async_request_tx
+            .blocking_send(AsyncRequest::LoadPlayback(folder_path.to_owned()))
+            .ok();
+    }
+}
+
+impl MainApp {
+    pub(super) fn preview_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.preview_view_state;
+        let app_state = &self.app_state;
+        egui::Window::new("Preview Recording")
+            .open(&mut state.open)
+            .default_size(egui::vec2(700.0, 500.0))
+            .show(ctx, |ui| {
+                let playback = match &state.playback {
+                    None => {
+                        ui.spinner();
+                        return;
+                    }
+                    Some(Err(e)) => {
+                        ui.label(
+                            egui::RichText::new(format!("Couldn't load the recording: {e}"))
+                                .color(theme::palette(ui.ctx()).error),
+                        );
+                        return;
+                    }
+                    Some(Ok(playback)) => playback,
+                };
+
+                if state.video.is_none()
+                    && let Some(video_path) = &playback.video_path
+                {
+                    let ctx = ui.ctx().clone();
+                    state.video = Some(VideoDecoder::spawn(video_path.clone(), move || {
+                        ctx.request_repaint()
+                    }));
+                }
+
+                if state.playing {
+                    state.position += ui.input(|i| i.stable_dt) as f64;
+                    if state.position >= playback.duration {
+                        state.position = playback.duration;
+                        state.playing = false;
+                    }
+                    ui.ctx().request_repaint();
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button(if state.playing { "Pause" } else { "Play" }).clicked() {
+                        if !state.playing && state.position >= playback.duration {
+                            state.position = 0.0;
+                        }
+                        state.playing = !state.playing;
+                    }
+                    ui.label(format!(
+                        "{} / {}",
+                        format_time(state.position),
+                        format_time(playback.duration)
+                    ));
+                    if let Some(video_path) = &playback.video_path
+                        && ui.button("Open Video").clicked()
+                    {
+                        // Opens in the default video player
+                        app_state
+                            .async_request_tx
+                            .blocking_send(AsyncRequest::OpenFolder(video_path.clone()))
+                            .ok();
+                    }
+                });
+                ui.spacing_mut().slider_width = ui.available_width();
+                if ui
+                    .add(egui::Slider::new(&mut state.position, 0.0..=playback.duration).show_value(false))
+                    .dragged()
+                {
+                    state.playing = false;
+                }
+                ui.label(
+                    egui::RichText::new(
+                        "The recording's video, with the cursor and the mouse's movement drawn over it, \
+                         to check that the inputs line up with what happened in the game.",
+                    )
+                    .size(12.0)
+                    .color(theme::palette(ui.ctx()).muted),
+                );
+                ui.separator();
+
+                let input = playback.state_at(state.position);
+                let held = input
+                    .held
+                    .iter()
+                    .map(|button| match button {
+                        Button::Key(key) => keycode_name(*key)
+                            .map(str::to_uppercase)
+                            .unwrap_or_else(|| format!("Key {key}")),
+                        Button::Mouse(button) => format!("Mouse {button}"),
+                    })
+                    .collect::<Vec<_>>();
+                ui.label(if held.is_empty() {
+                    "Held: nothing".to_string()
+                } else {
+                    format!("Held: {}", held.join(" + "))
+                });
+
+                // The game window, scaled to fit
+                let (width, height) = playback.game_resolution.unwrap_or((16, 9));
+                let available = ui.available_size();
+                let scale = (available.x / width as f32).min(available.y / height as f32);
+                let (rect, _) = ui.allocate_exact_size(
+                    egui::vec2(width as f32 * scale, height as f32 * scale),
+                    egui::Sense::hover(),
+                );
+                let painter = ui.painter_at(rect);
+                let stroke_color = ui.visuals().text_color();
+                painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+                if let Some(video) = &state.video {
+                    video.seek(state.position);
+                    match &*video.frame() {
+                        Some(Ok(frame))
+                            if state
+                                .video_texture
+                                .as_ref()
+                                .is_none_or(|(time, _)| *time != frame.time) =>
+                        {
+                            let image = egui::ColorImage::from_rgba_unmultiplied(
+                                [frame.width as usize, frame.height as usize],
+                                &frame.rgba,
+                            );
+                            match &mut state.video_texture {
+                                Some((time, texture)) => {
+                                    texture.set(image, egui::TextureOptions::LINEAR);
+                                    *time = frame.time;
+                                }
+                                None => {
+                                    let texture = ui.ctx().load_texture(
+                                        "playback_video",
+                                        image,
+                                        egui::TextureOptions::LINEAR,
+                                    );
+                                    state.video_texture = Some((frame.time, texture));
+                                }
+                            }
+                        }
+                        Some(Err(e)) => {
+                            painter.text(
+                                rect.center(),
+                                egui::Align2::CENTER_CENTER,
+                                format!("Couldn't play the video: {e}"),
+                                egui::FontId::proportional(12.0),
+                                theme::palette(ui.ctx()).error,
+                            );
+                        }
+                        _ => {}
+                    }
+                }
+                if playback.video_path.is_none() {
+                    painter.text(
+                        rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "The video is missing or encrypted, so only the inputs are shown",
+                        egui::FontId::proportional(12.0),
+                        theme::palette(ui.ctx()).muted,
+                    );
+                }
+                if let Some((_, texture)) = &state.video_texture {
+                    painter.image(
+                        texture.id(),
+                        rect,
+                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                        egui::Color32::WHITE,
+                    );
+                }
+
+                let (dx, dy) = input.movement;
+                if (dx, dy) != (0, 0) {
+                    painter.arrow(
+                        rect.center(),
+                        egui::vec2(dx as f32, dy as f32) * scale,
+                        egui::Stroke::new(2.0, theme::palette(ui.ctx()).warning),
+                    );
+                }
+                if let Some((x, y)) = input.cursor {
+                    let position = rect.min + egui::vec2(x as f32, y as f32) * scale;
+                    painter.circle_stroke(position, 6.0, egui::Stroke::new(2.0, stroke_color));
+                }
+            });
+        if !state.open {
+            state.playing = false;
+            // Stops decoding
+            state.video = None;
+            state.video_texture = None;
+        }
+    }
+}
+
+/// Formats seconds as minutes, seconds and tenths (e.g. "1:02.5")
+fn format_time(seconds: f64) -> String {
+    let tenths = (seconds * 10.0) as u64;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
@@ -0,0 +1,103 @@
+use crate::{
+    system::credential_store::ProtectedSecret,
+    ui::{MainApp, theme, views::main::tooltip},
+};
+
+#[derive(Default)]
+pub(crate) struct S3DestinationViewState {
+    /// Whether the S3 bucket window is open
+    pub(super) open: bool,
+    /// The secret access key being typed in. It's only kept (encrypted) once it's been entered.
+    secret_access_key: String,
+    /// Why the secret access key couldn't be kept
+    secret_error: Option<String>,
+}
+
+impl MainApp {
+    pub(super) fn s3_destination_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.s3_destination_view_state;
+        let destination = &mut self.local_preferences.s3_destination;
+        egui::Window::new("S3 Bucket")
+            .open(&mut state.open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "Recordings are also uploaded to this bucket, which can be on AWS or any S3-compatible service (e.g. Cloudflare R2 or MinIO).",
+                    )
+                    .small()
+                    .color(theme::palette(ctx).muted),
+                );
+                ui.add_space(6.0);
+
+                egui::Grid::new("s3_destination")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        let text_row = |ui: &mut egui::Ui, label: &str, value: &mut String, hint: &str| {
+                            let label = ui.label(label);
+                            ui.add(
+                                egui::TextEdit::singleline(value)
+                                    .desired_width(260.0)
+                                    .hint_text(hint),
+                            )
+                            .labelled_by(label.id);
+                            ui.end_row();
+                        };
+                        text_row(ui, "Endpoint:", &mut destination.endpoint, "https://s3.us-east-1.amazonaws.com");
+                        text_row(ui, "Region:", &mut destination.region, "us-east-1");
+                        text_row(ui, "Bucket:", &mut destination.bucket, "my-recordings");
+                        text_row(ui, "Key Prefix:", &mut destination.key_prefix, "recordings/");
+                        text_row(ui, "Access Key ID:", &mut destination.access_key_id, "");
+
+                        let label = ui.label("Secret Access Key:");
+                        let response = ui
+                            .add(
+                                egui::TextEdit::singleline(&mut state.secret_access_key)
+                                    .password(true)
+                                    .desired_width(260.0)
+                                    .hint_text(if destination.secret_access_key.is_empty() {
+                                        ""
+                                    } else {
+                                        "Saved; type a new one to replace it"
+                                    }),
+                            )
+                            .labelled_by(label.id);
+                        if response.lost_focus() && !state.secret_access_key.trim().is_empty() {
+                            match ProtectedSecret::protect(state.secret_access_key.trim()) {
+                                Ok(secret) => {
+                                    destination.secret_access_key = secret;
+                                    state.secret_error = None;
+                                }
+                                Err(e) => {
+                                    tracing::error!(e=?e, "Failed to protect the S3 secret access key");
+                                    state.secret_error = Some(format!("Couldn't keep the key safely: {e:#}"));
+                                }
+                            }
+                            state.secret_access_key.clear();
+                        }
+                        ui.end_row();
+                    });
+                if let Some(error) = &state.secret_error {
+                    ui.label(egui::RichText::new(error).color(theme::palette(ctx).error));
+                }
+
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut destination.enabled, "Upload to this bucket");
+                    tooltip(
+                        ui,
+                        "Each recording is uploaded as one archive, named after its folder. The bucket checks the archive's SHA-256 before keeping it, so only uploads that arrived intact count as done. The secret access key is encrypted so that only your Windows account can read it.",
+                        None,
+                    );
+                });
+                if destination.enabled && !destination.is_usable() {
+                    ui.label(
+                        egui::RichText::new("Fill in everything but the key prefix to upload to the bucket.")
+                            .color(theme::palette(ctx).warning),
+                    );
+                }
+            });
+    }
+}
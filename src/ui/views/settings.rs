@@ -0,0 +1,1382 @@
+use std::{path::PathBuf, sync::atomic::Ordering, time::Instant};
+
+use crate::{
+    app_state::AsyncRequest,
+    config::{
+        AmfSettings, CaptureSetting, CaptureWindow, FfmpegNvencSettings, Hotkey, ObsQsvSettings,
+        ObsX264Settings, Preferences,
+    },
+    record,
+    system::{
+        hardware_specs::{self, MonitorInfo},
+        keycode::is_common_game_key,
+    },
+    ui::{
+        MainApp,
+        i18n::tr,
+        theme,
+        views::{capture_region::capture_region_summary, main::tooltip},
+    },
+};
+
+use constants::{
+    GH_ORG, GH_REPO,
+    encoding::{VideoCodec, VideoEncoderType},
+};
+
+pub(super) const SETTINGS_TEXT_WIDTH: f32 = 150.0;
+pub(super) const SETTINGS_TEXT_HEIGHT: f32 = 20.0;
+
+pub(super) fn add_settings_text(ui: &mut egui::Ui, widget: impl egui::Widget) -> egui::Response {
+    ui.allocate_ui_with_layout(
+        egui::vec2(SETTINGS_TEXT_WIDTH, SETTINGS_TEXT_HEIGHT),
+        egui::Layout {
+            main_dir: egui::Direction::LeftToRight,
+            main_wrap: false,
+            main_align: egui::Align::RIGHT,
+            main_justify: true,
+            cross_align: egui::Align::Center,
+            cross_justify: true,
+        },
+        |ui| ui.add(widget),
+    )
+    .inner
+}
+
+pub(super) fn add_settings_ui<R>(
+    ui: &mut egui::Ui,
+    add_contents: impl FnOnce(&mut egui::Ui) -> R,
+) -> egui::InnerResponse<R> {
+    ui.allocate_ui_with_layout(
+        egui::vec2(ui.available_width(), SETTINGS_TEXT_HEIGHT),
+        egui::Layout {
+            main_dir: egui::Direction::LeftToRight,
+            main_wrap: false,
+            main_align: egui::Align::LEFT,
+            main_justify: true,
+            cross_align: egui::Align::Center,
+            cross_justify: true,
+        },
+        add_contents,
+    )
+}
+
+pub(super) fn add_settings_widget(ui: &mut egui::Ui, widget: impl egui::Widget) -> egui::Response {
+    add_settings_ui(ui, |ui| ui.add(widget)).inner
+}
+
+#[derive(Default)]
+pub(crate) struct SettingsViewState {
+    /// Problem with the recording folder that was just picked, shown until another one is picked.
+    /// `Err` if the folder can't be used, `Ok` if it can but probably shouldn't be.
+    recording_location_notice: Option<Result<String, String>>,
+    /// The capture denylist as it's being edited, one entry per line
+    capture_denylist_text: Option<String>,
+    /// The recording name template as it's being edited. It's only saved while it's valid.
+    recording_name_template_text: Option<String>,
+    /// Looked up while the capture monitor list is open, and refreshed every so often in case what's on
+    /// the screens or plugged in has changed
+    capture_monitors: Option<CaptureMonitorList>,
+    /// Like `capture_monitors`, the applications that can be picked as the capture window, and when they
+    /// were looked up
+    capture_windows: Option<(Instant, Vec<CaptureWindow>)>,
+}
+
+/// The applications with a window open, frontmost first, one entry per application
+fn open_capture_windows() -> Vec<CaptureWindow> {
+    let mut windows: Vec<CaptureWindow> = vec![];
+    for (hwnd, pid) in game_process::titled_windows() {
+        if pid.0 == std::process::id() {
+            continue;
+        }
+        let (Some(title), Ok(exe_path)) = (
+            game_process::window_title(hwnd),
+            game_process::exe_name_for_pid(pid),
+        ) else {
+            continue;
+        };
+        let Some(exe) = exe_path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !windows.iter().any(|window| window.matches(exe)) {
+            windows.push(CaptureWindow {
+                exe: exe.to_string(),
+                title,
+            });
+        }
+    }
+    windows
+}
+
+/// How an application is described in the capture window list
+fn capture_window_text(window: &CaptureWindow) -> String {
+    format!("{} ({})", window.title, window.exe)
+}
+
+/// How often the capture monitor and window lists are looked up again while they're open
+const CAPTURE_TARGET_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+/// Width of each monitor's thumbnail in the capture monitor list
+const CAPTURE_MONITOR_THUMBNAIL_WIDTH: u32 = 96;
+
+struct CaptureMonitorList {
+    loaded_at: Instant,
+    monitors: Vec<(MonitorInfo, Option<egui::TextureHandle>)>,
+}
+impl CaptureMonitorList {
+    fn load(ctx: &egui::Context) -> Self {
+        let monitors = hardware_specs::get_monitors()
+            .into_iter()
+            .map(|monitor| {
+                let thumbnail = hardware_specs::get_screen_thumbnail(
+                    monitor.rect,
+                    CAPTURE_MONITOR_THUMBNAIL_WIDTH,
+                )
+                .map(|thumbnail| {
+                    ctx.load_texture(
+                        format!("capture_monitor_{}", monitor.name),
+                        egui::ColorImage::from_rgba_unmultiplied(
+                            [thumbnail.width as usize, thumbnail.height as usize],
+                            &thumbnail.rgba,
+                        ),
+                        egui::TextureOptions::LINEAR,
+                    )
+                });
+                (monitor, thumbnail)
+            })
+            .collect();
+        Self {
+            loaded_at: Instant::now(),
+            monitors,
+        }
+    }
+}
+
+/// How a monitor is described in the capture monitor list
+fn capture_monitor_text(monitor: &MonitorInfo) -> String {
+    format!(
+        "{} ({}×{}){}",
+        monitor.name,
+        monitor.rect.width,
+        monitor.rect.height,
+        if monitor.primary { ", primary" } else { "" }
+    )
+}
+
+impl MainApp {
+    /// The hotkey and recorder settings. `refresh_rate` is the primary monitor's, if known.
+    pub(super) fn settings_sections(&mut self, ui: &mut egui::Ui, refresh_rate: Option<u32>) {
+        // Keyboard Shortcuts Section
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new("Keyboard Shortcuts")
+                        .size(18.0)
+                        .strong(),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    tooltip(ui, "Tip: You can set separate hotkeys for starting and stopping recording. By default, the start key will toggle recording.", None);
+                    if ui.button("Reset All").clicked() {
+                        self.local_preferences.reset_hotkeys();
+                        self.listening_for_hotkey_rebind = None;
+                        self.rejected_hotkey = None;
+                    }
+                    if ui.button("❓ Reference").clicked() {
+                        self.hotkey_reference_window_open = true;
+                    }
+                });
+            });
+            ui.separator();
+
+            let stop_hotkey_enabled = self.local_preferences.stop_hotkey_enabled;
+            for hotkey in Hotkey::ALL {
+                if !self.local_preferences.is_hotkey_enabled(hotkey) {
+                    continue;
+                }
+                ui.horizontal(|ui| {
+                    let label = add_settings_text(
+                        ui,
+                        egui::Label::new(hotkey_label(hotkey, stop_hotkey_enabled)),
+                    );
+                    let key = self.local_preferences.hotkey(hotkey).to_string();
+                    let button_text = if self.listening_for_hotkey_rebind == Some(hotkey) {
+                        rebind_prompt(self.rejected_hotkey.as_deref())
+                    } else {
+                        key.clone()
+                    };
+
+                    add_settings_ui(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.button(button_text).labelled_by(label.id).clicked() {
+                                self.listening_for_hotkey_rebind = Some(hotkey);
+                                self.rejected_hotkey = None;
+                            }
+                            let default_key = hotkey.default_key();
+                            if key != default_key
+                                && ui
+                                    .small_button("↺")
+                                    .on_hover_text(format!("Reset to {default_key}"))
+                                    .clicked()
+                            {
+                                *self.local_preferences.hotkey_mut(hotkey) = default_key;
+                            }
+                            common_game_key_warning(ui, &key);
+                            hotkey_conflict_warning(
+                                ui,
+                                &key,
+                                &self.local_preferences.hotkey_conflicts(hotkey),
+                                stop_hotkey_enabled,
+                            );
+                            match hotkey {
+                                Hotkey::PrivacyPause => tooltip(
+                                    ui,
+                                    "Press this if something private is about to appear on screen. Recording is paused straight away, and stays paused until you press the pause key, including after a window listed under \"Pause Recording For\" loses focus.",
+                                    None,
+                                ),
+                                Hotkey::SaveReplay => tooltip(
+                                    ui,
+                                    "Keeps what instant replay has captured so far as a recording. Only used when instant replay is on.",
+                                    None,
+                                ),
+                                _ => {}
+                            }
+                        });
+                    });
+                });
+            }
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Stop Hotkey:"));
+                add_settings_widget(
+                    ui,
+                    egui::Checkbox::new(
+                        &mut self.local_preferences.stop_hotkey_enabled,
+                        match stop_hotkey_enabled {
+                            true => "Enabled",
+                            false => "Disabled",
+                        },
+                    ),
+                );
+            });
+        });
+        ui.add_space(10.0);
+
+        // Overlay Settings Section
+        ui.group(|ui| {
+            ui.label(
+                egui::RichText::new("Recorder Customization")
+                    .size(18.0)
+                    .strong(),
+            );
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Theme:"));
+                add_settings_ui(ui, |ui| {
+                    egui::ComboBox::from_id_salt("theme")
+                        .selected_text(self.local_preferences.theme.to_string())
+                        .show_ui(ui, |ui| {
+                            for theme in crate::config::Theme::ALL {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.theme,
+                                    theme,
+                                    theme.to_string(),
+                                );
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new(tr("settings.language")));
+                add_settings_ui(ui, |ui| {
+                    egui::ComboBox::from_id_salt("language")
+                        .selected_text(self.local_preferences.language.to_string())
+                        .show_ui(ui, |ui| {
+                            for language in crate::config::Language::ALL {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.language,
+                                    language,
+                                    language.to_string(),
+                                );
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Update Checks:"));
+                add_settings_ui(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(
+                            &mut self.local_preferences.check_for_updates,
+                            "Check for new versions on startup",
+                        );
+                        tooltip(
+                            ui,
+                            "Older versions may record data that can't be used, so it's best to keep this on and update when you're told about a new version.",
+                            None,
+                        );
+                    });
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Anonymous Statistics:"));
+                add_settings_ui(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(
+                            &mut self.local_preferences.telemetry,
+                            "Share dropped frame and upload statistics",
+                        );
+                        ui.hyperlink_to(
+                            "What's collected",
+                            format!("https://github.com/{GH_ORG}/{GH_REPO}/blob/main/TELEMETRY.md"),
+                        );
+                        tooltip(
+                            ui,
+                            "Helps us find performance problems on setups we can't test ourselves. Only counts and totals are sent, once an hour, without your API key or anything about what you recorded.",
+                            None,
+                        );
+                    });
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Recording Overlay:"));
+                add_settings_widget(
+                    ui,
+                    egui::Checkbox::new(
+                        &mut self.local_preferences.show_overlay,
+                        "Show over games (not included in recordings)",
+                    ),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Recording Preview:"));
+                add_settings_ui(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(
+                            &mut self.local_preferences.show_recording_preview,
+                            "Show what's being recorded in this window",
+                        );
+                        tooltip(
+                            ui,
+                            "A small copy of the recording, updated a few times a second, so that you can tell the game is being captured. Turn it off to save a little CPU time. Not available when recording with OBS Studio, which shows its own preview.",
+                            None,
+                        );
+                    });
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Overlay Location:"));
+                add_settings_ui(ui, |ui| {
+                    egui::ComboBox::from_id_salt("overlay_location")
+                        .selected_text(self.local_preferences.overlay_location.to_string())
+                        .show_ui(ui, |ui| {
+                            for location in crate::config::OverlayLocation::ALL {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.overlay_location,
+                                    location,
+                                    location.to_string(),
+                                );
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Overlay Opacity:"));
+                let mut stored_opacity = self.local_preferences.overlay_opacity;
+
+                let mut egui_opacity = stored_opacity as f32 / 255.0 * 100.0;
+
+                let r = ui
+                    .scope(|ui| {
+                        // one day egui will make sliders respect their width properly
+                        ui.spacing_mut().slider_width = ui.available_width() - 50.0;
+                        add_settings_widget(
+                            ui,
+                            egui::Slider::new(&mut egui_opacity, 0.0..=100.0)
+                                .suffix("%")
+                                .integer(),
+                        )
+                    })
+                    .inner;
+                if r.changed() {
+                    stored_opacity = (egui_opacity / 100.0 * 255.0) as u8;
+                    self.local_preferences.overlay_opacity = stored_opacity;
+                }
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Test Recording:"));
+                add_settings_ui(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let mut test_recording =
+                            self.app_state.test_recording.load(Ordering::Relaxed);
+                        if ui
+                            .checkbox(&mut test_recording, "Keep new recordings local")
+                            .changed()
+                        {
+                            self.app_state
+                                .test_recording
+                                .store(test_recording, Ordering::Relaxed);
+                        }
+                        tooltip(
+                            ui,
+                            "For trying out your settings. Recordings are made exactly as usual, but are marked as tests and never uploaded, so they don't count towards your quota or the dataset. Turns itself off when OWL Control is closed.",
+                            None,
+                        );
+                    });
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Compact Window:"));
+                add_settings_ui(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(
+                            &mut self.local_preferences.compact_hud,
+                            "Shrink to a small HUD while recording",
+                        );
+                        tooltip(
+                            ui,
+                            "Keeps just the recording time, size and controls on top of other windows while recording, \
+                             and brings the full window back once recording stops. Drag the HUD to move it.",
+                            None,
+                        );
+                    });
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Recording Audio Cue:"));
+                let honk = self.local_preferences.honk;
+                add_settings_widget(
+                    ui,
+                    egui::Checkbox::new(
+                        &mut self.local_preferences.honk,
+                        match honk {
+                            true => "Honk.",
+                            false => "Honk?",
+                        },
+                    ),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Desktop Audio:"));
+                add_settings_ui(ui, |ui| {
+                    let devices = &self.app_state.audio_output_devices;
+                    ui.add_enabled(
+                        !devices.is_empty(),
+                        egui::Checkbox::new(&mut self.local_preferences.desktop_audio, "Record"),
+                    );
+                    let selected_name = match &self.local_preferences.desktop_audio_device {
+                        Some(id) => devices
+                            .iter()
+                            .find(|d| &d.id == id)
+                            .map_or("Unavailable device", |d| d.name.as_str()),
+                        None => "System Default",
+                    };
+                    ui.add_enabled_ui(self.local_preferences.desktop_audio, |ui| {
+                        egui::ComboBox::from_id_salt("desktop_audio_device")
+                            .selected_text(selected_name)
+                            .width(180.0)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.desktop_audio_device,
+                                    None,
+                                    "System Default",
+                                );
+                                for device in devices {
+                                    ui.selectable_value(
+                                        &mut self.local_preferences.desktop_audio_device,
+                                        Some(device.id.clone()),
+                                        &device.name,
+                                    );
+                                }
+                            });
+                    });
+                    if devices.is_empty() {
+                        tooltip(
+                            ui,
+                            "No audio output devices were found, so desktop audio can't be recorded.",
+                            Some(theme::palette(ui.ctx()).warning),
+                        );
+                    } else {
+                        tooltip(
+                            ui,
+                            "Records everything playing on this device (e.g. voice chat and music), not just the game. The game's own audio is always recorded. When using the OBS recorder, the device is picked in OBS instead.",
+                            None,
+                        );
+                    }
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Frame Rate:"));
+                add_settings_ui(ui, |ui| {
+                    let mandatory = self.local_preferences.is_remote_mandatory(CaptureSetting::Fps);
+                    ui.add_enabled_ui(!mandatory, |ui| {
+                        egui::ComboBox::from_id_salt("fps")
+                            .selected_text(format!("{} FPS", self.local_preferences.fps))
+                            .show_ui(ui, |ui| {
+                                for fps in constants::FPS_OPTIONS {
+                                    ui.selectable_value(
+                                        &mut self.local_preferences.fps,
+                                        fps,
+                                        format!("{fps} FPS"),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label.id);
+                    });
+                    remote_setting_note(ui, &self.local_preferences, CaptureSetting::Fps);
+
+                    if let Some(refresh_rate) = refresh_rate
+                        && self.local_preferences.fps > refresh_rate
+                    {
+                        tooltip(
+                            ui,
+                            &format!(
+                                "Your monitor only refreshes at {refresh_rate} Hz, so recording at {} FPS will capture duplicate frames. Consider picking a lower frame rate.",
+                                self.local_preferences.fps
+                            ),
+                            Some(theme::palette(ui.ctx()).warning),
+                        );
+                    } else {
+                        tooltip(
+                            ui,
+                            "Lower frame rates produce smaller recordings that upload faster, which works well for slower-paced games.",
+                            None,
+                        );
+                    }
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Low Impact Mode:"));
+                add_settings_ui(ui, |ui| {
+                    ui.checkbox(&mut self.local_preferences.low_impact, "")
+                        .labelled_by(label.id);
+                    tooltip(
+                        ui,
+                        "Use this if your game runs noticeably worse while recording. A hardware encoder is used instead of x264 if your computer has one; these barely affect the game, but can look slightly worse at the same bitrate. If frames still start being dropped, recording carries on at a lower frame rate for the rest of the session, which splits the recording and makes it less smooth.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Input Sample Rate:"));
+                add_settings_ui(ui, |ui| {
+                    let rate_text = |rate: Option<u32>| match rate {
+                        Some(rate) => format!("{rate} Hz"),
+                        None => "Every Movement".to_string(),
+                    };
+                    let mandatory = self
+                        .local_preferences
+                        .is_remote_mandatory(CaptureSetting::InputRate);
+                    ui.add_enabled_ui(!mandatory, |ui| {
+                        egui::ComboBox::from_id_salt("input_rate")
+                            .selected_text(rate_text(self.local_preferences.input_rate_hz))
+                            .show_ui(ui, |ui| {
+                                for rate in constants::INPUT_RATE_OPTIONS_HZ
+                                    .map(Some)
+                                    .into_iter()
+                                    .chain([None])
+                                {
+                                    ui.selectable_value(
+                                        &mut self.local_preferences.input_rate_hz,
+                                        rate,
+                                        rate_text(rate),
+                                    );
+                                }
+                            })
+                            .response
+                            .labelled_by(label.id);
+                    });
+                    remote_setting_note(ui, &self.local_preferences, CaptureSetting::InputRate);
+                    tooltip(
+                        ui,
+                        "How often mouse movement is recorded, separately from the video's frame rate. Movement in between is added up, so nothing is lost, but fast flicks keep more detail at higher rates. Keys, buttons and controllers are always recorded as they happen.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Segment Length:"));
+                add_settings_ui(ui, |ui| {
+                    egui::ComboBox::from_id_salt("segment_length")
+                        .selected_text(format!("{} minutes", self.local_preferences.segment_length_minutes))
+                        .show_ui(ui, |ui| {
+                            for minutes in constants::SEGMENT_LENGTH_OPTIONS_MINUTES {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.segment_length_minutes,
+                                    minutes,
+                                    format!("{minutes} minutes"),
+                                );
+                            }
+                        });
+                    tooltip(
+                        ui,
+                        "Long sessions are split into recordings of this length, which are uploaded separately. Shorter segments upload more easily, and less is lost if something goes wrong mid-session.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Instant Replay:"));
+                let replay_text = |minutes: Option<u32>| match minutes {
+                    Some(minutes) => format!("Last {minutes} minutes"),
+                    None => "Off".to_string(),
+                };
+                add_settings_ui(ui, |ui| {
+                    egui::ComboBox::from_id_salt("instant_replay")
+                        .selected_text(replay_text(self.local_preferences.instant_replay_minutes))
+                        .show_ui(ui, |ui| {
+                            for minutes in [None]
+                                .into_iter()
+                                .chain(constants::INSTANT_REPLAY_OPTIONS_MINUTES.map(Some))
+                            {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.instant_replay_minutes,
+                                    minutes,
+                                    replay_text(minutes),
+                                );
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                    let budget = match self.local_preferences.instant_replay_minutes {
+                        // Up to a segment more than the replay is kept while the next one is recorded
+                        Some(minutes) => format!(
+                            " Up to about {} MB of disk space is used at a time at the current bitrate.",
+                            record::estimated_recording_size_mb(
+                                std::time::Duration::from_secs(minutes as u64 * 60)
+                                    + record::REPLAY_SEGMENT_LENGTH * 2,
+                                self.local_preferences.encoder.bitrate,
+                            )
+                        ),
+                        None => String::new(),
+                    };
+                    tooltip(
+                        ui,
+                        &format!(
+                            "When on, the start key starts an instant replay instead of a recording. Only the last few minutes are kept, and nothing is uploaded unless you press the save replay key, which keeps them as a recording.{budget}"
+                        ),
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Start Countdown:"));
+                let countdown_text = |seconds: Option<u32>| match seconds {
+                    Some(seconds) => format!("{seconds} seconds"),
+                    None => "Off".to_string(),
+                };
+                add_settings_ui(ui, |ui| {
+                    egui::ComboBox::from_id_salt("start_countdown")
+                        .selected_text(countdown_text(self.local_preferences.start_countdown_seconds))
+                        .show_ui(ui, |ui| {
+                            for seconds in [None]
+                                .into_iter()
+                                .chain(constants::START_COUNTDOWN_OPTIONS_SECONDS.map(Some))
+                            {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.start_countdown_seconds,
+                                    seconds,
+                                    countdown_text(seconds),
+                                );
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                    tooltip(
+                        ui,
+                        "Wait this long after the start key is pressed before recording, so that you can get back into the game first. The overlay counts down, and pressing the start or stop key again cancels it.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Stop When Idle:"));
+                add_settings_ui(ui, |ui| {
+                    let mut enabled = self.local_preferences.idle_auto_stop_minutes.is_some();
+                    if ui.checkbox(&mut enabled, "").labelled_by(label.id).changed() {
+                        self.local_preferences.idle_auto_stop_minutes = enabled.then_some(30);
+                    }
+                    if let Some(minutes) = &mut self.local_preferences.idle_auto_stop_minutes {
+                        ui.add(egui::DragValue::new(minutes).range(5..=240).suffix(" minutes"));
+                    } else {
+                        ui.label("Never");
+                    }
+                    tooltip(
+                        ui,
+                        "Recording pauses after a short time without input, and picks up again on the next input. With this on, recording won't pick up again once there's been no input for this long, e.g. if you've left for the night. The overlay counts down before this happens.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Capture Window:"));
+                add_settings_ui(ui, |ui| {
+                    let state = &mut self.settings_view_state;
+                    egui::ComboBox::from_id_salt("capture_window")
+                        .selected_text(
+                            self.local_preferences
+                                .capture_window
+                                .as_ref()
+                                .map_or_else(|| "Any game".to_string(), capture_window_text),
+                        )
+                        .show_ui(ui, |ui| {
+                            if state.capture_windows.as_ref().is_none_or(|(loaded_at, _)| {
+                                loaded_at.elapsed() > CAPTURE_TARGET_REFRESH_INTERVAL
+                            }) {
+                                state.capture_windows = Some((Instant::now(), open_capture_windows()));
+                            }
+                            ui.selectable_value(
+                                &mut self.local_preferences.capture_window,
+                                None,
+                                "Any game",
+                            );
+                            for window in state.capture_windows.iter().flat_map(|(_, windows)| windows) {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.capture_window,
+                                    Some(window.clone()),
+                                    capture_window_text(window),
+                                );
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                    tooltip(
+                        ui,
+                        "Only record this application, so that nothing else (e.g. chat or a browser) can be recorded by mistake. Only the inside of its window is recorded, and recording pauses while it's minimized. Start it first, so that it's in the list.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Capture Monitor:"));
+                add_settings_ui(ui, |ui| {
+                    let state = &mut self.settings_view_state;
+                    let selected_text = match &self.local_preferences.capture_monitor {
+                        None => "Any monitor".to_string(),
+                        Some(name) => state
+                            .capture_monitors
+                            .iter()
+                            .flat_map(|list| &list.monitors)
+                            .find(|(monitor, _)| &monitor.name == name)
+                            .map(|(monitor, _)| capture_monitor_text(monitor))
+                            .unwrap_or_else(|| name.clone()),
+                    };
+                    egui::ComboBox::from_id_salt("capture_monitor")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            if state.capture_monitors.as_ref().is_none_or(|list| {
+                                list.loaded_at.elapsed() > CAPTURE_TARGET_REFRESH_INTERVAL
+                            }) {
+                                state.capture_monitors = Some(CaptureMonitorList::load(ui.ctx()));
+                            }
+                            ui.selectable_value(
+                                &mut self.local_preferences.capture_monitor,
+                                None,
+                                "Any monitor",
+                            );
+                            for (monitor, thumbnail) in
+                                state.capture_monitors.iter().flat_map(|list| &list.monitors)
+                            {
+                                ui.horizontal(|ui| {
+                                    if let Some(thumbnail) = thumbnail {
+                                        ui.image((thumbnail.id(), thumbnail.size_vec2()));
+                                    }
+                                    ui.selectable_value(
+                                        &mut self.local_preferences.capture_monitor,
+                                        Some(monitor.name.clone()),
+                                        capture_monitor_text(monitor),
+                                    );
+                                });
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                    tooltip(
+                        ui,
+                        "Only record games on this monitor, e.g. to keep anything on your other screens from being recorded by mistake. If the monitor isn't connected when recording starts, the primary monitor is used instead.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Capture Region:"));
+                add_settings_ui(ui, |ui| {
+                    if ui.button("Pick...").clicked() {
+                        self.capture_region_view_state
+                            .open(self.local_preferences.capture_region.as_ref());
+                    }
+                    if self.local_preferences.capture_region.is_some()
+                        && ui.button("Reset").clicked()
+                    {
+                        self.local_preferences.capture_region = None;
+                    }
+                    ui.label(capture_region_summary(
+                        self.local_preferences.capture_region.as_ref(),
+                    ));
+                    tooltip(
+                        ui,
+                        "Record just part of the screen, e.g. the game's viewport inside a bordered window. Only the part of the game inside the region is recorded; if the game isn't inside it, or its monitor is disconnected, the whole game is recorded instead.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Recording Folder:"));
+                add_settings_ui(ui, |ui| {
+                    let overridden = self.app_state.recording_location_override.is_some();
+                    let recording_location = self.app_state.recording_location();
+                    ui.add_enabled_ui(!overridden, |ui| {
+                        if ui.button("Change...").clicked()
+                            && let Some(path) = rfd::FileDialog::new()
+                                .set_directory(&recording_location)
+                                .pick_folder()
+                        {
+                            self.pick_recording_location(path);
+                        }
+                        if self.local_preferences.recording_location.is_some()
+                            && ui.button("Reset").clicked()
+                        {
+                            self.set_recording_location(None);
+                            self.settings_view_state.recording_location_notice = None;
+                        }
+                    });
+                    ui.add(
+                        egui::Label::new(recording_location.display().to_string())
+                            .truncate(),
+                    )
+                    .on_hover_text(recording_location.display().to_string());
+
+                    match &self.settings_view_state.recording_location_notice {
+                        _ if overridden => tooltip(
+                            ui,
+                            "The recording folder was set on the command line, so it can't be changed here.",
+                            None,
+                        ),
+                        Some(Err(error)) => {
+                            tooltip(ui, error, Some(theme::palette(ui.ctx()).error))
+                        }
+                        Some(Ok(warning)) => tooltip(
+                            ui,
+                            warning,
+                            Some(theme::palette(ui.ctx()).warning),
+                        ),
+                        None => tooltip(
+                            ui,
+                            "Recordings are kept here until they're uploaded. Pick a folder on a fast drive with plenty of free space.",
+                            None,
+                        ),
+                    }
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Recording Names:"));
+                add_settings_ui(ui, |ui| {
+                    let text = self
+                        .settings_view_state
+                        .recording_name_template_text
+                        .get_or_insert_with(|| {
+                            self.local_preferences.recording_name_template.clone()
+                        });
+                    let mut changed = ui
+                        .add(
+                            egui::TextEdit::singleline(text)
+                                .desired_width(250.0)
+                                .hint_text(record::DEFAULT_RECORDING_NAME_TEMPLATE),
+                        )
+                        .labelled_by(label.id)
+                        .changed();
+                    if text.trim() != record::DEFAULT_RECORDING_NAME_TEMPLATE
+                        && ui.button("Reset").clicked()
+                    {
+                        *text = record::DEFAULT_RECORDING_NAME_TEMPLATE.to_string();
+                        changed = true;
+                    }
+                    let problem = record::check_recording_name_template(text.trim()).err();
+                    if changed && problem.is_none() {
+                        self.local_preferences.recording_name_template =
+                            text.trim().to_string();
+                    }
+                    match problem {
+                        Some(problem) => {
+                            tooltip(ui, &problem, Some(theme::palette(ui.ctx()).error))
+                        }
+                        None => tooltip(
+                            ui,
+                            &format!(
+                                "What each recording's folder is called. Only new recordings are named this way. You can use:\n{}",
+                                record::RECORDING_NAME_TOKENS
+                                    .iter()
+                                    .map(|(token, meaning)| format!("{token}: {meaning}"))
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            ),
+                            None,
+                        ),
+                    }
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Pause Recording For:"));
+                add_settings_ui(ui, |ui| {
+                    let text = self
+                        .settings_view_state
+                        .capture_denylist_text
+                        .get_or_insert_with(|| self.local_preferences.capture_denylist.join("\n"));
+                    if ui
+                        .add(
+                            egui::TextEdit::multiline(text)
+                                .desired_rows(2)
+                                .desired_width(250.0)
+                                .hint_text("e.g. Discord.exe or 1Password"),
+                        )
+                        .changed()
+                    {
+                        self.local_preferences.capture_denylist = text
+                            .lines()
+                            .map(|line| line.trim().to_string())
+                            .filter(|line| !line.is_empty())
+                            .collect();
+                    }
+                    tooltip(
+                        ui,
+                        "Recording is paused while any of these windows is focused, and resumes once you switch away. Put one per line: a process name ending in .exe, or part of a window title.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let label = add_settings_text(ui, egui::Label::new("Minimum Free Space:"));
+                let mut min_free_space_gb = self.local_preferences.min_free_space_mb / 1024;
+                add_settings_ui(ui, |ui| {
+                    if ui
+                        .add(egui::DragValue::new(&mut min_free_space_gb).range(1..=100).suffix(" GB"))
+                        .labelled_by(label.id)
+                        .changed()
+                    {
+                        self.local_preferences.min_free_space_mb = min_free_space_gb * 1024;
+                    }
+                    tooltip(
+                        ui,
+                        "Recording stops once the free space on the recording drive drops below this, so that the recording can be saved properly.",
+                        None,
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Recording Encryption:"));
+                add_settings_ui(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let encryption = &mut self.local_preferences.encryption;
+                        // Needs a key first, which is made in the encryption window
+                        ui.add_enabled(
+                            !encryption.secrets.is_empty(),
+                            egui::Checkbox::new(&mut encryption.enabled, "Encrypt new recordings"),
+                        );
+                        if ui.button("🔒 Key...").clicked() {
+                            self.encryption_view_state.open = true;
+                        }
+                        tooltip(
+                            ui,
+                            "Encrypts each recording's video and inputs once it finishes, so that nobody else with access to this computer's files can watch them. This takes a few seconds per GB after each recording, and previews and uploads take a little longer.",
+                            None,
+                        );
+                    });
+                });
+            });
+
+            ui.horizontal(|ui| {
+                add_settings_text(ui, egui::Label::new("Video Encoder:"));
+                add_settings_ui(ui, |ui| {
+                    let encoder_name = self.local_preferences.encoder.encoder.to_string();
+                    egui::ComboBox::from_id_salt("video_encoder")
+                        .selected_text(&encoder_name)
+                        .width(150.0)
+                        .show_ui(ui, |ui| {
+                            // Only offer the encoders that this machine has the hardware for
+                            for encoder in &self.app_state.available_video_encoders {
+                                ui.selectable_value(
+                                    &mut self.local_preferences.encoder.encoder,
+                                    *encoder,
+                                    encoder.to_string(),
+                                );
+                            }
+                        });
+                    let encoder_settings = &mut self.local_preferences.encoder;
+                    if !encoder_settings.encoder.supports_codec(encoder_settings.codec) {
+                        encoder_settings.codec = VideoCodec::H264;
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui.button("⚙ Settings").clicked() {
+                            self.encoder_settings_window_open = true;
+                        }
+                        if ui
+                            .button("⏱ Benchmark...")
+                            .on_hover_text("Find the best settings this computer can record at without dropping frames")
+                            .clicked()
+                        {
+                            self.benchmark_view_state.open = true;
+                        }
+
+                        tooltip(ui, "Consider switching encoders and/or using a different preset if your recordings suffer from dropped frames. NVENC is known to drop frames when the GPU is under heavy load or does not have enough VRAM.", None)
+                    });
+                });
+            });
+        });
+    }
+
+    pub(super) fn encoder_settings_window(&mut self, ctx: &egui::Context) {
+        egui::Window::new(format!(
+            "{} Settings",
+            self.local_preferences.encoder.encoder
+        ))
+        .open(&mut self.encoder_settings_window_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            encoder_settings(ui, &mut self.local_preferences);
+        });
+    }
+
+    /// Every hotkey, with what it does
+    pub(super) fn hotkey_reference_window(&mut self, ctx: &egui::Context) {
+        let preferences = &self.local_preferences;
+        egui::Window::new("Hotkeys")
+            .open(&mut self.hotkey_reference_window_open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("hotkey_reference")
+                    .num_columns(3)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        for hotkey in Hotkey::ALL {
+                            if !preferences.is_hotkey_enabled(hotkey) {
+                                continue;
+                            }
+                            ui.label(egui::RichText::new(preferences.hotkey(hotkey)).strong());
+                            ui.label(hotkey_label(hotkey, preferences.stop_hotkey_enabled));
+                            ui.label(
+                                egui::RichText::new(hotkey_description(
+                                    hotkey,
+                                    preferences.stop_hotkey_enabled,
+                                ))
+                                .color(theme::palette(ctx).muted),
+                            );
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(6.0);
+                ui.label(
+                    egui::RichText::new("Hotkeys work while any window is focused, including games. You can rebind them under Keyboard Shortcuts.")
+                        .small()
+                        .color(theme::palette(ctx).muted),
+                );
+            });
+    }
+
+    /// Switches to a newly picked recording folder, as long as we can actually write to it
+    fn pick_recording_location(&mut self, path: PathBuf) {
+        if let Err(e) = record::check_recording_location_writable(&path) {
+            tracing::warn!(e=?e, "Can't record to {}", path.display());
+            self.settings_view_state.recording_location_notice =
+                Some(Err(format!("Can't record to this folder: {e:#}")));
+            return;
+        }
+        self.settings_view_state.recording_location_notice =
+            record::recording_location_drive_warning(&path).map(|warning| Ok(warning.to_string()));
+        self.set_recording_location(Some(path));
+    }
+
+    fn set_recording_location(&mut self, path: Option<PathBuf>) {
+        self.local_preferences.recording_location = path.clone();
+        // Apply this straight away instead of at the end of the frame, so that the rescan sees the new folder
+        self.app_state
+            .config
+            .write()
+            .unwrap()
+            .preferences
+            .recording_location = path;
+        self.config_last_edit = Some(Instant::now());
+        self.app_state
+            .async_request_tx
+            .blocking_send(AsyncRequest::LoadLocalRecordings)
+            .ok();
+    }
+}
+
+fn encoder_settings(ui: &mut egui::Ui, preferences: &mut Preferences) {
+    ui.horizontal(|ui| {
+        ui.label("Codec:");
+        let mandatory = preferences.is_remote_mandatory(CaptureSetting::Codec);
+        let encoder_settings = &mut preferences.encoder;
+        ui.add_enabled_ui(!mandatory, |ui| {
+            egui::ComboBox::from_id_salt("Codec:")
+                .selected_text(encoder_settings.codec.to_string())
+                .show_ui(ui, |ui| {
+                    for codec in VideoCodec::ALL {
+                        if encoder_settings.encoder.supports_codec(codec) {
+                            ui.selectable_value(
+                                &mut encoder_settings.codec,
+                                codec,
+                                codec.to_string(),
+                            );
+                        }
+                    }
+                });
+        });
+        tooltip(
+            ui,
+            "H.265 produces smaller recordings at the same quality, but is only available with hardware encoders.",
+            None,
+        );
+        remote_setting_note(ui, preferences, CaptureSetting::Codec);
+    });
+
+    ui.add_space(5.0);
+    ui.horizontal(|ui| {
+        ui.label("Bitrate:");
+        let mandatory = preferences.is_remote_mandatory(CaptureSetting::Bitrate);
+        ui.add_enabled(
+            !mandatory,
+            egui::DragValue::new(&mut preferences.encoder.bitrate)
+                .range(constants::encoding::MIN_BITRATE..=constants::encoding::MAX_BITRATE)
+                .speed(50)
+                .suffix(" kbps"),
+        );
+        tooltip(
+            ui,
+            &format!(
+                "Higher bitrates look better, but take up more space and take longer to upload. The default is {} kbps.",
+                constants::encoding::BITRATE
+            ),
+            None,
+        );
+        remote_setting_note(ui, preferences, CaptureSetting::Bitrate);
+    });
+
+    ui.add_space(5.0);
+    ui.horizontal(|ui| {
+        ui.label("Keyframe Interval:");
+        ui.add(
+            egui::DragValue::new(&mut preferences.encoder.keyframe_interval_seconds)
+                .range(
+                    constants::encoding::MIN_KEYFRAME_INTERVAL_SECONDS
+                        ..=constants::encoding::MAX_KEYFRAME_INTERVAL_SECONDS,
+                )
+                .suffix(" s"),
+        );
+        tooltip(
+            ui,
+            &format!(
+                "How often a full frame is stored. Recordings can only be seeked to or cut at these without re-encoding, but shorter intervals take more bitrate away from the rest of the video. The default is every {} seconds.",
+                constants::encoding::KEYFRAME_INTERVAL_SECONDS
+            ),
+            None,
+        );
+    });
+
+    ui.add_space(5.0);
+    let encoder_settings = &mut preferences.encoder;
+    match encoder_settings.encoder {
+        VideoEncoderType::X264 => encoder_settings_x264(ui, &mut encoder_settings.x264),
+        VideoEncoderType::NvEnc => encoder_settings_nvenc(ui, &mut encoder_settings.nvenc),
+        VideoEncoderType::Qsv => encoder_settings_qsv(ui, &mut encoder_settings.qsv),
+        VideoEncoderType::Amf => encoder_settings_amf(ui, &mut encoder_settings.amf),
+    }
+}
+
+/// Says so if the server picked the value of `kind`, and whether it can be changed
+fn remote_setting_note(ui: &mut egui::Ui, preferences: &Preferences, kind: CaptureSetting) {
+    if preferences.is_remote_mandatory(kind) {
+        ui.label(
+            egui::RichText::new("Required by the server")
+                .small()
+                .color(theme::palette(ui.ctx()).muted),
+        )
+        .on_hover_text("This is set by the server so that everyone's recordings are consistent, and can't be changed.");
+    } else if preferences.is_remote_sourced(kind) {
+        ui.label(
+            egui::RichText::new("Recommended by the server")
+                .small()
+                .color(theme::palette(ui.ctx()).muted),
+        )
+        .on_hover_text("The server suggested this so that everyone's recordings are consistent. You can still change it.");
+    }
+}
+
+fn encoder_settings_x264(ui: &mut egui::Ui, x264_settings: &mut ObsX264Settings) {
+    dropdown_list(
+        ui,
+        "Preset:",
+        constants::encoding::X264_PRESETS,
+        &mut x264_settings.preset,
+        |ui| {
+            tooltip(ui, PRESET_TOOLTIP, None);
+        },
+    );
+}
+
+fn encoder_settings_nvenc(ui: &mut egui::Ui, nvenc_settings: &mut FfmpegNvencSettings) {
+    dropdown_list(
+        ui,
+        "Preset:",
+        constants::encoding::NVENC_PRESETS,
+        &mut nvenc_settings.preset2,
+        |ui| {
+            tooltip(ui, PRESET_TOOLTIP, None);
+        },
+    );
+
+    ui.add_space(5.0);
+    dropdown_list(
+        ui,
+        "Tune:",
+        constants::encoding::NVENC_TUNE_OPTIONS,
+        &mut nvenc_settings.tune,
+        |_| {},
+    );
+}
+
+fn encoder_settings_qsv(ui: &mut egui::Ui, qsv_settings: &mut ObsQsvSettings) {
+    dropdown_list(
+        ui,
+        "Target Usage:",
+        constants::encoding::QSV_PRESETS,
+        &mut qsv_settings.target_usage,
+        |ui| {
+            tooltip(ui, PRESET_TOOLTIP, None);
+        },
+    );
+}
+
+fn encoder_settings_amf(ui: &mut egui::Ui, amf_settings: &mut AmfSettings) {
+    dropdown_list(
+        ui,
+        "Preset:",
+        constants::encoding::AMF_PRESETS,
+        &mut amf_settings.preset,
+        |ui| {
+            tooltip(ui, PRESET_TOOLTIP, None);
+        },
+    );
+}
+
+fn rebind_prompt(rejected_hotkey: Option<&str>) -> String {
+    match rejected_hotkey {
+        Some(key) => format!("{key} can't be used, press another key..."),
+        None => "Press any key...".to_string(),
+    }
+}
+
+fn hotkey_label(hotkey: Hotkey, stop_hotkey_enabled: bool) -> String {
+    match hotkey {
+        Hotkey::Start if !stop_hotkey_enabled => "Toggle Recording:".to_string(),
+        hotkey => format!("{hotkey}:"),
+    }
+}
+
+fn hotkey_description(hotkey: Hotkey, stop_hotkey_enabled: bool) -> &'static str {
+    match hotkey {
+        Hotkey::Start if !stop_hotkey_enabled => {
+            "Starts recording the game in the foreground, or stops the current recording"
+        }
+        Hotkey::Start => "Starts recording the game in the foreground",
+        Hotkey::Stop => "Stops the current recording",
+        Hotkey::Pause => "Pauses the current recording, or resumes it",
+        Hotkey::PrivacyPause => "Pauses straight away; only the pause key resumes",
+        Hotkey::SaveReplay => "Saves what instant replay has captured so far",
+    }
+}
+
+fn hotkey_conflict_warning(
+    ui: &mut egui::Ui,
+    key: &str,
+    conflicts: &[Hotkey],
+    stop_hotkey_enabled: bool,
+) {
+    if conflicts.is_empty() {
+        return;
+    }
+    let names = conflicts
+        .iter()
+        .map(|hotkey| {
+            hotkey_label(*hotkey, stop_hotkey_enabled)
+                .trim_end_matches(':')
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(" and ");
+    tooltip(
+        ui,
+        &format!(
+            "{key} is also used for {names}, so only one of them will happen when you press it. Pick a different key for one of them."
+        ),
+        Some(theme::palette(ui.ctx()).error),
+    );
+}
+
+fn common_game_key_warning(ui: &mut egui::Ui, key: &str) {
+    if is_common_game_key(key) {
+        tooltip(
+            ui,
+            &format!(
+                "{key} is commonly used by games, so you may end up toggling recording by accident. Consider using a function key instead."
+            ),
+            Some(theme::palette(ui.ctx()).warning),
+        );
+    }
+}
+
+fn dropdown_list(
+    ui: &mut egui::Ui,
+    label: &str,
+    options: &[&str],
+    selected: &mut String,
+    add_content: impl FnOnce(&mut egui::Ui),
+) -> egui::Response {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        egui::ComboBox::from_id_salt(label)
+            .selected_text(selected.as_str())
+            .show_ui(ui, |ui| {
+                for option in options {
+                    ui.selectable_value(selected, option.to_string(), *option);
+                }
+            });
+        add_content(ui);
+    })
+    .response
+}
@@ -0,0 +1,226 @@
+use std::path::PathBuf;
+
+use crate::{
+    app_state::AsyncRequest,
+    record::RecordingSummary,
+    ui::{MainApp, theme, util},
+    upload,
+};
+
+/// A recording that's been finished, and what the user has done with it from here
+struct FinishedRecording {
+    summary: RecordingSummary,
+    /// Test recordings are never uploaded
+    test: bool,
+    /// Whether the user has asked for it to be uploaded
+    queued: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct SummaryViewState {
+    /// Whether the summary window is open
+    pub(super) open: bool,
+    recordings: Vec<FinishedRecording>,
+}
+impl SummaryViewState {
+    pub(crate) fn recording_finished(&mut self, summaries: Vec<RecordingSummary>) {
+        // Sessions that end while the user is off playing pile up until they come back to look at them
+        if !self.open {
+            self.recordings.clear();
+        }
+        self.recordings
+            .extend(summaries.into_iter().map(|summary| FinishedRecording {
+                test: upload::is_test_recording(&summary.folder_path),
+                queued: false,
+                summary,
+            }));
+        self.open = true;
+    }
+}
+
+enum SummaryAction {
+    Upload(usize),
+    Preview(PathBuf),
+    Label(PathBuf),
+    Delete(usize),
+}
+
+impl MainApp {
+    pub(super) fn summary_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.summary_view_state;
+        let auto_upload = self.local_preferences.auto_upload;
+        let mut action = None;
+        egui::Window::new("Recording Finished")
+            .open(&mut state.open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(420.0)
+                    .show(ui, |ui| {
+                        for (i, recording) in state.recordings.iter().enumerate() {
+                            if i > 0 {
+                                ui.separator();
+                            }
+                            if let Some(a) = recording_card(ui, i, recording, auto_upload) {
+                                action = Some(a);
+                            }
+                        }
+                    });
+            });
+
+        match action {
+            Some(SummaryAction::Upload(i)) => {
+                let recording = &mut state.recordings[i];
+                recording.queued = true;
+                self.app_state
+                    .async_request_tx
+                    .blocking_send(AsyncRequest::UploadRecording(
+                        recording.summary.folder_path.clone(),
+                    ))
+                    .ok();
+            }
+            Some(SummaryAction::Preview(folder_path)) => {
+                self.preview_view_state.load(&self.app_state, &folder_path);
+            }
+            Some(SummaryAction::Label(folder_path)) => {
+                self.tagging_view_state.load(&folder_path);
+            }
+            Some(SummaryAction::Delete(i)) => {
+                let folder_path = &state.recordings[i].summary.folder_path;
+                if let Err(e) = std::fs::remove_dir_all(folder_path) {
+                    tracing::error!(
+                        "Failed to delete recording folder {}: {:?}",
+                        folder_path.display(),
+                        e
+                    );
+                } else {
+                    tracing::info!("Deleted recording folder: {}", folder_path.display());
+                    state.recordings.remove(i);
+                    if state.recordings.is_empty() {
+                        state.open = false;
+                    }
+                    self.app_state
+                        .async_request_tx
+                        .blocking_send(AsyncRequest::LoadLocalRecordings)
+                        .ok();
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+fn recording_card(
+    ui: &mut egui::Ui,
+    index: usize,
+    recording: &FinishedRecording,
+    auto_upload: bool,
+) -> Option<SummaryAction> {
+    let summary = &recording.summary;
+    let palette = theme::palette(ui.ctx());
+    let mut action = None;
+
+    ui.label(egui::RichText::new(&summary.game_name).strong());
+    egui::Grid::new(("recording_summary", index))
+        .num_columns(2)
+        .spacing([12.0, 4.0])
+        .show(ui, |ui| {
+            ui.label("Duration:");
+            ui.label(util::format_seconds(summary.duration.as_secs()));
+            ui.end_row();
+
+            ui.label("Game Resolution:");
+            let (width, height) = summary.game_resolution;
+            ui.label(format!("{width}x{height}"));
+            ui.end_row();
+
+            ui.label("Frame Rate:");
+            ui.label(format!("{} FPS", summary.fps));
+            ui.end_row();
+
+            ui.label("Dropped Frames:");
+            match summary.skipped_frames {
+                Some(skipped_frames) => {
+                    let text = format!(
+                        "{:.1}% ({} of {})",
+                        skipped_frames.percentage(),
+                        skipped_frames.skipped,
+                        skipped_frames.total
+                    );
+                    ui.label(egui::RichText::new(text).color(
+                        if summary.dropped_too_many_frames() {
+                            palette.warning
+                        } else {
+                            palette.success
+                        },
+                    ));
+                }
+                None => {
+                    ui.label(egui::RichText::new("Unknown").color(palette.muted));
+                }
+            }
+            ui.end_row();
+
+            ui.label("Size:");
+            ui.label(util::format_bytes(summary.size_bytes));
+            ui.end_row();
+
+            ui.label("Inputs:");
+            ui.label(summary.inputs.to_string());
+            ui.end_row();
+        });
+
+    if let Some(reason) = &summary.invalid_reason {
+        ui.label(
+            egui::RichText::new(format!(
+                "Something went wrong as the recording stopped, so it can't be uploaded: {reason}"
+            ))
+            .color(palette.error),
+        );
+    } else if summary.dropped_too_many_frames() {
+        ui.label(
+            egui::RichText::new(
+                "A lot of frames were dropped, so this recording may be choppy. Try a lower frame rate \
+                 or a hardware encoder, or run the benchmark in the settings to find ones that keep up.",
+            )
+            .color(palette.warning),
+        );
+    }
+    if summary.inputs == 0 {
+        ui.label(egui::RichText::new("No inputs were recorded.").color(palette.warning));
+    }
+
+    ui.horizontal(|ui| {
+        if recording.test {
+            ui.label(egui::RichText::new("Test recording, won't be uploaded").color(palette.muted));
+        } else if summary.invalid_reason.is_none() {
+            if auto_upload {
+                ui.label(egui::RichText::new("Uploading automatically").color(palette.muted));
+            } else if ui
+                .add_enabled(
+                    !recording.queued,
+                    egui::Button::new(if recording.queued { "Queued" } else { "Upload" }),
+                )
+                .clicked()
+            {
+                action = Some(SummaryAction::Upload(index));
+            }
+        }
+        if ui.button("Preview").clicked() {
+            action = Some(SummaryAction::Preview(summary.folder_path.clone()));
+        }
+        if ui.button("Label").clicked() {
+            action = Some(SummaryAction::Label(summary.folder_path.clone()));
+        }
+        if ui
+            .button("Delete")
+            .on_hover_text("Delete this recording from your computer")
+            .clicked()
+        {
+            action = Some(SummaryAction::Delete(index));
+        }
+    });
+
+    action
+}
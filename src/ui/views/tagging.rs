@@ -0,0 +1,317 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    app_state::AsyncRequest,
+    output_types::SessionLabels,
+    ui::{MainApp, theme},
+    upload,
+};
+
+/// How many previously used labels are suggested at a time
+const MAX_SUGGESTIONS: usize = 5;
+
+#[derive(Default)]
+pub(crate) struct TaggingViewState {
+    /// Whether the tagging window is open
+    pub(super) open: bool,
+    folder_path: PathBuf,
+    /// Set for a recording that has already been uploaded, whose labels are sent to the server instead
+    upload_id: Option<String>,
+    game_title: String,
+    genre: String,
+    tags: Vec<String>,
+    /// The tag being typed in, before it's added
+    new_tag: String,
+    notes: String,
+    /// The game's executable and window title, which the game's name was worked out from
+    detected_from: Option<String>,
+    /// Whether the new labels are on their way to the server
+    is_sending: bool,
+    error: Option<String>,
+}
+impl TaggingViewState {
+    /// Opens the window with the labels that the recording at `folder_path` already has
+    pub(super) fn load(&mut self, folder_path: &Path) {
+        let (labels, detected_from, error) = match upload::read_metadata(folder_path) {
+            Ok(metadata) => (
+                metadata.labels.unwrap_or_default(),
+                Some(match metadata.window_title {
+                    Some(window_title) => format!("{} (\"{window_title}\")", metadata.game_exe),
+                    None => metadata.game_exe,
+                }),
+                None,
+            ),
+            Err(e) => (SessionLabels::default(), None, Some(format!("{e:#}"))),
+        };
+        self.open_with(labels, detected_from, error);
+        self.folder_path = folder_path.to_owned();
+    }
+
+    /// Opens the window with the labels that a recording was uploaded with
+    pub(super) fn load_uploaded(&mut self, upload_id: &str, labels: SessionLabels) {
+        self.open_with(labels, None, None);
+        self.upload_id = Some(upload_id.to_string());
+    }
+
+    pub(crate) fn upload_labels_updated(&mut self, result: Result<(), String>) {
+        self.is_sending = false;
+        match result {
+            Ok(()) => self.open = false,
+            Err(e) => self.error = Some(format!("The server didn't take the labels: {e}")),
+        }
+    }
+
+    fn open_with(
+        &mut self,
+        labels: SessionLabels,
+        detected_from: Option<String>,
+        error: Option<String>,
+    ) {
+        *self = Self {
+            open: true,
+            game_title: labels.game_title.unwrap_or_default(),
+            genre: labels.genre.unwrap_or_default(),
+            tags: labels.tags,
+            notes: labels.notes.unwrap_or_default(),
+            detected_from,
+            error,
+            ..Default::default()
+        };
+    }
+
+    fn labels(&self) -> SessionLabels {
+        let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        SessionLabels {
+            game_title: non_empty(&self.game_title),
+            genre: non_empty(&self.genre),
+            tags: self.tags.clone(),
+            notes: non_empty(&self.notes),
+        }
+    }
+
+    fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim();
+        if !tag.is_empty() && !self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            self.tags.push(tag.to_string());
+        }
+        self.new_tag.clear();
+    }
+}
+
+impl MainApp {
+    pub(super) fn tagging_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.tagging_view_state;
+        let history = &mut self.local_preferences.label_history;
+        let app_state = &self.app_state;
+        let mut open = state.open;
+        let mut close = false;
+        egui::Window::new("Label Recording")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(if state.upload_id.is_some() {
+                        "This recording has already been uploaded. Saving sends the labels to the server, in place of the ones it was uploaded with."
+                    } else {
+                        "Describe what's in this recording. Labels are uploaded along with it."
+                    })
+                    .size(12.0)
+                    .color(theme::palette(ui.ctx()).muted),
+                );
+                ui.add_space(4.0);
+
+                egui::Grid::new("labels")
+                    .num_columns(2)
+                    .spacing([8.0, 6.0])
+                    .show(ui, |ui| {
+                        let label = ui.label("Game:");
+                        labelled_text_edit(ui, label.id, &mut state.game_title, &history.game_titles);
+                        ui.end_row();
+
+                        if let Some(detected_from) = &state.detected_from {
+                            ui.label("");
+                            ui.label(
+                                egui::RichText::new(format!("Recorded from {detected_from}"))
+                                    .size(12.0)
+                                    .color(theme::palette(ui.ctx()).muted),
+                            );
+                            ui.end_row();
+                        }
+
+                        let label = ui.label("Genre:");
+                        labelled_text_edit(ui, label.id, &mut state.genre, &history.genres);
+                        ui.end_row();
+
+                        let label = ui.label("Tags:");
+                        ui.vertical(|ui| {
+                            ui.horizontal_wrapped(|ui| {
+                                let mut removed = None;
+                                for (i, tag) in state.tags.iter().enumerate() {
+                                    if ui
+                                        .small_button(format!("{tag} ❌"))
+                                        .on_hover_text("Remove this tag")
+                                        .clicked()
+                                    {
+                                        removed = Some(i);
+                                    }
+                                }
+                                if let Some(i) = removed {
+                                    state.tags.remove(i);
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                let response = ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut state.new_tag)
+                                            .hint_text("Add a tag")
+                                            .desired_width(160.0),
+                                    )
+                                    .labelled_by(label.id);
+                                let entered = response.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                if ui.button("Add").clicked() || entered {
+                                    let tag = state.new_tag.clone();
+                                    state.add_tag(&tag);
+                                    response.request_focus();
+                                }
+                            });
+                            let suggested = suggestions(&history.tags, &state.new_tag)
+                                .filter(|s| !state.tags.iter().any(|t| t.eq_ignore_ascii_case(s)))
+                                .take(MAX_SUGGESTIONS)
+                                .map(str::to_string)
+                                .collect::<Vec<_>>();
+                            if let Some(tag) = suggestion_buttons(ui, &suggested) {
+                                state.add_tag(&tag);
+                            }
+                        });
+                        ui.end_row();
+
+                        let label = ui.label("Notes:");
+                        ui.add(
+                            egui::TextEdit::multiline(&mut state.notes)
+                                .hint_text("Anything else worth knowing")
+                                .desired_rows(3)
+                                .desired_width(220.0),
+                        )
+                        .labelled_by(label.id);
+                        ui.end_row();
+                    });
+
+                if let Some(error) = &state.error {
+                    ui.label(
+                        egui::RichText::new(error).color(theme::palette(ui.ctx()).error),
+                    );
+                }
+
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!state.is_sending, egui::Button::new("Save"))
+                        .clicked()
+                    {
+                        // Anything still typed in is meant to be kept too
+                        let new_tag = std::mem::take(&mut state.new_tag);
+                        state.add_tag(&new_tag);
+                        let labels = state.labels();
+                        if let Some(upload_id) = &state.upload_id {
+                            history.remember(&labels);
+                            // Closed once the server has taken them
+                            state.is_sending = true;
+                            state.error = None;
+                            app_state
+                                .async_request_tx
+                                .blocking_send(AsyncRequest::UpdateUploadLabels {
+                                    upload_id: upload_id.clone(),
+                                    labels,
+                                })
+                                .ok();
+                        } else {
+                            match upload::write_labels(&state.folder_path, labels.clone()) {
+                                Ok(()) => {
+                                    history.remember(&labels);
+                                    close = true;
+                                    app_state
+                                        .async_request_tx
+                                        .blocking_send(AsyncRequest::LoadLocalRecordings)
+                                        .ok();
+                                }
+                                Err(e) => {
+                                    tracing::error!(e=?e, "Failed to save labels for {}", state.folder_path.display());
+                                    state.error = Some(format!("Couldn't save the labels: {e:#}"));
+                                }
+                            }
+                        }
+                    }
+                    if state.is_sending {
+                        ui.spinner();
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        state.open = open && !close;
+    }
+}
+
+/// A single line text edit, with previously used values that match what's typed in offered below it
+fn labelled_text_edit(ui: &mut egui::Ui, label: egui::Id, value: &mut String, history: &[String]) {
+    ui.vertical(|ui| {
+        ui.add(egui::TextEdit::singleline(value).desired_width(220.0))
+            .labelled_by(label);
+        let suggested = suggestions(history, value)
+            .filter(|s| *s != value.trim())
+            .take(MAX_SUGGESTIONS)
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if let Some(suggestion) = suggestion_buttons(ui, &suggested) {
+            *value = suggestion;
+        }
+    });
+}
+
+/// Shows each suggestion as a button, returning the one that was clicked
+fn suggestion_buttons(ui: &mut egui::Ui, suggested: &[String]) -> Option<String> {
+    let mut clicked = None;
+    if !suggested.is_empty() {
+        ui.horizontal_wrapped(|ui| {
+            for suggestion in suggested {
+                if ui.small_button(suggestion).clicked() {
+                    clicked = Some(suggestion.clone());
+                }
+            }
+        });
+    }
+    clicked
+}
+
+/// Entries in `history` that contain `input`, ignoring case; the ones that start with it come first
+fn suggestions<'a>(history: &'a [String], input: &str) -> impl Iterator<Item = &'a str> {
+    let input = input.trim().to_lowercase();
+    let matching = |starts: bool| {
+        let input = input.clone();
+        history.iter().filter_map(move |entry| {
+            let lowercase = entry.to_lowercase();
+            (lowercase.contains(&input) && lowercase.starts_with(&input) == starts)
+                .then_some(entry.as_str())
+        })
+    };
+    matching(true).chain(matching(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestions() {
+        let history = ["Night", "pvp", "Midnight raid", "survival"].map(String::from);
+        assert_eq!(
+            suggestions(&history, "night").collect::<Vec<_>>(),
+            ["Night", "Midnight raid"]
+        );
+        assert_eq!(suggestions(&history, " ").count(), 4);
+        assert_eq!(suggestions(&history, "creative").count(), 0);
+    }
+}
@@ -0,0 +1,128 @@
+//! Uploaded recordings are kept around until the user's retention setting says they can go. Only folders
+//! with an upload marker are ever touched, and the marker is only written once the server has confirmed
+//! (and, if it can, verified) the upload.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::config::UploadedRecordingRetention;
+
+/// How much space the uploaded recordings that are still around take up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetainedRecordings {
+    pub count: usize,
+    pub size_bytes: u64,
+}
+
+/// Recording folders that have been uploaded, along with when the upload finished
+fn uploaded_folders(recording_location: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let Ok(entries) = recording_location.read_dir() else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let marker = std::fs::metadata(path.join(constants::filename::recording::UPLOADED))
+                .ok()
+                .filter(|m| m.is_file())?;
+            Some((path, marker.modified().ok()))
+        })
+        .collect()
+}
+
+pub fn retained_recordings(recording_location: &Path) -> RetainedRecordings {
+    let folders = uploaded_folders(recording_location);
+    RetainedRecordings {
+        count: folders.len(),
+        size_bytes: folders
+            .iter()
+            .map(|(path, _)| super::folder_size(path))
+            .sum(),
+    }
+}
+
+/// Deletes the uploaded recordings that `retention` no longer keeps. Returns how many were deleted.
+pub fn apply_retention(recording_location: &Path, retention: UploadedRecordingRetention) -> usize {
+    match retention {
+        UploadedRecordingRetention::KeepForever => 0,
+        // These are deleted as soon as they're uploaded; this catches any that couldn't be at the time
+        UploadedRecordingRetention::DeleteImmediately => {
+            delete_uploaded(recording_location, Duration::ZERO)
+        }
+        UploadedRecordingRetention::KeepDays(days) => delete_uploaded(
+            recording_location,
+            Duration::from_secs(days as u64 * 24 * 60 * 60),
+        ),
+    }
+}
+
+/// Deletes every uploaded recording whose upload finished at least `min_age` ago. Returns how many were deleted.
+pub fn delete_uploaded(recording_location: &Path, min_age: Duration) -> usize {
+    let now = SystemTime::now();
+    let mut deleted = 0;
+    for (path, uploaded_at) in uploaded_folders(recording_location) {
+        // Without a time to go by, only delete when everything is being cleaned up
+        let age = uploaded_at.and_then(|t| now.duration_since(t).ok());
+        if !age.map_or(min_age.is_zero(), |age| age >= min_age) {
+            continue;
+        }
+        match std::fs::remove_dir_all(&path) {
+            Ok(()) => {
+                tracing::info!("Deleted uploaded recording {}", path.display());
+                deleted += 1;
+            }
+            Err(e) => {
+                tracing::error!(e=?e, "Failed to delete uploaded recording {}", path.display());
+            }
+        }
+    }
+    deleted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TestDir;
+
+    #[test]
+    fn test_only_uploaded_recordings_are_deleted() {
+        let dir = TestDir::new("cleanup");
+        for (name, uploaded) in [("1", true), ("2", false), ("3", true)] {
+            let folder = dir.join(name);
+            std::fs::create_dir_all(&folder).unwrap();
+            std::fs::write(folder.join("recording.mp4"), [0u8; 100]).unwrap();
+            if uploaded {
+                std::fs::write(folder.join(constants::filename::recording::UPLOADED), "gc")
+                    .unwrap();
+            }
+        }
+
+        assert_eq!(
+            retained_recordings(&dir),
+            RetainedRecordings {
+                count: 2,
+                // The video and the marker
+                size_bytes: 2 * 102,
+            }
+        );
+        // Nothing is old enough yet
+        assert_eq!(
+            apply_retention(&dir, UploadedRecordingRetention::KeepDays(7)),
+            0
+        );
+        assert_eq!(
+            apply_retention(&dir, UploadedRecordingRetention::KeepForever),
+            0
+        );
+        assert_eq!(
+            apply_retention(&dir, UploadedRecordingRetention::DeleteImmediately),
+            2
+        );
+        assert!(dir.join("2").is_dir());
+        assert_eq!(retained_recordings(&dir), RetainedRecordings::default());
+    }
+}
@@ -0,0 +1,71 @@
+//! Labels are kept in the recording's metadata, so they go into the archive along with everything else
+//! and are sent as the upload's tags.
+
+use std::path::Path;
+
+use color_eyre::{Result, eyre::WrapErr as _};
+
+use crate::output_types::{Metadata, SessionLabels};
+
+pub fn read_metadata(folder_path: &Path) -> Result<Metadata> {
+    let metadata =
+        std::fs::read_to_string(folder_path.join(constants::filename::recording::METADATA))
+            .wrap_err("failed to read metadata")?;
+    serde_json::from_str(&metadata).wrap_err("failed to parse metadata")
+}
+
+/// Replaces the labels of the recording at `folder_path`. Empty labels are removed altogether.
+pub fn write_labels(folder_path: &Path, labels: SessionLabels) -> Result<()> {
+    let mut metadata = read_metadata(folder_path)?;
+    metadata.labels = (!labels.is_empty()).then_some(labels);
+    std::fs::write(
+        folder_path.join(constants::filename::recording::METADATA),
+        serde_json::to_string_pretty(&metadata)?,
+    )
+    .wrap_err("failed to write metadata")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TestDir;
+
+    #[test]
+    fn test_labels_roundtrip() {
+        let dir = TestDir::new("labels");
+        let metadata_path = dir.join(constants::filename::recording::METADATA);
+        std::fs::write(
+            &metadata_path,
+            serde_json::json!({
+                "game_exe": "game.exe",
+                "session_id": "session-1",
+                "hardware_id": "hw-1",
+                "hardware_specs": null,
+                "start_timestamp": 0,
+                "end_timestamp": 60,
+                "duration": 60.0,
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(read_metadata(&dir).unwrap().labels, None);
+
+        let labels = SessionLabels {
+            game_title: Some("Minecraft".to_string()),
+            genre: Some("Sandbox".to_string()),
+            tags: vec!["survival".to_string()],
+            notes: Some("First time in the nether".to_string()),
+        };
+        write_labels(&dir, labels.clone()).unwrap();
+        assert_eq!(read_metadata(&dir).unwrap().labels, Some(labels));
+        // The rest of the metadata is left alone
+        let metadata = read_metadata(&dir).unwrap();
+        assert_eq!(metadata.game_exe, "game.exe");
+        assert_eq!(metadata.duration, 60.0);
+
+        write_labels(&dir, SessionLabels::default()).unwrap();
+        assert_eq!(read_metadata(&dir).unwrap().labels, None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,21 +1,53 @@
 use std::{
+    io::SeekFrom,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
+use chrono::Timelike as _;
 use color_eyre::eyre::{self, Context as _, ContextCompat};
 use futures::TryStreamExt as _;
 use serde::Deserialize;
-use tokio::{io::AsyncReadExt, sync::mpsc};
+use tokio::io::{AsyncReadExt, AsyncSeekExt as _};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    api::{ApiClient, CompleteMultipartUploadChunk, InitMultipartUploadArgs},
-    app_state::{self, AppState, AsyncRequest},
-    output_types::Metadata,
+    api::{
+        ApiClient, ApiKeyRejected, ApiKeyValidationError, CompleteMultipartUploadChunk,
+        CompleteMultipartUploadResponse, InitMultipartUploadArgs, RateLimited, UserInfo,
+        is_network_failure,
+    },
+    app_state::{AppState, AsyncRequest, UiUpdate},
+    config::{Preferences, UploadedRecordingRetention},
+    encryption::{self, RecordingKeys},
+    input_track,
+    output_types::{Metadata, SessionLabels},
 };
 
+mod cleanup;
+mod labels;
+mod queue;
+mod resume;
+mod s3;
+mod sink;
+mod speed;
+mod throttle;
+mod transfer;
 pub mod validation;
 
+pub use cleanup::{RetainedRecordings, apply_retention, delete_uploaded, retained_recordings};
+pub use labels::{read_metadata, write_labels};
+pub use queue::{QueuedUpload, UploadQueue, UploadState};
+use resume::UploadSession;
+use sink::{UploadArchive, UploadSink};
+use speed::RollingSpeed;
+pub use throttle::UploadThrottle;
+pub use transfer::{export_recording, import_recording};
+
+/// Upper bound on the wait between retries of a failed chunk
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct ProgressData {
     pub bytes_uploaded: u64,
@@ -37,6 +69,14 @@ pub enum LocalRecording {
         folder_name: String,
         folder_path: PathBuf,
         timestamp: Option<std::time::SystemTime>,
+        /// Length of the recording in seconds; `None` if the recording hasn't finished (no metadata yet)
+        duration: Option<f32>,
+        /// Total size of the files in the folder
+        size_bytes: u64,
+        /// What the user has labelled the recording with, if anything
+        labels: Option<SessionLabels>,
+        /// Made to try out settings, so it's never uploaded
+        test: bool,
     },
 }
 
@@ -66,144 +106,364 @@ impl LocalRecording {
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
-pub struct FinalStats {
-    pub total_files_uploaded: u64,
-    pub total_duration_uploaded: f64,
-    pub total_bytes_uploaded: u64,
+/// How recordings should be sent to the server
+#[derive(Debug, Clone, Copy)]
+struct UploadOptions {
+    /// `None` lets the server pick
+    chunk_size_bytes: Option<u64>,
+    /// How many times a failed chunk is retried before giving up on the upload
+    max_retries: u32,
+    /// Send the inputs in the compact format instead of as CSV
+    compact_inputs: bool,
+}
+impl UploadOptions {
+    fn from_preferences(preferences: &Preferences) -> Self {
+        let chunk_size_mb = preferences.upload_chunk_size_mb.or(preferences
+            .unreliable_connection
+            .then_some(constants::UNRELIABLE_CONNECTION_CHUNK_SIZE_MB));
+        Self {
+            chunk_size_bytes: chunk_size_mb.map(|mb| mb * 1024 * 1024),
+            max_retries: preferences.upload_retries,
+            compact_inputs: preferences.compact_input_track,
+        }
+    }
 }
 
-pub async fn start(
+/// Queues every recording that's ready to be uploaded, and starts uploading them.
+pub fn enqueue_all(
+    app_state: &Arc<AppState>,
+    api_client: &Arc<ApiClient>,
+    recording_location: &Path,
+) {
+    let ready = scan_local_recordings(recording_location)
+        .into_iter()
+        .filter_map(|recording| match recording {
+            // Recordings without metadata are still being recorded
+            LocalRecording::Unuploaded {
+                folder_path,
+                test: false,
+                ..
+            } if folder_path
+                .join(constants::filename::recording::METADATA)
+                .is_file() =>
+            {
+                Some(folder_path)
+            }
+            _ => None,
+        });
+    {
+        let mut queue = app_state.upload_queue.write().unwrap();
+        for folder_path in ready {
+            queue.enqueue(folder_path);
+        }
+    }
+    process_queue(app_state.clone(), api_client.clone());
+}
+
+/// Queues everything that's ready and waits for the queue to drain. Used when exiting in headless mode.
+pub async fn upload_all_and_wait(
     app_state: Arc<AppState>,
     api_client: Arc<ApiClient>,
     recording_location: PathBuf,
 ) {
-    let tx = app_state.ui_update_tx.clone();
-    let (api_token, unreliable_connection, delete_uploaded) = {
+    enqueue_all(&app_state, &api_client, &recording_location);
+    while app_state.upload_queue.read().unwrap().is_active() {
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+
+    let held = app_state
+        .upload_queue
+        .read()
+        .unwrap()
+        .items()
+        .iter()
+        .filter(|i| i.state == UploadState::AwaitingLogin)
+        .count();
+    if held > 0 {
+        tracing::warn!(
+            "{held} recording(s) couldn't be uploaded without a validated API key, they'll be uploaded next time"
+        );
+    }
+    if app_state.upload_queue.read().unwrap().is_awaiting_network() {
+        tracing::warn!("Lost the connection while uploading, the rest will be uploaded next time");
+    }
+}
+
+/// Puts uploads that were held back after the connection dropped back in line, once the server can be
+/// reached again. They carry on from the last chunk that made it through.
+pub async fn resume_if_reconnected(app_state: Arc<AppState>, api_client: Arc<ApiClient>) {
+    let awaiting_network = app_state.upload_queue.read().unwrap().is_awaiting_network();
+    if !awaiting_network || !api_client.is_reachable().await {
+        return;
+    }
+    tracing::info!("Connection is back, resuming uploads");
+    if app_state
+        .upload_queue
+        .write()
+        .unwrap()
+        .resume_after_network()
+    {
+        process_queue(app_state, api_client);
+    }
+}
+
+/// Starts as many queued uploads as the concurrency limit allows.
+/// Called whenever the queue changes, including when an upload finishes.
+pub fn process_queue(app_state: Arc<AppState>, api_client: Arc<ApiClient>) {
+    if app_state
+        .valid_api_key_and_user_id
+        .read()
+        .unwrap()
+        .is_none()
+    {
+        // Offline, or the key hasn't been checked yet; the queue is resumed once it has been
+        app_state
+            .upload_queue
+            .write()
+            .unwrap()
+            .hold_pending_for_login();
+        app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+        return;
+    }
+    if app_state
+        .user_info
+        .read()
+        .unwrap()
+        .as_ref()
+        .is_some_and(UserInfo::is_client_outdated)
+    {
+        // Held until the user updates; the new version checks the key again on startup
+        tracing::warn!("This version is too old for the server to take uploads from");
+        app_state
+            .upload_queue
+            .write()
+            .unwrap()
+            .hold_pending_for_login();
+        app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+        return;
+    }
+    {
+        let mut queue = app_state.upload_queue.write().unwrap();
+        if queue.is_awaiting_network() {
+            // They'd only fail too; everything goes again once the connection is back
+            queue.hold_pending_for_network();
+            drop(queue);
+            app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+            return;
+        }
+    }
+
+    let max_concurrent = app_state
+        .config
+        .read()
+        .unwrap()
+        .preferences
+        .max_concurrent_uploads as usize;
+    loop {
+        let next = app_state
+            .upload_queue
+            .write()
+            .unwrap()
+            .start_next(max_concurrent);
+        let Some((folder_path, cancellation_token)) = next else {
+            break;
+        };
+        tokio::spawn(upload_queued(
+            app_state.clone(),
+            api_client.clone(),
+            folder_path,
+            cancellation_token,
+        ));
+    }
+    app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+}
+
+async fn upload_queued(
+    app_state: Arc<AppState>,
+    api_client: Arc<ApiClient>,
+    folder_path: PathBuf,
+    cancellation_token: CancellationToken,
+) {
+    let (api_token, options, retention, upload_to_server, s3_destination) = {
         let config = app_state.config.read().unwrap();
+        // Prefer the key that was last validated: after logging in again, the new key only reaches the
+        // config once the UI has caught up
+        let validated_api_key = app_state
+            .valid_api_key_and_user_id
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|(api_key, _)| api_key.clone());
         (
-            config.credentials.api_key.clone(),
-            config.preferences.unreliable_connection,
-            config.preferences.delete_uploaded_files,
+            validated_api_key.unwrap_or_else(|| config.credentials.api_key.clone()),
+            UploadOptions::from_preferences(&config.preferences),
+            config.preferences.uploaded_retention,
+            config.preferences.upload_to_server,
+            config.preferences.s3_destination.clone(),
         )
     };
+    let progress = ProgressReporter {
+        app_state: app_state.clone(),
+        folder_path: folder_path.clone(),
+        speed: Default::default(),
+    };
 
-    tx.send(app_state::UiUpdate::UpdateUploadProgress(Some(
-        ProgressData::default(),
-    )))
-    .await
-    .ok();
+    let upload = async {
+        let sinks = sink::enabled_sinks(
+            upload_to_server,
+            &s3_destination,
+            &api_client,
+            api_token,
+            options,
+        )?;
+        let keys = app_state.recording_keys()?;
+        upload_folder(
+            &folder_path,
+            &sinks,
+            &keys,
+            options.compact_inputs,
+            progress,
+        )
+        .await
+    };
+    // Cancelling drops the upload mid-chunk; its progress is saved, so a retry picks up where it left off
+    let result = tokio::select! {
+        r = upload => r,
+        _ = cancellation_token.cancelled() => Err(eyre::eyre!("Cancelled")),
+    };
 
-    match run(
-        &recording_location,
-        api_client,
-        api_token,
-        unreliable_connection,
-        delete_uploaded,
-        tx.clone(),
-        app_state.async_request_tx.clone(),
-    )
-    .await
-    {
-        Ok(_final_stats) => {
-            // Request a re-fetch of our upload stats and local recordings
+    match &result {
+        Ok(recording_stats) => app_state.telemetry.upload_succeeded(recording_stats.bytes),
+        // Neither is the upload's fault; they're held until the user logs in again or the connection is back
+        Err(e) if ApiKeyRejected::is_cause_of(e) || is_network_failure(e) => {}
+        Err(e) => app_state.telemetry.upload_failed(e),
+    }
+    let error = match result {
+        Ok(recording_stats) => {
+            tracing::info!(
+                "Uploaded {} ({:.1}s, {} bytes)",
+                folder_path.display(),
+                recording_stats.duration,
+                recording_stats.bytes
+            );
+
+            // The upload has been confirmed by now, so the recording is only kept if the user wants it
+            if retention == UploadedRecordingRetention::DeleteImmediately {
+                if let Err(e) = std::fs::remove_dir_all(&folder_path) {
+                    tracing::error!(
+                        "Failed to delete uploaded directory {}: {:?}",
+                        folder_path.display(),
+                        e
+                    );
+                } else {
+                    tracing::info!("Deleted uploaded directory: {}", folder_path.display());
+                }
+            }
+            None
+        }
+        Err(e) if ApiKeyRejected::is_cause_of(&e) => {
+            tracing::warn!(e=?e, "API key was rejected while uploading {}, waiting for a new login", folder_path.display());
             app_state
-                .async_request_tx
-                .send(AsyncRequest::LoadUploadStats)
-                .await
-                .ok();
+                .upload_queue
+                .write()
+                .unwrap()
+                .hold_for_login(&folder_path);
+            *app_state.valid_api_key_and_user_id.write().unwrap() = None;
+            // Sends the user back to the login screen
             app_state
-                .async_request_tx
-                .send(AsyncRequest::LoadLocalRecordings)
+                .ui_update_tx
+                .send(UiUpdate::UpdateUserId(Err(ApiKeyValidationError::Revoked)))
                 .await
                 .ok();
+            return;
+        }
+        Err(e) if is_network_failure(&e) => {
+            tracing::warn!(e=?e, "Lost the connection while uploading {}, waiting for it to come back", folder_path.display());
+            app_state
+                .upload_queue
+                .write()
+                .unwrap()
+                .hold_for_network(&folder_path);
+            app_state.ui_update_tx.try_send(UiUpdate::ForceUpdate).ok();
+            return;
         }
         Err(e) => {
-            tx.send(app_state::UiUpdate::UploadFailed(e.to_string()))
-                .await
-                .ok();
+            tracing::error!("Error uploading folder {}: {:?}", folder_path.display(), e);
+            Some(format!("{e:#}"))
         }
-    }
+    };
 
-    tx.send(app_state::UiUpdate::UpdateUploadProgress(None))
+    let queue_drained = {
+        let mut queue = app_state.upload_queue.write().unwrap();
+        queue.finish(&folder_path, error.clone().map_or(Ok(()), Err));
+        !queue.is_active()
+    };
+    app_state
+        .ui_update_tx
+        .send(UiUpdate::UploadFinished {
+            folder_name: folder_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            error,
+        })
         .await
         .ok();
-}
-
-/// Separate function to allow for fallibility
-async fn run(
-    recording_location: &Path,
-    api_client: Arc<ApiClient>,
-    api_token: String,
-    unreliable_connection: bool,
-    delete_uploaded: bool,
-    tx: app_state::UiUpdateSender,
-    async_req_tx: mpsc::Sender<AsyncRequest>,
-) -> eyre::Result<FinalStats> {
-    let mut stats = FinalStats::default();
-
-    for entry in recording_location.read_dir()? {
-        let entry = entry?;
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
-        }
-
-        if path.join(constants::filename::recording::INVALID).is_file()
-            || path
-                .join(constants::filename::recording::UPLOADED)
-                .is_file()
-        {
-            continue;
-        }
 
-        let recording_stats = match upload_folder(
-            &path,
-            api_client.clone(),
-            &api_token,
-            unreliable_connection,
-            tx.clone(),
-        )
+    // Refresh our local recordings, and once everything's done, our upload stats
+    app_state
+        .async_request_tx
+        .send(AsyncRequest::LoadLocalRecordings)
         .await
-        {
-            Ok(recording_stats) => recording_stats,
-            Err(e) => {
-                tracing::error!("Error uploading folder {}: {:?}", path.display(), e);
-                continue;
-            }
-        };
+        .ok();
+    if queue_drained {
+        app_state
+            .async_request_tx
+            .send(AsyncRequest::LoadUploadStats)
+            .await
+            .ok();
+    }
 
-        stats.total_duration_uploaded += recording_stats.duration;
-        stats.total_files_uploaded += 1;
-        stats.total_bytes_uploaded += recording_stats.bytes;
-
-        // delete the uploaded recording directory if the preference is enabled
-        if delete_uploaded {
-            if let Err(e) = std::fs::remove_dir_all(&path) {
-                tracing::error!(
-                    "Failed to delete uploaded directory {}: {:?}",
-                    path.display(),
-                    e
-                );
-            } else {
-                tracing::info!("Deleted uploaded directory: {}", path.display());
-            }
-        }
+    process_queue(app_state, api_client);
+}
 
-        // every 5 files uploaded we check with server to update list of successfully uploaded files
-        if stats.total_files_uploaded % 5 == 0 {
-            let async_req_tx = async_req_tx.clone();
-            tokio::spawn(async move {
-                async_req_tx.send(AsyncRequest::LoadUploadStats).await.ok();
-                async_req_tx
-                    .send(AsyncRequest::LoadLocalRecordings)
-                    .await
-                    .ok();
-            });
-        }
+/// Publishes the progress of an upload to its entry in the queue
+#[derive(Clone)]
+struct ProgressReporter {
+    app_state: Arc<AppState>,
+    folder_path: PathBuf,
+    speed: Arc<Mutex<RollingSpeed>>,
+}
+impl ProgressReporter {
+    fn report(&self, progress: ProgressData) {
+        self.app_state
+            .upload_queue
+            .write()
+            .unwrap()
+            .set_progress(&self.folder_path, progress);
+        self.app_state
+            .ui_update_tx
+            .try_send(UiUpdate::ForceUpdate)
+            .ok();
     }
 
-    Ok(stats)
+    /// Waits for `delay` after the server asked us to slow down, showing as much in the queue
+    async fn wait_out_rate_limit(&self, delay: Duration) {
+        let set_rate_limited = |until| {
+            self.app_state
+                .upload_queue
+                .write()
+                .unwrap()
+                .set_rate_limited(&self.folder_path, until);
+            self.app_state
+                .ui_update_tx
+                .try_send(UiUpdate::ForceUpdate)
+                .ok();
+        };
+        set_rate_limited(Some(std::time::Instant::now() + delay));
+        tokio::time::sleep(delay).await;
+        set_rate_limited(None);
+    }
 }
 
 struct RecordingStats {
@@ -213,13 +473,16 @@ struct RecordingStats {
 
 async fn upload_folder(
     path: &Path,
-    api_client: Arc<ApiClient>,
-    api_token: &str,
-    unreliable_connection: bool,
-    tx: app_state::UiUpdateSender,
+    sinks: &[Box<dyn UploadSink>],
+    keys: &RecordingKeys,
+    compact_inputs: bool,
+    progress: ProgressReporter,
 ) -> eyre::Result<RecordingStats> {
+    if is_test_recording(path) {
+        eyre::bail!("This is a test recording, which isn't uploaded");
+    }
     tracing::info!("Validating folder {}", path.display());
-    let validation = match validate_folder(path) {
+    let validation = match validate_folder(path, keys) {
         Ok(validation_paths) => validation_paths,
         Err(e) => {
             std::fs::write(
@@ -231,79 +494,181 @@ async fn upload_folder(
         }
     };
 
-    tracing::info!("Creating tar file for {}", path.display());
-    let tar_path = tokio::task::spawn_blocking({
-        let validation = validation.clone();
-        move || {
-            let tar_path = PathBuf::from(format!(
-                "{}.tar",
-                &uuid::Uuid::new_v4().simple().to_string()[0..16]
-            ));
-            let mut tar = tar::Builder::new(std::fs::File::create(&tar_path)?);
-            for path in [
-                &validation.mp4_path,
-                &validation.csv_path,
-                &validation.meta_path,
-            ] {
-                tar.append_file(
-                    path.file_name().context("failed to get file name")?,
-                    &mut std::fs::File::open(path)?,
-                )?;
-            }
-
-            eyre::Ok(tar_path)
-        }
-    })
-    .await
-    .map_err(eyre::Error::from)
-    .flatten()
-    .context("error creating tar file")?;
+    let result = upload_archive(path, &validation, sinks, keys, compact_inputs, progress).await;
+    finish_upload(path, &validation, result)
+}
 
-    struct DeleteFileOnDrop(PathBuf);
-    impl Drop for DeleteFileOnDrop {
-        fn drop(&mut self) {
-            std::fs::remove_file(&self.0).ok();
-        }
-    }
-    let tar_path = DeleteFileOnDrop(tar_path);
+/// Packs the recording into an archive, or picks up the one from an interrupted upload, and uploads it to
+/// each of `sinks`. Returns the ID of the last sink to get it, and the size of the archive.
+async fn upload_archive(
+    path: &Path,
+    validation: &ValidationResult,
+    sinks: &[Box<dyn UploadSink>],
+    keys: &RecordingKeys,
+    compact_inputs: bool,
+    progress: ProgressReporter,
+) -> eyre::Result<(String, u64)> {
+    // The labels are in the metadata and may have been changed since the archive was packed
+    let metadata_sha256 =
+        sha256::try_digest(validation.meta_path.as_path()).context("failed to hash metadata")?;
+    // If a previous upload of this recording was interrupted, reuse its archive so that we can pick up where it left off
+    let session = UploadSession::load(path);
+    let tar_path = match session
+        .as_ref()
+        .filter(|s| s.matches_metadata(&metadata_sha256))
+        .map(|s| path.join(&s.archive_filename))
+        .filter(|p| p.is_file())
+    {
+        Some(tar_path) => tar_path,
+        None => create_tar(path, validation, keys.clone(), compact_inputs).await?,
+    };
+    // Whichever form of the inputs went into the archive; the compact one only exists if it was used
+    let inputs_path = Some(path.join(constants::filename::recording::INPUTS_COMPACT))
+        .filter(|p| p.is_file())
+        .unwrap_or_else(|| validation.csv_path.clone());
 
-    let game_control_id = upload_tar(
-        &tar_path.0,
-        api_client,
-        api_token,
-        unreliable_connection,
-        validation
+    let size_bytes = std::fs::metadata(&tar_path)
+        .map(|m| m.len())
+        .context("failed to get file size")?;
+    // Checked against what the destination ends up with, so that a corrupted upload isn't mistaken for a finished one
+    let sha256 = tokio::task::spawn_blocking({
+        let tar_path = tar_path.clone();
+        move || sha256::try_digest(tar_path.as_path())
+    })
+    .await?
+    .context("failed to hash tar file")?;
+    let archive = UploadArchive {
+        recording_folder: path,
+        tar_path: &tar_path,
+        size_bytes,
+        sha256,
+        metadata_sha256,
+        video_filename: validation
             .mp4_path
             .file_name()
             .context("failed to get mp4 filename")?
             .to_string_lossy()
-            .as_ref(),
-        validation
-            .csv_path
+            .into_owned(),
+        control_filename: inputs_path
             .file_name()
-            .context("failed to get csv filename")?
+            .context("failed to get inputs filename")?
             .to_string_lossy()
-            .as_ref(),
-        validation.metadata.duration,
-        tx,
-    )
-    .await
-    .context("error uploading tar file")?;
+            .into_owned(),
+        duration_seconds: validation.metadata.duration,
+        fps: validation.metadata.fps.unwrap_or(constants::FPS),
+        tags: validation
+            .metadata
+            .labels
+            .as_ref()
+            .map(SessionLabels::upload_tags),
+    };
+
+    // The server's ID is the one worth keeping, and it's uploaded to last if at all
+    let mut upload_id = String::new();
+    for sink in sinks {
+        tracing::info!("Uploading {} to {}", path.display(), sink.name());
+        upload_id = sink
+            .upload(&archive, &progress)
+            .await
+            .with_context(|| format!("error uploading tar file to {}", sink.name()))?;
+    }
+    Ok((upload_id, size_bytes))
+}
+
+/// Marks the recording as uploaded if it was. The archive is kept after a failed upload so that it can be
+/// resumed, unless the recording is encrypted at rest: the archive and the compact inputs are packed from
+/// the decrypted recording, so they're removed either way rather than left on disk unencrypted.
+fn finish_upload(
+    path: &Path,
+    validation: &ValidationResult,
+    result: eyre::Result<(String, u64)>,
+) -> eyre::Result<RecordingStats> {
+    let encrypted = encryption::is_encrypted(&validation.csv_path).unwrap_or_default();
+    if result.is_ok() || encrypted {
+        remove_archives(path);
+    }
+    if encrypted {
+        std::fs::remove_file(path.join(constants::filename::recording::INPUTS_COMPACT)).ok();
+    }
+    let (upload_id, size_bytes) = result?;
 
     std::fs::write(
         path.join(constants::filename::recording::UPLOADED),
-        game_control_id,
+        upload_id,
     )
     .ok();
 
     Ok(RecordingStats {
         duration: validation.metadata.duration as f64,
-        bytes: std::fs::metadata(&tar_path.0)
-            .map(|m| m.len())
-            .unwrap_or_default(),
+        bytes: size_bytes,
     })
 }
 
+/// Removes any archives packed for uploading the recording in `path`
+fn remove_archives(path: &Path) {
+    for entry in path.read_dir().into_iter().flatten().flatten() {
+        let entry_path = entry.path();
+        if entry_path.extension().and_then(|e| e.to_str()) == Some("tar") {
+            std::fs::remove_file(&entry_path).ok();
+        }
+    }
+}
+
+/// Packs the recording into a tar file within its folder. The archive is kept until the upload succeeds,
+/// so that an interrupted upload can be resumed with the exact same bytes, unless the recording is encrypted
+/// (see [`finish_upload`]). With `compact_inputs`, the inputs
+/// are converted to the compact format, which is kept alongside the archive, and packed instead of the CSV.
+async fn create_tar(
+    path: &Path,
+    validation: &ValidationResult,
+    keys: RecordingKeys,
+    compact_inputs: bool,
+) -> eyre::Result<PathBuf> {
+    tracing::info!("Creating tar file for {}", path.display());
+    tokio::task::spawn_blocking({
+        let path = path.to_owned();
+        let validation = validation.clone();
+        move || {
+            // Clear out archives from uploads that can no longer be resumed
+            remove_archives(&path);
+            let compact_inputs_path = path.join(constants::filename::recording::INPUTS_COMPACT);
+            std::fs::remove_file(&compact_inputs_path).ok();
+
+            let inputs_path = if compact_inputs {
+                let events = input_track::read_file(&validation.csv_path, &keys)?;
+                std::fs::write(&compact_inputs_path, input_track::encode(&events, true)?)?;
+                &compact_inputs_path
+            } else {
+                &validation.csv_path
+            };
+
+            let tar_path = path.join(format!(
+                "{}.tar",
+                &uuid::Uuid::new_v4().simple().to_string()[0..16]
+            ));
+            let mut tar = tar::Builder::new(std::fs::File::create(&tar_path)?);
+            for path in [&validation.mp4_path, inputs_path, &validation.meta_path] {
+                // Decrypted on the way in, if the recording was encrypted at rest
+                let file = encryption::open(path, &keys)?;
+                let mut header = tar::Header::new_gnu();
+                header.set_metadata(&std::fs::metadata(path)?);
+                header.set_size(file.size());
+                tar.append_data(
+                    &mut header,
+                    path.file_name().context("failed to get file name")?,
+                    file,
+                )?;
+            }
+
+            eyre::Ok(tar_path)
+        }
+    })
+    .await
+    .map_err(eyre::Error::from)
+    .flatten()
+    .context("error creating tar file")
+}
+
 // This is a bit messy - I don't love using a Vec of Strings for the errors -
 // but I wanted to capture the multi-error nature of the validation process
 //
@@ -315,7 +680,7 @@ struct ValidationResult {
     meta_path: PathBuf,
     metadata: Metadata,
 }
-fn validate_folder(path: &Path) -> Result<ValidationResult, Vec<String>> {
+fn validate_folder(path: &Path, keys: &RecordingKeys) -> Result<ValidationResult, Vec<String>> {
     // This is not guaranteed to be constants::recording::VIDEO_FILENAME if the WebSocket recorder
     // is being used, which is why we search for it
     let Some(mp4_path) = path
@@ -350,7 +715,7 @@ fn validate_folder(path: &Path) -> Result<ValidationResult, Vec<String>> {
         .map_err(|e| vec![format!("Error parsing metadata file: {e:?}")])?;
 
     let (input_stats, mut invalid_reasons) =
-        validation::for_recording(&metadata, &mp4_path, &csv_path)
+        validation::for_recording(&metadata, &mp4_path, &csv_path, keys)
             .map_err(|e| vec![format!("Error validating recording at {path:?}: {e:?}")])?;
 
     metadata.input_stats = Some(input_stats);
@@ -376,121 +741,129 @@ fn validate_folder(path: &Path) -> Result<ValidationResult, Vec<String>> {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Uploads the archive to the server in chunks, picking up where an interrupted upload left off if it can.
+/// Returns the server's ID for the recording.
 async fn upload_tar(
-    tar_path: &Path,
+    archive: &UploadArchive<'_>,
     api_client: Arc<ApiClient>,
     api_token: &str,
-    unreliable_connection: bool,
-    video_filename: &str,
-    control_filename: &str,
-    video_duration_seconds: f32,
-    tx: app_state::UiUpdateSender,
+    options: UploadOptions,
+    progress: ProgressReporter,
 ) -> eyre::Result<String> {
-    let file_size = std::fs::metadata(tar_path)
-        .map(|m| m.len())
-        .context("failed to get file size")?;
-
-    tx.send(app_state::UiUpdate::UpdateUploadProgress(Some(
-        ProgressData::default(),
-    )))
-    .await
-    .ok();
+    let recording_folder = archive.recording_folder;
+    let tar_path = archive.tar_path;
+    let file_size = archive.size_bytes;
+    let archive_sha256 = &archive.sha256;
+    let archive_filename = tar_path
+        .file_name()
+        .context("failed to get tar filename")?
+        .to_string_lossy()
+        .into_owned();
+
+    let mut session = match UploadSession::load(recording_folder) {
+        Some(session)
+            if session.is_resumable(&archive_filename, file_size, &archive.metadata_sha256) =>
+        {
+            tracing::info!(
+                "Resuming upload_id={} from chunk {}/{}",
+                session.upload_id,
+                session.chunk_etags.len() + 1,
+                session.total_chunks
+            );
+            session
+        }
+        stale_session => {
+            if let Some(stale_session) = stale_session {
+                tracing::info!(
+                    "Upload {} can no longer be resumed, starting over",
+                    stale_session.upload_id
+                );
+                api_client
+                    .abort_multipart_upload(api_token, &stale_session.upload_id)
+                    .await
+                    .ok();
+            }
 
-    let upload_session = api_client
-        .init_multipart_upload(
-            api_token,
-            tar_path,
-            file_size,
-            InitMultipartUploadArgs {
-                tags: None,
-                video_filename: Some(video_filename),
-                control_filename: Some(control_filename),
-                video_duration_seconds: Some(video_duration_seconds),
-                video_width: Some(constants::RECORDING_WIDTH),
-                video_height: Some(constants::RECORDING_HEIGHT),
-                video_fps: Some(constants::FPS as f32),
-                video_codec: None,
-                chunk_size_bytes: if unreliable_connection {
-                    Some(5 * 1024 * 1024)
-                } else {
-                    None
-                },
-            },
-        )
-        .await
-        .context("failed to initialize multipart upload")?;
+            let response = retry_rate_limited(&progress, options.max_retries, || {
+                api_client.init_multipart_upload(
+                    api_token,
+                    tar_path,
+                    file_size,
+                    InitMultipartUploadArgs {
+                        tags: archive.tags.as_deref(),
+                        video_filename: Some(&archive.video_filename),
+                        control_filename: Some(&archive.control_filename),
+                        video_duration_seconds: Some(archive.duration_seconds),
+                        video_width: Some(constants::RECORDING_WIDTH),
+                        video_height: Some(constants::RECORDING_HEIGHT),
+                        video_fps: Some(archive.fps as f32),
+                        video_codec: None,
+                        chunk_size_bytes: options.chunk_size_bytes,
+                        archive_sha256: Some(archive_sha256),
+                    },
+                )
+            })
+            .await
+            .context("failed to initialize multipart upload")?;
+
+            let session = UploadSession {
+                archive_filename,
+                archive_size_bytes: file_size,
+                metadata_sha256: Some(archive.metadata_sha256.clone()),
+                upload_id: response.upload_id,
+                game_control_id: response.game_control_id,
+                total_chunks: response.total_chunks,
+                chunk_size_bytes: response.chunk_size_bytes,
+                expires_at: response.expires_at,
+                chunk_etags: vec![],
+            };
+            session.save(recording_folder)?;
+            session
+        }
+    };
 
     tracing::info!(
         "Starting upload of {} bytes in {} chunks of {} bytes each; upload_id={}, game_control_id={}",
         file_size,
-        upload_session.total_chunks,
-        upload_session.chunk_size_bytes,
-        upload_session.upload_id,
-        upload_session.game_control_id
+        session.total_chunks,
+        session.chunk_size_bytes,
+        session.upload_id,
+        session.game_control_id
     );
 
-    // Set up auto-abort for upload
-    struct AbortUploadOnDrop {
-        api_client: Arc<ApiClient>,
-        api_token: String,
-        upload_id: Option<String>,
-    }
-    impl AbortUploadOnDrop {
-        pub fn disarm(&mut self) {
-            self.upload_id = None;
-        }
-    }
-    impl Drop for AbortUploadOnDrop {
-        fn drop(&mut self) {
-            if let Some(upload_id) = self.upload_id.take() {
-                tracing::info!("Aborting upload of {upload_id} (auto-abort)");
-                let api_client = self.api_client.clone();
-                let api_token = self.api_token.clone();
-                tokio::spawn(async move {
-                    api_client
-                        .abort_multipart_upload(&api_token, &upload_id)
-                        .await
-                        .ok();
-                });
-            }
-        }
-    }
-    let mut abort_upload_on_drop = AbortUploadOnDrop {
-        api_client: api_client.clone(),
-        api_token: api_token.to_string(),
-        upload_id: Some(upload_session.upload_id.clone()),
-    };
-
-    let mut chunk_etags = vec![];
-    let start_time = std::time::Instant::now();
+    let resumed_bytes = session.bytes_uploaded();
 
     struct UploadProgressState {
         bytes_uploaded: u64,
         last_update_time: std::time::Instant,
     }
     let upload_progress_state = Arc::new(Mutex::new(UploadProgressState {
-        bytes_uploaded: 0,
-        last_update_time: start_time,
+        bytes_uploaded: resumed_bytes,
+        last_update_time: std::time::Instant::now(),
     }));
+    send_progress(&progress, resumed_bytes, file_size);
 
     {
         let mut file = tokio::fs::File::open(tar_path)
             .await
             .context("failed to open tar file")?;
+        file.seek(SeekFrom::Start(resumed_bytes))
+            .await
+            .context("failed to seek to resume point in tar file")?;
 
         // TODO: make this less sloppy.
         // Instead of allocating a chunk-sized buffer, and then allocating that buffer
         // again for each chunk's stream, figure out a way to stream each chunk from the file
         // directly into the hasher, and then stream each chunk directly into the uploader
-        let mut buffer = vec![0u8; upload_session.chunk_size_bytes as usize];
-        let client = reqwest::Client::new();
-        for chunk_number in 1..=upload_session.total_chunks {
+        let mut buffer = vec![0u8; session.chunk_size_bytes as usize];
+        let client = api_client.http_client();
+        let first_chunk = session.chunk_etags.len() as u64 + 1;
+        for chunk_number in first_chunk..=session.total_chunks {
             tracing::info!(
                 "Uploading chunk {}/{} for upload_id {}",
                 chunk_number,
-                upload_session.total_chunks,
-                upload_session.upload_id
+                session.total_chunks,
+                session.upload_id
             );
 
             let mut buffer_start = 0;
@@ -508,85 +881,146 @@ async fn upload_tar(
             // After the loop, buffer_start is the total number of bytes read
             let chunk_size = buffer_start;
 
-            let chunk_data = buffer[..chunk_size].to_vec();
-            let chunk_hash = sha256::digest(&chunk_data);
-            let multipart_chunk_response = api_client
-                .upload_multipart_chunk(
-                    api_token,
-                    &upload_session.upload_id,
-                    chunk_number,
-                    &chunk_hash,
-                )
-                .await
-                .context("failed to upload chunk")?;
-
-            // Create a stream that wraps chunk_data and tracks upload progress
-            let progress_stream =
-                tokio_util::io::ReaderStream::new(std::io::Cursor::new(chunk_data)).inspect_ok({
-                    let tx = tx.clone();
-                    let ups = upload_progress_state.clone();
-                    move |bytes| {
-                        let bytes_uploaded =
-                            ups.lock().unwrap().bytes_uploaded + bytes.len() as u64;
-                        ups.lock().unwrap().bytes_uploaded = bytes_uploaded;
-
-                        let last_update_time = ups.lock().unwrap().last_update_time;
-                        if last_update_time.elapsed().as_millis() > 25 {
-                            send_progress(tx.clone(), bytes_uploaded, file_size, start_time);
-                            ups.lock().unwrap().last_update_time = std::time::Instant::now();
+            let chunk_data = &buffer[..chunk_size];
+            let chunk_hash = sha256::digest(chunk_data);
+            let chunk_start = upload_progress_state.lock().unwrap().bytes_uploaded;
+
+            let mut attempt = 0;
+            let etag = loop {
+                let result: eyre::Result<String> = async {
+                    let multipart_chunk_response = api_client
+                        .upload_multipart_chunk(
+                            api_token,
+                            &session.upload_id,
+                            chunk_number,
+                            &chunk_hash,
+                        )
+                        .await
+                        .context("failed to upload chunk")?;
+
+                    // Create a stream that wraps chunk_data and tracks upload progress
+                    let progress_stream = tokio_util::io::ReaderStream::new(std::io::Cursor::new(
+                        chunk_data.to_vec(),
+                    ))
+                    .and_then({
+                        let app_state = progress.app_state.clone();
+                        move |bytes| {
+                            let app_state = app_state.clone();
+                            async move {
+                                wait_for_bandwidth(&app_state, bytes.len()).await;
+                                Ok(bytes)
+                            }
+                        }
+                    })
+                    .inspect_ok({
+                        let progress = progress.clone();
+                        let ups = upload_progress_state.clone();
+                        move |bytes| {
+                            let bytes_uploaded =
+                                ups.lock().unwrap().bytes_uploaded + bytes.len() as u64;
+                            ups.lock().unwrap().bytes_uploaded = bytes_uploaded;
+
+                            let last_update_time = ups.lock().unwrap().last_update_time;
+                            if last_update_time.elapsed().as_millis() > 25 {
+                                send_progress(&progress, bytes_uploaded, file_size);
+                                ups.lock().unwrap().last_update_time = std::time::Instant::now();
+                            }
                         }
+                    });
+
+                    let res = client
+                        .put(&multipart_chunk_response.upload_url)
+                        .header("Content-Type", "application/octet-stream")
+                        .header("Content-Length", chunk_size)
+                        .body(reqwest::Body::wrap_stream(progress_stream))
+                        .send()
+                        .await
+                        .map_err(crate::api::request_error)
+                        .context("failed to stream chunk to upload url")?;
+
+                    if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        return Err(eyre::Report::new(RateLimited {
+                            retry_after: crate::api::retry_after(&res),
+                        })
+                        .wrap_err(format!(
+                            "Uploading chunk {chunk_number}/{} for upload_id {} was rate limited",
+                            session.total_chunks, session.upload_id
+                        )));
+                    }
+                    if !res.status().is_success() {
+                        eyre::bail!(
+                            "Uploading chunk {}/{} for upload_id {} failed with status: {}",
+                            chunk_number,
+                            session.total_chunks,
+                            session.upload_id,
+                            res.status()
+                        )
                     }
-                });
-
-            let res = client
-                .put(&multipart_chunk_response.upload_url)
-                .header("Content-Type", "application/octet-stream")
-                .header("Content-Length", chunk_size)
-                .body(reqwest::Body::wrap_stream(progress_stream))
-                .send()
-                .await
-                .context("failed to stream chunk to upload url")?;
-
-            if !res.status().is_success() {
-                eyre::bail!(
-                    "Uploading chunk {}/{} for upload_id {} failed with status: {}",
-                    chunk_number,
-                    upload_session.total_chunks,
-                    upload_session.upload_id,
-                    res.status()
-                )
-            }
+
+                    // Extract etag header from response
+                    res.headers()
+                        .get("etag")
+                        .and_then(|hv| hv.to_str().ok())
+                        .map(|s| s.trim_matches('"').to_owned())
+                        .ok_or_else(|| eyre::eyre!("No ETag header found after chunk upload"))
+                }
+                .await;
+
+                match result {
+                    Ok(etag) => break etag,
+                    // Retrying won't help if the key was revoked
+                    Err(e) if attempt < options.max_retries && !ApiKeyRejected::is_cause_of(&e) => {
+                        attempt += 1;
+                        let delay = retry_delay_after(&e, attempt);
+                        tracing::warn!(
+                            e=?e,
+                            "Chunk {chunk_number}/{} failed, retrying in {delay:?} (attempt {attempt}/{})",
+                            session.total_chunks,
+                            options.max_retries
+                        );
+
+                        // Whatever made it through of the failed attempt will be sent again
+                        upload_progress_state.lock().unwrap().bytes_uploaded = chunk_start;
+                        send_progress(&progress, chunk_start, file_size);
+                        if RateLimited::find_in(&e).is_some() {
+                            progress.wait_out_rate_limit(delay).await;
+                        } else {
+                            tokio::time::sleep(delay).await;
+                        }
+                    }
+                    Err(e) => return Err(e),
+                }
+            };
 
             send_progress(
-                tx.clone(),
+                &progress,
                 upload_progress_state.lock().unwrap().bytes_uploaded,
                 file_size,
-                start_time,
             );
 
-            // Extract etag header from response
-            let etag = res
-                .headers()
-                .get("etag")
-                .and_then(|hv| hv.to_str().ok())
-                .map(|s| s.trim_matches('"').to_owned())
-                .ok_or_else(|| eyre::eyre!("No ETag header found after chunk upload"))?;
-
-            chunk_etags.push(CompleteMultipartUploadChunk { chunk_number, etag });
+            session
+                .chunk_etags
+                .push(CompleteMultipartUploadChunk { chunk_number, etag });
+            if let Err(e) = session.save(recording_folder) {
+                // Not fatal; we just won't be able to resume from this chunk
+                tracing::warn!(e=?e, "Failed to save upload progress");
+            }
             tracing::info!(
                 "Uploaded chunk {}/{} for upload_id {}",
                 chunk_number,
-                upload_session.total_chunks,
-                upload_session.upload_id
+                session.total_chunks,
+                session.upload_id
             );
         }
     }
-    let completion_result = api_client
-        .complete_multipart_upload(api_token, &upload_session.upload_id, &chunk_etags)
-        .await
-        .context("failed to complete multipart upload")?;
+    let completion_result = retry_rate_limited(&progress, options.max_retries, || {
+        api_client.complete_multipart_upload(api_token, &session.upload_id, &session.chunk_etags)
+    })
+    .await
+    .context("failed to complete multipart upload")?;
 
-    abort_upload_on_drop.disarm();
+    // Either way, this session is finished with; a failed completion has to start over
+    UploadSession::remove(recording_folder);
 
     if !completion_result.success {
         eyre::bail!(
@@ -594,6 +1028,7 @@ async fn upload_tar(
             completion_result.message
         );
     }
+    verify_upload(archive_sha256, &completion_result)?;
 
     tracing::info!(
         "Upload completed successfully! Game Control ID: {}, Object Key: {}, Verified: {}",
@@ -605,13 +1040,80 @@ async fn upload_tar(
     Ok(completion_result.game_control_id)
 }
 
-fn send_progress(
-    tx: app_state::UiUpdateSender,
-    bytes_uploaded: u64,
-    total_bytes: u64,
-    start_time: std::time::Instant,
-) {
-    let bps = bytes_uploaded as f64 / start_time.elapsed().as_secs_f64();
+/// Makes sure that the server received the archive intact. Older servers don't report a checksum, in which
+/// case there's nothing to compare against.
+fn verify_upload(
+    archive_sha256: &str,
+    completion_result: &CompleteMultipartUploadResponse,
+) -> eyre::Result<()> {
+    match &completion_result.sha256 {
+        Some(sha256) if !sha256.eq_ignore_ascii_case(archive_sha256) => eyre::bail!(
+            "The uploaded archive was corrupted in transit (checksum {sha256}, expected {archive_sha256})"
+        ),
+        Some(_) => Ok(()),
+        None => {
+            tracing::warn!("The server didn't report a checksum, the upload couldn't be verified");
+            Ok(())
+        }
+    }
+}
+
+/// Exponential backoff for chunk retries: 2s, 4s, 8s, ... up to [`MAX_RETRY_DELAY`]
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.min(6)).min(MAX_RETRY_DELAY)
+}
+
+/// How long to wait before retrying after `error`: as long as the server asked if it was rate limiting us,
+/// as trying any sooner would only be turned away again, and the usual backoff otherwise
+fn retry_delay_after(error: &eyre::Report, attempt: u32) -> Duration {
+    RateLimited::find_in(error)
+        .and_then(|rate_limited| rate_limited.retry_after)
+        .unwrap_or_else(|| retry_delay(attempt))
+}
+
+/// Makes `request` again for as long as the server turns it away for making too many, up to `max_retries` times
+async fn retry_rate_limited<T, F>(
+    progress: &ProgressReporter,
+    max_retries: u32,
+    mut request: impl FnMut() -> F,
+) -> eyre::Result<T>
+where
+    F: Future<Output = eyre::Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Err(e) if attempt < max_retries && RateLimited::find_in(&e).is_some() => {
+                attempt += 1;
+                let delay = retry_delay_after(&e, attempt);
+                tracing::warn!(
+                    e=?e,
+                    "Rate limited, retrying in {delay:?} (attempt {attempt}/{max_retries})"
+                );
+                progress.wait_out_rate_limit(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Waits until `bytes` more can be sent without going over the user's upload speed limit
+async fn wait_for_bandwidth(app_state: &AppState, bytes: usize) {
+    let limit = app_state
+        .config
+        .read()
+        .unwrap()
+        .preferences
+        .upload_speed_limit_at(chrono::Local::now().hour());
+    app_state.upload_throttle.wait(bytes, limit).await;
+}
+
+fn send_progress(progress: &ProgressReporter, bytes_uploaded: u64, total_bytes: u64) {
+    let bps = progress
+        .speed
+        .lock()
+        .unwrap()
+        .update(std::time::Instant::now(), bytes_uploaded);
     let data = ProgressData {
         bytes_uploaded,
         total_bytes,
@@ -627,8 +1129,7 @@ fn send_progress(
             0.0
         },
     };
-    tx.try_send(app_state::UiUpdate::UpdateUploadProgress(Some(data)))
-        .ok();
+    progress.report(data);
 }
 
 /// Scans the recording location for folders with .invalid files or without .uploaded files and returns information about them
@@ -641,13 +1142,25 @@ pub fn scan_local_recordings(recording_location: &Path) -> Vec<LocalRecording> {
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if !path.is_dir() {
+        // The instant replay buffer only becomes recordings once it's saved, and imports once they're unpacked
+        if !path.is_dir()
+            || entry.file_name() == constants::filename::recording::REPLAY_BUFFER
+            || entry.file_name() == constants::filename::recording::IMPORTING
+        {
             continue;
         }
 
         let invalid_file_path = path.join(".invalid");
         let uploaded_file_path = path.join(".uploaded");
 
+        // Still being recorded, or waiting to be recovered
+        if path
+            .join(constants::filename::recording::IN_PROGRESS)
+            .is_file()
+        {
+            continue;
+        }
+
         // Get the folder name
         let folder_name = path
             .file_name()
@@ -655,11 +1168,12 @@ pub fn scan_local_recordings(recording_location: &Path) -> Vec<LocalRecording> {
             .unwrap_or("Unknown")
             .to_string();
 
-        // Parse the timestamp from the folder name (unix timestamp in seconds)
-        // Surely the user won't change the folder name :cluegi:
+        // Parse the timestamp from the folder name (unix timestamp in seconds). Folders can be named
+        // differently (see [`crate::record::recording_folder_name`]), in which case the metadata knows.
         let timestamp = folder_name
             .parse::<u64>()
             .ok()
+            .or_else(|| read_metadata(&path).ok().map(|m| m.start_timestamp))
             .map(|secs| std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs));
 
         if invalid_file_path.is_file() {
@@ -678,10 +1192,20 @@ pub fn scan_local_recordings(recording_location: &Path) -> Vec<LocalRecording> {
             });
         } else if !uploaded_file_path.is_file() {
             // Not uploaded yet (and not invalid)
+            let metadata =
+                std::fs::read_to_string(path.join(constants::filename::recording::METADATA))
+                    .ok()
+                    .and_then(|m| serde_json::from_str::<Metadata>(&m).ok());
+            let size_bytes = folder_size(&path);
+
             local_recordings.push(LocalRecording::Unuploaded {
                 folder_name,
                 folder_path: path,
                 timestamp,
+                duration: metadata.as_ref().map(|m| m.duration),
+                size_bytes,
+                labels: metadata.and_then(|m| m.labels),
+                test: is_test_recording(&path),
             });
         }
     }
@@ -695,3 +1219,293 @@ pub fn scan_local_recordings(recording_location: &Path) -> Vec<LocalRecording> {
 
     local_recordings
 }
+
+/// Whether the recording in `folder_path` was made as a test, and so must never be uploaded
+pub fn is_test_recording(folder_path: &Path) -> bool {
+    folder_path
+        .join(constants::filename::recording::TEST)
+        .is_file()
+}
+
+/// Total size of the files in a recording folder
+pub(crate) fn folder_size(path: &Path) -> u64 {
+    path.read_dir()
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::fake_server::{FakeResponse, FakeServer},
+        util::TestDir,
+    };
+
+    fn completion(sha256: Option<&str>) -> CompleteMultipartUploadResponse {
+        serde_json::from_value(serde_json::json!({
+            "success": true,
+            "game_control_id": "gc-1",
+            "object_key": "key",
+            "message": "done",
+            "sha256": sha256,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_upload() {
+        assert!(verify_upload("ab12", &completion(Some("ab12"))).is_ok());
+        assert!(verify_upload("ab12", &completion(Some("AB12"))).is_ok());
+        assert!(verify_upload("ab12", &completion(None)).is_ok());
+        assert!(verify_upload("ab12", &completion(Some("cd34"))).is_err());
+    }
+
+    /// A recording with the archive and compact inputs from an upload of it, as they're left when it fails
+    fn recording_being_uploaded(dir: &Path, encrypted: bool) -> ValidationResult {
+        let validation = ValidationResult {
+            mp4_path: dir.join(constants::filename::recording::VIDEO),
+            csv_path: dir.join(constants::filename::recording::INPUTS),
+            meta_path: dir.join(constants::filename::recording::METADATA),
+            metadata: serde_json::from_value(serde_json::json!({
+                "game_exe": "game.exe",
+                "session_id": "session-1",
+                "hardware_id": "hw-1",
+                "hardware_specs": null,
+                "start_timestamp": 0,
+                "end_timestamp": 60,
+                "duration": 60.0,
+            }))
+            .unwrap(),
+        };
+        std::fs::write(&validation.mp4_path, b"video").unwrap();
+        std::fs::write(&validation.csv_path, b"timestamp,event_type,event_args\n").unwrap();
+        if encrypted {
+            encryption::encrypt_file(&validation.mp4_path, "secret").unwrap();
+            encryption::encrypt_file(&validation.csv_path, "secret").unwrap();
+        }
+        std::fs::write(dir.join("0123456789abcdef.tar"), b"video and inputs").unwrap();
+        std::fs::write(
+            dir.join(constants::filename::recording::INPUTS_COMPACT),
+            b"inputs",
+        )
+        .unwrap();
+        validation
+    }
+
+    fn upload_files(dir: &Path) -> Vec<String> {
+        let mut files = dir
+            .read_dir()
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| {
+                name.ends_with(".tar") || name == constants::filename::recording::INPUTS_COMPACT
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    /// Records a couple of seconds of the test window with the mock backend, into `dir`'s first segment
+    async fn record(dir: &Path, app_state: &Arc<AppState>) -> ValidationResult {
+        let window = crate::record::TestWindow::new(1280, 720);
+        let mut recorder = crate::record::Recorder::for_test(app_state.clone(), dir, window.0);
+        recorder
+            .start(&crate::unsupported_games::UnsupportedGames::load_from_embedded())
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        recorder.stop().await.unwrap();
+
+        // Not run through `validate_folder`, as the mock's video isn't one
+        let path = dir.join("0");
+        let meta_path = path.join(constants::filename::recording::METADATA);
+        ValidationResult {
+            mp4_path: path.join(constants::filename::recording::VIDEO),
+            csv_path: path.join(constants::filename::recording::INPUTS),
+            metadata: serde_json::from_str(&std::fs::read_to_string(&meta_path).unwrap()).unwrap(),
+            meta_path,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recording_is_uploaded_after_a_failed_chunk() {
+        let dir = TestDir::new("upload");
+        let mut config = crate::config::Config::default();
+        config.credentials.accept_consent();
+        let (app_state, _, _) = AppState::for_test(config);
+        let validation = record(&dir, &app_state).await;
+        let path = dir.join("0");
+
+        // Chunks go wherever the server says, which is usually storage of its own
+        let storage = FakeServer::start(vec![
+            FakeResponse::status(503),
+            FakeResponse::status(200).with_header("ETag", "\"etag-1\""),
+        ])
+        .await;
+        let chunk = serde_json::json!({
+            "upload_url": format!("{}/chunk/1", storage.base_url),
+            "chunk_number": 1,
+            "expires_at": 0,
+        });
+        let server = FakeServer::start(vec![
+            FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "upload_id": "upload-1",
+                    "game_control_id": "gc-1",
+                    "total_chunks": 1,
+                    "chunk_size_bytes": 64 * 1024 * 1024,
+                    "expires_at": chrono::Utc::now().timestamp() + 3600,
+                }),
+            ),
+            FakeResponse::json(200, chunk.clone()),
+            FakeResponse::json(200, chunk),
+            FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "success": true,
+                    "game_control_id": "gc-1",
+                    "object_key": "key",
+                    "message": "done",
+                }),
+            ),
+        ])
+        .await;
+        let api_client = Arc::new(ApiClient::new(server.base_url.clone(), None));
+        let sinks = sink::enabled_sinks(
+            true,
+            &crate::config::S3Destination::default(),
+            &api_client,
+            "api-key".to_string(),
+            UploadOptions {
+                chunk_size_bytes: None,
+                max_retries: 1,
+                compact_inputs: false,
+            },
+        )
+        .unwrap();
+        let progress = ProgressReporter {
+            app_state,
+            folder_path: path.clone(),
+            speed: Default::default(),
+        };
+
+        let result = upload_archive(
+            &path,
+            &validation,
+            &sinks,
+            &RecordingKeys::default(),
+            false,
+            progress,
+        )
+        .await;
+        let stats = finish_upload(&path, &validation, result).unwrap();
+
+        let requests = server.requests();
+        let paths = requests.iter().map(|r| r.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "/tracker/upload/game_control/multipart/init",
+                "/tracker/upload/game_control/multipart/chunk",
+                "/tracker/upload/game_control/multipart/chunk",
+                "/tracker/upload/game_control/multipart/complete",
+            ]
+        );
+        let init = requests[0].json();
+        assert_eq!(init["video_fps"], constants::DEFAULT_FPS as f64);
+        assert_eq!(init["total_size_bytes"], stats.bytes);
+        assert_eq!(
+            requests[3].json()["chunk_etags"],
+            serde_json::json!([{ "chunk_number": 1, "etag": "etag-1" }])
+        );
+
+        // The failed chunk was sent again whole
+        let puts = storage.requests();
+        assert_eq!(puts.len(), 2);
+        assert!(
+            puts.iter()
+                .all(|r| r.method == "PUT" && r.path == "/chunk/1")
+        );
+        assert_eq!(puts[0].body, puts[1].body);
+        assert_eq!(puts[1].body.len() as u64, stats.bytes);
+
+        // The archive has everything that was recorded
+        let mut archive = tar::Archive::new(puts[1].body.as_slice());
+        let mut files = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .path()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                constants::filename::recording::INPUTS,
+                constants::filename::recording::METADATA,
+                constants::filename::recording::VIDEO,
+            ]
+        );
+
+        assert_eq!(
+            std::fs::read_to_string(path.join(constants::filename::recording::UPLOADED)).unwrap(),
+            "gc-1"
+        );
+        assert!(UploadSession::load(&path).is_none());
+        assert!(upload_files(&path).is_empty());
+    }
+
+    #[test]
+    fn test_failed_upload_leaves_no_plaintext_behind() {
+        let dir = TestDir::new("upload");
+        let validation = recording_being_uploaded(&dir, true);
+        assert!(finish_upload(&dir, &validation, Err(eyre::eyre!("connection reset"))).is_err());
+        assert!(upload_files(&dir).is_empty());
+        assert!(!dir.join(constants::filename::recording::UPLOADED).exists());
+        // The recording itself is left alone, to be packed again next time
+        assert!(encryption::is_encrypted(&validation.mp4_path).unwrap());
+        assert!(encryption::is_encrypted(&validation.csv_path).unwrap());
+    }
+
+    #[test]
+    fn test_failed_upload_keeps_archive_to_resume() {
+        let dir = TestDir::new("upload");
+        let validation = recording_being_uploaded(&dir, false);
+        assert!(finish_upload(&dir, &validation, Err(eyre::eyre!("connection reset"))).is_err());
+        assert_eq!(
+            upload_files(&dir),
+            [
+                "0123456789abcdef.tar",
+                constants::filename::recording::INPUTS_COMPACT
+            ]
+        );
+
+        let stats = finish_upload(&dir, &validation, Ok(("gc-1".to_string(), 16))).unwrap();
+        assert_eq!(stats.bytes, 16);
+        assert_eq!(
+            std::fs::read_to_string(dir.join(constants::filename::recording::UPLOADED)).unwrap(),
+            "gc-1"
+        );
+        // The compact inputs are kept alongside the plain recording
+        assert_eq!(
+            upload_files(&dir),
+            [constants::filename::recording::INPUTS_COMPACT]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Tracks which recordings are waiting to be uploaded, being uploaded, or done, so that several
+//! recordings can be uploaded in the background (possibly at the same time) while recording continues.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::upload::ProgressData;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadState {
+    Pending,
+    Uploading,
+    Done,
+    /// Failed or cancelled; stays in the queue until the user retries it
+    Failed(String),
+    /// The API key was rejected; picked up again once the user has logged in with a working key
+    AwaitingLogin,
+    /// The connection dropped; picked up again from the last chunk once the server can be reached
+    AwaitingNetwork,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueuedUpload {
+    pub folder_name: String,
+    pub folder_path: PathBuf,
+    pub state: UploadState,
+    /// Only set while uploading
+    pub progress: Option<ProgressData>,
+    /// Set while the upload is waiting until then, because the server asked it to slow down
+    pub rate_limited_until: Option<Instant>,
+    cancellation_token: CancellationToken,
+}
+
+#[derive(Debug, Default)]
+pub struct UploadQueue {
+    items: Vec<QueuedUpload>,
+}
+impl UploadQueue {
+    pub fn items(&self) -> &[QueuedUpload] {
+        &self.items
+    }
+
+    /// Whether anything is still waiting to be uploaded or being uploaded
+    pub fn is_active(&self) -> bool {
+        self.items
+            .iter()
+            .any(|i| matches!(i.state, UploadState::Pending | UploadState::Uploading))
+    }
+
+    /// Whether anything is held back until the connection comes back
+    pub fn is_awaiting_network(&self) -> bool {
+        self.items
+            .iter()
+            .any(|i| i.state == UploadState::AwaitingNetwork)
+    }
+
+    /// Adds a recording to the queue. Recordings that are already queued are left alone,
+    /// including failed ones, which have to be retried explicitly.
+    pub fn enqueue(&mut self, folder_path: PathBuf) -> bool {
+        if let Some(item) = self.find(&folder_path) {
+            if item.state != UploadState::Done {
+                return false;
+            }
+            self.items.retain(|i| i.folder_path != folder_path);
+        }
+
+        self.items.push(QueuedUpload {
+            folder_name: folder_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "Unknown".to_string()),
+            folder_path,
+            state: UploadState::Pending,
+            progress: None,
+            rate_limited_until: None,
+            cancellation_token: CancellationToken::new(),
+        });
+        true
+    }
+
+    /// Puts a failed upload back in line
+    pub fn retry(&mut self, folder_path: &Path) -> bool {
+        match self.find_mut(folder_path) {
+            Some(item) if matches!(item.state, UploadState::Failed(_)) => {
+                item.state = UploadState::Pending;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Stops an upload that's in progress, or takes a pending one out of line. Either way,
+    /// the recording stays in the queue as failed so that it can be retried.
+    pub fn cancel(&mut self, folder_path: &Path) {
+        let Some(item) = self.find_mut(folder_path) else {
+            return;
+        };
+        match item.state {
+            UploadState::Pending => item.state = UploadState::Failed("Cancelled".to_string()),
+            // The upload task notices this and reports back through [`Self::finish`]
+            UploadState::Uploading => item.cancellation_token.cancel(),
+            UploadState::AwaitingLogin | UploadState::AwaitingNetwork => {
+                item.state = UploadState::Failed("Cancelled".to_string())
+            }
+            UploadState::Done | UploadState::Failed(_) => {}
+        }
+    }
+
+    /// Holds back everything that hasn't been uploaded yet, after the API key stopped working
+    pub(super) fn hold_for_login(&mut self, folder_path: &Path) {
+        if let Some(item) = self.find_mut(folder_path) {
+            item.progress = None;
+            item.rate_limited_until = None;
+            item.state = UploadState::AwaitingLogin;
+        }
+        self.hold_pending_for_login();
+    }
+
+    /// Holds back everything that's waiting to be uploaded, as there's no validated API key to upload with
+    pub(super) fn hold_pending_for_login(&mut self) {
+        for item in &mut self.items {
+            if item.state == UploadState::Pending {
+                item.state = UploadState::AwaitingLogin;
+            }
+        }
+    }
+
+    /// Puts everything that was held back by [`Self::hold_for_login`] back in line
+    pub fn resume_after_login(&mut self) -> bool {
+        let mut resumed = false;
+        for item in &mut self.items {
+            if item.state == UploadState::AwaitingLogin {
+                item.state = UploadState::Pending;
+                resumed = true;
+            }
+        }
+        resumed
+    }
+
+    /// Holds back everything that hasn't been uploaded yet, after the connection dropped mid-upload
+    pub(super) fn hold_for_network(&mut self, folder_path: &Path) {
+        if let Some(item) = self.find_mut(folder_path) {
+            item.progress = None;
+            item.rate_limited_until = None;
+            item.state = UploadState::AwaitingNetwork;
+        }
+        self.hold_pending_for_network();
+    }
+
+    /// Holds back everything that's waiting to be uploaded, as it would only fail without a connection
+    pub(super) fn hold_pending_for_network(&mut self) {
+        for item in &mut self.items {
+            if item.state == UploadState::Pending {
+                item.state = UploadState::AwaitingNetwork;
+            }
+        }
+    }
+
+    /// Puts everything that was held back by [`Self::hold_for_network`] back in line
+    pub fn resume_after_network(&mut self) -> bool {
+        let mut resumed = false;
+        for item in &mut self.items {
+            if item.state == UploadState::AwaitingNetwork {
+                item.state = UploadState::Pending;
+                resumed = true;
+            }
+        }
+        resumed
+    }
+
+    /// Combined progress of everything that's uploading right now
+    pub fn total_progress(&self) -> Option<ProgressData> {
+        let mut total: Option<ProgressData> = None;
+        for progress in self.items.iter().filter_map(|i| i.progress.as_ref()) {
+            let total = total.get_or_insert_with(ProgressData::default);
+            total.bytes_uploaded += progress.bytes_uploaded;
+            total.total_bytes += progress.total_bytes;
+            total.speed_mbps += progress.speed_mbps;
+            // Uploads run side by side, so the slowest one decides when they're all done
+            total.eta_seconds = total.eta_seconds.max(progress.eta_seconds);
+        }
+        if let Some(total) = &mut total
+            && total.total_bytes > 0
+        {
+            total.percent =
+                (total.bytes_uploaded as f64 / total.total_bytes as f64 * 100.0).min(100.0);
+        }
+        total
+    }
+
+    /// Removes uploads that have completed successfully
+    pub fn clear_done(&mut self) {
+        self.items.retain(|i| i.state != UploadState::Done);
+    }
+
+    /// Marks the next pending upload as uploading, as long as fewer than `max_concurrent` uploads are already running.
+    pub(super) fn start_next(
+        &mut self,
+        max_concurrent: usize,
+    ) -> Option<(PathBuf, CancellationToken)> {
+        let uploading = self
+            .items
+            .iter()
+            .filter(|i| i.state == UploadState::Uploading)
+            .count();
+        if uploading >= max_concurrent {
+            return None;
+        }
+
+        let item = self
+            .items
+            .iter_mut()
+            .find(|i| i.state == UploadState::Pending)?;
+        item.state = UploadState::Uploading;
+        item.progress = Some(ProgressData::default());
+        item.cancellation_token = CancellationToken::new();
+        Some((item.folder_path.clone(), item.cancellation_token.clone()))
+    }
+
+    pub(super) fn set_progress(&mut self, folder_path: &Path, progress: ProgressData) {
+        if let Some(item) = self.find_mut(folder_path)
+            && item.state == UploadState::Uploading
+        {
+            item.progress = Some(progress);
+        }
+    }
+
+    pub(super) fn set_rate_limited(&mut self, folder_path: &Path, until: Option<Instant>) {
+        if let Some(item) = self.find_mut(folder_path)
+            && item.state == UploadState::Uploading
+        {
+            item.rate_limited_until = until;
+        }
+    }
+
+    pub(super) fn finish(&mut self, folder_path: &Path, result: Result<(), String>) {
+        if let Some(item) = self.find_mut(folder_path) {
+            item.progress = None;
+            item.rate_limited_until = None;
+            item.state = match result {
+                Ok(()) => UploadState::Done,
+                Err(e) => UploadState::Failed(e),
+            };
+        }
+    }
+
+    fn find(&self, folder_path: &Path) -> Option<&QueuedUpload> {
+        self.items.iter().find(|i| i.folder_path == folder_path)
+    }
+
+    fn find_mut(&mut self, folder_path: &Path) -> Option<&mut QueuedUpload> {
+        self.items.iter_mut().find(|i| i.folder_path == folder_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrency_limit() {
+        let mut queue = UploadQueue::default();
+        for name in ["1", "2", "3"] {
+            assert!(queue.enqueue(PathBuf::from(name)));
+        }
+        // Already queued
+        assert!(!queue.enqueue(PathBuf::from("1")));
+
+        assert_eq!(queue.start_next(2).unwrap().0, PathBuf::from("1"));
+        assert_eq!(queue.start_next(2).unwrap().0, PathBuf::from("2"));
+        assert!(queue.start_next(2).is_none());
+
+        queue.finish(Path::new("1"), Ok(()));
+        assert_eq!(queue.start_next(2).unwrap().0, PathBuf::from("3"));
+        assert!(queue.is_active());
+    }
+
+    #[test]
+    fn test_failed_uploads_stay_until_retried() {
+        let mut queue = UploadQueue::default();
+        queue.enqueue(PathBuf::from("1"));
+        let (_, token) = queue.start_next(1).unwrap();
+
+        queue.cancel(Path::new("1"));
+        assert!(token.is_cancelled());
+        queue.finish(Path::new("1"), Err("Cancelled".to_string()));
+        assert!(!queue.is_active());
+        assert!(queue.start_next(1).is_none());
+
+        // Enqueuing everything again doesn't pick up failed uploads
+        assert!(!queue.enqueue(PathBuf::from("1")));
+        assert!(queue.retry(Path::new("1")));
+        assert_eq!(queue.start_next(1).unwrap().0, PathBuf::from("1"));
+    }
+
+    #[test]
+    fn test_uploads_held_for_login() {
+        let mut queue = UploadQueue::default();
+        for name in ["1", "2", "3"] {
+            queue.enqueue(PathBuf::from(name));
+        }
+        queue.start_next(1).unwrap();
+        queue.hold_for_login(Path::new("1"));
+        // Nothing goes out with the rejected key, and the headless upload-on-exit doesn't wait forever
+        assert!(!queue.is_active());
+        assert!(queue.start_next(1).is_none());
+        assert!(
+            queue
+                .items()
+                .iter()
+                .all(|i| i.state == UploadState::AwaitingLogin)
+        );
+
+        assert!(queue.resume_after_login());
+        assert_eq!(queue.start_next(1).unwrap().0, PathBuf::from("1"));
+        assert!(!queue.resume_after_login());
+    }
+
+    #[test]
+    fn test_uploads_held_for_network() {
+        let mut queue = UploadQueue::default();
+        for name in ["1", "2"] {
+            queue.enqueue(PathBuf::from(name));
+        }
+        queue.start_next(1).unwrap();
+        queue.hold_for_network(Path::new("1"));
+        assert!(queue.is_awaiting_network());
+        assert!(!queue.is_active());
+        assert!(queue.start_next(1).is_none());
+
+        // Logging in again doesn't help without a connection
+        assert!(!queue.resume_after_login());
+        assert!(queue.resume_after_network());
+        assert!(!queue.is_awaiting_network());
+        assert_eq!(queue.start_next(1).unwrap().0, PathBuf::from("1"));
+
+        // Recordings queued while offline wait along with the rest
+        queue.hold_for_network(Path::new("1"));
+        queue.enqueue(PathBuf::from("3"));
+        queue.hold_pending_for_network();
+        assert!(
+            queue
+                .items()
+                .iter()
+                .all(|i| i.state == UploadState::AwaitingNetwork)
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Persisted state for in-progress uploads, so that an interrupted upload (network failure, crash, app closed)
+//! can pick up from the last confirmed chunk instead of starting from scratch.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{self, Context as _};
+use serde::{Deserialize, Serialize};
+
+use crate::api::CompleteMultipartUploadChunk;
+
+/// Don't try to resume a session that's about to expire; the remaining chunks are unlikely to make it in time.
+const EXPIRY_MARGIN_SECS: u64 = 5 * 60;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct UploadSession {
+    /// Name of the archive within the recording folder that is being uploaded
+    pub archive_filename: String,
+    pub archive_size_bytes: u64,
+    /// Of the recording's metadata when the archive was packed, in hex. The labels are kept in the metadata and
+    /// can be changed after a failed upload, so the archive is only still current if this matches. Missing from
+    /// sessions saved before it was recorded.
+    #[serde(default)]
+    pub metadata_sha256: Option<String>,
+    pub upload_id: String,
+    pub game_control_id: String,
+    pub total_chunks: u64,
+    pub chunk_size_bytes: u64,
+    /// Unix timestamp
+    pub expires_at: u64,
+    /// Chunks that the server has confirmed, in order
+    pub chunk_etags: Vec<CompleteMultipartUploadChunk>,
+}
+impl UploadSession {
+    pub fn path(recording_folder: &Path) -> PathBuf {
+        recording_folder.join(constants::filename::recording::UPLOAD_PROGRESS)
+    }
+
+    /// Loads the session for this recording folder, if there is one.
+    pub fn load(recording_folder: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(Self::path(recording_folder)).ok()?;
+        match serde_json::from_str::<Self>(&contents) {
+            Ok(session) => Some(session),
+            Err(e) => {
+                tracing::warn!(e=?e, "Failed to parse upload progress in {recording_folder:?}, ignoring it");
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, recording_folder: &Path) -> eyre::Result<()> {
+        std::fs::write(Self::path(recording_folder), serde_json::to_string(self)?)
+            .context("failed to save upload progress")
+    }
+
+    pub fn remove(recording_folder: &Path) {
+        std::fs::remove_file(Self::path(recording_folder)).ok();
+    }
+
+    /// Whether the archive was packed from the recording's current metadata
+    pub fn matches_metadata(&self, metadata_sha256: &str) -> bool {
+        self.metadata_sha256.as_deref() == Some(metadata_sha256)
+    }
+
+    /// Whether this session is for the given archive, and the server will still accept chunks for it
+    pub fn is_resumable(
+        &self,
+        archive_filename: &str,
+        archive_size_bytes: u64,
+        metadata_sha256: &str,
+    ) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.archive_filename == archive_filename
+            && self.archive_size_bytes == archive_size_bytes
+            && self.matches_metadata(metadata_sha256)
+            && self.expires_at > now + EXPIRY_MARGIN_SECS
+    }
+
+    /// Bytes of the archive that have already been uploaded
+    pub fn bytes_uploaded(&self) -> u64 {
+        (self.chunk_etags.len() as u64 * self.chunk_size_bytes).min(self.archive_size_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARCHIVE: &str = "0123456789abcdef.tar";
+
+    fn session(expires_in_secs: i64) -> UploadSession {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        UploadSession {
+            archive_filename: ARCHIVE.to_string(),
+            archive_size_bytes: 2500,
+            metadata_sha256: Some("ab12".to_string()),
+            upload_id: "upload-1".to_string(),
+            game_control_id: "gc-1".to_string(),
+            total_chunks: 3,
+            chunk_size_bytes: 1000,
+            expires_at: now.saturating_add_signed(expires_in_secs),
+            chunk_etags: vec![],
+        }
+    }
+
+    fn chunks(count: u64) -> Vec<CompleteMultipartUploadChunk> {
+        (1..=count)
+            .map(|chunk_number| CompleteMultipartUploadChunk {
+                chunk_number,
+                etag: format!("etag-{chunk_number}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_is_resumable() {
+        let current = session(60 * 60);
+        assert!(current.is_resumable(ARCHIVE, 2500, "ab12"));
+
+        // A different archive, or one packed before the labels were changed
+        assert!(!current.is_resumable("fedcba9876543210.tar", 2500, "ab12"));
+        assert!(!current.is_resumable(ARCHIVE, 2400, "ab12"));
+        assert!(!current.is_resumable(ARCHIVE, 2500, "cd34"));
+        let legacy = UploadSession {
+            metadata_sha256: None,
+            ..session(60 * 60)
+        };
+        assert!(!legacy.is_resumable(ARCHIVE, 2500, "ab12"));
+    }
+
+    #[test]
+    fn test_expiring_sessions_are_not_resumable() {
+        assert!(!session(-60).is_resumable(ARCHIVE, 2500, "ab12"));
+        // Still accepted by the server, but not for long enough to be worth it
+        assert!(!session(60).is_resumable(ARCHIVE, 2500, "ab12"));
+        let margin = EXPIRY_MARGIN_SECS as i64;
+        assert!(!session(margin).is_resumable(ARCHIVE, 2500, "ab12"));
+        assert!(session(margin + 60).is_resumable(ARCHIVE, 2500, "ab12"));
+    }
+
+    #[test]
+    fn test_bytes_uploaded() {
+        let mut partial = session(60 * 60);
+        assert_eq!(partial.bytes_uploaded(), 0);
+        partial.chunk_etags = chunks(2);
+        assert_eq!(partial.bytes_uploaded(), 2000);
+        // The last chunk is only as long as what's left of the archive
+        partial.chunk_etags = chunks(3);
+        assert_eq!(partial.bytes_uploaded(), 2500);
+    }
+}
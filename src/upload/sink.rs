@@ -0,0 +1,104 @@
+//! Where recordings are uploaded to. Each recording is packed into one archive, which is then handed to every
+//! enabled sink in turn: the API server, and/or an S3-compatible bucket of the user's own.
+//!
+//! To upload somewhere else, implement [`UploadSink`] and add it to [`enabled_sinks`].
+
+use std::{path::Path, sync::Arc};
+
+use color_eyre::eyre;
+
+use crate::{
+    api::ApiClient,
+    config::S3Destination,
+    upload::{ProgressReporter, UploadOptions, s3::S3Sink},
+};
+
+/// A recording's archive, ready to be uploaded
+pub(super) struct UploadArchive<'a> {
+    pub recording_folder: &'a Path,
+    pub tar_path: &'a Path,
+    pub size_bytes: u64,
+    /// Of the whole archive, in hex
+    pub sha256: String,
+    /// Of the recording's metadata as it was packed, in hex
+    pub metadata_sha256: String,
+    pub video_filename: String,
+    pub control_filename: String,
+    pub duration_seconds: f32,
+    pub fps: u32,
+    pub tags: Option<Vec<String>>,
+}
+
+#[async_trait::async_trait]
+pub(super) trait UploadSink: Send + Sync {
+    /// What the destination is called in logs and errors
+    fn name(&self) -> &'static str;
+
+    /// Uploads `archive`, returning the destination's ID for it. Only succeeds once the destination has
+    /// confirmed that it received the archive intact, i.e. that its checksum matches [`UploadArchive::sha256`].
+    async fn upload(
+        &self,
+        archive: &UploadArchive<'_>,
+        progress: &ProgressReporter,
+    ) -> eyre::Result<String>;
+}
+
+/// The API server, through its multipart upload endpoints
+struct ServerSink {
+    api_client: Arc<ApiClient>,
+    api_token: String,
+    options: UploadOptions,
+}
+#[async_trait::async_trait]
+impl UploadSink for ServerSink {
+    fn name(&self) -> &'static str {
+        "the server"
+    }
+
+    async fn upload(
+        &self,
+        archive: &UploadArchive<'_>,
+        progress: &ProgressReporter,
+    ) -> eyre::Result<String> {
+        super::upload_tar(
+            archive,
+            self.api_client.clone(),
+            &self.api_token,
+            self.options,
+            progress.clone(),
+        )
+        .await
+    }
+}
+
+/// Every sink that's turned on, in the order they're uploaded to. The bucket goes first, as uploading to it
+/// again just replaces the same object: if the server's upload then fails, retrying doesn't leave duplicates.
+pub(super) fn enabled_sinks(
+    upload_to_server: bool,
+    s3_destination: &S3Destination,
+    api_client: &Arc<ApiClient>,
+    api_token: String,
+    options: UploadOptions,
+) -> eyre::Result<Vec<Box<dyn UploadSink>>> {
+    let mut sinks: Vec<Box<dyn UploadSink>> = vec![];
+    if s3_destination.is_usable() {
+        sinks.push(Box::new(S3Sink::new(
+            s3_destination,
+            api_client.http_client(),
+            options.max_retries,
+        )?));
+    }
+    if upload_to_server {
+        sinks.push(Box::new(ServerSink {
+            api_client: api_client.clone(),
+            api_token,
+            options,
+        }));
+    }
+    if sinks.is_empty() {
+        eyre::bail!(
+            "There's nowhere to upload to: uploading to the server is off, and the S3 bucket isn't set up"
+        );
+    }
+    Ok(sinks)
+}
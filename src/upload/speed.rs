@@ -0,0 +1,69 @@
+//! Upload speed averaged over a sliding window, so that the speed and ETA follow changes in
+//! connection speed instead of being dragged down (or up) by how the upload started.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+const WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+pub(super) struct RollingSpeed {
+    /// (time, total bytes uploaded at that time), oldest first
+    samples: VecDeque<(Instant, u64)>,
+}
+impl RollingSpeed {
+    /// Records how many bytes have been uploaded in total, and returns the current speed in bytes per second
+    pub fn update(&mut self, now: Instant, bytes_uploaded: u64) -> f64 {
+        // Progress went backwards (a chunk is being retried), so the old samples no longer line up
+        if self
+            .samples
+            .back()
+            .is_some_and(|&(_, bytes)| bytes_uploaded < bytes)
+        {
+            self.samples.clear();
+        }
+        self.samples.push_back((now, bytes_uploaded));
+
+        // Keep one sample from just outside the window, so that the window is always covered
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|&(time, _)| now.duration_since(time) >= WINDOW)
+        {
+            self.samples.pop_front();
+        }
+
+        let &(oldest_time, oldest_bytes) = self.samples.front().unwrap();
+        let elapsed = now.duration_since(oldest_time).as_secs_f64();
+        if elapsed > 0.0 {
+            (bytes_uploaded - oldest_bytes) as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_speed() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut speed = RollingSpeed::default();
+
+        assert_eq!(speed.update(at(0), 0), 0.0);
+        // 100 B/s for the first 20 seconds...
+        assert_eq!(speed.update(at(10), 1000), 100.0);
+        assert_eq!(speed.update(at(20), 2000), 100.0);
+        // ...then 10 B/s, which only the last 10 seconds should reflect
+        assert_eq!(speed.update(at(30), 2100), 10.0);
+
+        // A retry rewinds progress, which starts the average over
+        assert_eq!(speed.update(at(31), 2050), 0.0);
+        assert_eq!(speed.update(at(33), 2250), 100.0);
+    }
+}
@@ -0,0 +1,33 @@
+//! Keeps uploads under the user's bandwidth limit. The limit covers all uploads together, so that
+//! running several at once doesn't multiply it.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct UploadThrottle {
+    /// When the bandwidth that's been handed out so far will have been used up
+    next_free: tokio::sync::Mutex<Instant>,
+}
+impl Default for UploadThrottle {
+    fn default() -> Self {
+        Self {
+            next_free: tokio::sync::Mutex::new(Instant::now()),
+        }
+    }
+}
+impl UploadThrottle {
+    /// Waits until `bytes` more can be sent without going over `bytes_per_second`.
+    /// Unused bandwidth isn't saved up, so there are no bursts after a pause.
+    pub(super) async fn wait(&self, bytes: usize, bytes_per_second: Option<u64>) {
+        let Some(bytes_per_second) = bytes_per_second.filter(|&b| b > 0) else {
+            return;
+        };
+        let wake_at = {
+            let mut next_free = self.next_free.lock().await;
+            let start = (*next_free).max(Instant::now());
+            *next_free = start + Duration::from_secs_f64(bytes as f64 / bytes_per_second as f64);
+            *next_free
+        };
+        tokio::time::sleep_until(wake_at.into()).await;
+    }
+}
@@ -0,0 +1,271 @@
+//! Moving recordings between machines, e.g. to record on one and upload from another. A recording is exported
+//! as a single tar archive of its folder's video, inputs and metadata. Importing one checks the metadata before
+//! adding the recording to the recording location, where it's uploaded like any other.
+//!
+//! Recordings encrypted at rest are decrypted as they're exported, as the other machine won't have the key.
+
+use std::path::{Component, Path, PathBuf};
+
+use color_eyre::eyre::{self, Context as _, ContextCompat as _};
+use constants::filename::recording::{IMPORTING, IN_PROGRESS, INPUTS, METADATA, VIDEO};
+
+use crate::{
+    encryption::{self, RecordingKeys},
+    output_types::{METADATA_SCHEMA_VERSION, Metadata},
+    upload::is_test_recording,
+};
+
+/// What a recording is made of. Everything else in its folder (markers, upload progress, leftover archives)
+/// only means something on the machine it was made on.
+const FILES: [&str; 3] = [VIDEO, INPUTS, METADATA];
+
+/// Writes the recording in `folder_path` to a new archive at `archive_path`
+pub fn export_recording(
+    folder_path: &Path,
+    archive_path: &Path,
+    keys: &RecordingKeys,
+) -> eyre::Result<()> {
+    if folder_path.join(IN_PROGRESS).is_file() {
+        eyre::bail!("the recording hasn't finished yet");
+    }
+    if is_test_recording(folder_path) {
+        eyre::bail!("test recordings can't be uploaded, so they can't be exported either");
+    }
+    let folder_name = folder_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("failed to get recording folder name")?;
+    // Checked now rather than on import, when it would be too late to do anything about it
+    read_manifest(folder_path)?;
+
+    let result = (|| {
+        let mut tar = tar::Builder::new(
+            std::fs::File::create(archive_path).context("failed to create archive")?,
+        );
+        for file in FILES {
+            let path = folder_path.join(file);
+            let reader = encryption::open(&path, keys)?;
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&std::fs::metadata(&path)?);
+            header.set_size(reader.size());
+            tar.append_data(&mut header, format!("{folder_name}/{file}"), reader)
+                .with_context(|| format!("failed to add {file} to the archive"))?;
+        }
+        tar.into_inner()?.sync_all()?;
+        eyre::Ok(())
+    })();
+    if result.is_err() {
+        std::fs::remove_file(archive_path).ok();
+    }
+    result
+}
+
+/// Adds the recording exported to `archive_path` to `recording_location`, returning its new folder
+pub fn import_recording(archive_path: &Path, recording_location: &Path) -> eyre::Result<PathBuf> {
+    // Unpacked away from the other recordings, so that nothing tries to upload it halfway through
+    let staging_path = recording_location.join(IMPORTING);
+    if staging_path.exists() {
+        // Left over from an import that was interrupted
+        std::fs::remove_dir_all(&staging_path).context("failed to clear out previous import")?;
+    }
+    std::fs::create_dir_all(&staging_path).context("failed to create import folder")?;
+
+    let result = (|| {
+        let folder_name = unpack(archive_path, &staging_path)?;
+        for file in FILES {
+            if !staging_path.join(file).is_file() {
+                eyre::bail!("the archive is missing {file}");
+            }
+        }
+        let metadata = read_manifest(&staging_path)?;
+
+        let folder_path = recording_location.join(&folder_name);
+        if folder_path.exists() {
+            let existing = read_manifest(&folder_path).ok();
+            if existing.is_some_and(|m| m.session_id == metadata.session_id) {
+                eyre::bail!("recording {folder_name} has already been imported");
+            }
+            eyre::bail!(
+                "a different recording named {folder_name} is already in the recording folder"
+            );
+        }
+        std::fs::rename(&staging_path, &folder_path)
+            .context("failed to move recording into place")?;
+        eyre::Ok(folder_path)
+    })();
+    std::fs::remove_dir_all(&staging_path).ok();
+    result
+}
+
+/// Unpacks the archive's files into `staging_path`, returning the name of the folder they were exported from
+fn unpack(archive_path: &Path, staging_path: &Path) -> eyre::Result<String> {
+    let mut archive =
+        tar::Archive::new(std::fs::File::open(archive_path).context("failed to open archive")?);
+    let mut folder_name: Option<String> = None;
+    for entry in archive.entries().context("failed to read archive")? {
+        let mut entry = entry.context("failed to read archive")?;
+        let path = entry.path()?.into_owned();
+        let (name, file) = entry_location(&path)
+            .filter(|_| entry.header().entry_type().is_file())
+            .with_context(|| {
+                format!(
+                    "the archive isn't an exported recording: it has {}",
+                    path.display()
+                )
+            })?;
+        match &folder_name {
+            Some(folder_name) if folder_name != name => {
+                eyre::bail!("the archive has more than one recording in it");
+            }
+            Some(_) => {}
+            None => folder_name = Some(name.to_string()),
+        }
+        let file_path = staging_path.join(file);
+        if file_path.exists() {
+            eyre::bail!("the archive has {file} in it more than once");
+        }
+        entry
+            .unpack(&file_path)
+            .with_context(|| format!("failed to unpack {file}"))?;
+    }
+    folder_name.context("the archive is empty")
+}
+
+/// Which recording folder an archive entry is from, and which of the recording's [`FILES`] it is. `None` if it
+/// isn't one of them, including anything that would end up outside the folder.
+fn entry_location(path: &Path) -> Option<(&str, &'static str)> {
+    let mut components = path.components();
+    let (Some(Component::Normal(folder_name)), Some(Component::Normal(file)), None) =
+        (components.next(), components.next(), components.next())
+    else {
+        return None;
+    };
+    let folder_name = folder_name.to_str()?;
+    // Dot folders are OWL Control's own, e.g. the instant replay buffer
+    if folder_name.starts_with('.') {
+        return None;
+    }
+    let file = FILES.into_iter().find(|f| file.to_str() == Some(*f))?;
+    Some((folder_name, file))
+}
+
+/// Reads the recording's metadata, making sure that this version of OWL Control understands it
+fn read_manifest(folder_path: &Path) -> eyre::Result<Metadata> {
+    let metadata =
+        std::fs::read_to_string(folder_path.join(METADATA)).context("failed to read metadata")?;
+    let metadata: serde_json::Value =
+        serde_json::from_str(&metadata).context("failed to parse metadata")?;
+    // Checked before parsing the rest, as a newer version is the likeliest reason for that to fail
+    check_schema_version(metadata.get("schema_version").and_then(|v| v.as_u64()))?;
+    serde_json::from_value(metadata).context("failed to parse metadata")
+}
+
+/// Recordings from before the metadata was versioned have no version, and are fine
+fn check_schema_version(schema_version: Option<u64>) -> eyre::Result<()> {
+    match schema_version {
+        Some(version) if version > METADATA_SCHEMA_VERSION as u64 => eyre::bail!(
+            "the recording was made by a newer version of OWL Control (metadata version {version}, \
+             this version understands up to {METADATA_SCHEMA_VERSION}); update OWL Control to import it"
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::TestDir;
+
+    fn write_recording(folder_path: &Path, schema_version: u32) {
+        std::fs::create_dir_all(folder_path).unwrap();
+        std::fs::write(folder_path.join(VIDEO), b"video").unwrap();
+        std::fs::write(
+            folder_path.join(INPUTS),
+            b"timestamp,event_type,event_args\n",
+        )
+        .unwrap();
+        std::fs::write(
+            folder_path.join(METADATA),
+            serde_json::json!({
+                "schema_version": schema_version,
+                "game_exe": "game.exe",
+                "session_id": "session-1",
+                "hardware_id": "hw-1",
+                "hardware_specs": null,
+                "start_timestamp": 0,
+                "end_timestamp": 60,
+                "duration": 60.0,
+            })
+            .to_string(),
+        )
+        .unwrap();
+        // Local to this machine, so not exported
+        std::fs::write(
+            folder_path.join(constants::filename::recording::UPLOADED),
+            b"",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let dir = TestDir::new("transfer");
+        let source = dir.join("source");
+        let destination = dir.join("destination");
+        std::fs::create_dir_all(&destination).unwrap();
+        write_recording(&source.join("1700000000"), METADATA_SCHEMA_VERSION);
+
+        let archive_path = dir.join("recording.tar");
+        export_recording(
+            &source.join("1700000000"),
+            &archive_path,
+            &RecordingKeys::default(),
+        )
+        .unwrap();
+        let folder_path = import_recording(&archive_path, &destination).unwrap();
+        assert_eq!(folder_path, destination.join("1700000000"));
+        for file in FILES {
+            assert_eq!(
+                std::fs::read(folder_path.join(file)).unwrap(),
+                std::fs::read(source.join("1700000000").join(file)).unwrap()
+            );
+        }
+        assert!(
+            !folder_path
+                .join(constants::filename::recording::UPLOADED)
+                .exists()
+        );
+        assert!(!destination.join(IMPORTING).exists());
+
+        // Importing it again is turned away, rather than replacing it
+        assert!(import_recording(&archive_path, &destination).is_err());
+    }
+
+    #[test]
+    fn test_schema_version() {
+        assert!(check_schema_version(None).is_ok());
+        assert!(check_schema_version(Some(METADATA_SCHEMA_VERSION as u64)).is_ok());
+        assert!(check_schema_version(Some(METADATA_SCHEMA_VERSION as u64 + 1)).is_err());
+
+        let dir = TestDir::new("transfer");
+        write_recording(&dir, METADATA_SCHEMA_VERSION + 1);
+        assert!(
+            export_recording(&dir, &dir.join("recording.tar"), &RecordingKeys::default()).is_err()
+        );
+        assert!(!dir.join("recording.tar").exists());
+    }
+
+    #[test]
+    fn test_entry_location() {
+        assert_eq!(
+            entry_location(Path::new("1700000000/recording.mp4")),
+            Some(("1700000000", VIDEO))
+        );
+        assert_eq!(entry_location(Path::new("1700000000/.uploaded")), None);
+        assert_eq!(entry_location(Path::new("recording.mp4")), None);
+        assert_eq!(entry_location(Path::new("../recording.mp4")), None);
+        assert_eq!(entry_location(Path::new("/1700000000/recording.mp4")), None);
+        assert_eq!(entry_location(Path::new("a/b/recording.mp4")), None);
+        assert_eq!(entry_location(Path::new(".replay/recording.mp4")), None);
+    }
+}
@@ -3,7 +3,10 @@ use std::{path::Path, str::FromStr as _};
 use color_eyre::eyre::{self, Context as _};
 use serde::{Deserialize, Serialize};
 
-use crate::output_types::{InputEvent, InputEventType, Metadata};
+use crate::{
+    encryption::{self, RecordingKeys},
+    output_types::{InputEvent, InputEventType, Metadata},
+};
 
 pub mod gamepad;
 pub mod keyboard;
@@ -24,14 +27,17 @@ struct ValidationInput<'a> {
     pub start_time: f64,
     pub filtered_events: &'a [InputEvent],
     pub duration_minutes: f64,
+    pub fps: u32,
 }
 
 pub fn for_recording(
     metadata: &Metadata,
     mp4_path: &Path,
     csv_path: &Path,
+    keys: &RecordingKeys,
 ) -> eyre::Result<(InputStats, Vec<String>)> {
-    let events = std::fs::read_to_string(csv_path)
+    let events = encryption::read(csv_path, keys)
+        .and_then(|bytes| Ok(String::from_utf8(bytes)?))
         .with_context(|| format!("Error reading CSV file at {csv_path:?})"))?
         .lines()
         .skip(1)
@@ -62,6 +68,7 @@ pub fn for_recording(
         start_time,
         filtered_events: &filtered_events,
         duration_minutes: end_time - start_time,
+        fps: metadata.fps.unwrap_or(constants::FPS),
     };
 
     let mut invalid_reasons = video::validate(mp4_path, metadata);
@@ -1,7 +1,6 @@
 use std::collections::HashMap;
 
 use crate::output_types::InputEventType;
-use constants::FPS;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -50,7 +49,7 @@ struct MouseStats {
 }
 
 fn get_stats(input: &super::ValidationInput) -> MouseStats {
-    let frame_duration = 1.0 / FPS as f64;
+    let frame_duration = 1.0 / input.fps as f64;
 
     // Extract mouse movement data
     let mouse_moves: Vec<_> = input
@@ -15,7 +15,7 @@ pub fn validate(video_path: &Path, metadata: &Metadata) -> Vec<String> {
         invalid_reasons.push(format!("Video length {} too long.", metadata.duration));
     }
 
-    let size_bytes = match std::fs::metadata(video_path).map(|m| m.len()) {
+    let size_bytes = match crate::encryption::plaintext_len(video_path) {
         Ok(size_bytes) => size_bytes,
         Err(e) => {
             invalid_reasons.push(format!("Video size unknown: {e}"));
@@ -1 +1,28 @@
 pub mod version;
+
+/// A folder of its own for a test to work in, deleted again once the test is done with it
+#[cfg(test)]
+pub(crate) struct TestDir(std::path::PathBuf);
+#[cfg(test)]
+impl TestDir {
+    /// `name` goes in the folder's name, to tell which test left it behind if it's ever not deleted
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("owl-{name}-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+#[cfg(test)]
+impl std::ops::Deref for TestDir {
+    type Target = std::path::Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}